@@ -50,5 +50,7 @@ pub fn default_tenant() -> TenantContext {
         tenant_id: "pagi-companion-ui".to_string(),
         correlation_id: None,
         agent_id: None,
+        session_id: None,
+        capabilities: None,
     }
 }
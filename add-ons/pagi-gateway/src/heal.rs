@@ -4,7 +4,7 @@
 //! generates a security-wrapping snippet and calls the refactor skill. Re-runs
 //! audit after fixes and logs the session to KB-08.
 
-use pagi_core::{KnowledgeStore, LiveSkillRegistry, TenantContext};
+use pagi_core::{run_live_skill, EnergyLedger, KnowledgeStore, LiveSkillRegistry, SkillManifestRegistry, TenantContext};
 use std::path::Path;
 
 const WORKSPACE_ROOT: &str = ".";
@@ -18,9 +18,13 @@ pub struct HealRefactorResult {
 }
 
 /// Run audit, then for each path in skills_without_kb05 try to apply a KB-05 security fix via refactor.
+/// Both skills run with capability handles from `manifests` and are checked and debited against
+/// `ledger` when one is given.
 /// Returns (audit_before, refactor_results, audit_after, final_score).
 pub async fn run_heal_flow(
     knowledge: &KnowledgeStore,
+    manifests: &SkillManifestRegistry,
+    ledger: Option<&EnergyLedger>,
 ) -> Result<
     (
         serde_json::Value,
@@ -42,7 +46,15 @@ pub async fn run_heal_flow(
         tenant_id: "heal".to_string(),
         correlation_id: None,
         agent_id: Some("phoenix".to_string()),
+        session_id: None,
+        capabilities: None,
     };
+    let audit_ctx = manifests
+        .scoped_context(audit_skill.name(), &tenant_ctx)
+        .map_err(|e| e.to_string())?;
+    let refactor_ctx = manifests
+        .scoped_context(refactor_skill.name(), &tenant_ctx)
+        .map_err(|e| e.to_string())?;
     let audit_params = serde_json::json!({ "workspace_root": WORKSPACE_ROOT });
 
     // Step A: Run audit
@@ -52,8 +64,7 @@ pub async fn run_heal_flow(
             .await
             .map_err(|e| format!("audit KB-05 blocked: {}", e))?;
     }
-    let audit_before = audit_skill
-        .execute(&tenant_ctx, knowledge, audit_params.clone())
+    let audit_before = run_live_skill(ledger, audit_skill.as_ref(), &audit_ctx, knowledge, audit_params.clone())
         .await
         .map_err(|e| format!("audit execute failed: {}", e))?;
 
@@ -123,10 +134,7 @@ pub async fn run_heal_flow(
             }
         }
 
-        match refactor_skill
-            .execute(&tenant_ctx, knowledge, refactor_params)
-            .await
-        {
+        match run_live_skill(ledger, refactor_skill.as_ref(), &refactor_ctx, knowledge, refactor_params).await {
             Ok(res) => {
                 let msg = res
                     .get("message")
@@ -151,13 +159,11 @@ pub async fn run_heal_flow(
     // Re-run audit to get updated score
     let audit_after = if audit_skill.requires_security_check() {
         let _ = audit_skill.validate_security(knowledge, &audit_params).await;
-        audit_skill
-            .execute(&tenant_ctx, knowledge, audit_params)
+        run_live_skill(ledger, audit_skill.as_ref(), &audit_ctx, knowledge, audit_params)
             .await
             .unwrap_or(audit_before.clone())
     } else {
-        audit_skill
-            .execute(&tenant_ctx, knowledge, audit_params)
+        run_live_skill(ledger, audit_skill.as_ref(), &audit_ctx, knowledge, audit_params)
             .await
             .unwrap_or(audit_before.clone())
     };
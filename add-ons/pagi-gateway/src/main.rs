@@ -43,8 +43,8 @@ use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::field::Visit;
 use tracing_subscriber::layer::Context;
 use pagi_core::{
-    initialize_core_identity, initialize_core_skills, initialize_ethos_policy, initialize_therapist_fit_checklist, self_audit, sync_env_files, AlignmentResult, BlueprintRegistry, CoreConfig, EventRecord, Goal, Gater, KbRecord, KbType,
-    HeuristicProcessor, KnowledgeStore, MentalState, MemoryManager, MoEMode, MoEExpert, Orchestrator, OrchestratorMode, RelationRecord, route_to_experts, ShadowStore, ShadowStoreHandle, SkillManifestEntry, SkillManifestRegistry, SkillRegistry, SovereigntyViolation, SovereignConfig, TierManifest, SovereignDomain, SovereignState, TenantContext, UserPersona, VitalityLevel,
+    initialize_core_identity, initialize_core_skills, initialize_ethos_policy, initialize_therapist_fit_checklist, self_audit, sync_env_files, AlignmentResult, BlueprintRegistry, CapabilityViolation, CoreConfig, EventRecord, ExecutionLimits, Goal, KbRecord, KbType,
+    HeuristicProcessor, KnowledgeStore, MentalState, MemoryManager, MoEMode, MoEExpert, Orchestrator, OrchestratorMode, RelationRecord, ShadowStore, ShadowStoreHandle, PromotionError, record_routing_correction, record_routing_decision, RoutingCorrection, RoutingDecision, SemanticRouter, SkillDispatchError, SkillManifestEntry, SkillManifestRegistry, BudgetExceeded, BudgetPolicy, EnergyDebit, EnergyLedger, run_live_skill, SkillSignature, SkillSigner, SkillRegistry, SovereigntyViolation, SovereignConfig, TierManifest, TrustTier, SovereignDomain, SovereignState, TenantContext, UserPersona, VitalityLevel,
     onboarding_sequence, ONBOARDING_COMPLETE_KEY, KB01_USER_PROFILE_KEY,
     process_archetype_triggers, active_archetype_label, get_sovereignty_leak_triggers,
     matched_sovereignty_triggers, rank_subject_from_sovereignty_triggers, ArchetypeTriggerResult,
@@ -88,7 +88,7 @@ use pagi_skills::{
     MissionValidatorSkill,
     SentinelInputVelocityConfig, SentinelInputVelocityMetrics, SentinelInputVelocitySensor,
     SentinelPhysicalGuardAction, SentinelPhysicalGuardSensor,
-    create_skill_from_spec, ForgeSkill, ToolSpec,
+    create_skill_from_spec, ForgeOverlay, ForgeSkill, ToolSpec,
 };
 use std::path::Path as StdPath;
 use std::pin::Pin;
@@ -196,6 +196,105 @@ fn run_verify() -> Result<(), String> {
     Ok(())
 }
 
+/// `--sign-skill <SKILL_ID|--all> [--artifact <PATH>]`: sign Tier 1 entries in core/manifest.json
+/// with the local user key (created on first use). With `--artifact`, records the Forge artifact
+/// path and its SHA-256 on the entry before signing; an entry that already names an artifact is re-hashed.
+///
+/// `--sign-skill <SKILL_ID> --detached`: print a signature over the skill's ephemeral (Tier 3)
+/// entry without touching any manifest, for `POST /api/v1/skills/promote`.
+fn run_sign_skill(args: &[String]) -> Result<(), String> {
+    let target = args.first().cloned().unwrap_or_default();
+    if target.is_empty() {
+        return Err(
+            "Usage: pagi-gateway --sign-skill <SKILL_ID|--all> [--artifact <PATH>] | <SKILL_ID> --detached".to_string(),
+        );
+    }
+    if args.iter().any(|a| a == "--detached") {
+        return run_sign_skill_detached(&target);
+    }
+    let artifact = args
+        .iter()
+        .position(|a| a == "--artifact")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+    if artifact.is_some() && target == "--all" {
+        return Err("--artifact can only be used when signing a single skill".to_string());
+    }
+
+    let key_path = SkillSigner::default_key_path();
+    let (signer, created) = SkillSigner::load_or_create(&key_path)
+        .map_err(|e| format!("Signing key {}: {}", key_path.display(), e))?;
+    if created {
+        println!("Created skill signing key at {} (keep it private).", key_path.display());
+    }
+
+    let skills_root = pagi_skills_root();
+    let core_path = skills_root.join("core").join("manifest.json");
+    let bytes = std::fs::read(&core_path).map_err(|e| format!("{}: {}", core_path.display(), e))?;
+    let mut manifest: TierManifest =
+        serde_json::from_slice(&bytes).map_err(|e| format!("{}: {}", core_path.display(), e))?;
+
+    let mut signed = Vec::new();
+    for entry in manifest.skills.iter_mut() {
+        if target != "--all" && entry.skill_id != target {
+            continue;
+        }
+        if let Some(ref path) = artifact {
+            entry.artifact = Some(path.clone());
+        }
+        if let Some(ref path) = entry.artifact {
+            let hash = pagi_core::sha256_file(&skills_root.join(path)).map_err(|e| format!("Artifact {}: {}", path, e))?;
+            entry.artifact_sha256 = Some(hash);
+        }
+        signer.sign_entry(entry);
+        signed.push(entry.skill_id.clone());
+    }
+    if signed.is_empty() {
+        return Err(format!("Skill {} not found in {}", target, core_path.display()));
+    }
+
+    let tmp = core_path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(&tmp, json).map_err(|e| format!("{}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, &core_path).map_err(|e| format!("{}: {}", core_path.display(), e))?;
+
+    println!("Signed {} core skill(s) with key {}:", signed.len(), signer.key_id());
+    for id in signed {
+        println!("  - {}", id);
+    }
+    Ok(())
+}
+
+/// Signs the ephemeral manifest entry for `skill_id` as it stands and prints the detached
+/// signature as JSON. Promotion verifies it against the gateway's in-memory entry, so the
+/// ephemeral manifest must not change between signing and promoting.
+fn run_sign_skill_detached(skill_id: &str) -> Result<(), String> {
+    if skill_id == "--all" {
+        return Err("--detached signs a single skill".to_string());
+    }
+    let key_path = SkillSigner::default_key_path();
+    let signer = SkillSigner::load(&key_path).map_err(|e| format!("Signing key {}: {}", key_path.display(), e))?;
+
+    let ephemeral_path = pagi_skills_root().join("ephemeral").join("manifest.json");
+    let bytes = std::fs::read(&ephemeral_path).map_err(|e| format!("{}: {}", ephemeral_path.display(), e))?;
+    let manifest: TierManifest =
+        serde_json::from_slice(&bytes).map_err(|e| format!("{}: {}", ephemeral_path.display(), e))?;
+    let entry = manifest
+        .skills
+        .iter()
+        .find(|e| e.skill_id == skill_id)
+        .ok_or_else(|| format!("Skill {} not found in {}", skill_id, ephemeral_path.display()))?;
+
+    let body = serde_json::json!({
+        "skill_id": skill_id,
+        "confirmed": true,
+        "signature": signer.sign(entry),
+    });
+    println!("{}", serde_json::to_string_pretty(&body).map_err(|e| e.to_string())?);
+    eprintln!("POST this body to /api/v1/skills/promote to promote {} to core.", skill_id);
+    Ok(())
+}
+
 /// Sovereignty Drill: Verifies Master Template layers in sequence.
 /// 1. Read a config file via FileSystemSkill (KB-05 validated).
 /// 2. Cross-reference with Ethos (KB-06).
@@ -222,6 +321,8 @@ fn run_sovereignty_drill() -> Result<(), String> {
             tenant_id: "drill".to_string(),
             correlation_id: None,
             agent_id: Some("phoenix".to_string()),
+            session_id: None,
+            capabilities: None,
         };
         let registry = Arc::new(LiveSkillRegistry::default());
 
@@ -253,8 +354,11 @@ fn run_sovereignty_drill() -> Result<(), String> {
         println!("OK");
 
         print!("  [3/5] FileSystemSkill execute (read config)... ");
+        let skill_ctx = load_skill_manifests()
+            .scoped_context(&skill_name, &tenant_ctx)
+            .map_err(|e| e.to_string())?;
         let read_result = skill
-            .execute(&tenant_ctx, &knowledge, params)
+            .execute(&skill_ctx, &knowledge, params)
             .await
             .map_err(|e| format!("Execute failed: {}", e))?;
         let content_len = read_result
@@ -323,6 +427,8 @@ fn run_audit() -> Result<(), String> {
             tenant_id: "audit".to_string(),
             correlation_id: None,
             agent_id: Some("phoenix".to_string()),
+            session_id: None,
+            capabilities: None,
         };
         let registry = Arc::new(LiveSkillRegistry::default());
 
@@ -334,8 +440,11 @@ fn run_audit() -> Result<(), String> {
                 .await
                 .map_err(|e| format!("KB-05 blocked: {}", e))?;
         }
+        let skill_ctx = load_skill_manifests()
+            .scoped_context(skill.name(), &tenant_ctx)
+            .map_err(|e| e.to_string())?;
         let result = skill
-            .execute(&tenant_ctx, &knowledge, audit_params)
+            .execute(&skill_ctx, &knowledge, audit_params)
             .await
             .map_err(|e| format!("Audit execute failed: {}", e))?;
 
@@ -397,7 +506,7 @@ fn run_heal() -> Result<(), String> {
         knowledge.pagi_init_kb_metadata().ok();
 
         let (audit_before, refactor_results, _audit_after, final_score) =
-            run_heal_flow(knowledge.as_ref()).await?;
+            run_heal_flow(knowledge.as_ref(), &load_skill_manifests(), None).await?;
 
         let applied = refactor_results.iter().filter(|r| r.applied).count();
         let session_msg = format!(
@@ -461,6 +570,18 @@ async fn main() {
         }
     }
 
+    // Handle --sign-skill <SKILL_ID|--all> [--artifact PATH] (sign Tier 1 manifest entries and exit),
+    // or --sign-skill <SKILL_ID> --detached (print a promotion signature and exit)
+    if let Some(pos) = args.iter().position(|a| a == "--sign-skill") {
+        match run_sign_skill(&args[pos + 1..]) {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("Sign skill failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     // Handle --verify and --sovereignty-drill flags (pre-flight / Master Template verification)
     let headless = args.iter().any(|a| {
         a == "--verify" || a == "--sovereignty-drill" || a == "--audit" || a == "--heal"
@@ -647,8 +768,16 @@ async fn main() {
         Arc::new(tokio::sync::RwLock::new(None))
     };
 
+    let federation = start_federation_master(Arc::clone(&knowledge));
+
+    // Every skill the gateway runs (orchestrator or live) must be listed in a tier manifest.
+    let skill_manifest_registry = load_skill_manifests();
+
     // Sovereign Brain: chat + file system / OS access (workspace analysis, read file, sandbox write)
     let mut registry = SkillRegistry::new();
+    if let Some(ref handle) = federation {
+        register_federation_skills(&mut registry, &skill_manifest_registry, handle, &log_tx);
+    }
     let model_router = Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge)));
     registry.register(Arc::new(ModelRouter::with_knowledge(Arc::clone(&knowledge))));
     registry.register(Arc::new(BioGateSync::new(Arc::clone(&knowledge))));
@@ -694,7 +823,7 @@ async fn main() {
     // Deep Audit: Sovereign document ingestion and KB routing
     registry.register(Arc::new(pagi_skills::DeepAuditSkill::new(storage.to_path_buf())));
 
-    // The Forge: Self-synthesis skill (create new .rs from JSON tool-spec, append to lib.rs, cargo check)
+    // The Forge: Self-synthesis skill (generate into the data/forge_overlay crate, cargo check there)
     registry.register(Arc::new(ForgeSkill::new()));
 
     // SAO–Copilot Bridge: user-level UI automation; redacted transcript → Copilot sidebar
@@ -704,6 +833,8 @@ async fn main() {
         storage.to_path_buf(),
     )));
 
+    let sovereign_config = Arc::new(SovereignConfig::from_env());
+
     // Sovereign Operator: The Forge (unified integration for self-evolution)
     // Load forge safety setting from environment
     let forge_safety_enabled = std::env::var("PAGI_FORGE_SAFETY_ENABLED")
@@ -719,11 +850,23 @@ async fn main() {
         workspace_path: ".".to_string(),
     };
     
+    let mut forge_loader = None;
     match SovereignOperator::with_config(sovereign_operator_config) {
         Ok(mut operator) => {
             // Set knowledge store for KB-08 logging
             operator.set_knowledge_store(Arc::clone(&knowledge));
+            // WASM skills get their KB layers and egress from their manifest entries
+            operator.set_skill_manifests(Arc::clone(&skill_manifest_registry), sovereign_config.firewall_strict_mode);
             
+            // Forge overlay: register checked generated skills from data/forge_overlay/forge_manifest.json
+            let overlay = ForgeOverlay::from_env(&std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")));
+            match overlay.register_skills(&registry, &skill_manifest_registry, Arc::clone(operator.skill_loader())) {
+                Ok(0) => {}
+                Ok(n) => tracing::info!(target: "pagi::forge", count = n, "Forge overlay skills registered"),
+                Err(e) => tracing::warn!(target: "pagi::forge", "Forge overlay not loaded: {}", e),
+            }
+            forge_loader = Some(Arc::clone(operator.skill_loader()));
+
             let sovereign_operator = Arc::new(operator);
             registry.register(Arc::new(SovereignOperatorSkill::new(Arc::clone(&sovereign_operator))));
             
@@ -793,24 +936,19 @@ async fn main() {
         .unwrap_or_else(|_| "config/blueprint.json".to_string());
     let blueprint = Arc::new(BlueprintRegistry::load_json_path(&blueprint_path));
 
-    let skill_manifest_registry = match SkillManifestRegistry::load_from_dir(&pagi_skills_root()) {
-        Ok(r) => {
-            let n = r.list_inventory().len();
-            tracing::info!(target: "pagi::skills", count = n, "3-tier skills manifest loaded");
-            Arc::new(r)
-        }
-        Err(e) => {
-            tracing::warn!(target: "pagi::skills", "Skills manifest load failed: {}; using empty registry", e);
-            Arc::new(SkillManifestRegistry::new())
-        }
-    };
-    let sovereign_config = Arc::new(SovereignConfig::from_env());
-    let orchestrator = Arc::new(Orchestrator::with_blueprint_and_permissions(
-        Arc::new(registry),
-        Arc::clone(&blueprint),
-        Arc::clone(&skill_manifest_registry),
-        sovereign_config.firewall_strict_mode,
-    ));
+    let orchestrator = Arc::new(
+        Orchestrator::with_blueprint_and_permissions(
+            Arc::new(registry),
+            Arc::clone(&blueprint),
+            Arc::clone(&skill_manifest_registry),
+            sovereign_config.firewall_strict_mode,
+        )
+        .with_execution_limits(ExecutionLimits::from_env())
+        .with_moe_router(Arc::new(SemanticRouter::from_env()))
+        .with_energy_ledger(Arc::new(
+            EnergyLedger::new(BudgetPolicy::from_env()).with_store(Arc::clone(&knowledge)),
+        )),
+    );
 
     // Heartbeat (Autonomous Orchestrator): in-process background task so we can share
     // the same Sled-backed KnowledgeStore without cross-process lock contention.
@@ -862,12 +1000,21 @@ async fn main() {
     let moe_is_sparse = moe_mode_final.eq_ignore_ascii_case("sparse");
     orchestrator.set_moe_mode(MoEMode::from_str(moe_mode_final));
     let moe_active = Arc::new(AtomicBool::new(moe_is_sparse));
+    let moe_examples = orchestrator.moe_router().fit_from_kb(&knowledge);
+    tracing::info!(
+        target: "pagi::chat",
+        examples = moe_examples,
+        trained = ?orchestrator.moe_router().trained_experts(),
+        "MoE router fitted from KB-05"
+    );
 
     // Autonomous Maintenance & Reflexion Loop: idle tracker + approval bridge + background spawn.
     let idle_tracker = IdleTracker::new();
     let approval_bridge = new_approval_bridge();
     let mut maint_config = MaintenanceConfig::default();
     maint_config.approval_bridge = Some(Arc::clone(&approval_bridge));
+    // Forge-declared params drive property-based smoke tests for patches to overlay skills.
+    maint_config.skill_params = forge_overlay().manifest().skill_params();
     let _maintenance_handle = init_maintenance_loop(
         Arc::clone(&knowledge),
         idle_tracker.clone(),
@@ -963,6 +1110,10 @@ async fn main() {
     let knowledge_shutdown = Arc::clone(&knowledge);
     #[cfg(feature = "vector")]
     let vector_store_shutdown = Arc::clone(&vector_store);
+    #[cfg(feature = "voice")]
+    let skill_manifests_for_live = Arc::clone(&skill_manifest_registry);
+    #[cfg(feature = "voice")]
+    let energy_for_live = orchestrator.energy_ledger().cloned();
 
     let project_associations = Arc::new(tokio::sync::RwLock::new(load_project_associations()));
     let folder_summary_cache = Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new()));
@@ -991,6 +1142,8 @@ async fn main() {
         folder_summary_cache,
         chronos_db,
         mimir_session: Arc::new(tokio::sync::Mutex::new(None)),
+        federation,
+        forge_loader,
     });
 
     // Voice mode: start Sovereign Voice loop (Ear → STT → chat API → TTS) when --voice is passed.
@@ -1012,6 +1165,8 @@ async fn main() {
             log_tx.clone(),
             knowledge_for_live,
             Arc::new(live_registry),
+            skill_manifests_for_live,
+            energy_for_live,
             Arc::clone(&_memory),
         );
         tracing::info!(target: "pagi::voice", "🌐 OpenRouter Live Mode active. Phoenix is listening with streaming responses.");
@@ -1780,6 +1935,30 @@ fn pagi_skills_root() -> std::path::PathBuf {
         .join("pagi-skills")
 }
 
+/// Loads the 3-tier skill manifests from [`pagi_skills_root`]. On failure the registry is empty,
+/// so no skill can run until the manifests are fixed.
+fn load_skill_manifests() -> Arc<SkillManifestRegistry> {
+    match SkillManifestRegistry::load_from_dir(&pagi_skills_root()) {
+        Ok(r) => {
+            let inventory = r.list_inventory();
+            let downgraded = inventory.iter().filter(|e| e.signature_error.is_some()).count();
+            tracing::info!(target: "pagi::skills", count = inventory.len(), "3-tier skills manifest loaded");
+            if downgraded > 0 {
+                tracing::warn!(
+                    target: "pagi::skills",
+                    downgraded,
+                    "Unsigned or invalid core skills downgraded to import; run `pagi-gateway --sign-skill --all` to sign them"
+                );
+            }
+            Arc::new(r)
+        }
+        Err(e) => {
+            tracing::warn!(target: "pagi::skills", "Skills manifest load failed: {}; using empty registry", e);
+            Arc::new(SkillManifestRegistry::new())
+        }
+    }
+}
+
 fn build_app(state: AppState) -> Router {
     let frontend_enabled = state.config.frontend_enabled;
 
@@ -1819,6 +1998,12 @@ fn build_app(state: AppState) -> Router {
         .route("/api/v1/kb-status", get(kb_status))
         .route("/api/v1/sovereign-status", get(sovereign_status))
         .route("/api/v1/settings/moe", get(get_moe_settings).post(set_moe_settings))
+        .route("/api/v1/moe/router", get(moe_router_status))
+        .route("/api/v1/budget", get(budget_report))
+        .route("/api/v1/moe/examples", get(moe_examples_list).post(moe_examples_add))
+        .route("/api/v1/moe/feedback", post(moe_feedback))
+        .route("/api/v1/moe/decisions", get(moe_decisions_list))
+        .route("/api/v1/moe/training-set", get(moe_training_set))
         .route("/api/v1/settings/orchestrator-role", get(get_orchestrator_role_settings).post(set_orchestrator_role_settings))
         .route("/api/v1/settings/persona", get(get_orchestrator_role_settings).post(set_orchestrator_role_settings))
         .route("/api/v1/settings/density", get(get_density_settings).post(set_density_settings))
@@ -1845,6 +2030,11 @@ fn build_app(state: AppState) -> Router {
         // Intelligence Layer endpoints (SAO background service)
         .route("/api/v1/intelligence/insights", get(intelligence_insights_get))
         .route("/api/v1/intelligence/toggle", post(intelligence_toggle_post))
+        // Federation: progress of tasks running on Satellites
+        .route("/api/v1/federation/progress", get(federation_progress_stream))
+        .route("/api/v1/federation/nodes", get(federation_nodes))
+        .route("/api/v1/federation/tasks", get(federation_tasks))
+        .route("/api/v1/federation/events", get(federation_node_events))
         // Maintenance Dashboard endpoints
         .route("/api/v1/maintenance/pulse", get(maintenance_pulse_stream))
         .route("/api/v1/maintenance/status", get(maintenance_status))
@@ -1854,10 +2044,13 @@ fn build_app(state: AppState) -> Router {
         // Evolutionary Versioning & Rollback endpoints
         .route("/api/v1/maintenance/patch-history", get(patch_version_history))
         .route("/api/v1/maintenance/rollback", post(rollback_skill_endpoint))
+        .route("/api/v1/maintenance/lineage/:skill", get(maintenance_lineage))
         // Forge Safety Governor endpoints (Kill Switch + Auto-Revert UI)
         .route("/api/v1/forge/safety-status", get(forge_safety_status_get))
         .route("/api/v1/forge/safety", post(forge_safety_set))
         .route("/api/v1/forge/create", post(forge_create_post))
+        .route("/api/v1/forge/overlay", get(forge_overlay_get))
+        .route("/api/v1/forge/promote", post(forge_promote_post))
         // Forge Hot-Reload endpoints (Dynamic skill loading)
         .route("/api/v1/forge/hot-reload/status", get(forge_hot_reload_status))
         .route("/api/v1/forge/hot-reload/enable", post(forge_hot_reload_enable))
@@ -1971,6 +2164,8 @@ async fn mission_validate_post(
         tenant_id,
         correlation_id: Some(uuid::Uuid::new_v4().to_string()),
         agent_id: Some(agent_id),
+        session_id: None,
+        capabilities: None,
     };
 
     let payload = serde_json::json!({
@@ -2013,7 +2208,7 @@ pub(crate) struct AppState {
     pub(crate) log_tx: broadcast::Sender<String>,
     pub(crate) model_router: Arc<ModelRouter>,
     pub(crate) shadow_store: ShadowStoreHandle,
-    /// When true, chat requests are routed via MoE gating (route_to_experts) to OpenRouter / LanceDB / SystemTool.
+    /// When true, chat requests are routed via MoE gating (Orchestrator::route_moe) to OpenRouter / LanceDB / SystemTool.
     pub(crate) moe_active: Arc<AtomicBool>,
     /// Tracks gateway idle time for the Autonomous Maintenance Loop.
     pub(crate) idle_tracker: IdleTracker,
@@ -2047,6 +2242,12 @@ pub(crate) struct AppState {
 
     /// Mimir meeting capture: active session when recording (start/stop/status).
     pub(crate) mimir_session: Arc<tokio::sync::Mutex<Option<mimir::MimirSession>>>,
+
+    /// Federation master (The Creator) when PAGI_FEDERATION_LISTEN is set; backs /api/v1/federation/{nodes,tasks,events}.
+    pub(crate) federation: Option<Arc<pagi_federation::FederationHandle>>,
+    /// Loader serving the Forge overlay library; None when the Sovereign Operator failed to start.
+    /// Overlay skills built by POST /api/v1/forge/create are registered through it without a restart.
+    pub(crate) forge_loader: Option<Arc<pagi_evolution::SkillLoader>>,
 }
 
 /// GET /api/v1/health – liveness check. Returns PHOENIX MARIE identity (SAO Orchestrator Core).
@@ -2081,10 +2282,15 @@ async fn skills_list(State(state): State<AppState>) -> axum::Json<serde_json::Va
 struct SkillsPromoteBody {
     skill_id: String,
     confirmed: bool,
+    /// Detached signature from `pagi-gateway --sign-skill <SKILL_ID> --detached`.
+    #[serde(default)]
+    signature: Option<SkillSignature>,
 }
 
-/// POST /api/v1/skills/promote – move a skill from ephemeral (generated) to core. Requires manual confirmation.
-/// Updates in-memory registry and persists to disk (ephemeral/manifest.json and core/manifest.json).
+/// POST /api/v1/skills/promote – move a skill from ephemeral (generated) to core. Requires manual confirmation
+/// and a signature made offline with the user's key; the gateway only verifies it, so a compromised
+/// gateway process cannot mint Tier 1 skills. Updates in-memory registry and persists to disk
+/// (ephemeral/manifest.json and core/manifest.json).
 async fn skills_promote(
     State(state): State<AppState>,
     Json(body): Json<SkillsPromoteBody>,
@@ -2099,13 +2305,26 @@ async fn skills_promote(
         );
     }
     let skill_id = body.skill_id.trim().to_string();
-    let ok = state.skill_manifest_registry.promote_to_core(&skill_id);
-    if !ok {
+    // Tier 1 is "Signed by the User": the signature is made offline; the registry verifies it.
+    let Some(signature) = body.signature else {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Promotion requires a signature. Run `pagi-gateway --sign-skill {} --detached` and send its output as the request body.", skill_id)
+            })),
+        );
+    };
+    if let Err(e) = state.skill_manifest_registry.promote_to_core(&skill_id, signature) {
+        let message = match e {
+            PromotionError::NotGenerated(_) => "Skill not found in ephemeral tier or already core/import.".to_string(),
+            PromotionError::Signature { .. } => e.to_string(),
+        };
         return (
             axum::http::StatusCode::BAD_REQUEST,
             axum::Json(serde_json::json!({
                 "status": "error",
-                "message": "Skill not found in ephemeral tier or already core/import."
+                "message": message
             })),
         );
     }
@@ -2126,15 +2345,7 @@ fn persist_promotion_to_disk(
     skill_id: &str,
     registry: &SkillManifestRegistry,
 ) -> String {
-    let entry = registry
-        .list_inventory()
-        .into_iter()
-        .find(|e| e.skill_id == skill_id)
-        .map(|e| SkillManifestEntry {
-            skill_id: e.skill_id,
-            kb_layers_allowed: e.kb_layers_allowed,
-            description: e.description,
-        });
+    let entry = registry.manifest_entry(skill_id);
     let Some(entry) = entry else {
         return "Skill promoted in memory; disk persist skipped (entry not found).".to_string();
     };
@@ -2392,6 +2603,8 @@ async fn system_vitality_get(
         tenant_id: "default".to_string(),
         correlation_id: Some(uuid::Uuid::new_v4().to_string()),
         agent_id: Some(pagi_core::DEFAULT_AGENT_ID.to_string()),
+        session_id: None,
+        capabilities: None,
     };
     let goal = Goal::ExecuteSkill {
         name: "GetHardwareStats".to_string(),
@@ -2466,6 +2679,8 @@ async fn mimir_synthesize_post(
         tenant_id: "default".to_string(),
         correlation_id: Some(uuid::Uuid::new_v4().to_string()),
         agent_id: Some(pagi_core::DEFAULT_AGENT_ID.to_string()),
+        session_id: None,
+        capabilities: None,
     };
     let payload = serde_json::json!({
         "summary_path": summary_path,
@@ -3120,6 +3335,136 @@ async fn set_moe_settings(
     }))
 }
 
+#[derive(serde::Deserialize)]
+struct BudgetQuery {
+    #[serde(default)]
+    tenant: Option<String>,
+    #[serde(default)]
+    session: Option<String>,
+}
+
+/// GET /api/v1/budget?tenant=&session= – today's energy usage against the tenant / session budgets.
+async fn budget_report(
+    State(state): State<AppState>,
+    Query(q): Query<BudgetQuery>,
+) -> axum::Json<serde_json::Value> {
+    let Some(ledger) = state.orchestrator.energy_ledger() else {
+        return axum::Json(serde_json::json!({ "enabled": false }));
+    };
+    let tenant = q.tenant.as_deref().filter(|s| !s.is_empty()).unwrap_or("studio-user");
+    let session = q.session.as_deref().filter(|s| !s.is_empty());
+    let report = ledger.report(tenant, session);
+    axum::Json(serde_json::json!({ "enabled": true, "report": report }))
+}
+
+/// GET /api/v1/moe/router – semantic router status (threshold, trained experts, KB-05 example count).
+async fn moe_router_status(State(state): State<AppState>) -> axum::Json<serde_json::Value> {
+    let router = state.orchestrator.moe_router();
+    let trained: Vec<&str> = router.trained_experts().iter().map(|e| e.as_str()).collect();
+    axum::Json(serde_json::json!({
+        "threshold": router.threshold(),
+        "trained_experts": trained,
+        "examples": pagi_core::load_routing_examples(&state.knowledge).len(),
+    }))
+}
+
+/// GET /api/v1/moe/examples – labeled routing examples stored in KB-05.
+async fn moe_examples_list(State(state): State<AppState>) -> axum::Json<serde_json::Value> {
+    let examples = pagi_core::load_routing_examples(&state.knowledge);
+    axum::Json(serde_json::json!({ "count": examples.len(), "examples": examples }))
+}
+
+#[derive(serde::Deserialize)]
+struct MoEExampleRequest {
+    text: String,
+    /// "openrouter" | "lancedb" | "system_tool"
+    expert: String,
+}
+
+/// POST /api/v1/moe/examples – add a labeled example to KB-05 and re-fit the router.
+async fn moe_examples_add(
+    State(state): State<AppState>,
+    Json(body): Json<MoEExampleRequest>,
+) -> (axum::http::StatusCode, axum::Json<serde_json::Value>) {
+    let Some(expert) = MoEExpert::from_label(&body.expert).filter(|e| *e != MoEExpert::Unmatched) else {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(serde_json::json!({ "status": "error", "message": "expert must be openrouter, lancedb or system_tool" })),
+        );
+    };
+    if body.text.trim().is_empty() {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(serde_json::json!({ "status": "error", "message": "text is required" })),
+        );
+    }
+    let example = pagi_core::RoutingExample {
+        text: body.text.trim().to_string(),
+        expert: expert.as_str().to_string(),
+        created_at_ms: chrono::Utc::now().timestamp_millis(),
+        source: Some("api".to_string()),
+    };
+    if let Err(e) = pagi_core::store_routing_example(&state.knowledge, &example) {
+        return (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+        );
+    }
+    let examples = state.orchestrator.moe_router().fit_from_kb(&state.knowledge);
+    (
+        axum::http::StatusCode::OK,
+        axum::Json(serde_json::json!({ "status": "ok", "examples": examples })),
+    )
+}
+
+#[derive(serde::Deserialize)]
+struct MoEFeedbackRequest {
+    decision_id: String,
+    /// Expert the prompt should have gone to.
+    expert: String,
+}
+
+/// POST /api/v1/moe/feedback – explicit correction of a routing decision (logged to KB-08).
+async fn moe_feedback(
+    State(state): State<AppState>,
+    Json(body): Json<MoEFeedbackRequest>,
+) -> (axum::http::StatusCode, axum::Json<serde_json::Value>) {
+    let Some(expert) = MoEExpert::from_label(&body.expert) else {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            axum::Json(serde_json::json!({ "status": "error", "message": "unknown expert" })),
+        );
+    };
+    let correction = RoutingCorrection::Explicit { expert: expert.as_str().to_string() };
+    match record_routing_correction(&state.knowledge, body.decision_id.trim(), correction) {
+        Ok(true) => (axum::http::StatusCode::OK, axum::Json(serde_json::json!({ "status": "ok" }))),
+        Ok(false) => (
+            axum::http::StatusCode::NOT_FOUND,
+            axum::Json(serde_json::json!({ "status": "error", "message": "routing decision not found" })),
+        ),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(serde_json::json!({ "status": "error", "message": e.to_string() })),
+        ),
+    }
+}
+
+/// GET /api/v1/moe/decisions?limit=N – most recent routing decisions from KB-08 (default 100).
+async fn moe_decisions_list(
+    State(state): State<AppState>,
+    axum::extract::Query(params): axum::extract::Query<std::collections::HashMap<String, String>>,
+) -> axum::Json<serde_json::Value> {
+    let limit = params.get("limit").and_then(|s| s.parse::<usize>().ok()).unwrap_or(100);
+    let decisions = pagi_core::recent_routing_decisions(&state.knowledge, limit);
+    axum::Json(serde_json::json!({ "count": decisions.len(), "decisions": decisions }))
+}
+
+/// GET /api/v1/moe/training-set – KB-05 examples plus explicitly corrected decisions, for offline re-fit.
+async fn moe_training_set(State(state): State<AppState>) -> axum::Json<serde_json::Value> {
+    let examples = pagi_core::export_training_set(&state.knowledge);
+    axum::Json(serde_json::json!({ "count": examples.len(), "examples": examples }))
+}
+
 /// GET /api/v1/settings/orchestrator-role – current system role (counselor; companion legacy).
 async fn get_orchestrator_role_settings(State(state): State<AppState>) -> axum::Json<serde_json::Value> {
     let role = state.persona_coordinator.get_mode().as_str();
@@ -3360,6 +3705,8 @@ async fn sovereignty_audit_post(State(state): State<AppState>) -> axum::Json<ser
         tenant_id: "sovereignty-audit".to_string(),
         correlation_id: None,
         agent_id: Some("phoenix".to_string()),
+        session_id: None,
+        capabilities: None,
     };
     let audit_params = serde_json::json!({ "workspace_root": "." });
     if skill.requires_security_check() {
@@ -3370,7 +3717,17 @@ async fn sovereignty_audit_post(State(state): State<AppState>) -> axum::Json<ser
             }));
         }
     }
-    match skill.execute(&tenant_ctx, &state.knowledge, audit_params).await {
+    let tenant_ctx = match state.skill_manifest_registry.scoped_context(skill.name(), &tenant_ctx) {
+        Ok(ctx) => ctx,
+        Err(e) => {
+            return axum::Json(serde_json::json!({
+                "status": "error",
+                "error": e.to_string(),
+            }));
+        }
+    };
+    let ledger = state.orchestrator.energy_ledger().map(Arc::as_ref);
+    match run_live_skill(ledger, skill.as_ref(), &tenant_ctx, &state.knowledge, audit_params).await {
         Ok(result) => {
             let score = result.get("sovereignty_score").and_then(|v| v.as_f64()).unwrap_or(0.0);
             state.sovereignty_score_bits.store(f64::to_bits(score), std::sync::atomic::Ordering::Relaxed);
@@ -3390,7 +3747,8 @@ async fn sovereignty_audit_post(State(state): State<AppState>) -> axum::Json<ser
 
 /// POST /api/v1/heal – Run audit, then for each skills_without_kb05 apply refactor (security wrap). Re-audit and log session to KB-08.
 async fn heal_post(State(state): State<AppState>) -> axum::Json<serde_json::Value> {
-    match run_heal_flow(&state.knowledge).await {
+    let ledger = state.orchestrator.energy_ledger().map(Arc::as_ref);
+    match run_heal_flow(&state.knowledge, &state.skill_manifest_registry, ledger).await {
         Ok((audit_before, refactor_results, audit_after, final_score)) => {
             state.sovereignty_score_bits.store(
                 f64::to_bits(final_score),
@@ -3825,13 +4183,22 @@ async fn forge_safety_status_get(State(state): State<AppState>) -> axum::Json<se
         tenant_id: "system".to_string(),
         correlation_id: None,
         agent_id: Some("phoenix".to_string()),
+        session_id: None,
+        capabilities: None,
     };
     
     let payload = serde_json::json!({ "action": "get_forge_safety_status" });
     
     match registry.get("sovereign_operator") {
         Some(skill) => {
-            match skill.execute(&tenant_ctx, &state.knowledge, payload).await {
+            let result = match state.skill_manifest_registry.scoped_context(skill.name(), &tenant_ctx) {
+                Ok(ctx) => {
+                    let ledger = state.orchestrator.energy_ledger().map(Arc::as_ref);
+                    run_live_skill(ledger, skill.as_ref(), &ctx, &state.knowledge, payload).await
+                }
+                Err(e) => Err(e.into()),
+            };
+            match result {
                 Ok(result) => axum::Json(result),
                 Err(e) => {
                     tracing::warn!("Failed to get forge safety status: {}", e);
@@ -3869,6 +4236,8 @@ async fn forge_safety_set(
         tenant_id: "system".to_string(),
         correlation_id: None,
         agent_id: Some("phoenix".to_string()),
+        session_id: None,
+        capabilities: None,
     };
     
     let payload = serde_json::json!({
@@ -3878,7 +4247,14 @@ async fn forge_safety_set(
     
     match registry.get("sovereign_operator") {
         Some(skill) => {
-            match skill.execute(&tenant_ctx, &state.knowledge, payload).await {
+            let result = match state.skill_manifest_registry.scoped_context(skill.name(), &tenant_ctx) {
+                Ok(ctx) => {
+                    let ledger = state.orchestrator.energy_ledger().map(Arc::as_ref);
+                    run_live_skill(ledger, skill.as_ref(), &ctx, &state.knowledge, payload).await
+                }
+                Err(e) => Err(e.into()),
+            };
+            match result {
                 Ok(_) => {
                     let mode = if body.enabled { "HITL" } else { "Autonomous" };
                     tracing::info!("🏛️ Forge Safety Governor: {} (safety: {})", mode, if body.enabled { "ENABLED" } else { "DISABLED" });
@@ -3928,8 +4304,50 @@ async fn forge_safety_set(
     }
 }
 
+#[derive(serde::Deserialize)]
+struct ForgePromoteRequest {
+    name: String,
+}
+
+fn forge_overlay() -> ForgeOverlay {
+    ForgeOverlay::from_env(&std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")))
+}
+
+/// GET /api/v1/forge/overlay – Generated skills in the Forge overlay manifest.
+async fn forge_overlay_get() -> axum::Json<serde_json::Value> {
+    let overlay = forge_overlay();
+    axum::Json(serde_json::json!({
+        "root": overlay.root().display().to_string(),
+        "built": overlay.artifact_path().exists(),
+        "skills": overlay.manifest().skills,
+    }))
+}
+
+/// POST /api/v1/forge/promote – Write a reviewable patch moving an overlay skill into pagi-skills.
+async fn forge_promote_post(
+    Json(body): Json<ForgePromoteRequest>,
+) -> (StatusCode, axum::Json<serde_json::Value>) {
+    match forge_overlay().promote(&body.name) {
+        Ok(result) => (
+            StatusCode::OK,
+            axum::Json(serde_json::to_value(&result).unwrap_or_else(|_| serde_json::json!({
+                "status": "error",
+                "message": "Serialization failed"
+            }))),
+        ),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            axum::Json(serde_json::json!({
+                "status": "error",
+                "message": e
+            })),
+        ),
+    }
+}
+
 /// POST /api/v1/forge/create – Create a new skill from a JSON tool-spec (The Forge).
 async fn forge_create_post(
+    State(state): State<AppState>,
     Json(body): Json<ToolSpec>,
 ) -> (StatusCode, axum::Json<serde_json::Value>) {
     let workspace_root = match std::env::current_dir() {
@@ -3945,29 +4363,89 @@ async fn forge_create_post(
             );
         }
     };
-    let skills_src = workspace_root.join("crates/pagi-skills/src");
-    if !skills_src.join("lib.rs").exists() {
+    if !workspace_root.join("crates/pagi-core/Cargo.toml").exists() {
         return (
             StatusCode::BAD_REQUEST,
             axum::Json(serde_json::json!({
                 "status": "error",
-                "message": "Not running from workspace root (crates/pagi-skills/src/lib.rs not found)"
+                "message": "Not running from workspace root (crates/pagi-core/Cargo.toml not found)"
             })),
         );
     }
-    match create_skill_from_spec(&body, &skills_src, &workspace_root) {
+    let overlay = ForgeOverlay::from_env(&workspace_root);
+    let created = {
+        let overlay = overlay.clone();
+        let spec = body.clone();
+        tokio::task::spawn_blocking(move || create_skill_from_spec(&spec, &overlay))
+            .await
+            .unwrap_or_else(|e| Err(format!("Forge task failed: {}", e)))
+    };
+    match created {
         Ok(result) => {
             let code = if result.cargo_check_ok {
-                // If cargo check passed and hot-reload is enabled, trigger hot-reload
+                // If cargo check passed and hot-reload is enabled, build the overlay library
                 if is_hot_reload_enabled() {
-                    let file_path = std::path::PathBuf::from(&result.file_path);
-                    match hot_reload_skill(&body.name, &result.module_name, file_path) {
-                        Ok(hot_reload_result) => {
+                    let started = std::time::Instant::now();
+                    let build_overlay = overlay.clone();
+                    match tokio::task::spawn_blocking(move || build_overlay.build())
+                        .await
+                        .unwrap_or_else(|e| Err(format!("Forge task failed: {}", e)))
+                    {
+                        Ok(_artifact) => {
+                            let compilation_time_ms = started.elapsed().as_millis() as u64;
                             tracing::info!(
-                                "🔥 Forge: Skill '{}' created and hot-reloaded in {}ms",
+                                "🔥 Forge: Skill '{}' created and overlay rebuilt in {}ms",
                                 body.name,
-                                hot_reload_result.load_time_ms
+                                compilation_time_ms
                             );
+                            // Property-based smoke tests from the declared params
+                            let name = body.name.clone();
+                            let property_test = tokio::task::spawn_blocking(move || {
+                                overlay.property_test(&name, pagi_core::PropertyConfig::from_env())
+                            })
+                            .await
+                            .unwrap_or_else(|e| Err(format!("Forge task failed: {}", e)));
+                            let (property_passed, property_json) = match &property_test {
+                                Ok(report) => {
+                                    if let Some(failure) = &report.failure {
+                                        tracing::warn!(
+                                            target: "pagi::forge",
+                                            skill = %body.name,
+                                            "Forge skill failed property tests: {}",
+                                            report.summary()
+                                        );
+                                        let source = std::fs::read_to_string(&result.file_path).unwrap_or_default();
+                                        pagi_core::record_genetic_dead_end_with_payload(
+                                            &state.knowledge,
+                                            &pagi_core::compute_patch_dna(&source),
+                                            &body.name,
+                                            &format!("Property test failed: {}", failure.violation),
+                                            Some(&failure.minimal),
+                                        );
+                                    }
+                                    (report.passed(), serde_json::to_value(report).unwrap_or_default())
+                                }
+                                Err(e) => (false, serde_json::json!({ "error": e })),
+                            };
+                            // Swap the rebuilt overlay into the running loader and register the new skill
+                            let registered = match (&state.forge_loader, property_passed) {
+                                (Some(loader), true) => {
+                                    let loader = Arc::clone(loader);
+                                    let registry = Arc::clone(state.orchestrator.skill_registry());
+                                    let manifests = Arc::clone(&state.skill_manifest_registry);
+                                    tokio::task::spawn_blocking(move || {
+                                        ForgeOverlay::from_env(&workspace_root).register_skills(&registry, &manifests, loader)
+                                    })
+                                    .await
+                                    .unwrap_or_else(|e| Err(format!("Forge task failed: {}", e)))
+                                    .map_err(|e| {
+                                        tracing::warn!(target: "pagi::forge", skill = %body.name, "Forge overlay reload failed: {}", e);
+                                        e
+                                    })
+                                    .is_ok()
+                                }
+                                _ => false,
+                            };
                             // Return combined result
                             return (
                                 StatusCode::OK,
@@ -3977,17 +4455,32 @@ async fn forge_create_post(
                                     "module_name": result.module_name,
                                     "file_path": result.file_path,
                                     "cargo_check_ok": true,
-                                    "hot_reloaded": true,
-                                    "compilation_time_ms": hot_reload_result.compilation_time_ms,
-                                    "message": format!(
-                                        "Skill '{}' created and hot-reloaded successfully. Ready for immediate use.",
-                                        body.name
-                                    )
+                                    "overlay_built": true,
+                                    "compilation_time_ms": compilation_time_ms,
+                                    "property_test_passed": property_passed,
+                                    "property_test": property_json,
+                                    "registered": registered,
+                                    "message": if registered {
+                                        format!(
+                                            "Skill '{}' created, the Forge overlay rebuilt and the skill registered.",
+                                            body.name
+                                        )
+                                    } else if property_passed {
+                                        format!(
+                                            "Skill '{}' created and the Forge overlay rebuilt, but it could not be loaded into the running gateway; restart the gateway to register it.",
+                                            body.name
+                                        )
+                                    } else {
+                                        format!(
+                                            "Skill '{}' created and the Forge overlay rebuilt, but it failed property-based smoke tests; see property_test for the minimal failing payload.",
+                                            body.name
+                                        )
+                                    }
                                 })),
                             );
                         }
                         Err(e) => {
-                            tracing::warn!("🔥 Forge: Skill created but hot-reload failed: {}", e);
+                            tracing::warn!("🔥 Forge: Skill created but overlay build failed: {}", e);
                             // Fall through to return standard result
                         }
                     }
@@ -4494,6 +4987,8 @@ async fn execute(
         tenant_id: req.tenant_id,
         correlation_id: req.correlation_id,
         agent_id: Some(agent_id.to_string()),
+        session_id: None,
+        capabilities: None,
     };
 
     // ReflectShadow: require session_key to match PAGI_SHADOW_KEY (vault must be explicitly opened)
@@ -4611,6 +5106,61 @@ async fn execute(
                 )
                     .into_response();
             }
+            if let Some(v) = e.downcast_ref::<CapabilityViolation>() {
+                let msg = format!("Capability Overreach: {}", v);
+                if state.sovereign_config.kb08_success_logging {
+                    let _ = state.knowledge.record_success_metric(&msg);
+                }
+                tracing::warn!(target: "pagi::sovereignty", skill_id = %v.skill_id, capability = %v.capability, "CapabilityViolation logged to KB-08");
+                return (
+                    axum::http::StatusCode::FORBIDDEN,
+                    axum::Json(serde_json::json!({
+                        "error": msg,
+                        "status": "capability_violation",
+                        "skill_id": v.skill_id,
+                        "capability": v.capability,
+                        "message": "The skill attempted a side effect its manifest does not declare."
+                    })),
+                )
+                    .into_response();
+            }
+            if let Some(b) = e.downcast_ref::<BudgetExceeded>() {
+                let msg = format!("Energy Budget Exceeded: {}", b);
+                if state.sovereign_config.kb08_success_logging {
+                    let _ = state.knowledge.record_success_metric(&msg);
+                }
+                tracing::warn!(target: "pagi::budget", scope = b.scope, id = %b.id, dimension = b.dimension, "BudgetExceeded logged to KB-08");
+                return (
+                    axum::http::StatusCode::TOO_MANY_REQUESTS,
+                    axum::Json(serde_json::json!({
+                        "error": msg,
+                        "status": "budget_exceeded",
+                        "scope": b.scope,
+                        "dimension": b.dimension,
+                        "used": b.used,
+                        "limit": b.limit,
+                    })),
+                )
+                    .into_response();
+            }
+            if let Some(d) = e.downcast_ref::<SkillDispatchError>() {
+                let status = match d {
+                    SkillDispatchError::Timeout { .. } | SkillDispatchError::GoalTimeout { .. } => {
+                        axum::http::StatusCode::GATEWAY_TIMEOUT
+                    }
+                    SkillDispatchError::Cancelled { .. } => axum::http::StatusCode::SERVICE_UNAVAILABLE,
+                    SkillDispatchError::RejectedForCapacity { .. } => axum::http::StatusCode::TOO_MANY_REQUESTS,
+                };
+                tracing::warn!(target: "pagi::dispatch", kind = d.kind(), "{}", d);
+                return (
+                    status,
+                    axum::Json(serde_json::json!({
+                        "error": d.to_string(),
+                        "status": d.kind(),
+                    })),
+                )
+                    .into_response();
+            }
             // Reflexion: log skill failure to Chronos (Failures) for self-correction
             let (skill_name, goal_summary) = reflexion_info;
             let _ = state.knowledge.log_skill_failure(
@@ -4869,8 +5419,62 @@ async fn chat(
     }
 }
 
+/// Fallback text when the LanceDB expert finds nothing (also the "empty result" routing signal).
+const MOE_NO_KB_MATCHES: &str = "No matches in knowledge base.";
+
+/// Routes a chat prompt through the Orchestrator's MoE router and logs the decision to KB-08.
+/// A rephrase of the session's previous prompt is recorded as a correction of that decision.
+fn route_chat_prompt(state: &AppState, user_id: &str, agent_id: &str, local_ctx: &str, prompt: &str) -> RoutingDecision {
+    let session = format!("{}:{}", user_id, agent_id);
+    let (decision, rephrase) = state.orchestrator.route_moe(&session, local_ctx, prompt);
+    tracing::info!(
+        target: "pagi::chat",
+        expert = %decision.expert,
+        method = ?decision.method,
+        confidence = decision.confidence,
+        "MoE route"
+    );
+    if let Err(e) = record_routing_decision(&state.knowledge, &decision) {
+        tracing::warn!(target: "pagi::chat", error = %e, "Failed to log MoE routing decision to KB-08");
+    }
+    if let Some((previous_id, correction)) = rephrase {
+        let _ = record_routing_correction(&state.knowledge, &previous_id, correction);
+    }
+    decision
+}
+
+/// Records that the routed expert produced nothing useful for this decision.
+fn note_empty_expert_result(state: &AppState, decision_id: &str) {
+    let _ = record_routing_correction(&state.knowledge, decision_id, RoutingCorrection::EmptyResult);
+}
+
+/// Energy-budget context for LLM streams that call the ModelRouter directly instead of `dispatch`.
+fn chat_budget_ctx(user_id: &str, agent_id: &str, thread_id: Option<&String>) -> TenantContext {
+    TenantContext {
+        tenant_id: user_id.to_string(),
+        correlation_id: None,
+        agent_id: Some(agent_id.to_string()),
+        session_id: thread_id.cloned(),
+        capabilities: None,
+    }
+}
+
+/// Debits a finished live LLM stream (tokens estimated at ~4 chars each) from the energy ledger.
+fn debit_chat_stream(state: &AppState, ctx: &TenantContext, prompt: &str, response: &str, started: StdInstant) {
+    if let Some(ledger) = state.orchestrator.energy_ledger() {
+        ledger.debit(
+            ctx,
+            EnergyDebit {
+                tokens: ((prompt.len() + response.len()) / 4) as u64,
+                wall_time_ms: started.elapsed().as_millis() as u64,
+                api_calls: 1,
+            },
+        );
+    }
+}
+
 /// Non-streaming chat handler - returns JSON response.
-/// When MoE is ON, routes via route_to_experts to OpenRouter / LanceDB / SystemTool; otherwise uses ModelRouter.
+/// When MoE is ON, routes via Orchestrator::route_moe to OpenRouter / LanceDB / SystemTool; otherwise uses ModelRouter.
 async fn chat_json(
     state: AppState,
     headers: HeaderMap,
@@ -4882,16 +5486,15 @@ async fn chat_json(
         tenant_id: user_id.to_string(),
         correlation_id: Some(uuid::Uuid::new_v4().to_string()),
         agent_id: Some(agent_id.to_string()),
+        session_id: req.thread_id.clone(),
+        capabilities: None,
     };
 
-    // MoE gating: when Sparse use Gater::route_with_context; otherwise route_to_experts.
+    // MoE gating: semantic router (keyword fallback; Sparse also weighs local_ctx), logged to KB-08.
     if state.moe_active.load(Ordering::Acquire) {
         let local_ctx = state.knowledge.build_local_context_for_bridge(agent_id, local_context_limit());
-        let expert = match state.orchestrator.get_moe_mode() {
-            MoEMode::Sparse => Gater::route_with_context(&local_ctx, &req.prompt),
-            MoEMode::Dense => route_to_experts(&req.prompt),
-        };
-        tracing::info!(target: "pagi::chat", expert = ?expert, "MoE route");
+        let decision = route_chat_prompt(&state, user_id, agent_id, &local_ctx, &req.prompt);
+        let expert = decision.expert();
         match expert {
             MoEExpert::LanceDB => {
                 let slot_id = 1u8;
@@ -4899,14 +5502,18 @@ async fn chat_json(
                 match state.orchestrator.dispatch(&ctx, goal).await {
                     Ok(result) => {
                         let response = result.get("value").or(result.get("result")).and_then(|v| v.as_str())
-                            .unwrap_or_else(|| result.get("message").and_then(|v| v.as_str()).unwrap_or("No matches in knowledge base."))
+                            .unwrap_or_else(|| result.get("message").and_then(|v| v.as_str()).unwrap_or(MOE_NO_KB_MATCHES))
                             .to_string();
+                        if response == MOE_NO_KB_MATCHES {
+                            note_empty_expert_result(&state, &decision.id);
+                        }
                         save_to_memory(&state.knowledge, &req.prompt, &response);
                         return axum::Json(serde_json::json!({
                             "status": "ok",
                             "response": response,
                             "thought": "MoE: LanceDB (knowledge expert)",
                             "expert_routing": "LanceDB",
+                            "routing_decision_id": decision.id,
                             "model": "moe-lancedb",
                             "raw_result": result
                         }));
@@ -4932,10 +5539,13 @@ async fn chat_json(
                         }
                         Err(e) => (format!("[Reflex error: {}]", e), serde_json::Value::Null),
                     },
-                    None => (
-                        "MoE System expert: For file or system commands, use the Execute Skill (e.g. ReadFile) via the Skill Tester or /v1/execute.".to_string(),
-                        serde_json::Value::Null,
-                    ),
+                    None => {
+                        note_empty_expert_result(&state, &decision.id);
+                        (
+                            "MoE System expert: For file or system commands, use the Execute Skill (e.g. ReadFile) via the Skill Tester or /v1/execute.".to_string(),
+                            serde_json::Value::Null,
+                        )
+                    }
                 };
                 save_to_memory(&state.knowledge, &req.prompt, &response);
                 return axum::Json(serde_json::json!({
@@ -4943,6 +5553,7 @@ async fn chat_json(
                     "response": response,
                     "thought": "MoE: SystemTool (Local Reflex)",
                     "expert_routing": "Local System Tool",
+                    "routing_decision_id": decision.id,
                     "model": "moe-system",
                     "raw_result": raw_result
                 }));
@@ -5357,27 +5968,29 @@ async fn chat_streaming(
     let agent_id = req.agent_id.as_deref().filter(|s| !s.is_empty()).unwrap_or(pagi_core::DEFAULT_AGENT_ID);
     let knowledge = Arc::clone(&state.knowledge);
 
-    // MoE gating: when Sparse use Gater::route_with_context; LanceDB/SystemTool stream one chunk (reflex runs for SystemTool).
+    // MoE gating: semantic router (keyword fallback); LanceDB/SystemTool stream one chunk (reflex runs for SystemTool).
     if state.moe_active.load(Ordering::Acquire) {
         let local_ctx = state.knowledge.build_local_context_for_bridge(agent_id, local_context_limit());
-        let expert = match state.orchestrator.get_moe_mode() {
-            MoEMode::Sparse => Gater::route_with_context(&local_ctx, &req.prompt),
-            MoEMode::Dense => route_to_experts(&req.prompt),
-        };
-        tracing::info!(target: "pagi::chat", expert = ?expert, "MoE stream route");
+        let decision = route_chat_prompt(&state, user_id, agent_id, &local_ctx, &req.prompt);
+        let expert = decision.expert();
         if matches!(expert, MoEExpert::LanceDB) {
             let ctx = TenantContext {
                 tenant_id: user_id.to_string(),
                 correlation_id: Some(uuid::Uuid::new_v4().to_string()),
                 agent_id: Some(agent_id.to_string()),
+                session_id: req.thread_id.clone(),
+                capabilities: None,
             };
             let goal = Goal::QueryKnowledge { slot_id: 1, query: req.prompt.trim().to_string() };
             let chunk = match state.orchestrator.dispatch(&ctx, goal).await {
                 Ok(result) => result.get("value").or(result.get("result")).and_then(|v| v.as_str())
-                    .unwrap_or_else(|| result.get("message").and_then(|v| v.as_str()).unwrap_or("No matches in knowledge base."))
+                    .unwrap_or_else(|| result.get("message").and_then(|v| v.as_str()).unwrap_or(MOE_NO_KB_MATCHES))
                     .to_string(),
                 Err(e) => format!("[Knowledge query error: {}]", e),
             };
+            if chunk == MOE_NO_KB_MATCHES {
+                note_empty_expert_result(&state, &decision.id);
+            }
             if !chunk.is_empty() {
                 save_to_memory(&knowledge, &req.prompt, &chunk);
             }
@@ -5387,6 +6000,7 @@ async fn chat_streaming(
                 .header("Content-Type", "text/plain; charset=utf-8")
                 .header("Cache-Control", "no-cache")
                 .header("X-Expert-Routing", "LanceDB")
+                .header("X-Routing-Decision-Id", decision.id.as_str())
                 .body(body)
                 .unwrap();
         }
@@ -5395,6 +6009,8 @@ async fn chat_streaming(
                 tenant_id: user_id.to_string(),
                 correlation_id: Some(uuid::Uuid::new_v4().to_string()),
                 agent_id: Some(agent_id.to_string()),
+                session_id: req.thread_id.clone(),
+                capabilities: None,
             };
             let chunk = match prompt_to_system_tool_goal(&req.prompt) {
                 Some(goal) => match state.orchestrator.dispatch(&ctx, goal.clone()).await {
@@ -5406,7 +6022,10 @@ async fn chat_streaming(
                     }
                     Err(e) => format!("[Reflex error: {}]", e),
                 },
-                None => "MoE System expert: For file or system commands, use the Execute Skill (e.g. ReadFile) via the Skill Tester or /v1/execute.".to_string(),
+                None => {
+                    note_empty_expert_result(&state, &decision.id);
+                    "MoE System expert: For file or system commands, use the Execute Skill (e.g. ReadFile) via the Skill Tester or /v1/execute.".to_string()
+                }
             };
             save_to_memory(&knowledge, &req.prompt, &chunk);
             let body = Body::from_stream(stream! { yield Ok::<_, std::convert::Infallible>(chunk) });
@@ -5415,6 +6034,7 @@ async fn chat_streaming(
                 .header("Content-Type", "text/plain; charset=utf-8")
                 .header("Cache-Control", "no-cache")
                 .header("X-Expert-Routing", "Local System Tool")
+                .header("X-Routing-Decision-Id", decision.id.as_str())
                 .body(body)
                 .unwrap();
         }
@@ -5717,6 +6337,16 @@ async fn chat_streaming(
     };
     
     let is_live = std::env::var("PAGI_LLM_MODE").as_deref() == Ok("live");
+    let budget_ctx = chat_budget_ctx(user_id, agent_id, req.thread_id.as_ref());
+    if is_live {
+        if let Some(Err(e)) = state.orchestrator.energy_ledger().map(|l| l.check(&budget_ctx)) {
+            return Response::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .header("Content-Type", "text/plain; charset=utf-8")
+                .body(Body::from(format!("[Energy Budget Exceeded: {}]", e)))
+                .unwrap();
+        }
+    }
     
     let stream = stream! {
         let mut accumulated_response = String::new();
@@ -5732,6 +6362,7 @@ async fn chat_streaming(
         
         if is_live {
             // Live streaming from OpenRouter — [system (Mission Directive), user]
+            let started = StdInstant::now();
             match state.model_router.stream_generate(
                 Some(&system_directive),
                 &req.prompt,
@@ -5744,6 +6375,7 @@ async fn chat_streaming(
                         accumulated_response.push_str(&chunk);
                         yield chunk;
                     }
+                    debit_chat_stream(&state, &budget_ctx, &req.prompt, &accumulated_response, started);
                 }
                 Err(e) => {
                     tracing::error!(
@@ -5806,34 +6438,38 @@ async fn chat_stream_sse(
         .interval(Duration::from_secs(15))
         .text("keepalive");
 
-    // MoE gating: when Sparse use Gater::route_with_context(local_ctx, prompt); else route_to_experts.
-    // LanceDB/SystemTool yield expert_routing event (for UI) then token(s) then done.
+    // MoE gating: semantic router (keyword fallback; Sparse also weighs local_ctx), logged to KB-08.
+    // LanceDB/SystemTool yield expert_routing + routing_decision events (for UI) then token(s) then done.
     if state.moe_active.load(Ordering::Acquire) {
         let local_ctx = state.knowledge.build_local_context_for_bridge(agent_id, local_context_limit());
-        let expert = match state.orchestrator.get_moe_mode() {
-            MoEMode::Sparse => Gater::route_with_context(&local_ctx, &req.prompt),
-            MoEMode::Dense => route_to_experts(&req.prompt),
-        };
-        tracing::info!(target: "pagi::chat", expert = ?expert, "MoE stream route");
+        let decision = route_chat_prompt(&state, user_id, agent_id, &local_ctx, &req.prompt);
+        let expert = decision.expert();
+        let decision_id = decision.id.clone();
 
         if matches!(expert, MoEExpert::LanceDB) {
             let ctx = TenantContext {
                 tenant_id: user_id.to_string(),
                 correlation_id: Some(uuid::Uuid::new_v4().to_string()),
                 agent_id: Some(agent_id.to_string()),
+                session_id: req.thread_id.clone(),
+                capabilities: None,
             };
             let goal = Goal::QueryKnowledge { slot_id: 1, query: req.prompt.trim().to_string() };
             let data = match state.orchestrator.dispatch(&ctx, goal).await {
                 Ok(result) => result.get("value").or(result.get("result")).and_then(|v| v.as_str())
-                    .unwrap_or_else(|| result.get("message").and_then(|v| v.as_str()).unwrap_or("No matches in knowledge base."))
+                    .unwrap_or_else(|| result.get("message").and_then(|v| v.as_str()).unwrap_or(MOE_NO_KB_MATCHES))
                     .to_string(),
                 Err(e) => format!("[Knowledge query error: {}]", e),
             };
+            if data == MOE_NO_KB_MATCHES {
+                note_empty_expert_result(&state, &decision.id);
+            }
             if !data.is_empty() {
                 save_to_memory(&knowledge, &req.prompt, &data);
             }
             let s = stream! {
                 yield Ok(Event::default().event("expert_routing").data("LanceDB"));
+                yield Ok(Event::default().event("routing_decision").data(decision_id));
                 yield Ok(Event::default().event("token").data(data));
                 yield Ok(Event::default().event("done").data(""));
             };
@@ -5845,6 +6481,8 @@ async fn chat_stream_sse(
                 tenant_id: user_id.to_string(),
                 correlation_id: Some(uuid::Uuid::new_v4().to_string()),
                 agent_id: Some(agent_id.to_string()),
+                session_id: req.thread_id.clone(),
+                capabilities: None,
             };
             let data = match prompt_to_system_tool_goal(&req.prompt) {
                 Some(goal) => match state.orchestrator.dispatch(&ctx, goal.clone()).await {
@@ -5856,11 +6494,15 @@ async fn chat_stream_sse(
                     }
                     Err(e) => format!("[Reflex error: {}]", e),
                 },
-                None => "MoE System expert: For file or system commands, use the Execute Skill (e.g. ReadFile) via the Skill Tester or /v1/execute.".to_string(),
+                None => {
+                    note_empty_expert_result(&state, &decision.id);
+                    "MoE System expert: For file or system commands, use the Execute Skill (e.g. ReadFile) via the Skill Tester or /v1/execute.".to_string()
+                }
             };
             save_to_memory(&knowledge, &req.prompt, &data);
             let s = stream! {
                 yield Ok(Event::default().event("expert_routing").data("Local System Tool"));
+                yield Ok(Event::default().event("routing_decision").data(decision_id));
                 yield Ok(Event::default().event("token").data(data));
                 yield Ok(Event::default().event("done").data(""));
             };
@@ -5919,6 +6561,20 @@ async fn chat_stream_sse(
         "Finalizing Synthesis...",
     ];
 
+    let budget_ctx = chat_budget_ctx(user_id, agent_id, req.thread_id.as_ref());
+    if is_live {
+        if let Some(Err(e)) = state.orchestrator.energy_ledger().map(|l| l.check(&budget_ctx)) {
+            let msg = format!("Energy Budget Exceeded: {}", e);
+            let stream = stream! {
+                yield Ok(Event::default().event("error").data(msg));
+                yield Ok(Event::default().event("done").data(""));
+            };
+            let boxed: Pin<Box<dyn futures_util::Stream<Item = Result<Event, std::convert::Infallible>> + Send + 'static>> = Box::pin(stream);
+            return Sse::new(boxed).keep_alive(keep_alive);
+        }
+    }
+    let budget_state = state.clone();
+
     let model_router = state.model_router.clone();
     let system_directive_clone = system_directive.clone();
     let req_prompt = req.prompt.clone();
@@ -5929,6 +6585,7 @@ async fn chat_stream_sse(
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Result<String, String>>();
     let _llm_handle = tokio::spawn(async move {
         if is_live {
+            let started = StdInstant::now();
             match model_router
                 .stream_generate(
                     Some(&system_directive_clone),
@@ -5940,9 +6597,12 @@ async fn chat_stream_sse(
                 .await
             {
                 Ok(mut recv) => {
+                    let mut streamed = String::new();
                     while let Some(chunk) = recv.recv().await {
+                        streamed.push_str(&chunk);
                         let _ = tx.send(Ok(chunk));
                     }
+                    debit_chat_stream(&budget_state, &budget_ctx, &req_prompt, &streamed, started);
                 }
                 Err(e) => {
                     let _ = tx.send(Err(e.to_string()));
@@ -6096,6 +6756,205 @@ async fn get_research_trace(
     Ok(axum::Json(trace))
 }
 
+// ---------------------------------------------------------------------------
+// Federation Handlers
+// ---------------------------------------------------------------------------
+
+/// Starts the federation master when PAGI_FEDERATION_LISTEN is set (e.g. `0.0.0.0:8002`).
+/// Tasks persist in PAGI_FEDERATION_QUEUE_DIR (default `./data/pagi_federation_queue`), node
+/// transitions are logged to KB-08, and TLS / node policy / CA / artifacts / knowledge replication
+/// come from the usual `PAGI_FEDERATION_*` variables. Returns `None` (federation endpoints answer
+/// 503) otherwise.
+fn start_federation_master(knowledge: Arc<KnowledgeStore>) -> Option<Arc<pagi_federation::FederationHandle>> {
+    use pagi_federation::{ArtifactStore, KnowledgeReplicator, MasterServer, MasterState, TaskQueue};
+    let listen = std::env::var("PAGI_FEDERATION_LISTEN").ok().filter(|s| !s.trim().is_empty())?;
+    let addr: std::net::SocketAddr = match listen.trim().parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            tracing::error!(target: "pagi::federation", "Invalid PAGI_FEDERATION_LISTEN {:?}: {}", listen, e);
+            return None;
+        }
+    };
+    let queue_dir = std::env::var("PAGI_FEDERATION_QUEUE_DIR")
+        .unwrap_or_else(|_| "./data/pagi_federation_queue".to_string());
+    let queue = match TaskQueue::open(&queue_dir) {
+        Ok(queue) => Arc::new(queue),
+        Err(e) => {
+            tracing::error!(target: "pagi::federation", "Federation queue at {} unavailable: {}", queue_dir, e);
+            return None;
+        }
+    };
+    let mut master_state = MasterState::new().with_knowledge(Arc::clone(&knowledge)).with_queue(queue);
+    match ArtifactStore::from_env() {
+        Ok(Some(store)) => master_state = master_state.with_artifacts(Arc::new(store)),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(target: "pagi::federation", "Artifact store unavailable: {}", e),
+    }
+    let mut server = match MasterServer::new(Arc::new(master_state)).with_security_from_env() {
+        Ok(server) => server,
+        Err(e) => {
+            tracing::error!(target: "pagi::federation", "Federation security config invalid: {}", e);
+            return None;
+        }
+    };
+    match KnowledgeReplicator::from_env(knowledge) {
+        Ok(Some(replicator)) => server = server.with_replication(Arc::new(replicator)),
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!(target: "pagi::federation", "Federation replication policy invalid: {}", e);
+            return None;
+        }
+    }
+    let server = Arc::new(server);
+    let handle = server.handle();
+    tokio::spawn(async move {
+        if let Err(e) = MasterServer::serve(server, addr).await {
+            tracing::error!(target: "pagi::federation", "Federation master stopped: {}", e);
+        }
+    });
+    tracing::info!(target: "pagi::federation", "Federation master listening on {} (queue: {}).", addr, queue_dir);
+    Some(handle)
+}
+
+/// Registers a `FederatedBridgeSkill` for each goal in PAGI_FEDERATION_CAPABILITIES (e.g.
+/// `red_team_scan,market_scan`), so the orchestrator can hand them to satellites. Their progress
+/// goes to the log broadcast and out on `/api/v1/federation/progress`. Each one is listed in
+/// `manifests` as an import-tier skill with no local capabilities (the work runs on a satellite).
+fn register_federation_skills(
+    registry: &mut SkillRegistry,
+    manifests: &SkillManifestRegistry,
+    handle: &Arc<pagi_federation::FederationHandle>,
+    log_tx: &broadcast::Sender<String>,
+) {
+    let capabilities = std::env::var("PAGI_FEDERATION_CAPABILITIES").unwrap_or_default();
+    for capability in capabilities.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+        registry.register(Arc::new(
+            pagi_federation::FederatedBridgeSkill::new(capability.to_string(), Arc::clone(handle))
+                .with_progress_sink(log_tx.clone()),
+        ));
+        manifests.register_runtime(
+            TrustTier::Import,
+            SkillManifestEntry {
+                skill_id: capability.to_string(),
+                kb_layers_allowed: Vec::new(),
+                description: Some("Federated goal (runs on a satellite)".to_string()),
+                capabilities: Default::default(),
+                artifact: None,
+                artifact_sha256: None,
+                signature: None,
+            },
+        );
+        tracing::info!(target: "pagi::federation", "Federated skill registered: {}", capability);
+    }
+}
+
+const FEDERATION_DISABLED: (StatusCode, &str) = (
+    StatusCode::SERVICE_UNAVAILABLE,
+    "Federation master not running (set PAGI_FEDERATION_LISTEN)",
+);
+
+/// GET /api/v1/federation/nodes – registered satellites: role, capabilities, hardware, last
+/// heartbeat (CPU / RAM / bandwidth), in-flight tasks, liveness and whether the task stream is open.
+async fn federation_nodes(
+    State(state): State<AppState>,
+) -> Result<axum::Json<serde_json::Value>, (StatusCode, &'static str)> {
+    let federation = state.federation.as_ref().ok_or(FEDERATION_DISABLED)?;
+    let nodes = federation.nodes();
+    Ok(axum::Json(serde_json::json!({
+        "count": nodes.len(),
+        "nodes": nodes,
+    })))
+}
+
+#[derive(serde::Deserialize)]
+struct FederationTasksQuery {
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+/// GET /api/v1/federation/tasks?limit=100 – task queue and history (unfinished first, then newest):
+/// state, node, attempts, duration and outcome. History is kept for 7 days by the durable queue.
+async fn federation_tasks(
+    State(state): State<AppState>,
+    Query(q): Query<FederationTasksQuery>,
+) -> Result<axum::Json<serde_json::Value>, (StatusCode, &'static str)> {
+    let federation = state.federation.clone().ok_or(FEDERATION_DISABLED)?;
+    let limit = q.limit.unwrap_or(100).clamp(1, 1000);
+    let tasks = tokio::task::spawn_blocking(move || federation.tasks(limit))
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Federation task listing panicked"))?
+        .map_err(|e| {
+            tracing::warn!(target: "pagi::federation", "Federation task listing failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Federation queue read failed")
+        })?;
+    Ok(axum::Json(serde_json::json!({
+        "count": tasks.len(),
+        "tasks": tasks,
+    })))
+}
+
+/// GET /api/v1/federation/events – SSE stream of satellite state changes. Each registration and
+/// each healthy / suspect / dead transition is an `event: node_event` with
+/// `{node_id, from, to, reason, at_ms}`.
+async fn federation_node_events(
+    State(state): State<AppState>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>> + Send + 'static>, (StatusCode, &'static str)>
+{
+    use async_stream::stream;
+    let federation = state.federation.as_ref().ok_or(FEDERATION_DISABLED)?;
+    let mut rx = federation.subscribe_node_events();
+    let stream = stream! {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let data = serde_json::to_string(&event).unwrap_or_default();
+                    yield Ok(Event::default().event("node_event").data(data));
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    yield Ok(Event::default().event("node_event_lagged").data(n.to_string()));
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    Ok(Sse::new(stream).keep_alive(
+        axum::response::sse::KeepAlive::new()
+            .interval(Duration::from_secs(30))
+            .text("keepalive"),
+    ))
+}
+
+/// GET /api/v1/federation/progress – SSE stream of progress from federated tasks.
+/// `FederatedBridgeSkill`s built with `with_progress_sink(log_tx)` publish
+/// `PROGRESS_LINE_PREFIX` prefixed lines; each becomes an `event: federation_progress` SSE event
+/// carrying the task id, node, percent, log lines and partial artifacts.
+async fn federation_progress_stream(
+    State(state): State<AppState>,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>> + Send + 'static> {
+    use async_stream::stream;
+    let mut rx = state.log_tx.subscribe();
+    let stream = stream! {
+        loop {
+            match rx.recv().await {
+                Ok(line) => {
+                    if let Some(json) = line.strip_prefix(pagi_federation::PROGRESS_LINE_PREFIX) {
+                        yield Ok(Event::default().event("federation_progress").data(json));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    yield Ok(Event::default().event("federation_progress_lagged").data(n.to_string()));
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    Sse::new(stream).keep_alive(
+        axum::response::sse::KeepAlive::new()
+            .interval(Duration::from_secs(30))
+            .text("keepalive"),
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Maintenance Dashboard Handlers
 // ---------------------------------------------------------------------------
@@ -6317,6 +7176,7 @@ async fn patch_version_history(
         }
     };
 
+    let active = pagi_evolution::ActiveManifest::load(patches_dir).0.snapshot().skills;
     let mut history: Vec<serde_json::Value> = Vec::new();
 
     for entry in entries.flatten() {
@@ -6329,11 +7189,6 @@ async fn patch_version_history(
             None => continue,
         };
 
-        // Skip current_* symlinks/copies.
-        if filename.starts_with("current_") {
-            continue;
-        }
-
         // Parse versioned filename: {skill_name}_v{timestamp}.rs
         if let Some(stem) = filename.strip_suffix(".rs") {
             if let Some(v_pos) = stem.rfind("_v") {
@@ -6348,16 +7203,10 @@ async fn patch_version_history(
                 }
 
                 if let Ok(timestamp) = timestamp_str.parse::<i64>() {
-                    // Check if this is the current active version.
-                    let current_link = patches_dir.join(format!("current_{}.rs", skill_name));
-                    let is_active = if current_link.exists() {
-                        // Compare file contents.
-                        let current_code = std::fs::read_to_string(&current_link).unwrap_or_default();
-                        let version_code = std::fs::read_to_string(&path).unwrap_or_default();
-                        current_code == version_code
-                    } else {
-                        false
-                    };
+                    // The active-versions manifest says which version is live.
+                    let is_active = active
+                        .get(skill_name)
+                        .is_some_and(|e| e.timestamp_ms == timestamp);
 
                     let file_size = std::fs::metadata(&path)
                         .map(|m| m.len())
@@ -6394,6 +7243,72 @@ async fn patch_version_history(
     }))
 }
 
+/// GET /api/v1/maintenance/lineage/:skill – genealogy of a skill's patch versions.
+/// Query params: ?format=json|dot|mermaid (default json), ?ancestors_of=<timestamp_ms>
+/// (default: the active version).
+#[derive(serde::Deserialize)]
+struct LineageQuery {
+    format: Option<String>,
+    ancestors_of: Option<i64>,
+}
+
+async fn maintenance_lineage(
+    Path(skill): Path<String>,
+    axum::extract::Query(q): axum::extract::Query<LineageQuery>,
+) -> Response {
+    // Reads the patches dir and lineage sidecars directly; opening a RollbackManager here would
+    // repair and rewrite the active-versions manifest on every GET.
+    let patches_dir = pagi_evolution::RollbackConfig::default().patches_dir;
+    let name = skill.clone();
+    let loaded = tokio::task::spawn_blocking(move || pagi_evolution::LineageGraph::load(&patches_dir, &name))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r.map_err(|e| e.to_string()));
+    let graph = match loaded {
+        Ok(graph) => graph,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "status": "error", "message": e })),
+            )
+                .into_response();
+        }
+    };
+
+    match q.format.as_deref().unwrap_or("json") {
+        "dot" => (
+            [(axum::http::header::CONTENT_TYPE, "text/vnd.graphviz; charset=utf-8")],
+            graph.to_dot(),
+        )
+            .into_response(),
+        "mermaid" => (
+            [(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            graph.to_mermaid(),
+        )
+            .into_response(),
+        "json" => {
+            let ancestors_of = q.ancestors_of.or_else(|| graph.active().map(|n| n.timestamp_ms));
+            let ancestors = ancestors_of.map(|ts| graph.ancestors(ts)).unwrap_or_default();
+            Json(serde_json::json!({
+                "skill": skill,
+                "active": graph.active().map(|n| n.timestamp_ms),
+                "ancestors_of": ancestors_of,
+                "ancestors": ancestors,
+                "graph": graph,
+            }))
+            .into_response()
+        }
+        other => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Unknown format '{}' (expected json, dot or mermaid)", other),
+            })),
+        )
+            .into_response(),
+    }
+}
+
 /// POST /api/v1/maintenance/rollback – revert a skill to a previous version.
 /// Body: { "skill": "patch_fs_tools", "target_timestamp": 1707307200000, "reason": "..." }
 async fn rollback_skill_endpoint(
@@ -6476,24 +7391,25 @@ async fn rollback_skill_endpoint(
             }));
         }
     };
+    let target_ts = target_file
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.rsplit_once("_v"))
+        .and_then(|(_, ts)| ts.parse::<i64>().ok())
+        .unwrap_or_default();
 
-    // Update the current symlink to point to the target version.
-    let current_path = patches_dir.join(format!("current_{}.rs", sanitized_skill));
-    let temp_path = patches_dir.join(format!("current_{}.rs.tmp", sanitized_skill));
-    let _ = std::fs::remove_file(&temp_path);
-
-    if let Err(e) = std::fs::copy(&target_file, &temp_path) {
-        return axum::Json(serde_json::json!({
-            "status": "error",
-            "message": format!("Failed to copy target version: {}", e)
-        }));
-    }
-
-    let _ = std::fs::remove_file(&current_path);
-    if let Err(e) = std::fs::rename(&temp_path, &current_path) {
+    // Swap the active-versions manifest from the live version to the target.
+    let (manifest, _) = pagi_evolution::ActiveManifest::load(patches_dir);
+    let previous = manifest.get(&sanitized_skill);
+    let entry = pagi_evolution::ActiveEntry::for_source(target_ts, &target_file, &target_code);
+    if let Err(e) = manifest.compare_and_swap(
+        &sanitized_skill,
+        previous.as_ref().map(|e| e.timestamp_ms),
+        Some(entry),
+    ) {
         return axum::Json(serde_json::json!({
             "status": "error",
-            "message": format!("Failed to update current symlink: {}", e)
+            "message": format!("Failed to activate target version: {}", e)
         }));
     }
 
@@ -6514,8 +7430,10 @@ async fn rollback_skill_endpoint(
         .append_chronos_event("MAINTENANCE_LOOP", &event);
 
     // Record the rolled-back version's DNA as a dead-end.
-    let current_code = std::fs::read_to_string(&current_path).unwrap_or_default();
-    if current_code != target_code {
+    let current_code = previous
+        .and_then(|e| std::fs::read_to_string(manifest.source_path(&e)).ok())
+        .unwrap_or_default();
+    if !current_code.is_empty() && current_code != target_code {
         let code_hash = pagi_core::compute_patch_dna(&current_code);
         pagi_core::record_genetic_dead_end(
             &state.knowledge,
@@ -6634,6 +7552,8 @@ mod tests {
             sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
             project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            federation: None,
+            forge_loader: None,
             });
         let req = Request::builder()
             .method("GET")
@@ -6684,6 +7604,8 @@ mod tests {
             sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
             project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            federation: None,
+            forge_loader: None,
             });
 
         let body = serde_json::json!({
@@ -6755,6 +7677,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         let req = Request::builder()
@@ -6813,6 +7737,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         let body = serde_json::json!({
@@ -6874,6 +7800,8 @@ mod tests {
             sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
             project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            federation: None,
+            forge_loader: None,
             });
 
         let query_body = serde_json::json!({
@@ -6956,6 +7884,8 @@ mod tests {
             sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
             project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            federation: None,
+            forge_loader: None,
             });
 
         let write_body = serde_json::json!({
@@ -7053,6 +7983,8 @@ mod tests {
             sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
             project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            federation: None,
+            forge_loader: None,
             });
 
         let sentiment_body = serde_json::json!({
@@ -7150,6 +8082,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         let insert_body = serde_json::json!({
@@ -7249,6 +8183,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         // 1. Capture a lead to get lead_id (IngestData)
@@ -7363,6 +8299,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         // 1. Capture a lead (IngestData)
@@ -7460,6 +8398,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         // 1. Capture a lead (IngestData)
@@ -7577,6 +8517,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         let mock_html = r#"<!DOCTYPE html>
@@ -7674,6 +8616,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         let mock_html = r#"<html><body><h1>Fall Festival Next Week</h1></body></html>"#;
@@ -7748,6 +8692,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         let lead_body = serde_json::json!({
@@ -7840,6 +8786,8 @@ mod tests {
         sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
         project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
         folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+        federation: None,
+        forge_loader: None,
         });
 
         let body = serde_json::json!({
@@ -7929,6 +8877,8 @@ mod tests {
             sovereignty_score_bits: Arc::new(AtomicU64::new(0)),
             project_associations: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             folder_summary_cache: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
+            federation: None,
+            forge_loader: None,
             });
 
         let prune_body = serde_json::json!({
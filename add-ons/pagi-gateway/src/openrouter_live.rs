@@ -8,7 +8,8 @@ use pagi_voice::{
 };
 use pagi_core::{
     KnowledgeStore, MemoryManager, SkillRegistry, KbType, LiveSkillRegistry,
-    LiveSkill, SkillExecutionRequest, SkillExecutionResult, SkillPriority, TenantContext,
+    LiveSkill, SkillExecutionRequest, SkillExecutionResult, SkillManifestRegistry, SkillPriority,
+    TenantContext, EnergyLedger, run_live_skill,
 };
 use crate::knowledge_router::{KnowledgeRouter, KbQueryRequest};
 use futures_util::StreamExt;
@@ -32,6 +33,10 @@ pub struct OpenRouterLiveSession {
     knowledge: Arc<KnowledgeStore>,
     kb_router: Arc<KnowledgeRouter>,
     live_skills: Arc<LiveSkillRegistry>,
+    /// Tier manifests; every live skill call runs with a capability handle from here
+    skill_manifests: Arc<SkillManifestRegistry>,
+    /// Energy ledger shared with the Orchestrator; live skill calls are checked and debited here
+    energy: Option<Arc<EnergyLedger>>,
     _skills: Arc<SkillRegistry>,
     _memory: Arc<MemoryManager>,
     /// Sentence buffer for streaming TTS (speak on sentence boundaries)
@@ -51,6 +56,8 @@ impl OpenRouterLiveSession {
     pub fn from_env(
         knowledge: Arc<KnowledgeStore>,
         skills: Arc<SkillRegistry>,
+        skill_manifests: Arc<SkillManifestRegistry>,
+        energy: Option<Arc<EnergyLedger>>,
         memory: Arc<MemoryManager>,
     ) -> Result<Self, String> {
         let stt = OpenRouterStt::from_env()
@@ -75,6 +82,8 @@ impl OpenRouterLiveSession {
         let tenant_ctx = TenantContext {
             agent_id: "phoenix".to_string(),
             tenant_id: "default".to_string(),
+            session_id: None,
+            capabilities: None,
         };
         
         Ok(Self {
@@ -84,6 +93,8 @@ impl OpenRouterLiveSession {
             knowledge,
             kb_router,
             live_skills,
+            skill_manifests,
+            energy,
             _skills: skills,
             _memory: memory,
             sentence_buffer: String::new(),
@@ -487,17 +498,15 @@ impl OpenRouterLiveSession {
             info!("✓ KB-05 security validation passed for skill '{}'", request.skill_name);
         }
         
+        let ctx = self.skill_manifests
+            .scoped_context(&request.skill_name, &self.tenant_ctx)
+            .map_err(|e| e.to_string())?;
+        
         // Execute skill
-        match skill.execute(&self.tenant_ctx, &self.knowledge, request.params.clone()).await {
+        match run_live_skill(self.energy.as_deref(), skill.as_ref(), &ctx, &self.knowledge, request.params.clone()).await {
             Ok(output) => {
                 let duration_ms = start_time.elapsed().as_millis() as u64;
-                let energy_used = match skill.energy_cost() {
-                    pagi_core::EnergyCost::Minimal => 50,
-                    pagi_core::EnergyCost::Low => 200,
-                    pagi_core::EnergyCost::Medium => 1000,
-                    pagi_core::EnergyCost::High => 3000,
-                    pagi_core::EnergyCost::VeryHigh => 6000,
-                };
+                let energy_used = skill.energy_cost().estimated_tokens() as u32;
                 
                 Ok(SkillExecutionResult {
                     skill_name: request.skill_name,
@@ -533,6 +542,8 @@ pub fn start_openrouter_live_session(
     log_tx: tokio::sync::broadcast::Sender<String>,
     knowledge: Arc<KnowledgeStore>,
     skills: Arc<SkillRegistry>,
+    skill_manifests: Arc<SkillManifestRegistry>,
+    energy: Option<Arc<EnergyLedger>>,
     memory: Arc<MemoryManager>,
 ) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || {
@@ -545,7 +556,7 @@ pub fn start_openrouter_live_session(
         };
         
         rt.block_on(async move {
-            match OpenRouterLiveSession::from_env(knowledge, skills, memory) {
+            match OpenRouterLiveSession::from_env(knowledge, skills, skill_manifests, energy, memory) {
                 Ok(session) => {
                     info!(target: "pagi::voice", "🌐 OpenRouter Live Mode started");
                     let _ = log_tx.send("🎙️ Phoenix is listening (OpenRouter Live)".to_string());
@@ -47,5 +47,7 @@ pub fn default_tenant() -> TenantContext {
         tenant_id: "pagi-offsec-ui".to_string(),
         correlation_id: None,
         agent_id: None,
+        session_id: None,
+        capabilities: None,
     }
 }
@@ -9,12 +9,14 @@
 //! 4. **Ethos (Slot 6)** — Active philosophical school and maxims
 //! 5. **Oikos (Slot 2)** — Task governance summary and governed tasks
 //! 6. **Shadow (Slot 9)** — Vault lock status
+//! 7. **Federation** — Satellites (role, load, liveness) and recent tasks, from the running gateway
 //!
 //! ## Usage
 //!
 //! ```text
 //! pagi status     — full dashboard (default)
 //! pagi dash       — alias for status
+//! pagi federation — federation section only
 //! pagi            — same (no args)
 //! pagi --help     — print usage
 //! ```
@@ -40,6 +42,15 @@ fn main() {
                 std::process::exit(1);
             }
         }
+        "federation" | "fed" => {
+            match CoreConfig::load() {
+                Ok(config) => print_federation(config.port),
+                Err(e) => {
+                    eprintln!("pagi {}: Config: {}", sub, e);
+                    std::process::exit(1);
+                }
+            }
+        }
         "--help" | "-h" | "help" => {
             println!("PAGI Sovereign Dashboard v{}", VERSION);
             println!();
@@ -48,13 +59,14 @@ fn main() {
             println!("Commands:");
             println!("  status   Full Sovereign Dashboard — cross-layer situation report (default)");
             println!("  dash     Alias for 'status'");
+            println!("  federation  Satellites and federated tasks (from the running gateway)");
             println!("  help     Print this help message");
             println!();
             println!("The dashboard reads from the KnowledgeStore at {{storage_path}}/pagi_knowledge.");
             println!("Configure via PAGI_CONFIG env var or config/gateway.toml.");
         }
         other => {
-            eprintln!("Unknown subcommand '{}'. Use: pagi status | pagi dash | pagi --help", other);
+            eprintln!("Unknown subcommand '{}'. Use: pagi status | pagi dash | pagi federation | pagi --help", other);
             std::process::exit(1);
         }
     }
@@ -91,6 +103,7 @@ fn run_status() -> Result<(), String> {
     println!();
 
     render_sovereign_state(&state);
+    print_federation(port);
 
     println!("  Run `pagi status` at any time to refresh this report.");
     println!();
@@ -99,7 +112,22 @@ fn run_status() -> Result<(), String> {
 
 /// Fetches full sovereign state from the gateway's Live Status API (used when Sled is locked).
 fn fetch_sovereign_state_from_api(port: u16) -> Result<SovereignState, String> {
-    let url = format!("http://127.0.0.1:{}/api/v1/sovereign-status", port);
+    let resp = gateway_get(port, "/api/v1/sovereign-status")?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().unwrap_or_default();
+        return Err(format!(
+            "Sovereign-status API error {}: {}",
+            status,
+            if body.is_empty() { "unauthorized? Set PAGI_API_KEY if the endpoint is protected." } else { body.as_str() }
+        ));
+    }
+    resp.json().map_err(|e| format!("Invalid JSON from gateway: {}", e))
+}
+
+/// GET `path` on the local gateway, sending `PAGI_API_KEY` as `X-API-Key` when set.
+fn gateway_get(port: u16, path: &str) -> Result<reqwest::blocking::Response, String> {
+    let url = format!("http://127.0.0.1:{}{}", port, path);
     let client = reqwest::blocking::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
         .build()
@@ -111,22 +139,24 @@ fn fetch_sovereign_state_from_api(port: u16) -> Result<SovereignState, String> {
             req = req.header("X-API-Key", key);
         }
     }
-    let resp = req.send().map_err(|e| {
+    req.send().map_err(|e| {
         format!(
             "Gateway unreachable at {} ({}). Start the gateway or use a different port.",
             url, e
         )
-    })?;
+    })
+}
+
+/// Fetches a federation endpoint; `Ok(None)` when the gateway runs without a federation master.
+fn fetch_federation_json(port: u16, path: &str) -> Result<Option<serde_json::Value>, String> {
+    let resp = gateway_get(port, path)?;
+    if resp.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+        return Ok(None);
+    }
     if !resp.status().is_success() {
-        let status = resp.status();
-        let body = resp.text().unwrap_or_default();
-        return Err(format!(
-            "Sovereign-status API error {}: {}",
-            status,
-            if body.is_empty() { "unauthorized? Set PAGI_API_KEY if the endpoint is protected." } else { body.as_str() }
-        ));
+        return Err(format!("Federation API error {} on {}", resp.status(), path));
     }
-    resp.json().map_err(|e| format!("Invalid JSON from gateway: {}", e))
+    resp.json().map(Some).map_err(|e| format!("Invalid JSON from gateway: {}", e))
 }
 
 /// Renders the full dashboard from a SovereignState (direct store or API).
@@ -667,10 +697,214 @@ fn print_shadow_from_state(state: &SovereignState) {
     println!();
 }
 
+/// Satellites and recent tasks from the gateway's federation master (`/api/v1/federation/*`).
+/// Unlike the KB sections this needs the gateway running with PAGI_FEDERATION_LISTEN set.
+fn print_federation(port: u16) {
+    println!("  ┌─ FEDERATION ─ Satellites & Tasks ───────────────────────────────┐");
+    println!();
+
+    let nodes = match fetch_federation_json(port, "/api/v1/federation/nodes") {
+        Ok(Some(v)) => v,
+        Ok(None) => {
+            println!("  (Federation master not running — set PAGI_FEDERATION_LISTEN on the gateway)");
+            println!();
+            return;
+        }
+        Err(e) => {
+            println!("  (Unavailable: {})", e);
+            println!();
+            return;
+        }
+    };
+    let now_ms = Utc::now().timestamp_millis();
+    let nodes = nodes["nodes"].as_array().cloned().unwrap_or_default();
+
+    if nodes.is_empty() {
+        println!("  Satellites: (none registered)");
+    } else {
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("Node").add_attribute(Attribute::Bold),
+                Cell::new("Role").add_attribute(Attribute::Bold),
+                Cell::new("Capabilities").add_attribute(Attribute::Bold),
+                Cell::new("Hardware").add_attribute(Attribute::Bold),
+                Cell::new("Load")
+                    .set_alignment(CellAlignment::Right)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Heartbeat")
+                    .set_alignment(CellAlignment::Right)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Liveness")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+            ]);
+
+        for n in &nodes {
+            let caps: Vec<&str> = n["capabilities"]
+                .as_array()
+                .map(|a| a.iter().filter_map(|c| c.as_str()).collect())
+                .unwrap_or_default();
+            let hw = &n["hardware"];
+            let hardware = if hw.is_null() {
+                "—".to_string()
+            } else {
+                format!(
+                    "{} cores, {} MB\n{}/{}",
+                    hw["cpu_cores"], hw["ram_mb"],
+                    hw["os"].as_str().unwrap_or("?"),
+                    hw["arch"].as_str().unwrap_or("?")
+                )
+            };
+            let hb = &n["last_heartbeat"];
+            let in_flight = n["in_flight"].as_u64().unwrap_or(0);
+            let (load, heartbeat) = if hb.is_null() {
+                (format!("{} task(s)", in_flight), "—".to_string())
+            } else {
+                (
+                    format!(
+                        "CPU {:.0}%  RAM {} MB\n{} task(s)",
+                        hb["cpu_percent"].as_f64().unwrap_or(0.0),
+                        hb["ram_used_mb"],
+                        in_flight
+                    ),
+                    age(now_ms - hb["at_ms"].as_i64().unwrap_or(now_ms)),
+                )
+            };
+            let health = n["health"].as_str().unwrap_or("unknown");
+            let connected = n["connected"].as_bool().unwrap_or(false);
+            let (liveness, color) = match (health, connected) {
+                ("healthy", true) => ("● HEALTHY", Color::Green),
+                ("healthy", false) => ("○ NO STREAM", Color::DarkYellow),
+                ("suspect", _) => ("⚠ SUSPECT", Color::Yellow),
+                ("dead", _) => ("✗ DEAD", Color::Red),
+                _ => ("? UNKNOWN", Color::DarkYellow),
+            };
+
+            table.add_row(vec![
+                Cell::new(n["node_id"].as_str().unwrap_or("?")).add_attribute(Attribute::Bold),
+                Cell::new(n["role"].as_str().unwrap_or("—")),
+                Cell::new(if caps.is_empty() { "—".to_string() } else { caps.join(", ") }),
+                Cell::new(hardware),
+                Cell::new(load).set_alignment(CellAlignment::Right),
+                Cell::new(heartbeat).set_alignment(CellAlignment::Right),
+                Cell::new(liveness).set_alignment(CellAlignment::Center).fg(color),
+            ]);
+        }
+        println!("{table}");
+
+        let healthy = nodes.iter().filter(|n| n["health"] == "healthy").count();
+        let in_flight: u64 = nodes.iter().filter_map(|n| n["in_flight"].as_u64()).sum();
+        println!(
+            "  Satellites: {}  |  Healthy: {}/{}  |  Tasks in flight: {}",
+            nodes.len(), healthy, nodes.len(), in_flight
+        );
+    }
+    println!();
+
+    let tasks = match fetch_federation_json(port, "/api/v1/federation/tasks?limit=10") {
+        Ok(Some(v)) => v["tasks"].as_array().cloned().unwrap_or_default(),
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            println!("  Tasks: (unavailable: {})", e);
+            println!();
+            return;
+        }
+    };
+    if tasks.is_empty() {
+        println!("  Federated Tasks: (none queued or recorded)");
+    } else {
+        println!("  Federated Tasks (latest {}):", tasks.len());
+        println!();
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .apply_modifier(UTF8_ROUND_CORNERS)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("Task").add_attribute(Attribute::Bold),
+                Cell::new("Goal").add_attribute(Attribute::Bold),
+                Cell::new("Node").add_attribute(Attribute::Bold),
+                Cell::new("State")
+                    .set_alignment(CellAlignment::Center)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Attempts")
+                    .set_alignment(CellAlignment::Right)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Duration")
+                    .set_alignment(CellAlignment::Right)
+                    .add_attribute(Attribute::Bold),
+                Cell::new("Outcome").add_attribute(Attribute::Bold),
+            ]);
+
+        for t in &tasks {
+            let task_id = t["task_id"].as_str().unwrap_or("?");
+            let short_id: String = task_id.chars().take(8).collect();
+            let state = t["state"].as_str().unwrap_or("?");
+            let duration = match t["duration_ms"].as_i64() {
+                Some(ms) => duration(ms),
+                None => format!(
+                    "{} (running)",
+                    duration(now_ms - t["created_at_ms"].as_i64().unwrap_or(now_ms))
+                ),
+            };
+            let (outcome, color) = match (state, t["success"].as_bool()) {
+                ("completed", Some(true)) => (t["summary"].as_str().unwrap_or("✓ ok").to_string(), Color::Green),
+                ("completed", _) => (t["summary"].as_str().unwrap_or("✗ failed").to_string(), Color::Red),
+                ("failed", _) => (t["error"].as_str().unwrap_or("✗ failed").to_string(), Color::Red),
+                _ => ("…".to_string(), Color::DarkYellow),
+            };
+            let outcome: String = if outcome.chars().count() > 60 {
+                format!("{}…", outcome.chars().take(59).collect::<String>())
+            } else {
+                outcome
+            };
+
+            table.add_row(vec![
+                Cell::new(short_id),
+                Cell::new(t["goal"].as_str().unwrap_or("—")),
+                Cell::new(t["node_id"].as_str().unwrap_or("—")),
+                Cell::new(state).set_alignment(CellAlignment::Center),
+                Cell::new(t["attempts"].as_u64().unwrap_or(0)).set_alignment(CellAlignment::Right),
+                Cell::new(duration).set_alignment(CellAlignment::Right),
+                Cell::new(outcome).fg(color),
+            ]);
+        }
+        println!("{table}");
+    }
+    println!();
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Helpers
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// `850ms`, `12.3s`, `4m 05s`, `2h 10m`.
+fn duration(ms: i64) -> String {
+    let ms = ms.max(0);
+    match ms {
+        0..=999 => format!("{}ms", ms),
+        1_000..=59_999 => format!("{:.1}s", ms as f64 / 1000.0),
+        60_000..=3_599_999 => format!("{}m {:02}s", ms / 60_000, (ms / 1000) % 60),
+        _ => format!("{}h {:02}m", ms / 3_600_000, (ms / 60_000) % 60),
+    }
+}
+
+/// Time since an event, e.g. `12s ago`.
+fn age(ms: i64) -> String {
+    let secs = ms.max(0) / 1000;
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86_399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
 /// Renders a small ASCII trust bar: `[████░░░░░░]` for a 0.0–1.0 score.
 fn trust_bar_ascii(score: f32) -> String {
     let filled = (score * 10.0).round() as usize;
@@ -108,11 +108,22 @@ const WardenSidebar: React.FC<WardenSidebarProps> = ({
     if (!window.confirm(`Promote "${skillId}" to Core? This moves the skill from Ephemeral to Core (human-in-the-loop).`)) {
       return;
     }
+    const signed = window.prompt(`Paste the output of: pagi-gateway --sign-skill ${skillId} --detached`);
+    if (!signed) {
+      return;
+    }
+    let signature: unknown;
+    try {
+      signature = JSON.parse(signed).signature;
+    } catch {
+      window.alert('Signature is not valid JSON.');
+      return;
+    }
     try {
       const res = await fetch(`${API_BASE_URL}/skills/promote`, {
         method: 'POST',
         headers: { 'Content-Type': 'application/json' },
-        body: JSON.stringify({ skill_id: skillId, confirmed: true }),
+        body: JSON.stringify({ skill_id: skillId, confirmed: true, signature }),
       });
       const data = await res.json().catch(() => ({}));
       if (res.ok && data.status === 'ok') {
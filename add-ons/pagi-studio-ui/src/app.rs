@@ -63,6 +63,8 @@ pub fn build_studio_stack(
         tenant_id: "pagi-studio-ui".to_string(),
         correlation_id: None,
         agent_id: None,
+        session_id: None,
+        capabilities: None,
     };
 
     Ok((
@@ -71,6 +71,18 @@ pub use orchestrator::{
     HeuristicProcessor, HeuristicResult, MoEMode, MoEExpert, Orchestrator, OrchestratorMode,
     Plan, PersonaCoordinator, PersonaCoordinatorState, route_to_experts, SignProfile, SkillRegistry,
     SkillInventoryEntry, SkillManifestEntry, SkillManifestRegistry, SovereigntyViolation, TierManifest, TrustTier, validate_skill_permissions,
+    // Capability-based skill permissions (network, fs, shell, env, LLM spend)
+    CapabilityHandle, CapabilityRequest, CapabilityViolation, FsAccess, FsGrant, SkillCapabilities, validate_skill_capability,
+    // Energy budgets (per tenant / session, per day)
+    BudgetEnforcement, BudgetExceeded, BudgetLimits, BudgetPolicy, BudgetReport, EnergyDebit, EnergyLedger, EnergyUsage,
+    ScopeBudget, BUDGET_KEY_PREFIX, DEFAULT_BUDGET_SOFT_RATIO, run_live_skill,
+    // Semantic MoE gating (embedding centroids + keyword fallback)
+    HashingEmbedder, RoutingCorrection, RoutingDecision, RoutingExample, RoutingMethod, SemanticRouter, TextEmbedder,
+    export_training_set, get_decision, load_routing_examples, recent_routing_decisions, record_routing_correction,
+    record_routing_decision, store_routing_example, DEFAULT_ROUTER_THRESHOLD, MOE_DECISION_PREFIX, MOE_EXAMPLE_PREFIX,
+    // Signed Tier 1 manifests (Ed25519)
+    PromotionError, SignatureError, SkillSignature, SkillSigner, TrustedSkillKeys, sha256_file, signing_payload, verify_artifact,
+    DEFAULT_SKILL_SIGNING_KEY_PATH, SKILL_SIGNING_KEY_PATH_ENV, SKILL_TRUSTED_KEYS_ENV,
     SovereignDomain, UserArchetype, zodiac_behavioral_hint, humanity_blend_label,
     get_effective_archetype_for_turn, query_domain, QueryDomain, suggest_archetype_from_query,
     archetype_auto_switch_disabled, ArchetypeOverlay, ArchetypePrompt,
@@ -89,7 +101,10 @@ pub use orchestrator::{
     // Phase 4.5: Validation Benchmarks
     PerformanceDelta, ValidationResult, SmokeTestRunner,
     // Evolutionary Versioning & Genetic Memory
-    compute_patch_dna, check_genetic_dead_end, record_genetic_dead_end,
+    compute_patch_dna, check_genetic_dead_end, record_genetic_dead_end, record_genetic_dead_end_with_payload,
+    // Property-based smoke tests from declared skill parameters
+    CaseKind, MemoryProbe, ParamKind, ParamSchema, PayloadGenerator, PropertyCase, PropertyConfig,
+    PropertyFailure, PropertyHarness, PropertyReport, PropertyViolation, SkillProbe,
     // Astro-Weather (Transit vs KB-01, SYSTEM_PROMPT + KB-08 correlation)
     check_astro_weather, record_transit_correlation_if_high_risk, system_alert_if_high_risk,
     system_prompt_block, should_refresh, AstroWeatherState, TransitRiskLevel, STALE_MS,
@@ -100,6 +115,9 @@ pub use orchestrator::{
     calculate_thinking_latency,
     // Sovereign Voice + Tone Firewall
     detect_tone_drift,
+    // Dispatch deadlines, cancellation and concurrency caps
+    CancellationToken, ExecutionLimits, SideEffectClass, SkillDispatchError,
+    DEFAULT_GOAL_TIMEOUT_SECS, DEFAULT_SKILL_TIMEOUT_SECS,
 };
 
 // OpenRouter Sovereign Bridge (high-level reasoning only; actions and memory stay local)
@@ -104,6 +104,11 @@ impl OpenRouterBridge {
         self
     }
 
+    /// Model used for requests.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
     /// Request a high-level plan for the given intent. **Callers should query local KBs first**
     /// and pass the result as `context` so the Bridge reasons over grounded data and saves tokens.
     pub async fn plan(
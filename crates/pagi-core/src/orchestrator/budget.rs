@@ -0,0 +1,553 @@
+//! Energy budgets: daily per-tenant and per-session allowances for LLM tokens, wall time and
+//! external API calls.
+//!
+//! `Orchestrator::run_skill` checks the [`EnergyLedger`] before every skill call and debits it
+//! afterwards (tokens from the result's `token_usage` / `energy_used`, else the skill's
+//! [`EnergyCost`] estimate). Live skills called outside `dispatch` go through [`run_live_skill`];
+//! other callers that reach an LLM outside `dispatch` (e.g. gateway streaming) debit the ledger
+//! themselves. Usage is keyed by UTC day and persisted to KB-08 (Soma) under `budget/{date}/…`,
+//! so it survives restarts and resets at midnight UTC. In-memory entries for past days are
+//! dropped when the date changes.
+//!
+//! Crossing `soft_ratio` of any limit logs a warning once per scope and day; reaching a limit
+//! rejects further calls with [`BudgetExceeded`] when enforcement is `hard`.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::knowledge::{KbType, KnowledgeStore};
+use crate::shared::TenantContext;
+use crate::skills::{EnergyCost, LiveSkill};
+
+use super::execution::SideEffectClass;
+
+/// KB-08 key prefix for persisted daily usage.
+pub const BUDGET_KEY_PREFIX: &str = "budget/";
+/// Default fraction of a limit at which a soft warning is logged (PAGI_BUDGET_SOFT_RATIO).
+pub const DEFAULT_BUDGET_SOFT_RATIO: f32 = 0.8;
+
+impl EnergyCost {
+    /// Token estimate used when a skill does not report actual usage.
+    pub fn estimated_tokens(&self) -> u64 {
+        match self {
+            EnergyCost::Minimal => 50,
+            EnergyCost::Low => 200,
+            EnergyCost::Medium => 1000,
+            EnergyCost::High => 3000,
+            EnergyCost::VeryHigh => 6000,
+        }
+    }
+}
+
+/// What one call consumed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnergyDebit {
+    pub tokens: u64,
+    pub wall_time_ms: u64,
+    pub api_calls: u64,
+}
+
+/// Accumulated usage for one scope and day.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnergyUsage {
+    pub tokens: u64,
+    pub wall_time_ms: u64,
+    pub api_calls: u64,
+    /// Number of debits (skill or LLM calls).
+    pub calls: u64,
+}
+
+impl EnergyUsage {
+    fn add(&mut self, debit: &EnergyDebit) {
+        self.tokens = self.tokens.saturating_add(debit.tokens);
+        self.wall_time_ms = self.wall_time_ms.saturating_add(debit.wall_time_ms);
+        self.api_calls = self.api_calls.saturating_add(debit.api_calls);
+        self.calls = self.calls.saturating_add(1);
+    }
+}
+
+/// Daily limits for one scope. None = unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetLimits {
+    pub tokens: Option<u64>,
+    pub wall_time_ms: Option<u64>,
+    pub api_calls: Option<u64>,
+}
+
+impl BudgetLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.tokens.is_none() && self.wall_time_ms.is_none() && self.api_calls.is_none()
+    }
+
+    fn dimensions(&self, usage: &EnergyUsage) -> [(&'static str, u64, Option<u64>); 3] {
+        [
+            ("tokens", usage.tokens, self.tokens),
+            ("wall_time_ms", usage.wall_time_ms, self.wall_time_ms),
+            ("api_calls", usage.api_calls, self.api_calls),
+        ]
+    }
+
+    /// First dimension whose limit is reached: (dimension, used, limit).
+    fn exhausted(&self, usage: &EnergyUsage) -> Option<(&'static str, u64, u64)> {
+        self.dimensions(usage)
+            .into_iter()
+            .find_map(|(dim, used, limit)| limit.filter(|l| used >= *l).map(|l| (dim, used, l)))
+    }
+
+    /// Highest used/limit ratio across limited dimensions (0 when unlimited).
+    fn max_ratio(&self, usage: &EnergyUsage) -> f32 {
+        self.dimensions(usage)
+            .into_iter()
+            .filter_map(|(_, used, limit)| limit.map(|l| if l == 0 { 1.0 } else { used as f32 / l as f32 }))
+            .fold(0.0, f32::max)
+    }
+
+    fn remaining(&self, usage: &EnergyUsage) -> BudgetLimits {
+        BudgetLimits {
+            tokens: self.tokens.map(|l| l.saturating_sub(usage.tokens)),
+            wall_time_ms: self.wall_time_ms.map(|l| l.saturating_sub(usage.wall_time_ms)),
+            api_calls: self.api_calls.map(|l| l.saturating_sub(usage.api_calls)),
+        }
+    }
+}
+
+/// What happens when a limit is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetEnforcement {
+    /// Track usage only.
+    Off,
+    /// Track and warn, never reject.
+    Soft,
+    /// Warn at the soft ratio and reject at the limit.
+    #[default]
+    Hard,
+}
+
+/// Budget configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BudgetPolicy {
+    pub tenant: BudgetLimits,
+    pub session: BudgetLimits,
+    pub soft_ratio: f32,
+    pub enforcement: BudgetEnforcement,
+}
+
+impl Default for BudgetPolicy {
+    fn default() -> Self {
+        Self {
+            tenant: BudgetLimits::default(),
+            session: BudgetLimits::default(),
+            soft_ratio: DEFAULT_BUDGET_SOFT_RATIO,
+            enforcement: BudgetEnforcement::Hard,
+        }
+    }
+}
+
+impl BudgetPolicy {
+    /// From env: `PAGI_BUDGET_{TENANT,SESSION}_{TOKENS,WALL_SECS,API_CALLS}` (unset or 0 = unlimited),
+    /// `PAGI_BUDGET_SOFT_RATIO` (default 0.8) and `PAGI_BUDGET_ENFORCEMENT` (hard | soft | off).
+    pub fn from_env() -> Self {
+        fn limit(name: &str) -> Option<u64> {
+            std::env::var(name).ok().and_then(|s| s.trim().parse::<u64>().ok()).filter(|n| *n > 0)
+        }
+        fn scope(prefix: &str) -> BudgetLimits {
+            BudgetLimits {
+                tokens: limit(&format!("{}_TOKENS", prefix)),
+                wall_time_ms: limit(&format!("{}_WALL_SECS", prefix)).map(|s| s.saturating_mul(1000)),
+                api_calls: limit(&format!("{}_API_CALLS", prefix)),
+            }
+        }
+        let soft_ratio = std::env::var("PAGI_BUDGET_SOFT_RATIO")
+            .ok()
+            .and_then(|s| s.trim().parse::<f32>().ok())
+            .map(|r| r.clamp(0.0, 1.0))
+            .unwrap_or(DEFAULT_BUDGET_SOFT_RATIO);
+        let enforcement = match std::env::var("PAGI_BUDGET_ENFORCEMENT")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "off" => BudgetEnforcement::Off,
+            "soft" => BudgetEnforcement::Soft,
+            _ => BudgetEnforcement::Hard,
+        };
+        Self {
+            tenant: scope("PAGI_BUDGET_TENANT"),
+            session: scope("PAGI_BUDGET_SESSION"),
+            soft_ratio,
+            enforcement,
+        }
+    }
+}
+
+/// Hard stop: a tenant or session has used up a daily limit.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("energy budget exhausted for {scope} '{id}': {dimension} {used}/{limit} today")]
+pub struct BudgetExceeded {
+    /// "tenant" | "session"
+    pub scope: &'static str,
+    pub id: String,
+    pub dimension: &'static str,
+    pub used: u64,
+    pub limit: u64,
+}
+
+/// Usage, limits and remaining allowance for one scope (GET /api/v1/budget).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopeBudget {
+    pub id: String,
+    pub usage: EnergyUsage,
+    pub limits: BudgetLimits,
+    pub remaining: BudgetLimits,
+    pub soft_limit_reached: bool,
+    pub exhausted: bool,
+}
+
+/// Budget report for a tenant and optionally one of its sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetReport {
+    pub date: String,
+    pub enforcement: BudgetEnforcement,
+    pub soft_ratio: f32,
+    pub tenant: ScopeBudget,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<ScopeBudget>,
+}
+
+/// Daily energy ledger, optionally persisted to KB-08.
+pub struct EnergyLedger {
+    policy: BudgetPolicy,
+    usage: DashMap<String, EnergyUsage>,
+    /// Scopes already warned about today (soft limit).
+    warned: DashMap<String, ()>,
+    /// UTC day `usage` and `warned` currently hold entries for.
+    day: Mutex<String>,
+    store: Option<Arc<KnowledgeStore>>,
+}
+
+impl EnergyLedger {
+    /// In-memory ledger (usage is lost on restart).
+    pub fn new(policy: BudgetPolicy) -> Self {
+        Self {
+            policy,
+            usage: DashMap::new(),
+            warned: DashMap::new(),
+            day: Mutex::new(Self::today()),
+            store: None,
+        }
+    }
+
+    /// Persist daily usage to KB-08 and resume today's totals from it.
+    pub fn with_store(mut self, store: Arc<KnowledgeStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    pub fn policy(&self) -> &BudgetPolicy {
+        &self.policy
+    }
+
+    fn today() -> String {
+        Utc::now().format("%Y-%m-%d").to_string()
+    }
+
+    /// Returns today's date, first dropping in-memory usage and warnings of earlier days (their
+    /// totals stay in KB-08).
+    fn current_day(&self) -> String {
+        let date = Self::today();
+        self.roll_over(&date);
+        date
+    }
+
+    fn roll_over(&self, date: &str) {
+        let mut day = self.day.lock().unwrap_or_else(|e| e.into_inner());
+        if *day == date {
+            return;
+        }
+        let prefix = format!("{}{}/", BUDGET_KEY_PREFIX, date);
+        self.usage.retain(|key, _| key.starts_with(&prefix));
+        self.warned.retain(|key, _| key.starts_with(&prefix));
+        *day = date.to_string();
+    }
+
+    fn tenant_key(date: &str, tenant: &str) -> String {
+        format!("{}{}/tenant/{}", BUDGET_KEY_PREFIX, date, tenant)
+    }
+
+    fn session_key(date: &str, tenant: &str, session: &str) -> String {
+        format!("{}{}/session/{}/{}", BUDGET_KEY_PREFIX, date, tenant, session)
+    }
+
+    fn load(&self, key: &str) -> EnergyUsage {
+        if let Some(u) = self.usage.get(key) {
+            return *u;
+        }
+        let persisted = self
+            .store
+            .as_ref()
+            .and_then(|s| s.get(KbType::Soma.slot_id(), key).ok().flatten())
+            .and_then(|b| serde_json::from_slice::<EnergyUsage>(&b).ok())
+            .unwrap_or_default();
+        *self.usage.entry(key.to_string()).or_insert(persisted)
+    }
+
+    /// Rejects the call when enforcement is hard and the tenant or session is out of allowance.
+    pub fn check(&self, ctx: &TenantContext) -> Result<(), BudgetExceeded> {
+        if self.policy.enforcement != BudgetEnforcement::Hard {
+            return Ok(());
+        }
+        let date = self.current_day();
+        let tenant = ctx.tenant_id.as_str();
+        let session = ctx.resolved_session_id();
+        let scopes = [
+            ("tenant", tenant.to_string(), Self::tenant_key(&date, tenant), &self.policy.tenant),
+            ("session", session.to_string(), Self::session_key(&date, tenant, session), &self.policy.session),
+        ];
+        for (scope, id, key, limits) in scopes {
+            if limits.is_unlimited() {
+                continue;
+            }
+            if let Some((dimension, used, limit)) = limits.exhausted(&self.load(&key)) {
+                return Err(BudgetExceeded { scope, id, dimension, used, limit });
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a debit to the tenant and session totals for today and persists them.
+    pub fn debit(&self, ctx: &TenantContext, debit: EnergyDebit) {
+        let date = self.current_day();
+        let tenant = ctx.tenant_id.as_str();
+        let session = ctx.resolved_session_id();
+        let scopes = [
+            ("tenant", tenant, Self::tenant_key(&date, tenant), &self.policy.tenant),
+            ("session", session, Self::session_key(&date, tenant, session), &self.policy.session),
+        ];
+        for (scope, id, key, limits) in scopes {
+            self.load(&key);
+            let usage = {
+                let mut entry = self.usage.entry(key.clone()).or_default();
+                entry.add(&debit);
+                *entry
+            };
+            if let Some(ref store) = self.store {
+                let _ = store.insert(KbType::Soma.slot_id(), &key, &serde_json::to_vec(&usage).unwrap_or_default());
+            }
+            if self.policy.enforcement != BudgetEnforcement::Off
+                && limits.max_ratio(&usage) >= self.policy.soft_ratio
+                && self.warned.insert(key.clone(), ()).is_none()
+            {
+                tracing::warn!(
+                    target: "pagi::budget",
+                    scope,
+                    id,
+                    tokens = usage.tokens,
+                    wall_time_ms = usage.wall_time_ms,
+                    api_calls = usage.api_calls,
+                    "Energy budget at {:.0}% of today's allowance",
+                    limits.max_ratio(&usage) * 100.0
+                );
+            }
+        }
+    }
+
+    fn scope_report(&self, id: &str, key: &str, limits: &BudgetLimits) -> ScopeBudget {
+        let usage = self.load(key);
+        ScopeBudget {
+            id: id.to_string(),
+            usage,
+            limits: *limits,
+            remaining: limits.remaining(&usage),
+            soft_limit_reached: limits.max_ratio(&usage) >= self.policy.soft_ratio,
+            exhausted: limits.exhausted(&usage).is_some(),
+        }
+    }
+
+    /// Today's usage and remaining allowance for a tenant (and session, if given).
+    pub fn report(&self, tenant: &str, session: Option<&str>) -> BudgetReport {
+        let date = self.current_day();
+        BudgetReport {
+            tenant: self.scope_report(tenant, &Self::tenant_key(&date, tenant), &self.policy.tenant),
+            session: session.map(|s| self.scope_report(s, &Self::session_key(&date, tenant, s), &self.policy.session)),
+            date,
+            enforcement: self.policy.enforcement,
+            soft_ratio: self.policy.soft_ratio,
+        }
+    }
+}
+
+/// Runs a live skill outside `Orchestrator::dispatch` against the ledger (when there is one):
+/// rejects the call when the tenant or session is out of allowance, then debits the reported
+/// tokens (else the skill's [`EnergyCost`] estimate) and wall time.
+pub async fn run_live_skill(
+    ledger: Option<&EnergyLedger>,
+    skill: &dyn LiveSkill,
+    ctx: &TenantContext,
+    knowledge: &KnowledgeStore,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(ledger) = ledger else {
+        return skill.execute(ctx, knowledge, params).await;
+    };
+    ledger.check(ctx)?;
+    let started = std::time::Instant::now();
+    let result = skill.execute(ctx, knowledge, params).await;
+    let tokens = match result {
+        Ok(ref value) => reported_tokens(value).unwrap_or_else(|| skill.energy_cost().estimated_tokens()),
+        Err(_) => 0,
+    };
+    let class = SideEffectClass::for_skill_name(skill.name());
+    ledger.debit(
+        ctx,
+        EnergyDebit {
+            tokens,
+            wall_time_ms: started.elapsed().as_millis() as u64,
+            api_calls: matches!(class, SideEffectClass::Network | SideEffectClass::Llm) as u64,
+        },
+    );
+    result
+}
+
+/// Tokens reported by a skill result: `token_usage.total_tokens` (ModelRouter) or `energy_used`.
+pub(crate) fn reported_tokens(result: &serde_json::Value) -> Option<u64> {
+    result
+        .get("token_usage")
+        .and_then(|u| u.get("total_tokens"))
+        .and_then(|v| v.as_u64())
+        .or_else(|| result.get("energy_used").and_then(|v| v.as_u64()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(tenant: &str, session: &str) -> TenantContext {
+        TenantContext {
+            tenant_id: tenant.to_string(),
+            correlation_id: None,
+            agent_id: None,
+            session_id: Some(session.to_string()),
+            capabilities: None,
+        }
+    }
+
+    fn policy() -> BudgetPolicy {
+        BudgetPolicy {
+            tenant: BudgetLimits { tokens: Some(1000), wall_time_ms: None, api_calls: Some(10) },
+            session: BudgetLimits { tokens: Some(500), wall_time_ms: None, api_calls: None },
+            ..BudgetPolicy::default()
+        }
+    }
+
+    #[test]
+    fn session_limit_stops_before_tenant_limit() {
+        let ledger = EnergyLedger::new(policy());
+        let a = ctx("t", "s1");
+        assert!(ledger.check(&a).is_ok());
+        ledger.debit(&a, EnergyDebit { tokens: 500, wall_time_ms: 10, api_calls: 1 });
+        let err = ledger.check(&a).unwrap_err();
+        assert_eq!((err.scope, err.dimension, err.limit), ("session", "tokens", 500));
+
+        // Another session of the same tenant still has allowance until the tenant total runs out.
+        let b = ctx("t", "s2");
+        assert!(ledger.check(&b).is_ok());
+        ledger.debit(&b, EnergyDebit { tokens: 499, wall_time_ms: 10, api_calls: 1 });
+        ledger.debit(&b, EnergyDebit { tokens: 1, wall_time_ms: 10, api_calls: 1 });
+        assert_eq!(ledger.check(&b).unwrap_err().scope, "tenant");
+        let report = ledger.report("t", Some("s2"));
+        assert!(report.tenant.exhausted);
+        assert_eq!(report.tenant.remaining.api_calls, Some(7));
+    }
+
+    #[test]
+    fn soft_enforcement_never_rejects() {
+        let ledger = EnergyLedger::new(BudgetPolicy { enforcement: BudgetEnforcement::Soft, ..policy() });
+        let a = ctx("t", "s");
+        ledger.debit(&a, EnergyDebit { tokens: 10_000, wall_time_ms: 0, api_calls: 0 });
+        assert!(ledger.check(&a).is_ok());
+        assert!(ledger.report("t", None).tenant.soft_limit_reached);
+    }
+
+    #[test]
+    fn usage_is_persisted_per_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(dir.path()).unwrap());
+        let a = ctx("t", "s");
+        EnergyLedger::new(policy())
+            .with_store(Arc::clone(&store))
+            .debit(&a, EnergyDebit { tokens: 600, wall_time_ms: 5, api_calls: 2 });
+
+        let reloaded = EnergyLedger::new(policy()).with_store(store);
+        let report = reloaded.report("t", Some("s"));
+        assert_eq!(report.tenant.usage.tokens, 600);
+        assert_eq!(report.session.unwrap().usage.api_calls, 2);
+        assert!(reloaded.check(&a).is_err());
+    }
+
+    #[test]
+    fn past_days_are_dropped_from_memory() {
+        let ledger = EnergyLedger::new(policy());
+        let a = ctx("t", "s");
+        ledger.debit(&a, EnergyDebit { tokens: 900, wall_time_ms: 0, api_calls: 0 });
+        assert_eq!(ledger.usage.len(), 2);
+        assert_eq!(ledger.warned.len(), 2);
+
+        ledger.roll_over("2999-01-01");
+        assert!(ledger.usage.is_empty());
+        assert!(ledger.warned.is_empty());
+    }
+
+    struct Echo;
+
+    #[async_trait::async_trait]
+    impl LiveSkill for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "echo"
+        }
+
+        async fn execute(
+            &self,
+            _ctx: &TenantContext,
+            _knowledge: &KnowledgeStore,
+            params: serde_json::Value,
+        ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(params)
+        }
+    }
+
+    #[tokio::test]
+    async fn live_skills_are_checked_and_debited() {
+        let dir = tempfile::tempdir().unwrap();
+        let knowledge = KnowledgeStore::open_path(dir.path()).unwrap();
+        let ledger = EnergyLedger::new(policy());
+        let a = ctx("t", "s");
+        let out = run_live_skill(Some(&ledger), &Echo, &a, &knowledge, serde_json::json!({ "energy_used": 450 }))
+            .await
+            .unwrap();
+        assert_eq!(out["energy_used"], 450);
+        assert_eq!(ledger.report("t", Some("s")).session.unwrap().usage.tokens, 450);
+
+        ledger.debit(&a, EnergyDebit { tokens: 50, wall_time_ms: 0, api_calls: 0 });
+        let err = run_live_skill(Some(&ledger), &Echo, &a, &knowledge, serde_json::json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<BudgetExceeded>().is_some());
+    }
+
+    #[test]
+    fn reported_tokens_prefers_token_usage() {
+        let v = serde_json::json!({ "token_usage": { "total_tokens": 42 }, "energy_used": 7 });
+        assert_eq!(reported_tokens(&v), Some(42));
+        assert_eq!(reported_tokens(&serde_json::json!({ "energy_used": 7 })), Some(7));
+        assert_eq!(reported_tokens(&serde_json::json!({})), None);
+    }
+}
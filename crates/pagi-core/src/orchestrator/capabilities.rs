@@ -0,0 +1,495 @@
+//! Capability-based permissions for skills (beyond KB layers).
+//!
+//! A manifest entry may declare `capabilities`: network egress hosts, filesystem roots (read or
+//! read_write), shell access, readable env vars, and an LLM token allowance. Tier 1 (core) skills
+//! are trusted with everything; Tier 2/3 skills only get what they declare.
+//!
+//! The Orchestrator (and every direct live-skill call site, via
+//! `SkillManifestRegistry::scoped_context`) attaches a [`CapabilityHandle`] to
+//! `TenantContext::capabilities`; skills missing from the manifests are refused. Skills go through
+//! the handle (or the `TenantContext::http_request` / `env_var` helpers) for HTTP, filesystem,
+//! process and env access, so undeclared side effects fail with [`CapabilityViolation`].
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::execution::SideEffectClass;
+use super::skills::TrustTier;
+
+/// Filesystem access level for a declared root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsAccess {
+    #[default]
+    Read,
+    ReadWrite,
+}
+
+/// One filesystem root a skill may touch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FsGrant {
+    /// Directory root (absolute, or relative to the gateway working directory).
+    pub root: String,
+    #[serde(default)]
+    pub access: FsAccess,
+}
+
+/// Capabilities declared in a manifest entry. Everything defaults to denied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillCapabilities {
+    /// Hosts the skill may reach: exact ("api.tavily.com"), subdomain wildcard ("*.openrouter.ai") or "*".
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub network_egress: Vec<String>,
+    /// Filesystem roots with read or read_write access.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filesystem: Vec<FsGrant>,
+    /// May spawn processes / shell commands.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shell: bool,
+    /// Environment variables the skill may read (and that are passed to spawned processes).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_vars: Vec<String>,
+    /// Maximum LLM tokens per skill call. None = no LLM access; Some(0) is the same as None.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_max_tokens: Option<u64>,
+}
+
+impl SkillCapabilities {
+    /// Whether the declared capabilities cover a side-effect class (checked at dispatch).
+    pub fn covers_class(&self, class: SideEffectClass) -> bool {
+        match class {
+            SideEffectClass::ReadOnly => true,
+            SideEffectClass::Network => !self.network_egress.is_empty(),
+            SideEffectClass::Llm => self.llm_max_tokens.unwrap_or(0) > 0,
+            SideEffectClass::FileWrite => self
+                .filesystem
+                .iter()
+                .any(|g| g.access == FsAccess::ReadWrite),
+            SideEffectClass::Shell => self.shell,
+        }
+    }
+
+    fn allows_host(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_lowercase();
+        self.network_egress.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            if pattern == "*" {
+                return true;
+            }
+            match pattern.strip_prefix("*.") {
+                Some(suffix) => host.ends_with(&format!(".{}", suffix)),
+                None => host == pattern,
+            }
+        })
+    }
+
+    fn allows_path(&self, path: &Path, write: bool) -> bool {
+        self.filesystem.iter().any(|grant| {
+            if write && grant.access != FsAccess::ReadWrite {
+                return false;
+            }
+            resolve_path(Path::new(&grant.root))
+                .map(|root| path.starts_with(root))
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// A single side effect a skill wants to perform.
+#[derive(Debug, Clone)]
+pub enum CapabilityRequest<'a> {
+    NetworkEgress { host: &'a str },
+    FsRead { path: &'a Path },
+    FsWrite { path: &'a Path },
+    Shell,
+    EnvVar { name: &'a str },
+    LlmTokens { tokens: u64 },
+}
+
+impl CapabilityRequest<'_> {
+    fn describe(&self) -> String {
+        match self {
+            CapabilityRequest::NetworkEgress { host } => format!("reach host '{}'", host),
+            CapabilityRequest::FsRead { path } => format!("read '{}'", path.display()),
+            CapabilityRequest::FsWrite { path } => format!("write '{}'", path.display()),
+            CapabilityRequest::Shell => "spawn processes".to_string(),
+            CapabilityRequest::EnvVar { name } => format!("read env var '{}'", name),
+            CapabilityRequest::LlmTokens { tokens } => format!("spend {} LLM tokens", tokens),
+        }
+    }
+}
+
+/// Error returned when a skill attempts a side effect its manifest does not declare.
+/// Logged in KB-08 alongside Sovereignty Firewall violations.
+#[derive(Debug, Clone)]
+pub struct CapabilityViolation {
+    pub skill_id: String,
+    /// Human-readable description of the denied action (e.g. "spawn processes").
+    pub capability: String,
+}
+
+impl std::fmt::Display for CapabilityViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "CapabilityViolation: skill '{}' is not allowed to {} (manifest capabilities)",
+            self.skill_id, self.capability
+        )
+    }
+}
+
+impl std::error::Error for CapabilityViolation {}
+
+/// Resolves a path for capability checks: rejects `..`, makes it absolute, and canonicalizes the
+/// longest existing prefix so symlinks cannot escape a root.
+fn resolve_path(path: &Path) -> Option<PathBuf> {
+    if path.components().any(|c| matches!(c, Component::ParentDir)) {
+        return None;
+    }
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+    let mut existing = absolute.as_path();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        rest.push(existing.file_name()?.to_os_string());
+        existing = existing.parent()?;
+    }
+    let mut resolved = existing.canonicalize().ok()?;
+    for part in rest.into_iter().rev() {
+        resolved.push(part);
+    }
+    Some(resolved)
+}
+
+#[derive(Debug)]
+struct HandleInner {
+    skill_id: String,
+    tier: TrustTier,
+    capabilities: SkillCapabilities,
+    llm_tokens_used: AtomicU64,
+}
+
+/// Per-call capability handle. Cheap to clone; LLM spend is shared across clones of one call.
+#[derive(Debug, Clone)]
+pub struct CapabilityHandle {
+    inner: Arc<HandleInner>,
+}
+
+impl CapabilityHandle {
+    pub fn new(skill_id: impl Into<String>, tier: TrustTier, capabilities: SkillCapabilities) -> Self {
+        Self {
+            inner: Arc::new(HandleInner {
+                skill_id: skill_id.into(),
+                tier,
+                capabilities,
+                llm_tokens_used: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn skill_id(&self) -> &str {
+        &self.inner.skill_id
+    }
+
+    pub fn tier(&self) -> TrustTier {
+        self.inner.tier
+    }
+
+    pub fn capabilities(&self) -> &SkillCapabilities {
+        &self.inner.capabilities
+    }
+
+    /// LLM tokens charged so far through this handle.
+    pub fn llm_tokens_used(&self) -> u64 {
+        self.inner.llm_tokens_used.load(Ordering::Acquire)
+    }
+
+    fn violation(&self, req: &CapabilityRequest<'_>) -> CapabilityViolation {
+        CapabilityViolation {
+            skill_id: self.inner.skill_id.clone(),
+            capability: req.describe(),
+        }
+    }
+
+    /// Checks a single request. Core skills are always allowed.
+    pub fn check(&self, req: &CapabilityRequest<'_>) -> Result<(), CapabilityViolation> {
+        if self.inner.tier == TrustTier::Core {
+            return Ok(());
+        }
+        let caps = &self.inner.capabilities;
+        let allowed = match req {
+            CapabilityRequest::NetworkEgress { host } => caps.allows_host(host),
+            CapabilityRequest::FsRead { path } => {
+                resolve_path(path).is_some_and(|p| caps.allows_path(&p, false))
+            }
+            CapabilityRequest::FsWrite { path } => {
+                resolve_path(path).is_some_and(|p| caps.allows_path(&p, true))
+            }
+            CapabilityRequest::Shell => caps.shell,
+            CapabilityRequest::EnvVar { name } => caps.env_vars.iter().any(|v| v == name),
+            CapabilityRequest::LlmTokens { tokens } => {
+                self.llm_tokens_used().saturating_add(*tokens) <= caps.llm_max_tokens.unwrap_or(0)
+            }
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(self.violation(req))
+        }
+    }
+
+    /// Builds an HTTP request after checking the URL host against `network_egress`. The client
+    /// is built here with [`Self::redirect_policy`] so every redirect hop is checked as well.
+    pub fn http_request(
+        &self,
+        client: reqwest::ClientBuilder,
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error + Send + Sync>> {
+        let parsed = reqwest::Url::parse(url)?;
+        let host = parsed.host_str().unwrap_or_default();
+        self.check(&CapabilityRequest::NetworkEgress { host })?;
+        let client = client.redirect(self.redirect_policy()).build()?;
+        Ok(client.request(method, parsed))
+    }
+
+    /// Redirect policy that re-checks `network_egress` for each hop (at most 10).
+    pub fn redirect_policy(&self) -> reqwest::redirect::Policy {
+        let handle = self.clone();
+        reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= 10 {
+                return attempt.error("too many redirects");
+            }
+            let host = attempt.url().host_str().unwrap_or_default().to_string();
+            match handle.check(&CapabilityRequest::NetworkEgress { host: &host }) {
+                Ok(()) => attempt.follow(),
+                Err(e) => attempt.error(e),
+            }
+        })
+    }
+
+    /// Reads a file after checking it lies under a declared root.
+    pub async fn read_to_string(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        self.check(&CapabilityRequest::FsRead { path })?;
+        Ok(tokio::fs::read_to_string(path).await?)
+    }
+
+    /// Writes a file after checking it lies under a declared read_write root.
+    pub async fn write(
+        &self,
+        path: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        self.check(&CapabilityRequest::FsWrite { path })?;
+        Ok(tokio::fs::write(path, contents).await?)
+    }
+
+    /// Returns a process builder when `shell` is declared. For non-core skills the environment is
+    /// cleared down to `PATH` plus the declared `env_vars`.
+    pub fn command(
+        &self,
+        program: impl AsRef<std::ffi::OsStr>,
+    ) -> Result<tokio::process::Command, CapabilityViolation> {
+        self.check(&CapabilityRequest::Shell)?;
+        let mut cmd = tokio::process::Command::new(program);
+        if self.inner.tier != TrustTier::Core {
+            cmd.env_clear();
+            if let Ok(path) = std::env::var("PATH") {
+                cmd.env("PATH", path);
+            }
+            for name in &self.inner.capabilities.env_vars {
+                if let Ok(value) = std::env::var(name) {
+                    cmd.env(name, value);
+                }
+            }
+        }
+        Ok(cmd)
+    }
+
+    /// Reads an environment variable declared in `env_vars`.
+    pub fn env_var(&self, name: &str) -> Result<Option<String>, CapabilityViolation> {
+        self.check(&CapabilityRequest::EnvVar { name })?;
+        Ok(std::env::var(name).ok())
+    }
+
+    /// Reserves LLM tokens against `llm_max_tokens` before a completion call.
+    pub fn charge_llm_tokens(&self, tokens: u64) -> Result<(), CapabilityViolation> {
+        let req = CapabilityRequest::LlmTokens { tokens };
+        self.check(&req)?;
+        self.inner.llm_tokens_used.fetch_add(tokens, Ordering::AcqRel);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(caps: SkillCapabilities) -> CapabilityHandle {
+        CapabilityHandle::new("TestSkill", TrustTier::Import, caps)
+    }
+
+    #[test]
+    fn deserializes_manifest_capabilities() {
+        let caps: SkillCapabilities = serde_json::from_value(serde_json::json!({
+            "network_egress": ["api.tavily.com", "*.openrouter.ai"],
+            "filesystem": [{ "root": "data/sandbox", "access": "read_write" }],
+            "env_vars": ["TAVILY_API_KEY"],
+            "llm_max_tokens": 2000
+        }))
+        .unwrap();
+        assert!(!caps.shell);
+        assert_eq!(caps.filesystem[0].access, FsAccess::ReadWrite);
+        assert!(caps.covers_class(SideEffectClass::Network));
+        assert!(!caps.covers_class(SideEffectClass::Shell));
+    }
+
+    #[test]
+    fn network_hosts_are_matched() {
+        let h = handle(SkillCapabilities {
+            network_egress: vec!["api.tavily.com".into(), "*.openrouter.ai".into()],
+            ..Default::default()
+        });
+        assert!(h.check(&CapabilityRequest::NetworkEgress { host: "api.tavily.com" }).is_ok());
+        assert!(h.check(&CapabilityRequest::NetworkEgress { host: "eu.openrouter.ai" }).is_ok());
+        assert!(h.check(&CapabilityRequest::NetworkEgress { host: "evil.com" }).is_err());
+        assert!(h
+            .http_request(reqwest::Client::builder(), reqwest::Method::GET, "https://evil.com/x")
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn redirects_to_undeclared_hosts_are_refused() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        // The redirect target records whether it was ever reached.
+        let internal = TcpListener::bind("127.0.0.1:0").unwrap();
+        internal.set_nonblocking(true).unwrap();
+        let internal_addr = internal.local_addr().unwrap();
+        let allowed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let allowed_port = allowed.local_addr().unwrap().port();
+        let stub = std::thread::spawn(move || {
+            let (mut conn, _) = allowed.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = conn.read(&mut buf);
+            let response = format!(
+                "HTTP/1.1 302 Found\r\nLocation: http://{}/secret\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                internal_addr
+            );
+            conn.write_all(response.as_bytes()).unwrap();
+        });
+
+        let h = handle(SkillCapabilities {
+            network_egress: vec!["localhost".into()],
+            ..Default::default()
+        });
+        let url = format!("http://localhost:{}/", allowed_port);
+        let request = h
+            .http_request(reqwest::Client::builder(), reqwest::Method::GET, &url)
+            .unwrap();
+        let err = request.send().await.unwrap_err();
+        stub.join().unwrap();
+
+        assert!(err.is_redirect(), "{err}");
+        assert!(internal.accept().is_err(), "redirect target was contacted");
+    }
+
+    #[test]
+    fn filesystem_roots_and_access_are_enforced() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("sandbox");
+        std::fs::create_dir_all(&root).unwrap();
+        let h = handle(SkillCapabilities {
+            filesystem: vec![FsGrant {
+                root: root.to_string_lossy().to_string(),
+                access: FsAccess::Read,
+            }],
+            ..Default::default()
+        });
+        let inside = root.join("notes.md");
+        assert!(h.check(&CapabilityRequest::FsRead { path: &inside }).is_ok());
+        assert!(h.check(&CapabilityRequest::FsWrite { path: &inside }).is_err());
+        let escape = root.join("..").join("secret");
+        assert!(h.check(&CapabilityRequest::FsRead { path: &escape }).is_err());
+        assert!(h
+            .check(&CapabilityRequest::FsRead { path: &dir.path().join("other") })
+            .is_err());
+    }
+
+    #[test]
+    fn shell_env_and_llm_spend() {
+        let h = handle(SkillCapabilities {
+            env_vars: vec!["HOME".into()],
+            llm_max_tokens: Some(100),
+            ..Default::default()
+        });
+        assert!(h.command("sh").is_err());
+        assert!(h.env_var("HOME").is_ok());
+        assert!(h.env_var("OPENROUTER_API_KEY").is_err());
+        assert!(h.charge_llm_tokens(60).is_ok());
+        assert!(h.charge_llm_tokens(60).is_err());
+        assert_eq!(h.llm_tokens_used(), 60);
+    }
+
+    #[test]
+    fn unlisted_skills_are_refused_and_listed_ones_are_scoped() {
+        use super::super::skills::{SkillManifestEntry, SkillManifestRegistry};
+        use crate::shared::TenantContext;
+
+        let reg = SkillManifestRegistry::new();
+        let ctx = TenantContext {
+            tenant_id: "t".to_string(),
+            correlation_id: None,
+            agent_id: None,
+            session_id: None,
+            capabilities: None,
+        };
+        assert!(reg.validate_side_effect_class("Ghost", SideEffectClass::ReadOnly).is_err());
+        assert!(reg.scoped_context("Ghost", &ctx).is_err());
+
+        let entry = |id: &str| SkillManifestEntry {
+            skill_id: id.to_string(),
+            kb_layers_allowed: Vec::new(),
+            description: None,
+            capabilities: SkillCapabilities {
+                network_egress: vec!["api.tavily.com".into()],
+                ..Default::default()
+            },
+            artifact: None,
+            artifact_sha256: None,
+            signature: None,
+        };
+        reg.register_runtime(TrustTier::Import, entry("Search"));
+        assert!(reg.validate_side_effect_class("Search", SideEffectClass::Network).is_ok());
+        assert!(reg.validate_side_effect_class("Search", SideEffectClass::Shell).is_err());
+        let scoped = reg.scoped_context("Search", &ctx).unwrap();
+        let client = reqwest::Client::builder;
+        assert!(scoped.http_request(client(), reqwest::Method::GET, "https://api.tavily.com/search").is_ok());
+        assert!(scoped.http_request(client(), reqwest::Method::GET, "https://evil.com/").is_err());
+        assert!(scoped.env_var("HOME").is_err());
+
+        // Unsigned runtime entries cannot claim the core tier.
+        reg.register_runtime(TrustTier::Core, entry("Sneaky"));
+        let scoped = reg.scoped_context("Sneaky", &ctx).unwrap();
+        assert_eq!(scoped.capabilities.unwrap().tier(), TrustTier::Import);
+    }
+
+    #[test]
+    fn core_tier_is_unrestricted() {
+        let h = CapabilityHandle::new("Core", TrustTier::Core, SkillCapabilities::default());
+        assert!(h.command("sh").is_ok());
+        assert!(h.check(&CapabilityRequest::NetworkEgress { host: "anything.io" }).is_ok());
+    }
+}
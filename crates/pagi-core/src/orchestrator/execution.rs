@@ -0,0 +1,467 @@
+//! Execution limits for `Orchestrator::dispatch`: per-skill / per-goal timeouts, cooperative
+//! cancellation, and concurrency caps per skill or side-effect class.
+//!
+//! Skills receive a [`CancellationToken`] through `AgentSkill::execute_cancellable`; skills that do
+//! not override it are simply dropped when the token fires or the deadline passes.
+//! Capacity is non-blocking: when every permit is taken the call is rejected instead of queued.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Default per-skill deadline when no override is configured (PAGI_SKILL_TIMEOUT_SECS).
+pub const DEFAULT_SKILL_TIMEOUT_SECS: u64 = 120;
+/// Default deadline for a whole goal, e.g. a multi-step AutonomousGoal (PAGI_GOAL_TIMEOUT_SECS).
+pub const DEFAULT_GOAL_TIMEOUT_SECS: u64 = 300;
+
+/// Side-effect class of a skill. Concurrency caps can be set per class so that, for example,
+/// no more than a handful of shell commands run at once regardless of which skill spawns them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SideEffectClass {
+    /// Reads local state only (KB queries, telemetry).
+    ReadOnly,
+    /// Outbound HTTP (web search, scraping, Graph).
+    Network,
+    /// LLM completion calls (OpenRouter, ModelRouter).
+    Llm,
+    /// Writes to the local filesystem.
+    FileWrite,
+    /// Spawns processes / shell commands.
+    Shell,
+}
+
+impl SideEffectClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SideEffectClass::ReadOnly => "read_only",
+            SideEffectClass::Network => "network",
+            SideEffectClass::Llm => "llm",
+            SideEffectClass::FileWrite => "file_write",
+            SideEffectClass::Shell => "shell",
+        }
+    }
+
+    /// Best-effort classification by skill name, used when a skill does not declare its class.
+    pub fn for_skill_name(name: &str) -> Self {
+        match name {
+            "SystemCommand" | "SovereignOperator" | "execute_shell_command" | "forge" => {
+                SideEffectClass::Shell
+            }
+            "FileSystem" | "write_sandbox_file" | "write_file" | "delete_file" | "deep_audit" => {
+                SideEffectClass::FileWrite
+            }
+            "web_search" | "CommunityScraper" => SideEffectClass::Network,
+            "ModelRouter" | "ReflectShadow" | "ResearchAudit" => SideEffectClass::Llm,
+            _ => SideEffectClass::ReadOnly,
+        }
+    }
+}
+
+/// Configurable deadlines and concurrency caps applied by the orchestrator.
+///
+/// | Env | Default | Description |
+/// |-----|---------|-------------|
+/// | PAGI_SKILL_TIMEOUT_SECS | 120 | Deadline for a single skill call (0 = none). |
+/// | PAGI_GOAL_TIMEOUT_SECS | 300 | Deadline for a whole goal (0 = none). |
+/// | PAGI_SKILL_TIMEOUTS | — | Per-skill overrides, e.g. `SystemCommand=30,web_search=20`. |
+/// | PAGI_GOAL_TIMEOUTS | — | Per-goal overrides by goal kind, e.g. `AutonomousGoal=900`. |
+/// | PAGI_SKILL_CONCURRENCY | — | Per-skill caps, e.g. `SystemCommand=2`. |
+/// | PAGI_SHELL_MAX_CONCURRENT | 4 | Cap for the `shell` side-effect class. |
+/// | PAGI_NETWORK_MAX_CONCURRENT | 16 | Cap for the `network` side-effect class. |
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionLimits {
+    /// Deadline for a skill without a specific override. None = no deadline.
+    pub default_skill_timeout: Option<Duration>,
+    /// Per-skill deadline overrides (skill name -> timeout).
+    pub skill_timeouts: HashMap<String, Duration>,
+    /// Deadline for a whole goal without a specific override. None = no deadline.
+    pub default_goal_timeout: Option<Duration>,
+    /// Per-goal deadline overrides keyed by goal kind (e.g. "AutonomousGoal").
+    pub goal_timeouts: HashMap<String, Duration>,
+    /// Maximum in-flight calls per skill name.
+    pub skill_concurrency: HashMap<String, usize>,
+    /// Maximum in-flight calls per side-effect class.
+    pub class_concurrency: HashMap<SideEffectClass, usize>,
+}
+
+impl ExecutionLimits {
+    /// No deadlines and no caps (legacy behaviour).
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Load limits from environment. Unset or invalid => defaults (see struct docs).
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Same as [`Self::from_env`], reading variables through `lookup` instead of the process
+    /// environment.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let mut limits = Self {
+            default_skill_timeout: env_secs(
+                &lookup,
+                "PAGI_SKILL_TIMEOUT_SECS",
+                DEFAULT_SKILL_TIMEOUT_SECS,
+            ),
+            default_goal_timeout: env_secs(
+                &lookup,
+                "PAGI_GOAL_TIMEOUT_SECS",
+                DEFAULT_GOAL_TIMEOUT_SECS,
+            ),
+            ..Self::default()
+        };
+        for (name, secs) in env_pairs(&lookup, "PAGI_SKILL_TIMEOUTS") {
+            limits.skill_timeouts.insert(name, Duration::from_secs(secs));
+        }
+        for (kind, secs) in env_pairs(&lookup, "PAGI_GOAL_TIMEOUTS") {
+            limits.goal_timeouts.insert(kind, Duration::from_secs(secs));
+        }
+        for (name, n) in env_pairs(&lookup, "PAGI_SKILL_CONCURRENCY") {
+            limits.skill_concurrency.insert(name, n as usize);
+        }
+        limits.class_concurrency.insert(
+            SideEffectClass::Shell,
+            env_usize(&lookup, "PAGI_SHELL_MAX_CONCURRENT", 4),
+        );
+        limits.class_concurrency.insert(
+            SideEffectClass::Network,
+            env_usize(&lookup, "PAGI_NETWORK_MAX_CONCURRENT", 16),
+        );
+        limits
+    }
+
+    pub fn with_default_skill_timeout(mut self, timeout: Duration) -> Self {
+        self.default_skill_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_skill_timeout(mut self, skill: impl Into<String>, timeout: Duration) -> Self {
+        self.skill_timeouts.insert(skill.into(), timeout);
+        self
+    }
+
+    pub fn with_default_goal_timeout(mut self, timeout: Duration) -> Self {
+        self.default_goal_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_goal_timeout(mut self, goal_kind: impl Into<String>, timeout: Duration) -> Self {
+        self.goal_timeouts.insert(goal_kind.into(), timeout);
+        self
+    }
+
+    pub fn with_skill_concurrency(mut self, skill: impl Into<String>, max: usize) -> Self {
+        self.skill_concurrency.insert(skill.into(), max);
+        self
+    }
+
+    pub fn with_class_concurrency(mut self, class: SideEffectClass, max: usize) -> Self {
+        self.class_concurrency.insert(class, max);
+        self
+    }
+
+    /// Effective deadline for one call of `skill`.
+    pub fn skill_timeout(&self, skill: &str) -> Option<Duration> {
+        self.skill_timeouts
+            .get(skill)
+            .copied()
+            .or(self.default_skill_timeout)
+    }
+
+    /// Effective deadline for a goal of the given kind.
+    pub fn goal_timeout(&self, goal_kind: &str) -> Option<Duration> {
+        self.goal_timeouts
+            .get(goal_kind)
+            .copied()
+            .or(self.default_goal_timeout)
+    }
+}
+
+fn env_secs(
+    lookup: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    default: u64,
+) -> Option<Duration> {
+    let secs = lookup(name)
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(default);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+fn env_usize(lookup: &impl Fn(&str) -> Option<String>, name: &str, default: usize) -> usize {
+    lookup(name)
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(default)
+}
+
+/// Parses `Name=123,Other=4` into (name, value) pairs; malformed entries are skipped.
+fn env_pairs(lookup: &impl Fn(&str) -> Option<String>, name: &str) -> Vec<(String, u64)> {
+    let raw = lookup(name).unwrap_or_default();
+    raw.split(',')
+        .filter_map(|pair| {
+            let (k, v) = pair.split_once('=')?;
+            let k = k.trim();
+            let v = v.trim().parse::<u64>().ok()?;
+            (!k.is_empty()).then(|| (k.to_string(), v))
+        })
+        .collect()
+}
+
+/// Structured dispatch failure so callers can tell a hung skill from a full queue or a user abort.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SkillDispatchError {
+    /// The skill did not finish before its deadline.
+    #[error("skill '{skill}' timed out after {}ms", .after.as_millis())]
+    Timeout { skill: String, after: Duration },
+    /// The whole goal did not finish before its deadline.
+    #[error("goal '{goal}' timed out after {}ms", .after.as_millis())]
+    GoalTimeout { goal: String, after: Duration },
+    /// The caller's cancellation token fired before the skill finished.
+    #[error("skill '{skill}' was cancelled")]
+    Cancelled { skill: String },
+    /// Every permit for the skill or its side-effect class was in use.
+    #[error("skill '{skill}' rejected: {scope} is at capacity ({limit} concurrent)")]
+    RejectedForCapacity {
+        skill: String,
+        scope: String,
+        limit: usize,
+    },
+}
+
+impl SkillDispatchError {
+    /// Stable machine-readable kind for API responses.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SkillDispatchError::Timeout { .. } | SkillDispatchError::GoalTimeout { .. } => "timeout",
+            SkillDispatchError::Cancelled { .. } => "cancelled",
+            SkillDispatchError::RejectedForCapacity { .. } => "rejected_for_capacity",
+        }
+    }
+}
+
+/// Runtime state for [`ExecutionLimits`]: lazily created semaphores per skill and per class.
+#[derive(Debug, Default)]
+pub(crate) struct ExecutionGovernor {
+    limits: ExecutionLimits,
+    skill_permits: DashMap<String, Arc<Semaphore>>,
+    class_permits: DashMap<SideEffectClass, Arc<Semaphore>>,
+}
+
+/// Permits held for the duration of one skill call; released on drop.
+pub(crate) struct ExecutionPermit {
+    _skill: Option<OwnedSemaphorePermit>,
+    _class: Option<OwnedSemaphorePermit>,
+}
+
+impl ExecutionGovernor {
+    pub(crate) fn new(limits: ExecutionLimits) -> Self {
+        Self {
+            limits,
+            skill_permits: DashMap::new(),
+            class_permits: DashMap::new(),
+        }
+    }
+
+    pub(crate) fn limits(&self) -> &ExecutionLimits {
+        &self.limits
+    }
+
+    /// Takes a skill permit and a class permit without waiting.
+    pub(crate) fn try_acquire(
+        &self,
+        skill: &str,
+        class: SideEffectClass,
+    ) -> Result<ExecutionPermit, SkillDispatchError> {
+        let skill_permit = match self.limits.skill_concurrency.get(skill) {
+            Some(&limit) => {
+                let sem = self
+                    .skill_permits
+                    .entry(skill.to_string())
+                    .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+                    .clone();
+                Some(sem.try_acquire_owned().map_err(|_| {
+                    SkillDispatchError::RejectedForCapacity {
+                        skill: skill.to_string(),
+                        scope: format!("skill {}", skill),
+                        limit,
+                    }
+                })?)
+            }
+            None => None,
+        };
+        let class_permit = match self.limits.class_concurrency.get(&class) {
+            Some(&limit) => {
+                let sem = self
+                    .class_permits
+                    .entry(class)
+                    .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+                    .clone();
+                Some(sem.try_acquire_owned().map_err(|_| {
+                    SkillDispatchError::RejectedForCapacity {
+                        skill: skill.to_string(),
+                        scope: format!("class {}", class.as_str()),
+                        limit,
+                    }
+                })?)
+            }
+            None => None,
+        };
+        Ok(ExecutionPermit {
+            _skill: skill_permit,
+            _class: class_permit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::{AgentSkill, Orchestrator, SkillRegistry};
+    use crate::shared::{Goal, TenantContext};
+
+    struct SleepSkill {
+        name: &'static str,
+        ms: u64,
+    }
+
+    #[async_trait::async_trait]
+    impl AgentSkill for SleepSkill {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        async fn execute(
+            &self,
+            _ctx: &TenantContext,
+            _payload: Option<serde_json::Value>,
+        ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+            tokio::time::sleep(Duration::from_millis(self.ms)).await;
+            Ok(serde_json::json!({ "slept_ms": self.ms }))
+        }
+    }
+
+    fn ctx() -> TenantContext {
+        TenantContext {
+            tenant_id: "test".to_string(),
+            correlation_id: None,
+            agent_id: None,
+            session_id: None,
+            capabilities: None,
+        }
+    }
+
+    fn orchestrator(ms: u64, limits: ExecutionLimits) -> Arc<Orchestrator> {
+        let mut registry = SkillRegistry::new();
+        registry.register(Arc::new(SleepSkill { name: "SystemCommand", ms }));
+        Arc::new(Orchestrator::new(Arc::new(registry)).with_execution_limits(limits))
+    }
+
+    fn goal() -> Goal {
+        Goal::ExecuteSkill {
+            name: "SystemCommand".to_string(),
+            payload: None,
+        }
+    }
+
+    fn dispatch_error(e: Box<dyn std::error::Error + Send + Sync>) -> SkillDispatchError {
+        e.downcast_ref::<SkillDispatchError>()
+            .cloned()
+            .expect("expected SkillDispatchError")
+    }
+
+    #[tokio::test]
+    async fn skill_timeout_is_reported() {
+        let limits = ExecutionLimits::unlimited()
+            .with_skill_timeout("SystemCommand", Duration::from_millis(20));
+        let orch = orchestrator(1_000, limits);
+        let err = dispatch_error(orch.dispatch(&ctx(), goal()).await.unwrap_err());
+        assert_eq!(err.kind(), "timeout");
+        assert!(matches!(err, SkillDispatchError::Timeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn goal_timeout_is_reported() {
+        let limits = ExecutionLimits::unlimited()
+            .with_goal_timeout("ExecuteSkill", Duration::from_millis(20));
+        let orch = orchestrator(1_000, limits);
+        let err = dispatch_error(orch.dispatch(&ctx(), goal()).await.unwrap_err());
+        assert!(matches!(err, SkillDispatchError::GoalTimeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn cancellation_stops_skill() {
+        let orch = orchestrator(1_000, ExecutionLimits::unlimited());
+        let token = CancellationToken::new();
+        let child = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            child.cancel();
+        });
+        let err = dispatch_error(
+            orch.dispatch_with_cancel(&ctx(), goal(), token)
+                .await
+                .unwrap_err(),
+        );
+        assert_eq!(err.kind(), "cancelled");
+    }
+
+    #[tokio::test]
+    async fn class_capacity_rejects_excess_calls() {
+        let limits =
+            ExecutionLimits::unlimited().with_class_concurrency(SideEffectClass::Shell, 1);
+        let orch = orchestrator(200, limits);
+        let first = {
+            let orch = Arc::clone(&orch);
+            tokio::spawn(async move { orch.dispatch(&ctx(), goal()).await.map_err(|e| e.to_string()) })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let err = dispatch_error(orch.dispatch(&ctx(), goal()).await.unwrap_err());
+        assert!(matches!(
+            err,
+            SkillDispatchError::RejectedForCapacity { limit: 1, .. }
+        ));
+        assert!(first.await.unwrap().is_ok());
+        // Permit is released once the first call completes.
+        assert!(orch.dispatch(&ctx(), goal()).await.is_ok());
+    }
+
+    #[test]
+    fn skill_override_wins_over_default() {
+        let limits = ExecutionLimits::unlimited()
+            .with_default_skill_timeout(Duration::from_secs(120))
+            .with_skill_timeout("WebSearch", Duration::from_secs(20));
+        assert_eq!(limits.skill_timeout("WebSearch"), Some(Duration::from_secs(20)));
+        assert_eq!(limits.skill_timeout("Other"), Some(Duration::from_secs(120)));
+        assert_eq!(SideEffectClass::for_skill_name("SystemCommand"), SideEffectClass::Shell);
+    }
+
+    #[test]
+    fn goal_overrides_come_from_env() {
+        let limits = ExecutionLimits::from_lookup(|name| match name {
+            "PAGI_GOAL_TIMEOUTS" => Some("AutonomousGoal=900, bogus, ExecuteSkill=5".to_string()),
+            "PAGI_GOAL_TIMEOUT_SECS" => Some("60".to_string()),
+            _ => None,
+        });
+        assert_eq!(
+            limits.goal_timeout("AutonomousGoal"),
+            Some(Duration::from_secs(900))
+        );
+        assert_eq!(
+            limits.goal_timeout("ExecuteSkill"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(limits.goal_timeout("Other"), Some(Duration::from_secs(60)));
+        assert_eq!(
+            limits.skill_timeout("Other"),
+            Some(Duration::from_secs(DEFAULT_SKILL_TIMEOUT_SECS))
+        );
+    }
+}
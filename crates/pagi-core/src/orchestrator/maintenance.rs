@@ -27,6 +27,7 @@
 //!   the main Orchestrator — failure records are read-only scans.
 //! * The `pagi-evolution::Compiler` writes to an isolated `patches/` directory.
 
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -38,6 +39,13 @@ use tracing::{debug, error, info, warn};
 
 use crate::knowledge::{EventRecord, KbType, KnowledgeStore};
 use crate::openrouter_service::OpenRouterBridge;
+use super::property::{ParamSchema, PropertyReport};
+#[cfg(feature = "validation")]
+use super::property::{
+    CaseKind, PropertyConfig, PropertyFailure, PropertyHarness, PropertyViolation, SkillProbe,
+};
+#[cfg(feature = "validation")]
+use pagi_evolution::{DynamicSkill, SandboxConfig, SandboxedSkill};
 
 // ---------------------------------------------------------------------------
 // Configuration
@@ -72,6 +80,9 @@ pub struct MaintenanceConfig {
     pub require_approval: bool,
     /// Optional approval bridge for UI-based approval (bypasses terminal stdin).
     pub approval_bridge: Option<ApprovalBridgeHandle>,
+    /// Declared parameters per skill (e.g. from the Forge manifest). Patches for these skills
+    /// also get property-based smoke tests generated from the parameters.
+    pub skill_params: HashMap<String, Vec<ParamSchema>>,
 }
 
 impl Default for MaintenanceConfig {
@@ -94,6 +105,7 @@ impl Default for MaintenanceConfig {
             patches_dir: PathBuf::from("crates/pagi-skills/src/generated/patches"),
             require_approval: true,
             approval_bridge: None,
+            skill_params: HashMap::new(),
         }
     }
 }
@@ -357,15 +369,22 @@ pub struct ValidationResult {
     pub auto_reject: bool,
     /// Reason for auto-rejection (empty if not rejected).
     pub rejection_reason: String,
+    /// Property-based smoke test run (only when the target skill declares parameters).
+    /// On failure it carries the shrunk payload that reproduces the crash.
+    pub property_report: Option<PropertyReport>,
 }
 
 /// Compiles a patch to a temporary cdylib, runs a smoke test, and measures
 /// CPU/memory delta. This is the core of Phase 4.5.
 ///
 /// The runner is gated behind `#[cfg(feature = "validation")]` so that
-/// `pagi-core` can still compile without `sysinfo` / `libloading` / `tempfile`.
+/// `pagi-core` can still compile without `sysinfo` / `pagi-evolution`.
 pub struct SmokeTestRunner;
 
+/// Deadline for the whole smoke test and for the candidate's single call.
+#[cfg(feature = "validation")]
+const SMOKE_TEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[cfg(feature = "validation")]
 impl SmokeTestRunner {
     /// Compile the patch code to a temporary cdylib and run a smoke test.
@@ -381,6 +400,17 @@ impl SmokeTestRunner {
         code: &str,
         patch_name: &str,
         target_skill: &str,
+    ) -> ValidationResult {
+        Self::validate_with_params(code, patch_name, target_skill, &[]).await
+    }
+
+    /// Like [`Self::validate`], and when `params` is non-empty also runs property-based smoke
+    /// tests generated from the skill's declared parameters (see [`PropertyHarness`]).
+    pub async fn validate_with_params(
+        code: &str,
+        patch_name: &str,
+        target_skill: &str,
+        params: &[ParamSchema],
     ) -> ValidationResult {
         info!(
             target: "pagi::maintenance::validation",
@@ -415,6 +445,7 @@ impl SmokeTestRunner {
                 ),
                 auto_reject: true,
                 rejection_reason: format!("Compilation failed: {}", compile_error),
+                property_report: None,
             };
         }
 
@@ -475,6 +506,34 @@ impl SmokeTestRunner {
                 ),
                 auto_reject: true,
                 rejection_reason: format!("Smoke test failed: {}", smoke_detail),
+                property_report: None,
+            };
+        }
+
+        // Step 6: Property-based smoke tests from the declared parameters
+        let property_report = if params.is_empty() {
+            None
+        } else {
+            Some(Self::run_property_tests(artifact, params).await)
+        };
+        if let Some(report) = property_report.as_ref().filter(|r| !r.passed()) {
+            let detail = report.summary();
+            return ValidationResult {
+                compiled: true,
+                artifact_path: artifact_path.clone(),
+                smoke_test_passed: false,
+                performance_delta: PerformanceDelta {
+                    smoke_test_passed: false,
+                    detail: detail.clone(),
+                    ..perf_delta
+                },
+                summary: format!(
+                    "REJECTED: Patch '{}' compiled but failed property-based smoke tests — {}",
+                    patch_name, detail
+                ),
+                auto_reject: true,
+                rejection_reason: format!("Property test failed: {}", detail),
+                property_report,
             };
         }
 
@@ -498,86 +557,35 @@ impl SmokeTestRunner {
             ),
             auto_reject: false,
             rejection_reason: String::new(),
+            property_report,
         }
     }
 
-    /// Compile Rust code to a temporary cdylib. Returns the path to the artifact.
+    /// Compile Rust code to a cdylib. Returns the path to the artifact.
+    ///
+    /// Builds offline in the Forge's persistent build environment (dependencies pinned to the
+    /// workspace lockfile and vendored, shared target directory), so validation needs no network
+    /// and tests exactly what the Forge would ship.
     fn compile_to_temp(code: &str, name: &str) -> Result<PathBuf, String> {
-        let dir = tempfile::tempdir()
-            .map_err(|e| format!("Failed to create temp dir: {}", e))?;
-        let root = dir.path().to_path_buf();
-
-        // Write Cargo.toml for a cdylib crate
-        let toml = format!(
-            r#"[package]
-name = "pagi_validation_{}"
-version = "0.1.0"
-edition = "2021"
-
-[lib]
-crate-type = ["cdylib"]
-
-[dependencies]
-serde = {{ version = "1", features = ["derive"] }}
-serde_json = "1"
-libc = "0.2"
-"#,
-            sanitize_filename(name)
+        // Copied to a stable temp location so the next validation build cannot overwrite it
+        let lib_name = format!(
+            "{}pagi_validation_{}{}",
+            std::env::consts::DLL_PREFIX,
+            sanitize_filename(name),
+            std::env::consts::DLL_SUFFIX
         );
-
-        std::fs::write(root.join("Cargo.toml"), &toml)
-            .map_err(|e| format!("Failed to write Cargo.toml: {}", e))?;
-        std::fs::create_dir_all(root.join("src"))
-            .map_err(|e| format!("Failed to create src dir: {}", e))?;
-        std::fs::write(root.join("src").join("lib.rs"), code)
-            .map_err(|e| format!("Failed to write lib.rs: {}", e))?;
-
-        // Run cargo build --release
-        let target_dir = root.join("target");
-        let output = std::process::Command::new("cargo")
-            .current_dir(&root)
-            .args([
-                "build",
-                "--release",
-                "--target-dir",
-                target_dir.to_str().unwrap_or("target"),
-            ])
-            .output()
-            .map_err(|e| format!("cargo build spawn failed: {}", e))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!(
-                "cargo build failed (exit {}): {}",
-                output.status.code().unwrap_or(-1),
-                stderr.chars().take(1000).collect::<String>()
-            ));
-        }
-
-        // Find the artifact
-        let lib_name = if cfg!(target_os = "windows") {
-            format!("pagi_validation_{}.dll", sanitize_filename(name))
-        } else {
-            format!("libpagi_validation_{}.so", sanitize_filename(name))
-        };
-        let lib_path = target_dir.join("release").join(&lib_name);
-
-        if lib_path.exists() {
-            // Copy to a stable temp location so the tempdir can be dropped later
-            let stable_dir = std::env::temp_dir().join("pagi_validation");
-            let _ = std::fs::create_dir_all(&stable_dir);
-            let stable_path = stable_dir.join(&lib_name);
-            std::fs::copy(&lib_path, &stable_path)
-                .map_err(|e| format!("Failed to copy artifact: {}", e))?;
-            info!(
-                target: "pagi::maintenance::validation",
-                path = %stable_path.display(),
-                "Patch compiled successfully to temp cdylib"
-            );
-            Ok(stable_path)
-        } else {
-            Err(format!("Artifact not found at {}", lib_path.display()))
-        }
+        let stable_path = std::env::temp_dir().join("pagi_validation").join(&lib_name);
+        let built = pagi_evolution::BuildEnv::from_env()
+            .build("pagi_validation", code, None, &stable_path)
+            .map_err(|e| e.to_string())?;
+        info!(
+            target: "pagi::maintenance::validation",
+            path = %stable_path.display(),
+            rustc = %built.fingerprint.rustc_version,
+            artifact_sha256 = %built.fingerprint.artifact_sha256,
+            "Patch compiled successfully to temp cdylib"
+        );
+        Ok(stable_path)
     }
 
     /// Measure current system telemetry: (cpu_usage_percent, used_memory_bytes).
@@ -601,7 +609,8 @@ libc = "0.2"
     /// - `FileSystemSkill` / `fs_*`: Calls the exported function with a "list directory" arg.
     /// - Default: Calls with an empty JSON object `{}` and checks for non-null return.
     ///
-    /// The test runs in a `spawn_blocking` thread with a timeout to prevent hangs.
+    /// The library runs in its own `pagi-skill-host` worker (see [`Self::spawn_candidate`]); the
+    /// test runs in a `spawn_blocking` thread with a timeout to prevent hangs.
     async fn run_smoke_test(
         artifact_path: &Path,
         target_skill: &str,
@@ -611,7 +620,7 @@ libc = "0.2"
 
         // Run in a blocking thread with a 30-second timeout
         let result = tokio::time::timeout(
-            Duration::from_secs(30),
+            SMOKE_TEST_TIMEOUT,
             tokio::task::spawn_blocking(move || {
                 Self::execute_smoke_test_sync(&path, &skill)
             }),
@@ -633,50 +642,95 @@ libc = "0.2"
         // Construct the smoke test input based on the target skill
         let test_input = Self::build_smoke_test_input(target_skill);
 
-        // Try to load the library and call the exported function
-        unsafe {
-            let lib = libloading::Library::new(artifact_path)
-                .map_err(|e| format!("Failed to load library: {}", e))?;
+        // Start the library in a worker and call the exported function; the worker rejects
+        // output that is not valid JSON.
+        let skill = Self::spawn_candidate(artifact_path, SMOKE_TEST_TIMEOUT)?;
+        let result_str = Self::call_candidate(&skill, test_input)?;
 
-            // Look for the standard pagi_dynamic_skill_execute symbol
-            let execute_fn: libloading::Symbol<
-                unsafe extern "C" fn(*const std::ffi::c_char) -> *mut std::ffi::c_char,
-            > = lib
-                .get(b"pagi_dynamic_skill_execute")
-                .map_err(|e| format!("Symbol pagi_dynamic_skill_execute not found: {}", e))?;
+        Ok(format!(
+            "Smoke test passed. Output: {}",
+            result_str.chars().take(200).collect::<String>()
+        ))
+    }
 
-            let free_fn: libloading::Symbol<unsafe extern "C" fn(*mut std::ffi::c_char)> = lib
-                .get(b"pagi_dynamic_skill_free")
-                .map_err(|e| format!("Symbol pagi_dynamic_skill_free not found: {}", e))?;
+    /// Loads the candidate library in a supervised `pagi-skill-host` worker (limits from
+    /// `SandboxConfig::from_env`), so a panic, segfault or hang in generated code ends that
+    /// process rather than the gateway. A call running past `call_timeout` kills the worker,
+    /// which is restarted for the next call.
+    fn spawn_candidate(
+        artifact_path: &Path,
+        call_timeout: Duration,
+    ) -> Result<SandboxedSkill, String> {
+        let config = SandboxConfig {
+            call_timeout,
+            max_restarts: u32::MAX,
+            ..SandboxConfig::from_env()
+        };
+        SandboxedSkill::spawn(artifact_path, "validation_candidate", config)
+            .map_err(|e| format!("Failed to load library: {}", e))
+    }
 
-            // Call the function
-            let c_input = std::ffi::CString::new(test_input.as_bytes())
-                .map_err(|e| format!("CString creation failed: {}", e))?;
+    /// Call the candidate with a JSON payload and return its output, re-serialized.
+    fn call_candidate(skill: &SandboxedSkill, input: serde_json::Value) -> Result<String, String> {
+        skill
+            .execute(input)
+            .map(|output| output.to_string())
+            .map_err(|e| e.to_string())
+    }
 
-            let result_ptr = execute_fn(c_input.as_ptr());
+    /// Run the property harness against the compiled cdylib in a worker process (blocking work
+    /// off the runtime). Memory growth is measured on the worker, not the gateway.
+    async fn run_property_tests(artifact_path: &Path, params: &[ParamSchema]) -> PropertyReport {
+        let path = artifact_path.to_path_buf();
+        let config = PropertyConfig::from_env();
+        let call_timeout = config.call_timeout;
+        let harness = PropertyHarness::new(params, config);
+        let report = tokio::task::spawn_blocking(move || {
+            let skill = Self::spawn_candidate(&path, call_timeout).map(Arc::new);
+            let harness = match skill {
+                Ok(ref skill) => {
+                    let skill = Arc::clone(skill);
+                    harness.with_memory_probe(Arc::new(move || skill.resident_memory_bytes()))
+                }
+                Err(_) => harness,
+            };
+            let probe: SkillProbe = Arc::new(move |payload: &serde_json::Value| {
+                let skill = skill.as_ref().map_err(Clone::clone)?;
+                Self::call_candidate(skill, payload.clone())
+            });
+            harness.run(&probe)
+        })
+        .await;
 
-            if result_ptr.is_null() {
-                return Err("Skill returned null pointer".to_string());
+        match report {
+            Ok(report) => {
+                info!(
+                    target: "pagi::maintenance::validation",
+                    passed = report.passed(),
+                    "{}",
+                    report.summary()
+                );
+                report
             }
-
-            let result_str = std::ffi::CStr::from_ptr(result_ptr)
-                .to_string_lossy()
-                .into_owned();
-            free_fn(result_ptr);
-
-            // Validate the result is valid JSON
-            let _: serde_json::Value = serde_json::from_str(&result_str)
-                .map_err(|e| format!("Skill returned invalid JSON: {}", e))?;
-
-            Ok(format!(
-                "Smoke test passed. Output: {}",
-                result_str.chars().take(200).collect::<String>()
-            ))
+            Err(e) => PropertyReport {
+                cases_run: 0,
+                seed: PropertyConfig::from_env().seed,
+                failure: Some(PropertyFailure {
+                    case_kind: CaseKind::Valid,
+                    violation: PropertyViolation::Crash(format!(
+                        "property test thread panicked: {}",
+                        e
+                    )),
+                    original: serde_json::Value::Null,
+                    minimal: serde_json::Value::Null,
+                    shrink_steps: 0,
+                }),
+            },
         }
     }
 
     /// Build the JSON input for the smoke test based on the target skill type.
-    fn build_smoke_test_input(target_skill: &str) -> String {
+    fn build_smoke_test_input(target_skill: &str) -> serde_json::Value {
         let lower = target_skill.to_lowercase();
 
         if lower.contains("filesystem") || lower.contains("fs_") || lower.contains("file") {
@@ -685,23 +739,20 @@ libc = "0.2"
                 "operation": "list",
                 "path": "."
             })
-            .to_string()
         } else if lower.contains("sentiment") || lower.contains("analyze") {
             // Sentiment analysis smoke test
             serde_json::json!({
                 "text": "This is a test sentence for validation."
             })
-            .to_string()
         } else if lower.contains("knowledge") || lower.contains("query") {
             // Knowledge query smoke test
             serde_json::json!({
                 "query": "test",
                 "limit": 1
             })
-            .to_string()
         } else {
             // Default: empty object
-            serde_json::json!({}).to_string()
+            serde_json::json!({})
         }
     }
 }
@@ -710,9 +761,18 @@ libc = "0.2"
 #[cfg(not(feature = "validation"))]
 impl SmokeTestRunner {
     pub async fn validate(
+        code: &str,
+        patch_name: &str,
+        target_skill: &str,
+    ) -> ValidationResult {
+        Self::validate_with_params(code, patch_name, target_skill, &[]).await
+    }
+
+    pub async fn validate_with_params(
         _code: &str,
         patch_name: &str,
         _target_skill: &str,
+        _params: &[ParamSchema],
     ) -> ValidationResult {
         warn!(
             target: "pagi::maintenance::validation",
@@ -730,6 +790,7 @@ impl SmokeTestRunner {
             ),
             auto_reject: false,
             rejection_reason: String::new(),
+            property_report: None,
         }
     }
 }
@@ -954,8 +1015,9 @@ fn audit_recent_failures(
 // Patch Writer
 // ---------------------------------------------------------------------------
 
-/// Saves a patch to the patches directory using versioned naming: `{name}_v{timestamp}.rs`.
-/// Also maintains a `current_{name}.rs` that always points to the latest version.
+/// Saves a patch to the patches directory using versioned naming: `{name}_v{timestamp}.rs`,
+/// and makes it the active version in the `active_versions.json` manifest shared with the
+/// `RollbackManager` (without the `validation` feature, a `current_{name}.rs` copy instead).
 /// Returns the path if successful.
 fn save_patch(patches_dir: &Path, patch_name: &str, code: &str) -> Result<PathBuf, String> {
     std::fs::create_dir_all(patches_dir)
@@ -970,19 +1032,69 @@ fn save_patch(patches_dir: &Path, patch_name: &str, code: &str) -> Result<PathBu
     std::fs::write(&versioned_path, code)
         .map_err(|e| format!("Failed to write patch: {}", e))?;
 
-    // Update the `current_{name}.rs` pointer (file copy for cross-platform compatibility).
-    let current_path = patches_dir.join(format!("current_{}.rs", base_name));
-    let temp_path = patches_dir.join(format!("current_{}.rs.tmp", base_name));
-    let _ = std::fs::remove_file(&temp_path);
-    std::fs::copy(&versioned_path, &temp_path)
-        .map_err(|e| format!("Failed to copy to current: {}", e))?;
-    let _ = std::fs::remove_file(&current_path);
-    std::fs::rename(&temp_path, &current_path)
-        .map_err(|e| format!("Failed to rename current: {}", e))?;
+    #[cfg(feature = "validation")]
+    {
+        let (manifest, _) = pagi_evolution::ActiveManifest::load(patches_dir);
+        let previous = manifest.get(&base_name).map(|e| e.timestamp_ms);
+        let entry = pagi_evolution::ActiveEntry::for_source(timestamp, &versioned_path, code);
+        manifest
+            .compare_and_swap(&base_name, previous, Some(entry))
+            .map_err(|e| format!("Failed to activate patch: {}", e))?;
+    }
+
+    #[cfg(not(feature = "validation"))]
+    {
+        // Update the `current_{name}.rs` pointer (file copy for cross-platform compatibility).
+        let current_path = patches_dir.join(format!("current_{}.rs", base_name));
+        let temp_path = patches_dir.join(format!("current_{}.rs.tmp", base_name));
+        let _ = std::fs::remove_file(&temp_path);
+        std::fs::copy(&versioned_path, &temp_path)
+            .map_err(|e| format!("Failed to copy to current: {}", e))?;
+        let _ = std::fs::remove_file(&current_path);
+        std::fs::rename(&temp_path, &current_path)
+            .map_err(|e| format!("Failed to rename current: {}", e))?;
+    }
 
     Ok(versioned_path)
 }
 
+/// Writes the lineage sidecar of a saved patch: the failure that triggered it, the model and
+/// prompt that wrote it, and its validation metrics. The parent is the previous version of the
+/// same patch in the directory.
+#[cfg(feature = "validation")]
+fn record_lineage(
+    path: &Path,
+    failures: &[FailureRecord],
+    model: &str,
+    prompt: &str,
+    validation: &ValidationResult,
+) {
+    let origin = pagi_evolution::PatchOrigin {
+        trigger: failures.first().map(|f| pagi_evolution::FailureTrigger {
+            key: f.key.clone(),
+            skill: f.skill.clone(),
+            description: f.description.clone(),
+            timestamp_ms: f.timestamp_ms,
+        }),
+        model: Some(model.to_string()),
+        prompt_hash: Some(pagi_evolution::prompt_hash(prompt)),
+        ..Default::default()
+    };
+    let pd = &validation.performance_delta;
+    let delta = pagi_evolution::PatchPerformanceDelta {
+        cpu: pd.cpu.clone(),
+        mem: pd.mem.clone(),
+        compiled: pd.compiled,
+        smoke_test_passed: pd.smoke_test_passed,
+        security_audit: None,
+        canary: None,
+    };
+    let description = format!("Maintenance patch for {} failure(s)", failures.len());
+    if let Err(e) = pagi_evolution::record_patch_lineage(path, origin, &description, Some(delta)) {
+        warn!(target: "pagi::maintenance", error = %e, "Failed to record patch lineage");
+    }
+}
+
 /// Computes a simple hash of the patch code for genetic memory / dead-end detection.
 /// Returns a hex string suitable for storage in Chronos.
 pub fn compute_patch_dna(code: &str) -> String {
@@ -1015,15 +1127,30 @@ pub fn record_genetic_dead_end(
     code_hash: &str,
     skill_name: &str,
     reason: &str,
+) {
+    record_genetic_dead_end_with_payload(knowledge, code_hash, skill_name, reason, None);
+}
+
+/// Like [`record_genetic_dead_end`], also storing the minimal payload that reproduces the
+/// failure (from a shrunk property-test case).
+pub fn record_genetic_dead_end_with_payload(
+    knowledge: &KnowledgeStore,
+    code_hash: &str,
+    skill_name: &str,
+    reason: &str,
+    minimal_failing_payload: Option<&serde_json::Value>,
 ) {
     let chronos_slot = KbType::Chronos.slot_id();
     let dead_end_key = format!("dead_end/{}", code_hash);
-    let value = format!(
-        "{{\"skill\":\"{}\",\"reason\":\"{}\",\"timestamp_ms\":{}}}",
-        skill_name,
-        reason.replace('"', "'"),
-        now_epoch_ms()
-    );
+    let mut record = serde_json::json!({
+        "skill": skill_name,
+        "reason": reason.replace('"', "'"),
+        "timestamp_ms": now_epoch_ms(),
+    });
+    if let Some(payload) = minimal_failing_payload {
+        record["minimal_failing_payload"] = payload.clone();
+    }
+    let value = record.to_string();
     if let Err(e) = knowledge.insert(chronos_slot, &dead_end_key, value.as_bytes()) {
         warn!(
             target: "pagi::maintenance::genetic",
@@ -1207,7 +1334,12 @@ async fn maintenance_tick(
         "Entering Phase 4.5: Validation Benchmark"
     );
 
-    let validation = SmokeTestRunner::validate(&code, &patch_name, target_skill).await;
+    let params = config
+        .skill_params
+        .get(target_skill)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let validation = SmokeTestRunner::validate_with_params(&code, &patch_name, target_skill, params).await;
 
     if validation.auto_reject {
         // Auto-rejection: record as "Syntactic Hallucination" in Chronos
@@ -1232,11 +1364,17 @@ async fn maintenance_tick(
         let _ = knowledge.append_chronos_event(MAINTENANCE_AGENT_ID, &event);
 
         // Record the DNA hash as a dead-end so the agent never re-suggests this code
-        record_genetic_dead_end(
+        let minimal_payload = validation
+            .property_report
+            .as_ref()
+            .and_then(|r| r.failure.as_ref())
+            .map(|f| &f.minimal);
+        record_genetic_dead_end_with_payload(
             knowledge,
             &code_hash,
             target_skill,
             &format!("Syntactic Hallucination: {}", validation.rejection_reason),
+            minimal_payload,
         );
 
         // Clean up temp artifact if it exists
@@ -1387,7 +1525,7 @@ async fn maintenance_tick(
         }
     }
 
-    // Save the patch (versioned: {name}_v{timestamp}.rs + current_{name}.rs pointer).
+    // Save the patch (versioned: {name}_v{timestamp}.rs + active-versions manifest entry).
     emit_pulse(log_tx, "applying", target_skill, &format!("Saving versioned patch '{}'...", patch_name), *applied_patches_count, failure_count);
     match save_patch(&config.patches_dir, &patch_name, &code) {
         Ok(path) => {
@@ -1401,6 +1539,9 @@ async fn maintenance_tick(
             emit_pulse(log_tx, "complete", target_skill, &format!("✓ {}", msg), *applied_patches_count, failure_count);
             info!(target: "pagi::maintenance", path = %path.display(), dna = &code_hash[..code_hash.len().min(12)], "Versioned patch saved");
 
+            #[cfg(feature = "validation")]
+            record_lineage(&path, &failures, bridge.model(), &prompt, &validation);
+
             // Record the patch DNA in Chronos for genetic memory tracking
             let dna_key = format!("patch_dna/{}/{}", target_skill, code_hash);
             let dna_value = format!(
@@ -3,17 +3,25 @@
 //! When MoE (Mixture of Experts) mode is enabled, the gateway uses `route_to_experts`
 //! to send inputs to the appropriate expert: OpenRouter (creative/complex), LanceDB (knowledge),
 //! or SystemTool (commands). Unmatched tasks fall back to OpenRouter or can trigger pagi-evolution.
+//! `SemanticRouter` (see `router`) classifies by embedding centroids trained from KB-05 examples
+//! and falls back to these keyword heuristics below its confidence threshold.
 
 mod archetype_logic;
 mod astro_weather;
 mod blueprint;
+mod budget;
+mod capabilities;
+mod signing;
+mod router;
 mod health_report;
 mod control;
+mod execution;
 pub mod heuristics;
 pub mod init;
 pub mod maintenance;
 mod persona;
 mod planner;
+pub mod property;
 pub mod protocols;
 pub mod skills;
 pub mod sovereign_voice;
@@ -28,7 +36,14 @@ pub use health_report::{
     HealthReport, LeakStats, RestVsOutputEntry, ShieldedEvent, TransitCorrelationEntry, ArchetypeUsageBreakdown,
 };
 pub use blueprint::{BlueprintRegistry, Plan};
+pub use capabilities::{
+    CapabilityHandle, CapabilityRequest, CapabilityViolation, FsAccess, FsGrant, SkillCapabilities,
+};
 pub use control::ControlPanelMessage;
+pub use execution::{
+    CancellationToken, ExecutionLimits, SideEffectClass, SkillDispatchError,
+    DEFAULT_GOAL_TIMEOUT_SECS, DEFAULT_SKILL_TIMEOUT_SECS,
+};
 pub use archetype_logic::{
     active_archetype_label, get_sovereignty_leak_triggers, process_archetype_triggers,
     ArchetypeTriggerResult,
@@ -50,8 +65,22 @@ pub use protocols::{
     matched_sovereignty_triggers, rank_subject_from_sovereignty_triggers, ProtocolEngine,
 };
 pub use skills::{
-    validate_skill_permissions, SkillInventoryEntry, SkillManifestEntry, SkillManifestRegistry,
-    SovereigntyViolation, TierManifest, TrustTier,
+    validate_skill_capability, validate_skill_permissions, PromotionError, SkillInventoryEntry, SkillManifestEntry,
+    SkillManifestRegistry, SovereigntyViolation, TierManifest, TrustTier,
+};
+pub use budget::{
+    BudgetEnforcement, BudgetExceeded, BudgetLimits, BudgetPolicy, BudgetReport, EnergyDebit, EnergyLedger, EnergyUsage,
+    ScopeBudget, BUDGET_KEY_PREFIX, DEFAULT_BUDGET_SOFT_RATIO, run_live_skill,
+};
+pub use router::{
+    export_training_set, get_decision, load_examples as load_routing_examples, recent_decisions as recent_routing_decisions,
+    record_correction as record_routing_correction, record_decision as record_routing_decision,
+    store_example as store_routing_example, HashingEmbedder, RoutingCorrection, RoutingDecision, RoutingExample,
+    RoutingMethod, SemanticRouter, TextEmbedder, DEFAULT_ROUTER_THRESHOLD, MOE_DECISION_PREFIX, MOE_EXAMPLE_PREFIX,
+};
+pub use signing::{
+    sha256_file, signing_payload, verify_artifact, SignatureError, SkillSignature, SkillSigner, TrustedSkillKeys,
+    DEFAULT_SKILL_SIGNING_KEY_PATH, SKILL_SIGNING_KEY_PATH_ENV, SKILL_TRUSTED_KEYS_ENV,
 };
 pub use traits::{Heuristic, ManeuverOutcome, Protector, RoiResult, ThreatContext, VitalityLevel};
 pub use maintenance::{
@@ -59,7 +88,11 @@ pub use maintenance::{
     MaintenancePulseEvent, PendingApproval, ApprovalBridgeHandle, new_approval_bridge,
     PerformanceDelta, ValidationResult, SmokeTestRunner,
     // Evolutionary Versioning & Genetic Memory
-    compute_patch_dna, check_genetic_dead_end, record_genetic_dead_end,
+    compute_patch_dna, check_genetic_dead_end, record_genetic_dead_end, record_genetic_dead_end_with_payload,
+};
+pub use property::{
+    CaseKind, MemoryProbe, ParamKind, ParamSchema, PayloadGenerator, PropertyCase, PropertyConfig,
+    PropertyFailure, PropertyHarness, PropertyReport, PropertyViolation, SkillProbe,
 };
 pub use sovereign_voice::{
     SOVEREIGN_VOICE_PROMPT, FORMATTING_GUIDELINES, FORBIDDEN_PHRASES,
@@ -67,6 +100,7 @@ pub use sovereign_voice::{
 };
 
 use crate::shared::{Goal, TenantContext};
+use execution::ExecutionGovernor;
 use std::fmt;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::{Arc, RwLock};
@@ -103,7 +137,7 @@ impl MoEMode {
 }
 
 /// Expert route for toggle-based MoE. When MoE is Sparse, the gateway branches on this.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MoEExpert {
     /// Creative, complex reasoning, or general chat → OpenRouter (LLM).
     OpenRouter,
@@ -115,6 +149,28 @@ pub enum MoEExpert {
     Unmatched,
 }
 
+impl MoEExpert {
+    /// Label used in KB-05 examples and KB-08 routing logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MoEExpert::OpenRouter => "openrouter",
+            MoEExpert::LanceDB => "lancedb",
+            MoEExpert::SystemTool => "system_tool",
+            MoEExpert::Unmatched => "unmatched",
+        }
+    }
+
+    pub fn from_label(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "openrouter" | "llm" => Some(MoEExpert::OpenRouter),
+            "lancedb" | "knowledge" => Some(MoEExpert::LanceDB),
+            "system_tool" | "systemtool" | "system" => Some(MoEExpert::SystemTool),
+            "unmatched" => Some(MoEExpert::Unmatched),
+            _ => None,
+        }
+    }
+}
+
 /// Lightweight gating: classify user input to choose which expert handles it.
 /// Used only when MoE mode is enabled. Heuristics: system-ish keywords → SystemTool;
 /// knowledge-ish → LanceDB; else OpenRouter. Unmatched reserved for evolution hook.
@@ -239,31 +295,77 @@ pub trait AgentSkill: Send + Sync {
         ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Cancellation-aware entry point used by the Orchestrator. Long-running skills override this
+    /// to poll `cancel` and stop early; the default delegates to `execute` (the orchestrator still
+    /// drops the call when the token fires or its deadline passes).
+    async fn execute_cancellable(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+        _cancel: &CancellationToken,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.execute(ctx, payload).await
+    }
+
+    /// Token estimate debited from the energy budget when the result reports no actual usage.
+    fn energy_cost(&self) -> crate::skills::EnergyCost {
+        use crate::skills::EnergyCost;
+        match self.side_effect_class() {
+            SideEffectClass::Llm => EnergyCost::Medium,
+            SideEffectClass::Network | SideEffectClass::Shell => EnergyCost::Low,
+            SideEffectClass::ReadOnly | SideEffectClass::FileWrite => EnergyCost::Minimal,
+        }
+    }
+
+    /// Side-effect class used for concurrency caps. Defaults to a name-based classification.
+    fn side_effect_class(&self) -> SideEffectClass {
+        SideEffectClass::for_skill_name(self.name())
+    }
 }
 
 /// Registry of agent skills that can be dispatched by name.
 pub struct SkillRegistry {
-    skills: Vec<Arc<dyn AgentSkill>>,
+    skills: RwLock<Vec<Arc<dyn AgentSkill>>>,
 }
 
 impl SkillRegistry {
     pub fn new() -> Self {
         Self {
-            skills: Vec::new(),
+            skills: RwLock::new(Vec::new()),
         }
     }
 
     pub fn register(&mut self, skill: Arc<dyn AgentSkill>) {
-        self.skills.push(skill);
+        self.skills
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(skill);
+    }
+
+    /// Adds a skill after the registry is shared with the Orchestrator (Forge overlay skills
+    /// built while the gateway runs).
+    pub fn register_runtime(&self, skill: Arc<dyn AgentSkill>) {
+        if let Ok(mut guard) = self.skills.write() {
+            guard.push(skill);
+        }
     }
 
     pub fn get(&self, name: &str) -> Option<Arc<dyn AgentSkill>> {
-        self.skills.iter().find(|s| s.name() == name).cloned()
+        self.skills
+            .read()
+            .ok()?
+            .iter()
+            .find(|s| s.name() == name)
+            .cloned()
     }
 
     /// Returns the names of all registered skills (for discovery and planning).
     pub fn skill_names(&self) -> Vec<String> {
-        self.skills.iter().map(|s| s.name().to_string()).collect()
+        self.skills
+            .read()
+            .map(|g| g.iter().map(|s| s.name().to_string()).collect())
+            .unwrap_or_default()
     }
 }
 
@@ -294,6 +396,12 @@ pub struct Orchestrator {
     skill_manifest_registry: Option<Arc<SkillManifestRegistry>>,
     /// When true (PAGI_FIREWALL_STRICT_MODE), only Core (Tier 1) skills may touch any KB layer.
     firewall_strict_mode: bool,
+    /// Per-skill / per-goal deadlines and concurrency caps (see `ExecutionLimits`).
+    execution: ExecutionGovernor,
+    /// MoE gating: embedding-centroid router with keyword fallback.
+    moe_router: Arc<SemanticRouter>,
+    /// When Some, every skill call is checked against and debited from daily energy budgets.
+    energy: Option<Arc<EnergyLedger>>,
 }
 
 impl Orchestrator {
@@ -307,6 +415,9 @@ impl Orchestrator {
             moe_mode: AtomicU8::new(0), // Dense
             skill_manifest_registry: None,
             firewall_strict_mode: false,
+            execution: ExecutionGovernor::default(),
+            moe_router: Arc::new(SemanticRouter::default()),
+            energy: None,
         }
     }
 
@@ -320,6 +431,9 @@ impl Orchestrator {
             moe_mode: AtomicU8::new(0), // Dense
             skill_manifest_registry: None,
             firewall_strict_mode: false,
+            execution: ExecutionGovernor::default(),
+            moe_router: Arc::new(SemanticRouter::default()),
+            energy: None,
         }
     }
 
@@ -340,9 +454,67 @@ impl Orchestrator {
             moe_mode: AtomicU8::new(0),
             skill_manifest_registry: Some(skill_manifest_registry),
             firewall_strict_mode,
+            execution: ExecutionGovernor::default(),
+            moe_router: Arc::new(SemanticRouter::default()),
+            energy: None,
         }
     }
 
+    /// Applies deadlines and concurrency caps to every skill call made by `dispatch`.
+    /// Without this the orchestrator runs skills with no deadline and no caps.
+    pub fn with_execution_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.execution = ExecutionGovernor::new(limits);
+        self
+    }
+
+    /// Enforces per-tenant / per-session energy budgets on every skill call.
+    pub fn with_energy_ledger(mut self, ledger: Arc<EnergyLedger>) -> Self {
+        self.energy = Some(ledger);
+        self
+    }
+
+    /// Skills dispatched by this orchestrator (for registering skills built at runtime).
+    pub fn skill_registry(&self) -> &Arc<SkillRegistry> {
+        &self.registry
+    }
+
+    /// Energy ledger (for debiting LLM calls made outside `dispatch` and for status endpoints).
+    pub fn energy_ledger(&self) -> Option<&Arc<EnergyLedger>> {
+        self.energy.as_ref()
+    }
+
+    /// Replaces the MoE router (e.g. a different embedder or threshold).
+    pub fn with_moe_router(mut self, router: Arc<SemanticRouter>) -> Self {
+        self.moe_router = router;
+        self
+    }
+
+    /// MoE router, for re-fitting from KB-05 and routing chat prompts.
+    pub fn moe_router(&self) -> &Arc<SemanticRouter> {
+        &self.moe_router
+    }
+
+    /// Routes a chat prompt to an expert. In Sparse mode the keyword fallback also considers
+    /// `local_ctx` (see `Gater::route_with_context`). See `SemanticRouter::route` for the
+    /// returned rephrase correction.
+    pub fn route_moe(
+        &self,
+        session: &str,
+        local_ctx: &str,
+        input: &str,
+    ) -> (RoutingDecision, Option<(String, RoutingCorrection)>) {
+        let ctx = match self.get_moe_mode() {
+            MoEMode::Sparse => Some(local_ctx),
+            MoEMode::Dense => None,
+        };
+        self.moe_router.route(session, ctx, input)
+    }
+
+    /// Current execution limits (for status endpoints).
+    pub fn execution_limits(&self) -> &ExecutionLimits {
+        self.execution.limits()
+    }
+
     /// Set MoE mode (Dense = standard LLM, Sparse = expert routing). Caller should persist to KB via KnowledgeStore::set_sovereign_moe_mode.
     pub fn set_moe_mode(&self, mode: MoEMode) {
         self.moe_mode.store(mode as u8, Ordering::SeqCst);
@@ -439,6 +611,106 @@ impl Orchestrator {
         &self,
         ctx: &TenantContext,
         goal: Goal,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        self.dispatch_with_cancel(ctx, goal, CancellationToken::new()).await
+    }
+
+    /// Same as `dispatch`, but the caller can abort the goal through `cancel`.
+    /// The goal deadline (`ExecutionLimits::goal_timeout`) wraps the whole goal; each skill call
+    /// inside it also gets its own deadline and concurrency permits. Failures surface as
+    /// [`SkillDispatchError`].
+    pub async fn dispatch_with_cancel(
+        &self,
+        ctx: &TenantContext,
+        goal: Goal,
+        cancel: CancellationToken,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let kind = goal_kind(&goal);
+        let Some(after) = self.execution.limits().goal_timeout(kind) else {
+            return self.dispatch_goal(ctx, goal, &cancel).await;
+        };
+        match tokio::time::timeout(after, self.dispatch_goal(ctx, goal, &cancel)).await {
+            Ok(result) => result,
+            Err(_) => {
+                cancel.cancel();
+                Err(SkillDispatchError::GoalTimeout {
+                    goal: kind.to_string(),
+                    after,
+                }
+                .into())
+            }
+        }
+    }
+
+    /// Runs one skill call under its deadline, the caller's cancellation token, the
+    /// per-skill / per-class concurrency caps and the energy budget.
+    async fn run_skill(
+        &self,
+        skill: &Arc<dyn AgentSkill>,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+        cancel: &CancellationToken,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let name = skill.name();
+        if cancel.is_cancelled() {
+            return Err(SkillDispatchError::Cancelled { skill: name.to_string() }.into());
+        }
+        let class = skill.side_effect_class();
+        // With a manifest registry every skill must be listed and runs with a capability handle
+        // scoped to what it declares.
+        let scoped_ctx = match self.skill_manifest_registry {
+            Some(ref reg) => {
+                reg.validate_side_effect_class(name, class)?;
+                Some(reg.scoped_context(name, ctx)?)
+            }
+            None => None,
+        };
+        let ctx = scoped_ctx.as_ref().unwrap_or(ctx);
+        if let Some(ref ledger) = self.energy {
+            ledger.check(ctx)?;
+        }
+        let _permit = self.execution.try_acquire(name, class)?;
+        let started = std::time::Instant::now();
+        let call = skill.execute_cancellable(ctx, payload, cancel);
+        let timeout = self.execution.limits().skill_timeout(name);
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => {
+                Err(SkillDispatchError::Cancelled { skill: name.to_string() }.into())
+            }
+            result = async {
+                match timeout {
+                    Some(after) => tokio::time::timeout(after, call).await.map_err(|_| after),
+                    None => Ok(call.await),
+                }
+            } => match result {
+                Ok(r) => r,
+                Err(after) => Err(SkillDispatchError::Timeout { skill: name.to_string(), after }.into()),
+            },
+        };
+        if let Some(ref ledger) = self.energy {
+            let tokens = match result {
+                Ok(ref value) => budget::reported_tokens(value).unwrap_or_else(|| skill.energy_cost().estimated_tokens()),
+                Err(_) => 0,
+            };
+            let api_calls = matches!(class, SideEffectClass::Network | SideEffectClass::Llm) as u64;
+            ledger.debit(
+                ctx,
+                EnergyDebit {
+                    tokens,
+                    wall_time_ms: started.elapsed().as_millis() as u64,
+                    api_calls,
+                },
+            );
+        }
+        result
+    }
+
+    async fn dispatch_goal(
+        &self,
+        ctx: &TenantContext,
+        goal: Goal,
+        cancel: &CancellationToken,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         if !self.skills_enabled.load(Ordering::Acquire) {
             return Ok(serde_json::json!({
@@ -465,7 +737,7 @@ impl Orchestrator {
                     .registry
                     .get(&name)
                     .ok_or_else(|| UnknownSkill(name.clone()))?;
-                skill.execute(ctx, payload).await
+                self.run_skill(&skill, ctx, payload, cancel).await
             }
             Goal::QueryKnowledge { slot_id, query } => {
                 if !self.pagi_kb_active(slot_id) {
@@ -490,14 +762,14 @@ impl Orchestrator {
                     .registry
                     .get("KnowledgeQuery")
                     .ok_or_else(|| UnknownSkill("KnowledgeQuery".into()))?;
-                skill.execute(ctx, Some(payload)).await
+                self.run_skill(&skill, ctx, Some(payload), cancel).await
             }
             Goal::IngestData { payload } => {
                 let skill = self
                     .registry
                     .get("LeadCapture")
                     .ok_or_else(|| UnknownSkill("LeadCapture".into()))?;
-                skill.execute(ctx, payload).await
+                self.run_skill(&skill, ctx, payload, cancel).await
             }
             Goal::AssembleContext { context_id } => {
                 let payload = serde_json::json!({ "lead_id": context_id });
@@ -505,7 +777,7 @@ impl Orchestrator {
                     .registry
                     .get("DraftResponse")
                     .ok_or_else(|| UnknownSkill("DraftResponse".into()))?;
-                skill.execute(ctx, Some(payload)).await
+                self.run_skill(&skill, ctx, Some(payload), cancel).await
             }
             Goal::GenerateFinalResponse { context_id } => {
                 let draft_skill = self
@@ -513,7 +785,7 @@ impl Orchestrator {
                     .get("DraftResponse")
                     .ok_or_else(|| UnknownSkill("DraftResponse".into()))?;
                 let draft_payload = serde_json::json!({ "lead_id": context_id });
-                let draft_result = draft_skill.execute(ctx, Some(draft_payload)).await?;
+                let draft_result = self.run_skill(&draft_skill, ctx, Some(draft_payload), cancel).await?;
                 let prompt = draft_result
                     .get("draft")
                     .and_then(|v| v.as_str())
@@ -524,7 +796,7 @@ impl Orchestrator {
                     .get("ModelRouter")
                     .ok_or_else(|| UnknownSkill("ModelRouter".into()))?;
                 let router_payload = serde_json::json!({ "prompt": prompt });
-                let router_result = router_skill.execute(ctx, Some(router_payload)).await?;
+                let router_result = self.run_skill(&router_skill, ctx, Some(router_payload), cancel).await?;
                 let mut map = match router_result {
                     serde_json::Value::Object(m) => m,
                     _ => {
@@ -558,7 +830,7 @@ impl Orchestrator {
                         .get(skill_name)
                         .ok_or_else(|| UnknownSkill(skill_name.clone()))?;
                     let step_input = chain_payload(previous_skill.as_deref(), skill_name, &previous_result, payload.clone());
-                    previous_result = skill.execute(ctx, step_input.clone()).await?;
+                    previous_result = self.run_skill(&skill, ctx, step_input.clone(), cancel).await?;
                     previous_skill = Some(skill_name.clone());
                     payload = previous_result.clone();
 
@@ -580,7 +852,7 @@ impl Orchestrator {
 
                 if let Some(audit_skill) = self.registry.get("ResearchAudit") {
                     let audit_payload = serde_json::json!({ "trace": thought_log });
-                    if let Ok(audit_result) = audit_skill.execute(ctx, Some(audit_payload)).await {
+                    if let Ok(audit_result) = self.run_skill(&audit_skill, ctx, Some(audit_payload), cancel).await {
                         if let Some(trace_id) = audit_result.get("trace_id").and_then(|v| v.as_str()) {
                             let mut out = match final_result {
                                 serde_json::Value::Object(m) => m,
@@ -640,7 +912,7 @@ impl Orchestrator {
                     .registry
                     .get("CommunityScraper")
                     .ok_or_else(|| UnknownSkill("CommunityScraper".into()))?;
-                skill.execute(ctx, Some(payload)).await
+                self.run_skill(&skill, ctx, Some(payload), cancel).await
             }
             Goal::MemoryOp { path, value } => {
                 Ok(serde_json::json!({ "path": path, "value": value, "status": "dispatched" }))
@@ -650,6 +922,21 @@ impl Orchestrator {
     }
 }
 
+/// Goal variant name, used as the key for per-goal deadlines.
+fn goal_kind(goal: &Goal) -> &'static str {
+    match goal {
+        Goal::ExecuteSkill { .. } => "ExecuteSkill",
+        Goal::QueryKnowledge { .. } => "QueryKnowledge",
+        Goal::MemoryOp { .. } => "MemoryOp",
+        Goal::IngestData { .. } => "IngestData",
+        Goal::AssembleContext { .. } => "AssembleContext",
+        Goal::GenerateFinalResponse { .. } => "GenerateFinalResponse",
+        Goal::AutonomousGoal { .. } => "AutonomousGoal",
+        Goal::UpdateKnowledgeSlot { .. } => "UpdateKnowledgeSlot",
+        Goal::Custom(_) => "Custom",
+    }
+}
+
 /// Extracts KB layer (1..=9) from a skill payload when present. Used by the Sovereignty Firewall.
 fn extract_kb_layer_from_payload(payload: Option<&serde_json::Value>) -> Option<u8> {
     let p = payload.as_ref()?;
@@ -0,0 +1,768 @@
+//! Property-based smoke tests derived from a skill's declared parameters.
+//!
+//! [`PayloadGenerator`] turns a parameter list (name, type, required) into valid and adversarial
+//! JSON payloads: missing fields, wrong types, huge strings, unicode, extra fields and non-object
+//! payloads. [`PropertyHarness`] runs each payload against a skill probe and asserts that the skill
+//! never crashes, always answers with valid JSON, and stays within its time and memory bounds.
+//! The first failing payload is shrunk to a minimal reproducer so it can be stored next to the
+//! dead-end record for the patch.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Executes a skill with a JSON payload and returns its raw output string.
+/// `Err` means the skill crashed (null return, missing symbol, error status, ...).
+pub type SkillProbe = Arc<dyn Fn(&Value) -> Result<String, String> + Send + Sync>;
+
+/// Reads the resident memory, in bytes, of whatever process runs the skill.
+/// `None` skips the memory bound for that call.
+pub type MemoryProbe = Arc<dyn Fn() -> Option<u64> + Send + Sync>;
+
+const DEFAULT_CASES: usize = 64;
+const DEFAULT_SEED: u64 = 0x005e_ed0f_9a91;
+const DEFAULT_CALL_TIMEOUT_MS: u64 = 5_000;
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 1 << 20;
+const DEFAULT_MAX_MEMORY_GROWTH_MB: u64 = 64;
+const DEFAULT_MAX_SHRINK_STEPS: usize = 200;
+const HUGE_STRING_BYTES: usize = 256 * 1024;
+
+// ---------------------------------------------------------------------------
+// Parameter schema
+// ---------------------------------------------------------------------------
+
+/// JSON type of a declared skill parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamKind {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Object,
+    Array,
+    /// Unknown or untyped parameter; any JSON value is valid.
+    Any,
+}
+
+impl ParamKind {
+    /// Parse a Forge/ToolSpec type name (`"string"`, `"i64"`, `"bool"`, `"Vec<String>"`, ...).
+    pub fn parse(type_name: &str) -> Self {
+        let t = type_name.trim().to_lowercase();
+        match t.as_str() {
+            "string" | "str" | "&str" | "text" => Self::String,
+            "number" | "float" | "f32" | "f64" | "double" => Self::Number,
+            "integer" | "int" | "i32" | "i64" | "u32" | "u64" | "usize" => Self::Integer,
+            "boolean" | "bool" => Self::Boolean,
+            "object" | "map" | "dict" | "json" => Self::Object,
+            _ if t.starts_with("vec<") || t.starts_with("array") || t == "list" => Self::Array,
+            _ => Self::Any,
+        }
+    }
+}
+
+/// A declared skill parameter: the input to payload generation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamSchema {
+    pub name: String,
+    pub kind: ParamKind,
+    pub required: bool,
+}
+
+impl ParamSchema {
+    pub fn new(name: impl Into<String>, type_name: &str, required: bool) -> Self {
+        Self {
+            name: name.into(),
+            kind: ParamKind::parse(type_name),
+            required,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Bounds and budget for a property-test run.
+#[derive(Debug, Clone)]
+pub struct PropertyConfig {
+    /// Total payloads to try (the fixed edge cases always run; random cases fill the rest).
+    pub cases: usize,
+    /// Seed for the payload generator, so failures are reproducible.
+    pub seed: u64,
+    /// Maximum wall time for a single call.
+    pub call_timeout: Duration,
+    /// Maximum size of a single response.
+    pub max_output_bytes: usize,
+    /// Maximum resident-memory growth attributed to a single call.
+    pub max_memory_growth_bytes: u64,
+    /// Maximum number of re-runs spent shrinking a failing payload.
+    pub max_shrink_steps: usize,
+}
+
+impl Default for PropertyConfig {
+    fn default() -> Self {
+        Self {
+            cases: DEFAULT_CASES,
+            seed: DEFAULT_SEED,
+            call_timeout: Duration::from_millis(DEFAULT_CALL_TIMEOUT_MS),
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            max_memory_growth_bytes: DEFAULT_MAX_MEMORY_GROWTH_MB * 1024 * 1024,
+            max_shrink_steps: DEFAULT_MAX_SHRINK_STEPS,
+        }
+    }
+}
+
+impl PropertyConfig {
+    /// Reads `PAGI_PROPERTY_CASES`, `PAGI_PROPERTY_SEED`, `PAGI_PROPERTY_TIMEOUT_MS` and
+    /// `PAGI_PROPERTY_MAX_MEMORY_MB`, falling back to the defaults.
+    pub fn from_env() -> Self {
+        fn env<T: std::str::FromStr>(key: &str) -> Option<T> {
+            std::env::var(key).ok().and_then(|s| s.trim().parse().ok())
+        }
+        let d = Self::default();
+        Self {
+            cases: env("PAGI_PROPERTY_CASES").unwrap_or(d.cases),
+            seed: env("PAGI_PROPERTY_SEED").unwrap_or(d.seed),
+            call_timeout: env("PAGI_PROPERTY_TIMEOUT_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(d.call_timeout),
+            max_memory_growth_bytes: env::<u64>("PAGI_PROPERTY_MAX_MEMORY_MB")
+                .map(|mb| mb * 1024 * 1024)
+                .unwrap_or(d.max_memory_growth_bytes),
+            ..d
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Payload generation
+// ---------------------------------------------------------------------------
+
+/// What a generated payload is probing for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseKind {
+    Valid,
+    MissingField,
+    WrongType,
+    HugeString,
+    Unicode,
+    ExtraField,
+    NonObject,
+}
+
+/// One generated payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyCase {
+    pub kind: CaseKind,
+    pub payload: Value,
+}
+
+/// Small deterministic PRNG (SplitMix64); enough for payload fuzzing and reproducible by seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next() % n as u64) as usize
+        }
+    }
+}
+
+const UNICODE_SAMPLES: &[&str] = &[
+    "héllo wörld",
+    "日本語のテキスト",
+    "emoji 🔥🧬🛡️",
+    "rtl \u{202e}txet\u{202c}",
+    "zero\u{200b}width",
+    "nul\u{0}byte",
+    "combining e\u{301}\u{301}\u{301}",
+];
+
+/// Generates valid and adversarial payloads from a parameter list.
+pub struct PayloadGenerator {
+    params: Vec<ParamSchema>,
+    rng: SplitMix64,
+}
+
+impl PayloadGenerator {
+    pub fn new(params: &[ParamSchema], seed: u64) -> Self {
+        Self {
+            params: params.to_vec(),
+            rng: SplitMix64(seed),
+        }
+    }
+
+    /// The fixed edge cases followed by random cases, `total` in all (never fewer than the
+    /// fixed cases).
+    pub fn cases(&mut self, total: usize) -> Vec<PropertyCase> {
+        let mut cases = self.edge_cases();
+        while cases.len() < total {
+            let case = self.random_case();
+            cases.push(case);
+        }
+        cases
+    }
+
+    /// Deterministic cases: minimal and full valid payloads, then one targeted violation per
+    /// parameter, then non-object payloads.
+    fn edge_cases(&mut self) -> Vec<PropertyCase> {
+        let mut cases = vec![
+            PropertyCase { kind: CaseKind::Valid, payload: self.valid_payload(false) },
+            PropertyCase { kind: CaseKind::Valid, payload: self.valid_payload(true) },
+        ];
+        let params = self.params.clone();
+        for p in &params {
+            if p.required {
+                let mut payload = self.valid_payload(true);
+                if let Some(obj) = payload.as_object_mut() {
+                    obj.remove(&p.name);
+                }
+                cases.push(PropertyCase { kind: CaseKind::MissingField, payload });
+            }
+            let mut payload = self.valid_payload(true);
+            payload[&p.name] = wrong_value(p.kind);
+            cases.push(PropertyCase { kind: CaseKind::WrongType, payload });
+            payload = self.valid_payload(true);
+            payload[&p.name] = Value::Null;
+            cases.push(PropertyCase { kind: CaseKind::WrongType, payload });
+            if matches!(p.kind, ParamKind::String | ParamKind::Any) {
+                let mut payload = self.valid_payload(true);
+                payload[&p.name] = Value::String("A".repeat(HUGE_STRING_BYTES));
+                cases.push(PropertyCase { kind: CaseKind::HugeString, payload });
+                for sample in UNICODE_SAMPLES {
+                    let mut payload = self.valid_payload(true);
+                    payload[&p.name] = Value::String((*sample).to_string());
+                    cases.push(PropertyCase { kind: CaseKind::Unicode, payload });
+                }
+            }
+        }
+        let mut extra = self.valid_payload(true);
+        extra["__unexpected"] = Value::String("A".repeat(HUGE_STRING_BYTES));
+        cases.push(PropertyCase { kind: CaseKind::ExtraField, payload: extra });
+        for payload in [
+            Value::Object(Map::new()),
+            Value::Null,
+            Value::Array(vec![]),
+            Value::String(String::new()),
+            serde_json::json!(0),
+        ] {
+            cases.push(PropertyCase { kind: CaseKind::NonObject, payload });
+        }
+        cases
+    }
+
+    /// A valid payload mutated in one random way (or left valid).
+    fn random_case(&mut self) -> PropertyCase {
+        let full = self.rng.below(2) == 0;
+        let mut payload = self.valid_payload(full);
+        let target = if self.params.is_empty() {
+            None
+        } else {
+            Some(self.params[self.rng.below(self.params.len())].clone())
+        };
+        let kind = match (self.rng.below(6), target) {
+            (0, Some(p)) => {
+                if let Some(obj) = payload.as_object_mut() {
+                    obj.remove(&p.name);
+                }
+                CaseKind::MissingField
+            }
+            (1, Some(p)) => {
+                payload[&p.name] = wrong_value(p.kind);
+                CaseKind::WrongType
+            }
+            (2, Some(p)) => {
+                let len = HUGE_STRING_BYTES / (1 + self.rng.below(8));
+                payload[&p.name] = Value::String("x".repeat(len));
+                CaseKind::HugeString
+            }
+            (3, Some(p)) => {
+                let sample = UNICODE_SAMPLES[self.rng.below(UNICODE_SAMPLES.len())];
+                payload[&p.name] = Value::String(sample.repeat(1 + self.rng.below(64)));
+                CaseKind::Unicode
+            }
+            (4, _) => {
+                let key = format!("extra_{}", self.rng.below(1000));
+                payload[&key] = self.random_value(ParamKind::Any, 2);
+                CaseKind::ExtraField
+            }
+            _ => CaseKind::Valid,
+        };
+        PropertyCase { kind, payload }
+    }
+
+    /// All required parameters (and, with `full`, the optional ones) with type-correct values.
+    fn valid_payload(&mut self, full: bool) -> Value {
+        let mut obj = Map::new();
+        for p in self.params.clone() {
+            if p.required || full {
+                obj.insert(p.name.clone(), self.random_value(p.kind, 2));
+            }
+        }
+        Value::Object(obj)
+    }
+
+    fn random_value(&mut self, kind: ParamKind, depth: usize) -> Value {
+        match kind {
+            ParamKind::String => {
+                let len = self.rng.below(33);
+                let s: String = (0..len)
+                    .map(|_| (b' ' + self.rng.below(95) as u8) as char)
+                    .collect();
+                Value::String(s)
+            }
+            ParamKind::Integer => {
+                const EDGES: [i64; 5] = [0, -1, 1, i64::MIN, i64::MAX];
+                if self.rng.below(3) == 0 {
+                    serde_json::json!(EDGES[self.rng.below(EDGES.len())])
+                } else {
+                    serde_json::json!(self.rng.next() as i64 % 10_000)
+                }
+            }
+            ParamKind::Number => {
+                const EDGES: [f64; 5] = [0.0, -0.0, 1e308, -1e308, f64::MIN_POSITIVE];
+                if self.rng.below(3) == 0 {
+                    serde_json::json!(EDGES[self.rng.below(EDGES.len())])
+                } else {
+                    serde_json::json!((self.rng.next() % 1_000_000) as f64 / 100.0)
+                }
+            }
+            ParamKind::Boolean => Value::Bool(self.rng.below(2) == 0),
+            ParamKind::Object => {
+                let mut obj = Map::new();
+                if depth > 0 {
+                    for i in 0..self.rng.below(3) {
+                        obj.insert(format!("k{}", i), self.random_value(ParamKind::Any, depth - 1));
+                    }
+                }
+                Value::Object(obj)
+            }
+            ParamKind::Array => {
+                let len = if depth > 0 { self.rng.below(4) } else { 0 };
+                Value::Array(
+                    (0..len)
+                        .map(|_| self.random_value(ParamKind::Any, depth.saturating_sub(1)))
+                        .collect(),
+                )
+            }
+            ParamKind::Any => {
+                const KINDS: [ParamKind; 6] = [
+                    ParamKind::String,
+                    ParamKind::Integer,
+                    ParamKind::Number,
+                    ParamKind::Boolean,
+                    ParamKind::Object,
+                    ParamKind::Array,
+                ];
+                let k = KINDS[self.rng.below(if depth > 0 { 6 } else { 4 })];
+                self.random_value(k, depth)
+            }
+        }
+    }
+}
+
+/// A value of a different JSON type than `kind`.
+fn wrong_value(kind: ParamKind) -> Value {
+    match kind {
+        ParamKind::String => serde_json::json!(12345),
+        ParamKind::Number | ParamKind::Integer => serde_json::json!("not a number"),
+        ParamKind::Boolean => serde_json::json!("true"),
+        ParamKind::Object => serde_json::json!([1, 2, 3]),
+        ParamKind::Array => serde_json::json!({ "not": "an array" }),
+        ParamKind::Any => serde_json::json!([[[[[[]]]]]]),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Harness
+// ---------------------------------------------------------------------------
+
+/// A property the skill broke.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum PropertyViolation {
+    #[error("skill crashed: {0}")]
+    Crash(String),
+    #[error("skill returned invalid JSON: {0}")]
+    InvalidJson(String),
+    #[error("call exceeded {0} ms")]
+    Timeout(u64),
+    #[error("response of {0} bytes exceeds the output bound")]
+    OutputTooLarge(usize),
+    #[error("resident memory grew by {0} bytes during the call")]
+    MemoryExceeded(u64),
+}
+
+/// The first failing payload of a run, shrunk to a minimal reproducer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyFailure {
+    pub case_kind: CaseKind,
+    pub violation: PropertyViolation,
+    /// The generated payload that first failed.
+    pub original: Value,
+    /// The smallest payload found that still fails the same way.
+    pub minimal: Value,
+    /// Successful shrink steps between `original` and `minimal`.
+    pub shrink_steps: usize,
+}
+
+/// Outcome of a property-test run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyReport {
+    pub cases_run: usize,
+    pub seed: u64,
+    pub failure: Option<PropertyFailure>,
+}
+
+impl PropertyReport {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// One-line summary for validation logs and pulses.
+    pub fn summary(&self) -> String {
+        match &self.failure {
+            None => format!("{} property cases passed (seed {})", self.cases_run, self.seed),
+            Some(f) => format!(
+                "property case #{} ({:?}) failed: {}; minimal payload: {}",
+                self.cases_run,
+                f.case_kind,
+                f.violation,
+                truncate(&f.minimal.to_string(), 300)
+            ),
+        }
+    }
+}
+
+/// Runs generated payloads against a skill probe and shrinks the first failure.
+pub struct PropertyHarness {
+    params: Vec<ParamSchema>,
+    config: PropertyConfig,
+    memory: MemoryProbe,
+}
+
+impl PropertyHarness {
+    /// Memory growth is measured on this process; see [`Self::with_memory_probe`].
+    pub fn new(params: &[ParamSchema], config: PropertyConfig) -> Self {
+        Self {
+            params: params.to_vec(),
+            config,
+            memory: Arc::new(resident_memory_bytes),
+        }
+    }
+
+    /// Measures memory growth with `memory`, e.g. on the worker process a sandboxed skill runs in.
+    pub fn with_memory_probe(mut self, memory: MemoryProbe) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Blocking: run every case until the first failure. Each call runs on its own thread, so a
+    /// call that exceeds the timeout is abandoned rather than waited on.
+    pub fn run(&self, probe: &SkillProbe) -> PropertyReport {
+        let cases = PayloadGenerator::new(&self.params, self.config.seed).cases(self.config.cases);
+        let total = cases.len();
+        for (i, case) in cases.into_iter().enumerate() {
+            if let Err(violation) = self.check(probe, &case.payload) {
+                let (minimal, shrink_steps) = self.shrink(probe, &case.payload, &violation);
+                return PropertyReport {
+                    cases_run: i + 1,
+                    seed: self.config.seed,
+                    failure: Some(PropertyFailure {
+                        case_kind: case.kind,
+                        violation,
+                        original: case.payload,
+                        minimal,
+                        shrink_steps,
+                    }),
+                };
+            }
+        }
+        PropertyReport {
+            cases_run: total,
+            seed: self.config.seed,
+            failure: None,
+        }
+    }
+
+    /// Run one payload and check every property.
+    pub fn check(&self, probe: &SkillProbe, payload: &Value) -> Result<(), PropertyViolation> {
+        let (tx, rx) = mpsc::channel();
+        let probe = Arc::clone(probe);
+        let memory = Arc::clone(&self.memory);
+        let input = payload.clone();
+        std::thread::spawn(move || {
+            let mem_before = memory();
+            let started = Instant::now();
+            let result = catch_unwind(AssertUnwindSafe(|| probe(&input)));
+            let elapsed = started.elapsed();
+            let growth = match (mem_before, memory()) {
+                (Some(before), Some(after)) => after.saturating_sub(before),
+                _ => 0,
+            };
+            let _ = tx.send((result, elapsed, growth));
+        });
+
+        let timeout_ms = self.config.call_timeout.as_millis() as u64;
+        let (result, elapsed, growth) = rx
+            .recv_timeout(self.config.call_timeout)
+            .map_err(|_| PropertyViolation::Timeout(timeout_ms))?;
+        let output = match result {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return Err(PropertyViolation::Crash(truncate(&e, 500))),
+            Err(panic) => {
+                let msg = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "panic".to_string());
+                return Err(PropertyViolation::Crash(format!("panicked: {}", msg)));
+            }
+        };
+        if elapsed > self.config.call_timeout {
+            return Err(PropertyViolation::Timeout(timeout_ms));
+        }
+        if output.len() > self.config.max_output_bytes {
+            return Err(PropertyViolation::OutputTooLarge(output.len()));
+        }
+        serde_json::from_str::<Value>(&output)
+            .map_err(|e| PropertyViolation::InvalidJson(e.to_string()))?;
+        if growth > self.config.max_memory_growth_bytes {
+            return Err(PropertyViolation::MemoryExceeded(growth));
+        }
+        Ok(())
+    }
+
+    /// Greedy shrinking: repeatedly take the first simpler candidate that still fails with the
+    /// same kind of violation, within `max_shrink_steps` re-runs.
+    fn shrink(&self, probe: &SkillProbe, payload: &Value, violation: &PropertyViolation) -> (Value, usize) {
+        let mut current = payload.clone();
+        let mut steps = 0;
+        let mut budget = self.config.max_shrink_steps;
+        'outer: while budget > 0 {
+            for candidate in shrink_candidates(&current) {
+                if budget == 0 {
+                    break 'outer;
+                }
+                budget -= 1;
+                if let Err(v) = self.check(probe, &candidate) {
+                    if std::mem::discriminant(&v) == std::mem::discriminant(violation) {
+                        current = candidate;
+                        steps += 1;
+                        continue 'outer;
+                    }
+                }
+            }
+            break;
+        }
+        (current, steps)
+    }
+}
+
+/// Strictly simpler variants of `value`, simplest first.
+fn shrink_candidates(value: &Value) -> Vec<Value> {
+    let mut out = Vec::new();
+    match value {
+        Value::Object(obj) => {
+            for key in obj.keys() {
+                let mut smaller = obj.clone();
+                smaller.remove(key);
+                out.push(Value::Object(smaller));
+            }
+            for (key, v) in obj {
+                for c in shrink_candidates(v) {
+                    let mut smaller = obj.clone();
+                    smaller.insert(key.clone(), c);
+                    out.push(Value::Object(smaller));
+                }
+            }
+        }
+        Value::Array(items) => {
+            if !items.is_empty() {
+                out.push(Value::Array(vec![]));
+                if items.len() > 1 {
+                    out.push(Value::Array(items[..items.len() / 2].to_vec()));
+                }
+                for i in 0..items.len().min(8) {
+                    let mut smaller = items.clone();
+                    smaller.remove(i);
+                    out.push(Value::Array(smaller));
+                }
+            }
+        }
+        Value::String(s) => {
+            if !s.is_empty() {
+                out.push(Value::String(String::new()));
+                let chars = s.chars().count();
+                if chars > 1 {
+                    out.push(Value::String(s.chars().take(chars / 2).collect()));
+                    out.push(Value::String(s.chars().skip(chars / 2).collect()));
+                }
+                if !s.is_ascii() {
+                    out.push(Value::String(s.chars().filter(char::is_ascii).collect()));
+                }
+            }
+        }
+        Value::Number(n) => {
+            if n.as_f64() != Some(0.0) {
+                out.push(serde_json::json!(0));
+            }
+        }
+        Value::Bool(true) => out.push(Value::Bool(false)),
+        Value::Bool(false) | Value::Null => {}
+    }
+    out
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        format!("{}…", s.chars().take(max_chars).collect::<String>())
+    }
+}
+
+/// Resident set size of this process, where the platform exposes it cheaply.
+#[cfg(target_os = "linux")]
+fn resident_memory_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * page_size.max(0) as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_memory_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    fn params() -> Vec<ParamSchema> {
+        vec![
+            ParamSchema::new("location", "string", true),
+            ParamSchema::new("days", "integer", false),
+        ]
+    }
+
+    fn probe(f: impl Fn(&Value) -> Result<String, String> + Send + Sync + 'static) -> SkillProbe {
+        Arc::new(f)
+    }
+
+    #[test]
+    fn generator_covers_valid_and_adversarial_cases() {
+        let cases = PayloadGenerator::new(&params(), 7).cases(40);
+        assert_eq!(cases.len(), 40);
+        assert!(cases[0].payload.get("location").is_some_and(Value::is_string));
+        for kind in [
+            CaseKind::MissingField,
+            CaseKind::WrongType,
+            CaseKind::HugeString,
+            CaseKind::Unicode,
+            CaseKind::ExtraField,
+            CaseKind::NonObject,
+        ] {
+            assert!(cases.iter().any(|c| c.kind == kind), "missing {:?}", kind);
+        }
+        let again = PayloadGenerator::new(&params(), 7).cases(40);
+        assert_eq!(
+            serde_json::to_string(&cases).unwrap(),
+            serde_json::to_string(&again).unwrap(),
+            "same seed must give the same payloads"
+        );
+    }
+
+    #[test]
+    fn well_behaved_skill_passes() {
+        let harness = PropertyHarness::new(&params(), PropertyConfig::default());
+        let report = harness.run(&probe(|payload| {
+            let location = payload.get("location").and_then(Value::as_str);
+            Ok(match location {
+                Some(l) => serde_json::json!({ "status": "ok", "len": l.len() }).to_string(),
+                None => serde_json::json!({ "error": "missing location" }).to_string(),
+            })
+        }));
+        assert!(report.passed(), "{}", report.summary());
+        assert!(report.cases_run >= DEFAULT_CASES);
+    }
+
+    #[test]
+    fn failure_is_shrunk_to_minimal_payload() {
+        let harness = PropertyHarness::new(&params(), PropertyConfig::default());
+        // Panics whenever `location` holds a non-ASCII string.
+        let report = harness.run(&probe(|payload| {
+            let loc = payload.get("location").and_then(Value::as_str).unwrap_or("");
+            if !loc.is_ascii() {
+                panic!("byte index out of range");
+            }
+            Ok("{}".to_string())
+        }));
+        let failure = report.failure.expect("unicode payload should fail");
+        assert_eq!(failure.case_kind, CaseKind::Unicode);
+        assert!(matches!(failure.violation, PropertyViolation::Crash(_)));
+        let minimal = failure.minimal.as_object().unwrap();
+        assert_eq!(minimal.len(), 1, "optional fields shrunk away: {:?}", minimal);
+        let loc = minimal["location"].as_str().unwrap();
+        assert_eq!(loc.chars().count(), 1, "string shrunk to one char: {:?}", loc);
+        assert!(failure.shrink_steps > 0);
+    }
+
+    #[test]
+    fn memory_growth_is_read_from_the_memory_probe() {
+        let rss = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let reading = Arc::clone(&rss);
+        let harness = PropertyHarness::new(&params(), PropertyConfig::default())
+            .with_memory_probe(Arc::new(move || Some(reading.load(Ordering::SeqCst))));
+        let growing = Arc::clone(&rss);
+        let violation = harness.check(
+            &probe(move |_| {
+                growing.fetch_add(128 * 1024 * 1024, Ordering::SeqCst);
+                Ok("{}".into())
+            }),
+            &serde_json::json!({ "location": "x" }),
+        );
+        assert_eq!(violation, Err(PropertyViolation::MemoryExceeded(128 * 1024 * 1024)));
+    }
+
+    #[test]
+    fn invalid_json_and_timeouts_are_violations() {
+        let config = PropertyConfig {
+            call_timeout: Duration::from_millis(50),
+            ..PropertyConfig::default()
+        };
+        let harness = PropertyHarness::new(&params(), config);
+        let payload = serde_json::json!({ "location": "x" });
+        assert!(matches!(
+            harness.check(&probe(|_| Ok("not json".into())), &payload),
+            Err(PropertyViolation::InvalidJson(_))
+        ));
+        assert_eq!(
+            harness.check(
+                &probe(|_| {
+                    std::thread::sleep(Duration::from_millis(200));
+                    Ok("{}".into())
+                }),
+                &payload
+            ),
+            Err(PropertyViolation::Timeout(50))
+        );
+    }
+}
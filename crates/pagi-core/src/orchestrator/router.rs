@@ -0,0 +1,594 @@
+//! Semantic MoE gating: embedding-centroid classification with keyword fallback.
+//!
+//! Each expert (OpenRouter, LanceDB, SystemTool) gets a centroid built from labeled examples
+//! stored in KB-05 (Techne) under `moe/examples/`. A prompt is routed to the nearest centroid when
+//! the softmax confidence clears the threshold; otherwise the keyword heuristic
+//! (`route_to_experts` / `Gater::route_with_context`) decides, exactly as before training data
+//! existed. Every decision is logged to KB-08 (Soma) under `moe/decisions/`, together with any
+//! correction (explicit feedback, an empty expert result, or the user rephrasing the same prompt),
+//! so the router can be re-fit offline from `export_training_set`.
+//!
+//! The embedder is pluggable ([`TextEmbedder`]); the default [`HashingEmbedder`] is local and
+//! deterministic (feature-hashed words, bigrams and character trigrams), so routing never needs a
+//! network call.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use super::{route_to_experts, Gater, MoEExpert};
+use crate::knowledge::{KbType, KnowledgeStore};
+
+/// KB-05 key prefix for labeled routing examples.
+pub const MOE_EXAMPLE_PREFIX: &str = "moe/examples/";
+/// KB-08 key prefix for routing decisions (and their corrections).
+pub const MOE_DECISION_PREFIX: &str = "moe/decisions/";
+/// Default confidence below which the keyword heuristic decides (PAGI_MOE_ROUTER_THRESHOLD).
+pub const DEFAULT_ROUTER_THRESHOLD: f32 = 0.6;
+
+/// Experts that can be trained (Unmatched is never a label).
+const TRAINABLE_EXPERTS: [MoEExpert; 3] = [MoEExpert::OpenRouter, MoEExpert::LanceDB, MoEExpert::SystemTool];
+/// Softmax temperature over cosine similarities.
+const SOFTMAX_TEMPERATURE: f32 = 0.05;
+/// Below this raw similarity the prompt is out of distribution for every centroid.
+const MIN_SIMILARITY: f32 = 0.1;
+/// A follow-up this similar to the previous prompt within the window counts as a rephrase.
+const REPHRASE_SIMILARITY: f32 = 0.75;
+const REPHRASE_WINDOW: Duration = Duration::from_secs(300);
+/// Sessions remembered for rephrase detection; expired ones are swept first, then the oldest.
+const DEFAULT_MAX_SESSIONS: usize = 10_000;
+/// Max characters of the prompt kept in a decision record.
+const MAX_LOGGED_INPUT_CHARS: usize = 2000;
+
+/// Turns text into a fixed-size vector. Implementations should return L2-normalized vectors.
+pub trait TextEmbedder: Send + Sync {
+    fn name(&self) -> &str;
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Local feature-hashing embedder: lowercase word unigrams and bigrams plus character trigrams,
+/// hashed (FNV-1a, signed) into `dims` buckets and L2-normalized.
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dims: usize,
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self { dims: 512 }
+    }
+}
+
+impl HashingEmbedder {
+    pub fn new(dims: usize) -> Self {
+        Self { dims: dims.max(16) }
+    }
+
+    fn add(&self, v: &mut [f32], feature: &str, weight: f32) {
+        let h = fnv1a(feature.as_bytes());
+        let idx = (h % self.dims as u64) as usize;
+        let sign = if (h >> 63) == 0 { 1.0 } else { -1.0 };
+        v[idx] += sign * weight;
+    }
+}
+
+impl TextEmbedder for HashingEmbedder {
+    fn name(&self) -> &str {
+        "hashing"
+    }
+
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut v = vec![0.0f32; self.dims];
+        let lower = text.to_lowercase();
+        let words: Vec<&str> = lower
+            .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '.' && c != '_')
+            .filter(|w| !w.is_empty())
+            .collect();
+        for w in &words {
+            self.add(&mut v, &format!("w:{}", w), 1.0);
+            let padded: Vec<char> = format!("^{}$", w).chars().collect();
+            for tri in padded.windows(3) {
+                self.add(&mut v, &format!("c:{}", tri.iter().collect::<String>()), 0.5);
+            }
+        }
+        for pair in words.windows(2) {
+            self.add(&mut v, &format!("b:{} {}", pair[0], pair[1]), 1.0);
+        }
+        normalize(&mut v);
+        v
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
+
+fn normalize(v: &mut [f32]) {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Labeled prompt used to train the router (KB-05).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingExample {
+    pub text: String,
+    /// Expert label: "openrouter" | "lancedb" | "system_tool".
+    pub expert: String,
+    #[serde(default)]
+    pub created_at_ms: i64,
+    /// "seed", "feedback", etc.
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// How a decision was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingMethod {
+    /// Nearest embedding centroid with confidence >= threshold.
+    Centroid,
+    /// Keyword heuristic (no centroids, low confidence, or out of distribution).
+    Keyword,
+}
+
+/// Implicit or explicit signal that a decision was wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RoutingCorrection {
+    /// The user named the right expert (UI feedback).
+    Explicit { expert: String },
+    /// The chosen expert had nothing useful (e.g. no KB matches, no reflex for a "system" prompt).
+    EmptyResult,
+    /// The user re-asked nearly the same prompt shortly after.
+    Rephrased { next_decision_id: String },
+}
+
+/// One routed prompt, persisted to KB-08.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingDecision {
+    pub id: String,
+    pub session: String,
+    pub input: String,
+    pub expert: String,
+    pub method: RoutingMethod,
+    /// Softmax probability of the chosen centroid (0 for keyword decisions without centroids).
+    pub confidence: f32,
+    /// Per-expert softmax scores (empty without centroids).
+    #[serde(default)]
+    pub scores: HashMap<String, f32>,
+    pub embedder: String,
+    pub timestamp_ms: i64,
+    #[serde(default)]
+    pub corrections: Vec<RoutingCorrection>,
+}
+
+impl RoutingDecision {
+    pub fn expert(&self) -> MoEExpert {
+        MoEExpert::from_label(&self.expert).unwrap_or(MoEExpert::OpenRouter)
+    }
+}
+
+struct RecentRoute {
+    decision_id: String,
+    embedding: Vec<f32>,
+    at: Instant,
+}
+
+/// Embedding-centroid router with keyword fallback.
+pub struct SemanticRouter {
+    embedder: Arc<dyn TextEmbedder>,
+    threshold: f32,
+    min_examples: usize,
+    centroids: RwLock<HashMap<MoEExpert, Vec<f32>>>,
+    /// Last decision per session, for rephrase detection.
+    recent: DashMap<String, RecentRoute>,
+    max_sessions: usize,
+}
+
+impl Default for SemanticRouter {
+    fn default() -> Self {
+        Self::new(Arc::new(HashingEmbedder::default()))
+    }
+}
+
+impl std::fmt::Debug for SemanticRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SemanticRouter")
+            .field("embedder", &self.embedder.name())
+            .field("threshold", &self.threshold)
+            .field("trained_experts", &self.trained_experts())
+            .finish()
+    }
+}
+
+impl SemanticRouter {
+    pub fn new(embedder: Arc<dyn TextEmbedder>) -> Self {
+        Self {
+            embedder,
+            threshold: DEFAULT_ROUTER_THRESHOLD,
+            min_examples: 3,
+            centroids: RwLock::new(HashMap::new()),
+            recent: DashMap::new(),
+            max_sessions: DEFAULT_MAX_SESSIONS,
+        }
+    }
+
+    /// Default router with the threshold from PAGI_MOE_ROUTER_THRESHOLD.
+    pub fn from_env() -> Self {
+        let threshold = std::env::var("PAGI_MOE_ROUTER_THRESHOLD")
+            .ok()
+            .and_then(|s| s.trim().parse::<f32>().ok())
+            .unwrap_or(DEFAULT_ROUTER_THRESHOLD);
+        Self::default().with_threshold(threshold)
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Minimum labeled examples before an expert gets a centroid.
+    pub fn with_min_examples(mut self, n: usize) -> Self {
+        self.min_examples = n.max(1);
+        self
+    }
+
+    /// Cap on sessions kept for rephrase detection.
+    pub fn with_max_sessions(mut self, n: usize) -> Self {
+        self.max_sessions = n.max(1);
+        self
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Experts that currently have a centroid.
+    pub fn trained_experts(&self) -> Vec<MoEExpert> {
+        let mut experts: Vec<MoEExpert> = self.centroids.read().map(|c| c.keys().copied().collect()).unwrap_or_default();
+        experts.sort_by_key(|e| e.as_str());
+        experts
+    }
+
+    /// Rebuild centroids from labeled examples. Unknown labels are ignored.
+    pub fn fit(&self, examples: &[RoutingExample]) {
+        let mut sums: HashMap<MoEExpert, (Vec<f32>, usize)> = HashMap::new();
+        for ex in examples {
+            let Some(expert) = MoEExpert::from_label(&ex.expert).filter(|e| TRAINABLE_EXPERTS.contains(e)) else {
+                continue;
+            };
+            let v = self.embedder.embed(&ex.text);
+            let (sum, n) = sums.entry(expert).or_insert_with(|| (vec![0.0; v.len()], 0));
+            if sum.len() == v.len() {
+                sum.iter_mut().zip(&v).for_each(|(s, x)| *s += x);
+                *n += 1;
+            }
+        }
+        let centroids = sums
+            .into_iter()
+            .filter(|(_, (_, n))| *n >= self.min_examples)
+            .map(|(expert, (mut sum, _))| {
+                normalize(&mut sum);
+                (expert, sum)
+            })
+            .collect();
+        if let Ok(mut guard) = self.centroids.write() {
+            *guard = centroids;
+        }
+    }
+
+    /// Fit from the examples stored in KB-05. Returns how many examples were used.
+    pub fn fit_from_kb(&self, store: &KnowledgeStore) -> usize {
+        let examples = load_examples(store);
+        self.fit(&examples);
+        examples.len()
+    }
+
+    /// Route a prompt for `session`. `local_ctx` (Sparse mode) is only used by the keyword fallback.
+    /// When the prompt rephrases the session's previous one, the returned correction should be
+    /// recorded against that earlier decision (see [`record_correction`]).
+    pub fn route(
+        &self,
+        session: &str,
+        local_ctx: Option<&str>,
+        input: &str,
+    ) -> (RoutingDecision, Option<(String, RoutingCorrection)>) {
+        let embedding = self.embedder.embed(input);
+        let scores = self.score(&embedding);
+
+        let best = scores
+            .iter()
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+            .map(|(e, (sim, p))| (*e, *sim, *p));
+        let (expert, method, confidence) = match best {
+            Some((expert, sim, p)) if scores.len() >= 2 && sim >= MIN_SIMILARITY && p >= self.threshold => {
+                (expert, RoutingMethod::Centroid, p)
+            }
+            _ => {
+                let expert = match local_ctx {
+                    Some(ctx) => Gater::route_with_context(ctx, input),
+                    None => route_to_experts(input),
+                };
+                let p = scores.get(&expert).map(|(_, p)| *p).unwrap_or(0.0);
+                (expert, RoutingMethod::Keyword, p)
+            }
+        };
+
+        let now_ms = Utc::now().timestamp_millis();
+        let decision = RoutingDecision {
+            id: format!("{:016x}-{}", now_ms, &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            session: session.to_string(),
+            input: input.chars().take(MAX_LOGGED_INPUT_CHARS).collect(),
+            expert: expert.as_str().to_string(),
+            method,
+            confidence,
+            scores: scores.iter().map(|(e, (_, p))| (e.as_str().to_string(), *p)).collect(),
+            embedder: self.embedder.name().to_string(),
+            timestamp_ms: now_ms,
+            corrections: Vec::new(),
+        };
+
+        self.evict_recent(session);
+        let previous = self.recent.insert(
+            session.to_string(),
+            RecentRoute {
+                decision_id: decision.id.clone(),
+                embedding: embedding.clone(),
+                at: Instant::now(),
+            },
+        );
+        let rephrase = previous
+            .filter(|p| p.at.elapsed() <= REPHRASE_WINDOW && cosine(&p.embedding, &embedding) >= REPHRASE_SIMILARITY)
+            .map(|p| {
+                (
+                    p.decision_id,
+                    RoutingCorrection::Rephrased {
+                        next_decision_id: decision.id.clone(),
+                    },
+                )
+            });
+        (decision, rephrase)
+    }
+
+    /// Make room for `session` in `recent`: drop entries past the rephrase window and, if that is
+    /// not enough, the least recently routed sessions.
+    fn evict_recent(&self, session: &str) {
+        if self.recent.len() < self.max_sessions || self.recent.contains_key(session) {
+            return;
+        }
+        self.recent.retain(|_, r| r.at.elapsed() <= REPHRASE_WINDOW);
+        let excess = (self.recent.len() + 1).saturating_sub(self.max_sessions);
+        if excess == 0 {
+            return;
+        }
+        let mut by_age: Vec<(Instant, String)> = self.recent.iter().map(|r| (r.at, r.key().clone())).collect();
+        by_age.sort();
+        for (_, key) in by_age.into_iter().take(excess) {
+            self.recent.remove(&key);
+        }
+    }
+
+    /// expert -> (cosine similarity, softmax probability)
+    fn score(&self, embedding: &[f32]) -> HashMap<MoEExpert, (f32, f32)> {
+        let guard = match self.centroids.read() {
+            Ok(g) => g,
+            Err(_) => return HashMap::new(),
+        };
+        let sims: Vec<(MoEExpert, f32)> = guard.iter().map(|(e, c)| (*e, cosine(c, embedding))).collect();
+        let max = sims.iter().map(|(_, s)| *s).fold(f32::MIN, f32::max);
+        let exps: Vec<f32> = sims.iter().map(|(_, s)| ((s - max) / SOFTMAX_TEMPERATURE).exp()).collect();
+        let total: f32 = exps.iter().sum();
+        sims.into_iter()
+            .zip(exps)
+            .map(|((e, s), x)| (e, (s, if total > 0.0 { x / total } else { 0.0 })))
+            .collect()
+    }
+}
+
+/// Store a labeled example in KB-05.
+pub fn store_example(store: &KnowledgeStore, example: &RoutingExample) -> Result<String, sled::Error> {
+    let key = format!("{}{}", MOE_EXAMPLE_PREFIX, uuid::Uuid::new_v4());
+    let bytes = serde_json::to_vec(example).unwrap_or_default();
+    store.insert(KbType::Techne.slot_id(), &key, &bytes)?;
+    Ok(key)
+}
+
+/// All labeled examples from KB-05.
+pub fn load_examples(store: &KnowledgeStore) -> Vec<RoutingExample> {
+    store
+        .scan_kv(KbType::Techne.slot_id())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(k, _)| k.starts_with(MOE_EXAMPLE_PREFIX))
+        .filter_map(|(_, v)| serde_json::from_slice(&v).ok())
+        .collect()
+}
+
+/// Persist a decision to KB-08.
+pub fn record_decision(store: &KnowledgeStore, decision: &RoutingDecision) -> Result<(), sled::Error> {
+    let key = format!("{}{}", MOE_DECISION_PREFIX, decision.id);
+    store.insert(KbType::Soma.slot_id(), &key, &serde_json::to_vec(decision).unwrap_or_default())?;
+    Ok(())
+}
+
+/// Load one decision from KB-08.
+pub fn get_decision(store: &KnowledgeStore, decision_id: &str) -> Option<RoutingDecision> {
+    let key = format!("{}{}", MOE_DECISION_PREFIX, decision_id);
+    store
+        .get(KbType::Soma.slot_id(), &key)
+        .ok()
+        .flatten()
+        .and_then(|b| serde_json::from_slice(&b).ok())
+}
+
+/// Append a correction to a logged decision. Returns false when the decision is unknown.
+pub fn record_correction(
+    store: &KnowledgeStore,
+    decision_id: &str,
+    correction: RoutingCorrection,
+) -> Result<bool, sled::Error> {
+    let Some(mut decision) = get_decision(store, decision_id) else {
+        return Ok(false);
+    };
+    if !decision.corrections.contains(&correction) {
+        decision.corrections.push(correction);
+        record_decision(store, &decision)?;
+    }
+    Ok(true)
+}
+
+/// Most recent decisions (newest first), for offline analysis.
+pub fn recent_decisions(store: &KnowledgeStore, limit: usize) -> Vec<RoutingDecision> {
+    let mut decisions: Vec<RoutingDecision> = store
+        .scan_kv(KbType::Soma.slot_id())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(k, _)| k.starts_with(MOE_DECISION_PREFIX))
+        .filter_map(|(_, v)| serde_json::from_slice(&v).ok())
+        .collect();
+    decisions.sort_by(|a, b| b.id.cmp(&a.id));
+    decisions.truncate(limit);
+    decisions
+}
+
+/// Labeled training set for an offline re-fit: the KB-05 examples plus every logged decision the
+/// user explicitly corrected (labeled with the corrected expert).
+pub fn export_training_set(store: &KnowledgeStore) -> Vec<RoutingExample> {
+    let mut examples = load_examples(store);
+    for decision in recent_decisions(store, usize::MAX) {
+        let explicit = decision.corrections.iter().rev().find_map(|c| match c {
+            RoutingCorrection::Explicit { expert } => Some(expert.clone()),
+            _ => None,
+        });
+        if let Some(expert) = explicit {
+            examples.push(RoutingExample {
+                text: decision.input,
+                expert,
+                created_at_ms: decision.timestamp_ms,
+                source: Some("correction".to_string()),
+            });
+        }
+    }
+    examples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ex(text: &str, expert: MoEExpert) -> RoutingExample {
+        RoutingExample {
+            text: text.to_string(),
+            expert: expert.as_str().to_string(),
+            created_at_ms: 0,
+            source: None,
+        }
+    }
+
+    fn trained() -> SemanticRouter {
+        let router = SemanticRouter::default().with_threshold(0.5);
+        router.fit(&[
+            ex("show me cpu usage and memory stats", MoEExpert::SystemTool),
+            ex("list the files in this directory", MoEExpert::SystemTool),
+            ex("run the shell command to check disk space", MoEExpert::SystemTool),
+            ex("what did we save about the ranch lease", MoEExpert::LanceDB),
+            ex("find my notes about the ranch budget", MoEExpert::LanceDB),
+            ex("what do my saved notes say about the lease", MoEExpert::LanceDB),
+            ex("write a poem about the ocean", MoEExpert::OpenRouter),
+            ex("help me brainstorm a story about dragons", MoEExpert::OpenRouter),
+            ex("compose a short poem for my friend", MoEExpert::OpenRouter),
+        ]);
+        router
+    }
+
+    #[test]
+    fn untrained_router_uses_keyword_heuristic() {
+        let router = SemanticRouter::default();
+        let (d, _) = router.route("s", None, "git status please");
+        assert_eq!(d.method, RoutingMethod::Keyword);
+        assert_eq!(d.expert(), MoEExpert::SystemTool);
+    }
+
+    #[test]
+    fn centroids_route_confident_prompts() {
+        let router = trained();
+        assert_eq!(router.trained_experts().len(), 3);
+        let (d, _) = router.route("s", None, "write a short poem about dragons");
+        assert_eq!(d.method, RoutingMethod::Centroid);
+        assert_eq!(d.expert(), MoEExpert::OpenRouter);
+        assert!(d.confidence >= 0.5);
+        let (d, _) = router.route("t", None, "find my saved notes about the lease");
+        assert_eq!(d.expert(), MoEExpert::LanceDB);
+    }
+
+    #[test]
+    fn low_confidence_falls_through_to_keywords() {
+        let router = trained().with_threshold(1.0);
+        let (d, _) = router.route("s", None, "git status");
+        assert_eq!(d.method, RoutingMethod::Keyword);
+        assert_eq!(d.expert(), MoEExpert::SystemTool);
+    }
+
+    #[test]
+    fn rephrase_is_reported_against_previous_decision() {
+        let router = trained();
+        let (first, none) = router.route("s", None, "find my notes about the ranch lease");
+        assert!(none.is_none());
+        let (second, rephrase) = router.route("s", None, "find my notes about the ranch lease please");
+        let (prev_id, correction) = rephrase.expect("rephrase");
+        assert_eq!(prev_id, first.id);
+        assert_eq!(correction, RoutingCorrection::Rephrased { next_decision_id: second.id });
+        let (_, other) = router.route("s", None, "write a poem about the ocean");
+        assert!(other.is_none());
+    }
+
+    #[test]
+    fn recent_sessions_are_capped_oldest_first() {
+        let router = trained().with_max_sessions(2);
+        router.route("a", None, "find my notes about the ranch lease");
+        router.route("b", None, "git status");
+        router.route("c", None, "write a poem about the ocean");
+        assert_eq!(router.recent.len(), 2);
+        assert!(!router.recent.contains_key("a"));
+        // A known session is updated in place without evicting anyone.
+        router.route("b", None, "git log");
+        assert!(router.recent.contains_key("c"));
+        let (_, rephrase) = router.route("a", None, "find my notes about the ranch lease please");
+        assert!(rephrase.is_none());
+    }
+
+    #[test]
+    fn decisions_and_corrections_roundtrip_through_kb() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = KnowledgeStore::open_path(dir.path()).unwrap();
+        store_example(&store, &ex("check disk space", MoEExpert::SystemTool)).unwrap();
+        let router = SemanticRouter::default();
+        let (d, _) = router.route("s", None, "tell me about rust lifetimes");
+        record_decision(&store, &d).unwrap();
+        assert!(record_correction(&store, &d.id, RoutingCorrection::Explicit { expert: "lancedb".into() }).unwrap());
+        assert!(!record_correction(&store, "missing", RoutingCorrection::EmptyResult).unwrap());
+
+        let set = export_training_set(&store);
+        assert_eq!(set.len(), 2);
+        assert!(set.iter().any(|e| e.expert == "lancedb" && e.source.as_deref() == Some("correction")));
+        assert_eq!(recent_decisions(&store, 10)[0].corrections.len(), 1);
+    }
+}
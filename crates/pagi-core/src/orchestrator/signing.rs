@@ -0,0 +1,394 @@
+//! Ed25519 signatures for Tier 1 (core) skill manifest entries.
+//!
+//! "Signed by the User" is enforced here: every entry in `core/manifest.json` must carry a
+//! signature from a trusted key over its skill id, KB layers, capabilities and (for Forge-built
+//! skills) the SHA-256 of the compiled artifact. Entries that are unsigned, signed by an unknown
+//! key, or whose artifact no longer matches are downgraded to Import on load. Promotion from the
+//! ephemeral tier is only accepted with a valid signature.
+//!
+//! The user's signing key is a 32-byte seed stored hex-encoded at `PAGI_SKILL_SIGNING_KEY_PATH`
+//! (default `./data/keys/skill_signing.key`) and is managed with `pagi-gateway --sign-skill`.
+//! Its public half is trusted automatically; extra public keys can be listed (hex, comma
+//! separated) in `PAGI_SKILL_TRUSTED_KEYS`.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::skills::SkillManifestEntry;
+
+/// Env var for the signing key path.
+pub const SKILL_SIGNING_KEY_PATH_ENV: &str = "PAGI_SKILL_SIGNING_KEY_PATH";
+/// Env var listing additional trusted public keys (hex, comma separated).
+pub const SKILL_TRUSTED_KEYS_ENV: &str = "PAGI_SKILL_TRUSTED_KEYS";
+/// Default signing key location (relative to the gateway working directory).
+pub const DEFAULT_SKILL_SIGNING_KEY_PATH: &str = "./data/keys/skill_signing.key";
+
+/// Domain separator so a manifest signature can never be replayed as another kind of message.
+const SIGNING_CONTEXT: &[u8] = b"pagi-skill-manifest-v1\n";
+
+/// Signature attached to a manifest entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillSignature {
+    /// Hex-encoded Ed25519 public key that produced the signature.
+    pub key_id: String,
+    /// Hex-encoded Ed25519 signature over [`signing_payload`].
+    pub signature: String,
+}
+
+/// Why a manifest entry failed verification.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("unsigned")]
+    Missing,
+    #[error("signed by untrusted key {0}")]
+    UntrustedKey(String),
+    #[error("malformed signature: {0}")]
+    Malformed(String),
+    #[error("signature does not match manifest entry")]
+    Invalid,
+    #[error("artifact hash mismatch (expected {expected}, found {actual})")]
+    ArtifactMismatch { expected: String, actual: String },
+    #[error("artifact unreadable: {0}")]
+    ArtifactUnreadable(String),
+}
+
+#[derive(Serialize)]
+struct SignedFields<'a> {
+    skill_id: &'a str,
+    kb_layers_allowed: Vec<u8>,
+    capabilities: &'a super::capabilities::SkillCapabilities,
+    artifact_sha256: Option<&'a str>,
+}
+
+/// Canonical bytes covered by a signature. The description, artifact path and the signature
+/// itself are excluded; KB layers are sorted so reordering the JSON array does not invalidate it.
+pub fn signing_payload(entry: &SkillManifestEntry) -> Vec<u8> {
+    let mut kb_layers_allowed = entry.kb_layers_allowed.clone();
+    kb_layers_allowed.sort_unstable();
+    kb_layers_allowed.dedup();
+    let fields = SignedFields {
+        skill_id: &entry.skill_id,
+        kb_layers_allowed,
+        capabilities: &entry.capabilities,
+        artifact_sha256: entry.artifact_sha256.as_deref(),
+    };
+    let mut out = SIGNING_CONTEXT.to_vec();
+    out.extend(serde_json::to_vec(&fields).unwrap_or_default());
+    out
+}
+
+/// Hex SHA-256 of a file (Forge artifacts).
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// The user's local signing key.
+pub struct SkillSigner {
+    key: SigningKey,
+}
+
+impl std::fmt::Debug for SkillSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkillSigner").field("key_id", &self.key_id()).finish()
+    }
+}
+
+impl SkillSigner {
+    /// Key path from `PAGI_SKILL_SIGNING_KEY_PATH`, or the default under `./data/keys`.
+    pub fn default_key_path() -> PathBuf {
+        std::env::var(SKILL_SIGNING_KEY_PATH_ENV)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SKILL_SIGNING_KEY_PATH))
+    }
+
+    /// Fresh key from the OS RNG.
+    pub fn generate() -> std::io::Result<Self> {
+        let mut seed = [0u8; 32];
+        getrandom::getrandom(&mut seed).map_err(std::io::Error::other)?;
+        Ok(Self { key: SigningKey::from_bytes(&seed) })
+    }
+
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self { key: SigningKey::from_bytes(&seed) }
+    }
+
+    /// Load a hex-encoded seed from `path`.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let bytes = hex::decode(text.trim())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+        let seed: [u8; 32] = bytes.try_into().map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: expected 32-byte seed", path.display()))
+        })?;
+        Ok(Self::from_seed(seed))
+    }
+
+    /// Load the key at `path`, creating it (mode 0600 on Unix) when missing.
+    /// Returns the signer and whether a new key was created.
+    pub fn load_or_create(path: &Path) -> std::io::Result<(Self, bool)> {
+        if path.exists() {
+            return Ok((Self::load(path)?, false));
+        }
+        let signer = Self::generate()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut opts = std::fs::OpenOptions::new();
+        opts.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            opts.mode(0o600);
+        }
+        let mut file = opts.open(path)?;
+        std::io::Write::write_all(&mut file, hex::encode(signer.key.to_bytes()).as_bytes())?;
+        Ok((signer, true))
+    }
+
+    /// Hex-encoded public key.
+    pub fn key_id(&self) -> String {
+        hex::encode(self.key.verifying_key().to_bytes())
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.key.verifying_key()
+    }
+
+    /// Signature over the entry as it currently stands.
+    pub fn sign(&self, entry: &SkillManifestEntry) -> SkillSignature {
+        let sig = self.key.sign(&signing_payload(entry));
+        SkillSignature {
+            key_id: self.key_id(),
+            signature: hex::encode(sig.to_bytes()),
+        }
+    }
+
+    /// Sign in place, replacing any existing signature.
+    pub fn sign_entry(&self, entry: &mut SkillManifestEntry) {
+        entry.signature = Some(self.sign(entry));
+    }
+}
+
+/// Public keys whose signatures make a manifest entry Tier 1.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedSkillKeys {
+    keys: Vec<VerifyingKey>,
+}
+
+impl TrustedSkillKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_key(mut self, key: VerifyingKey) -> Self {
+        if !self.keys.contains(&key) {
+            self.keys.push(key);
+        }
+        self
+    }
+
+    /// Public half of the local signing key (when present) plus `PAGI_SKILL_TRUSTED_KEYS`.
+    /// Malformed entries are skipped with a warning.
+    pub fn from_env() -> Self {
+        let mut trusted = Self::new();
+        let key_path = SkillSigner::default_key_path();
+        if key_path.exists() {
+            match SkillSigner::load(&key_path) {
+                Ok(signer) => trusted = trusted.with_key(signer.verifying_key()),
+                Err(e) => tracing::warn!(target: "pagi::skills", "Skill signing key unreadable: {}", e),
+            }
+        }
+        if let Ok(list) = std::env::var(SKILL_TRUSTED_KEYS_ENV) {
+            for hex_key in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                match parse_verifying_key(hex_key) {
+                    Ok(key) => trusted = trusted.with_key(key),
+                    Err(e) => tracing::warn!(target: "pagi::skills", "Ignoring trusted skill key {}: {}", hex_key, e),
+                }
+            }
+        }
+        trusted
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verify the entry's signature against the trusted keys.
+    pub fn verify(&self, entry: &SkillManifestEntry) -> Result<(), SignatureError> {
+        let sig = entry.signature.as_ref().ok_or(SignatureError::Missing)?;
+        let key = parse_verifying_key(&sig.key_id)?;
+        if !self.keys.contains(&key) {
+            return Err(SignatureError::UntrustedKey(sig.key_id.clone()));
+        }
+        let bytes: [u8; 64] = hex::decode(&sig.signature)
+            .map_err(|e| SignatureError::Malformed(e.to_string()))?
+            .try_into()
+            .map_err(|_| SignatureError::Malformed("expected 64-byte signature".to_string()))?;
+        key.verify(&signing_payload(entry), &Signature::from_bytes(&bytes))
+            .map_err(|_| SignatureError::Invalid)
+    }
+
+    /// Verify the signature and, when the entry names an artifact, that the file on disk still
+    /// hashes to the signed `artifact_sha256`. Relative artifact paths resolve against `base`.
+    pub fn verify_with_artifact(&self, entry: &SkillManifestEntry, base: &Path) -> Result<(), SignatureError> {
+        self.verify(entry)?;
+        if let Some(ref artifact) = entry.artifact {
+            verify_artifact(entry, &base.join(artifact))?;
+        }
+        Ok(())
+    }
+}
+
+/// Check a Forge artifact against the entry's signed `artifact_sha256`.
+pub fn verify_artifact(entry: &SkillManifestEntry, path: &Path) -> Result<(), SignatureError> {
+    let expected = entry.artifact_sha256.clone().unwrap_or_default();
+    let actual = sha256_file(path).map_err(|e| SignatureError::ArtifactUnreadable(format!("{}: {}", path.display(), e)))?;
+    if !expected.eq_ignore_ascii_case(&actual) {
+        return Err(SignatureError::ArtifactMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+fn parse_verifying_key(hex_key: &str) -> Result<VerifyingKey, SignatureError> {
+    let bytes: [u8; 32] = hex::decode(hex_key.trim())
+        .map_err(|e| SignatureError::Malformed(e.to_string()))?
+        .try_into()
+        .map_err(|_| SignatureError::Malformed("expected 32-byte public key".to_string()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| SignatureError::Malformed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(skill_id: &str) -> SkillManifestEntry {
+        SkillManifestEntry {
+            skill_id: skill_id.to_string(),
+            kb_layers_allowed: vec![1, 5],
+            description: Some("test".to_string()),
+            capabilities: Default::default(),
+            artifact: None,
+            artifact_sha256: None,
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn sign_and_verify_roundtrip() {
+        let signer = SkillSigner::from_seed([7u8; 32]);
+        let trusted = TrustedSkillKeys::new().with_key(signer.verifying_key());
+        let mut e = entry("EthosSync");
+        assert_eq!(trusted.verify(&e), Err(SignatureError::Missing));
+        signer.sign_entry(&mut e);
+        assert_eq!(trusted.verify(&e), Ok(()));
+
+        // Description is not covered; KB layer order is canonicalized.
+        e.description = Some("edited".to_string());
+        e.kb_layers_allowed = vec![5, 1];
+        assert_eq!(trusted.verify(&e), Ok(()));
+
+        e.kb_layers_allowed.push(9);
+        assert_eq!(trusted.verify(&e), Err(SignatureError::Invalid));
+    }
+
+    #[test]
+    fn untrusted_key_is_rejected() {
+        let signer = SkillSigner::from_seed([1u8; 32]);
+        let other = SkillSigner::from_seed([2u8; 32]);
+        let trusted = TrustedSkillKeys::new().with_key(other.verifying_key());
+        let mut e = entry("EthosSync");
+        signer.sign_entry(&mut e);
+        assert!(matches!(trusted.verify(&e), Err(SignatureError::UntrustedKey(_))));
+    }
+
+    #[test]
+    fn artifact_hash_is_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("libskill.so");
+        std::fs::write(&path, b"v1").unwrap();
+        let signer = SkillSigner::from_seed([3u8; 32]);
+        let trusted = TrustedSkillKeys::new().with_key(signer.verifying_key());
+        let mut e = entry("ForgeSkill");
+        e.artifact = Some("libskill.so".to_string());
+        e.artifact_sha256 = Some(sha256_file(&path).unwrap());
+        signer.sign_entry(&mut e);
+        assert_eq!(trusted.verify_with_artifact(&e, dir.path()), Ok(()));
+
+        std::fs::write(&path, b"v2").unwrap();
+        assert!(matches!(
+            trusted.verify_with_artifact(&e, dir.path()),
+            Err(SignatureError::ArtifactMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn registry_downgrades_unsigned_core_and_requires_signed_promotion() {
+        use super::super::skills::{PromotionError, SkillManifestRegistry, TierManifest};
+
+        let dir = tempfile::tempdir().unwrap();
+        let signer = SkillSigner::from_seed([9u8; 32]);
+        let write = |tier: &str, skills: Vec<SkillManifestEntry>| {
+            let path = dir.path().join(if tier == "generated" { "ephemeral" } else { tier });
+            std::fs::create_dir_all(&path).unwrap();
+            let manifest = TierManifest {
+                trust_tier: tier.to_string(),
+                skills,
+                description: None,
+            };
+            std::fs::write(path.join("manifest.json"), serde_json::to_vec(&manifest).unwrap()).unwrap();
+        };
+        let mut signed = entry("Signed");
+        signer.sign_entry(&mut signed);
+        write("core", vec![signed, entry("Unsigned")]);
+        write("generated", vec![entry("Macro")]);
+
+        let trusted = TrustedSkillKeys::new().with_key(signer.verifying_key());
+        let reg = SkillManifestRegistry::load_from_dir_with_keys(dir.path(), trusted).unwrap();
+        assert!(reg.validate_skill_permissions("Signed", 1, false));
+        assert!(!reg.validate_skill_permissions("Unsigned", 1, false));
+        let inv = reg.list_inventory();
+        let unsigned = inv.iter().find(|e| e.skill_id == "Unsigned").unwrap();
+        assert_eq!(unsigned.trust_tier, "import");
+        assert!(unsigned.signature_error.is_some());
+
+        let rogue = SkillSigner::from_seed([10u8; 32]);
+        let macro_entry = reg.manifest_entry("Macro").unwrap();
+        assert!(matches!(
+            reg.promote_to_core("Macro", rogue.sign(&macro_entry)),
+            Err(PromotionError::Signature { .. })
+        ));
+        assert_eq!(reg.promote_to_core("Macro", signer.sign(&macro_entry)), Ok(()));
+        assert!(reg.manifest_entry("Macro").unwrap().signature.is_some());
+        assert_eq!(
+            reg.promote_to_core("Macro", signer.sign(&macro_entry)),
+            Err(PromotionError::NotGenerated("Macro".to_string()))
+        );
+    }
+
+    #[test]
+    fn key_file_is_created_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys").join("skill_signing.key");
+        let (a, created) = SkillSigner::load_or_create(&path).unwrap();
+        assert!(created);
+        let (b, created) = SkillSigner::load_or_create(&path).unwrap();
+        assert!(!created);
+        assert_eq!(a.key_id(), b.key_id());
+    }
+}
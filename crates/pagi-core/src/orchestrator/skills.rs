@@ -4,10 +4,19 @@
 //! - **Tier 2 (import):** Community patterns, quarantined. No KB-01 or KB-09.
 //! - **Tier 3 (generated):** Orchestrator-generated (ephemeral). No KB-01 or KB-09.
 //!
-//! The Orchestrator should call `validate_skill_permissions` before executing a skill that touches a KB layer.
+//! Tier 1 is "Signed by the User": core entries need a valid Ed25519 signature (see `signing`)
+//! or they are downgraded to Import on load, and promotion to core requires a signature.
+//!
+//! The Orchestrator should call `validate_skill_permissions` before executing a skill that touches a KB layer,
+//! and `validate_skill_capability` before a skill performs a side effect (network, fs, shell, env, LLM spend).
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+
+use super::capabilities::{CapabilityHandle, CapabilityRequest, CapabilityViolation, SkillCapabilities};
+use super::execution::SideEffectClass;
+use super::signing::{SignatureError, SkillSignature, TrustedSkillKeys};
+use crate::shared::TenantContext;
 use std::path::Path;
 use std::sync::RwLock;
 
@@ -49,6 +58,18 @@ pub struct SkillManifestEntry {
     pub kb_layers_allowed: Vec<u8>,
     #[serde(default)]
     pub description: Option<String>,
+    /// Side effects beyond KB access (network, filesystem, shell, env vars, LLM spend).
+    #[serde(default)]
+    pub capabilities: SkillCapabilities,
+    /// Forge artifact (.so/.dll) path, relative to the skills root or absolute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<String>,
+    /// Hex SHA-256 of the artifact; covered by the signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_sha256: Option<String>,
+    /// User signature (required for Tier 1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SkillSignature>,
 }
 
 /// Per-tier manifest (e.g. core/manifest.json).
@@ -69,16 +90,61 @@ pub struct SkillInventoryEntry {
     pub kb_layers_allowed: Vec<u8>,
     #[serde(default)]
     pub description: Option<String>,
+    #[serde(default)]
+    pub capabilities: SkillCapabilities,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SkillSignature>,
+    /// Set when a core entry failed signature verification and was downgraded to import.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature_error: Option<String>,
+}
+
+impl SkillInventoryEntry {
+    /// Manifest form of this entry (for writing back to a tier manifest).
+    pub fn to_manifest_entry(&self) -> SkillManifestEntry {
+        SkillManifestEntry {
+            skill_id: self.skill_id.clone(),
+            kb_layers_allowed: self.kb_layers_allowed.clone(),
+            description: self.description.clone(),
+            capabilities: self.capabilities.clone(),
+            artifact: self.artifact.clone(),
+            artifact_sha256: self.artifact_sha256.clone(),
+            signature: self.signature.clone(),
+        }
+    }
+}
+
+/// Why a promotion to core was refused.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PromotionError {
+    #[error("skill '{0}' is not in the generated tier")]
+    NotGenerated(String),
+    #[error("skill '{skill_id}' signature rejected: {source}")]
+    Signature { skill_id: String, source: SignatureError },
+}
+
+/// Indexed permissions for one skill.
+#[derive(Debug, Clone)]
+struct IndexedSkill {
+    tier: TrustTier,
+    kb_layers_allowed: Vec<u8>,
+    capabilities: SkillCapabilities,
 }
 
 /// Registry that scans `core/`, `import/`, `ephemeral/` and loads manifest.json from each.
 /// Provides permission validation: only `core` may access KB-01 or KB-09.
 #[derive(Debug, Default)]
 pub struct SkillManifestRegistry {
-    /// skill_id -> (TrustTier, kb_layers_allowed)
-    index: RwLock<HashMap<String, (TrustTier, Vec<u8>)>>,
+    /// skill_id -> tier, allowed KB layers and capabilities
+    index: RwLock<HashMap<String, IndexedSkill>>,
     /// Ordered list for API (GET /api/v1/skills).
     inventory: RwLock<Vec<SkillInventoryEntry>>,
+    /// Keys accepted for Tier 1 signatures (load and promotion).
+    trusted_keys: TrustedSkillKeys,
 }
 
 impl SkillManifestRegistry {
@@ -86,12 +152,21 @@ impl SkillManifestRegistry {
         Self {
             index: RwLock::new(HashMap::new()),
             inventory: RwLock::new(Vec::new()),
+            trusted_keys: TrustedSkillKeys::new(),
         }
     }
 
     /// Load manifests from the three tier directories under `skills_root`.
     /// `skills_root` should be the path to `crates/pagi-skills` (or equivalent).
+    /// Core signatures are checked against [`TrustedSkillKeys::from_env`].
     pub fn load_from_dir(skills_root: &Path) -> std::io::Result<Self> {
+        Self::load_from_dir_with_keys(skills_root, TrustedSkillKeys::from_env())
+    }
+
+    /// Like [`Self::load_from_dir`] with an explicit trusted key set. Core entries that are
+    /// unsigned, signed by an untrusted key, or whose artifact hash no longer matches are
+    /// downgraded to Import (recorded in `SkillInventoryEntry::signature_error`).
+    pub fn load_from_dir_with_keys(skills_root: &Path, trusted_keys: TrustedSkillKeys) -> std::io::Result<Self> {
         let mut index = HashMap::new();
         let mut inventory = Vec::new();
 
@@ -109,15 +184,40 @@ impl SkillManifestRegistry {
             let manifest: TierManifest = serde_json::from_slice(&bytes).map_err(|e| {
                 std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", manifest_path.display(), e))
             })?;
-            let tier = TrustTier::from_str(&manifest.trust_tier);
+            let manifest_tier = TrustTier::from_str(&manifest.trust_tier);
             for entry in manifest.skills {
-                let layers = entry.kb_layers_allowed.clone();
-                index.insert(entry.skill_id.clone(), (tier, layers.clone()));
+                let mut tier = manifest_tier;
+                let mut signature_error = None;
+                if tier == TrustTier::Core {
+                    if let Err(e) = trusted_keys.verify_with_artifact(&entry, skills_root) {
+                        tracing::warn!(
+                            target: "pagi::skills",
+                            "Core skill {} downgraded to import: {}",
+                            entry.skill_id,
+                            e
+                        );
+                        tier = TrustTier::Import;
+                        signature_error = Some(e.to_string());
+                    }
+                }
+                index.insert(
+                    entry.skill_id.clone(),
+                    IndexedSkill {
+                        tier,
+                        kb_layers_allowed: entry.kb_layers_allowed.clone(),
+                        capabilities: entry.capabilities.clone(),
+                    },
+                );
                 inventory.push(SkillInventoryEntry {
                     skill_id: entry.skill_id,
                     trust_tier: tier.as_str().to_string(),
-                    kb_layers_allowed: layers,
+                    kb_layers_allowed: entry.kb_layers_allowed,
                     description: entry.description,
+                    capabilities: entry.capabilities,
+                    artifact: entry.artifact,
+                    artifact_sha256: entry.artifact_sha256,
+                    signature: entry.signature,
+                    signature_error,
                 });
             }
         }
@@ -125,6 +225,7 @@ impl SkillManifestRegistry {
         Ok(Self {
             index: RwLock::new(index),
             inventory: RwLock::new(inventory),
+            trusted_keys,
         })
     }
 
@@ -145,22 +246,123 @@ impl SkillManifestRegistry {
             Ok(g) => g,
             Err(_) => return false,
         };
-        let Some((tier, allowed)) = guard.get(skill_id) else {
+        let Some(skill) = guard.get(skill_id) else {
             return false;
         };
-        if !allowed.contains(&target_kb_layer) {
+        if !skill.kb_layers_allowed.contains(&target_kb_layer) {
             return false;
         }
         if strict_mode {
-            return *tier == TrustTier::Core;
+            return skill.tier == TrustTier::Core;
         }
         // Restriction: only core may access KB-01 (Soul) or KB-09 (Shadow/PII).
         if target_kb_layer == 1 || target_kb_layer == 9 {
-            return *tier == TrustTier::Core;
+            return skill.tier == TrustTier::Core;
         }
         true
     }
 
+    /// Returns whether the skill's declared capabilities cover a side effect.
+    /// Core skills are always allowed; skills missing from the registry are denied.
+    pub fn validate_skill_capability(
+        &self,
+        skill_id: &str,
+        request: &CapabilityRequest<'_>,
+    ) -> Result<(), CapabilityViolation> {
+        match self.capability_handle(skill_id) {
+            Some(handle) => handle.check(request),
+            None => Err(CapabilityViolation {
+                skill_id: skill_id.to_string(),
+                capability: "run (not in any skill manifest)".to_string(),
+            }),
+        }
+    }
+
+    /// Dispatch-time check that a skill declares the capability for its side-effect class
+    /// (e.g. a Shell-class skill must declare `shell: true`). Skills missing from the registry
+    /// are denied.
+    pub fn validate_side_effect_class(
+        &self,
+        skill_id: &str,
+        class: SideEffectClass,
+    ) -> Result<(), CapabilityViolation> {
+        let unlisted = || CapabilityViolation {
+            skill_id: skill_id.to_string(),
+            capability: "run (not in any skill manifest)".to_string(),
+        };
+        let guard = self.index.read().map_err(|_| unlisted())?;
+        let skill = guard.get(skill_id).ok_or_else(unlisted)?;
+        if skill.tier == TrustTier::Core || skill.capabilities.covers_class(class) {
+            return Ok(());
+        }
+        Err(CapabilityViolation {
+            skill_id: skill_id.to_string(),
+            capability: format!("perform {} side effects", class.as_str()),
+        })
+    }
+
+    /// Capability handle for one call of a manifest-listed skill (None when not listed).
+    pub fn capability_handle(&self, skill_id: &str) -> Option<CapabilityHandle> {
+        let guard = self.index.read().ok()?;
+        let skill = guard.get(skill_id)?;
+        Some(CapabilityHandle::new(
+            skill_id,
+            skill.tier,
+            skill.capabilities.clone(),
+        ))
+    }
+
+    /// Copy of `ctx` carrying a fresh capability handle for one call of `skill_id`. Every path
+    /// that executes a skill outside the Orchestrator (live skills, CLI drills) goes through
+    /// this, so a skill missing from the manifests cannot run unscoped.
+    pub fn scoped_context(&self, skill_id: &str, ctx: &TenantContext) -> Result<TenantContext, CapabilityViolation> {
+        let handle = self.capability_handle(skill_id).ok_or_else(|| CapabilityViolation {
+            skill_id: skill_id.to_string(),
+            capability: "run (not in any skill manifest)".to_string(),
+        })?;
+        Ok(TenantContext {
+            capabilities: Some(handle),
+            ..ctx.clone()
+        })
+    }
+
+    /// Adds a skill that is only known at runtime (Forge overlay skills, federated goals).
+    /// Core entries go through the same signature check as on load and are downgraded to Import
+    /// when it fails. An existing entry with the same id is replaced.
+    pub fn register_runtime(&self, tier: TrustTier, entry: SkillManifestEntry) {
+        let mut tier = tier;
+        let mut signature_error = None;
+        if tier == TrustTier::Core {
+            if let Err(e) = self.trusted_keys.verify(&entry) {
+                tier = TrustTier::Import;
+                signature_error = Some(e.to_string());
+            }
+        }
+        let (Ok(mut index), Ok(mut inventory)) = (self.index.write(), self.inventory.write()) else {
+            return;
+        };
+        index.insert(
+            entry.skill_id.clone(),
+            IndexedSkill {
+                tier,
+                kb_layers_allowed: entry.kb_layers_allowed.clone(),
+                capabilities: entry.capabilities.clone(),
+            },
+        );
+        inventory.retain(|e| e.skill_id != entry.skill_id);
+        inventory.push(SkillInventoryEntry {
+            skill_id: entry.skill_id,
+            trust_tier: tier.as_str().to_string(),
+            kb_layers_allowed: entry.kb_layers_allowed,
+            description: entry.description,
+            capabilities: entry.capabilities,
+            artifact: entry.artifact,
+            artifact_sha256: entry.artifact_sha256,
+            signature: entry.signature,
+            signature_error,
+        });
+    }
+
     /// List all skills with trust status (for GET /api/v1/skills).
     pub fn list_inventory(&self) -> Vec<SkillInventoryEntry> {
         self.inventory
@@ -169,37 +371,44 @@ impl SkillManifestRegistry {
             .unwrap_or_default()
     }
 
-    /// Promote a skill from ephemeral to core by moving its manifest entry.
-    /// Updates the in-memory registry; caller may persist to disk by writing back manifest files.
-    /// Returns true if the skill was found in generated tier and moved to core.
-    pub fn promote_to_core(&self, skill_id: &str) -> bool {
-        let layers = {
-            let idx_guard = match self.index.read() {
-                Ok(g) => g,
-                Err(_) => return false,
-            };
-            let (tier, layers) = match idx_guard.get(skill_id) {
-                Some((t, l)) => (*t, l.clone()),
-                None => return false,
-            };
-            if tier != TrustTier::Generated {
-                return false;
-            }
-            layers
-        };
-        {
-            let mut idx_guard = match self.index.write() {
-                Ok(g) => g,
-                Err(_) => return false,
-            };
-            idx_guard.insert(skill_id.to_string(), (TrustTier::Core, layers));
-        }
-        if let Ok(mut inv_guard) = self.inventory.write() {
-            if let Some(entry) = inv_guard.iter_mut().find(|e| e.skill_id == skill_id) {
-                entry.trust_tier = "core".to_string();
-            }
-        }
-        true
+    /// Manifest entry for a loaded skill (e.g. to sign it before promotion).
+    pub fn manifest_entry(&self, skill_id: &str) -> Option<SkillManifestEntry> {
+        self.inventory
+            .read()
+            .ok()?
+            .iter()
+            .find(|e| e.skill_id == skill_id)
+            .map(SkillInventoryEntry::to_manifest_entry)
+    }
+
+    /// Promote a skill from ephemeral to core. `signature` must be a valid signature from a
+    /// trusted key over the skill's manifest entry (artifact hash included); it is stored on the
+    /// entry so the caller can persist it by writing back the manifest files.
+    pub fn promote_to_core(&self, skill_id: &str, signature: SkillSignature) -> Result<(), PromotionError> {
+        let not_generated = || PromotionError::NotGenerated(skill_id.to_string());
+        let mut idx_guard = self.index.write().map_err(|_| not_generated())?;
+        let mut inv_guard = self.inventory.write().map_err(|_| not_generated())?;
+        let skill = idx_guard
+            .get_mut(skill_id)
+            .filter(|s| s.tier == TrustTier::Generated)
+            .ok_or_else(not_generated)?;
+        let entry = inv_guard
+            .iter_mut()
+            .find(|e| e.skill_id == skill_id)
+            .ok_or_else(not_generated)?;
+        let mut signed = entry.to_manifest_entry();
+        signed.signature = Some(signature);
+        self.trusted_keys
+            .verify(&signed)
+            .map_err(|source| PromotionError::Signature {
+                skill_id: skill_id.to_string(),
+                source,
+            })?;
+        skill.tier = TrustTier::Core;
+        entry.trust_tier = TrustTier::Core.as_str().to_string();
+        entry.signature = signed.signature;
+        entry.signature_error = None;
+        Ok(())
     }
 }
 
@@ -215,6 +424,16 @@ pub fn validate_skill_permissions(
     registry.validate_skill_permissions(skill_id, target_kb_layer, strict_mode)
 }
 
+/// Checks a skill side effect against its manifest capabilities. Use from pagi-core helpers
+/// that perform network, filesystem, process, env or LLM access on behalf of a skill.
+pub fn validate_skill_capability(
+    registry: &SkillManifestRegistry,
+    skill_id: &str,
+    request: &CapabilityRequest<'_>,
+) -> Result<(), CapabilityViolation> {
+    registry.validate_skill_capability(skill_id, request)
+}
+
 /// Error returned when a skill attempts to access a KB layer it is not allowed to touch
 /// (e.g. Tier 3 generated skill touching KB-01 or KB-09). Logged in KB-08 as "Failed Leak Attempt".
 #[derive(Debug, Clone)]
@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use crate::orchestrator::{CapabilityHandle, CapabilityViolation};
 
 // -----------------------------------------------------------------------------
 // Emotional Context Layer — Cognitive Governor state (Kardia/Soma as state-modifiers for Logos)
@@ -424,6 +425,14 @@ pub struct TenantContext {
     /// When None or empty, [`DEFAULT_AGENT_ID`] is used.
     #[serde(default)]
     pub agent_id: Option<String>,
+    /// Session (e.g. chat thread) for per-session energy budgets. When None or empty, the
+    /// resolved agent ID is used.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Capability handle for the skill currently executing. Set by the Orchestrator for skills
+    /// listed in a tier manifest; None means the caller is not capability-scoped.
+    #[serde(skip)]
+    pub capabilities: Option<CapabilityHandle>,
 }
 
 impl TenantContext {
@@ -434,6 +443,37 @@ impl TenantContext {
             .filter(|s| !s.is_empty())
             .unwrap_or(DEFAULT_AGENT_ID)
     }
+
+    /// Resolved session ID (never empty): `session_id`, else the resolved agent ID.
+    pub fn resolved_session_id(&self) -> &str {
+        self.session_id
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| self.resolved_agent_id())
+    }
+
+    /// HTTP request builder for the executing skill, checked against its `network_egress` when
+    /// the context is capability-scoped (including every redirect hop).
+    pub fn http_request(
+        &self,
+        client: reqwest::ClientBuilder,
+        method: reqwest::Method,
+        url: &str,
+    ) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error + Send + Sync>> {
+        match &self.capabilities {
+            Some(caps) => caps.http_request(client, method, url),
+            None => Ok(client.build()?.request(method, url)),
+        }
+    }
+
+    /// Environment variable read for the executing skill, checked against its `env_vars` when
+    /// the context is capability-scoped.
+    pub fn env_var(&self, name: &str) -> Result<Option<String>, CapabilityViolation> {
+        match &self.capabilities {
+            Some(caps) => caps.env_var(name),
+            None => Ok(std::env::var(name).ok()),
+        }
+    }
 }
 
 /// High-level goal types the orchestrator can delegate.
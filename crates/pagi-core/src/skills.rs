@@ -3,7 +3,8 @@
 //! Extends the base AgentSkill trait with priority, energy cost, and security validation.
 //! This enables Phoenix to execute actions mid-stream with proper governance.
 
-use crate::{KnowledgeStore, TenantContext};
+use crate::{CapabilityRequest, KnowledgeStore, TenantContext};
+use std::path::Path;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -151,7 +152,7 @@ impl LiveSkill for FileSystemSkill {
     
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         _knowledge: &KnowledgeStore,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
@@ -164,6 +165,9 @@ impl LiveSkill for FileSystemSkill {
                 let path = params.get("path")
                     .and_then(|v| v.as_str())
                     .ok_or("Missing 'path' parameter")?;
+                if let Some(caps) = &ctx.capabilities {
+                    caps.check(&CapabilityRequest::FsRead { path: Path::new(path) })?;
+                }
                 
                 let content = tokio::fs::read_to_string(path).await
                     .map_err(|e| format!("Failed to read file: {}", e))?;
@@ -182,6 +186,9 @@ impl LiveSkill for FileSystemSkill {
                 let content = params.get("content")
                     .and_then(|v| v.as_str())
                     .ok_or("Missing 'content' parameter")?;
+                if let Some(caps) = &ctx.capabilities {
+                    caps.check(&CapabilityRequest::FsWrite { path: Path::new(path) })?;
+                }
                 
                 tokio::fs::write(path, content).await
                     .map_err(|e| format!("Failed to write file: {}", e))?;
@@ -196,6 +203,9 @@ impl LiveSkill for FileSystemSkill {
                 let path = params.get("path")
                     .and_then(|v| v.as_str())
                     .unwrap_or(".");
+                if let Some(caps) = &ctx.capabilities {
+                    caps.check(&CapabilityRequest::FsRead { path: Path::new(path) })?;
+                }
                 
                 let mut entries = Vec::new();
                 let mut dir = tokio::fs::read_dir(path).await
@@ -286,7 +296,7 @@ impl LiveSkill for FolderSkill {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         _knowledge: &KnowledgeStore,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
@@ -310,6 +320,10 @@ impl LiveSkill for FolderSkill {
                     .get("content")
                     .and_then(|v| v.as_str())
                     .ok_or("Missing 'content' parameter")?;
+                if let Some(caps) = &ctx.capabilities {
+                    let target = Path::new(root).join(relative_path);
+                    caps.check(&CapabilityRequest::FsWrite { path: &target })?;
+                }
                 crate::project_vault::write_document_under_root(
                     std::path::Path::new(root),
                     relative_path,
@@ -325,6 +339,9 @@ impl LiveSkill for FolderSkill {
             }
             "list" => {
                 let path = path.ok_or("Missing 'path' parameter for list")?;
+                if let Some(caps) = &ctx.capabilities {
+                    caps.check(&CapabilityRequest::FsRead { path: Path::new(path) })?;
+                }
                 let mut entries = Vec::new();
                 let mut dir = tokio::fs::read_dir(path)
                     .await
@@ -354,6 +371,9 @@ impl LiveSkill for FolderSkill {
                     return Err("Path traversal not allowed".into());
                 }
                 let full = std::path::Path::new(path).join(rel);
+                if let Some(caps) = &ctx.capabilities {
+                    caps.check(&CapabilityRequest::FsRead { path: &full })?;
+                }
                 let content = tokio::fs::read_to_string(&full)
                     .await
                     .map_err(|e| format!("Failed to read file: {}", e))?;
@@ -430,7 +450,7 @@ impl LiveSkill for ShellExecutorSkill {
     
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         _knowledge: &KnowledgeStore,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
@@ -438,7 +458,11 @@ impl LiveSkill for ShellExecutorSkill {
             .and_then(|v| v.as_str())
             .ok_or("Missing 'command' parameter")?;
         
-        let output = tokio::process::Command::new("sh")
+        let mut shell = match &ctx.capabilities {
+            Some(caps) => caps.command("sh")?,
+            None => tokio::process::Command::new("sh"),
+        };
+        let output = shell
             .arg("-c")
             .arg(command)
             .output()
@@ -539,7 +563,7 @@ impl LiveSkill for AuditSkill {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         knowledge: &KnowledgeStore,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
@@ -584,11 +608,11 @@ impl LiveSkill for AuditSkill {
         }
 
         // ─── 3. INFRASTRUCTURE SCAN ───
-        if std::env::var("PAGI_REDIS_URL").unwrap_or_default().trim().is_empty() {
+        if ctx.env_var("PAGI_REDIS_URL")?.unwrap_or_default().trim().is_empty() {
             capability_gaps.push("PAGI_REDIS_URL unset".to_string());
             let _ = knowledge.record_success_metric("System Self-Audit: Capability Gap — PAGI_REDIS_URL unset (optional)");
         }
-        if std::env::var("PAGI_VECTOR_DB_URL").unwrap_or_default().trim().is_empty() {
+        if ctx.env_var("PAGI_VECTOR_DB_URL")?.unwrap_or_default().trim().is_empty() {
             capability_gaps.push("PAGI_VECTOR_DB_URL unset".to_string());
             let _ = knowledge.record_success_metric("System Self-Audit: Capability Gap — PAGI_VECTOR_DB_URL unset (optional)");
         }
@@ -722,7 +746,7 @@ impl LiveSkill for RefactorSkill {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         knowledge: &KnowledgeStore,
         params: serde_json::Value,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
@@ -734,6 +758,9 @@ impl LiveSkill for RefactorSkill {
             .ok_or("missing new_snippet")?;
         let workspace_root = params.get("workspace_root").and_then(|v| v.as_str()).unwrap_or(".");
         let full_path = std::path::Path::new(workspace_root).join(file_path);
+        if let Some(caps) = &ctx.capabilities {
+            caps.check(&CapabilityRequest::FsWrite { path: &full_path })?;
+        }
 
         let content = tokio::fs::read_to_string(&full_path).await
             .map_err(|e| format!("read failed: {}", e))?;
@@ -758,7 +785,11 @@ impl LiveSkill for RefactorSkill {
             .map_err(|e| format!("write failed: {}", e))?;
 
         // Verify with cargo check
-        let output = tokio::process::Command::new("cargo")
+        let mut cargo = match &ctx.capabilities {
+            Some(caps) => caps.command("cargo")?,
+            None => tokio::process::Command::new("cargo"),
+        };
+        let output = cargo
             .args(["check"])
             .current_dir(workspace_root)
             .output()
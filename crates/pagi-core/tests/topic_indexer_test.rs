@@ -16,6 +16,8 @@ fn test_ctx(agent_id: &str) -> TenantContext {
         tenant_id: "test_tenant".to_string(),
         correlation_id: None,
         agent_id: Some(agent_id.to_string()),
+        session_id: None,
+        capabilities: None,
     }
 }
 
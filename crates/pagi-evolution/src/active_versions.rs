@@ -0,0 +1,604 @@
+//! Active-versions manifest: the single source of truth for which version of each skill is live.
+//!
+//! Replaces the `current_{skill}.rs` / `.so` symlinks (copies on Windows). The manifest is one
+//! JSON file in the patches directory, `active_versions.json`, that names each skill's active
+//! version by timestamp and source file name, so the directory can be copied or backed up
+//! without dangling links.
+//!
+//! ## Writes
+//!
+//! Every change is a compare-and-swap ([`ActiveManifest::compare_and_swap`]): the caller names
+//! the version it expects to be active and the swap fails with [`ManifestError::Conflict`] if
+//! someone else activated a different one first. A write goes to `active_versions.json.tmp`, is
+//! fsync'd, the previous manifest is kept as `active_versions.json.bak`, and the temp file is
+//! renamed over the manifest (then the directory is fsync'd). An in-process mutex and an OS lock
+//! on `active_versions.json.lock` serialize writers across threads and processes; the manifest
+//! is re-read from disk inside the lock, so a version activated by another process is seen as a
+//! conflict rather than silently overwritten.
+//!
+//! ## Recovery
+//!
+//! [`ActiveManifest::load`] removes a leftover `.tmp` from an interrupted write, restores from
+//! `.bak` when the manifest is missing or unreadable, and adopts legacy `current_*.rs` links
+//! once (removing them). Entries whose source file is gone are kept (the file may come back,
+//! e.g. from a backup) and reported as issues. What it found is returned as a
+//! [`RecoveryReport`]. [`check_consistency`] reports drift without changing anything.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::rollback::parse_versioned_filename;
+
+/// File name of the manifest inside the patches directory.
+pub const MANIFEST_FILE: &str = "active_versions.json";
+
+/// OS lock file held around every manifest write.
+const LOCK_FILE: &str = "active_versions.json.lock";
+
+/// Library extensions considered artifacts by [`check_consistency`].
+const ARTIFACT_EXTENSIONS: &[&str] = &["so", "dll", "dylib", "wasm"];
+
+/// Error from reading or updating the manifest.
+#[derive(Error, Debug)]
+pub enum ManifestError {
+    #[error("manifest I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("manifest is corrupt: {0}")]
+    Corrupt(String),
+    #[error("activation conflict for '{skill}': expected {expected:?} to be active, found {actual:?}")]
+    Conflict {
+        skill: String,
+        expected: Option<i64>,
+        actual: Option<i64>,
+    },
+}
+
+/// The active version of one skill.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveEntry {
+    pub timestamp_ms: i64,
+    /// Hash of the source at activation, to detect edits in place.
+    pub code_hash: String,
+    /// Source file name, relative to the patches directory.
+    pub source: String,
+    /// Compiled artifact, once registered.
+    #[serde(default)]
+    pub artifact: Option<PathBuf>,
+    pub activated_at_ms: i64,
+}
+
+impl ActiveEntry {
+    pub fn new(timestamp_ms: i64, code_hash: &str, source_path: &Path) -> Self {
+        Self {
+            timestamp_ms,
+            code_hash: code_hash.to_string(),
+            source: source_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            artifact: None,
+            activated_at_ms: now_epoch_ms(),
+        }
+    }
+    /// Entry for a source file written outside the `RollbackManager` (hashes `code` the same
+    /// way the manager does).
+    pub fn for_source(timestamp_ms: i64, source_path: &Path, code: &str) -> Self {
+        Self::new(timestamp_ms, &crate::rollback::compute_sha256(code), source_path)
+    }
+}
+
+/// Manifest contents. `generation` increases with every committed write.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActiveVersions {
+    pub generation: u64,
+    /// Keyed by the skill's file-name form (`{skill}` in `{skill}_v{timestamp}.rs`).
+    pub skills: BTreeMap<String, ActiveEntry>,
+}
+
+impl ActiveVersions {
+    /// Reads the committed manifest in `patches_dir` (or its backup) without repairing or
+    /// writing anything, for read-only views. Unreadable or missing state reads as empty.
+    pub fn read(patches_dir: &Path) -> Self {
+        let path = patches_dir.join(MANIFEST_FILE);
+        read_manifest(&path)
+            .ok()
+            .flatten()
+            .or_else(|| read_manifest(&bak_path(&path)).ok().flatten())
+            .unwrap_or_default()
+    }
+}
+
+/// What [`ActiveManifest::load`] repaired or found.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecoveryReport {
+    /// An interrupted write's temp file was discarded.
+    pub discarded_partial_write: bool,
+    /// The manifest was missing or unreadable and the backup was used.
+    pub restored_from_backup: bool,
+    /// The manifest and backup were unreadable; the manifest was moved aside to this path.
+    pub quarantined: Option<PathBuf>,
+    /// Skills adopted from legacy `current_*.rs` links.
+    pub migrated_links: Vec<String>,
+    /// Problems left in place, e.g. an active entry whose source file no longer exists.
+    pub issues: Vec<ConsistencyIssue>,
+}
+
+impl RecoveryReport {
+    pub fn is_clean(&self) -> bool {
+        !self.repaired() && self.issues.is_empty()
+    }
+
+    /// Whether the recovered state differs from the committed manifest and must be written.
+    fn repaired(&self) -> bool {
+        self.discarded_partial_write
+            || self.restored_from_backup
+            || self.quarantined.is_some()
+            || !self.migrated_links.is_empty()
+    }
+}
+
+/// The on-disk manifest plus its in-process writer lock.
+#[derive(Debug)]
+pub struct ActiveManifest {
+    dir: PathBuf,
+    state: Mutex<ActiveVersions>,
+}
+
+impl ActiveManifest {
+    /// Opens the manifest in `patches_dir`, recovering from interrupted writes and migrating
+    /// legacy `current_*.rs` links. Never fails: unreadable state is reported and set aside.
+    pub fn load(patches_dir: &Path) -> (Self, RecoveryReport) {
+        let mut report = RecoveryReport::default();
+        // Another process may be mid-write: its `.tmp` is only leftover once we hold the lock.
+        let _lock = if patches_dir.is_dir() {
+            lock_dir(patches_dir)
+                .map_err(|e| {
+                    tracing::warn!(
+                        target: "pagi::rollback",
+                        error = %e,
+                        "Could not lock the active-versions manifest; recovering without it"
+                    )
+                })
+                .ok()
+        } else {
+            None
+        };
+        let path = patches_dir.join(MANIFEST_FILE);
+        let tmp = tmp_path(&path);
+        let bak = bak_path(&path);
+
+        if tmp.exists() {
+            // The rename never happened, so the manifest still holds the last committed state.
+            let _ = std::fs::remove_file(&tmp);
+            report.discarded_partial_write = true;
+        }
+
+        let mut state = match read_manifest(&path) {
+            Ok(Some(state)) => state,
+            primary => match read_manifest(&bak) {
+                Ok(Some(state)) => {
+                    report.restored_from_backup = true;
+                    state
+                }
+                _ => {
+                    if primary.is_err() {
+                        let aside = path.with_extension(format!("json.corrupt-{}", now_epoch_ms()));
+                        if std::fs::rename(&path, &aside).is_ok() {
+                            report.quarantined = Some(aside);
+                        }
+                    }
+                    ActiveVersions::default()
+                }
+            },
+        };
+
+        for (skill, entry) in &state.skills {
+            let source = patches_dir.join(&entry.source);
+            if !source.is_file() {
+                report.issues.push(ConsistencyIssue::MissingSource {
+                    skill: skill.clone(),
+                    path: source,
+                });
+            }
+        }
+
+        for (skill, entry, link) in legacy_links(patches_dir) {
+            if !state.skills.contains_key(&skill) {
+                state.skills.insert(skill.clone(), entry);
+                report.migrated_links.push(skill);
+            }
+            let _ = std::fs::remove_file(link);
+        }
+
+        let manifest = Self {
+            dir: patches_dir.to_path_buf(),
+            state: Mutex::new(state),
+        };
+        if report.repaired() {
+            let mut state = manifest.lock();
+            state.generation += 1;
+            if let Err(e) = write_manifest(&manifest.dir, &state) {
+                tracing::warn!(
+                    target: "pagi::rollback",
+                    error = %e,
+                    "Failed to write recovered active-versions manifest"
+                );
+            }
+        }
+        (manifest, report)
+    }
+
+    /// Path of the manifest file.
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(MANIFEST_FILE)
+    }
+
+    /// Copy of the current manifest.
+    pub fn snapshot(&self) -> ActiveVersions {
+        self.lock().clone()
+    }
+
+    /// Active entry of a skill.
+    pub fn get(&self, skill_name: &str) -> Option<ActiveEntry> {
+        self.lock().skills.get(skill_name).cloned()
+    }
+
+    /// Absolute source path of an entry.
+    pub fn source_path(&self, entry: &ActiveEntry) -> PathBuf {
+        self.dir.join(&entry.source)
+    }
+
+    /// Replaces the active entry of `skill_name` (`None` deactivates it) if the active version
+    /// is still `expected` (`None` = no active version). Returns the new generation.
+    pub fn compare_and_swap(
+        &self,
+        skill_name: &str,
+        expected: Option<i64>,
+        new: Option<ActiveEntry>,
+    ) -> Result<u64, ManifestError> {
+        self.update(skill_name, expected, |entry| *entry = new)
+    }
+
+    /// Records the artifact of the active version `timestamp_ms`.
+    pub fn set_artifact(
+        &self,
+        skill_name: &str,
+        timestamp_ms: i64,
+        artifact: PathBuf,
+    ) -> Result<u64, ManifestError> {
+        self.update(skill_name, Some(timestamp_ms), |entry| {
+            if let Some(entry) = entry {
+                entry.artifact = Some(artifact);
+            }
+        })
+    }
+
+    fn update(
+        &self,
+        skill_name: &str,
+        expected: Option<i64>,
+        apply: impl FnOnce(&mut Option<ActiveEntry>),
+    ) -> Result<u64, ManifestError> {
+        let mut state = self.lock();
+        let _lock = lock_dir(&self.dir)?;
+        // Pick up commits from other processes before comparing.
+        if let Some(on_disk) = read_manifest(&self.path())? {
+            if on_disk.generation > state.generation {
+                *state = on_disk;
+            }
+        }
+
+        let actual = state.skills.get(skill_name).map(|e| e.timestamp_ms);
+        if actual != expected {
+            return Err(ManifestError::Conflict {
+                skill: skill_name.to_string(),
+                expected,
+                actual,
+            });
+        }
+
+        let mut next = state.clone();
+        let mut entry = next.skills.remove(skill_name);
+        apply(&mut entry);
+        if let Some(entry) = entry {
+            next.skills.insert(skill_name.to_string(), entry);
+        }
+        next.generation += 1;
+        write_manifest(&self.dir, &next)?;
+        *state = next;
+        Ok(state.generation)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ActiveVersions> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Consistency check
+// ---------------------------------------------------------------------------
+
+/// Drift between the manifest, the patches directory and the artifacts directory.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConsistencyIssue {
+    /// The active version's source file is gone.
+    MissingSource { skill: String, path: PathBuf },
+    /// The active version's source no longer hashes to what was activated.
+    SourceModified { skill: String, path: PathBuf },
+    /// The active version's registered artifact is gone.
+    MissingArtifact { skill: String, path: PathBuf },
+    /// A library in the artifacts directory that no version references.
+    OrphanedArtifact { path: PathBuf },
+    /// A `current_*` link or copy left over from before the manifest.
+    LegacyLink { path: PathBuf },
+}
+
+/// Compares the manifest with the files on disk. `referenced_artifacts` are artifacts known to
+/// the version registry (non-active versions included); `hash` computes the source hash the
+/// manifest entries were recorded with.
+pub fn check_consistency(
+    manifest: &ActiveManifest,
+    artifacts_dir: &Path,
+    referenced_artifacts: &HashSet<PathBuf>,
+    hash: impl Fn(&str) -> String,
+) -> Vec<ConsistencyIssue> {
+    let mut issues = Vec::new();
+    let snapshot = manifest.snapshot();
+    let mut referenced: HashSet<PathBuf> = referenced_artifacts.iter().map(|p| normalize(p)).collect();
+
+    for (skill, entry) in &snapshot.skills {
+        let source = manifest.source_path(entry);
+        match std::fs::read_to_string(&source) {
+            Ok(code) if hash(&code) != entry.code_hash => {
+                issues.push(ConsistencyIssue::SourceModified {
+                    skill: skill.clone(),
+                    path: source,
+                })
+            }
+            Ok(_) => {}
+            Err(_) => issues.push(ConsistencyIssue::MissingSource {
+                skill: skill.clone(),
+                path: source,
+            }),
+        }
+        if let Some(ref artifact) = entry.artifact {
+            if !artifact.is_file() {
+                issues.push(ConsistencyIssue::MissingArtifact {
+                    skill: skill.clone(),
+                    path: artifact.clone(),
+                });
+            }
+            referenced.insert(normalize(artifact));
+        }
+    }
+
+    for dir in [manifest.dir.as_path(), artifacts_dir] {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with("current_") {
+                issues.push(ConsistencyIssue::LegacyLink { path });
+                continue;
+            }
+            let is_artifact = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| ARTIFACT_EXTENSIONS.contains(&e));
+            if dir == artifacts_dir && is_artifact && path.is_file() && !referenced.contains(&normalize(&path)) {
+                issues.push(ConsistencyIssue::OrphanedArtifact { path });
+            }
+        }
+    }
+    issues
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Takes the exclusive OS lock for `dir`; released when the returned file is dropped.
+fn lock_dir(dir: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::create_dir_all(dir)?;
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(LOCK_FILE))?;
+    file.lock()?;
+    Ok(file)
+}
+
+fn tmp_path(path: &Path) -> PathBuf {
+    path.with_extension("json.tmp")
+}
+
+fn bak_path(path: &Path) -> PathBuf {
+    path.with_extension("json.bak")
+}
+
+/// `Ok(None)` if the file does not exist.
+fn read_manifest(path: &Path) -> Result<Option<ActiveVersions>, ManifestError> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|e| ManifestError::Corrupt(format!("{}: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Temp file + fsync, keep the previous manifest as `.bak`, rename, fsync the directory.
+fn write_manifest(dir: &Path, state: &ActiveVersions) -> Result<(), ManifestError> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(MANIFEST_FILE);
+    let tmp = tmp_path(&path);
+    let json = serde_json::to_vec_pretty(state).map_err(|e| ManifestError::Corrupt(e.to_string()))?;
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+    }
+    if path.exists() {
+        let bak = bak_path(&path);
+        std::fs::copy(&path, &bak)?;
+        std::fs::File::open(&bak)?.sync_all()?;
+    }
+    std::fs::rename(&tmp, &path)?;
+    sync_dir(dir);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(d) = std::fs::File::open(dir) {
+        let _ = d.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
+/// Legacy `current_{skill}.rs` links/copies resolved to the versioned source with the same
+/// content (newest wins). Links that match no source are left for [`check_consistency`].
+fn legacy_links(patches_dir: &Path) -> Vec<(String, ActiveEntry, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(patches_dir) else {
+        return Vec::new();
+    };
+    let files: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    let mut out = Vec::new();
+    for link in &files {
+        let Some(skill) = link
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix("current_"))
+            .and_then(|n| n.strip_suffix(".rs"))
+        else {
+            continue;
+        };
+        let Ok(code) = std::fs::read(link) else {
+            continue;
+        };
+        let newest = files
+            .iter()
+            .filter_map(|f| {
+                let (s, ts) = parse_versioned_filename(f.file_name()?.to_str()?)?;
+                (s == skill && std::fs::read(f).ok()? == code).then_some((ts, f))
+            })
+            .max_by_key(|(ts, _)| *ts);
+        if let Some((ts, source)) = newest {
+            let code = String::from_utf8_lossy(&code);
+            let entry = ActiveEntry::for_source(ts, source, &code);
+            out.push((skill.to_string(), entry, link.clone()));
+        }
+    }
+    out
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn now_epoch_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_and_swap_rejects_stale_expectation() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("fs_v1.rs"), "// v1").unwrap();
+        std::fs::write(dir.path().join("fs_v2.rs"), "// v2").unwrap();
+        let (manifest, report) = ActiveManifest::load(dir.path());
+        assert!(report.is_clean());
+
+        let v1 = ActiveEntry::new(1, "h1", &dir.path().join("fs_v1.rs"));
+        let v2 = ActiveEntry::new(2, "h2", &dir.path().join("fs_v2.rs"));
+        assert_eq!(manifest.compare_and_swap("fs", None, Some(v1)).unwrap(), 1);
+        let err = manifest.compare_and_swap("fs", None, Some(v2.clone())).unwrap_err();
+        assert!(matches!(err, ManifestError::Conflict { actual: Some(1), .. }));
+        assert_eq!(manifest.compare_and_swap("fs", Some(1), Some(v2)).unwrap(), 2);
+
+        // A second handle (another process) sees the commit and must expect v2.
+        let (other, _) = ActiveManifest::load(dir.path());
+        assert_eq!(other.get("fs").unwrap().timestamp_ms, 2);
+        assert!(dir.path().join("active_versions.json.bak").exists());
+    }
+
+    #[test]
+    fn load_recovers_partial_write_and_migrates_links() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("fs_v1.rs"), "// v1").unwrap();
+        std::fs::write(dir.path().join("fs_v2.rs"), "// v2").unwrap();
+        std::fs::write(dir.path().join("current_fs.rs"), "// v1").unwrap();
+        std::fs::write(dir.path().join("active_versions.json.tmp"), "{\"gener").unwrap();
+
+        let (manifest, report) = ActiveManifest::load(dir.path());
+        assert!(report.discarded_partial_write);
+        assert_eq!(report.migrated_links, vec!["fs".to_string()]);
+        assert_eq!(manifest.get("fs").unwrap().timestamp_ms, 1);
+        assert!(!dir.path().join("current_fs.rs").exists());
+
+        // Corrupt manifest falls back to the backup written by the next commit.
+        let v2 = ActiveEntry::new(2, "h2", &dir.path().join("fs_v2.rs"));
+        manifest.compare_and_swap("fs", Some(1), Some(v2)).unwrap();
+        std::fs::write(manifest.path(), "not json").unwrap();
+        let (restored, report) = ActiveManifest::load(dir.path());
+        assert!(report.restored_from_backup);
+        assert_eq!(restored.get("fs").unwrap().timestamp_ms, 1);
+
+        // A deleted source is reported but the entry is kept, and nothing is rewritten.
+        std::fs::remove_file(dir.path().join("fs_v1.rs")).unwrap();
+        let before = std::fs::read(restored.path()).unwrap();
+        let (reloaded, report) = ActiveManifest::load(dir.path());
+        assert_eq!(
+            report.issues,
+            vec![ConsistencyIssue::MissingSource {
+                skill: "fs".to_string(),
+                path: dir.path().join("fs_v1.rs"),
+            }]
+        );
+        assert_eq!(reloaded.get("fs").unwrap().timestamp_ms, 1);
+        assert_eq!(std::fs::read(reloaded.path()).unwrap(), before);
+    }
+
+    #[test]
+    fn writers_in_separate_handles_never_lose_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let threads: Vec<_> = (0..4)
+            .map(|t| {
+                let dir = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    // Each thread stands in for a separate process with its own handle.
+                    let (manifest, _) = ActiveManifest::load(&dir);
+                    for i in 0..10 {
+                        let source = dir.join(format!("s{}_{}_v1.rs", t, i));
+                        let entry = ActiveEntry::new(1, "h", &source);
+                        manifest.compare_and_swap(&format!("s{}_{}", t, i), None, Some(entry)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let committed = ActiveVersions::read(dir.path());
+        assert_eq!(committed.skills.len(), 40);
+        assert_eq!(committed.generation, 40);
+    }
+}
@@ -0,0 +1,1185 @@
+//! AST-based static analysis for Forge patches.
+//!
+//! Parses proposed skill source with `syn` and walks it with import resolution, so risky APIs
+//! are found by what they *are* rather than how they are spelled: `use std::process::Command as
+//! Cmd; Cmd::new("sh")` is still process spawning, while the same words inside a comment or a
+//! string literal are not. Every finding carries a [`SourceSpan`] pointing at the offending node.
+//!
+//! | Class | Severity |
+//! |-------|----------|
+//! | `unsafe` outside the skill ABI exports | High (Low inside `pagi_dynamic_skill_*`) |
+//! | FFI: `extern` blocks, `#[link]`, `libc`/`nix`/`winapi`/`libloading`, `transmute`, `asm!` | High |
+//! | Process spawning (`std::process::Command`, `tokio::process`, …) | High |
+//! | Network APIs (`std::net`, `reqwest`, `hyper`, …) | Medium |
+//! | Filesystem APIs (`std::fs`, `tokio::fs`, …) | Medium (High for deletes/permissions/links) |
+//! | `include_bytes!`/`include_str!`/`#[path]` of absolute, `..` or computed paths | Critical |
+//! | `#[path]` modules inside the source directory (never analyzed) | High |
+//! | Build scripts, `cargo:` directives, proc-macros, `#[link_section]` | High |
+//!
+//! Macro bodies that do not parse as expressions (`macro_rules!` arms, `thread_local!`, custom
+//! DSLs) are scanned token by token for paths, nested macros and `unsafe`.
+//!
+//! Items under `#[cfg(test)]` and `#[test]` functions are skipped; they never reach the
+//! compiled artifact.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use serde::{Deserialize, Serialize};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Attribute, Expr, Lit, Meta, Token, UseTree};
+
+use crate::red_team::{SecurityFinding, Severity};
+
+/// Reviewer id recorded on verdicts produced by the static analyzer.
+pub const AST_ANALYZER_MODEL: &str = "ast-analyzer-v1";
+
+/// Exported symbols every native Forge skill must define; `unsafe` inside them is expected.
+const ABI_EXPORTS: &[&str] = &["pagi_dynamic_skill_execute", "pagi_dynamic_skill_free"];
+
+/// Binding names that mark a string literal as a probable secret.
+const SECRET_NAMES: &[&str] = &[
+    "api_key", "apikey", "secret", "password", "passwd", "token", "private_key",
+];
+
+/// More `.unwrap()`/`.expect()` calls than this is reported as a panic risk.
+const MAX_PANIC_POINTS: usize = 5;
+
+// ---------------------------------------------------------------------------
+// Spans
+// ---------------------------------------------------------------------------
+
+/// 1-based line/column range of a finding in the analyzed source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSpan {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SourceSpan {
+    fn from_span(span: Span) -> Self {
+        let (start, end) = (span.start(), span.end());
+        Self {
+            line: start.line,
+            column: start.column + 1,
+            end_line: end.line.max(start.line),
+            end_column: end.column + 1,
+        }
+    }
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {}:{}-{}:{}",
+            self.line, self.column, self.end_line, self.end_column
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// API classification
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ApiClass {
+    Process,
+    Ffi,
+    Network,
+    Filesystem,
+}
+
+/// Path prefixes (after import resolution and `core`/`alloc` → `std` normalization).
+const API_RULES: &[(&[&str], ApiClass)] = &[
+    (&["std", "process", "Command"], ApiClass::Process),
+    (&["std", "os", "unix", "process"], ApiClass::Process),
+    (&["std", "os", "windows", "process"], ApiClass::Process),
+    (&["tokio", "process"], ApiClass::Process),
+    (&["async_std", "process"], ApiClass::Process),
+    (&["async_process"], ApiClass::Process),
+    (&["duct"], ApiClass::Process),
+    (&["subprocess"], ApiClass::Process),
+    (&["nix", "unistd", "fork"], ApiClass::Process),
+    (&["nix", "unistd", "execv"], ApiClass::Process),
+    (&["nix", "unistd", "execve"], ApiClass::Process),
+    (&["nix", "unistd", "execvp"], ApiClass::Process),
+    (&["libc"], ApiClass::Ffi),
+    (&["nix"], ApiClass::Ffi),
+    (&["winapi"], ApiClass::Ffi),
+    (&["windows_sys"], ApiClass::Ffi),
+    (&["windows"], ApiClass::Ffi),
+    (&["libloading"], ApiClass::Ffi),
+    (&["std", "mem", "transmute"], ApiClass::Ffi),
+    (&["std", "arch"], ApiClass::Ffi),
+    (&["std", "net"], ApiClass::Network),
+    (&["std", "os", "unix", "net"], ApiClass::Network),
+    (&["tokio", "net"], ApiClass::Network),
+    (&["async_std", "net"], ApiClass::Network),
+    (&["mio", "net"], ApiClass::Network),
+    (&["socket2"], ApiClass::Network),
+    (&["reqwest"], ApiClass::Network),
+    (&["hyper"], ApiClass::Network),
+    (&["ureq"], ApiClass::Network),
+    (&["surf"], ApiClass::Network),
+    (&["isahc"], ApiClass::Network),
+    (&["curl"], ApiClass::Network),
+    (&["attohttpc"], ApiClass::Network),
+    (&["tungstenite"], ApiClass::Network),
+    (&["std", "fs"], ApiClass::Filesystem),
+    (&["std", "os", "unix", "fs"], ApiClass::Filesystem),
+    (&["std", "os", "windows", "fs"], ApiClass::Filesystem),
+    (&["tokio", "fs"], ApiClass::Filesystem),
+    (&["async_std", "fs"], ApiClass::Filesystem),
+    (&["fs_extra"], ApiClass::Filesystem),
+    (&["walkdir"], ApiClass::Filesystem),
+];
+
+/// Filesystem operations that destroy data or change access rather than read/write it.
+const DESTRUCTIVE_FS: &[&str] = &[
+    "remove_file",
+    "remove_dir",
+    "remove_dir_all",
+    "set_permissions",
+    "symlink",
+    "symlink_file",
+    "symlink_dir",
+    "hard_link",
+    "rename",
+];
+
+fn classify(path: &[String]) -> Option<ApiClass> {
+    API_RULES
+        .iter()
+        .filter(|(prefix, _)| {
+            path.len() >= prefix.len() && prefix.iter().zip(path).all(|(a, b)| a == b)
+        })
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, class)| *class)
+}
+
+// ---------------------------------------------------------------------------
+// Import resolution
+// ---------------------------------------------------------------------------
+
+/// Names brought into scope by `use`, `extern crate … as` and `type` aliases.
+///
+/// Resolution is file-wide rather than scope-accurate: a `use` inside one function also applies
+/// to the others. That over-approximates, which is the safe direction for a security gate.
+#[derive(Default)]
+struct Imports {
+    aliases: HashMap<String, Vec<String>>,
+    globs: Vec<Vec<String>>,
+}
+
+impl Imports {
+    fn collect(file: &syn::File) -> Self {
+        let mut collector = ImportCollector::default();
+        collector.visit_file(file);
+        collector.imports
+    }
+
+    fn add_use(&mut self, tree: &UseTree, prefix: &mut Vec<String>) {
+        match tree {
+            UseTree::Path(p) => {
+                prefix.push(p.ident.to_string());
+                self.add_use(&p.tree, prefix);
+                prefix.pop();
+            }
+            UseTree::Name(n) => {
+                let name = n.ident.to_string();
+                if name == "self" {
+                    if let Some(last) = prefix.last() {
+                        self.aliases.insert(last.clone(), prefix.clone());
+                    }
+                } else {
+                    let mut target = prefix.clone();
+                    target.push(name.clone());
+                    self.aliases.insert(name, target);
+                }
+            }
+            UseTree::Rename(r) => {
+                let mut target = prefix.clone();
+                if r.ident != "self" {
+                    target.push(r.ident.to_string());
+                }
+                self.aliases.insert(r.rename.to_string(), target);
+            }
+            UseTree::Glob(_) => self.globs.push(prefix.clone()),
+            UseTree::Group(g) => {
+                for item in &g.items {
+                    self.add_use(item, prefix);
+                }
+            }
+        }
+    }
+
+    /// Expand a path through aliases; returns the resolved path plus glob-import candidates.
+    fn resolve(&self, segments: &[String]) -> Vec<Vec<String>> {
+        let mut path = segments.to_vec();
+        // Follow alias chains (`use a as b; type C = b::X;`), bounded against cycles.
+        for _ in 0..8 {
+            let Some(first) = path.first() else { break };
+            let Some(target) = self.aliases.get(first) else { break };
+            if target.len() == 1 && &target[0] == first {
+                break;
+            }
+            let mut expanded = target.clone();
+            expanded.extend_from_slice(&path[1..]);
+            path = expanded;
+        }
+        let mut candidates = vec![normalize(path.clone())];
+        if !self.aliases.contains_key(&segments[0]) {
+            for glob in &self.globs {
+                let mut candidate = glob.clone();
+                candidate.extend_from_slice(&path);
+                candidates.push(normalize(self.resolve_glob_base(candidate)));
+            }
+        }
+        candidates
+    }
+
+    fn resolve_glob_base(&self, path: Vec<String>) -> Vec<String> {
+        match path.first().and_then(|first| self.aliases.get(first)) {
+            Some(target) if target.len() > 1 || target.first() != path.first() => {
+                let mut expanded = target.clone();
+                expanded.extend_from_slice(&path[1..]);
+                expanded
+            }
+            _ => path,
+        }
+    }
+}
+
+fn normalize(mut path: Vec<String>) -> Vec<String> {
+    if let Some(first) = path.first_mut() {
+        if first == "core" || first == "alloc" {
+            *first = "std".to_string();
+        }
+    }
+    path
+}
+
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments.iter().map(|s| s.ident.to_string()).collect()
+}
+
+#[derive(Default)]
+struct ImportCollector {
+    imports: Imports,
+}
+
+impl<'ast> Visit<'ast> for ImportCollector {
+    fn visit_item_use(&mut self, node: &'ast syn::ItemUse) {
+        self.imports.add_use(&node.tree, &mut Vec::new());
+    }
+
+    fn visit_item_extern_crate(&mut self, node: &'ast syn::ItemExternCrate) {
+        if let Some((_, rename)) = &node.rename {
+            self.imports
+                .aliases
+                .insert(rename.to_string(), vec![node.ident.to_string()]);
+        }
+    }
+
+    fn visit_item_type(&mut self, node: &'ast syn::ItemType) {
+        if let syn::Type::Path(tp) = &*node.ty {
+            self.imports
+                .aliases
+                .insert(node.ident.to_string(), path_segments(&tp.path));
+        }
+        visit::visit_item_type(self, node);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Analyzer
+// ---------------------------------------------------------------------------
+
+/// `syn`-based static analyzer used by the Red-Team gate when no reviewer model is reachable
+/// (and merged into live reviews so the gate never depends on the model alone).
+pub struct AstAnalyzer;
+
+impl AstAnalyzer {
+    /// Analyze a single Rust source file.
+    pub fn analyze(code: &str) -> Vec<SecurityFinding> {
+        Self::analyze_file("lib.rs", code)
+    }
+
+    /// Analyze source with its file name; a file named `build.rs` is itself reported as a
+    /// build script, since Cargo executes it on the build host before any sandbox applies.
+    pub fn analyze_file(file_name: &str, code: &str) -> Vec<SecurityFinding> {
+        let file = match syn::parse_file(code) {
+            Ok(file) => file,
+            Err(e) => {
+                return vec![finding(
+                    "Unanalyzable Source",
+                    Severity::High,
+                    format!("Source does not parse as Rust ({e}); it cannot be verified."),
+                    SourceSpan::from_span(e.span()),
+                    "Submit syntactically valid Rust so the patch can be analyzed.",
+                )];
+            }
+        };
+
+        let mut walker = Walker {
+            imports: Imports::collect(&file),
+            findings: Vec::new(),
+            seen: HashSet::new(),
+            abi_depth: 0,
+            panic_sites: Vec::new(),
+        };
+
+        let is_build_script = std::path::Path::new(file_name)
+            .file_name()
+            .is_some_and(|n| n == "build.rs");
+        if is_build_script {
+            walker.report(
+                "Build Script",
+                Severity::High,
+                "Build scripts run on the build host at compile time, outside the skill sandbox."
+                    .to_string(),
+                Span::call_site(),
+                "Skills may not ship a build.rs; move the logic into the skill body.",
+            );
+        }
+
+        walker.visit_file(&file);
+        walker.finish()
+    }
+}
+
+struct Walker {
+    imports: Imports,
+    findings: Vec<SecurityFinding>,
+    /// (category, line) pairs already reported, so `Command` in a type and a call on the same
+    /// line yields one finding.
+    seen: HashSet<(String, usize)>,
+    /// Non-zero while inside one of the [`ABI_EXPORTS`] functions.
+    abi_depth: usize,
+    panic_sites: Vec<SourceSpan>,
+}
+
+fn finding(
+    category: &str,
+    severity: Severity,
+    description: String,
+    span: SourceSpan,
+    remediation: &str,
+) -> SecurityFinding {
+    SecurityFinding {
+        category: category.to_string(),
+        severity,
+        description,
+        affected_region: Some(span.to_string()),
+        remediation: Some(remediation.to_string()),
+        span: Some(span),
+    }
+}
+
+fn is_test_only(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if attr.path().is_ident("test") {
+            return true;
+        }
+        if !attr.path().is_ident("cfg") {
+            return false;
+        }
+        attr.parse_args::<syn::Ident>().is_ok_and(|ident| ident == "test")
+    })
+}
+
+fn str_lit(expr: &Expr) -> Option<&syn::LitStr> {
+    match expr {
+        Expr::Lit(syn::ExprLit { lit: Lit::Str(s), .. }) => Some(s),
+        _ => None,
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SECRET_NAMES.iter().any(|s| name.contains(s))
+}
+
+/// The file named by `#[path = "…"]`, also when wrapped in `#[cfg_attr(…, path = "…")]`.
+/// `Some(None)` when the attribute is present but its value is not a string literal.
+fn module_path(meta: &Meta) -> Option<Option<String>> {
+    if meta.path().is_ident("path") {
+        return Some(match meta {
+            Meta::NameValue(nv) => str_lit(&nv.value).map(|lit| lit.value()),
+            _ => None,
+        });
+    }
+    if !meta.path().is_ident("cfg_attr") {
+        return None;
+    }
+    let Meta::List(list) = meta else { return None };
+    let nested = list
+        .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        .ok()?;
+    nested.iter().skip(1).find_map(module_path)
+}
+
+/// End (exclusive) of the `a::b::c` path starting at `tokens[start]`, or `start` if none does.
+fn path_end(tokens: &[TokenTree], start: usize) -> usize {
+    let is_colon = |i: usize| matches!(tokens.get(i), Some(TokenTree::Punct(p)) if p.as_char() == ':');
+    let is_ident = |i: usize| matches!(tokens.get(i), Some(TokenTree::Ident(_)));
+    let mut i = start;
+    if is_colon(i) && is_colon(i + 1) {
+        i += 2;
+    }
+    if !is_ident(i) {
+        return start;
+    }
+    i += 1;
+    while is_colon(i) && is_colon(i + 1) && is_ident(i + 2) {
+        i += 3;
+    }
+    i
+}
+
+/// True for paths that leave the skill's own source directory.
+fn escapes_source_dir(path: &str) -> bool {
+    let p = std::path::Path::new(path);
+    p.is_absolute()
+        || path.starts_with('/')
+        || path.starts_with('\\')
+        || path.starts_with('~')
+        || path.get(1..2) == Some(":")
+        || p.components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+}
+
+impl Walker {
+    fn report(
+        &mut self,
+        category: &str,
+        severity: Severity,
+        description: String,
+        span: Span,
+        remediation: &str,
+    ) {
+        let span = SourceSpan::from_span(span);
+        if self.seen.insert((category.to_string(), span.line)) {
+            self.findings
+                .push(finding(category, severity, description, span, remediation));
+        }
+    }
+
+    fn finish(mut self) -> Vec<SecurityFinding> {
+        if self.panic_sites.len() > MAX_PANIC_POINTS {
+            let lines: Vec<String> = self.panic_sites.iter().map(|s| s.line.to_string()).collect();
+            self.findings.push(finding(
+                "Panic in Production",
+                Severity::Medium,
+                format!(
+                    "Code contains {} potential panic points (.unwrap()/.expect()) on lines {}. \
+                     These can crash the process in production.",
+                    self.panic_sites.len(),
+                    lines.join(", ")
+                ),
+                self.panic_sites[0],
+                "Replace .unwrap() with proper error handling (? operator or match).",
+            ));
+        }
+        self.findings
+    }
+
+    fn check_path(&mut self, path: &syn::Path) {
+        let segments = path_segments(path);
+        if segments.is_empty() {
+            return;
+        }
+        let written = segments.join("::");
+        let Some((resolved, class)) = self
+            .imports
+            .resolve(&segments)
+            .into_iter()
+            .find_map(|p| classify(&p).map(|c| (p, c)))
+        else {
+            return;
+        };
+        let shown = resolved.join("::");
+        let via = if shown == written {
+            String::new()
+        } else {
+            format!(" (written as `{written}`)")
+        };
+        let span = path.span();
+        match class {
+            ApiClass::Process => self.report(
+                "Process Spawning (CWE-78)",
+                Severity::High,
+                format!("`{shown}`{via} spawns or replaces processes from inside the skill."),
+                span,
+                "Skills may not execute external programs; implement the logic in Rust or expose it as a reviewed host skill.",
+            ),
+            ApiClass::Ffi => self.report(
+                "FFI Boundary",
+                Severity::High,
+                format!("`{shown}`{via} bypasses Rust's safety guarantees through raw system or foreign calls."),
+                span,
+                "Use safe standard-library APIs; foreign calls are not permitted in generated skills.",
+            ),
+            ApiClass::Network => self.report(
+                "Network Access",
+                Severity::Medium,
+                format!("`{shown}`{via} opens network connections."),
+                span,
+                "Declare the egress need in the skill manifest and restrict it to allowed hosts.",
+            ),
+            ApiClass::Filesystem => {
+                let destructive = resolved
+                    .last()
+                    .is_some_and(|leaf| DESTRUCTIVE_FS.contains(&leaf.as_str()));
+                if destructive {
+                    self.report(
+                        "Destructive Filesystem Operation",
+                        Severity::High,
+                        format!("`{shown}`{via} deletes, relinks or changes permissions on files."),
+                        span,
+                        "Skills may only write inside their sandbox directory and must not delete or relink files.",
+                    );
+                } else {
+                    self.report(
+                        "Filesystem Access",
+                        Severity::Medium,
+                        format!("`{shown}`{via} reads or writes the filesystem."),
+                        span,
+                        "Validate paths against the sandbox root; prefer the KB host APIs for persistence.",
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_attrs(&mut self, attrs: &[Attribute], item_name: Option<&str>) {
+        for attr in attrs {
+            let path = attr.path();
+            if path.is_ident("link_section") {
+                self.report(
+                    "Load-Time Code Execution",
+                    Severity::High,
+                    "`#[link_section]` can place code or data in loader-run sections such as .init_array."
+                        .to_string(),
+                    attr.span(),
+                    "Remove custom link sections.",
+                );
+            } else if path.is_ident("link") {
+                self.report(
+                    "FFI Boundary",
+                    Severity::High,
+                    "`#[link]` pulls a native library into the skill.".to_string(),
+                    attr.span(),
+                    "Foreign libraries are not permitted in generated skills.",
+                );
+            } else if path.is_ident("proc_macro")
+                || path.is_ident("proc_macro_derive")
+                || path.is_ident("proc_macro_attribute")
+            {
+                self.report(
+                    "Build Script",
+                    Severity::High,
+                    "Procedural macros execute arbitrary code inside the compiler.".to_string(),
+                    attr.span(),
+                    "Skills may not define procedural macros.",
+                );
+            } else if (path.is_ident("no_mangle") || path.is_ident("export_name"))
+                && !item_name.is_some_and(|n| ABI_EXPORTS.contains(&n))
+            {
+                self.report(
+                    "Exported Symbol",
+                    Severity::Medium,
+                    format!(
+                        "`{}` exports a symbol beyond the skill ABI ({}).",
+                        item_name.unwrap_or("item"),
+                        ABI_EXPORTS.join(", ")
+                    ),
+                    attr.span(),
+                    "Export only the skill ABI functions.",
+                );
+            }
+        }
+    }
+
+    fn report_unsafe(&mut self, what: &str, span: Span) {
+        if self.abi_depth > 0 {
+            self.report(
+                "Unsafe Code",
+                Severity::Low,
+                format!("{what} at the skill ABI boundary (expected for C-string conversion)."),
+                span,
+                "Keep ABI unsafe limited to CStr/CString conversion and document it with // SAFETY: comments.",
+            );
+        } else {
+            self.report(
+                "Unsafe Code",
+                Severity::High,
+                format!("{what} outside the skill ABI exports must be audited for soundness."),
+                span,
+                "Remove unsafe code; generated skills may only use unsafe at the ABI boundary.",
+            );
+        }
+    }
+
+    fn check_macro(&mut self, mac: &syn::Macro) {
+        let name = mac
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident.to_string())
+            .unwrap_or_default();
+        let args = mac
+            .parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            .ok();
+
+        match name.as_str() {
+            "include_bytes" | "include_str" | "include" => {
+                let target = args.as_ref().and_then(|a| a.first()).and_then(str_lit);
+                match target {
+                    Some(lit) if !escapes_source_dir(&lit.value()) => self.report(
+                        "Compile-Time File Inclusion",
+                        Severity::Low,
+                        format!("`{name}!(\"{}\")` embeds a file from the skill's source directory.", lit.value()),
+                        mac.span(),
+                        "Prefer inline constants so the reviewed source is the whole skill.",
+                    ),
+                    Some(lit) => self.report(
+                        "Compile-Time File Inclusion",
+                        Severity::Critical,
+                        format!(
+                            "`{name}!(\"{}\")` embeds a file from outside the skill's source directory into the artifact.",
+                            lit.value()
+                        ),
+                        mac.span(),
+                        "Never include host files; this can exfiltrate secrets at build time.",
+                    ),
+                    None => self.report(
+                        "Compile-Time File Inclusion",
+                        Severity::Critical,
+                        format!("`{name}!` with a computed path cannot be verified to stay inside the skill's sources."),
+                        mac.span(),
+                        "Never include host files; this can exfiltrate secrets at build time.",
+                    ),
+                }
+                return;
+            }
+            "env" | "option_env" => self.report(
+                "Compile-Time Environment Access",
+                Severity::Medium,
+                format!("`{name}!` bakes build-host environment variables into the artifact."),
+                mac.span(),
+                "Read configuration at runtime from the skill arguments instead.",
+            ),
+            "asm" | "global_asm" | "naked_asm" => self.report(
+                "FFI Boundary",
+                Severity::High,
+                format!("`{name}!` embeds inline assembly."),
+                mac.span(),
+                "Inline assembly is not permitted in generated skills.",
+            ),
+            _ => {}
+        }
+
+        if let Some(args) = &args {
+            let directive = args
+                .iter()
+                .filter_map(str_lit)
+                .any(|lit| lit.value().trim_start().starts_with("cargo:"));
+            if directive {
+                self.report(
+                    "Build Script",
+                    Severity::High,
+                    format!("`{name}!` emits a `cargo:` build-script directive (link flags, env or cfg injection)."),
+                    mac.span(),
+                    "Skills may not ship build scripts or emit Cargo directives.",
+                );
+            }
+            // Macro bodies are opaque to `syn`; re-visit arguments that parse as expressions
+            // so `println!("{:?}", Command::new("sh").output())` is not a blind spot.
+            for arg in args {
+                self.visit_expr(arg);
+            }
+        } else {
+            self.check_tokens(mac.tokens.clone());
+        }
+    }
+
+    /// Scan a macro body that is not an expression list. Groups that parse as statements get
+    /// the full walk; anything else is searched for paths, nested macros, `unsafe` and string
+    /// literals.
+    fn check_tokens(&mut self, tokens: TokenStream) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut i = 0;
+        while i < tokens.len() {
+            let end = path_end(&tokens, i);
+            if end > i {
+                let is_bang = matches!(tokens.get(end), Some(TokenTree::Punct(p)) if p.as_char() == '!');
+                if is_bang && matches!(tokens.get(end + 1), Some(TokenTree::Group(_))) {
+                    let invocation: TokenStream = tokens[i..end + 2].iter().cloned().collect();
+                    if let Ok(mac) = syn::parse2::<syn::Macro>(invocation) {
+                        self.visit_macro(&mac);
+                    }
+                    i = end + 2;
+                    continue;
+                }
+                if matches!(&tokens[i], TokenTree::Ident(ident) if ident == "unsafe") {
+                    self.report_unsafe("`unsafe` in a macro body", tokens[i].span());
+                } else {
+                    let path: TokenStream = tokens[i..end].iter().cloned().collect();
+                    if let Ok(path) = syn::parse2::<syn::Path>(path) {
+                        self.check_path(&path);
+                    }
+                }
+                i = end;
+                continue;
+            }
+            match &tokens[i] {
+                TokenTree::Group(group) => {
+                    match syn::parse::Parser::parse2(syn::Block::parse_within, group.stream()) {
+                        Ok(stmts) => stmts.iter().for_each(|stmt| self.visit_stmt(stmt)),
+                        Err(_) => self.check_tokens(group.stream()),
+                    }
+                }
+                TokenTree::Literal(lit) => {
+                    if let Ok(lit) = syn::parse2::<syn::LitStr>(TokenTree::Literal(lit.clone()).into()) {
+                        if lit.value().trim_start().starts_with("cargo:") {
+                            self.report(
+                                "Build Script",
+                                Severity::High,
+                                "Macro body emits a `cargo:` build-script directive.".to_string(),
+                                lit.span(),
+                                "Skills may not ship build scripts or emit Cargo directives.",
+                            );
+                        }
+                        self.visit_lit_str(&lit);
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    fn check_secret_binding(&mut self, name: &str, value: &Expr) {
+        if let Some(lit) = str_lit(value) {
+            if is_secret_name(name) && !lit.value().trim().is_empty() {
+                self.report(
+                    "Hardcoded Secrets",
+                    Severity::High,
+                    format!("`{name}` is initialized from a string literal."),
+                    lit.span(),
+                    "Use environment variables or a secrets manager instead of hardcoding.",
+                );
+            }
+        }
+    }
+}
+
+/// Whether an expression derives from external input (a cast or a `.parse()`).
+fn looks_external(expr: &Expr) -> bool {
+    struct Finder(bool);
+    impl<'ast> Visit<'ast> for Finder {
+        fn visit_expr_cast(&mut self, _: &'ast syn::ExprCast) {
+            self.0 = true;
+        }
+        fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+            if node.method == "parse" {
+                self.0 = true;
+            }
+            visit::visit_expr_method_call(self, node);
+        }
+    }
+    let mut finder = Finder(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
+impl<'ast> Visit<'ast> for Walker {
+    fn visit_item(&mut self, node: &'ast syn::Item) {
+        let attrs: &[Attribute] = match node {
+            syn::Item::Fn(i) => &i.attrs,
+            syn::Item::Mod(i) => &i.attrs,
+            syn::Item::Impl(i) => &i.attrs,
+            syn::Item::Const(i) => &i.attrs,
+            syn::Item::Static(i) => &i.attrs,
+            syn::Item::Use(i) => &i.attrs,
+            _ => &[],
+        };
+        if is_test_only(attrs) {
+            return;
+        }
+        visit::visit_item(self, node);
+    }
+
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        let name = node.sig.ident.to_string();
+        self.check_attrs(&node.attrs, Some(&name));
+        let is_abi = ABI_EXPORTS.contains(&name.as_str());
+        if node.sig.unsafety.is_some() && !is_abi {
+            self.report_unsafe(&format!("`unsafe fn {name}`"), node.sig.span());
+        }
+        if is_abi {
+            self.abi_depth += 1;
+        }
+        visit::visit_item_fn(self, node);
+        if is_abi {
+            self.abi_depth -= 1;
+        }
+    }
+
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        if is_test_only(&node.attrs) {
+            return;
+        }
+        let name = node.sig.ident.to_string();
+        self.check_attrs(&node.attrs, Some(&name));
+        if node.sig.unsafety.is_some() {
+            self.report_unsafe(&format!("`unsafe fn {name}`"), node.sig.span());
+        }
+        visit::visit_impl_item_fn(self, node);
+    }
+
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        if let Some(token) = &node.unsafety {
+            self.report_unsafe("`unsafe impl`", token.span);
+        }
+        visit::visit_item_impl(self, node);
+    }
+
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        if let Some(token) = &node.unsafety {
+            self.report_unsafe("`unsafe trait`", token.span);
+        }
+        visit::visit_item_trait(self, node);
+    }
+
+    fn visit_item_static(&mut self, node: &'ast syn::ItemStatic) {
+        self.check_attrs(&node.attrs, Some(&node.ident.to_string()));
+        if matches!(node.mutability, syn::StaticMutability::Mut(_)) {
+            self.report_unsafe("`static mut`", node.span());
+        }
+        self.check_secret_binding(&node.ident.to_string(), &node.expr);
+        visit::visit_item_static(self, node);
+    }
+
+    fn visit_item_const(&mut self, node: &'ast syn::ItemConst) {
+        self.check_secret_binding(&node.ident.to_string(), &node.expr);
+        visit::visit_item_const(self, node);
+    }
+
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        let name = node.ident.to_string();
+        for attr in &node.attrs {
+            let Some(file) = module_path(&attr.meta) else { continue };
+            match file {
+                Some(file) if !escapes_source_dir(&file) => self.report(
+                    "Compile-Time File Inclusion",
+                    Severity::High,
+                    format!("`mod {name}` is compiled from `{file}`, which is not part of the reviewed source."),
+                    attr.span(),
+                    "Inline the module so the reviewed source is the whole skill.",
+                ),
+                Some(file) => self.report(
+                    "Compile-Time File Inclusion",
+                    Severity::Critical,
+                    format!("`mod {name}` is compiled from `{file}`, outside the skill's source directory."),
+                    attr.span(),
+                    "Never compile host files into a skill; inline the module.",
+                ),
+                None => self.report(
+                    "Compile-Time File Inclusion",
+                    Severity::Critical,
+                    format!("`mod {name}` has a `#[path]` that cannot be verified to stay inside the skill's sources."),
+                    attr.span(),
+                    "Never compile host files into a skill; inline the module.",
+                ),
+            }
+        }
+        visit::visit_item_mod(self, node);
+    }
+
+    fn visit_item_foreign_mod(&mut self, node: &'ast syn::ItemForeignMod) {
+        self.check_attrs(&node.attrs, None);
+        let abi = node
+            .abi
+            .name
+            .as_ref()
+            .map(|n| n.value())
+            .unwrap_or_else(|| "C".to_string());
+        self.report(
+            "FFI Boundary",
+            Severity::High,
+            format!("`extern \"{abi}\"` block declares {} foreign item(s).", node.items.len()),
+            node.span(),
+            "Foreign function declarations are not permitted in generated skills.",
+        );
+        visit::visit_item_foreign_mod(self, node);
+    }
+
+    fn visit_expr_unsafe(&mut self, node: &'ast syn::ExprUnsafe) {
+        self.report_unsafe("`unsafe` block", node.unsafe_token.span);
+        visit::visit_expr_unsafe(self, node);
+    }
+
+    fn visit_path(&mut self, node: &'ast syn::Path) {
+        self.check_path(node);
+        visit::visit_path(self, node);
+    }
+
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        self.check_macro(node);
+        visit::visit_macro(self, node);
+    }
+
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        if node.method == "unwrap" || node.method == "expect" {
+            self.panic_sites
+                .push(SourceSpan::from_span(node.method.span()));
+        }
+        visit::visit_expr_method_call(self, node);
+    }
+
+    fn visit_expr_call(&mut self, node: &'ast syn::ExprCall) {
+        if let Expr::Path(func) = &*node.func {
+            let is_with_capacity = func
+                .path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "with_capacity");
+            if is_with_capacity && node.args.iter().any(looks_external) {
+                self.report(
+                    "Unbounded Allocation",
+                    Severity::Medium,
+                    "Allocation size may be controlled by external input.".to_string(),
+                    node.span(),
+                    "Cap allocation sizes with a maximum limit.",
+                );
+            }
+        }
+        visit::visit_expr_call(self, node);
+    }
+
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        if let (syn::Pat::Ident(pat), Some(init)) = (&node.pat, &node.init) {
+            self.check_secret_binding(&pat.ident.to_string(), &init.expr);
+        }
+        visit::visit_local(self, node);
+    }
+
+    fn visit_field_value(&mut self, node: &'ast syn::FieldValue) {
+        if let syn::Member::Named(ident) = &node.member {
+            self.check_secret_binding(&ident.to_string(), &node.expr);
+        }
+        visit::visit_field_value(self, node);
+    }
+
+    fn visit_lit_str(&mut self, node: &'ast syn::LitStr) {
+        let value = node.value();
+        if value.contains("../") || value.contains("..\\") {
+            self.report(
+                "Path Traversal (CWE-22)",
+                Severity::Medium,
+                format!("String literal {:?} contains a parent-directory component.", value),
+                node.span(),
+                "Validate paths against a root directory. Use canonicalize() and check prefix.",
+            );
+        }
+        if let Some(token) = value.strip_prefix("Bearer ") {
+            if token.trim().len() >= 8 {
+                self.report(
+                    "Hardcoded Secrets",
+                    Severity::High,
+                    "String literal embeds a bearer token.".to_string(),
+                    node.span(),
+                    "Use environment variables or a secrets manager instead of hardcoding.",
+                );
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(findings: &[SecurityFinding]) -> Vec<&str> {
+        findings.iter().map(|f| f.category.as_str()).collect()
+    }
+
+    #[test]
+    fn abi_skill_is_clean_apart_from_boundary_unsafe() {
+        let code = r#"
+            use std::ffi::{CStr, CString};
+            use std::os::raw::c_char;
+
+            #[no_mangle]
+            pub extern "C" fn pagi_dynamic_skill_execute(args: *const c_char) -> *mut c_char {
+                let input = unsafe { CStr::from_ptr(args) }.to_string_lossy().into_owned();
+                CString::new(format!("{{\"echo\":{input:?}}}")).unwrap().into_raw()
+            }
+
+            #[no_mangle]
+            pub extern "C" fn pagi_dynamic_skill_free(ptr: *mut c_char) {
+                if !ptr.is_null() {
+                    unsafe { drop(CString::from_raw(ptr)) };
+                }
+            }
+        "#;
+        let findings = AstAnalyzer::analyze(code);
+        assert!(findings.iter().all(|f| f.severity == Severity::Low), "{findings:?}");
+        assert!(categories(&findings).iter().all(|c| *c == "Unsafe Code"));
+    }
+
+    #[test]
+    fn comments_and_string_literals_are_not_findings() {
+        let code = r#"
+            // std::process::Command::new("rm") would be bad; unsafe { } too.
+            pub fn describe() -> &'static str {
+                "use std::process::Command; unsafe { libc::system() }"
+            }
+        "#;
+        assert!(AstAnalyzer::analyze(code).is_empty());
+    }
+
+    #[test]
+    fn use_aliases_are_resolved() {
+        let code = r#"
+            use std::process::Command as Harmless;
+            use std::{net as n, fs::remove_dir_all as tidy};
+            use std::process::*;
+            type Shell = Harmless;
+
+            pub fn run() {
+                let _ = Harmless::new("sh");
+                let _ = <Shell>::new("bash");
+                let _ = n::TcpStream::connect("10.0.0.1:4444");
+                let _ = tidy("/");
+                let _ = Stdio::null();
+            }
+        "#;
+        let findings = AstAnalyzer::analyze(code);
+        let spawn: Vec<_> = findings
+            .iter()
+            .filter(|f| f.category == "Process Spawning (CWE-78)")
+            .collect();
+        // The `type` alias itself, the aliased call and the qualified-self call.
+        let lines: Vec<usize> = spawn.iter().map(|f| f.span.unwrap().line).collect();
+        assert_eq!(lines, vec![5, 8, 9], "{findings:?}");
+        assert!(spawn[1].description.contains("written as `Harmless::new`"));
+        assert!(categories(&findings).contains(&"Network Access"));
+        assert!(categories(&findings).contains(&"Destructive Filesystem Operation"));
+    }
+
+    #[test]
+    fn unsafe_ffi_and_macro_bodies_are_flagged_with_spans() {
+        let code = "extern \"C\" { fn system(cmd: *const u8) -> i32; }\n\
+                    pub fn go() {\n    println!(\"{:?}\", std::process::Command::new(\"id\").output());\n    \
+                    unsafe { system(b\"id\\0\".as_ptr()); }\n}\n";
+        let findings = AstAnalyzer::analyze(code);
+        let ffi = findings.iter().find(|f| f.category == "FFI Boundary").unwrap();
+        assert_eq!(ffi.span.unwrap().line, 1);
+        let spawn = findings
+            .iter()
+            .find(|f| f.category == "Process Spawning (CWE-78)")
+            .unwrap();
+        assert_eq!((spawn.span.unwrap().line, spawn.span.unwrap().column), (3, 22));
+        let unsafe_block = findings.iter().find(|f| f.category == "Unsafe Code").unwrap();
+        assert_eq!(unsafe_block.severity, Severity::High);
+        assert_eq!(unsafe_block.span.unwrap().line, 4);
+    }
+
+    #[test]
+    fn include_of_outside_paths_and_build_directives() {
+        let code = r#"
+            const KEY: &[u8] = include_bytes!("../../../etc/shadow");
+            const LOCAL: &str = include_str!("data/table.txt");
+            fn main() { println!("cargo:rustc-link-lib=dylib=evil"); }
+        "#;
+        let findings = AstAnalyzer::analyze(code);
+        let include: Vec<_> = findings
+            .iter()
+            .filter(|f| f.category == "Compile-Time File Inclusion")
+            .map(|f| f.severity)
+            .collect();
+        assert_eq!(include, vec![Severity::Critical, Severity::Low]);
+        assert!(categories(&findings).contains(&"Build Script"));
+
+        let build = AstAnalyzer::analyze_file("skill/build.rs", "fn main() {}");
+        assert_eq!(categories(&build), vec!["Build Script"]);
+    }
+
+    #[test]
+    fn test_modules_are_skipped_and_parse_errors_block() {
+        let code = r#"
+            pub fn ok() {}
+            #[cfg(test)]
+            mod tests {
+                #[test]
+                fn t() { std::process::Command::new("true").status().unwrap(); }
+            }
+        "#;
+        assert!(AstAnalyzer::analyze(code).is_empty());
+
+        let broken = AstAnalyzer::analyze("pub fn (");
+        assert_eq!(broken[0].category, "Unanalyzable Source");
+        assert!(broken[0].severity.is_blocking());
+    }
+
+    #[test]
+    fn macro_bodies_that_are_not_expressions_are_scanned() {
+        let code = r#"
+            use std::process::Command as C;
+            macro_rules! run {
+                ($cmd:expr) => { C::new($cmd).spawn() };
+            }
+            macro_rules! poke {
+                () => { unsafe { *(0x10 as *mut u8) = 0 } };
+            }
+            thread_local! {
+                static SOCK: std::net::UdpSocket = std::net::UdpSocket::bind("0.0.0.0:0").unwrap();
+            }
+            pub fn go() {
+                let _ = run!("sh");
+                poke!();
+            }
+        "#;
+        let findings = AstAnalyzer::analyze(code);
+        let spawn = findings
+            .iter()
+            .find(|f| f.category == "Process Spawning (CWE-78)")
+            .expect("spawn inside macro_rules! arm");
+        assert_eq!(spawn.span.unwrap().line, 4);
+        assert!(findings
+            .iter()
+            .any(|f| f.category == "Unsafe Code" && f.severity == Severity::High));
+        assert!(categories(&findings).contains(&"Network Access"));
+    }
+
+    #[test]
+    fn path_modules_are_flagged() {
+        let code = r#"
+            #[path = "/etc/pagi/secrets.rs"]
+            mod secrets;
+            #[cfg_attr(not(test), path = "helpers.rs")]
+            mod helpers;
+            mod inline {}
+        "#;
+        let findings = AstAnalyzer::analyze(code);
+        let include: Vec<_> = findings
+            .iter()
+            .filter(|f| f.category == "Compile-Time File Inclusion")
+            .map(|f| (f.severity, f.span.unwrap().line))
+            .collect();
+        assert_eq!(include, vec![(Severity::Critical, 2), (Severity::High, 4)]);
+    }
+}
@@ -0,0 +1,17 @@
+//! `pagi-skill-host`: runs one Forge-generated skill library out-of-process.
+//!
+//! Spawned by `SkillLoader` in sandbox mode and driven over stdin/stdout; not meant to be
+//! started by hand. See `pagi_evolution::sandbox` for the protocol and limits.
+
+use pagi_evolution::sandbox::{run_host, HostArgs};
+
+fn main() {
+    let args = match HostArgs::parse(std::env::args_os().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("pagi-skill-host: {}", e);
+            std::process::exit(2);
+        }
+    };
+    std::process::exit(run_host(&args));
+}
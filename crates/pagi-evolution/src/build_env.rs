@@ -0,0 +1,546 @@
+//! Offline, reproducible build environment for Forge-generated crates.
+//!
+//! Every Forge build goes through a persistent directory (default `./data/pagi_evolution/build`):
+//!
+//! ```text
+//! build/
+//!   crates/<crate>/   Cargo.toml (deps pinned to the workspace lockfile), Cargo.lock, src/lib.rs
+//!   vendor/           dependency sources copied out of the local registry by `cargo vendor`
+//!   target/           shared target dir, so serde & co. compile once
+//! ```
+//!
+//! The crate's `Cargo.lock` is seeded from the workspace `Cargo.lock` and pruned with
+//! `cargo update --workspace`, which keeps every remaining package at the workspace version.
+//! Builds then run `cargo build --offline --locked` against the vendored sources with
+//! `codegen-units = 1` and the build root remapped out of debug paths, so the same source on the
+//! same toolchain rebuilds byte-identically. The [`ToolchainFingerprint`] recorded with each
+//! artifact captures everything needed to check that later.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::skill::SkillError;
+
+/// Direct dependencies available to generated skills, with extra manifest keys.
+/// Versions come from the workspace lockfile.
+pub const FORGE_DEPENDENCIES: &[(&str, &str)] = &[
+    ("serde", r#"features = ["derive"]"#),
+    ("serde_json", ""),
+    ("libc", ""),
+];
+
+/// Prefix the build root is remapped to in compiled artifacts.
+const REMAPPED_ROOT: &str = "/pagi/forge";
+
+/// Records which workspace lockfile and manifest a crate directory was prepared from.
+const STAMP_FILE: &str = ".pagi-build-stamp";
+
+/// Serializes builds: crate directories and `src/lib.rs` are reused between builds.
+static BUILD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Toolchain and input identity of a Forge build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolchainFingerprint {
+    /// `rustc` release, e.g. `1.83.0`.
+    pub rustc_version: String,
+    /// `rustc` commit hash.
+    pub rustc_commit: String,
+    /// `cargo -V` output.
+    pub cargo_version: String,
+    /// Host triple the compiler runs on.
+    pub host: String,
+    /// Target triple of the artifact.
+    pub target: String,
+    /// SHA-256 of the crate's `Cargo.lock` (the pinned dependency set).
+    pub lockfile_sha256: String,
+    /// SHA-256 of the produced artifact.
+    pub artifact_sha256: String,
+}
+
+impl ToolchainFingerprint {
+    /// True when `other` was produced by the same toolchain from the same dependency set,
+    /// i.e. rebuilding the same source should reproduce `artifact_sha256`.
+    pub fn same_toolchain(&self, other: &Self) -> bool {
+        self.rustc_version == other.rustc_version
+            && self.rustc_commit == other.rustc_commit
+            && self.cargo_version == other.cargo_version
+            && self.host == other.host
+            && self.target == other.target
+            && self.lockfile_sha256 == other.lockfile_sha256
+    }
+}
+
+/// A built artifact copied out of the shared target directory, plus its fingerprint.
+#[derive(Debug, Clone)]
+pub struct BuildOutput {
+    pub artifact: PathBuf,
+    pub fingerprint: ToolchainFingerprint,
+}
+
+/// Configuration for offline Forge builds.
+#[derive(Debug, Clone)]
+pub struct BuildEnv {
+    /// Persistent root holding `crates/`, `vendor/` and `target/`.
+    pub root: PathBuf,
+    /// Workspace `Cargo.lock` that pins dependency versions. When missing, the crate is
+    /// locked against whatever the local registry cache holds (and a warning is logged).
+    pub workspace_lock: PathBuf,
+    /// Pass `--offline` to cargo (default). Disable once to let `cargo vendor` download
+    /// dependencies the local registry cache does not have.
+    pub offline: bool,
+}
+
+impl Default for BuildEnv {
+    fn default() -> Self {
+        let base = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self {
+            root: base.join("data").join("pagi_evolution").join("build"),
+            workspace_lock: default_workspace_lock(),
+            offline: true,
+        }
+    }
+}
+
+/// The lockfile of the workspace this crate was built in, else `./Cargo.lock`.
+fn default_workspace_lock() -> PathBuf {
+    let built_in = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../Cargo.lock");
+    if built_in.exists() {
+        built_in
+    } else {
+        PathBuf::from("Cargo.lock")
+    }
+}
+
+impl BuildEnv {
+    /// Reads `PAGI_FORGE_BUILD_DIR`, `PAGI_FORGE_WORKSPACE_LOCK` and `PAGI_FORGE_OFFLINE`.
+    pub fn from_env() -> Self {
+        let mut env = Self::default();
+        if let Ok(dir) = std::env::var("PAGI_FORGE_BUILD_DIR") {
+            if !dir.trim().is_empty() {
+                env.root = PathBuf::from(dir.trim());
+            }
+        }
+        if let Ok(lock) = std::env::var("PAGI_FORGE_WORKSPACE_LOCK") {
+            if !lock.trim().is_empty() {
+                env.workspace_lock = PathBuf::from(lock.trim());
+            }
+        }
+        if let Ok(v) = std::env::var("PAGI_FORGE_OFFLINE") {
+            env.offline = !matches!(v.trim().to_lowercase().as_str(), "false" | "0" | "no");
+        }
+        env
+    }
+
+    pub fn crate_dir(&self, crate_name: &str) -> PathBuf {
+        self.root.join("crates").join(crate_name)
+    }
+
+    pub fn vendor_dir(&self) -> PathBuf {
+        self.root.join("vendor")
+    }
+
+    pub fn target_dir(&self) -> PathBuf {
+        self.root.join("target")
+    }
+
+    /// Build `source` as the `src/lib.rs` of cdylib `crate_name`, for `target`
+    /// (`None` = host), and copy the artifact to `dest`.
+    ///
+    /// The copy happens before the build lock is released, so a concurrent build of the same
+    /// crate cannot overwrite the artifact in the shared target directory first.
+    pub fn build(
+        &self,
+        crate_name: &str,
+        source: &str,
+        target: Option<&str>,
+        dest: &Path,
+    ) -> Result<BuildOutput, SkillError> {
+        let _guard = BUILD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let crate_dir = self.prepare_crate(crate_name)?;
+        let lib_rs = crate_dir.join("src").join("lib.rs");
+        // Leave an unchanged source untouched so cargo sees nothing to rebuild.
+        if std::fs::read_to_string(&lib_rs).ok().as_deref() != Some(source) {
+            std::fs::write(&lib_rs, source).map_err(io_err("write src/lib.rs"))?;
+        }
+
+        let mut args = vec!["build", "--release", "--locked"];
+        if self.offline {
+            args.push("--offline");
+        }
+        if let Some(t) = target {
+            args.extend(["--target", t]);
+        }
+        let output = self
+            .cargo(&crate_dir)
+            .args(&args)
+            .arg("--target-dir")
+            .arg(self.target_dir())
+            .env("RUSTFLAGS", self.rustflags())
+            .output()
+            .map_err(|e| SkillError::Load(format!("cargo build spawn failed: {}", e)))?;
+        check("cargo build", &output)?;
+
+        let release = match target {
+            Some(t) => self.target_dir().join(t).join("release"),
+            None => self.target_dir().join("release"),
+        };
+        let artifact = if target.is_some_and(|t| t.starts_with("wasm32")) {
+            release.join(format!("{}.wasm", crate_name))
+        } else {
+            release.join(format!(
+                "{}{}{}",
+                std::env::consts::DLL_PREFIX,
+                crate_name,
+                std::env::consts::DLL_SUFFIX
+            ))
+        };
+        if !artifact.exists() {
+            return Err(SkillError::Load(format!(
+                "artifact not found at {}",
+                artifact.display()
+            )));
+        }
+
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(io_err("create artifact directory"))?;
+        }
+        std::fs::copy(&artifact, dest).map_err(io_err("copy artifact"))?;
+        let artifact = dest.to_path_buf();
+
+        let fingerprint = self.fingerprint(&crate_dir, &artifact, target)?;
+        info!(
+            target: "pagi::evolution",
+            path = %artifact.display(),
+            rustc = %fingerprint.rustc_version,
+            lock = &fingerprint.lockfile_sha256[..12],
+            artifact = &fingerprint.artifact_sha256[..12],
+            "Reproducible Forge build complete"
+        );
+        Ok(BuildOutput {
+            artifact,
+            fingerprint,
+        })
+    }
+
+    /// Fingerprint the current toolchain for an existing artifact built from `crate_dir`.
+    pub fn fingerprint(
+        &self,
+        crate_dir: &Path,
+        artifact: &Path,
+        target: Option<&str>,
+    ) -> Result<ToolchainFingerprint, SkillError> {
+        let rustc = Command::new("rustc")
+            .arg("-vV")
+            .output()
+            .map_err(|e| SkillError::Load(format!("rustc -vV failed: {}", e)))?;
+        check("rustc -vV", &rustc)?;
+        let rustc = String::from_utf8_lossy(&rustc.stdout).into_owned();
+        let field = |key: &str| {
+            rustc
+                .lines()
+                .find_map(|l| l.strip_prefix(key))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+        let cargo = self
+            .cargo(crate_dir)
+            .arg("-V")
+            .output()
+            .map_err(|e| SkillError::Load(format!("cargo -V failed: {}", e)))?;
+        let host = field("host:");
+
+        Ok(ToolchainFingerprint {
+            rustc_version: field("release:"),
+            rustc_commit: field("commit-hash:"),
+            cargo_version: String::from_utf8_lossy(&cargo.stdout).trim().to_string(),
+            target: target.map(str::to_string).unwrap_or_else(|| host.clone()),
+            host,
+            lockfile_sha256: sha256_file(&crate_dir.join("Cargo.lock"))?,
+            artifact_sha256: sha256_file(artifact)?,
+        })
+    }
+
+    fn cargo(&self, dir: &Path) -> Command {
+        let mut cmd = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+        cmd.current_dir(dir);
+        // Inherited from `cargo run`; they would redirect or reconfigure the nested build.
+        for var in ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET", "CARGO_ENCODED_RUSTFLAGS"] {
+            cmd.env_remove(var);
+        }
+        cmd
+    }
+
+    fn rustflags(&self) -> String {
+        format!("--remap-path-prefix={}={}", self.root.display(), REMAPPED_ROOT)
+    }
+
+    /// Write the manifest, lockfile and vendor config for `crate_name` unless the stamp
+    /// shows they already match the current workspace lockfile.
+    fn prepare_crate(&self, crate_name: &str) -> Result<PathBuf, SkillError> {
+        let crate_dir = self.crate_dir(crate_name);
+        std::fs::create_dir_all(crate_dir.join("src")).map_err(io_err("create crate dir"))?;
+
+        let workspace_lock = std::fs::read_to_string(&self.workspace_lock).ok();
+        let manifest = manifest(crate_name, workspace_lock.as_deref());
+        let stamp = sha256_hex(
+            format!("{}\n{}", manifest, workspace_lock.as_deref().unwrap_or("")).as_bytes(),
+        );
+        let stamp_path = crate_dir.join(STAMP_FILE);
+        if std::fs::read_to_string(&stamp_path).ok().as_deref() == Some(stamp.as_str())
+            && crate_dir.join("Cargo.lock").exists()
+        {
+            return Ok(crate_dir);
+        }
+
+        info!(
+            target: "pagi::evolution",
+            crate_dir = %crate_dir.display(),
+            "Preparing offline Forge build environment"
+        );
+        std::fs::write(crate_dir.join("Cargo.toml"), &manifest).map_err(io_err("write Cargo.toml"))?;
+        // The vendor config from a previous preparation must not affect re-locking.
+        let _ = std::fs::remove_file(crate_dir.join(".cargo").join("config.toml"));
+        if !crate_dir.join("src").join("lib.rs").exists() {
+            std::fs::write(crate_dir.join("src").join("lib.rs"), "")
+                .map_err(io_err("write src/lib.rs"))?;
+        }
+
+        let offline: &[&str] = if self.offline { &["--offline"] } else { &[] };
+        match &workspace_lock {
+            Some(lock) => {
+                std::fs::write(crate_dir.join("Cargo.lock"), lock)
+                    .map_err(io_err("seed Cargo.lock"))?;
+                // Drops packages this crate does not use; keeps the rest at workspace versions.
+                let out = self
+                    .cargo(&crate_dir)
+                    .args(["update", "--workspace"])
+                    .args(offline)
+                    .output()
+                    .map_err(|e| SkillError::Load(format!("cargo update spawn failed: {}", e)))?;
+                check("cargo update --workspace", &out)?;
+            }
+            None => {
+                warn!(
+                    target: "pagi::evolution",
+                    lock = %self.workspace_lock.display(),
+                    "Workspace Cargo.lock not found — locking Forge dependencies from the local registry"
+                );
+                let out = self
+                    .cargo(&crate_dir)
+                    .arg("generate-lockfile")
+                    .args(offline)
+                    .output()
+                    .map_err(|e| SkillError::Load(format!("cargo generate-lockfile spawn failed: {}", e)))?;
+                check("cargo generate-lockfile", &out)?;
+            }
+        }
+
+        let vendor = self.vendor_dir();
+        let out = self
+            .cargo(&crate_dir)
+            .args(["vendor", "--locked", "--versioned-dirs", "--no-delete"])
+            .args(offline)
+            .arg(&vendor)
+            .output()
+            .map_err(|e| SkillError::Load(format!("cargo vendor spawn failed: {}", e)))?;
+        check("cargo vendor", &out).map_err(|e| {
+            SkillError::Load(format!(
+                "{} (run `cargo fetch` in the workspace, or build once with PAGI_FORGE_OFFLINE=false)",
+                e
+            ))
+        })?;
+
+        std::fs::create_dir_all(crate_dir.join(".cargo")).map_err(io_err("create .cargo"))?;
+        let config = format!(
+            "[source.crates-io]\nreplace-with = \"vendored-sources\"\n\n\
+             [source.vendored-sources]\ndirectory = {}\n\n[net]\noffline = true\n",
+            toml_string(&vendor.display().to_string())
+        );
+        std::fs::write(crate_dir.join(".cargo").join("config.toml"), config)
+            .map_err(io_err("write .cargo/config.toml"))?;
+        std::fs::write(&stamp_path, stamp).map_err(io_err("write build stamp"))?;
+        Ok(crate_dir)
+    }
+}
+
+/// Cargo manifest for a Forge crate, pinning [`FORGE_DEPENDENCIES`] to the versions in
+/// `workspace_lock` (falling back to caret requirements when a package is not locked there).
+fn manifest(crate_name: &str, workspace_lock: Option<&str>) -> String {
+    let mut deps = String::new();
+    for (name, extra) in FORGE_DEPENDENCIES {
+        let version = workspace_lock
+            .and_then(|lock| locked_version(lock, name))
+            .map(|v| format!("={}", v))
+            .unwrap_or_else(|| match *name {
+                "libc" => "0.2".to_string(),
+                _ => "1".to_string(),
+            });
+        let extra = if extra.is_empty() {
+            String::new()
+        } else {
+            format!(", {}", extra)
+        };
+        deps.push_str(&format!("{} = {{ version = \"{}\"{} }}\n", name, version, extra));
+    }
+    format!(
+        r#"[package]
+name = "{crate_name}"
+version = "0.1.0"
+edition = "2021"
+
+# Standalone workspace: never picked up by an enclosing Cargo.toml.
+[workspace]
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+{deps}
+[profile.release]
+codegen-units = 1
+debug = false
+"#
+    )
+}
+
+/// Highest version of registry package `name` in a `Cargo.lock`.
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Lock {
+        #[serde(default)]
+        package: Vec<Package>,
+    }
+    #[derive(Deserialize)]
+    struct Package {
+        name: String,
+        version: String,
+        source: Option<String>,
+    }
+
+    let lock: Lock = toml::from_str(lock).ok()?;
+    lock.package
+        .into_iter()
+        .filter(|p| p.name == name && p.source.as_deref().is_some_and(|s| s.starts_with("registry+")))
+        .map(|p| p.version)
+        .max_by_key(|v| {
+            v.split(['.', '-', '+'])
+                .map(|part| part.parse::<u64>().unwrap_or(0))
+                .collect::<Vec<_>>()
+        })
+}
+
+fn toml_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn check(what: &str, output: &Output) -> Result<(), SkillError> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let tail: String = stderr
+        .chars()
+        .rev()
+        .take(1000)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    Err(SkillError::Load(format!(
+        "{} failed (exit {}): {}",
+        what,
+        output.status.code().unwrap_or(-1),
+        tail.trim()
+    )))
+}
+
+fn io_err(what: &'static str) -> impl Fn(std::io::Error) -> SkillError {
+    move |e| SkillError::Load(format!("Failed to {}: {}", what, e))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn sha256_file(path: &Path) -> Result<String, SkillError> {
+    std::fs::read(path)
+        .map(|bytes| sha256_hex(&bytes))
+        .map_err(|e| SkillError::Load(format!("Failed to hash {}: {}", path.display(), e)))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"
+version = 4
+
+[[package]]
+name = "serde"
+version = "1.0.9"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.10"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "libc"
+version = "0.2.1"
+source = "git+https://example.com/libc"
+
+[[package]]
+name = "pagi-core"
+version = "0.1.0"
+"#;
+
+    #[test]
+    fn locked_version_picks_highest_registry_version() {
+        assert_eq!(locked_version(LOCK, "serde").as_deref(), Some("1.0.10"));
+        assert_eq!(locked_version(LOCK, "libc"), None);
+        assert_eq!(locked_version(LOCK, "missing"), None);
+    }
+
+    #[test]
+    fn manifest_pins_workspace_versions_and_is_standalone() {
+        let toml = manifest("pagi_dynamic_skill", Some(LOCK));
+        assert!(toml.contains(r#"serde = { version = "=1.0.10", features = ["derive"] }"#));
+        assert!(toml.contains(r#"libc = { version = "0.2" }"#));
+        assert!(toml.contains("\n[workspace]\n"));
+        assert!(toml.contains("codegen-units = 1"));
+        assert_eq!(toml, manifest("pagi_dynamic_skill", Some(LOCK)));
+    }
+
+    #[test]
+    fn same_toolchain_ignores_artifact_hash() {
+        let a = ToolchainFingerprint {
+            rustc_version: "1.80.0".into(),
+            rustc_commit: "abc".into(),
+            cargo_version: "cargo 1.80.0".into(),
+            host: "x86_64-unknown-linux-gnu".into(),
+            target: "x86_64-unknown-linux-gnu".into(),
+            lockfile_sha256: "11".into(),
+            artifact_sha256: "22".into(),
+        };
+        let mut b = a.clone();
+        b.artifact_sha256 = "33".into();
+        assert!(a.same_toolchain(&b));
+        b.lockfile_sha256 = "44".into();
+        assert!(!a.same_toolchain(&b));
+    }
+}
+
@@ -0,0 +1,633 @@
+//! Shadow execution and staged canary rollout for new patch versions.
+//!
+//! While a canary runs, every call to the skill executes **both** the active version and the
+//! candidate on the same input (concurrently, so latency is the slower of the two). Outputs and
+//! latency are compared and the traffic share served by the candidate follows
+//! [`CanaryPolicy::stages`] (default `0% → 10% → 100%`). A stage is promoted once it has seen
+//! `min_calls_per_stage` calls within thresholds; reaching 100% makes the candidate the active
+//! version. Breaching the error-rate or divergence threshold rolls the candidate back at once.
+//!
+//! When the candidate is routed a call but fails, the active version's result is served instead,
+//! so users never see a canary-only failure. Shadowing runs the skill twice per call, so skills
+//! with external side effects should keep the shadow (`0%`) stage short.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::skill::{DynamicSkill, SkillError};
+
+/// Invoked once when a canary is promoted or rolled back.
+pub type CanaryCallback = Arc<dyn Fn(&CanaryReport) + Send + Sync>;
+
+// ---------------------------------------------------------------------------
+// Policy
+// ---------------------------------------------------------------------------
+
+/// Rollout stages and rollback thresholds for a canary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryPolicy {
+    /// Percent of calls served by the candidate at each stage; reaching 100 promotes it.
+    pub stages: Vec<u8>,
+    /// Calls a stage must observe before it is promoted.
+    pub min_calls_per_stage: u64,
+    /// Roll back when the candidate fails on more than this fraction of calls.
+    pub max_error_rate: f64,
+    /// Roll back when outputs differ on more than this fraction of calls where both succeed.
+    pub max_divergence_rate: f64,
+}
+
+impl Default for CanaryPolicy {
+    fn default() -> Self {
+        Self {
+            stages: vec![0, 10, 100],
+            min_calls_per_stage: 20,
+            max_error_rate: 0.05,
+            max_divergence_rate: 0.10,
+        }
+    }
+}
+
+impl CanaryPolicy {
+    /// Reads `PAGI_CANARY_STAGES` (e.g. `0,10,100`), `PAGI_CANARY_MIN_CALLS`,
+    /// `PAGI_CANARY_MAX_ERROR_RATE` and `PAGI_CANARY_MAX_DIVERGENCE`.
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+        if let Ok(raw) = std::env::var("PAGI_CANARY_STAGES") {
+            let stages: Vec<u8> = raw
+                .split(',')
+                .filter_map(|s| s.trim().parse::<u8>().ok())
+                .collect();
+            if !stages.is_empty() {
+                policy.stages = stages;
+            }
+        }
+        if let Some(n) = env_parse("PAGI_CANARY_MIN_CALLS") {
+            policy.min_calls_per_stage = n;
+        }
+        if let Some(r) = env_parse("PAGI_CANARY_MAX_ERROR_RATE") {
+            policy.max_error_rate = r;
+        }
+        if let Some(r) = env_parse("PAGI_CANARY_MAX_DIVERGENCE") {
+            policy.max_divergence_rate = r;
+        }
+        policy.normalized()
+    }
+
+    /// Stages clamped to 0..=100, ascending, and ending in 100; at least one call per stage.
+    pub fn normalized(mut self) -> Self {
+        for s in &mut self.stages {
+            *s = (*s).min(100);
+        }
+        self.stages.sort_unstable();
+        self.stages.dedup();
+        if self.stages.last() != Some(&100) {
+            self.stages.push(100);
+        }
+        self.min_calls_per_stage = self.min_calls_per_stage.max(1);
+        self
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok())
+}
+
+// ---------------------------------------------------------------------------
+// Stats & report
+// ---------------------------------------------------------------------------
+
+/// Counters for one stage (or the whole canary).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CanaryStats {
+    /// Calls that ran both versions.
+    pub calls: u64,
+    /// Calls whose response came from the candidate.
+    pub candidate_served: u64,
+    /// Calls where the candidate failed but the active version succeeded.
+    pub candidate_errors: u64,
+    /// Calls where the active version failed.
+    pub active_errors: u64,
+    /// Calls where both succeeded (the divergence denominator).
+    pub comparisons: u64,
+    /// Comparisons whose outputs differed.
+    pub divergences: u64,
+    /// Summed latency of the active version, microseconds.
+    pub active_latency_us: u64,
+    /// Summed latency of the candidate, microseconds.
+    pub candidate_latency_us: u64,
+}
+
+impl CanaryStats {
+    pub fn error_rate(&self) -> f64 {
+        ratio(self.candidate_errors, self.calls)
+    }
+
+    pub fn divergence_rate(&self) -> f64 {
+        ratio(self.divergences, self.comparisons)
+    }
+
+    /// Candidate mean latency relative to the active version, in percent (`+12.5` = slower).
+    pub fn latency_delta_pct(&self) -> f64 {
+        if self.active_latency_us == 0 {
+            return 0.0;
+        }
+        (self.candidate_latency_us as f64 / self.active_latency_us as f64 - 1.0) * 100.0
+    }
+
+    fn record(&mut self, sample: &Sample) {
+        self.calls += 1;
+        self.active_latency_us += sample.active_latency.as_micros() as u64;
+        self.candidate_latency_us += sample.candidate_latency.as_micros() as u64;
+        if sample.served_candidate {
+            self.candidate_served += 1;
+        }
+        if !sample.active_ok {
+            self.active_errors += 1;
+        }
+        if sample.active_ok && !sample.candidate_ok {
+            self.candidate_errors += 1;
+        }
+        if let Some(diverged) = sample.diverged {
+            self.comparisons += 1;
+            if diverged {
+                self.divergences += 1;
+            }
+        }
+    }
+}
+
+fn ratio(n: u64, d: u64) -> f64 {
+    if d == 0 {
+        0.0
+    } else {
+        n as f64 / d as f64
+    }
+}
+
+/// Final (or in-progress) state of a canary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryOutcome {
+    Running,
+    Promoted,
+    RolledBack,
+}
+
+/// Snapshot of a canary, recorded in the candidate's `PatchPerformanceDelta` when it finishes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CanaryReport {
+    pub skill: String,
+    pub outcome: CanaryOutcome,
+    /// Traffic share of the current (or final) stage.
+    pub stage_percent: u8,
+    pub stage_index: usize,
+    /// Counters of the current stage only.
+    pub stage: CanaryStats,
+    /// Counters across all stages.
+    pub total: CanaryStats,
+    /// Why the canary was rolled back.
+    pub reason: Option<String>,
+    /// Rolled back because the candidate itself errored, as opposed to diverging from the
+    /// active version or being aborted. Only these candidates are known to be broken.
+    #[serde(default)]
+    pub candidate_failed: bool,
+    /// Most recent output difference, e.g. `$.result: 3 != 4`.
+    pub last_divergence: Option<String>,
+    pub started_at_ms: i64,
+    pub finished_at_ms: Option<i64>,
+}
+
+// ---------------------------------------------------------------------------
+// Canary
+// ---------------------------------------------------------------------------
+
+struct Sample {
+    active_ok: bool,
+    candidate_ok: bool,
+    /// `Some` when both succeeded.
+    diverged: Option<bool>,
+    served_candidate: bool,
+    active_latency: Duration,
+    candidate_latency: Duration,
+}
+
+struct CanaryState {
+    stage_index: usize,
+    stage: CanaryStats,
+    total: CanaryStats,
+    outcome: CanaryOutcome,
+    reason: Option<String>,
+    candidate_failed: bool,
+    last_divergence: Option<String>,
+    started_at_ms: i64,
+    finished_at_ms: Option<i64>,
+    /// Calls routed in the current stage, used for deterministic traffic splitting.
+    seq: u64,
+}
+
+/// A candidate version shadowing the active one (owned by the `SkillLoader`).
+pub(crate) struct Canary {
+    skill: String,
+    pub(crate) candidate: Arc<dyn DynamicSkill>,
+    policy: CanaryPolicy,
+    state: Mutex<CanaryState>,
+    pub(crate) on_finish: Option<CanaryCallback>,
+}
+
+impl Canary {
+    pub(crate) fn new(
+        skill: &str,
+        candidate: Arc<dyn DynamicSkill>,
+        policy: CanaryPolicy,
+        on_finish: Option<CanaryCallback>,
+    ) -> Self {
+        Self {
+            skill: skill.to_string(),
+            candidate,
+            policy: policy.normalized(),
+            state: Mutex::new(CanaryState {
+                stage_index: 0,
+                stage: CanaryStats::default(),
+                total: CanaryStats::default(),
+                outcome: CanaryOutcome::Running,
+                reason: None,
+                candidate_failed: false,
+                last_divergence: None,
+                started_at_ms: now_epoch_ms(),
+                finished_at_ms: None,
+                seq: 0,
+            }),
+            on_finish,
+        }
+    }
+
+    pub(crate) fn report(&self) -> CanaryReport {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.snapshot(&state)
+    }
+
+    fn snapshot(&self, state: &CanaryState) -> CanaryReport {
+        CanaryReport {
+            skill: self.skill.clone(),
+            outcome: state.outcome,
+            stage_percent: self.policy.stages[state.stage_index],
+            stage_index: state.stage_index,
+            stage: state.stage.clone(),
+            total: state.total.clone(),
+            reason: state.reason.clone(),
+            candidate_failed: state.candidate_failed,
+            last_divergence: state.last_divergence.clone(),
+            started_at_ms: state.started_at_ms,
+            finished_at_ms: state.finished_at_ms,
+        }
+    }
+
+    /// Ends a running canary. Returns the final report, or `None` if it had already finished.
+    pub(crate) fn finish(&self, outcome: CanaryOutcome, reason: Option<String>) -> Option<CanaryReport> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.outcome != CanaryOutcome::Running {
+            return None;
+        }
+        state.outcome = outcome;
+        state.reason = reason;
+        state.finished_at_ms = Some(now_epoch_ms());
+        Some(self.snapshot(&state))
+    }
+
+    /// Run both versions on `args` and serve one result. The second value is the final report
+    /// when this call promoted or rolled back the canary.
+    pub(crate) fn execute(
+        &self,
+        active: &Arc<dyn DynamicSkill>,
+        args: Value,
+    ) -> (Result<Value, SkillError>, Option<CanaryReport>) {
+        let route_to_candidate = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            // Serve the candidate whenever the running share crosses a whole call, which spreads
+            // `percent` of calls evenly across the stage (10% = every 10th call).
+            let percent = u64::from(self.policy.stages[state.stage_index]);
+            let n = state.seq;
+            state.seq += 1;
+            state.outcome == CanaryOutcome::Running && (n + 1) * percent / 100 > n * percent / 100
+        };
+
+        let shadow_args = args.clone();
+        let ((active_result, active_latency), (candidate_result, candidate_latency)) =
+            std::thread::scope(|scope| {
+                let shadow = scope.spawn(|| timed(|| self.candidate.execute(shadow_args)));
+                let active_run = timed(|| active.execute(args));
+                let shadow_run = shadow.join().unwrap_or_else(|_| {
+                    (
+                        Err(SkillError::Execution("candidate panicked".to_string())),
+                        Duration::ZERO,
+                    )
+                });
+                (active_run, shadow_run)
+            });
+
+        let divergence = match (&active_result, &candidate_result) {
+            (Ok(a), Ok(c)) => Some(first_difference(a, c, "$")),
+            _ => None,
+        };
+        let served_candidate = route_to_candidate && candidate_result.is_ok();
+        let sample = Sample {
+            active_ok: active_result.is_ok(),
+            candidate_ok: candidate_result.is_ok(),
+            diverged: divergence.as_ref().map(Option::is_some),
+            served_candidate,
+            active_latency,
+            candidate_latency,
+        };
+
+        let finished = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.outcome == CanaryOutcome::Running {
+                state.stage.record(&sample);
+                state.total.record(&sample);
+                if let Some(Some(diff)) = divergence {
+                    state.last_divergence = Some(diff);
+                }
+                if let Err(e) = &candidate_result {
+                    if sample.active_ok {
+                        warn!(
+                            target: "pagi::canary",
+                            skill = %self.skill,
+                            error = %e,
+                            "Canary candidate failed where the active version succeeded"
+                        );
+                    }
+                }
+                self.evaluate(&mut state)
+            } else {
+                None
+            }
+        };
+
+        let result = if served_candidate {
+            candidate_result
+        } else {
+            active_result
+        };
+        (result, finished)
+    }
+
+    /// Advance, promote or roll back after a recorded call.
+    fn evaluate(&self, state: &mut CanaryState) -> Option<CanaryReport> {
+        let policy = &self.policy;
+        let min = policy.min_calls_per_stage as f64;
+        let stage = &state.stage;
+
+        // Breaches are final as soon as they are certain, not only at the end of a stage:
+        // a stage ends at `min` calls, so more failures than `rate * min` cannot recover.
+        let breach = if stage.candidate_errors as f64 > policy.max_error_rate * min
+            || (stage.calls >= policy.min_calls_per_stage && stage.error_rate() > policy.max_error_rate)
+        {
+            Some((
+                format!(
+                    "error rate {:.1}% exceeds {:.1}% at the {}% stage",
+                    stage.error_rate() * 100.0,
+                    policy.max_error_rate * 100.0,
+                    policy.stages[state.stage_index]
+                ),
+                true,
+            ))
+        } else if stage.divergences as f64 > policy.max_divergence_rate * min
+            || (stage.calls >= policy.min_calls_per_stage
+                && stage.divergence_rate() > policy.max_divergence_rate)
+        {
+            Some((
+                format!(
+                    "output divergence {:.1}% exceeds {:.1}% at the {}% stage ({})",
+                    stage.divergence_rate() * 100.0,
+                    policy.max_divergence_rate * 100.0,
+                    policy.stages[state.stage_index],
+                    state.last_divergence.as_deref().unwrap_or("no sample")
+                ),
+                false,
+            ))
+        } else {
+            None
+        };
+
+        if let Some((reason, candidate_failed)) = breach {
+            warn!(target: "pagi::canary", skill = %self.skill, reason = %reason, "Canary rolled back");
+            state.outcome = CanaryOutcome::RolledBack;
+            state.reason = Some(reason);
+            state.candidate_failed = candidate_failed;
+            state.finished_at_ms = Some(now_epoch_ms());
+            return Some(self.snapshot(state));
+        }
+
+        if stage.calls < policy.min_calls_per_stage {
+            return None;
+        }
+
+        state.stage_index += 1;
+        state.stage = CanaryStats::default();
+        state.seq = 0;
+        let percent = policy.stages[state.stage_index];
+        if percent >= 100 {
+            info!(target: "pagi::canary", skill = %self.skill, calls = state.total.calls, "Canary promoted to 100%");
+            state.outcome = CanaryOutcome::Promoted;
+            state.finished_at_ms = Some(now_epoch_ms());
+            return Some(self.snapshot(state));
+        }
+        info!(target: "pagi::canary", skill = %self.skill, percent, "Canary advanced to next stage");
+        None
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let out = f();
+    (out, start.elapsed())
+}
+
+/// First differing JSON path between two values, e.g. `$.items[2].score: 0.5 != 0.7`.
+fn first_difference(a: &Value, b: &Value, path: &str) -> Option<String> {
+    match (a, b) {
+        (Value::Object(x), Value::Object(y)) => {
+            let mut keys: Vec<&String> = x.keys().chain(y.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            keys.into_iter().find_map(|k| {
+                let child = format!("{}.{}", path, k);
+                match (x.get(k), y.get(k)) {
+                    (Some(l), Some(r)) => first_difference(l, r, &child),
+                    (l, r) => Some(format!("{}: {} != {}", child, show(l), show(r))),
+                }
+            })
+        }
+        (Value::Array(x), Value::Array(y)) if x.len() == y.len() => x
+            .iter()
+            .zip(y)
+            .enumerate()
+            .find_map(|(i, (l, r))| first_difference(l, r, &format!("{}[{}]", path, i))),
+        _ if a == b => None,
+        _ => Some(format!("{}: {} != {}", path, truncate(a), truncate(b))),
+    }
+}
+
+fn show(v: Option<&Value>) -> String {
+    v.map(truncate).unwrap_or_else(|| "<missing>".to_string())
+}
+
+fn truncate(v: &Value) -> String {
+    let s = v.to_string();
+    if s.len() > 80 {
+        format!("{}…", s.chars().take(80).collect::<String>())
+    } else {
+        s
+    }
+}
+
+fn now_epoch_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SkillLoader;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Returns `{"v": version}`; fails every `fail_every`-th call when set.
+    struct Versioned {
+        version: u64,
+        fail_every: Option<u64>,
+        calls: AtomicU64,
+    }
+
+    impl Versioned {
+        fn arc(version: u64, fail_every: Option<u64>) -> Arc<dyn DynamicSkill> {
+            Arc::new(Self {
+                version,
+                fail_every,
+                calls: AtomicU64::new(0),
+            })
+        }
+    }
+
+    impl DynamicSkill for Versioned {
+        fn execute(&self, args: Value) -> Result<Value, SkillError> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if self.fail_every.is_some_and(|k| n.is_multiple_of(k)) {
+                return Err(SkillError::Execution("boom".to_string()));
+            }
+            Ok(json!({ "echo": args, "v": self.version }))
+        }
+    }
+
+    fn policy() -> CanaryPolicy {
+        CanaryPolicy {
+            stages: vec![0, 10, 100],
+            min_calls_per_stage: 10,
+            max_error_rate: 0.2,
+            max_divergence_rate: 0.1,
+        }
+    }
+
+    fn loader_with_canary(candidate: Arc<dyn DynamicSkill>) -> (SkillLoader, Arc<Mutex<Vec<CanaryReport>>>) {
+        let loader = SkillLoader::new();
+        loader.insert("s", Versioned::arc(1, None));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let callback: CanaryCallback = Arc::new(move |r: &CanaryReport| sink.lock().unwrap().push(r.clone()));
+        loader
+            .start_canary_with("s", candidate, policy(), Some(callback))
+            .unwrap();
+        (loader, seen)
+    }
+
+    #[test]
+    fn identical_candidate_is_promoted_through_stages() {
+        // Same output as the active version, so nothing diverges.
+        let (loader, seen) = loader_with_canary(Versioned::arc(1, None));
+
+        for i in 0..10 {
+            loader.execute("s", json!(i)).unwrap();
+        }
+        assert_eq!(loader.canary_report("s").unwrap().stage_percent, 10);
+        for i in 0..10 {
+            loader.execute("s", json!(i)).unwrap();
+        }
+
+        assert!(loader.canary_report("s").is_none());
+        let reports = seen.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].outcome, CanaryOutcome::Promoted);
+        assert_eq!(reports[0].total.calls, 20);
+        // 10% of the second stage's 10 calls went to the candidate.
+        assert_eq!(reports[0].total.candidate_served, 1);
+    }
+
+    #[test]
+    fn divergent_candidate_is_rolled_back_while_shadowing() {
+        let (loader, seen) = loader_with_canary(Versioned::arc(2, None));
+
+        for i in 0..3 {
+            // Shadow stage: users keep getting the active version's output.
+            assert_eq!(loader.execute("s", json!(i)).unwrap()["v"], 1);
+        }
+
+        let reports = seen.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].outcome, CanaryOutcome::RolledBack);
+        assert!(reports[0].reason.as_deref().unwrap().contains("divergence"));
+        assert!(!reports[0].candidate_failed);
+        assert_eq!(reports[0].last_divergence.as_deref(), Some("$.v: 1 != 2"));
+        assert!(loader.canary_report("s").is_none());
+        assert_eq!(loader.execute("s", json!(0)).unwrap()["v"], 1);
+    }
+
+    #[test]
+    fn failing_candidate_is_rolled_back_and_never_surfaces_errors() {
+        let (loader, seen) = loader_with_canary(Versioned::arc(1, Some(2)));
+
+        for i in 0..6 {
+            assert!(loader.execute("s", json!(i)).is_ok());
+        }
+        let reports = seen.lock().unwrap();
+        assert_eq!(reports[0].outcome, CanaryOutcome::RolledBack);
+        assert!(reports[0].reason.as_deref().unwrap().contains("error rate"));
+        assert!(reports[0].candidate_failed);
+        assert_eq!(reports[0].total.candidate_errors, 3);
+    }
+
+    #[test]
+    fn first_difference_reports_paths() {
+        let a = json!({ "items": [1, { "x": 1 }], "ok": true });
+        let b = json!({ "items": [1, { "x": 2 }], "ok": true });
+        assert_eq!(first_difference(&a, &b, "$").as_deref(), Some("$.items[1].x: 1 != 2"));
+        assert_eq!(first_difference(&a, &a, "$"), None);
+        let c = json!({ "ok": true });
+        assert_eq!(
+            first_difference(&a, &c, "$").as_deref(),
+            Some("$.items: [1,{\"x\":1}] != <missing>")
+        );
+    }
+
+    #[test]
+    fn policy_normalizes_stages() {
+        let p = CanaryPolicy {
+            stages: vec![50, 0, 200, 10],
+            min_calls_per_stage: 0,
+            ..CanaryPolicy::default()
+        }
+        .normalized();
+        assert_eq!(p.stages, vec![0, 10, 50, 100]);
+        assert_eq!(p.min_calls_per_stage, 1);
+    }
+}
@@ -1,60 +1,197 @@
 //! Compiler: write Rust code to a directory, run `cargo build --release`, return path to artifact.
+//! Builds either a native cdylib or a `wasm32-wasip1` module (see [`CompileTarget`]).
+//! Generated sources build offline against the pinned, vendored dependency set in
+//! [`BuildEnv`](crate::build_env::BuildEnv).
 
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::io;
 use tracing::info;
 
+use crate::build_env::{BuildEnv, ToolchainFingerprint};
 use crate::SkillError;
 
+/// Rust target triple for WebAssembly skills.
+pub const WASM_TARGET: &str = "wasm32-wasip1";
+
+/// Package name of the generated crate (and thus of its artifact).
+const FORGE_CRATE: &str = "pagi_dynamic_skill";
+
+/// Appended to `src/lib.rs` for wasm builds: the allocator the host uses to pass arguments in,
+/// and safe wrappers over the capability-gated `"pagi"` host imports (see `crate::wasm`).
+const WASM_PRELUDE: &str = r#"
+// ---- injected by the PAGI Forge for wasm32-wasip1 builds ----
+#[cfg(target_arch = "wasm32")]
+#[no_mangle]
+pub extern "C" fn pagi_alloc(len: usize) -> *mut u8 {
+    let mut buf = Vec::<u8>::with_capacity(len.max(1));
+    let ptr = buf.as_mut_ptr();
+    std::mem::forget(buf);
+    ptr
+}
+
+/// Host services; each call is checked against the skill's manifest capabilities.
+/// Errors: -1 denied, -2 not found, -3 host error.
+#[cfg(target_arch = "wasm32")]
+#[allow(dead_code)]
+pub mod pagi_host {
+    mod ffi {
+        #[link(wasm_import_module = "pagi")]
+        extern "C" {
+            pub fn log(level: i32, ptr: *const u8, len: usize);
+            pub fn kb_read(slot: i32, key_ptr: *const u8, key_len: usize) -> i32;
+            pub fn kb_write(slot: i32, key_ptr: *const u8, key_len: usize, val_ptr: *const u8, val_len: usize) -> i32;
+            pub fn http_get(url_ptr: *const u8, url_len: usize) -> i32;
+            pub fn take_result(ptr: *mut u8);
+        }
+    }
+
+    fn take(len: i32) -> Result<Vec<u8>, i32> {
+        if len < 0 {
+            return Err(len);
+        }
+        let mut buf = vec![0u8; len as usize];
+        unsafe { ffi::take_result(buf.as_mut_ptr()) };
+        Ok(buf)
+    }
+
+    /// 0 error, 1 warn, 2 info, 3 debug, 4 trace.
+    pub fn log(level: i32, msg: &str) {
+        unsafe { ffi::log(level, msg.as_ptr(), msg.len()) }
+    }
+
+    pub fn kb_read(slot: u8, key: &str) -> Result<Vec<u8>, i32> {
+        take(unsafe { ffi::kb_read(slot as i32, key.as_ptr(), key.len()) })
+    }
+
+    pub fn kb_write(slot: u8, key: &str, value: &[u8]) -> Result<(), i32> {
+        match unsafe { ffi::kb_write(slot as i32, key.as_ptr(), key.len(), value.as_ptr(), value.len()) } {
+            0 => Ok(()),
+            code => Err(code),
+        }
+    }
+
+    pub fn http_get(url: &str) -> Result<Vec<u8>, i32> {
+        take(unsafe { ffi::http_get(url.as_ptr(), url.len()) })
+    }
+}
+"#;
+
+/// Artifact kind produced by the Forge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompileTarget {
+    /// Host cdylib (`.so` / `.dll`), loaded via libloading or `pagi-skill-host`.
+    #[default]
+    Native,
+    /// `wasm32-wasip1` module, run in the embedded WASM runtime.
+    Wasm,
+}
+
+impl CompileTarget {
+    /// `PAGI_FORGE_TARGET=wasm` selects WebAssembly; anything else is native.
+    pub fn from_env() -> Self {
+        match std::env::var("PAGI_FORGE_TARGET").as_deref().map(str::trim) {
+            Ok("wasm") | Ok("wasm32") | Ok(WASM_TARGET) => Self::Wasm,
+            _ => Self::Native,
+        }
+    }
+
+    /// File extension of the artifact.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Native if cfg!(target_os = "windows") => "dll",
+            Self::Native => "so",
+            Self::Wasm => "wasm",
+        }
+    }
+}
+
+/// A compiled Forge artifact and the toolchain that produced it.
+#[derive(Debug, Clone)]
+pub struct CompiledArtifact {
+    /// Where the artifact was copied (`output_path` or `./data/pagi_evolution/<name>.<ext>`).
+    pub path: PathBuf,
+    pub fingerprint: ToolchainFingerprint,
+}
+
 /// Compiles Rust code (or a path to a crate) into a cdylib and returns the path to the built library.
 ///
-/// - `compile_from_string`: writes `code` as `src/lib.rs` in a temp crate, builds, copies artifact to `output_path` (or default `./data/pagi_evolution/`).
+/// - `compile_from_string`: writes `code` as `src/lib.rs` of the persistent Forge crate, builds it
+///   offline (`--offline --locked`, vendored deps), copies artifact to `output_path` (or default `./data/pagi_evolution/`).
 /// - `compile_from_path`: runs `cargo build --release` in the given directory (must be a crate root).
 pub struct Compiler;
 
 impl Compiler {
     /// Compile a string of Rust code as the entire `src/lib.rs` of a cdylib crate.
-    /// Copies the built .so/.dll to `output_path` so it remains valid after the temp dir is dropped.
+    /// Copies the built .so/.dll to `output_path` so it remains valid after the next build.
     /// If `output_path` is None, uses `./data/pagi_evolution/<name>.so` (or .dll on Windows).
     pub fn compile_from_string(
         code: &str,
         name: &str,
         output_path: Option<PathBuf>,
     ) -> Result<PathBuf, SkillError> {
-        let dir = tempfile::tempdir().map_err(|e| SkillError::Load(e.to_string()))?;
-        let root = dir.path().to_path_buf();
-
-        let toml = r#"
-[package]
-name = "pagi_dynamic_skill"
-version = "0.1.0"
-edition = "2021"
-
-[lib]
-crate-type = ["cdylib"]
-
-[dependencies]
-serde = { version = "1", features = ["derive"] }
-serde_json = "1"
-libc = "0.2"
-"#;
-        std::fs::write(root.join("Cargo.toml"), toml)
-            .map_err(|e| SkillError::Load(e.to_string()))?;
-        std::fs::create_dir_all(root.join("src")).map_err(|e| SkillError::Load(e.to_string()))?;
-        std::fs::write(root.join("src").join("lib.rs"), code)
-            .map_err(|e| SkillError::Load(e.to_string()))?;
+        Self::compile_for_target(code, name, output_path, CompileTarget::Native)
+    }
+
+    /// Compile the same source for `wasm32-wasip1` (requires `rustup target add wasm32-wasip1`).
+    /// If `output_path` is None, uses `./data/pagi_evolution/<name>.wasm`.
+    pub fn compile_wasm_from_string(
+        code: &str,
+        name: &str,
+        output_path: Option<PathBuf>,
+    ) -> Result<PathBuf, SkillError> {
+        Self::compile_for_target(code, name, output_path, CompileTarget::Wasm)
+    }
 
-        let built = Self::build_and_return_lib_path(&root)?;
+    /// Compile `code` for `target` and copy the artifact out of the shared target dir.
+    pub fn compile_for_target(
+        code: &str,
+        name: &str,
+        output_path: Option<PathBuf>,
+        target: CompileTarget,
+    ) -> Result<PathBuf, SkillError> {
+        Self::compile_reproducible(code, name, output_path, target).map(|a| a.path)
+    }
+
+    /// Like [`compile_for_target`](Self::compile_for_target), using [`BuildEnv::from_env`], and
+    /// also returns the [`ToolchainFingerprint`] to record in the patch's `PatchVersion`.
+    pub fn compile_reproducible(
+        code: &str,
+        name: &str,
+        output_path: Option<PathBuf>,
+        target: CompileTarget,
+    ) -> Result<CompiledArtifact, SkillError> {
+        Self::compile_with_env(&BuildEnv::from_env(), code, name, output_path, target)
+    }
+
+    /// Compile in an explicit build environment.
+    pub fn compile_with_env(
+        env: &BuildEnv,
+        code: &str,
+        name: &str,
+        output_path: Option<PathBuf>,
+        target: CompileTarget,
+    ) -> Result<CompiledArtifact, SkillError> {
+        let (source, triple) = match target {
+            CompileTarget::Native => (code.to_string(), None),
+            CompileTarget::Wasm => (format!("{}\n{}", code, WASM_PRELUDE), Some(WASM_TARGET)),
+        };
         let dest = output_path.unwrap_or_else(|| {
             let base = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-            let dir = base.join("data").join("pagi_evolution");
-            let _ = std::fs::create_dir_all(&dir);
-            let ext = if cfg!(target_os = "windows") { "dll" } else { "so" };
-            dir.join(format!("{}.{}", name, ext))
+            base.join("data")
+                .join("pagi_evolution")
+                .join(format!("{}.{}", name, target.extension()))
         });
-        std::fs::copy(&built, &dest).map_err(|e: io::Error| SkillError::Load(e.to_string()))?;
-        Ok(dest)
+        let built = env.build(FORGE_CRATE, &source, triple, &dest).map_err(|e| match target {
+            CompileTarget::Wasm => SkillError::Load(format!(
+                "{} (is the target installed? rustup target add {})",
+                e, WASM_TARGET
+            )),
+            CompileTarget::Native => e,
+        })?;
+        Ok(CompiledArtifact {
+            path: built.artifact,
+            fingerprint: built.fingerprint,
+        })
     }
 
     /// Compile a crate at the given path (directory containing Cargo.toml).
@@ -10,40 +10,92 @@
 //!   Returns a JSON string (allocated; caller frees via below). Null on error.
 //! - `pagi_dynamic_skill_free(ptr: *mut c_char)` to free the returned string.
 //!
+//! ## Reproducible Builds
+//!
+//! Generated crates build offline (`--offline --locked`) against dependencies pinned to the
+//! workspace `Cargo.lock` and vendored once, in a persistent target directory. Each build returns
+//! a [`ToolchainFingerprint`] (rustc/cargo versions, lockfile and artifact hashes) that is stored
+//! on the patch's `PatchVersion`. See [`build_env`].
+//!
+//! ## Sandboxed Execution
+//!
+//! `SkillLoader::from_env()` loads each library in its own `pagi-skill-host` worker process
+//! (rlimits, scratch working directory, optional seccomp, per-call timeout, supervised restarts),
+//! so a crashing or hanging generated skill cannot take down the Gateway. See [`sandbox`].
+//!
+//! ## WebAssembly Skills
+//!
+//! `Compiler::compile_wasm_from_string` builds the same source for `wasm32-wasip1`; `.wasm`
+//! artifacts run in an embedded interpreter with fuel metering, and their host imports (KB
+//! read/write by slot, HTTP to allowed hosts, logging) are gated by manifest capabilities.
+//! See [`wasm`].
+//!
 //! ## Evolutionary Versioning & Rollback
 //!
 //! The `RollbackManager` provides:
-//! - **Versioned Storage:** Patches stored as `{skill}_v{timestamp}.rs` with `current_*` symlinks.
-//! - **Atomic Rollback:** Symlink swap + hot-reload of previous `.dll`/`.so`.
+//! - **Versioned Storage:** Patches stored as `{skill}_v{timestamp}.rs`; the active version of each
+//!   skill lives in an fsync'd `active_versions.json` manifest. See [`active_versions`].
+//! - **Atomic Rollback:** Compare-and-swap on the manifest + hot-reload of previous `.dll`/`.so`.
 //! - **Genetic Memory:** SHA-256 hashing of patch DNA to detect evolutionary dead-ends.
+//! - **Canary Rollout:** A staged version shadows the active one on real calls; outputs and latency
+//!   are diffed, traffic moves `0% → 10% → 100%`, and error-rate or divergence breaches roll it
+//!   back automatically. See [`canary`].
+//! - **Lineage:** Each version records its parent, triggering failure, model and prompt hash; a
+//!   skill's history is queryable as a DAG and renders to Graphviz or Mermaid. See [`lineage`].
 //!
 //! ## Adversarial Peer Review (Red-Team)
 //!
 //! The `RedTeamAnalyzer` provides **Phase 4.75: Consensus Gating**:
 //! - **Multi-Agent Review:** Sends proposed patches to a secondary LLM for security analysis.
 //! - **CVE Checklist:** Injects common vulnerability patterns into the review prompt.
+//! - **Static Analysis:** [`AstAnalyzer`] parses the patch with `syn`, resolves `use` aliases and
+//!   flags unsafe, FFI, process, network, filesystem and build-time APIs with exact spans; it
+//!   decides the verdict offline and is merged into every live review.
+//! - **Review Panel:** Several reviewer models and the AST analyzer review the same patch
+//!   concurrently; the [`QuorumPolicy`] decides how many must agree (any Critical rejects, High
+//!   needs 2 votes by default) and disagreements are kept in the audit. A High or Critical AST
+//!   finding is a veto that no number of approving models can outvote.
 //! - **Consensus Gate:** Auto-rejects Critical/High findings; marks Critical as Lethal Mutations.
 
+pub mod active_versions;
+pub mod ast_analyzer;
+pub mod build_env;
+pub mod canary;
 mod compiler;
+pub mod lineage;
 mod loader;
 pub mod operator;
 pub mod red_team;
 pub mod rollback;
+pub mod sandbox;
 mod skill;
+pub mod wasm;
 
-pub use compiler::Compiler;
+pub use active_versions::{
+    ActiveEntry, ActiveManifest, ActiveVersions, ConsistencyIssue, ManifestError, RecoveryReport,
+};
+pub use ast_analyzer::{AstAnalyzer, SourceSpan};
+pub use build_env::{BuildEnv, BuildOutput, ToolchainFingerprint};
+pub use canary::{CanaryCallback, CanaryOutcome, CanaryPolicy, CanaryReport, CanaryStats};
+pub use compiler::{CompileTarget, CompiledArtifact, Compiler};
+pub use lineage::{
+    prompt_hash, record_patch_lineage, FailureTrigger, LineageEdge, LineageGraph, LineageNode,
+    LineageRecord, PatchOrigin,
+};
 pub use loader::SkillLoader;
 pub use operator::{
     ApprovalGate, ApprovalStatus, ChangeSeverity, ProposedChange,
     create_kb08_log_entry, log_forge_approval_to_kb08,
 };
 pub use red_team::{
-    ConsensusGate, ConsensusResult, CveCheckList, RedTeamAnalyzer, RedTeamConfig,
-    SecurityFinding, SecurityVerdict, Severity,
+    ConsensusGate, ConsensusResult, CveCheckList, QuorumPolicy, RedTeamAnalyzer, RedTeamConfig,
+    ReviewPanel, ReviewerVerdict, SecurityFinding, SecurityReviewer, SecurityVerdict, Severity,
 };
 pub use rollback::{
     DeadEndRecord, GeneticMemory, PatchPerformanceDelta, PatchStatus, PatchVersion,
     RollbackConfig, RollbackManager, SecurityAuditSummary,
 };
+pub use sandbox::{SandboxConfig, SandboxedSkill};
 pub use skill::{DynamicSkill, SkillError};
+pub use wasm::{WasmCapabilities, WasmConfig, WasmHost, WasmSkill};
 pub use std::path::PathBuf;
@@ -0,0 +1,458 @@
+//! Patch genealogy: which version a patch was derived from, and what produced it.
+//!
+//! Every versioned patch carries a [`PatchOrigin`]: its parent version, the failure record that
+//! triggered it, the model and prompt hash that wrote it, and the coaching conversation it came
+//! out of. Together with the reviewer verdicts and performance delta already kept on
+//! [`PatchVersion`], the versions of a skill form a lineage DAG ([`LineageGraph`]) that can be
+//! rendered as Graphviz or Mermaid and walked for "ancestors of the current version".
+//!
+//! ## Persistence
+//!
+//! The origin, status, description and performance delta of each version are written to a
+//! sidecar next to its source, `{skill}_v{timestamp}.lineage.json`, so history survives restarts
+//! and is shared between the `RollbackManager` and the maintenance loop, which both write to the
+//! patches directory.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::active_versions::ActiveVersions;
+use crate::red_team::ReviewerVerdict;
+use crate::rollback::{
+    parse_versioned_filename, read_patch_versions, PatchPerformanceDelta, PatchStatus, PatchVersion,
+};
+use crate::skill::SkillError;
+
+/// The failure record that triggered a patch (e.g. a Chronos event from the maintenance audit).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureTrigger {
+    /// Chronos key of the failure record.
+    pub key: String,
+    /// Skill or tool that failed.
+    pub skill: String,
+    pub description: String,
+    pub timestamp_ms: i64,
+}
+
+/// What produced a patch version.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PatchOrigin {
+    /// Version of the same skill this patch was derived from. `None` for a root.
+    #[serde(default)]
+    pub parent_timestamp_ms: Option<i64>,
+    #[serde(default)]
+    pub trigger: Option<FailureTrigger>,
+    /// Model that generated the code.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// SHA-256 of the generation prompt (see [`prompt_hash`]).
+    #[serde(default)]
+    pub prompt_hash: Option<String>,
+    /// Coaching conversation (chat thread / correlation id) the patch came out of.
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+}
+
+/// Hex SHA-256 of a generation prompt, for [`PatchOrigin::prompt_hash`].
+pub fn prompt_hash(prompt: &str) -> String {
+    Sha256::digest(prompt.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Sidecar persistence
+// ---------------------------------------------------------------------------
+
+/// Persisted lineage of one version (`{skill}_v{timestamp}.lineage.json`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageRecord {
+    pub origin: PatchOrigin,
+    pub status: PatchStatus,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub performance_delta: Option<PatchPerformanceDelta>,
+}
+
+/// Sidecar path for a versioned source file.
+pub fn lineage_path(source_path: &Path) -> PathBuf {
+    source_path.with_extension("lineage.json")
+}
+
+/// Reads the sidecar of a versioned source file, if present and parseable.
+pub fn read_lineage(source_path: &Path) -> Option<LineageRecord> {
+    let raw = std::fs::read_to_string(lineage_path(source_path)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// Writes the sidecar atomically (temp file + rename).
+pub fn write_lineage(source_path: &Path, record: &LineageRecord) -> std::io::Result<()> {
+    let path = lineage_path(source_path);
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(record).map_err(std::io::Error::other)?;
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, &path)
+}
+
+/// Persists the lineage fields of `version` next to its source.
+pub(crate) fn persist_version(version: &PatchVersion) -> std::io::Result<()> {
+    write_lineage(
+        &version.source_path,
+        &LineageRecord {
+            origin: version.origin.clone(),
+            status: version.status.clone(),
+            description: version.description.clone(),
+            performance_delta: version.performance_delta.clone(),
+        },
+    )
+}
+
+/// Records the lineage of a patch written to the patches directory outside the
+/// `RollbackManager` (the maintenance loop saves `{skill}_v{timestamp}.rs` itself). When
+/// `origin` has no parent, the newest earlier version of the same skill in that directory is
+/// used.
+pub fn record_patch_lineage(
+    source_path: &Path,
+    mut origin: PatchOrigin,
+    description: &str,
+    performance_delta: Option<PatchPerformanceDelta>,
+) -> std::io::Result<()> {
+    if origin.parent_timestamp_ms.is_none() {
+        origin.parent_timestamp_ms = previous_version(source_path);
+    }
+    write_lineage(
+        source_path,
+        &LineageRecord {
+            origin,
+            status: PatchStatus::Applied,
+            description: description.to_string(),
+            performance_delta,
+        },
+    )
+}
+
+/// Timestamp of the newest sibling version of the same skill older than `source_path`.
+fn previous_version(source_path: &Path) -> Option<i64> {
+    let (skill, timestamp) = parse_versioned_filename(source_path.file_name()?.to_str()?)?;
+    std::fs::read_dir(source_path.parent()?)
+        .ok()?
+        .flatten()
+        .filter_map(|e| parse_versioned_filename(e.file_name().to_str()?))
+        .filter(|(s, ts)| *s == skill && *ts < timestamp)
+        .map(|(_, ts)| ts)
+        .max()
+}
+
+// ---------------------------------------------------------------------------
+// Lineage graph
+// ---------------------------------------------------------------------------
+
+/// One version in the lineage graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageNode {
+    pub timestamp_ms: i64,
+    pub code_hash: String,
+    pub status: PatchStatus,
+    pub is_active: bool,
+    pub description: String,
+    pub origin: PatchOrigin,
+    /// Panel reviewer verdicts from the security audit (empty if not reviewed).
+    pub reviews: Vec<ReviewerVerdict>,
+    pub performance_delta: Option<PatchPerformanceDelta>,
+}
+
+impl From<&PatchVersion> for LineageNode {
+    fn from(v: &PatchVersion) -> Self {
+        Self {
+            timestamp_ms: v.timestamp_ms,
+            code_hash: v.code_hash.clone(),
+            status: v.status.clone(),
+            is_active: v.is_active,
+            description: v.description.clone(),
+            origin: v.origin.clone(),
+            reviews: v
+                .performance_delta
+                .as_ref()
+                .and_then(|pd| pd.security_audit.as_ref())
+                .map(|sa| sa.reviews.clone())
+                .unwrap_or_default(),
+            performance_delta: v.performance_delta.clone(),
+        }
+    }
+}
+
+/// Parent → child edge between two versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineageEdge {
+    pub parent: i64,
+    pub child: i64,
+}
+
+/// Lineage DAG of one skill: versions as nodes, derivation as edges.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageGraph {
+    pub skill_name: String,
+    /// Oldest first.
+    pub nodes: Vec<LineageNode>,
+    /// Only edges whose parent is still in the history (pruned parents leave roots).
+    pub edges: Vec<LineageEdge>,
+}
+
+impl LineageGraph {
+    pub fn from_versions(skill_name: &str, versions: &[PatchVersion]) -> Self {
+        let mut nodes: Vec<LineageNode> = versions.iter().map(LineageNode::from).collect();
+        nodes.sort_by_key(|n| n.timestamp_ms);
+        let known: HashSet<i64> = nodes.iter().map(|n| n.timestamp_ms).collect();
+        let edges = nodes
+            .iter()
+            .filter_map(|n| {
+                let parent = n.origin.parent_timestamp_ms?;
+                (known.contains(&parent) && parent != n.timestamp_ms).then_some(LineageEdge {
+                    parent,
+                    child: n.timestamp_ms,
+                })
+            })
+            .collect();
+        Self {
+            skill_name: skill_name.to_string(),
+            nodes,
+            edges,
+        }
+    }
+
+    /// Reads a skill's lineage straight from `patches_dir` (sources, sidecars and the
+    /// active-versions manifest). Unlike opening a `RollbackManager`, this never repairs,
+    /// migrates or writes anything, so it is safe for read-only endpoints.
+    pub fn load(patches_dir: &Path, skill_name: &str) -> Result<Self, SkillError> {
+        let active = ActiveVersions::read(patches_dir);
+        let versions = read_patch_versions(patches_dir, &active)?;
+        let skill_versions = versions.get(skill_name).map(Vec::as_slice).unwrap_or_default();
+        Ok(Self::from_versions(skill_name, skill_versions))
+    }
+
+    pub fn node(&self, timestamp_ms: i64) -> Option<&LineageNode> {
+        self.nodes.iter().find(|n| n.timestamp_ms == timestamp_ms)
+    }
+
+    pub fn active(&self) -> Option<&LineageNode> {
+        self.nodes.iter().find(|n| n.is_active)
+    }
+
+    /// Ancestors of `timestamp_ms`, nearest parent first, ending at a root.
+    pub fn ancestors(&self, timestamp_ms: i64) -> Vec<&LineageNode> {
+        let parents: HashMap<i64, i64> = self.edges.iter().map(|e| (e.child, e.parent)).collect();
+        let mut seen = HashSet::from([timestamp_ms]);
+        let mut out = Vec::new();
+        let mut current = timestamp_ms;
+        while let Some(&parent) = parents.get(&current) {
+            if !seen.insert(parent) {
+                break;
+            }
+            if let Some(node) = self.node(parent) {
+                out.push(node);
+            }
+            current = parent;
+        }
+        out
+    }
+
+    /// Graphviz `digraph`; the active version is drawn bold, dead branches dashed.
+    pub fn to_dot(&self) -> String {
+        let mut out = format!(
+            "digraph \"lineage_{}\" {{\n  rankdir=TB;\n  node [shape=box, style=rounded, fontname=\"monospace\"];\n",
+            escape_dot(&self.skill_name)
+        );
+        for n in &self.nodes {
+            let style = if n.is_active {
+                ", penwidth=2, color=\"darkgreen\""
+            } else if is_dead(&n.status) {
+                ", style=\"rounded,dashed\", color=\"firebrick\""
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "  \"{}\" [label=\"{}\"{}];\n",
+                n.timestamp_ms,
+                escape_dot(&node_label(n).join("\n")),
+                style
+            ));
+        }
+        for e in &self.edges {
+            out.push_str(&format!("  \"{}\" -> \"{}\";\n", e.parent, e.child));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid `graph TD`; the active version gets class `active`, dead branches `dead`.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph TD\n");
+        for n in &self.nodes {
+            let label: Vec<String> = node_label(n).iter().map(|l| escape_mermaid(l)).collect();
+            out.push_str(&format!("  v{}[\"{}\"]\n", n.timestamp_ms, label.join("<br/>")));
+        }
+        for e in &self.edges {
+            out.push_str(&format!("  v{} --> v{}\n", e.parent, e.child));
+        }
+        out.push_str("  classDef active stroke:#2e7d32,stroke-width:3px\n");
+        out.push_str("  classDef dead stroke:#c62828,stroke-dasharray:4 3\n");
+        for n in &self.nodes {
+            if n.is_active {
+                out.push_str(&format!("  class v{} active\n", n.timestamp_ms));
+            } else if is_dead(&n.status) {
+                out.push_str(&format!("  class v{} dead\n", n.timestamp_ms));
+            }
+        }
+        out
+    }
+}
+
+fn is_dead(status: &PatchStatus) -> bool {
+    !matches!(status, PatchStatus::Applied | PatchStatus::Pending | PatchStatus::Canary)
+}
+
+fn node_label(n: &LineageNode) -> Vec<String> {
+    let mut lines = vec![
+        format!("v{}", n.timestamp_ms),
+        format!("{:?} · {}", n.status, &n.code_hash[..n.code_hash.len().min(12)]),
+    ];
+    if let Some(ref model) = n.origin.model {
+        lines.push(format!("model: {}", model));
+    }
+    if let Some(ref trigger) = n.origin.trigger {
+        lines.push(format!("trigger: {}", trigger.skill));
+    }
+    if let Some(ref pd) = n.performance_delta {
+        lines.push(format!("cpu {} · mem {}", pd.cpu, pd.mem));
+    }
+    if !n.reviews.is_empty() {
+        lines.push(format!("{} reviewer(s)", n.reviews.len()));
+    }
+    lines
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(ts: i64, parent: Option<i64>, status: PatchStatus, active: bool) -> PatchVersion {
+        PatchVersion {
+            skill_name: "fs_tools".to_string(),
+            timestamp_ms: ts,
+            code_hash: format!("{:032x}", ts),
+            source_path: PathBuf::from(format!("fs_tools_v{}.rs", ts)),
+            artifact_path: None,
+            is_active: active,
+            status,
+            performance_delta: None,
+            description: String::new(),
+            toolchain: None,
+            origin: PatchOrigin {
+                parent_timestamp_ms: parent,
+                model: Some("model/a".to_string()),
+                ..PatchOrigin::default()
+            },
+        }
+    }
+
+    #[test]
+    fn ancestors_walk_parent_edges_and_renderers_mark_active() {
+        // 1 -> 2 -> 4 (active), 2 -> 3 (rolled back), 5's parent was pruned.
+        let versions = vec![
+            version(1, None, PatchStatus::Applied, false),
+            version(2, Some(1), PatchStatus::Applied, false),
+            version(3, Some(2), PatchStatus::RolledBack, false),
+            version(4, Some(2), PatchStatus::Applied, true),
+            version(5, Some(99), PatchStatus::Applied, false),
+        ];
+        let graph = LineageGraph::from_versions("fs_tools", &versions);
+        assert_eq!(graph.edges.len(), 3);
+
+        let active = graph.active().unwrap().timestamp_ms;
+        let ancestors: Vec<i64> = graph.ancestors(active).iter().map(|n| n.timestamp_ms).collect();
+        assert_eq!(ancestors, vec![2, 1]);
+        assert!(graph.ancestors(5).is_empty());
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"2\" -> \"4\";"));
+        assert!(dot.contains("\"4\" [label=\"v4\\nApplied"));
+        assert!(dot.contains("penwidth=2"));
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.contains("  v2 --> v3\n"));
+        assert!(mermaid.contains("class v4 active"));
+        assert!(mermaid.contains("class v3 dead"));
+    }
+
+    #[test]
+    fn sidecar_round_trips_and_infers_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let older = dir.path().join("patch_fs_v100.rs");
+        let newer = dir.path().join("patch_fs_v200.rs");
+        std::fs::write(&older, "// v1").unwrap();
+        std::fs::write(dir.path().join("patch_other_v150.rs"), "// other").unwrap();
+        std::fs::write(&newer, "// v2").unwrap();
+
+        let origin = PatchOrigin {
+            prompt_hash: Some(prompt_hash("fix it")),
+            ..PatchOrigin::default()
+        };
+        record_patch_lineage(&newer, origin, "fix", None).unwrap();
+
+        let record = read_lineage(&newer).unwrap();
+        assert_eq!(record.origin.parent_timestamp_ms, Some(100));
+        assert_eq!(record.origin.prompt_hash.as_deref().map(str::len), Some(64));
+        assert_eq!(record.status, PatchStatus::Applied);
+        assert!(read_lineage(&older).is_none());
+    }
+
+    #[test]
+    fn load_reads_history_without_touching_the_directory() {
+        use crate::active_versions::{ActiveEntry, MANIFEST_FILE};
+
+        let dir = tempfile::tempdir().unwrap();
+        let older = dir.path().join("patch_fs_v100.rs");
+        let newer = dir.path().join("patch_fs_v200.rs");
+        std::fs::write(&older, "// v1").unwrap();
+        std::fs::write(&newer, "// v2").unwrap();
+        record_patch_lineage(&newer, PatchOrigin::default(), "fix", None).unwrap();
+        let mut active = ActiveVersions::default();
+        active
+            .skills
+            .insert("patch_fs".to_string(), ActiveEntry::for_source(200, &newer, "// v2"));
+        std::fs::write(dir.path().join(MANIFEST_FILE), serde_json::to_vec(&active).unwrap()).unwrap();
+        // A manager would discard this interrupted write on open.
+        let partial = dir.path().join(format!("{}.tmp", MANIFEST_FILE));
+        std::fs::write(&partial, "{").unwrap();
+        let listing = || {
+            let mut names: Vec<_> = std::fs::read_dir(dir.path())
+                .unwrap()
+                .map(|e| e.unwrap().file_name())
+                .collect();
+            names.sort();
+            names
+        };
+        let before = listing();
+
+        let graph = LineageGraph::load(dir.path(), "patch_fs").unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges, vec![LineageEdge { parent: 100, child: 200 }]);
+        assert_eq!(graph.active().map(|n| n.timestamp_ms), Some(200));
+        assert_eq!(listing(), before);
+
+        let missing = LineageGraph::load(&dir.path().join("absent"), "patch_fs").unwrap();
+        assert!(missing.nodes.is_empty());
+    }
+}
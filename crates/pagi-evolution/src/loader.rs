@@ -1,4 +1,7 @@
-//! SkillLoader: load .so/.dll via libloading and execute via C ABI.
+//! SkillLoader: load .so/.dll via libloading and execute via C ABI, either in-process or
+//! inside a supervised `pagi-skill-host` worker (see [`crate::sandbox`]). `.wasm` artifacts
+//! run in the embedded WASM runtime (see [`crate::wasm`]). A new version can shadow the active
+//! one as a canary before taking over (see [`crate::canary`]).
 
 use libloading::Library;
 use serde_json::Value;
@@ -6,7 +9,10 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
 
+use crate::canary::{Canary, CanaryCallback, CanaryOutcome, CanaryPolicy, CanaryReport};
+use crate::sandbox::{SandboxConfig, SandboxedSkill};
 use crate::skill::{DynamicSkill, SkillError};
+use crate::wasm::{WasmCapabilities, WasmConfig, WasmHost, WasmSkill};
 
 /// C ABI: execute(args_json) returns allocated C string or null.
 type ExecuteFn = unsafe extern "C" fn(*const std::ffi::c_char) -> *mut std::ffi::c_char;
@@ -14,14 +20,30 @@ type ExecuteFn = unsafe extern "C" fn(*const std::ffi::c_char) -> *mut std::ffi:
 type FreeFn = unsafe extern "C" fn(*mut std::ffi::c_char);
 
 /// Wrapper that calls into a loaded library via C ABI.
-struct LoadedSkill {
+pub(crate) struct LoadedSkill {
     _lib: Library,
     execute: ExecuteFn,
     free: FreeFn,
 }
 
 impl LoadedSkill {
-    fn execute_json(&self, args: &Value) -> Result<Value, SkillError> {
+    /// Opens the library and resolves the `pagi_dynamic_skill_execute` / `pagi_dynamic_skill_free` symbols.
+    pub(crate) fn open(path: &Path) -> Result<Self, SkillError> {
+        let lib = unsafe {
+            Library::new(path).map_err(|e| SkillError::Load(format!("libloading: {}", e)))?
+        };
+        let execute = unsafe {
+            *lib.get(b"pagi_dynamic_skill_execute")
+                .map_err(|e| SkillError::Load(format!("symbol pagi_dynamic_skill_execute: {}", e)))?
+        };
+        let free = unsafe {
+            *lib.get(b"pagi_dynamic_skill_free")
+                .map_err(|e| SkillError::Load(format!("symbol pagi_dynamic_skill_free: {}", e)))?
+        };
+        Ok(Self { _lib: lib, execute, free })
+    }
+
+    pub(crate) fn execute_json(&self, args: &Value) -> Result<Value, SkillError> {
         let args_str = serde_json::to_string(args).map_err(SkillError::Serialization)?;
         let c_args = std::ffi::CString::new(args_str.as_bytes())
             .map_err(|e| SkillError::Execution(e.to_string()))?;
@@ -49,52 +71,202 @@ impl DynamicSkill for LoadedSkillAdapter {
 }
 
 /// Loads dynamic libraries and dispatches execute by skill name.
-/// Stores library handles so symbols remain valid; hot-reload = drop old, load new under same name.
+/// Stores library handles (or host workers) so symbols remain valid; hot-reload = drop old, load new under same name.
 pub struct SkillLoader {
-    skills: RwLock<HashMap<String, Arc<LoadedSkillAdapter>>>,
+    skills: RwLock<HashMap<String, Arc<dyn DynamicSkill>>>,
+    /// Candidate versions shadowing the active skill of the same name.
+    canaries: RwLock<HashMap<String, Arc<Canary>>>,
+    /// When Some, every library is loaded in its own `pagi-skill-host` process instead of in-process.
+    sandbox: Option<SandboxConfig>,
+    /// Fuel / memory bounds for `.wasm` skills.
+    wasm: WasmConfig,
+    /// KB / HTTP services for `.wasm` skills (set once the KnowledgeStore is available).
+    wasm_host: RwLock<Option<Arc<dyn WasmHost>>>,
 }
 
 impl SkillLoader {
+    /// In-process loader (libraries are mapped into the calling process).
     pub fn new() -> Self {
         Self {
             skills: RwLock::new(HashMap::new()),
+            canaries: RwLock::new(HashMap::new()),
+            sandbox: None,
+            wasm: WasmConfig::default(),
+            wasm_host: RwLock::new(None),
+        }
+    }
+
+    /// Loader that runs every library in a supervised `pagi-skill-host` worker.
+    pub fn sandboxed(config: SandboxConfig) -> Self {
+        Self {
+            sandbox: Some(config),
+            ..Self::new()
         }
     }
 
+    /// Sandboxed unless `PAGI_SKILL_SANDBOX=inprocess`; limits from `SandboxConfig::from_env`
+    /// and `WasmConfig::from_env`.
+    pub fn from_env() -> Self {
+        let loader = match std::env::var("PAGI_SKILL_SANDBOX").as_deref().map(str::trim) {
+            Ok("inprocess") | Ok("off") => Self::new(),
+            _ => Self::sandboxed(SandboxConfig::from_env()),
+        };
+        loader.with_wasm_config(WasmConfig::from_env())
+    }
+
+    /// Fuel / memory bounds for `.wasm` skills loaded after this call.
+    pub fn with_wasm_config(mut self, config: WasmConfig) -> Self {
+        self.wasm = config;
+        self
+    }
+
+    /// Attaches the KB / HTTP services that `.wasm` skills reach through their host imports.
+    pub fn set_wasm_host(&self, host: Arc<dyn WasmHost>) {
+        if let Ok(mut guard) = self.wasm_host.write() {
+            *guard = Some(host);
+        }
+    }
+
+    /// True when libraries run out-of-process.
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandbox.is_some()
+    }
+
     /// Load a dynamic library from the given path and register it under `name`.
     /// If `name` was already loaded, the previous library is replaced (hot-reload).
+    /// `.wasm` files are loaded with no capabilities beyond logging (see [`Self::load_wasm`]).
     pub fn load<P: AsRef<Path>>(&self, path: P, name: String) -> Result<(), SkillError> {
-        let path = path.as_ref();
-        let lib = unsafe {
-            Library::new(path).map_err(|e| SkillError::Load(format!("libloading: {}", e)))?
-        };
-        let execute_fn = unsafe {
-            *lib.get(b"pagi_dynamic_skill_execute")
-                .map_err(|e| SkillError::Load(format!("symbol pagi_dynamic_skill_execute: {}", e)))?
-        };
-        let free_fn = unsafe {
-            *lib.get(b"pagi_dynamic_skill_free")
-                .map_err(|e| SkillError::Load(format!("symbol pagi_dynamic_skill_free: {}", e)))?
-        };
-        let loaded = LoadedSkill {
-            _lib: lib,
-            execute: execute_fn,
-            free: free_fn,
-        };
-        let adapter = Arc::new(LoadedSkillAdapter(Arc::new(loaded)));
-        self.skills.write().map_err(|e| SkillError::Load(e.to_string()))?.insert(name, adapter);
+        let skill = self.open(path.as_ref(), &name, WasmCapabilities::default())?;
+        self.insert(&name, skill);
+        Ok(())
+    }
+
+    /// Load a `wasm32-wasip1` skill whose host imports are limited to `caps`.
+    pub fn load_wasm<P: AsRef<Path>>(&self, path: P, name: String, caps: WasmCapabilities) -> Result<(), SkillError> {
+        let skill = self.open_wasm(path.as_ref(), &name, caps)?;
+        self.insert(&name, skill);
+        Ok(())
+    }
+
+    /// Opens an artifact without registering it; `caps` applies to `.wasm` files only.
+    fn open(&self, path: &Path, name: &str, caps: WasmCapabilities) -> Result<Arc<dyn DynamicSkill>, SkillError> {
+        if path.extension().is_some_and(|e| e == "wasm") {
+            return self.open_wasm(path, name, caps);
+        }
+        Ok(match self.sandbox {
+            Some(ref config) => Arc::new(SandboxedSkill::spawn(path, name, config.clone())?),
+            None => Arc::new(LoadedSkillAdapter(Arc::new(LoadedSkill::open(path)?))),
+        })
+    }
+
+    fn open_wasm(&self, path: &Path, name: &str, caps: WasmCapabilities) -> Result<Arc<dyn DynamicSkill>, SkillError> {
+        let host = self.wasm_host.read().ok().and_then(|g| g.clone());
+        Ok(Arc::new(WasmSkill::from_file(path, name, caps, host, self.wasm.clone())?))
+    }
+
+    /// Registers `skill` under `name`, replacing any previous version and ending its canary.
+    pub(crate) fn insert(&self, name: &str, skill: Arc<dyn DynamicSkill>) {
+        if let Ok(mut guard) = self.canaries.write() {
+            guard.remove(name);
+        }
+        if let Ok(mut guard) = self.skills.write() {
+            guard.insert(name.to_string(), skill);
+        }
+    }
+
+    /// Load the artifact at `path` as a canary for the active skill `name`: it runs in shadow on
+    /// every call and takes over traffic according to `policy`. `on_finish` is invoked once when
+    /// the canary is promoted (the candidate becomes the active version) or rolled back.
+    /// Replaces any canary already running for `name`.
+    pub fn start_canary<P: AsRef<Path>>(
+        &self,
+        path: P,
+        name: &str,
+        policy: CanaryPolicy,
+        caps: WasmCapabilities,
+        on_finish: Option<CanaryCallback>,
+    ) -> Result<(), SkillError> {
+        let candidate = self.open(path.as_ref(), name, caps)?;
+        self.start_canary_with(name, candidate, policy, on_finish)
+    }
+
+    pub(crate) fn start_canary_with(
+        &self,
+        name: &str,
+        candidate: Arc<dyn DynamicSkill>,
+        policy: CanaryPolicy,
+        on_finish: Option<CanaryCallback>,
+    ) -> Result<(), SkillError> {
+        let active = self.skills.read().map_err(|e| SkillError::Load(e.to_string()))?;
+        if !active.contains_key(name) {
+            return Err(SkillError::NotLoaded(name.to_string()));
+        }
+        drop(active);
+        let canary = Arc::new(Canary::new(name, candidate, policy, on_finish));
+        self.canaries
+            .write()
+            .map_err(|e| SkillError::Load(e.to_string()))?
+            .insert(name.to_string(), canary);
         Ok(())
     }
 
+    /// Current state of the canary running for `name`, if any.
+    pub fn canary_report(&self, name: &str) -> Option<CanaryReport> {
+        let canary = self.canaries.read().ok()?.get(name).cloned()?;
+        Some(canary.report())
+    }
+
+    /// Roll back the canary running for `name` (the active version keeps serving).
+    /// Returns the final report, or `None` if no canary was running.
+    pub fn abort_canary(&self, name: &str, reason: &str) -> Option<CanaryReport> {
+        let canary = self.canaries.write().ok()?.remove(name)?;
+        let report = canary.finish(CanaryOutcome::RolledBack, Some(reason.to_string()))?;
+        if let Some(cb) = &canary.on_finish {
+            cb(&report);
+        }
+        Some(report)
+    }
+
     /// Execute a loaded skill by name with the given JSON args.
     pub fn execute(&self, name: &str, args: Value) -> Result<Value, SkillError> {
         let guard = self.skills.read().map_err(|e| SkillError::Load(e.to_string()))?;
-        let skill = guard.get(name).ok_or_else(|| SkillError::NotLoaded(name.to_string()))?;
-        skill.execute(args)
+        let skill = Arc::clone(guard.get(name).ok_or_else(|| SkillError::NotLoaded(name.to_string()))?);
+        drop(guard);
+        let canary = self.canaries.read().ok().and_then(|g| g.get(name).cloned());
+        let Some(canary) = canary else {
+            return skill.execute(args);
+        };
+        let (result, finished) = canary.execute(&skill, args);
+        if let Some(report) = finished {
+            self.finish_canary(name, &canary, &report);
+        }
+        result
+    }
+
+    /// Applies a finished canary: the candidate replaces the active version when promoted.
+    fn finish_canary(&self, name: &str, canary: &Arc<Canary>, report: &CanaryReport) {
+        if let Ok(mut guard) = self.canaries.write() {
+            // A newer canary (or a reload) may already have replaced this one.
+            if !guard.get(name).is_some_and(|c| Arc::ptr_eq(c, canary)) {
+                return;
+            }
+            guard.remove(name);
+            if report.outcome == CanaryOutcome::Promoted {
+                if let Ok(mut skills) = self.skills.write() {
+                    skills.insert(name.to_string(), Arc::clone(&canary.candidate));
+                }
+            }
+        }
+        if let Some(cb) = &canary.on_finish {
+            cb(report);
+        }
     }
 
     /// Unload a skill by name (drops the library handle).
     pub fn unload(&self, name: &str) -> bool {
+        if let Ok(mut guard) = self.canaries.write() {
+            guard.remove(name);
+        }
         self.skills.write().map(|mut g| g.remove(name).is_some()).unwrap_or(false)
     }
 
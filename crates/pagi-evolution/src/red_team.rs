@@ -11,8 +11,11 @@
 //! | Component | Role |
 //! |-----------|------|
 //! | [`RedTeamAnalyzer`] | Sends code to a secondary LLM for vulnerability analysis. |
+//! | [`AstAnalyzer`] | Offline `syn`-based analysis; merged into every verdict. |
 //! | [`SecurityVerdict`] | Structured result: severity, findings, recommendation. |
-//! | [`ConsensusGate`] | Decision engine: auto-reject "Critical"/"High" findings. |
+//! | [`SecurityReviewer`] | One independent reviewer (a model or the AST analyzer). |
+//! | [`ReviewPanel`] | Runs every reviewer concurrently on the same patch. |
+//! | [`ConsensusGate`] | Decision engine: applies the [`QuorumPolicy`] to the panel's verdicts. |
 //! | [`CveCheckList`] | Common vulnerability patterns injected into the review prompt. |
 //!
 //! ## Genetic Memory Integration
@@ -22,11 +25,17 @@
 //! preventing the agent from ever re-proposing the same flawed code.
 
 use std::fmt;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use async_trait::async_trait;
+use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+use crate::ast_analyzer::{AstAnalyzer, SourceSpan, AST_ANALYZER_MODEL};
+use crate::rollback::SecurityAuditSummary;
+
 // ---------------------------------------------------------------------------
 // Severity Classification
 // ---------------------------------------------------------------------------
@@ -88,6 +97,9 @@ pub struct SecurityFinding {
     pub affected_region: Option<String>,
     /// Suggested remediation.
     pub remediation: Option<String>,
+    /// Exact source location, when the finding comes from static analysis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
 }
 
 // ---------------------------------------------------------------------------
@@ -334,21 +346,15 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
     /// Perform an adversarial peer review of the proposed code.
     ///
     /// In **Live** mode, sends the code to the configured reviewer model via OpenRouter.
-    /// In **Mock** mode (no API key), performs a heuristic-based local analysis.
+    /// In **Mock** mode (no API key), runs the AST-based [`AstAnalyzer`] locally. Live verdicts
+    /// are merged with the same static findings.
     pub async fn review_patch(
         &self,
         skill_name: &str,
         code: &str,
         patch_description: &str,
     ) -> SecurityVerdict {
-        // Check if we have an API key for live review.
-        let api_key = std::env::var("PAGI_REDTEAM_API_KEY")
-            .or_else(|_| std::env::var("PAGI_LLM_API_KEY"))
-            .or_else(|_| std::env::var("OPENROUTER_API_KEY"))
-            .ok()
-            .filter(|k| !k.trim().is_empty());
-
-        match api_key {
+        match reviewer_api_key() {
             Some(key) => {
                 self.review_live(skill_name, code, patch_description, &key)
                     .await
@@ -356,9 +362,9 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
             None => {
                 info!(
                     target: "pagi::redteam",
-                    "No API key for Red-Team reviewer — falling back to heuristic analysis"
+                    "No API key for Red-Team reviewer — falling back to static analysis"
                 );
-                self.review_heuristic(skill_name, code)
+                self.review_static(skill_name, code)
             }
         }
     }
@@ -371,6 +377,23 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
         patch_description: &str,
         api_key: &str,
     ) -> SecurityVerdict {
+        match self
+            .request_review(skill_name, code, patch_description, api_key)
+            .await
+        {
+            Some(verdict) => self.merge_static(verdict, skill_name, code),
+            None => self.review_static(skill_name, code),
+        }
+    }
+
+    /// The reviewer model's own verdict, or `None` when the API is unreachable or errors.
+    async fn request_review(
+        &self,
+        skill_name: &str,
+        code: &str,
+        patch_description: &str,
+        api_key: &str,
+    ) -> Option<SecurityVerdict> {
         let prompt = Self::build_review_prompt(skill_name, code, patch_description);
 
         let request_body = serde_json::json!({
@@ -413,9 +436,9 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
                 warn!(
                     target: "pagi::redteam",
                     error = %e,
-                    "Red-Team API request failed — falling back to heuristic"
+                    "Red-Team API request failed — falling back to static analysis"
                 );
-                return self.review_heuristic(skill_name, code);
+                return None;
             }
         };
 
@@ -426,9 +449,9 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
                 target: "pagi::redteam",
                 status = %status,
                 body = %body,
-                "Red-Team API returned error — falling back to heuristic"
+                "Red-Team API returned error — falling back to static analysis"
             );
-            return self.review_heuristic(skill_name, code);
+            return None;
         }
 
         // Parse the OpenRouter response.
@@ -440,7 +463,7 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
                     error = %e,
                     "Failed to parse Red-Team API response"
                 );
-                return self.review_heuristic(skill_name, code);
+                return None;
             }
         };
 
@@ -451,7 +474,7 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
             .unwrap_or("");
 
         // Try to parse the content as our SecurityVerdict JSON.
-        self.parse_review_response(content, &self.config.reviewer_model)
+        Some(self.parse_review_response(content, &self.config.reviewer_model))
     }
 
     /// Parse the LLM's JSON response into a SecurityVerdict.
@@ -498,6 +521,7 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
                         description: f.description.unwrap_or_default(),
                         affected_region: f.affected_region,
                         remediation: f.remediation,
+                        span: None,
                     })
                     .collect();
 
@@ -553,212 +577,207 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
         }
     }
 
-    /// Heuristic-based local analysis (no LLM required).
-    /// Scans for common vulnerability patterns using string matching.
-    fn review_heuristic(&self, skill_name: &str, code: &str) -> SecurityVerdict {
-        let mut findings = Vec::new();
-        let lines: Vec<&str> = code.lines().collect();
-
-        // --- Unsafe blocks ---
-        let unsafe_count = code.matches("unsafe").count();
-        if unsafe_count > 0 {
-            findings.push(SecurityFinding {
-                category: "Unsafe Code".to_string(),
-                severity: if unsafe_count > 3 {
-                    Severity::High
-                } else {
-                    Severity::Medium
-                },
-                description: format!(
-                    "Code contains {} unsafe block(s). Each must be audited for soundness.",
-                    unsafe_count
-                ),
-                affected_region: None,
-                remediation: Some(
-                    "Minimize unsafe usage. Document safety invariants with // SAFETY: comments."
-                        .to_string(),
-                ),
-            });
-        }
+    /// Static analysis of the patch (no LLM required).
+    ///
+    /// Runs the [`AstAnalyzer`] and folds its span-precise findings into a verdict, so the
+    /// [`ConsensusGate`] has a deterministic input when no reviewer model is reachable.
+    pub fn review_static(&self, skill_name: &str, code: &str) -> SecurityVerdict {
+        static_verdict(skill_name, code)
+    }
 
-        // --- Command injection ---
-        for (i, line) in lines.iter().enumerate() {
-            if (line.contains("Command::new") || line.contains("process::Command"))
-                && !line.trim_start().starts_with("//")
-            {
-                // Check if the command argument comes from a variable (potential injection).
-                let has_format = lines[i.saturating_sub(3)..=(i + 3).min(lines.len() - 1)]
-                    .iter()
-                    .any(|l| l.contains("format!") || l.contains("&user") || l.contains("input"));
-
-                if has_format {
-                    findings.push(SecurityFinding {
-                        category: "Command Injection (CWE-78)".to_string(),
-                        severity: Severity::High,
-                        description: format!(
-                            "Line {}: Command execution with potentially user-controlled input.",
-                            i + 1
-                        ),
-                        affected_region: Some(format!("Line {}", i + 1)),
-                        remediation: Some(
-                            "Sanitize all inputs before passing to Command. Use allowlists for command names."
-                                .to_string(),
-                        ),
-                    });
-                }
-            }
+    /// Merge static-analysis findings into a live verdict so a lenient or confused reviewer
+    /// model cannot wave through what the AST analyzer blocks.
+    fn merge_static(&self, mut verdict: SecurityVerdict, skill_name: &str, code: &str) -> SecurityVerdict {
+        let static_verdict = self.review_static(skill_name, code);
+        if static_verdict.findings.is_empty() {
+            return verdict;
         }
-
-        // --- Path traversal ---
-        for (i, line) in lines.iter().enumerate() {
-            if (line.contains("..") && (line.contains("Path") || line.contains("join")))
-                || line.contains("canonicalize")
-            {
-                if !line.trim_start().starts_with("//") {
-                    findings.push(SecurityFinding {
-                        category: "Path Traversal (CWE-22)".to_string(),
-                        severity: Severity::Medium,
-                        description: format!(
-                            "Line {}: Potential path traversal pattern detected.",
-                            i + 1
-                        ),
-                        affected_region: Some(format!("Line {}", i + 1)),
-                        remediation: Some(
-                            "Validate paths against a root directory. Use canonicalize() and check prefix."
-                                .to_string(),
-                        ),
-                    });
-                }
-            }
+        verdict.findings.extend(static_verdict.findings);
+        verdict.overall_severity = verdict
+            .findings
+            .iter()
+            .map(|f| f.severity)
+            .max()
+            .unwrap_or(Severity::Info);
+        if verdict.has_blocking_findings() {
+            verdict.passed = false;
         }
+        verdict.reviewer_model = format!("{}+{}", verdict.reviewer_model, AST_ANALYZER_MODEL);
+        verdict.summary = format!("{} {}", verdict.summary, static_verdict.summary);
+        verdict
+    }
+}
 
-        // --- Unwrap in non-test code ---
-        let unwrap_count = code.matches(".unwrap()").count();
-        let expect_count = code.matches(".expect(").count();
-        let panic_points = unwrap_count + expect_count;
-        if panic_points > 5 {
-            findings.push(SecurityFinding {
-                category: "Panic in Production".to_string(),
-                severity: Severity::Medium,
-                description: format!(
-                    "Code contains {} potential panic points (.unwrap()/.expect()). \
-                     These can crash the process in production.",
-                    panic_points
-                ),
-                affected_region: None,
-                remediation: Some(
-                    "Replace .unwrap() with proper error handling (? operator or match)."
-                        .to_string(),
-                ),
-            });
-        }
+/// [`AstAnalyzer`] findings folded into a verdict.
+fn static_verdict(skill_name: &str, code: &str) -> SecurityVerdict {
+    let findings = AstAnalyzer::analyze(code);
 
-        // --- Hardcoded secrets ---
-        let secret_patterns = [
-            "api_key", "API_KEY", "secret", "password", "token", "Bearer ",
-        ];
-        for (i, line) in lines.iter().enumerate() {
-            if line.trim_start().starts_with("//") {
-                continue;
-            }
-            for pattern in &secret_patterns {
-                if line.contains(pattern)
-                    && (line.contains("= \"") || line.contains("= '"))
-                    && !line.contains("env::var")
-                    && !line.contains("std::env")
-                {
-                    findings.push(SecurityFinding {
-                        category: "Hardcoded Secrets".to_string(),
-                        severity: Severity::High,
-                        description: format!(
-                            "Line {}: Potential hardcoded secret ({}).",
-                            i + 1,
-                            pattern
-                        ),
-                        affected_region: Some(format!("Line {}", i + 1)),
-                        remediation: Some(
-                            "Use environment variables or a secrets manager instead of hardcoding."
-                                .to_string(),
-                        ),
-                    });
-                    break; // One finding per line is enough.
-                }
-            }
-        }
+    // --- Memory usage heuristic ---
+    let memory_warning = if code.contains("Vec::new()") && code.len() > 5000 {
+        Some("Large code with dynamic allocations — monitor runtime memory usage.".to_string())
+    } else {
+        None
+    };
 
-        // --- Unbounded allocation ---
-        for (i, line) in lines.iter().enumerate() {
-            if line.contains("Vec::with_capacity") || line.contains("String::with_capacity") {
-                // Check if capacity comes from external input.
-                if lines[i.saturating_sub(2)..=(i + 2).min(lines.len() - 1)]
-                    .iter()
-                    .any(|l| l.contains("as usize") || l.contains("parse"))
-                {
-                    findings.push(SecurityFinding {
-                        category: "Unbounded Allocation".to_string(),
-                        severity: Severity::Medium,
-                        description: format!(
-                            "Line {}: Allocation size may be controlled by external input.",
-                            i + 1
-                        ),
-                        affected_region: Some(format!("Line {}", i + 1)),
-                        remediation: Some(
-                            "Cap allocation sizes with a maximum limit.".to_string(),
-                        ),
-                    });
-                }
-            }
-        }
+    let overall = findings
+        .iter()
+        .map(|f| f.severity)
+        .max()
+        .unwrap_or(Severity::Info);
 
-        // --- Memory usage heuristic ---
-        let memory_warning = if code.contains("Vec::new()") && code.len() > 5000 {
-            Some("Large code with dynamic allocations — monitor runtime memory usage.".to_string())
-        } else {
-            None
-        };
+    let passed = !overall.is_blocking();
 
-        // Build verdict.
-        let overall = findings
-            .iter()
-            .map(|f| f.severity)
-            .max()
-            .unwrap_or(Severity::Info);
+    let summary = if findings.is_empty() {
+        format!(
+            "Static analysis of '{}': No vulnerabilities found.",
+            skill_name
+        )
+    } else {
+        format!(
+            "Static analysis of '{}': {} finding(s), highest severity: {}.",
+            skill_name,
+            findings.len(),
+            overall
+        )
+    };
+
+    info!(
+        target: "pagi::redteam",
+        skill = skill_name,
+        severity = %overall,
+        passed = passed,
+        findings = findings.len(),
+        "Static Red-Team review complete"
+    );
+
+    SecurityVerdict {
+        overall_severity: overall,
+        findings,
+        reviewer_model: AST_ANALYZER_MODEL.to_string(),
+        passed,
+        summary,
+        reviewed_at_ms: now_epoch_ms(),
+        memory_warning,
+        raw_response: None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Review Panel
+// ---------------------------------------------------------------------------
 
-        let passed = !overall.is_blocking();
+/// An independent reviewer on the [`ReviewPanel`].
+#[async_trait]
+pub trait SecurityReviewer: Send + Sync {
+    /// Reviewer identity recorded with its verdict (model name or analyzer id).
+    fn name(&self) -> String;
 
-        let summary = if findings.is_empty() {
-            format!(
-                "Heuristic analysis of '{}': No vulnerabilities found.",
-                skill_name
-            )
-        } else {
-            format!(
-                "Heuristic analysis of '{}': {} finding(s), highest severity: {}.",
-                skill_name,
-                findings.len(),
-                overall
-            )
-        };
+    /// Review the patch. `None` means the reviewer abstained (e.g. its API is unreachable).
+    async fn review(&self, skill_name: &str, code: &str, patch_description: &str) -> Option<SecurityVerdict>;
+}
 
-        info!(
-            target: "pagi::redteam",
-            skill = skill_name,
-            severity = %overall,
-            passed = passed,
-            findings = findings.len(),
-            "Heuristic Red-Team review complete"
-        );
+/// The configured reviewer model. Abstains when no API key is set or the request fails, so
+/// the AST analyzer is never counted twice.
+#[async_trait]
+impl SecurityReviewer for RedTeamAnalyzer {
+    fn name(&self) -> String {
+        self.config.reviewer_model.clone()
+    }
 
-        SecurityVerdict {
-            overall_severity: overall,
-            findings,
-            reviewer_model: "heuristic-analyzer-v1".to_string(),
-            passed,
-            summary,
-            reviewed_at_ms: now_epoch_ms(),
-            memory_warning,
-            raw_response: None,
-        }
+    async fn review(&self, skill_name: &str, code: &str, patch_description: &str) -> Option<SecurityVerdict> {
+        let key = reviewer_api_key()?;
+        self.request_review(skill_name, code, patch_description, &key).await
+    }
+}
+
+/// The offline `syn`-based analysis.
+#[async_trait]
+impl SecurityReviewer for AstAnalyzer {
+    fn name(&self) -> String {
+        AST_ANALYZER_MODEL.to_string()
+    }
+
+    async fn review(&self, skill_name: &str, code: &str, _patch_description: &str) -> Option<SecurityVerdict> {
+        Some(static_verdict(skill_name, code))
+    }
+}
+
+/// One reviewer's result, kept alongside the combined verdict for the audit trail.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewerVerdict {
+    pub reviewer: String,
+    /// `None` if the reviewer abstained.
+    pub verdict: Option<SecurityVerdict>,
+}
+
+impl ReviewerVerdict {
+    fn votes_critical(&self) -> bool {
+        self.verdict.as_ref().is_some_and(|v| v.has_lethal_findings())
+    }
+
+    fn votes_blocking(&self) -> bool {
+        self.verdict.as_ref().is_some_and(|v| v.has_blocking_findings())
+    }
+
+    fn is_static(&self) -> bool {
+        self.reviewer == AST_ANALYZER_MODEL
+    }
+}
+
+/// A set of independent reviewers that all see the same patch.
+#[derive(Clone, Default)]
+pub struct ReviewPanel {
+    reviewers: Vec<Arc<dyn SecurityReviewer>>,
+}
+
+impl ReviewPanel {
+    pub fn new(reviewers: Vec<Arc<dyn SecurityReviewer>>) -> Self {
+        Self { reviewers }
+    }
+
+    /// `primary`, one more reviewer per model in `PAGI_REDTEAM_EXTRA_MODELS` (comma-separated,
+    /// same API as `primary`), and the AST analyzer.
+    pub fn from_env(primary: Arc<RedTeamAnalyzer>) -> Self {
+        let extra: Vec<Arc<dyn SecurityReviewer>> = std::env::var("PAGI_REDTEAM_EXTRA_MODELS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty() && *m != primary.config.reviewer_model)
+            .map(|model| {
+                Arc::new(RedTeamAnalyzer::new(RedTeamConfig {
+                    reviewer_model: model.to_string(),
+                    ..primary.config.clone()
+                })) as Arc<dyn SecurityReviewer>
+            })
+            .collect();
+        let mut reviewers: Vec<Arc<dyn SecurityReviewer>> = vec![primary];
+        reviewers.extend(extra);
+        reviewers.push(Arc::new(AstAnalyzer));
+        Self::new(reviewers)
+    }
+
+    pub fn with_reviewer(mut self, reviewer: Arc<dyn SecurityReviewer>) -> Self {
+        self.reviewers.push(reviewer);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.reviewers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reviewers.is_empty()
+    }
+
+    /// Run every reviewer concurrently; results keep the panel order.
+    pub async fn review(&self, skill_name: &str, code: &str, patch_description: &str) -> Vec<ReviewerVerdict> {
+        join_all(self.reviewers.iter().map(|r| async move {
+            ReviewerVerdict {
+                reviewer: r.name(),
+                verdict: r.review(skill_name, code, patch_description).await,
+            }
+        }))
+        .await
     }
 }
 
@@ -766,17 +785,66 @@ Be thorough but fair. Only flag real vulnerabilities, not style issues."#,
 // Consensus Gate
 // ---------------------------------------------------------------------------
 
+/// How many reviewers must agree before the [`ConsensusGate`] rejects a patch.
+///
+/// Vote thresholds are capped at the number of reviewers that actually returned a verdict, so a
+/// panel reduced to the AST analyzer (offline) still rejects on its own. The AST analyzer's
+/// findings are deterministic evidence rather than an opinion, so a High or Critical finding
+/// from it rejects regardless of the vote thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuorumPolicy {
+    /// Reviewers reporting a Critical finding needed to reject as a Lethal Mutation (default 1).
+    pub critical_votes: usize,
+    /// Reviewers reporting a High-or-worse finding needed to reject (default 2).
+    pub high_votes: usize,
+    /// Reviewers that must return a verdict; fewer rejects the patch (default 1).
+    pub min_reviewers: usize,
+}
+
+impl Default for QuorumPolicy {
+    fn default() -> Self {
+        Self {
+            critical_votes: 1,
+            high_votes: 2,
+            min_reviewers: 1,
+        }
+    }
+}
+
+impl QuorumPolicy {
+    /// Reads `PAGI_REDTEAM_CRITICAL_VOTES`, `PAGI_REDTEAM_HIGH_VOTES` and
+    /// `PAGI_REDTEAM_MIN_REVIEWERS`.
+    pub fn from_env() -> Self {
+        let read = |key: &str, default: usize| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            critical_votes: read("PAGI_REDTEAM_CRITICAL_VOTES", defaults.critical_votes),
+            high_votes: read("PAGI_REDTEAM_HIGH_VOTES", defaults.high_votes),
+            min_reviewers: read("PAGI_REDTEAM_MIN_REVIEWERS", defaults.min_reviewers),
+        }
+    }
+}
+
 /// The Consensus Gate: decides whether a patch should proceed based on the
-/// Red-Team verdict and the system's consensus policy.
+/// Red-Team verdict(s) and the system's consensus policy.
 pub struct ConsensusGate {
     /// Whether to auto-reject on High severity (default: true).
     pub auto_reject_high: bool,
+    /// Agreement required across a [`ReviewPanel`].
+    pub quorum: QuorumPolicy,
 }
 
 impl Default for ConsensusGate {
     fn default() -> Self {
         Self {
             auto_reject_high: true,
+            quorum: QuorumPolicy::from_env(),
         }
     }
 }
@@ -790,13 +858,196 @@ pub struct ConsensusResult {
     pub reason: String,
     /// Whether the patch DNA should be marked as a Lethal Mutation.
     pub mark_lethal: bool,
-    /// The security verdict that informed this decision.
+    /// The security verdict that informed this decision (combined across the panel).
     pub verdict: SecurityVerdict,
+    /// Each reviewer's verdict, when the decision came from a [`ReviewPanel`].
+    #[serde(default)]
+    pub reviews: Vec<ReviewerVerdict>,
+    /// Where reviewers split on a severity vote or abstained.
+    #[serde(default)]
+    pub disagreements: Vec<String>,
+}
+
+impl ConsensusResult {
+    /// Audit record for `PatchPerformanceDelta::security_audit`.
+    pub fn audit_summary(&self) -> SecurityAuditSummary {
+        SecurityAuditSummary {
+            passed: self.approved,
+            overall_severity: self.verdict.overall_severity.to_string(),
+            reviewer_model: self.verdict.reviewer_model.clone(),
+            findings_count: self.verdict.findings.len(),
+            summary: self.reason.clone(),
+            memory_warning: self.verdict.memory_warning.clone(),
+            reviews: self.reviews.clone(),
+            disagreements: self.disagreements.clone(),
+        }
+    }
 }
 
 impl ConsensusGate {
     pub fn new(auto_reject_high: bool) -> Self {
-        Self { auto_reject_high }
+        Self {
+            auto_reject_high,
+            quorum: QuorumPolicy::default(),
+        }
+    }
+
+    pub fn with_quorum(mut self, quorum: QuorumPolicy) -> Self {
+        self.quorum = quorum;
+        self
+    }
+
+    /// Run `panel` on the patch and apply the quorum to its verdicts.
+    pub async fn review(
+        &self,
+        panel: &ReviewPanel,
+        skill_name: &str,
+        code: &str,
+        patch_description: &str,
+    ) -> ConsensusResult {
+        let reviews = panel.review(skill_name, code, patch_description).await;
+        self.evaluate_panel(skill_name, reviews)
+    }
+
+    /// Combine independent verdicts under the [`QuorumPolicy`].
+    ///
+    /// The combined verdict carries every reviewer's findings; the decision counts reviewers,
+    /// not findings, so one reviewer repeating a High finding is still one vote.
+    pub fn evaluate_panel(&self, skill_name: &str, reviews: Vec<ReviewerVerdict>) -> ConsensusResult {
+        let responded: Vec<&ReviewerVerdict> = reviews.iter().filter(|r| r.verdict.is_some()).collect();
+        let voters = |pred: fn(&ReviewerVerdict) -> bool| -> Vec<String> {
+            responded.iter().filter(|r| pred(r)).map(|r| r.reviewer.clone()).collect()
+        };
+        let critical = voters(ReviewerVerdict::votes_critical);
+        let blocking = voters(ReviewerVerdict::votes_blocking);
+        let static_veto = responded.iter().find(|r| r.is_static() && r.votes_blocking());
+
+        let mut disagreements: Vec<String> = reviews
+            .iter()
+            .filter(|r| r.verdict.is_none())
+            .map(|r| format!("{} abstained (no verdict)", r.reviewer))
+            .collect();
+        for (label, votes) in [("Critical", &critical), ("High", &blocking)] {
+            if !votes.is_empty() && votes.len() < responded.len() {
+                let others: Vec<&str> = responded
+                    .iter()
+                    .filter(|r| !votes.contains(&r.reviewer))
+                    .map(|r| r.reviewer.as_str())
+                    .collect();
+                disagreements.push(format!(
+                    "{}: flagged by {}; not by {}",
+                    label,
+                    votes.join(", "),
+                    others.join(", ")
+                ));
+            }
+        }
+
+        let mut findings = Vec::new();
+        let mut memory_warning = None;
+        for verdict in responded.iter().filter_map(|r| r.verdict.as_ref()) {
+            findings.extend(verdict.findings.iter().cloned());
+            memory_warning = memory_warning.or_else(|| verdict.memory_warning.clone());
+        }
+        let reviewer_model = responded
+            .iter()
+            .map(|r| r.reviewer.as_str())
+            .collect::<Vec<_>>()
+            .join("+");
+        let mut verdict = SecurityVerdict::failed(&reviewer_model, findings, "");
+        verdict.memory_warning = memory_warning;
+        verdict.summary = format!(
+            "Panel review of '{}': {}/{} reviewer(s) responded; Critical votes {}, High votes {}.",
+            skill_name,
+            responded.len(),
+            reviews.len(),
+            critical.len(),
+            blocking.len()
+        );
+
+        let quorum = &self.quorum;
+        let needed = |votes: usize| votes.min(responded.len()).max(1);
+        let (approved, mark_lethal, reason) = if responded.len() < quorum.min_reviewers {
+            (
+                false,
+                false,
+                format!(
+                    "Review quorum not reached: {} of {} required reviewer(s) responded — patch rejected.",
+                    responded.len(),
+                    quorum.min_reviewers
+                ),
+            )
+        } else if critical.len() >= needed(quorum.critical_votes) {
+            (
+                false,
+                true,
+                format!(
+                    "CRITICAL vulnerability confirmed by {} — patch auto-rejected as Lethal Mutation. {}",
+                    critical.join(", "),
+                    verdict.summary
+                ),
+            )
+        } else if self.auto_reject_high && blocking.len() >= needed(quorum.high_votes) {
+            (
+                false,
+                false,
+                format!(
+                    "HIGH severity vulnerability confirmed by {} — patch auto-rejected. {}",
+                    blocking.join(", "),
+                    verdict.summary
+                ),
+            )
+        } else if let Some(veto) = static_veto.filter(|r| self.auto_reject_high || r.votes_critical()) {
+            let lethal = veto.votes_critical();
+            (
+                false,
+                lethal,
+                format!(
+                    "{} finding from {} vetoes the panel — patch auto-rejected{}. {}",
+                    if lethal { "CRITICAL" } else { "HIGH severity" },
+                    veto.reviewer,
+                    if lethal { " as Lethal Mutation" } else { "" },
+                    verdict.summary
+                ),
+            )
+        } else if verdict.findings.is_empty() {
+            (
+                true,
+                false,
+                format!("✅ Passed Peer Review ({}: No vulnerabilities found).", reviewer_model),
+            )
+        } else {
+            (
+                true,
+                false,
+                format!(
+                    "⚠️ Passed Peer Review with {} finding(s) below quorum ({}). {}",
+                    verdict.findings.len(),
+                    reviewer_model,
+                    verdict.summary
+                ),
+            )
+        };
+        verdict.passed = approved;
+
+        info!(
+            target: "pagi::redteam",
+            skill = skill_name,
+            approved,
+            lethal = mark_lethal,
+            reviewers = responded.len(),
+            disagreements = disagreements.len(),
+            "Panel consensus reached"
+        );
+
+        ConsensusResult {
+            approved,
+            reason,
+            mark_lethal,
+            verdict,
+            reviews,
+            disagreements,
+        }
     }
 
     /// Evaluate a security verdict and return a consensus decision.
@@ -811,6 +1062,8 @@ impl ConsensusGate {
                 ),
                 mark_lethal: true,
                 verdict,
+                reviews: Vec::new(),
+                disagreements: Vec::new(),
             };
         }
 
@@ -824,6 +1077,8 @@ impl ConsensusGate {
                 ),
                 mark_lethal: false,
                 verdict,
+                reviews: Vec::new(),
+                disagreements: Vec::new(),
             };
         }
 
@@ -845,6 +1100,8 @@ impl ConsensusGate {
             },
             mark_lethal: false,
             verdict,
+            reviews: Vec::new(),
+            disagreements: Vec::new(),
         }
     }
 }
@@ -853,6 +1110,15 @@ impl ConsensusGate {
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// API key for the reviewer models, if configured.
+fn reviewer_api_key() -> Option<String> {
+    std::env::var("PAGI_REDTEAM_API_KEY")
+        .or_else(|_| std::env::var("PAGI_LLM_API_KEY"))
+        .or_else(|_| std::env::var("OPENROUTER_API_KEY"))
+        .ok()
+        .filter(|k| !k.trim().is_empty())
+}
+
 fn parse_severity(s: &str) -> Severity {
     match s.to_lowercase().as_str() {
         "critical" => Severity::Critical,
@@ -918,6 +1184,7 @@ mod tests {
             description: "User input passed to shell".to_string(),
             affected_region: Some("Line 42".to_string()),
             remediation: Some("Sanitize input".to_string()),
+            span: None,
         }];
         let v = SecurityVerdict::failed("test-model", findings, "Critical issue found");
         assert!(!v.passed);
@@ -935,6 +1202,7 @@ mod tests {
             description: "Unchecked buffer access".to_string(),
             affected_region: None,
             remediation: None,
+            span: None,
         }];
         let verdict = SecurityVerdict::failed("llama-3", findings, "Critical");
         let result = gate.evaluate(verdict);
@@ -951,6 +1219,7 @@ mod tests {
             description: "API key in source".to_string(),
             affected_region: None,
             remediation: None,
+            span: None,
         }];
         let verdict = SecurityVerdict::failed("gpt-4o", findings, "High");
         let result = gate.evaluate(verdict);
@@ -967,6 +1236,7 @@ mod tests {
             description: "Too many unwraps".to_string(),
             affected_region: None,
             remediation: None,
+            span: None,
         }];
         let verdict = SecurityVerdict {
             overall_severity: Severity::Medium,
@@ -984,7 +1254,7 @@ mod tests {
     }
 
     #[test]
-    fn test_heuristic_detects_unsafe() {
+    fn test_static_detects_unsafe() {
         let analyzer = RedTeamAnalyzer::new(RedTeamConfig {
             reviewer_model: "test".to_string(),
             api_url: "http://localhost".to_string(),
@@ -1002,13 +1272,13 @@ mod tests {
             }
         "#;
 
-        let verdict = analyzer.review_heuristic("test_skill", code);
+        let verdict = analyzer.review_static("test_skill", code);
         assert!(!verdict.passed);
         assert!(verdict.findings.iter().any(|f| f.category == "Unsafe Code"));
     }
 
     #[test]
-    fn test_heuristic_clean_code() {
+    fn test_static_clean_code() {
         let analyzer = RedTeamAnalyzer::new(RedTeamConfig {
             reviewer_model: "test".to_string(),
             api_url: "http://localhost".to_string(),
@@ -1023,11 +1293,34 @@ mod tests {
             }
         "#;
 
-        let verdict = analyzer.review_heuristic("test_skill", code);
+        let verdict = analyzer.review_static("test_skill", code);
         assert!(verdict.passed);
         assert!(verdict.findings.is_empty());
     }
 
+    #[test]
+    fn test_static_gate_rejects_aliased_spawn_offline() {
+        let analyzer = RedTeamAnalyzer::new(RedTeamConfig::default());
+        let code = r#"
+            use std::process::Command as Tool;
+            pub fn run(arg: &str) {
+                let _ = Tool::new("sh").arg("-c").arg(arg).status();
+            }
+        "#;
+
+        let verdict = analyzer.review_static("test_skill", code);
+        let spawn = verdict
+            .findings
+            .iter()
+            .find(|f| f.category == "Process Spawning (CWE-78)")
+            .expect("aliased Command must be flagged");
+        assert_eq!(spawn.span.map(|s| s.line), Some(4));
+
+        let result = ConsensusGate::default().evaluate(verdict);
+        assert!(!result.approved);
+        assert_eq!(result.verdict.reviewer_model, AST_ANALYZER_MODEL);
+    }
+
     #[test]
     fn test_parse_review_response_valid() {
         let analyzer = RedTeamAnalyzer::new(RedTeamConfig::default());
@@ -1083,4 +1376,130 @@ mod tests {
         assert!(prompt.contains("Command Injection"));
         assert!(prompt.contains("test_skill"));
     }
+
+    /// Returns a fixed verdict (or abstains) without any network access.
+    struct StubReviewer {
+        name: &'static str,
+        severity: Option<Severity>,
+    }
+
+    #[async_trait]
+    impl SecurityReviewer for StubReviewer {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        async fn review(&self, _skill: &str, _code: &str, _desc: &str) -> Option<SecurityVerdict> {
+            let severity = self.severity?;
+            let finding = SecurityFinding {
+                category: format!("{} finding", severity),
+                severity,
+                description: "stub".to_string(),
+                affected_region: None,
+                remediation: None,
+                span: None,
+            };
+            Some(SecurityVerdict::failed(self.name, vec![finding], "stub"))
+        }
+    }
+
+    fn panel(severities: &[(&'static str, Option<Severity>)]) -> ReviewPanel {
+        ReviewPanel::new(
+            severities
+                .iter()
+                .map(|&(name, severity)| Arc::new(StubReviewer { name, severity }) as Arc<dyn SecurityReviewer>)
+                .collect(),
+        )
+    }
+
+    async fn run(severities: &[(&'static str, Option<Severity>)]) -> ConsensusResult {
+        ConsensusGate::new(true)
+            .review(&panel(severities), "test_skill", "fn main() {}", "Test patch")
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_panel_single_high_is_outvoted_and_recorded() {
+        let result = run(&[
+            ("model-a", Some(Severity::High)),
+            ("model-b", Some(Severity::Low)),
+            ("ast", Some(Severity::Info)),
+        ])
+        .await;
+        assert!(result.approved);
+        assert_eq!(result.reviews.len(), 3);
+        assert_eq!(result.verdict.findings.len(), 3);
+        assert_eq!(result.disagreements, vec!["High: flagged by model-a; not by model-b, ast"]);
+
+        let audit = result.audit_summary();
+        assert!(audit.passed);
+        assert_eq!(audit.reviews.len(), 3);
+        assert_eq!(audit.disagreements.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_panel_high_needs_two_votes() {
+        let result = run(&[
+            ("model-a", Some(Severity::High)),
+            ("model-b", Some(Severity::Low)),
+            ("ast", Some(Severity::High)),
+        ])
+        .await;
+        assert!(!result.approved);
+        assert!(!result.mark_lethal);
+        assert!(result.reason.contains("model-a, ast"));
+    }
+
+    #[tokio::test]
+    async fn test_panel_any_critical_is_lethal() {
+        let result = run(&[
+            ("model-a", Some(Severity::Info)),
+            ("model-b", Some(Severity::Critical)),
+            ("ast", Some(Severity::Low)),
+        ])
+        .await;
+        assert!(!result.approved);
+        assert!(result.mark_lethal);
+    }
+
+    #[tokio::test]
+    async fn test_panel_caps_votes_at_responding_reviewers() {
+        // Offline: only the AST analyzer answers, so its High alone rejects.
+        let result = run(&[("model-a", None), ("ast", Some(Severity::High))]).await;
+        assert!(!result.approved);
+        assert_eq!(result.disagreements, vec!["model-a abstained (no verdict)"]);
+
+        // Nobody answers: fail closed.
+        let result = run(&[("model-a", None)]).await;
+        assert!(!result.approved);
+        assert!(result.reason.contains("quorum not reached"));
+    }
+
+    #[tokio::test]
+    async fn test_panel_ast_finding_vetoes_approving_model() {
+        // One model and the real AST analyzer: the model's clean verdict cannot outvote the
+        // analyzer's High finding, even though High normally needs two votes.
+        let model: Arc<dyn SecurityReviewer> = Arc::new(StubReviewer {
+            name: "model-a",
+            severity: Some(Severity::Low),
+        });
+        let panel = ReviewPanel::new(vec![model, Arc::new(AstAnalyzer)]);
+        let code = r#"
+            pub fn run(arg: &str) {
+                let _ = std::process::Command::new("sh").arg("-c").arg(arg).status();
+            }
+        "#;
+        let gate = ConsensusGate::new(true).with_quorum(QuorumPolicy::default());
+        let result = gate.review(&panel, "test_skill", code, "Test patch").await;
+        assert!(!result.approved);
+        assert!(!result.mark_lethal);
+        assert!(result.reason.contains(AST_ANALYZER_MODEL));
+        assert!(result.reason.contains("vetoes"));
+
+        // Clean code passes the same panel.
+        let result = gate
+            .review(&panel, "test_skill", "pub fn add(a: i32) -> i32 { a }", "Test patch")
+            .await;
+        assert!(result.approved);
+    }
 }
@@ -1,25 +1,41 @@
 //! Evolutionary Versioning & Rollback Manager
 //!
-//! Manages versioned skill patches with atomic symlink swapping and genetic memory.
+//! Manages versioned skill patches with an atomic active-versions manifest and genetic memory.
 //!
 //! ## Versioned Storage
 //!
 //! Patches are stored as `{skill_name}_v{timestamp}.rs` in the patches directory.
-//! A symlink `current_{skill_name}.rs` always points to the active version.
-//! Compiled artifacts follow the same scheme: `{skill_name}_v{timestamp}.dll/.so`
-//! with `current_{skill_name}.dll/.so` as the active symlink.
+//! The active version of each skill (and its compiled `.dll/.so`) is recorded in
+//! `active_versions.json` next to them (see [`crate::active_versions`]); legacy
+//! `current_*` symlinks are migrated into it on startup.
 //!
 //! ## Atomic Rollback
 //!
-//! Rollback is performed by atomically swapping the `current_*` symlink to point
-//! to a previous version, then reloading the `.dll/.so` via `SkillLoader`.
-//! On Windows, symlinks are emulated via file copy (atomic rename).
+//! Rollback is a compare-and-swap on the manifest from the active version to a previous
+//! one, followed by reloading its `.dll/.so` via `SkillLoader`. Concurrent activations of
+//! the same skill fail with a conflict instead of racing.
 //!
 //! ## Genetic Memory
 //!
 //! Every applied patch is hashed (SHA-256) and stored as "DNA" in the patch registry.
 //! If the agent suggests a fix whose hash matches a previously rejected or rolled-back
 //! patch, it is self-censored as an "Evolutionary Dead-End."
+//!
+//! ## Canary Rollout
+//!
+//! [`RollbackManager::stage_versioned_patch`] stores a version without activating it, and
+//! [`RollbackManager::start_canary`] loads its artifact as a canary next to the active version
+//! (see [`crate::canary`]). When the canary is promoted the manifest moves to it; when
+//! it breaches the policy it is marked `RolledBack`, and recorded as a dead-end only when the
+//! candidate itself failed (a diverging candidate may be an intended change). Either way the
+//! final [`CanaryReport`] is kept in the version's `PatchPerformanceDelta`.
+//!
+//! ## Lineage
+//!
+//! Each version records the version it was derived from and what produced it
+//! ([`PatchOrigin`]), persisted in a `.lineage.json` sidecar next to its source and restored on
+//! startup. [`RollbackManager::lineage`] returns the skill's genealogy as a DAG (see
+//! [`crate::lineage`]).
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -29,8 +45,14 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+use crate::active_versions::{self, ActiveEntry, ActiveManifest, ActiveVersions, ConsistencyIssue};
+use crate::build_env::ToolchainFingerprint;
+use crate::canary::{CanaryCallback, CanaryOutcome, CanaryPolicy, CanaryReport};
+use crate::lineage::{self, LineageGraph, LineageNode, PatchOrigin};
 use crate::loader::SkillLoader;
+use crate::red_team::ReviewerVerdict;
 use crate::skill::SkillError;
+use crate::wasm::WasmCapabilities;
 
 // ---------------------------------------------------------------------------
 // Patch Version Record
@@ -57,6 +79,13 @@ pub struct PatchVersion {
     pub performance_delta: Option<PatchPerformanceDelta>,
     /// Human-readable description of what this patch fixes.
     pub description: String,
+    /// Toolchain and pinned-dependency fingerprint of the compiled artifact, so the patch can
+    /// be rebuilt byte-identically later. `None` until an artifact is registered.
+    #[serde(default)]
+    pub toolchain: Option<ToolchainFingerprint>,
+    /// Parent version, triggering failure, model and prompt that produced this patch.
+    #[serde(default)]
+    pub origin: PatchOrigin,
 }
 
 /// Status of a patch version in the evolutionary timeline.
@@ -67,6 +96,8 @@ pub enum PatchStatus {
     Pending,
     /// Patch passed validation and is applied.
     Applied,
+    /// Patch is staged and running as a canary next to the active version.
+    Canary,
     /// Patch was rolled back (reverted).
     RolledBack,
     /// Patch was rejected (compilation failure, smoke test crash, or operator decline).
@@ -90,6 +121,10 @@ pub struct PatchPerformanceDelta {
     /// `None` if peer review was not performed.
     #[serde(default)]
     pub security_audit: Option<SecurityAuditSummary>,
+    /// Shadow/canary comparison against the previously active version.
+    /// `None` if the patch was activated directly.
+    #[serde(default)]
+    pub canary: Option<CanaryReport>,
 }
 
 /// Summary of the Red-Team security audit, embedded in the PerformanceDelta
@@ -108,6 +143,12 @@ pub struct SecurityAuditSummary {
     pub summary: String,
     /// Memory usage warning from the reviewer (if any).
     pub memory_warning: Option<String>,
+    /// Each panel reviewer's verdict and findings (empty for single-reviewer audits).
+    #[serde(default)]
+    pub reviews: Vec<ReviewerVerdict>,
+    /// Where panel reviewers split on a severity vote or abstained.
+    #[serde(default)]
+    pub disagreements: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -136,6 +177,9 @@ pub struct DeadEndRecord {
     pub timestamp_ms: i64,
     /// How many times this hash has been suggested (for tracking recurrence).
     pub occurrence_count: u32,
+    /// Smallest payload found to crash the patch (shrunk from a property-based smoke test).
+    #[serde(default)]
+    pub minimal_failing_payload: Option<serde_json::Value>,
 }
 
 impl GeneticMemory {
@@ -163,12 +207,20 @@ impl GeneticMemory {
                 reason: reason.to_string(),
                 timestamp_ms: now_epoch_ms(),
                 occurrence_count: 0,
+                minimal_failing_payload: None,
             });
         entry.occurrence_count += 1;
         entry.reason = reason.to_string();
         entry.timestamp_ms = now_epoch_ms();
     }
 
+    /// Attach the minimal payload that reproduces a dead-end's failure.
+    pub fn attach_failing_payload(&mut self, code_hash: &str, payload: serde_json::Value) {
+        if let Some(entry) = self.dead_ends.get_mut(code_hash) {
+            entry.minimal_failing_payload = Some(payload);
+        }
+    }
+
     /// Check if a code hash is a known dead-end. Returns the record if so.
     pub fn is_dead_end(&self, code_hash: &str) -> Option<&DeadEndRecord> {
         self.dead_ends.get(code_hash)
@@ -221,29 +273,42 @@ impl Default for RollbackConfig {
     }
 }
 
-/// The Rollback Manager: handles versioned patch storage, atomic symlink swapping,
+/// The Rollback Manager: handles versioned patch storage, compare-and-swap activation,
 /// and genetic memory for evolutionary dead-end detection.
 ///
 /// Thread-safe via internal `RwLock`.
 pub struct RollbackManager {
     config: RollbackConfig,
     /// All known patch versions, keyed by skill_name → Vec<PatchVersion> (ordered by timestamp).
-    versions: RwLock<HashMap<String, Vec<PatchVersion>>>,
+    /// Shared with canary completion callbacks, which run on the calling thread of `execute`.
+    versions: Arc<RwLock<HashMap<String, Vec<PatchVersion>>>>,
     /// Genetic memory: tracks code hashes for dead-end detection.
-    genetic_memory: RwLock<GeneticMemory>,
+    genetic_memory: Arc<RwLock<GeneticMemory>>,
     /// Reference to the SkillLoader for hot-reloading after rollback.
     skill_loader: Arc<SkillLoader>,
+    /// Which version of each skill is active; the source of truth for `is_active`.
+    manifest: Arc<ActiveManifest>,
 }
 
 impl RollbackManager {
     /// Create a new RollbackManager with the given configuration and skill loader.
     pub fn new(config: RollbackConfig, skill_loader: Arc<SkillLoader>) -> Self {
+        let (manifest, recovery) = ActiveManifest::load(&config.patches_dir);
+        if !recovery.is_clean() {
+            warn!(
+                target: "pagi::rollback",
+                report = ?recovery,
+                "Recovered active-versions manifest on startup"
+            );
+        }
         let manager = Self {
             config,
-            versions: RwLock::new(HashMap::new()),
-            genetic_memory: RwLock::new(GeneticMemory::new()),
+            versions: Arc::new(RwLock::new(HashMap::new())),
+            genetic_memory: Arc::new(RwLock::new(GeneticMemory::new())),
             skill_loader,
+            manifest: Arc::new(manifest),
         };
+        manager.migrate_legacy_artifact_links();
 
         // Scan existing patches directory to rebuild version history.
         if let Err(e) = manager.scan_existing_patches() {
@@ -254,6 +319,15 @@ impl RollbackManager {
             );
         }
 
+        let issues = manager.check_consistency();
+        if !issues.is_empty() {
+            warn!(
+                target: "pagi::rollback",
+                issues = ?issues,
+                "Active-versions manifest is out of sync with the patches and artifacts directories"
+            );
+        }
+
         manager
     }
 
@@ -272,14 +346,44 @@ impl RollbackManager {
     /// 1. Computes the SHA-256 hash of the code.
     /// 2. Checks genetic memory for dead-ends.
     /// 3. Writes `{skill_name}_v{timestamp}.rs` to the patches directory.
-    /// 4. Updates the `current_{skill_name}.rs` symlink.
+    /// 4. Activates it in the manifest (compare-and-swap against the active version).
     /// 5. Registers the DNA in genetic memory.
+    ///
+    /// If `origin` names no parent, the currently active version is recorded as the parent.
     pub fn save_versioned_patch(
         &self,
         skill_name: &str,
         code: &str,
         description: &str,
         performance_delta: Option<PatchPerformanceDelta>,
+        origin: PatchOrigin,
+    ) -> Result<PatchVersion, SkillError> {
+        self.save_patch(skill_name, code, description, performance_delta, origin, true)
+    }
+
+    /// Save a new versioned patch without activating it, for a canary rollout.
+    ///
+    /// The version gets status `Canary`; the active version and the manifest are left
+    /// untouched until [`Self::start_canary`] promotes it.
+    pub fn stage_versioned_patch(
+        &self,
+        skill_name: &str,
+        code: &str,
+        description: &str,
+        performance_delta: Option<PatchPerformanceDelta>,
+        origin: PatchOrigin,
+    ) -> Result<PatchVersion, SkillError> {
+        self.save_patch(skill_name, code, description, performance_delta, origin, false)
+    }
+
+    fn save_patch(
+        &self,
+        skill_name: &str,
+        code: &str,
+        description: &str,
+        performance_delta: Option<PatchPerformanceDelta>,
+        mut origin: PatchOrigin,
+        activate: bool,
     ) -> Result<PatchVersion, SkillError> {
         let code_hash = compute_sha256(code);
 
@@ -305,7 +409,15 @@ impl RollbackManager {
         std::fs::create_dir_all(&self.config.patches_dir)
             .map_err(|e| SkillError::Load(format!("Failed to create patches dir: {}", e)))?;
 
-        let timestamp = now_epoch_ms();
+        // Timestamps identify versions (and lineage parents), so keep them strictly increasing
+        // even when two patches land in the same millisecond.
+        let newest = self
+            .get_versions(skill_name)
+            .iter()
+            .map(|v| v.timestamp_ms)
+            .max()
+            .unwrap_or(0);
+        let timestamp = now_epoch_ms().max(newest + 1);
         let sanitized = sanitize_name(skill_name);
         let filename = format!("{}_v{}.rs", sanitized, timestamp);
         let source_path = self.config.patches_dir.join(&filename);
@@ -314,12 +426,18 @@ impl RollbackManager {
         std::fs::write(&source_path, code)
             .map_err(|e| SkillError::Load(format!("Failed to write patch: {}", e)))?;
 
-        // Update the current symlink.
-        let current_link = self
-            .config
-            .patches_dir
-            .join(format!("current_{}.rs", sanitized));
-        atomic_symlink_update(&source_path, &current_link)?;
+        let previous = self.manifest.get(&sanitized).map(|e| e.timestamp_ms);
+        if activate {
+            let entry = ActiveEntry::new(timestamp, &code_hash, &source_path);
+            if let Err(e) = self.manifest.compare_and_swap(&sanitized, previous, Some(entry)) {
+                let _ = std::fs::remove_file(&source_path);
+                return Err(SkillError::Load(format!("Failed to activate patch: {}", e)));
+            }
+        }
+
+        if origin.parent_timestamp_ms.is_none() {
+            origin.parent_timestamp_ms = previous;
+        }
 
         let version = PatchVersion {
             skill_name: skill_name.to_string(),
@@ -327,11 +445,18 @@ impl RollbackManager {
             code_hash: code_hash.clone(),
             source_path: source_path.clone(),
             artifact_path: None,
-            is_active: true,
-            status: PatchStatus::Applied,
+            is_active: activate,
+            status: if activate {
+                PatchStatus::Applied
+            } else {
+                PatchStatus::Canary
+            },
             performance_delta,
             description: description.to_string(),
+            toolchain: None,
+            origin,
         };
+        persist_lineage(&version);
 
         // Update version registry.
         {
@@ -342,8 +467,10 @@ impl RollbackManager {
             let skill_versions = versions.entry(skill_name.to_string()).or_default();
 
             // Deactivate all previous versions.
-            for v in skill_versions.iter_mut() {
-                v.is_active = false;
+            if activate {
+                for v in skill_versions.iter_mut() {
+                    v.is_active = false;
+                }
             }
 
             skill_versions.push(version.clone());
@@ -359,8 +486,9 @@ impl RollbackManager {
                     if removed >= excess || v.is_active {
                         true
                     } else {
-                        // Clean up the source file.
+                        // Clean up the source file and its lineage sidecar.
                         let _ = std::fs::remove_file(&v.source_path);
+                        let _ = std::fs::remove_file(lineage::lineage_path(&v.source_path));
                         if let Some(ref artifact) = v.artifact_path {
                             let _ = std::fs::remove_file(artifact);
                         }
@@ -391,12 +519,14 @@ impl RollbackManager {
         Ok(version)
     }
 
-    /// Register a compiled artifact for a patch version.
+    /// Register a compiled artifact for a patch version, with the toolchain fingerprint of the
+    /// build that produced it (from [`Compiler::compile_reproducible`](crate::Compiler::compile_reproducible)).
     pub fn register_artifact(
         &self,
         skill_name: &str,
         timestamp_ms: i64,
         artifact_path: PathBuf,
+        toolchain: Option<ToolchainFingerprint>,
     ) -> Result<(), SkillError> {
         let mut versions = self
             .versions
@@ -408,29 +538,20 @@ impl RollbackManager {
                 .iter_mut()
                 .find(|v| v.timestamp_ms == timestamp_ms)
             {
-                // Update the current artifact symlink.
-                let sanitized = sanitize_name(skill_name);
-                let ext = if cfg!(target_os = "windows") {
-                    "dll"
-                } else {
-                    "so"
-                };
-                let current_artifact_link = self
-                    .config
-                    .artifacts_dir
-                    .join(format!("current_{}.{}", sanitized, ext));
-
-                std::fs::create_dir_all(&self.config.artifacts_dir)
-                    .map_err(|e| SkillError::Load(format!("Failed to create artifacts dir: {}", e)))?;
-
-                atomic_symlink_update(&artifact_path, &current_artifact_link)?;
+                // Record the active artifact (staged canary versions wait for promotion).
+                if version.is_active {
+                    self.manifest
+                        .set_artifact(&sanitize_name(skill_name), timestamp_ms, artifact_path.clone())
+                        .map_err(|e| SkillError::Load(format!("Failed to record artifact: {}", e)))?;
+                }
                 version.artifact_path = Some(artifact_path);
+                version.toolchain = toolchain;
 
                 info!(
                     target: "pagi::rollback",
                     skill = skill_name,
                     timestamp = timestamp_ms,
-                    "Artifact registered and symlink updated"
+                    "Artifact registered"
                 );
                 return Ok(());
             }
@@ -450,7 +571,7 @@ impl RollbackManager {
     ///
     /// This:
     /// 1. Finds the previous version for the skill.
-    /// 2. Atomically swaps the `current_*` symlink to point to it.
+    /// 2. Swaps the manifest from the active version to it (compare-and-swap).
     /// 3. Reloads the `.dll/.so` via SkillLoader (if artifact exists).
     /// 4. Marks the current version as "RolledBack" and the target as "Applied".
     /// 5. Records the rolled-back version's hash as a dead-end in genetic memory.
@@ -462,6 +583,10 @@ impl RollbackManager {
         target_timestamp: Option<i64>,
         reason: &str,
     ) -> Result<PatchVersion, SkillError> {
+        // A running canary is rolled back first; its callback takes the version lock.
+        self.skill_loader
+            .abort_canary(skill_name, &format!("Superseded by rollback: {}", reason));
+
         let mut versions = self
             .versions
             .write()
@@ -519,36 +644,25 @@ impl RollbackManager {
         // Get the hash of the version being rolled back (for dead-end marking).
         let rolled_back_hash = skill_versions[active_idx].code_hash.clone();
 
+        activate_in_manifest(
+            &self.manifest,
+            &skill_versions[target_idx],
+            Some(skill_versions[active_idx].timestamp_ms),
+        )?;
+
         // Swap: deactivate current, activate target.
         skill_versions[active_idx].is_active = false;
         skill_versions[active_idx].status = PatchStatus::RolledBack;
         skill_versions[target_idx].is_active = true;
         skill_versions[target_idx].status = PatchStatus::Applied;
+        persist_lineage(&skill_versions[active_idx]);
+        persist_lineage(&skill_versions[target_idx]);
 
         let target_version = skill_versions[target_idx].clone();
 
-        // Update source symlink.
-        let sanitized = sanitize_name(skill_name);
-        let current_source_link = self
-            .config
-            .patches_dir
-            .join(format!("current_{}.rs", sanitized));
-        atomic_symlink_update(&target_version.source_path, &current_source_link)?;
-
-        // Update artifact symlink and reload if artifact exists.
+        // Reload the artifact if one exists.
         if let Some(ref artifact_path) = target_version.artifact_path {
             if artifact_path.exists() {
-                let ext = if cfg!(target_os = "windows") {
-                    "dll"
-                } else {
-                    "so"
-                };
-                let current_artifact_link = self
-                    .config
-                    .artifacts_dir
-                    .join(format!("current_{}.{}", sanitized, ext));
-                atomic_symlink_update(artifact_path, &current_artifact_link)?;
-
                 // Hot-reload the previous version's library.
                 if let Err(e) = self
                     .skill_loader
@@ -599,6 +713,178 @@ impl RollbackManager {
         Ok(target_version)
     }
 
+    // -----------------------------------------------------------------------
+    // Active Versions
+    // -----------------------------------------------------------------------
+
+    /// Activate a stored version if `expected_active` is still the active one (compare-and-swap),
+    /// hot-reloading its artifact if it has one. Unlike [`Self::rollback_skill`], the replaced
+    /// version is not marked as a dead-end.
+    pub fn activate_version(
+        &self,
+        skill_name: &str,
+        timestamp_ms: i64,
+        expected_active: Option<i64>,
+    ) -> Result<PatchVersion, SkillError> {
+        let mut versions = self
+            .versions
+            .write()
+            .map_err(|e| SkillError::Load(format!("Version lock: {}", e)))?;
+        let skill_versions = versions.get_mut(skill_name).ok_or_else(|| {
+            SkillError::Load(format!("No versions found for skill '{}'", skill_name))
+        })?;
+        let idx = skill_versions
+            .iter()
+            .position(|v| v.timestamp_ms == timestamp_ms)
+            .ok_or_else(|| {
+                SkillError::Load(format!(
+                    "No version found for skill '{}' at timestamp {}",
+                    skill_name, timestamp_ms
+                ))
+            })?;
+
+        activate_in_manifest(&self.manifest, &skill_versions[idx], expected_active)?;
+        for v in skill_versions.iter_mut() {
+            v.is_active = false;
+        }
+        let version = &mut skill_versions[idx];
+        version.is_active = true;
+        version.status = PatchStatus::Applied;
+        persist_lineage(version);
+        let version = version.clone();
+        drop(versions);
+
+        if let Some(ref artifact_path) = version.artifact_path {
+            if let Err(e) = self.skill_loader.load(artifact_path, skill_name.to_string()) {
+                warn!(
+                    target: "pagi::rollback",
+                    skill = skill_name,
+                    error = %e,
+                    "Failed to hot-reload activated artifact (skill may need manual reload)"
+                );
+            }
+        }
+        info!(
+            target: "pagi::rollback",
+            skill = skill_name,
+            timestamp = timestamp_ms,
+            "Version activated"
+        );
+        Ok(version)
+    }
+
+    /// The active-versions manifest.
+    pub fn manifest(&self) -> &ActiveManifest {
+        &self.manifest
+    }
+
+    /// Drift between the manifest and the patches/artifacts directories: missing or edited
+    /// sources, missing artifacts, orphaned libraries and leftover `current_*` links.
+    pub fn check_consistency(&self) -> Vec<ConsistencyIssue> {
+        let referenced = self
+            .versions
+            .read()
+            .map(|v| {
+                v.values()
+                    .flatten()
+                    .filter_map(|v| v.artifact_path.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        active_versions::check_consistency(
+            &self.manifest,
+            &self.config.artifacts_dir,
+            &referenced,
+            compute_sha256,
+        )
+    }
+
+    /// Moves legacy `current_{skill}.so/.dll` symlinks in the artifacts directory into the
+    /// manifest entry of the skill's active version.
+    fn migrate_legacy_artifact_links(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.config.artifacts_dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(skill) = path
+                .file_stem()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix("current_"))
+            else {
+                continue;
+            };
+            if let (Ok(target), Some(active)) = (std::fs::read_link(&path), self.manifest.get(skill)) {
+                if active.artifact.is_none() {
+                    let _ = self.manifest.set_artifact(skill, active.timestamp_ms, target);
+                }
+            }
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Canary Rollout
+    // -----------------------------------------------------------------------
+
+    /// Start a canary for a staged version (see [`Self::stage_versioned_patch`]) whose artifact
+    /// has been registered. The currently loaded version keeps serving while the candidate
+    /// shadows it; the outcome is applied to the version registry when the canary finishes.
+    pub fn start_canary(
+        &self,
+        skill_name: &str,
+        timestamp_ms: i64,
+        policy: CanaryPolicy,
+        caps: WasmCapabilities,
+    ) -> Result<(), SkillError> {
+        let artifact_path = self
+            .get_versions(skill_name)
+            .into_iter()
+            .find(|v| v.timestamp_ms == timestamp_ms && v.status == PatchStatus::Canary)
+            .and_then(|v| v.artifact_path)
+            .ok_or_else(|| {
+                SkillError::Load(format!(
+                    "No staged canary artifact for skill '{}' at timestamp {}",
+                    skill_name, timestamp_ms
+                ))
+            })?;
+
+        let manifest = Arc::clone(&self.manifest);
+        let versions = Arc::clone(&self.versions);
+        let genetic_memory = Arc::clone(&self.genetic_memory);
+        let skill = skill_name.to_string();
+        let on_finish: CanaryCallback = Arc::new(move |report: &CanaryReport| {
+            if let Err(e) = apply_canary_outcome(&manifest, &versions, &genetic_memory, &skill, timestamp_ms, report) {
+                warn!(
+                    target: "pagi::rollback",
+                    skill = %skill,
+                    error = %e,
+                    "Failed to record canary outcome"
+                );
+            }
+        });
+
+        self.skill_loader
+            .start_canary(&artifact_path, skill_name, policy, caps, Some(on_finish))?;
+        info!(
+            target: "pagi::rollback",
+            skill = skill_name,
+            timestamp = timestamp_ms,
+            "Canary started"
+        );
+        Ok(())
+    }
+
+    /// Current state of the canary running for `skill_name`, if any.
+    pub fn canary_report(&self, skill_name: &str) -> Option<CanaryReport> {
+        self.skill_loader.canary_report(skill_name)
+    }
+
+    /// Roll back the running canary for `skill_name`; the active version keeps serving.
+    pub fn abort_canary(&self, skill_name: &str, reason: &str) -> Option<CanaryReport> {
+        self.skill_loader.abort_canary(skill_name, reason)
+    }
+
     // -----------------------------------------------------------------------
     // Genetic Memory: Dead-End Detection
     // -----------------------------------------------------------------------
@@ -634,6 +920,23 @@ impl RollbackManager {
         Ok(())
     }
 
+    /// Mark a code hash as a dead-end together with the minimal payload that crashes it.
+    pub fn mark_dead_end_with_payload(
+        &self,
+        code: &str,
+        skill_name: &str,
+        reason: &str,
+        minimal_failing_payload: serde_json::Value,
+    ) -> Result<(), SkillError> {
+        self.mark_dead_end(code, skill_name, reason)?;
+        let mut gm = self
+            .genetic_memory
+            .write()
+            .map_err(|e| SkillError::Load(format!("Genetic memory lock: {}", e)))?;
+        gm.attach_failing_payload(&compute_sha256(code), minimal_failing_payload);
+        Ok(())
+    }
+
     /// Mark a patch version as rejected (updates status and genetic memory).
     pub fn mark_rejected(
         &self,
@@ -649,12 +952,23 @@ impl RollbackManager {
             if let Some(skill_versions) = versions.get_mut(skill_name) {
                 for v in skill_versions.iter_mut() {
                     if v.code_hash == hash {
+                        if v.is_active {
+                            if let Err(e) = self.manifest.compare_and_swap(&sanitize_name(skill_name), Some(v.timestamp_ms), None) {
+                                warn!(
+                                    target: "pagi::rollback",
+                                    skill = skill_name,
+                                    error = %e,
+                                    "Failed to deactivate rejected version"
+                                );
+                            }
+                        }
                         v.status = if is_hallucination {
                             PatchStatus::SyntacticHallucination
                         } else {
                             PatchStatus::Rejected
                         };
                         v.is_active = false;
+                        persist_lineage(v);
                     }
                 }
             }
@@ -709,6 +1023,52 @@ impl RollbackManager {
         all
     }
 
+    /// Lineage DAG of a skill's retained versions.
+    pub fn lineage(&self, skill_name: &str) -> LineageGraph {
+        LineageGraph::from_versions(skill_name, &self.get_versions(skill_name))
+    }
+
+    /// Ancestors of a version (default: the active one), nearest parent first.
+    pub fn ancestors(&self, skill_name: &str, timestamp_ms: Option<i64>) -> Vec<LineageNode> {
+        let graph = self.lineage(skill_name);
+        let Some(ts) = timestamp_ms.or_else(|| graph.active().map(|n| n.timestamp_ms)) else {
+            return Vec::new();
+        };
+        graph.ancestors(ts).into_iter().cloned().collect()
+    }
+
+    /// Attaches a Red-Team audit to the version of `skill_name` whose code matches `code`,
+    /// so reviewer verdicts show up in its lineage.
+    pub fn record_security_audit(
+        &self,
+        skill_name: &str,
+        code: &str,
+        audit: SecurityAuditSummary,
+    ) -> bool {
+        let code_hash = compute_sha256(code);
+        let Ok(mut versions) = self.versions.write() else {
+            return false;
+        };
+        let Some(v) = versions
+            .get_mut(skill_name)
+            .and_then(|vs| vs.iter_mut().rev().find(|v| v.code_hash == code_hash))
+        else {
+            return false;
+        };
+        v.performance_delta
+            .get_or_insert_with(|| PatchPerformanceDelta {
+                cpu: "N/A".to_string(),
+                mem: "N/A".to_string(),
+                compiled: true,
+                smoke_test_passed: true,
+                security_audit: None,
+                canary: None,
+            })
+            .security_audit = Some(audit);
+        persist_lineage(v);
+        true
+    }
+
     /// Get genetic memory statistics.
     pub fn genetic_memory_stats(&self) -> (usize, usize) {
         self.genetic_memory
@@ -733,89 +1093,21 @@ impl RollbackManager {
 
     /// Scan the patches directory for existing versioned files and rebuild the registry.
     fn scan_existing_patches(&self) -> Result<(), SkillError> {
-        let patches_dir = &self.config.patches_dir;
-        if !patches_dir.exists() {
-            debug!(
-                target: "pagi::rollback",
-                path = %patches_dir.display(),
-                "Patches directory does not exist yet — nothing to scan"
-            );
-            return Ok(());
-        }
-
-        let entries = std::fs::read_dir(patches_dir)
-            .map_err(|e| SkillError::Load(format!("Failed to read patches dir: {}", e)))?;
+        let scanned = read_patch_versions(&self.config.patches_dir, &self.manifest.snapshot())?;
 
         let mut versions = self
             .versions
             .write()
             .map_err(|e| SkillError::Load(format!("Version lock: {}", e)))?;
-
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-
-            let filename = match path.file_name().and_then(|n| n.to_str()) {
-                Some(n) => n.to_string(),
-                None => continue,
-            };
-
-            // Skip current_* symlinks/copies.
-            if filename.starts_with("current_") {
-                continue;
-            }
-
-            // Parse versioned filename: {skill_name}_v{timestamp}.rs
-            if let Some(parsed) = parse_versioned_filename(&filename) {
-                let (skill_name, timestamp) = parsed;
-
-                // Read the source to compute hash.
-                let code = std::fs::read_to_string(&path).unwrap_or_default();
-                let code_hash = compute_sha256(&code);
-
-                // Check if this is the current active version.
-                let sanitized = sanitize_name(&skill_name);
-                let current_link = patches_dir.join(format!("current_{}.rs", sanitized));
-                let is_active = if current_link.exists() {
-                    // Read the current link target and compare.
-                    match std::fs::read_to_string(&current_link) {
-                        Ok(current_code) => compute_sha256(&current_code) == code_hash,
-                        Err(_) => false,
-                    }
-                } else {
-                    false
-                };
-
-                let version = PatchVersion {
-                    skill_name: skill_name.clone(),
-                    timestamp_ms: timestamp,
-                    code_hash: code_hash.clone(),
-                    source_path: path.clone(),
-                    artifact_path: None,
-                    is_active,
-                    status: if is_active {
-                        PatchStatus::Applied
-                    } else {
-                        PatchStatus::Applied // Historical — we don't know the original status.
-                    },
-                    performance_delta: None,
-                    description: format!("Scanned from existing file: {}", filename),
-                };
-
-                let skill_versions = versions.entry(skill_name).or_default();
-                skill_versions.push(version);
-
-                // Register DNA.
-                if let Ok(mut gm) = self.genetic_memory.write() {
-                    gm.register_dna(&code_hash, &skill_versions.last().unwrap().skill_name);
+        for (skill_name, scanned_versions) in scanned {
+            // Register DNA.
+            if let Ok(mut gm) = self.genetic_memory.write() {
+                for version in &scanned_versions {
+                    gm.register_dna(&version.code_hash, &skill_name);
                 }
             }
-        }
-
-        // Sort each skill's versions by timestamp.
-        for skill_versions in versions.values_mut() {
+            let skill_versions = versions.entry(skill_name).or_default();
+            skill_versions.extend(scanned_versions);
             skill_versions.sort_by_key(|v| v.timestamp_ms);
         }
 
@@ -832,68 +1124,206 @@ impl RollbackManager {
     }
 }
 
-// ---------------------------------------------------------------------------
-// Atomic Symlink Update (cross-platform)
-// ---------------------------------------------------------------------------
-
-/// Atomically update a symlink (or file copy on Windows) to point to a new target.
-///
-/// On Unix: Uses `symlink` + `rename` for atomic swap.
-/// On Windows: Uses file copy + atomic rename (true symlinks require elevated privileges).
-fn atomic_symlink_update(target: &Path, link_path: &Path) -> Result<(), SkillError> {
-    // Ensure parent directory exists.
-    if let Some(parent) = link_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| SkillError::Load(format!("Failed to create parent dir: {}", e)))?;
+/// Rebuilds version history from the versioned sources and lineage sidecars in `patches_dir`,
+/// with `active` saying which version of each skill is active. Only reads; versions are sorted
+/// oldest first.
+pub(crate) fn read_patch_versions(
+    patches_dir: &Path,
+    active: &ActiveVersions,
+) -> Result<HashMap<String, Vec<PatchVersion>>, SkillError> {
+    let mut versions: HashMap<String, Vec<PatchVersion>> = HashMap::new();
+    if !patches_dir.exists() {
+        debug!(
+            target: "pagi::rollback",
+            path = %patches_dir.display(),
+            "Patches directory does not exist yet — nothing to scan"
+        );
+        return Ok(versions);
     }
 
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::symlink;
+    let entries = std::fs::read_dir(patches_dir)
+        .map_err(|e| SkillError::Load(format!("Failed to read patches dir: {}", e)))?;
 
-        // Create a temporary symlink, then atomically rename it.
-        let temp_link = link_path.with_extension("tmp_symlink");
-        let _ = std::fs::remove_file(&temp_link);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
 
-        symlink(target, &temp_link)
-            .map_err(|e| SkillError::Load(format!("Failed to create symlink: {}", e)))?;
+        let filename = match path.file_name().and_then(|n| n.to_str()) {
+            Some(n) => n.to_string(),
+            None => continue,
+        };
 
-        std::fs::rename(&temp_link, link_path)
-            .map_err(|e| SkillError::Load(format!("Failed to rename symlink: {}", e)))?;
+        // Parse versioned filename: {skill_name}_v{timestamp}.rs
+        if let Some(parsed) = parse_versioned_filename(&filename) {
+            let (skill_name, timestamp) = parsed;
+
+            // Read the source to compute hash.
+            let code = std::fs::read_to_string(&path).unwrap_or_default();
+            let code_hash = compute_sha256(&code);
+
+            // The manifest says which version is active and where its artifact is.
+            let active_entry = active
+                .skills
+                .get(&skill_name)
+                .filter(|e| e.timestamp_ms == timestamp);
+            let is_active = active_entry.is_some();
+
+            // Status, origin and metrics come from the lineage sidecar when present.
+            let record = lineage::read_lineage(&path);
+            let version = PatchVersion {
+                skill_name: skill_name.clone(),
+                timestamp_ms: timestamp,
+                code_hash,
+                source_path: path.clone(),
+                artifact_path: active_entry.and_then(|e| e.artifact.clone()),
+                is_active,
+                status: match record {
+                    Some(ref r) if !is_active => r.status.clone(),
+                    // Historical without a sidecar — we don't know the original status.
+                    _ => PatchStatus::Applied,
+                },
+                performance_delta: record.as_ref().and_then(|r| r.performance_delta.clone()),
+                description: record
+                    .as_ref()
+                    .map(|r| r.description.clone())
+                    .filter(|d| !d.is_empty())
+                    .unwrap_or_else(|| format!("Scanned from existing file: {}", filename)),
+                toolchain: None,
+                origin: record.map(|r| r.origin).unwrap_or_default(),
+            };
+
+            versions.entry(skill_name).or_default().push(version);
+        }
     }
 
-    #[cfg(windows)]
-    {
-        // On Windows, use file copy + atomic rename instead of symlinks
-        // (symlinks require elevated privileges or developer mode).
-        let temp_copy = link_path.with_extension("tmp_copy");
-        let _ = std::fs::remove_file(&temp_copy);
-
-        std::fs::copy(target, &temp_copy)
-            .map_err(|e| SkillError::Load(format!("Failed to copy file: {}", e)))?;
-
-        // Atomic rename: remove old, rename temp.
-        let _ = std::fs::remove_file(link_path);
-        std::fs::rename(&temp_copy, link_path)
-            .map_err(|e| SkillError::Load(format!("Failed to rename file: {}", e)))?;
+    // Sort each skill's versions by timestamp.
+    for skill_versions in versions.values_mut() {
+        skill_versions.sort_by_key(|v| v.timestamp_ms);
     }
+    Ok(versions)
+}
 
-    debug!(
-        target: "pagi::rollback",
-        target = %target.display(),
-        link = %link_path.display(),
-        "Symlink updated"
-    );
+/// Writes a version's lineage sidecar; failures only lose history, so they are logged.
+fn persist_lineage(version: &PatchVersion) {
+    if let Err(e) = lineage::persist_version(version) {
+        warn!(
+            target: "pagi::rollback",
+            skill = %version.skill_name,
+            error = %e,
+            "Failed to persist patch lineage"
+        );
+    }
+}
 
-    Ok(())
+/// Activates `version` in the manifest if `expected` is still the active version.
+fn activate_in_manifest(
+    manifest: &ActiveManifest,
+    version: &PatchVersion,
+    expected: Option<i64>,
+) -> Result<(), SkillError> {
+    let mut entry = ActiveEntry::new(version.timestamp_ms, &version.code_hash, &version.source_path);
+    entry.artifact = version.artifact_path.clone();
+    manifest
+        .compare_and_swap(&sanitize_name(&version.skill_name), expected, Some(entry))
+        .map(|_| ())
+        .map_err(|e| SkillError::Load(format!("Failed to activate version: {}", e)))
 }
 
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Records a finished canary on its version: promotion makes it the active version and moves
+/// the manifest; rollback marks it `RolledBack`, and a dead-end if the candidate failed.
+fn apply_canary_outcome(
+    manifest: &ActiveManifest,
+    versions: &RwLock<HashMap<String, Vec<PatchVersion>>>,
+    genetic_memory: &RwLock<GeneticMemory>,
+    skill_name: &str,
+    timestamp_ms: i64,
+    report: &CanaryReport,
+) -> Result<(), SkillError> {
+    let promoted = report.outcome == CanaryOutcome::Promoted;
+    let mut versions = versions
+        .write()
+        .map_err(|e| SkillError::Load(format!("Version lock: {}", e)))?;
+    let skill_versions = versions.get_mut(skill_name).ok_or_else(|| {
+        SkillError::Load(format!("No versions found for skill '{}'", skill_name))
+    })?;
+    let idx = skill_versions
+        .iter()
+        .position(|v| v.timestamp_ms == timestamp_ms)
+        .ok_or_else(|| {
+            SkillError::Load(format!(
+                "No version found for skill '{}' at timestamp {}",
+                skill_name, timestamp_ms
+            ))
+        })?;
+
+    {
+        let candidate = &mut skill_versions[idx];
+        let delta = candidate
+            .performance_delta
+            .get_or_insert_with(|| PatchPerformanceDelta {
+                cpu: "N/A".to_string(),
+                mem: "N/A".to_string(),
+                compiled: true,
+                smoke_test_passed: promoted,
+                security_audit: None,
+                canary: None,
+            });
+        delta.canary = Some(report.clone());
+    }
+
+    if promoted {
+        // The manifest, not the scanned history, says what is active (its source may be missing).
+        let previous = manifest.get(&sanitize_name(skill_name)).map(|e| e.timestamp_ms);
+        activate_in_manifest(manifest, &skill_versions[idx], previous)?;
+        for v in skill_versions.iter_mut() {
+            v.is_active = false;
+        }
+        let candidate = &mut skill_versions[idx];
+        candidate.is_active = true;
+        candidate.status = PatchStatus::Applied;
+        persist_lineage(candidate);
+        info!(
+            target: "pagi::rollback",
+            skill = skill_name,
+            timestamp = timestamp_ms,
+            calls = report.total.calls,
+            "Canary promoted; version is now active"
+        );
+        return Ok(());
+    }
+
+    let candidate = &mut skill_versions[idx];
+    candidate.status = PatchStatus::RolledBack;
+    persist_lineage(candidate);
+    let code_hash = candidate.code_hash.clone();
+    drop(versions);
+
+    let reason = report.reason.as_deref().unwrap_or("canary rolled back");
+    if report.candidate_failed {
+        genetic_memory
+            .write()
+            .map_err(|e| SkillError::Load(format!("Genetic memory lock: {}", e)))?
+            .mark_dead_end(&code_hash, skill_name, &format!("Canary rolled back: {}", reason));
+    }
+    warn!(
+        target: "pagi::rollback",
+        skill = skill_name,
+        timestamp = timestamp_ms,
+        reason = reason,
+        dead_end = report.candidate_failed,
+        "Canary rolled back; previous version stays active"
+    );
+    Ok(())
+}
+
 /// Compute SHA-256 hash of a string, returning a hex-encoded string.
-fn compute_sha256(input: &str) -> String {
+pub(crate) fn compute_sha256(input: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -921,7 +1351,7 @@ fn sanitize_name(name: &str) -> String {
 
 /// Parse a versioned filename like `{skill_name}_v{timestamp}.rs`.
 /// Returns `(skill_name, timestamp)` if successful.
-fn parse_versioned_filename(filename: &str) -> Option<(String, i64)> {
+pub(crate) fn parse_versioned_filename(filename: &str) -> Option<(String, i64)> {
     let stem = filename.strip_suffix(".rs")?;
 
     // Find the last `_v` followed by digits.
@@ -1001,6 +1431,11 @@ mod tests {
         let dead = gm.is_dead_end("hash1").unwrap();
         assert_eq!(dead.occurrence_count, 2);
         assert_eq!(dead.reason, "still broken");
+        assert!(dead.minimal_failing_payload.is_none());
+
+        gm.attach_failing_payload("hash1", serde_json::json!({ "location": "é" }));
+        let dead = gm.is_dead_end("hash1").unwrap();
+        assert_eq!(dead.minimal_failing_payload, Some(serde_json::json!({ "location": "é" })));
 
         assert!(gm.is_dead_end("hash_unknown").is_none());
     }
@@ -1019,14 +1454,14 @@ mod tests {
 
         // Save first version.
         let v1 = manager
-            .save_versioned_patch("test_skill", "fn v1() {}", "First version", None)
+            .save_versioned_patch("test_skill", "fn v1() {}", "First version", None, PatchOrigin::default())
             .unwrap();
         assert!(v1.is_active);
         assert_eq!(v1.status, PatchStatus::Applied);
 
         // Save second version.
         let v2 = manager
-            .save_versioned_patch("test_skill", "fn v2() {}", "Second version", None)
+            .save_versioned_patch("test_skill", "fn v2() {}", "Second version", None, PatchOrigin::default())
             .unwrap();
         assert!(v2.is_active);
 
@@ -1055,10 +1490,10 @@ mod tests {
 
         // Save two versions.
         let _v1 = manager
-            .save_versioned_patch("test_skill", "fn v1() {}", "First", None)
+            .save_versioned_patch("test_skill", "fn v1() {}", "First", None, PatchOrigin::default())
             .unwrap();
         let _v2 = manager
-            .save_versioned_patch("test_skill", "fn v2() {}", "Second", None)
+            .save_versioned_patch("test_skill", "fn v2() {}", "Second", None, PatchOrigin::default())
             .unwrap();
 
         // Rollback to v1.
@@ -1073,6 +1508,43 @@ mod tests {
         assert!(dead_end.unwrap().reason.contains("Rolled back"));
     }
 
+    #[test]
+    fn test_lineage_survives_rescan() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = RollbackConfig {
+            patches_dir: temp_dir.path().join("patches"),
+            artifacts_dir: temp_dir.path().join("artifacts"),
+            max_versions_per_skill: 10,
+        };
+
+        let manager = RollbackManager::new(config.clone(), Arc::new(SkillLoader::new()));
+        let v1 = manager
+            .save_versioned_patch("test_skill", "fn v1() {}", "First", None, PatchOrigin::default())
+            .unwrap();
+        let origin = PatchOrigin {
+            model: Some("model/a".to_string()),
+            ..PatchOrigin::default()
+        };
+        let v2 = manager
+            .save_versioned_patch("test_skill", "fn v2() {}", "Second", None, origin)
+            .unwrap();
+        assert_eq!(v1.origin.parent_timestamp_ms, None);
+        assert_eq!(v2.origin.parent_timestamp_ms, Some(v1.timestamp_ms));
+
+        // A fresh manager over the same directory restores the graph from the sidecars.
+        let rescanned = RollbackManager::new(config, Arc::new(SkillLoader::new()));
+        let graph = rescanned.lineage("test_skill");
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.node(v2.timestamp_ms).unwrap().description, "Second");
+        assert_eq!(
+            graph.node(v2.timestamp_ms).unwrap().origin.model.as_deref(),
+            Some("model/a")
+        );
+        let ancestors = rescanned.ancestors("test_skill", None);
+        assert_eq!(ancestors.len(), 1);
+        assert_eq!(ancestors[0].timestamp_ms, v1.timestamp_ms);
+    }
+
     #[test]
     fn test_dead_end_blocks_save() {
         let loader = Arc::new(SkillLoader::new());
@@ -1092,7 +1564,7 @@ mod tests {
 
         // Trying to save the same code should fail.
         let result =
-            manager.save_versioned_patch("test_skill", "fn broken() {}", "Retry", None);
+            manager.save_versioned_patch("test_skill", "fn broken() {}", "Retry", None, PatchOrigin::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("Evolutionary Dead-End"));
@@ -1111,13 +1583,13 @@ mod tests {
         let manager = RollbackManager::new(config, loader);
 
         manager
-            .save_versioned_patch("skill_a", "fn a1() {}", "A v1", None)
+            .save_versioned_patch("skill_a", "fn a1() {}", "A v1", None, PatchOrigin::default())
             .unwrap();
         manager
-            .save_versioned_patch("skill_b", "fn b1() {}", "B v1", None)
+            .save_versioned_patch("skill_b", "fn b1() {}", "B v1", None, PatchOrigin::default())
             .unwrap();
         manager
-            .save_versioned_patch("skill_a", "fn a2() {}", "A v2", None)
+            .save_versioned_patch("skill_a", "fn a2() {}", "A v2", None, PatchOrigin::default())
             .unwrap();
 
         let history = manager.get_full_history();
@@ -1125,4 +1597,123 @@ mod tests {
         // Newest first.
         assert!(history[0].timestamp_ms >= history[1].timestamp_ms);
     }
+
+    fn canary_report(outcome: CanaryOutcome, candidate_failed: bool) -> CanaryReport {
+        let reason = if candidate_failed { "error rate" } else { "output divergence" };
+        CanaryReport {
+            skill: "test_skill".to_string(),
+            outcome,
+            stage_percent: 10,
+            stage_index: 1,
+            stage: Default::default(),
+            total: Default::default(),
+            reason: (outcome == CanaryOutcome::RolledBack).then(|| reason.to_string()),
+            candidate_failed,
+            last_divergence: None,
+            started_at_ms: 0,
+            finished_at_ms: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_staged_canary_outcomes() {
+        let loader = Arc::new(SkillLoader::new());
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = RollbackConfig {
+            patches_dir: temp_dir.path().join("patches"),
+            artifacts_dir: temp_dir.path().join("artifacts"),
+            max_versions_per_skill: 10,
+        };
+        let manager = RollbackManager::new(config.clone(), loader);
+
+        let v1 = manager
+            .save_versioned_patch("test_skill", "fn v1() {}", "First", None, PatchOrigin::default())
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let v2 = manager
+            .stage_versioned_patch("test_skill", "fn v2() {}", "Second", None, PatchOrigin::default())
+            .unwrap();
+        assert_eq!(v2.status, PatchStatus::Canary);
+        assert_eq!(manager.get_active_version("test_skill").unwrap().timestamp_ms, v1.timestamp_ms);
+        assert_eq!(manager.manifest().get("test_skill").unwrap().timestamp_ms, v1.timestamp_ms);
+
+        // Rolled back for divergence: v1 stays active, v2 keeps the report but is not a
+        // dead-end, since a behaviour change may be intended.
+        let report = canary_report(CanaryOutcome::RolledBack, false);
+        apply_canary_outcome(&manager.manifest, &manager.versions, &manager.genetic_memory, "test_skill", v2.timestamp_ms, &report)
+            .unwrap();
+        let versions = manager.get_versions("test_skill");
+        assert_eq!(versions[1].status, PatchStatus::RolledBack);
+        assert!(versions[0].is_active);
+        let delta = versions[1].performance_delta.as_ref().unwrap();
+        assert_eq!(delta.canary.as_ref().unwrap().outcome, CanaryOutcome::RolledBack);
+        assert!(manager.check_dead_end("fn v2() {}").is_none());
+
+        // Rolled back because the candidate errored: recorded as a dead-end.
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let failing = manager
+            .stage_versioned_patch("test_skill", "fn v2b() {}", "Failing", None, PatchOrigin::default())
+            .unwrap();
+        let report = canary_report(CanaryOutcome::RolledBack, true);
+        apply_canary_outcome(&manager.manifest, &manager.versions, &manager.genetic_memory, "test_skill", failing.timestamp_ms, &report)
+            .unwrap();
+        assert!(manager
+            .check_dead_end("fn v2b() {}")
+            .unwrap()
+            .reason
+            .contains("error rate"));
+
+        // Promoted: v3 becomes active and the manifest follows.
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let v3 = manager
+            .stage_versioned_patch("test_skill", "fn v3() {}", "Third", None, PatchOrigin::default())
+            .unwrap();
+        let report = canary_report(CanaryOutcome::Promoted, false);
+        apply_canary_outcome(&manager.manifest, &manager.versions, &manager.genetic_memory, "test_skill", v3.timestamp_ms, &report)
+            .unwrap();
+        let active = manager.get_active_version("test_skill").unwrap();
+        assert_eq!(active.timestamp_ms, v3.timestamp_ms);
+        assert_eq!(active.status, PatchStatus::Applied);
+        assert!(active.performance_delta.unwrap().smoke_test_passed);
+        assert_eq!(manager.manifest().get("test_skill").unwrap().timestamp_ms, v3.timestamp_ms);
+    }
+
+    #[test]
+    fn test_concurrent_activation_conflicts_and_consistency() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = RollbackConfig {
+            patches_dir: temp_dir.path().join("patches"),
+            artifacts_dir: temp_dir.path().join("artifacts"),
+            max_versions_per_skill: 10,
+        };
+        let manager = RollbackManager::new(config.clone(), Arc::new(SkillLoader::new()));
+        let v1 = manager
+            .save_versioned_patch("test_skill", "fn v1() {}", "First", None, PatchOrigin::default())
+            .unwrap();
+        let v2 = manager
+            .save_versioned_patch("test_skill", "fn v2() {}", "Second", None, PatchOrigin::default())
+            .unwrap();
+
+        // A caller that still believes v1 is active loses the swap.
+        assert!(manager
+            .activate_version("test_skill", v1.timestamp_ms, Some(v1.timestamp_ms))
+            .is_err());
+        let active = manager
+            .activate_version("test_skill", v1.timestamp_ms, Some(v2.timestamp_ms))
+            .unwrap();
+        assert!(active.is_active);
+
+        // The manifest survives a restart; no current_* links are written.
+        let rescanned = RollbackManager::new(config.clone(), Arc::new(SkillLoader::new()));
+        assert_eq!(rescanned.get_active_version("test_skill").unwrap().timestamp_ms, v1.timestamp_ms);
+        assert!(!config.patches_dir.join("current_test_skill.rs").exists());
+        assert!(rescanned.check_consistency().is_empty());
+
+        std::fs::create_dir_all(&config.artifacts_dir).unwrap();
+        std::fs::write(config.artifacts_dir.join("stray.so"), b"").unwrap();
+        std::fs::write(&v1.source_path, "fn edited() {}").unwrap();
+        let issues = rescanned.check_consistency();
+        assert!(issues.iter().any(|i| matches!(i, ConsistencyIssue::OrphanedArtifact { .. })));
+        assert!(issues.iter().any(|i| matches!(i, ConsistencyIssue::SourceModified { .. })));
+    }
 }
@@ -0,0 +1,812 @@
+//! Out-of-process runner for Forge-generated dynamic skills.
+//!
+//! Each library is loaded by its own `pagi-skill-host` worker instead of into the Gateway, so a
+//! panic, segfault or runaway loop in LLM-written code only takes down that worker.
+//!
+//! ## Protocol
+//!
+//! Newline-delimited JSON over the worker's stdin/stdout. After loading the library the host
+//! writes a [`HostResponse`] with `id: 0` (`ok` on success, `error` if the symbols are missing),
+//! then answers every [`HostRequest`] with a response carrying the same `id`. The library itself
+//! still implements the `pagi_dynamic_skill_execute` / `pagi_dynamic_skill_free` C ABI; anything
+//! it prints to stdout is redirected to stderr so it cannot corrupt the protocol.
+//!
+//! ## Isolation
+//!
+//! - **rlimits** (Unix): address space and open files, no core dumps.
+//! - **CPU budget** (Unix): a watchdog thread in the worker exits it once a single call (or the
+//!   idle gap between calls) uses more than `cpu_secs` of CPU. The budget restarts with every
+//!   call, so a long-lived worker is not killed by the sum of many cheap calls.
+//! - **Scratch working directory**: the worker runs with a fresh temp dir as cwd/`TMPDIR`/`HOME`
+//!   and a cleared environment (no API keys leak into generated code). This only changes where
+//!   relative paths and temp files land; it is not filesystem isolation, and the worker can still
+//!   open any path its user can.
+//! - **seccomp** (Linux x86_64/aarch64, optional): denies sockets, exec, ptrace, mounts, module
+//!   loading, io_uring, signalling other processes and uid changes with `EPERM`. The filter is
+//!   installed before the library is opened, so its constructors already run under it.
+//!
+//! ## Supervision
+//!
+//! [`SandboxedSkill`] applies a per-call timeout, kills the worker on timeout or crash and
+//! restarts it on the next call. After `max_restarts` consecutive failures it refuses further
+//! calls until the skill is reloaded.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::loader::LoadedSkill;
+use crate::skill::{DynamicSkill, SkillError};
+
+/// File name of the worker binary (built alongside the Gateway).
+pub const SKILL_HOST_BIN: &str = "pagi-skill-host";
+
+/// One call sent to the worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostRequest {
+    pub id: u64,
+    pub args: Value,
+}
+
+/// Worker reply. Exactly one of `ok` / `error` is set; `id: 0` is the startup handshake.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostResponse {
+    pub id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ok: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Limits and supervision settings for `pagi-skill-host` workers.
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Path to the `pagi-skill-host` binary.
+    pub host_bin: PathBuf,
+    /// CPU seconds a single call may use before the worker exits (0 = unlimited).
+    pub cpu_secs: u64,
+    /// RLIMIT_AS in MiB (0 = unlimited).
+    pub memory_mb: u64,
+    /// RLIMIT_NOFILE (0 = inherit).
+    pub max_open_files: u64,
+    /// Deadline for a single call; the worker is killed when it is exceeded.
+    pub call_timeout: Duration,
+    /// Deadline for the worker to load the library and send its handshake.
+    pub startup_timeout: Duration,
+    /// Consecutive crashes/timeouts tolerated before the skill is disabled.
+    pub max_restarts: u32,
+    /// Install the seccomp denylist (Linux only; ignored elsewhere).
+    pub seccomp: bool,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            host_bin: Self::default_host_bin(),
+            cpu_secs: 30,
+            memory_mb: 512,
+            max_open_files: 64,
+            call_timeout: Duration::from_secs(30),
+            startup_timeout: Duration::from_secs(10),
+            max_restarts: 3,
+            seccomp: cfg!(target_os = "linux"),
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// `pagi-skill-host` next to the current executable (also checks the parent of `deps/`
+    /// for test binaries); falls back to a `PATH` lookup.
+    pub fn default_host_bin() -> PathBuf {
+        let file = format!("{}{}", SKILL_HOST_BIN, std::env::consts::EXE_SUFFIX);
+        if let Some(dir) = std::env::current_exe().ok().and_then(|p| p.parent().map(Path::to_path_buf)) {
+            let beside = dir.join(&file);
+            if beside.exists() {
+                return beside;
+            }
+            if dir.file_name().is_some_and(|n| n == "deps") {
+                if let Some(up) = dir.parent() {
+                    let up = up.join(&file);
+                    if up.exists() {
+                        return up;
+                    }
+                }
+            }
+        }
+        PathBuf::from(file)
+    }
+
+    /// Reads `PAGI_SKILL_HOST_*`; unset or invalid values keep the defaults.
+    pub fn from_env() -> Self {
+        fn num(name: &str) -> Option<u64> {
+            std::env::var(name).ok().and_then(|s| s.trim().parse().ok())
+        }
+        let mut config = Self::default();
+        if let Some(bin) = std::env::var_os("PAGI_SKILL_HOST_BIN").filter(|s| !s.is_empty()) {
+            config.host_bin = PathBuf::from(bin);
+        }
+        if let Some(n) = num("PAGI_SKILL_HOST_CPU_SECS") {
+            config.cpu_secs = n;
+        }
+        if let Some(n) = num("PAGI_SKILL_HOST_MEMORY_MB") {
+            config.memory_mb = n;
+        }
+        if let Some(n) = num("PAGI_SKILL_HOST_MAX_FILES") {
+            config.max_open_files = n;
+        }
+        if let Some(n) = num("PAGI_SKILL_HOST_TIMEOUT_SECS").filter(|n| *n > 0) {
+            config.call_timeout = Duration::from_secs(n);
+        }
+        if let Some(n) = num("PAGI_SKILL_HOST_MAX_RESTARTS") {
+            config.max_restarts = n.min(u32::MAX as u64) as u32;
+        }
+        if let Ok(v) = std::env::var("PAGI_SKILL_HOST_SECCOMP") {
+            config.seccomp = matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+        }
+        config
+    }
+
+    /// Command-line arguments passed to the worker for `lib` (parsed by [`HostArgs::parse`]).
+    pub fn host_args(&self, lib: &Path) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec![
+            "--lib".into(),
+            lib.as_os_str().to_owned(),
+            "--cpu-secs".into(),
+            self.cpu_secs.to_string().into(),
+            "--memory-mb".into(),
+            self.memory_mb.to_string().into(),
+            "--max-files".into(),
+            self.max_open_files.to_string().into(),
+        ];
+        if self.seccomp {
+            args.push("--seccomp".into());
+        }
+        args
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Supervisor side
+// ---------------------------------------------------------------------------
+
+/// A running worker process and the channel its stdout reader feeds.
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    responses: Receiver<HostResponse>,
+    /// Scratch cwd/TMPDIR/HOME (not a filesystem jail); removed when the worker is dropped.
+    _tmp: tempfile::TempDir,
+}
+
+impl Worker {
+    fn spawn(lib: &Path, name: &str, config: &SandboxConfig) -> Result<Self, SkillError> {
+        let tmp = tempfile::Builder::new()
+            .prefix("pagi-skill-host-")
+            .tempdir()
+            .map_err(|e| SkillError::Load(format!("skill host temp dir: {}", e)))?;
+        let mut cmd = Command::new(&config.host_bin);
+        cmd.args(config.host_args(lib))
+            .current_dir(tmp.path())
+            .env_clear()
+            .env("TMPDIR", tmp.path())
+            .env("TMP", tmp.path())
+            .env("TEMP", tmp.path())
+            .env("HOME", tmp.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        if let Some(root) = std::env::var_os("SystemRoot") {
+            // Windows cannot start a process without it.
+            cmd.env("SystemRoot", root);
+        }
+        let mut child = cmd.spawn().map_err(|e| {
+            SkillError::Load(format!("spawn {}: {}", config.host_bin.display(), e))
+        })?;
+        let stdin = child.stdin.take().ok_or_else(|| SkillError::Load("skill host stdin".to_string()))?;
+        let stdout = child.stdout.take().ok_or_else(|| SkillError::Load("skill host stdout".to_string()))?;
+
+        let (tx, responses) = mpsc::channel();
+        let skill = name.to_string();
+        std::thread::Builder::new()
+            .name(format!("skill-host-{}", name))
+            .spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    match serde_json::from_str::<HostResponse>(&line) {
+                        Ok(resp) => {
+                            if tx.send(resp).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => warn!(target: "pagi::evolution", skill = %skill, error = %e, "Ignoring malformed skill host output"),
+                    }
+                }
+            })
+            .map_err(|e| SkillError::Load(format!("skill host reader thread: {}", e)))?;
+
+        let mut worker = Self { child, stdin, responses, _tmp: tmp };
+        match worker.responses.recv_timeout(config.startup_timeout) {
+            Ok(HostResponse { error: Some(e), .. }) => {
+                worker.kill();
+                Err(SkillError::Load(e))
+            }
+            Ok(_) => Ok(worker),
+            Err(RecvTimeoutError::Timeout) => {
+                let status = worker.kill();
+                Err(SkillError::Load(format!("skill host did not start within {:?} ({})", config.startup_timeout, status)))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let status = worker.kill();
+                Err(SkillError::Load(format!("skill host exited during startup ({})", status)))
+            }
+        }
+    }
+
+    fn call(&mut self, id: u64, args: Value, timeout: Duration) -> Result<Value, SkillError> {
+        let line = serde_json::to_string(&HostRequest { id, args })?;
+        if writeln!(self.stdin, "{}", line).and_then(|_| self.stdin.flush()).is_err() {
+            return Err(SkillError::HostCrashed(self.kill()));
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.responses.recv_timeout(remaining) {
+                // Reply to an earlier call that timed out on our side; skip it.
+                Ok(resp) if resp.id != id => continue,
+                Ok(HostResponse { error: Some(e), .. }) => return Err(SkillError::Execution(e)),
+                Ok(HostResponse { ok, .. }) => return Ok(ok.unwrap_or(Value::Null)),
+                Err(RecvTimeoutError::Timeout) => return Err(SkillError::Timeout(timeout)),
+                Err(RecvTimeoutError::Disconnected) => return Err(SkillError::HostCrashed(self.kill())),
+            }
+        }
+    }
+
+    /// Kills (if still running) and reaps the worker; returns its exit status for error messages.
+    fn kill(&mut self) -> String {
+        let _ = self.child.kill();
+        match self.child.wait() {
+            Ok(status) => status.to_string(),
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+struct HostState {
+    worker: Option<Worker>,
+    next_id: u64,
+    consecutive_failures: u32,
+    restarts: u64,
+}
+
+/// A dynamic skill running in a supervised `pagi-skill-host` worker.
+/// Calls are serialized; the worker is (re)started lazily after a crash or timeout.
+pub struct SandboxedSkill {
+    name: String,
+    lib: PathBuf,
+    config: SandboxConfig,
+    state: Mutex<HostState>,
+}
+
+impl SandboxedSkill {
+    /// Starts a worker for `lib`; fails if the host cannot be spawned or the library does not
+    /// export the skill ABI.
+    pub fn spawn(lib: &Path, name: &str, config: SandboxConfig) -> Result<Self, SkillError> {
+        // The worker runs in its own temp dir, so relative paths would not resolve there.
+        let lib = std::fs::canonicalize(lib)
+            .map_err(|e| SkillError::Load(format!("{}: {}", lib.display(), e)))?;
+        let worker = Worker::spawn(&lib, name, &config)?;
+        info!(
+            target: "pagi::evolution",
+            skill = name,
+            pid = worker.child.id(),
+            "Dynamic skill loaded in sandboxed host"
+        );
+        Ok(Self {
+            name: name.to_string(),
+            lib,
+            config,
+            state: Mutex::new(HostState {
+                worker: Some(worker),
+                next_id: 0,
+                consecutive_failures: 0,
+                restarts: 0,
+            }),
+        })
+    }
+
+    /// Process id of the current worker, if one is running.
+    pub fn pid(&self) -> Option<u32> {
+        self.state.lock().ok()?.worker.as_ref().map(|w| w.child.id())
+    }
+
+    /// Number of times the worker has been restarted.
+    pub fn restarts(&self) -> u64 {
+        self.state.lock().map(|s| s.restarts).unwrap_or(0)
+    }
+
+    /// Resident memory of the current worker in bytes (Linux only).
+    pub fn resident_memory_bytes(&self) -> Option<u64> {
+        worker_rss_bytes(self.pid()?)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn worker_rss_bytes(pid: u32) -> Option<u64> {
+    let statm = std::fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    // SAFETY: sysconf has no preconditions.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * page_size.max(0) as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn worker_rss_bytes(_pid: u32) -> Option<u64> {
+    None
+}
+
+impl DynamicSkill for SandboxedSkill {
+    fn execute(&self, args: Value) -> Result<Value, SkillError> {
+        let mut state = self.state.lock().map_err(|e| SkillError::Load(e.to_string()))?;
+        if state.consecutive_failures > self.config.max_restarts {
+            return Err(SkillError::HostCrashed(format!(
+                "'{}' failed {} times in a row; reload the skill to retry",
+                self.name, state.consecutive_failures
+            )));
+        }
+        if state.worker.is_none() {
+            match Worker::spawn(&self.lib, &self.name, &self.config) {
+                Ok(worker) => {
+                    state.restarts += 1;
+                    info!(target: "pagi::evolution", skill = %self.name, pid = worker.child.id(), restarts = state.restarts, "Restarted skill host");
+                    state.worker = Some(worker);
+                }
+                Err(e) => {
+                    state.consecutive_failures += 1;
+                    return Err(e);
+                }
+            }
+        }
+        state.next_id += 1;
+        let id = state.next_id;
+        let timeout = self.config.call_timeout;
+        let result = match state.worker.as_mut() {
+            Some(worker) => worker.call(id, args, timeout),
+            None => return Err(SkillError::NotLoaded(self.name.clone())),
+        };
+        match &result {
+            Err(e @ SkillError::Timeout(_)) | Err(e @ SkillError::HostCrashed(_)) => {
+                if let Some(mut worker) = state.worker.take() {
+                    worker.kill();
+                }
+                state.consecutive_failures += 1;
+                warn!(
+                    target: "pagi::evolution",
+                    skill = %self.name,
+                    failures = state.consecutive_failures,
+                    error = %e,
+                    "Skill host failed; it will be restarted on the next call"
+                );
+            }
+            _ => state.consecutive_failures = 0,
+        }
+        result
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Worker side (`pagi-skill-host`)
+// ---------------------------------------------------------------------------
+
+/// Parsed `pagi-skill-host` command line (see [`SandboxConfig::host_args`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostArgs {
+    pub lib: PathBuf,
+    pub cpu_secs: u64,
+    pub memory_mb: u64,
+    pub max_open_files: u64,
+    pub seccomp: bool,
+}
+
+impl HostArgs {
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Self, String> {
+        let mut lib = None;
+        let mut parsed = Self { lib: PathBuf::new(), cpu_secs: 0, memory_mb: 0, max_open_files: 0, seccomp: false };
+        let mut it = args.into_iter();
+        while let Some(arg) = it.next() {
+            let flag = arg.to_string_lossy().into_owned();
+            if flag == "--seccomp" {
+                parsed.seccomp = true;
+                continue;
+            }
+            let value = it.next().ok_or_else(|| format!("{} requires a value", flag))?;
+            let number = || {
+                value
+                    .to_string_lossy()
+                    .parse::<u64>()
+                    .map_err(|e| format!("{}: {}", flag, e))
+            };
+            match flag.as_str() {
+                "--lib" => lib = Some(PathBuf::from(&value)),
+                "--cpu-secs" => parsed.cpu_secs = number()?,
+                "--memory-mb" => parsed.memory_mb = number()?,
+                "--max-files" => parsed.max_open_files = number()?,
+                other => return Err(format!("unknown argument: {}", other)),
+            }
+        }
+        parsed.lib = lib.ok_or_else(|| "--lib is required".to_string())?;
+        Ok(parsed)
+    }
+}
+
+/// Entry point of the `pagi-skill-host` binary. Returns the process exit code.
+pub fn run_host(args: &HostArgs) -> i32 {
+    let mut out = match protocol_output() {
+        Ok(out) => out,
+        Err(e) => {
+            eprintln!("{}: cannot set up protocol channel: {}", SKILL_HOST_BIN, e);
+            return 2;
+        }
+    };
+    let mut reply = |resp: HostResponse| -> bool {
+        match serde_json::to_string(&resp) {
+            Ok(line) => writeln!(out, "{}", line).and_then(|_| out.flush()).is_ok(),
+            Err(_) => false,
+        }
+    };
+    let fail = |e: String| HostResponse { id: 0, ok: None, error: Some(e) };
+
+    if let Err(e) = apply_rlimits(args) {
+        reply(fail(format!("setrlimit: {}", e)));
+        return 2;
+    }
+    let watchdog = if args.cpu_secs > 0 {
+        let budget = Duration::from_secs(args.cpu_secs);
+        let exceeded = move |used: Duration| {
+            eprintln!("{}: call used {:?} of CPU (budget {:?}); exiting", SKILL_HOST_BIN, used, budget);
+            std::process::exit(EXIT_CPU_BUDGET);
+        };
+        match CpuWatchdog::spawn(budget, exceeded) {
+            Ok(watchdog) => Some(watchdog),
+            Err(e) => {
+                reply(fail(format!("cpu watchdog: {}", e)));
+                return 2;
+            }
+        }
+    } else {
+        None
+    };
+    // Before dlopen: library constructors run as soon as it is mapped.
+    if args.seccomp {
+        if let Err(e) = apply_seccomp() {
+            reply(fail(format!("seccomp: {}", e)));
+            return 2;
+        }
+    }
+    let skill = match LoadedSkill::open(&args.lib) {
+        Ok(skill) => skill,
+        Err(e) => {
+            reply(fail(e.to_string()));
+            return 2;
+        }
+    };
+    if !reply(HostResponse { id: 0, ok: Some(serde_json::json!({ "ready": true, "pid": std::process::id() })), error: None }) {
+        return 2;
+    }
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let resp = match serde_json::from_str::<HostRequest>(&line) {
+            Ok(req) => {
+                if let Some(watchdog) = &watchdog {
+                    watchdog.reset();
+                }
+                let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| skill.execute_json(&req.args)));
+                if let Some(watchdog) = &watchdog {
+                    watchdog.reset();
+                }
+                match outcome {
+                    Ok(Ok(value)) => HostResponse { id: req.id, ok: Some(value), error: None },
+                    Ok(Err(e)) => HostResponse { id: req.id, ok: None, error: Some(e.to_string()) },
+                    Err(_) => HostResponse { id: req.id, ok: None, error: Some("skill panicked".to_string()) },
+                }
+            }
+            Err(e) => fail(format!("malformed request: {}", e)),
+        };
+        if !reply(resp) {
+            break;
+        }
+    }
+    0
+}
+
+/// Exit code of a worker that exceeded its per-call CPU budget.
+pub const EXIT_CPU_BUDGET: i32 = 152;
+
+/// Per-call CPU budget. The budget window restarts around every call; a watchdog thread polls
+/// the process CPU time and calls `on_exceeded` once a window uses more than `budget`.
+struct CpuWatchdog {
+    /// Process CPU time (µs) at the start of the current window.
+    window_start_us: Arc<AtomicU64>,
+}
+
+impl CpuWatchdog {
+    const TICK: Duration = Duration::from_millis(50);
+
+    fn spawn(budget: Duration, on_exceeded: impl Fn(Duration) + Send + 'static) -> std::io::Result<Self> {
+        let window_start_us = Arc::new(AtomicU64::new(process_cpu_time().as_micros() as u64));
+        let start = Arc::clone(&window_start_us);
+        std::thread::Builder::new().name("cpu-watchdog".to_string()).spawn(move || loop {
+            std::thread::sleep(Self::TICK);
+            let used = process_cpu_time().saturating_sub(Duration::from_micros(start.load(Ordering::Relaxed)));
+            if used > budget {
+                on_exceeded(used);
+                return;
+            }
+        })?;
+        Ok(Self { window_start_us })
+    }
+
+    /// Starts a new budget window.
+    fn reset(&self) {
+        self.window_start_us.store(process_cpu_time().as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// User + system CPU time of this process.
+#[cfg(unix)]
+fn process_cpu_time() -> Duration {
+    // SAFETY: getrusage only writes to `usage`.
+    let usage = unsafe {
+        let mut usage: libc::rusage = std::mem::zeroed();
+        libc::getrusage(libc::RUSAGE_SELF, &mut usage);
+        usage
+    };
+    let time = |t: libc::timeval| Duration::from_secs(t.tv_sec as u64) + Duration::from_micros(t.tv_usec as u64);
+    time(usage.ru_utime) + time(usage.ru_stime)
+}
+
+/// Not measured here, so the watchdog never fires.
+#[cfg(not(unix))]
+fn process_cpu_time() -> Duration {
+    Duration::ZERO
+}
+
+/// Moves the real stdout to a private fd and points fd 1 at stderr, so output printed by the
+/// skill cannot be mistaken for protocol lines.
+#[cfg(unix)]
+fn protocol_output() -> std::io::Result<Box<dyn Write>> {
+    use std::os::unix::io::FromRawFd;
+    // SAFETY: plain fd juggling on descriptors this process owns.
+    unsafe {
+        let fd = libc::dup(1);
+        if fd < 0 || libc::dup2(2, 1) < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Box::new(std::fs::File::from_raw_fd(fd)))
+    }
+}
+
+#[cfg(not(unix))]
+fn protocol_output() -> std::io::Result<Box<dyn Write>> {
+    Ok(Box::new(std::io::stdout()))
+}
+
+#[cfg(unix)]
+fn apply_rlimits(args: &HostArgs) -> std::io::Result<()> {
+    macro_rules! limit {
+        ($resource:expr, $value:expr) => {{
+            let value = $value as libc::rlim_t;
+            let lim = libc::rlimit { rlim_cur: value, rlim_max: value };
+            // SAFETY: setrlimit only reads `lim`.
+            if unsafe { libc::setrlimit($resource, &lim) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }};
+    }
+    limit!(libc::RLIMIT_CORE, 0u64);
+    if args.memory_mb > 0 {
+        limit!(libc::RLIMIT_AS, args.memory_mb.saturating_mul(1024 * 1024));
+    }
+    if args.max_open_files > 0 {
+        limit!(libc::RLIMIT_NOFILE, args.max_open_files);
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn apply_rlimits(_args: &HostArgs) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Denylist filter: the listed syscalls fail with EPERM, everything else is allowed.
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn apply_seccomp() -> std::io::Result<()> {
+    // BPF_LD | BPF_W | BPF_ABS, BPF_JMP | BPF_JEQ | BPF_K, BPF_RET | BPF_K
+    const BPF_LD_W_ABS: u16 = 0x20;
+    const BPF_JMP_JEQ_K: u16 = 0x15;
+    const BPF_RET_K: u16 = 0x06;
+    const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+    const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+    const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7;
+    const DENIED: &[libc::c_long] = &[
+        libc::SYS_socket,
+        libc::SYS_connect,
+        libc::SYS_bind,
+        libc::SYS_listen,
+        libc::SYS_accept4,
+        libc::SYS_execve,
+        libc::SYS_execveat,
+        libc::SYS_ptrace,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_kill,
+        libc::SYS_tkill,
+        libc::SYS_tgkill,
+        libc::SYS_pidfd_send_signal,
+        libc::SYS_io_uring_setup,
+        libc::SYS_io_uring_enter,
+        libc::SYS_io_uring_register,
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_chroot,
+        libc::SYS_unshare,
+        libc::SYS_setns,
+        libc::SYS_setuid,
+        libc::SYS_setgid,
+        libc::SYS_reboot,
+        libc::SYS_kexec_load,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+    ];
+
+    let op = |code: u16, k: u32| libc::sock_filter { code, jt: 0, jf: 0, k };
+    let mut filter = vec![
+        op(BPF_LD_W_ABS, 4), // seccomp_data.arch
+        libc::sock_filter { code: BPF_JMP_JEQ_K, jt: 1, jf: 0, k: AUDIT_ARCH },
+        op(BPF_RET_K, SECCOMP_RET_KILL_PROCESS),
+        op(BPF_LD_W_ABS, 0), // seccomp_data.nr
+    ];
+    for nr in DENIED {
+        filter.push(libc::sock_filter { code: BPF_JMP_JEQ_K, jt: 0, jf: 1, k: *nr as u32 });
+        filter.push(op(BPF_RET_K, SECCOMP_RET_ERRNO | libc::EPERM as u32));
+    }
+    filter.push(op(BPF_RET_K, SECCOMP_RET_ALLOW));
+    let prog = libc::sock_fprog { len: filter.len() as u16, filter: filter.as_mut_ptr() };
+    // SAFETY: `prog` points at `filter`, which outlives both calls; the kernel copies it.
+    unsafe {
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        if libc::prctl(libc::PR_SET_SECCOMP, libc::SECCOMP_MODE_FILTER, &prog as *const libc::sock_fprog) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+fn apply_seccomp() -> std::io::Result<()> {
+    warn!(target: "pagi::evolution", "seccomp is not supported on this platform; continuing without it");
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Stand-in for `pagi-skill-host` using only shell builtins (the worker env is cleared).
+    const FAKE_HOST: &str = r#"#!/bin/sh
+echo '{"id":0,"ok":{"ready":true}}'
+while read -r line; do
+  id=${line#*\"id\":}
+  id=${id%%,*}
+  case "$line" in
+    *spin*) while :; do :; done ;;
+    *crash*) exit 3 ;;
+  esac
+  echo "{\"id\":$id,\"ok\":{\"echo\":$id}}"
+done
+"#;
+
+    fn fake_host(dir: &Path) -> SandboxConfig {
+        let bin = dir.join("fake-skill-host");
+        std::fs::write(&bin, FAKE_HOST).unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(dir.join("libskill.so"), b"").unwrap();
+        SandboxConfig {
+            host_bin: bin,
+            call_timeout: Duration::from_millis(500),
+            max_restarts: 1,
+            ..SandboxConfig::default()
+        }
+    }
+
+    #[test]
+    fn host_args_round_trip() {
+        let config = SandboxConfig { seccomp: true, ..SandboxConfig::default() };
+        let parsed = HostArgs::parse(config.host_args(Path::new("/tmp/libx.so"))).unwrap();
+        assert_eq!(
+            parsed,
+            HostArgs { lib: PathBuf::from("/tmp/libx.so"), cpu_secs: 30, memory_mb: 512, max_open_files: 64, seccomp: true }
+        );
+        assert!(HostArgs::parse(vec![OsString::from("--cpu-secs"), OsString::from("1")]).is_err());
+    }
+
+    #[test]
+    fn supervisor_restarts_worker_after_timeout_and_crash() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = fake_host(dir.path());
+        let skill = SandboxedSkill::spawn(&dir.path().join("libskill.so"), "fake", config).unwrap();
+        let first_pid = skill.pid().unwrap();
+        if cfg!(target_os = "linux") {
+            assert!(skill.resident_memory_bytes().is_some_and(|bytes| bytes > 0));
+        }
+
+        assert_eq!(skill.execute(serde_json::json!({ "q": 1 })).unwrap()["echo"], 1);
+
+        assert!(matches!(skill.execute(serde_json::json!("spin")), Err(SkillError::Timeout(_))));
+        assert!(skill.pid().is_none());
+        assert_eq!(skill.execute(serde_json::json!({})).unwrap()["echo"], 3);
+        assert_ne!(skill.pid().unwrap(), first_pid);
+        assert_eq!(skill.restarts(), 1);
+
+        // Two consecutive crashes exceed max_restarts = 1: the skill stays down until reloaded.
+        assert!(matches!(skill.execute(serde_json::json!("crash")), Err(SkillError::HostCrashed(_))));
+        assert!(matches!(skill.execute(serde_json::json!("crash")), Err(SkillError::HostCrashed(_))));
+        let err = skill.execute(serde_json::json!({})).unwrap_err();
+        assert!(err.to_string().contains("reload the skill"), "{}", err);
+    }
+
+    #[test]
+    fn cpu_watchdog_fires_when_a_window_exceeds_its_budget() {
+        let (tx, rx) = mpsc::channel();
+        let watchdog = CpuWatchdog::spawn(Duration::from_millis(50), move |used| {
+            let _ = tx.send(used);
+        })
+        .unwrap();
+        watchdog.reset();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let used = loop {
+            if let Ok(used) = rx.try_recv() {
+                break used;
+            }
+            assert!(Instant::now() < deadline, "watchdog did not fire");
+            std::hint::black_box((0..10_000u64).sum::<u64>());
+        };
+        assert!(used > Duration::from_millis(50));
+    }
+
+    #[test]
+    fn missing_host_binary_fails_to_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = fake_host(dir.path());
+        config.host_bin = dir.path().join("does-not-exist");
+        let err = SandboxedSkill::spawn(&dir.path().join("libskill.so"), "fake", config).err().unwrap();
+        assert!(matches!(err, SkillError::Load(_)));
+    }
+}
@@ -14,6 +14,12 @@ pub enum SkillError {
     NotLoaded(String),
     #[error("load error: {0}")]
     Load(String),
+    #[error("skill timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    #[error("skill host crashed: {0}")]
+    HostCrashed(String),
+    #[error("skill exhausted its fuel budget ({0} units)")]
+    FuelExhausted(u64),
 }
 
 /// Trait for skills that can be executed with JSON args and return JSON.
@@ -0,0 +1,641 @@
+//! WebAssembly runtime for Forge skills built for `wasm32-wasip1`.
+//!
+//! A safer alternative to native cdylibs: the skill runs inside an embedded interpreter
+//! (`wasmi`) with fuel metering and a memory cap, and can only reach the outside world through
+//! host functions gated by its manifest capabilities.
+//!
+//! ## Guest contract
+//!
+//! Same C ABI as native skills, compiled to wasm32 (pointers are offsets into linear memory):
+//! - `memory` export.
+//! - `pagi_alloc(len: i32) -> i32`, injected by [`Compiler`](crate::Compiler) for wasm builds.
+//! - `pagi_dynamic_skill_execute(args: i32) -> i32` taking and returning NUL-terminated JSON.
+//! - optional `pagi_dynamic_skill_free(ptr: i32)`.
+//!
+//! ## Host imports (module `"pagi"`)
+//!
+//! | import | capability |
+//! |--------|------------|
+//! | `log(level, ptr, len)` | always allowed |
+//! | `kb_read(slot, key_ptr, key_len) -> i32` | `slot` in `kb_slots` |
+//! | `kb_write(slot, key_ptr, key_len, val_ptr, val_len) -> i32` | `slot` in `kb_slots` |
+//! | `http_get(url_ptr, url_len) -> i32` | URL host matches `network_egress` |
+//! | `take_result(ptr)` | copies the bytes staged by the last `kb_read` / `http_get` |
+//!
+//! `kb_read` / `http_get` return the staged length, or [`DENIED`], [`NOT_FOUND`], [`HOST_ERROR`].
+//! WASI preview1 imports are stubbed: stdout/stderr go to the log, there is no filesystem,
+//! environment, network or clock beyond wall time.
+//!
+//! Each call gets a fresh instance, so no state leaks between calls.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{debug, error, info, trace, warn};
+use wasmi::core::TrapCode;
+use wasmi::{Caller, Config, Engine, Extern, ExternType, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, Val};
+
+use crate::skill::{DynamicSkill, SkillError};
+
+/// `kb_read` / `kb_write` / `http_get`: the skill's capabilities do not allow the request.
+pub const DENIED: i32 = -1;
+/// `kb_read`: no value under that key.
+pub const NOT_FOUND: i32 = -2;
+/// The host failed (or no host is attached to the loader).
+pub const HOST_ERROR: i32 = -3;
+
+const HOST_MODULE: &str = "pagi";
+const WASI_MODULE: &str = "wasi_snapshot_preview1";
+/// Upper bound on a single guest-supplied buffer (key, value, URL, log line).
+const MAX_HOST_ARG_BYTES: usize = 1024 * 1024;
+const WASI_ERRNO_BADF: i32 = 8;
+const WASI_ERRNO_NOSYS: i32 = 52;
+
+/// Services the embedding application exposes to WASM skills (KB access, HTTP).
+/// Implemented in the skills layer, where the KnowledgeStore lives. KB calls carry the calling
+/// skill's name so the host can apply its own per-skill policy on top of the capabilities.
+pub trait WasmHost: Send + Sync {
+    fn kb_read(&self, skill: &str, slot: u8, key: &str) -> Result<Option<Vec<u8>>, String>;
+    fn kb_write(&self, skill: &str, slot: u8, key: &str, value: &[u8]) -> Result<(), String>;
+    fn http_get(&self, url: &str) -> Result<Vec<u8>, String>;
+}
+
+/// What a WASM skill may reach through its host imports (mirrors the manifest's
+/// `kb_layers` and `capabilities.network_egress`). Default: logging only.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmCapabilities {
+    #[serde(default)]
+    pub kb_slots: Vec<u8>,
+    /// Exact host, `*.domain` (subdomains) or `*`.
+    #[serde(default)]
+    pub network_egress: Vec<String>,
+}
+
+impl WasmCapabilities {
+    pub fn allows_slot(&self, slot: u8) -> bool {
+        self.kb_slots.contains(&slot)
+    }
+
+    /// http(s) URLs whose host matches `network_egress`.
+    pub fn allows_url(&self, url: &str) -> bool {
+        let Ok(parsed) = reqwest::Url::parse(url) else {
+            return false;
+        };
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return false;
+        }
+        let Some(host) = parsed.host_str() else {
+            return false;
+        };
+        let host = host.trim_end_matches('.').to_lowercase();
+        self.network_egress.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            if pattern == "*" {
+                return true;
+            }
+            match pattern.strip_prefix("*.") {
+                Some(domain) => host.ends_with(&format!(".{}", domain)),
+                None => host == pattern,
+            }
+        })
+    }
+}
+
+/// Resource bounds for WASM skill calls.
+#[derive(Debug, Clone)]
+pub struct WasmConfig {
+    /// Fuel per call (roughly one unit per executed instruction).
+    pub fuel: u64,
+    /// Linear memory cap in MiB.
+    pub max_memory_mb: usize,
+    /// Largest JSON result accepted from the guest.
+    pub max_output_bytes: usize,
+}
+
+impl Default for WasmConfig {
+    fn default() -> Self {
+        Self {
+            fuel: 500_000_000,
+            max_memory_mb: 64,
+            max_output_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl WasmConfig {
+    /// Reads `PAGI_WASM_FUEL` and `PAGI_WASM_MAX_MEMORY_MB`; invalid values keep the defaults.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(n) = std::env::var("PAGI_WASM_FUEL").ok().and_then(|s| s.trim().parse().ok()).filter(|n| *n > 0) {
+            config.fuel = n;
+        }
+        if let Some(n) = std::env::var("PAGI_WASM_MAX_MEMORY_MB").ok().and_then(|s| s.trim().parse().ok()).filter(|n| *n > 0) {
+            config.max_memory_mb = n;
+        }
+        config
+    }
+}
+
+/// Per-call store data.
+struct WasmState {
+    skill: String,
+    caps: WasmCapabilities,
+    host: Option<Arc<dyn WasmHost>>,
+    /// Result of the last `kb_read` / `http_get`, fetched by `take_result`.
+    staged: Vec<u8>,
+    limits: StoreLimits,
+}
+
+/// A compiled WASM skill; instantiated fresh for every call.
+pub struct WasmSkill {
+    name: String,
+    engine: Engine,
+    module: Module,
+    linker: Linker<WasmState>,
+    caps: WasmCapabilities,
+    host: Option<Arc<dyn WasmHost>>,
+    config: WasmConfig,
+}
+
+impl WasmSkill {
+    pub fn from_file(
+        path: &Path,
+        name: &str,
+        caps: WasmCapabilities,
+        host: Option<Arc<dyn WasmHost>>,
+        config: WasmConfig,
+    ) -> Result<Self, SkillError> {
+        let bytes = std::fs::read(path).map_err(|e| SkillError::Load(format!("{}: {}", path.display(), e)))?;
+        Self::from_bytes(&bytes, name, caps, host, config)
+    }
+
+    /// Validates the module and links host/WASI imports; unknown import modules are rejected.
+    pub fn from_bytes(
+        wasm: &[u8],
+        name: &str,
+        caps: WasmCapabilities,
+        host: Option<Arc<dyn WasmHost>>,
+        config: WasmConfig,
+    ) -> Result<Self, SkillError> {
+        let mut engine_config = Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, wasm).map_err(|e| SkillError::Load(format!("invalid wasm module: {}", e)))?;
+        for import in module.imports() {
+            if import.module() != HOST_MODULE && import.module() != WASI_MODULE {
+                return Err(SkillError::Load(format!(
+                    "wasm skill imports {}::{}; only \"{}\" and \"{}\" are provided",
+                    import.module(),
+                    import.name(),
+                    HOST_MODULE,
+                    WASI_MODULE
+                )));
+            }
+        }
+        let linker = build_linker(&engine, &module)?;
+        info!(target: "pagi::evolution", skill = name, kb_slots = ?caps.kb_slots, egress = ?caps.network_egress, "WASM skill loaded");
+        Ok(Self {
+            name: name.to_string(),
+            engine,
+            module,
+            linker,
+            caps,
+            host,
+            config,
+        })
+    }
+
+    pub fn capabilities(&self) -> &WasmCapabilities {
+        &self.caps
+    }
+
+    fn trap(&self, e: wasmi::Error) -> SkillError {
+        if e.as_trap_code() == Some(TrapCode::OutOfFuel) {
+            return SkillError::FuelExhausted(self.config.fuel);
+        }
+        if let Some(status) = e.i32_exit_status() {
+            return SkillError::Execution(format!("wasm skill exited with status {}", status));
+        }
+        SkillError::Execution(format!("wasm trap: {}", e))
+    }
+}
+
+impl DynamicSkill for WasmSkill {
+    fn execute(&self, args: Value) -> Result<Value, SkillError> {
+        let state = WasmState {
+            skill: self.name.clone(),
+            caps: self.caps.clone(),
+            host: self.host.clone(),
+            staged: Vec::new(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.config.max_memory_mb.saturating_mul(1024 * 1024))
+                .instances(1)
+                .build(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|s| &mut s.limits);
+        store.set_fuel(self.config.fuel).map_err(|e| SkillError::Load(e.to_string()))?;
+
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| self.trap(e))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| SkillError::Load("wasm skill exports no memory".to_string()))?;
+        let export = |name: &str| SkillError::Load(format!("wasm skill export {}: missing or wrong signature", name));
+        let alloc = instance.get_typed_func::<i32, i32>(&store, "pagi_alloc").map_err(|_| export("pagi_alloc"))?;
+        let execute = instance
+            .get_typed_func::<i32, i32>(&store, "pagi_dynamic_skill_execute")
+            .map_err(|_| export("pagi_dynamic_skill_execute"))?;
+        let free = instance.get_typed_func::<i32, ()>(&store, "pagi_dynamic_skill_free").ok();
+
+        let mut input = serde_json::to_vec(&args)?;
+        input.push(0);
+        let len = i32::try_from(input.len()).map_err(|_| SkillError::Execution("arguments too large".to_string()))?;
+        let args_ptr = alloc.call(&mut store, len).map_err(|e| self.trap(e))?;
+        memory
+            .write(&mut store, args_ptr as u32 as usize, &input)
+            .map_err(|e| SkillError::Execution(format!("writing arguments: {}", e)))?;
+
+        let out_ptr = execute.call(&mut store, args_ptr).map_err(|e| self.trap(e))?;
+        if out_ptr == 0 {
+            return Err(SkillError::Execution("skill returned null".to_string()));
+        }
+        let output = read_c_string(memory.data(&store), out_ptr as u32 as usize, self.config.max_output_bytes)?;
+        if let Some(free) = free {
+            let _ = free.call(&mut store, out_ptr);
+        }
+        debug!(
+            target: "pagi::evolution",
+            skill = %self.name,
+            fuel_used = self.config.fuel.saturating_sub(store.get_fuel().unwrap_or(0)),
+            "WASM skill call finished"
+        );
+        Ok(serde_json::from_slice(&output)?)
+    }
+}
+
+fn read_c_string(mem: &[u8], offset: usize, max: usize) -> Result<Vec<u8>, SkillError> {
+    let tail = mem
+        .get(offset..)
+        .ok_or_else(|| SkillError::Execution("skill returned an out-of-bounds pointer".to_string()))?;
+    let end = tail
+        .iter()
+        .take(max.saturating_add(1))
+        .position(|b| *b == 0)
+        .ok_or_else(|| SkillError::Execution(format!("skill output is not NUL-terminated within {} bytes", max)))?;
+    Ok(tail[..end].to_vec())
+}
+
+fn guest_memory(caller: &Caller<'_, WasmState>) -> Result<Memory, wasmi::Error> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("wasm skill exports no memory"))
+}
+
+fn read_guest(caller: &Caller<'_, WasmState>, ptr: i32, len: i32) -> Result<Vec<u8>, wasmi::Error> {
+    let len = usize::try_from(len).map_err(|_| wasmi::Error::new("negative length"))?;
+    if len > MAX_HOST_ARG_BYTES {
+        return Err(wasmi::Error::new(format!("host argument exceeds {} bytes", MAX_HOST_ARG_BYTES)));
+    }
+    let mut buf = vec![0u8; len];
+    guest_memory(caller)?
+        .read(caller, ptr as u32 as usize, &mut buf)
+        .map_err(|e| wasmi::Error::new(e.to_string()))?;
+    Ok(buf)
+}
+
+fn read_guest_str(caller: &Caller<'_, WasmState>, ptr: i32, len: i32) -> Result<String, wasmi::Error> {
+    String::from_utf8(read_guest(caller, ptr, len)?).map_err(|_| wasmi::Error::new("host argument is not UTF-8"))
+}
+
+fn write_guest(caller: &mut Caller<'_, WasmState>, ptr: i32, bytes: &[u8]) -> Result<(), wasmi::Error> {
+    guest_memory(caller)?
+        .write(caller, ptr as u32 as usize, bytes)
+        .map_err(|e| wasmi::Error::new(e.to_string()))
+}
+
+/// Stages `bytes` for `take_result` and returns their length.
+fn stage(caller: &mut Caller<'_, WasmState>, bytes: Vec<u8>) -> i32 {
+    match i32::try_from(bytes.len()) {
+        Ok(len) => {
+            caller.data_mut().staged = bytes;
+            len
+        }
+        Err(_) => HOST_ERROR,
+    }
+}
+
+fn build_linker(engine: &Engine, module: &Module) -> Result<Linker<WasmState>, SkillError> {
+    let mut linker = Linker::<WasmState>::new(engine);
+    let link = |e: wasmi::errors::LinkerError| SkillError::Load(format!("linking host functions: {}", e));
+
+    linker
+        .func_wrap(HOST_MODULE, "log", |caller: Caller<'_, WasmState>, level: i32, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            let msg = read_guest(&caller, ptr, len)?;
+            let msg = String::from_utf8_lossy(&msg);
+            let skill = caller.data().skill.as_str();
+            match level {
+                0 => error!(target: "pagi::evolution::wasm", skill, "{}", msg),
+                1 => warn!(target: "pagi::evolution::wasm", skill, "{}", msg),
+                2 => info!(target: "pagi::evolution::wasm", skill, "{}", msg),
+                3 => debug!(target: "pagi::evolution::wasm", skill, "{}", msg),
+                _ => trace!(target: "pagi::evolution::wasm", skill, "{}", msg),
+            }
+            Ok(())
+        })
+        .map_err(link)?;
+
+    linker
+        .func_wrap(HOST_MODULE, "kb_read", |mut caller: Caller<'_, WasmState>, slot: i32, key_ptr: i32, key_len: i32| -> Result<i32, wasmi::Error> {
+            let Some(slot) = u8::try_from(slot).ok().filter(|s| caller.data().caps.allows_slot(*s)) else {
+                warn!(target: "pagi::evolution::wasm", skill = %caller.data().skill, slot, "kb_read denied by capabilities");
+                return Ok(DENIED);
+            };
+            let key = read_guest_str(&caller, key_ptr, key_len)?;
+            let Some(host) = caller.data().host.clone() else { return Ok(HOST_ERROR) };
+            Ok(match host.kb_read(&caller.data().skill, slot, &key) {
+                Ok(Some(value)) => stage(&mut caller, value),
+                Ok(None) => NOT_FOUND,
+                Err(e) => {
+                    warn!(target: "pagi::evolution::wasm", skill = %caller.data().skill, slot, error = %e, "kb_read failed");
+                    HOST_ERROR
+                }
+            })
+        })
+        .map_err(link)?;
+
+    linker
+        .func_wrap(
+            HOST_MODULE,
+            "kb_write",
+            |caller: Caller<'_, WasmState>, slot: i32, key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32| -> Result<i32, wasmi::Error> {
+                let Some(slot) = u8::try_from(slot).ok().filter(|s| caller.data().caps.allows_slot(*s)) else {
+                    warn!(target: "pagi::evolution::wasm", skill = %caller.data().skill, slot, "kb_write denied by capabilities");
+                    return Ok(DENIED);
+                };
+                let key = read_guest_str(&caller, key_ptr, key_len)?;
+                let value = read_guest(&caller, val_ptr, val_len)?;
+                let Some(host) = caller.data().host.clone() else { return Ok(HOST_ERROR) };
+                Ok(match host.kb_write(&caller.data().skill, slot, &key, &value) {
+                    Ok(()) => 0,
+                    Err(e) => {
+                        warn!(target: "pagi::evolution::wasm", skill = %caller.data().skill, slot, error = %e, "kb_write failed");
+                        HOST_ERROR
+                    }
+                })
+            },
+        )
+        .map_err(link)?;
+
+    linker
+        .func_wrap(HOST_MODULE, "http_get", |mut caller: Caller<'_, WasmState>, url_ptr: i32, url_len: i32| -> Result<i32, wasmi::Error> {
+            let url = read_guest_str(&caller, url_ptr, url_len)?;
+            if !caller.data().caps.allows_url(&url) {
+                warn!(target: "pagi::evolution::wasm", skill = %caller.data().skill, url = %url, "http_get denied by capabilities");
+                return Ok(DENIED);
+            }
+            let Some(host) = caller.data().host.clone() else { return Ok(HOST_ERROR) };
+            Ok(match host.http_get(&url) {
+                Ok(body) => stage(&mut caller, body),
+                Err(e) => {
+                    warn!(target: "pagi::evolution::wasm", skill = %caller.data().skill, url = %url, error = %e, "http_get failed");
+                    HOST_ERROR
+                }
+            })
+        })
+        .map_err(link)?;
+
+    linker
+        .func_wrap(HOST_MODULE, "take_result", |mut caller: Caller<'_, WasmState>, ptr: i32| -> Result<(), wasmi::Error> {
+            let staged = std::mem::take(&mut caller.data_mut().staged);
+            write_guest(&mut caller, ptr, &staged)
+        })
+        .map_err(link)?;
+
+    link_wasi_stubs(&mut linker, module).map_err(link)?;
+    Ok(linker)
+}
+
+/// Minimal WASI preview1: enough for Rust's std to start, no ambient authority.
+/// Every other imported WASI function returns `ENOSYS`.
+fn link_wasi_stubs(linker: &mut Linker<WasmState>, module: &Module) -> Result<(), wasmi::errors::LinkerError> {
+    let imported: Vec<(String, Option<wasmi::FuncType>)> = module
+        .imports()
+        .filter(|i| i.module() == WASI_MODULE)
+        .map(|i| {
+            let ty = match i.ty() {
+                ExternType::Func(ty) => Some(ty.clone()),
+                _ => None,
+            };
+            (i.name().to_string(), ty)
+        })
+        .collect();
+
+    for (name, ty) in imported {
+        match name.as_str() {
+            "fd_write" => {
+                linker.func_wrap(
+                    WASI_MODULE,
+                    "fd_write",
+                    |mut caller: Caller<'_, WasmState>, fd: i32, iovs: i32, iovs_len: i32, nwritten: i32| -> Result<i32, wasmi::Error> {
+                        if fd != 1 && fd != 2 {
+                            return Ok(WASI_ERRNO_BADF);
+                        }
+                        let iovecs = read_guest(&caller, iovs, iovs_len.saturating_mul(8))?;
+                        let mut out = Vec::new();
+                        for iov in iovecs.chunks_exact(8) {
+                            let ptr = i32::from_le_bytes([iov[0], iov[1], iov[2], iov[3]]);
+                            let len = i32::from_le_bytes([iov[4], iov[5], iov[6], iov[7]]);
+                            out.extend(read_guest(&caller, ptr, len)?);
+                        }
+                        let text = String::from_utf8_lossy(&out);
+                        info!(target: "pagi::evolution::wasm", skill = %caller.data().skill, fd, "{}", text.trim_end());
+                        write_guest(&mut caller, nwritten, &(out.len() as u32).to_le_bytes())?;
+                        Ok(0)
+                    },
+                )?;
+            }
+            "proc_exit" => {
+                linker.func_wrap(WASI_MODULE, "proc_exit", |code: i32| -> Result<(), wasmi::Error> {
+                    Err(wasmi::Error::i32_exit(code))
+                })?;
+            }
+            "args_sizes_get" | "environ_sizes_get" => {
+                linker.func_wrap(WASI_MODULE, &name, |mut caller: Caller<'_, WasmState>, count: i32, size: i32| -> Result<i32, wasmi::Error> {
+                    write_guest(&mut caller, count, &0u32.to_le_bytes())?;
+                    write_guest(&mut caller, size, &0u32.to_le_bytes())?;
+                    Ok(0)
+                })?;
+            }
+            "args_get" | "environ_get" => {
+                linker.func_wrap(WASI_MODULE, &name, |_: i32, _: i32| -> i32 { 0 })?;
+            }
+            "clock_time_get" => {
+                linker.func_wrap(
+                    WASI_MODULE,
+                    "clock_time_get",
+                    |mut caller: Caller<'_, WasmState>, _id: i32, _precision: i64, out: i32| -> Result<i32, wasmi::Error> {
+                        let now = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_nanos() as u64)
+                            .unwrap_or(0);
+                        write_guest(&mut caller, out, &now.to_le_bytes())?;
+                        Ok(0)
+                    },
+                )?;
+            }
+            "random_get" => {
+                linker.func_wrap(WASI_MODULE, "random_get", |mut caller: Caller<'_, WasmState>, buf: i32, len: i32| -> Result<i32, wasmi::Error> {
+                    // Not cryptographic; enough for hash seeds. Skills needing secrets must not run as WASM.
+                    let mut x = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_nanos() as u64)
+                        .unwrap_or(0x9E37_79B9_7F4A_7C15)
+                        | 1;
+                    let bytes: Vec<u8> = (0..usize::try_from(len).unwrap_or(0).min(MAX_HOST_ARG_BYTES))
+                        .map(|_| {
+                            x ^= x << 13;
+                            x ^= x >> 7;
+                            x ^= x << 17;
+                            x as u8
+                        })
+                        .collect();
+                    write_guest(&mut caller, buf, &bytes)?;
+                    Ok(0)
+                })?;
+            }
+            "sched_yield" => {
+                linker.func_wrap(WASI_MODULE, "sched_yield", || -> i32 { 0 })?;
+            }
+            _ => {
+                // Signature-agnostic ENOSYS (or a trap if the import does not return an errno).
+                let Some(ty) = ty else { continue };
+                let returns_errno = ty.results() == [wasmi::core::ValType::I32];
+                let fn_name = name.clone();
+                linker.func_new(WASI_MODULE, &name, ty, move |_caller, _params, results| {
+                    if returns_errno {
+                        results[0] = Val::I32(WASI_ERRNO_NOSYS);
+                        Ok(())
+                    } else {
+                        Err(wasmi::Error::new(format!("WASI {} is not available to skills", fn_name)))
+                    }
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Returns `{"ok":true}`, importing a few WASI functions the way Rust's std does.
+    const ECHO: &str = r#"(module
+        (import "wasi_snapshot_preview1" "environ_sizes_get" (func (param i32 i32) (result i32)))
+        (import "wasi_snapshot_preview1" "path_open" (func (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 4096))
+        (data (i32.const 1024) "{\"ok\":true}\00")
+        (func (export "pagi_alloc") (param i32) (result i32)
+            (local $p i32)
+            (local.set $p (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get 0)))
+            (local.get $p))
+        (func (export "pagi_dynamic_skill_execute") (param i32) (result i32) (i32.const 1024)))"#;
+
+    /// Spins forever.
+    const SPIN: &str = r#"(module
+        (memory (export "memory") 1)
+        (func (export "pagi_alloc") (param i32) (result i32) (i32.const 4096))
+        (func (export "pagi_dynamic_skill_execute") (param i32) (result i32)
+            (loop $l (br $l))
+            (i32.const 0)))"#;
+
+    /// Reads KB slot 5 key "k": returns "denied" / "found" / "other" depending on the status code.
+    const KB_READ: &str = r#"(module
+        (import "pagi" "kb_read" (func $kb_read (param i32 i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 512) "k")
+        (data (i32.const 1024) "\"denied\"\00")
+        (data (i32.const 2048) "\"found\"\00")
+        (data (i32.const 3072) "\"other\"\00")
+        (func (export "pagi_alloc") (param i32) (result i32) (i32.const 4096))
+        (func (export "pagi_dynamic_skill_execute") (param i32) (result i32)
+            (local $r i32)
+            (local.set $r (call $kb_read (i32.const 5) (i32.const 512) (i32.const 1)))
+            (if (result i32) (i32.eq (local.get $r) (i32.const -1))
+                (then (i32.const 1024))
+                (else (if (result i32) (i32.ge_s (local.get $r) (i32.const 0))
+                    (then (i32.const 2048))
+                    (else (i32.const 3072)))))))"#;
+
+    #[derive(Default)]
+    struct RecordingHost {
+        reads: Mutex<Vec<(u8, String)>>,
+    }
+
+    impl WasmHost for RecordingHost {
+        fn kb_read(&self, _skill: &str, slot: u8, key: &str) -> Result<Option<Vec<u8>>, String> {
+            self.reads.lock().unwrap().push((slot, key.to_string()));
+            Ok(Some(b"v".to_vec()))
+        }
+        fn kb_write(&self, _skill: &str, _slot: u8, _key: &str, _value: &[u8]) -> Result<(), String> {
+            Ok(())
+        }
+        fn http_get(&self, _url: &str) -> Result<Vec<u8>, String> {
+            Err("offline".to_string())
+        }
+    }
+
+    fn skill(wat: &str, caps: WasmCapabilities, host: Option<Arc<dyn WasmHost>>) -> WasmSkill {
+        let config = WasmConfig { fuel: 1_000_000, ..WasmConfig::default() };
+        WasmSkill::from_bytes(&wat::parse_str(wat).unwrap(), "test", caps, host, config).unwrap()
+    }
+
+    #[test]
+    fn executes_with_stubbed_wasi() {
+        let s = skill(ECHO, WasmCapabilities::default(), None);
+        assert_eq!(s.execute(serde_json::json!({ "q": 1 })).unwrap(), serde_json::json!({ "ok": true }));
+    }
+
+    #[test]
+    fn fuel_bounds_runaway_loops() {
+        let s = skill(SPIN, WasmCapabilities::default(), None);
+        assert!(matches!(s.execute(Value::Null), Err(SkillError::FuelExhausted(1_000_000))));
+    }
+
+    #[test]
+    fn kb_access_requires_slot_capability() {
+        let host = Arc::new(RecordingHost::default());
+        let denied = skill(KB_READ, WasmCapabilities::default(), Some(host.clone()));
+        assert_eq!(denied.execute(Value::Null).unwrap(), "denied");
+        assert!(host.reads.lock().unwrap().is_empty());
+
+        let caps = WasmCapabilities { kb_slots: vec![5], ..WasmCapabilities::default() };
+        let allowed = skill(KB_READ, caps, Some(host.clone()));
+        assert_eq!(allowed.execute(Value::Null).unwrap(), "found");
+        assert_eq!(host.reads.lock().unwrap().as_slice(), &[(5, "k".to_string())]);
+    }
+
+    #[test]
+    fn rejects_foreign_imports_and_checks_egress() {
+        let foreign = r#"(module (import "env" "system" (func (param i32))) (memory (export "memory") 1))"#;
+        let err = WasmSkill::from_bytes(&wat::parse_str(foreign).unwrap(), "x", WasmCapabilities::default(), None, WasmConfig::default())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("env::system"), "{}", err);
+
+        let caps = WasmCapabilities { network_egress: vec!["*.example.com".into(), "api.tavily.com".into()], ..Default::default() };
+        assert!(caps.allows_url("https://api.tavily.com/search"));
+        assert!(caps.allows_url("https://docs.example.com/x"));
+        assert!(!caps.allows_url("https://example.com.evil.io/"));
+        assert!(!caps.allows_url("file:///etc/passwd"));
+    }
+}
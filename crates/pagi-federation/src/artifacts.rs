@@ -0,0 +1,924 @@
+//! Large task artifacts: a content-addressed blob store on The Creator, filled by chunked,
+//! resumable uploads from satellites and referenced from `TaskResult.artifacts`.
+//!
+//! Layout of the store directory (`PAGI_FEDERATION_ARTIFACT_DIR`):
+//!
+//! - `blobs/<sha256>` and `blobs/<sha256>.json`: verified content and its [`ArtifactMeta`]
+//! - `partial/<node>-<sha256>` and `partial/<node>-<sha256>.json`: an upload in progress, per
+//!   uploading node (`<node>` is a hash of its id), so one node cannot block or corrupt another's
+//!
+//! An upload sends [`ArtifactChunk`]s whose `offset` must equal what the store already holds, so
+//! a satellite that lost its connection asks `GetArtifactStatus` and continues from `received`.
+//! When the last byte arrives the content is hashed; only a matching digest becomes a blob.
+//! Content already stored is not uploaded again.
+//!
+//! [`ArtifactRetention`] bounds the store: blobs expire after `max_age`, the oldest are evicted
+//! beyond `max_total_bytes` (partial uploads count towards it), and abandoned partial uploads
+//! are dropped after `partial_ttl`. A new upload is refused while the sizes declared by uploads
+//! in progress would exceed `max_total_bytes`.
+//!
+//! A stored artifact is served by `FetchArtifact` only to the node that uploaded it and to nodes
+//! a task referencing it was dispatched to.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+
+use crate::ca::{hex, write_atomic};
+use crate::federation::FederationError;
+use crate::phoenix_federation::phoenix_service_client::PhoenixServiceClient;
+use crate::phoenix_federation::{
+    ArtifactChunk, ArtifactRef, ArtifactStatus, ArtifactStatusRequest, FetchArtifactRequest,
+};
+
+/// Bytes per chunk, well below tonic's 4 MiB message limit.
+pub const ARTIFACT_CHUNK_SIZE: usize = 256 * 1024;
+/// How often `MasterServer::serve` applies the retention rules.
+pub const ARTIFACT_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+/// Upload attempts (each resuming where the last stopped) before giving up.
+const UPLOAD_ATTEMPTS: usize = 3;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArtifactError {
+    #[error("artifact store: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid artifact digest {0:?} (expected 64 hex characters)")]
+    InvalidDigest(String),
+    #[error("artifact of {size} bytes exceeds the {limit}-byte limit")]
+    TooLarge { size: u64, limit: u64 },
+    #[error("artifact {sha256}: chunk at offset {offset}, but {expected} bytes are stored")]
+    OffsetMismatch {
+        sha256: String,
+        offset: u64,
+        expected: u64,
+    },
+    #[error("artifact store full: {reserved} bytes of uploads in progress, limit {limit}")]
+    StoreFull { reserved: u64, limit: u64 },
+    #[error("artifact {sha256}: chunk does not fit the declared size of {size} bytes")]
+    SizeMismatch { sha256: String, size: u64 },
+    #[error("artifact {0}: content does not match its digest")]
+    DigestMismatch(String),
+    #[error("artifact {0} not found")]
+    NotFound(String),
+    #[error("corrupt artifact metadata {path}: {reason}")]
+    Corrupt { path: String, reason: String },
+}
+
+/// What the store knows about an artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactMeta {
+    pub sha256: String,
+    pub size: u64,
+    pub name: String,
+    pub content_type: String,
+    /// Node that uploaded it first.
+    pub node_id: String,
+    pub created_at_ms: i64,
+    /// Tasks whose results reference it.
+    #[serde(default)]
+    pub task_ids: Vec<String>,
+}
+
+impl ArtifactMeta {
+    pub fn to_ref(&self) -> ArtifactRef {
+        ArtifactRef {
+            sha256: self.sha256.clone(),
+            size: self.size,
+            name: self.name.clone(),
+            content_type: self.content_type.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactRetention {
+    pub max_age: Duration,
+    pub max_total_bytes: u64,
+    pub partial_ttl: Duration,
+    /// Largest single artifact accepted.
+    pub max_artifact_bytes: u64,
+}
+
+impl Default for ArtifactRetention {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(7 * 24 * 3600),
+            max_total_bytes: 10 * 1024 * 1024 * 1024,
+            partial_ttl: Duration::from_secs(24 * 3600),
+            max_artifact_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl ArtifactRetention {
+    /// `PAGI_FEDERATION_ARTIFACT_MAX_AGE_HOURS` (168), `PAGI_FEDERATION_ARTIFACT_MAX_TOTAL_MB`
+    /// (10240) and `PAGI_FEDERATION_ARTIFACT_MAX_SIZE_MB` (1024).
+    pub fn from_env() -> Self {
+        fn var(name: &str) -> Option<u64> {
+            std::env::var(name)
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+                .filter(|n| *n > 0)
+        }
+        let defaults = Self::default();
+        Self {
+            max_age: var("PAGI_FEDERATION_ARTIFACT_MAX_AGE_HOURS")
+                .map(|h| Duration::from_secs(h * 3600))
+                .unwrap_or(defaults.max_age),
+            max_total_bytes: var("PAGI_FEDERATION_ARTIFACT_MAX_TOTAL_MB")
+                .map(|mb| mb * 1024 * 1024)
+                .unwrap_or(defaults.max_total_bytes),
+            partial_ttl: defaults.partial_ttl,
+            max_artifact_bytes: var("PAGI_FEDERATION_ARTIFACT_MAX_SIZE_MB")
+                .map(|mb| mb * 1024 * 1024)
+                .unwrap_or(defaults.max_artifact_bytes),
+        }
+    }
+}
+
+/// What one [`ArtifactStore::prune`] removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    pub expired: usize,
+    pub evicted: usize,
+    pub partials: usize,
+    pub bytes_freed: u64,
+}
+
+pub struct ArtifactStore {
+    dir: PathBuf,
+    retention: ArtifactRetention,
+    /// Serializes writes; reads of finished blobs need no lock.
+    lock: Mutex<()>,
+}
+
+impl ArtifactStore {
+    pub fn open(dir: &Path) -> Result<Self, ArtifactError> {
+        std::fs::create_dir_all(dir.join("blobs"))?;
+        std::fs::create_dir_all(dir.join("partial"))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            retention: ArtifactRetention::default(),
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// The store at `PAGI_FEDERATION_ARTIFACT_DIR` with [`ArtifactRetention::from_env`]; `None`
+    /// when unset.
+    pub fn from_env() -> Result<Option<Self>, ArtifactError> {
+        match std::env::var("PAGI_FEDERATION_ARTIFACT_DIR") {
+            Ok(dir) if !dir.trim().is_empty() => Ok(Some(
+                Self::open(Path::new(dir.trim()))?.with_retention(ArtifactRetention::from_env()),
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn with_retention(mut self, retention: ArtifactRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn retention(&self) -> &ArtifactRetention {
+        &self.retention
+    }
+
+    fn blob_path(&self, sha256: &str) -> PathBuf {
+        self.dir.join("blobs").join(sha256)
+    }
+
+    fn partial_path(&self, sha256: &str, node_id: &str) -> PathBuf {
+        let node = &sha256_hex(node_id.as_bytes())[..16];
+        self.dir.join("partial").join(format!("{node}-{sha256}"))
+    }
+
+    /// How much of `sha256` is stored, counting only `node_id`'s upload in progress.
+    pub fn status(&self, sha256: &str, node_id: &str) -> Result<ArtifactStatus, ArtifactError> {
+        check_digest(sha256)?;
+        if let Some(meta) = self.meta(sha256)? {
+            return Ok(complete(&meta.sha256, meta.size));
+        }
+        Ok(ArtifactStatus {
+            sha256: sha256.to_string(),
+            received: file_len(&self.partial_path(sha256, node_id))?,
+            complete: false,
+        })
+    }
+
+    /// Sizes declared by the uploads in progress.
+    fn reserved_bytes(&self) -> Result<u64, ArtifactError> {
+        let mut reserved = 0;
+        for entry in std::fs::read_dir(self.dir.join("partial"))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                reserved += read_meta(&path)?.map_or(0, |m| m.size);
+            }
+        }
+        Ok(reserved)
+    }
+
+    /// Appends one chunk of `chunk.node_id`'s upload; the last one verifies the digest and stores
+    /// the blob.
+    pub fn write_chunk(&self, chunk: &ArtifactChunk) -> Result<ArtifactStatus, ArtifactError> {
+        let sha256 = chunk.sha256.as_str();
+        check_digest(sha256)?;
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(meta) = self.meta(sha256)? {
+            return Ok(complete(sha256, meta.size));
+        }
+        if chunk.size > self.retention.max_artifact_bytes {
+            return Err(ArtifactError::TooLarge {
+                size: chunk.size,
+                limit: self.retention.max_artifact_bytes,
+            });
+        }
+
+        let partial = self.partial_path(sha256, &chunk.node_id);
+        let meta_path = json_path(&partial);
+        let received = file_len(&partial)?;
+        let meta = match read_meta(&meta_path)? {
+            Some(meta) => meta,
+            None => {
+                let reserved = self.reserved_bytes()?;
+                if reserved + chunk.size > self.retention.max_total_bytes {
+                    return Err(ArtifactError::StoreFull {
+                        reserved,
+                        limit: self.retention.max_total_bytes,
+                    });
+                }
+                let meta = ArtifactMeta {
+                    sha256: sha256.to_string(),
+                    size: chunk.size,
+                    name: chunk.name.clone(),
+                    content_type: chunk.content_type.clone(),
+                    node_id: chunk.node_id.clone(),
+                    created_at_ms: chrono::Utc::now().timestamp_millis(),
+                    task_ids: Vec::new(),
+                };
+                write_meta(&meta_path, &meta)?;
+                meta
+            }
+        };
+        if chunk.offset != received {
+            return Err(ArtifactError::OffsetMismatch {
+                sha256: sha256.to_string(),
+                offset: chunk.offset,
+                expected: received,
+            });
+        }
+        let received = received + chunk.data.len() as u64;
+        if chunk.size != meta.size || received > meta.size {
+            return Err(ArtifactError::SizeMismatch {
+                sha256: sha256.to_string(),
+                size: meta.size,
+            });
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&partial)?;
+        file.write_all(&chunk.data)?;
+        if received < meta.size {
+            return Ok(ArtifactStatus {
+                sha256: sha256.to_string(),
+                received,
+                complete: false,
+            });
+        }
+
+        file.sync_all()?;
+        drop(file);
+        if digest_file(&partial)?.0 != sha256 {
+            let _ = std::fs::remove_file(&partial);
+            let _ = std::fs::remove_file(&meta_path);
+            return Err(ArtifactError::DigestMismatch(sha256.to_string()));
+        }
+        let meta = ArtifactMeta {
+            created_at_ms: chrono::Utc::now().timestamp_millis(),
+            ..meta
+        };
+        let blob = self.blob_path(sha256);
+        write_meta(&json_path(&blob), &meta)?;
+        std::fs::rename(&partial, &blob)?;
+        std::fs::remove_file(&meta_path)?;
+        Ok(complete(sha256, meta.size))
+    }
+
+    /// Metadata of a stored (complete) artifact.
+    pub fn meta(&self, sha256: &str) -> Result<Option<ArtifactMeta>, ArtifactError> {
+        check_digest(sha256)?;
+        let blob = self.blob_path(sha256);
+        if !blob.exists() {
+            return Ok(None);
+        }
+        read_meta(&json_path(&blob))
+    }
+
+    /// Records that `task_id`'s result references `sha256`; `None` if it is not stored.
+    pub fn link(&self, sha256: &str, task_id: &str) -> Result<Option<ArtifactMeta>, ArtifactError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(mut meta) = self.meta(sha256)? else {
+            return Ok(None);
+        };
+        if !meta.task_ids.iter().any(|t| t == task_id) {
+            meta.task_ids.push(task_id.to_string());
+            write_meta(&json_path(&self.blob_path(sha256)), &meta)?;
+        }
+        Ok(Some(meta))
+    }
+
+    /// Up to `len` bytes of a stored artifact from `offset`.
+    pub fn read_at(&self, sha256: &str, offset: u64, len: usize) -> Result<Vec<u8>, ArtifactError> {
+        check_digest(sha256)?;
+        let mut file = std::fs::File::open(self.blob_path(sha256)).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ArtifactError::NotFound(sha256.to_string()),
+            _ => e.into(),
+        })?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::with_capacity(len);
+        file.take(len as u64).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Every stored artifact, oldest first.
+    pub fn list(&self) -> Result<Vec<ArtifactMeta>, ArtifactError> {
+        let mut all = Vec::new();
+        for entry in std::fs::read_dir(self.dir.join("blobs"))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                all.extend(read_meta(&path)?);
+            }
+        }
+        all.sort_by(|a, b| (a.created_at_ms, &a.sha256).cmp(&(b.created_at_ms, &b.sha256)));
+        Ok(all)
+    }
+
+    /// Applies the retention rules as of `now_ms`.
+    pub fn prune(&self, now_ms: i64) -> Result<PruneReport, ArtifactError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut report = PruneReport::default();
+
+        let partial_ttl_ms = self.retention.partial_ttl.as_millis() as i64;
+        let mut partial_bytes = 0;
+        for entry in std::fs::read_dir(self.dir.join("partial"))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                continue;
+            }
+            let modified_ms = std::fs::metadata(&path)?
+                .modified()?
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or(0);
+            if now_ms - modified_ms >= partial_ttl_ms {
+                report.bytes_freed += file_len(&path)?;
+                std::fs::remove_file(&path)?;
+                let _ = std::fs::remove_file(json_path(&path));
+                report.partials += 1;
+            } else {
+                partial_bytes += file_len(&path)?;
+            }
+        }
+
+        let max_age_ms = self.retention.max_age.as_millis() as i64;
+        let mut kept = Vec::new();
+        for meta in self.list()? {
+            if now_ms - meta.created_at_ms >= max_age_ms {
+                self.remove_blob(&meta)?;
+                report.expired += 1;
+                report.bytes_freed += meta.size;
+            } else {
+                kept.push(meta);
+            }
+        }
+        // Uploads in progress cannot be evicted, so blobs make room for them.
+        let mut total: u64 = partial_bytes + kept.iter().map(|m| m.size).sum::<u64>();
+        for meta in &kept {
+            if total <= self.retention.max_total_bytes {
+                break;
+            }
+            self.remove_blob(meta)?;
+            total -= meta.size;
+            report.evicted += 1;
+            report.bytes_freed += meta.size;
+        }
+        Ok(report)
+    }
+
+    fn remove_blob(&self, meta: &ArtifactMeta) -> Result<(), ArtifactError> {
+        let blob = self.blob_path(&meta.sha256);
+        std::fs::remove_file(json_path(&blob))?;
+        std::fs::remove_file(&blob)?;
+        Ok(())
+    }
+
+    /// Runs [`Self::prune`] every `every` until the store is dropped.
+    pub fn spawn_pruner(self: &Arc<Self>, every: Duration) -> tokio::task::JoinHandle<()> {
+        let weak = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                let Some(store) = weak.upgrade() else { break };
+                let now = chrono::Utc::now().timestamp_millis();
+                match tokio::task::spawn_blocking(move || store.prune(now)).await {
+                    Ok(Ok(report)) if report != PruneReport::default() => {
+                        tracing::info!(?report, "Pruned federation artifacts")
+                    }
+                    Ok(Err(e)) => tracing::warn!(error = %e, "Artifact pruning failed"),
+                    _ => {}
+                }
+            }
+        })
+    }
+}
+
+/// Content a satellite uploads: in memory or a file (read chunk by chunk).
+#[derive(Debug, Clone)]
+pub enum ArtifactSource {
+    Bytes(Arc<Vec<u8>>),
+    File(PathBuf),
+}
+
+impl From<Vec<u8>> for ArtifactSource {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Bytes(Arc::new(data))
+    }
+}
+
+impl From<Arc<Vec<u8>>> for ArtifactSource {
+    fn from(data: Arc<Vec<u8>>) -> Self {
+        Self::Bytes(data)
+    }
+}
+
+impl From<PathBuf> for ArtifactSource {
+    fn from(path: PathBuf) -> Self {
+        Self::File(path)
+    }
+}
+
+impl ArtifactSource {
+    /// Hex SHA-256 and size.
+    pub fn digest(&self) -> std::io::Result<(String, u64)> {
+        match self {
+            Self::Bytes(data) => Ok((sha256_hex(data), data.len() as u64)),
+            Self::File(path) => digest_file(path),
+        }
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Bytes(data) => {
+                let start = (offset as usize).min(data.len());
+                Ok(data[start..(start + len).min(data.len())].to_vec())
+            }
+            Self::File(path) => {
+                let mut file = std::fs::File::open(path)?;
+                file.seek(SeekFrom::Start(offset))?;
+                let mut data = Vec::with_capacity(len);
+                file.take(len as u64).read_to_end(&mut data)?;
+                Ok(data)
+            }
+        }
+    }
+}
+
+/// Uploads `source` for `node_id`/`task_id`, resuming after interruptions, and returns the
+/// reference for `TaskResult.artifacts`.
+pub async fn upload_artifact(
+    client: &mut PhoenixServiceClient<Channel>,
+    node_id: &str,
+    task_id: &str,
+    name: &str,
+    content_type: &str,
+    source: ArtifactSource,
+) -> Result<ArtifactRef, FederationError> {
+    let (sha256, size) = source
+        .digest()
+        .map_err(|e| FederationError::Artifact(ArtifactError::Io(e)))?;
+    let reference = ArtifactRef {
+        sha256: sha256.clone(),
+        size,
+        name: name.to_string(),
+        content_type: content_type.to_string(),
+    };
+    let mut last_error = String::new();
+    for _ in 0..UPLOAD_ATTEMPTS {
+        let status = client
+            .get_artifact_status(ArtifactStatusRequest {
+                sha256: sha256.clone(),
+                node_id: node_id.to_string(),
+            })
+            .await
+            .map_err(transfer_error)?
+            .into_inner();
+        if status.complete {
+            return Ok(reference);
+        }
+
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        let template = ArtifactChunk {
+            sha256: sha256.clone(),
+            size,
+            node_id: node_id.to_string(),
+            task_id: task_id.to_string(),
+            name: name.to_string(),
+            content_type: content_type.to_string(),
+            ..Default::default()
+        };
+        let source = source.clone();
+        let producer = tokio::spawn(async move {
+            let mut offset = status.received;
+            loop {
+                let data = source.read_at(offset, ARTIFACT_CHUNK_SIZE)?;
+                let len = data.len() as u64;
+                let chunk = ArtifactChunk {
+                    offset,
+                    data,
+                    ..template.clone()
+                };
+                if tx.send(chunk).await.is_err() {
+                    break;
+                }
+                offset += len;
+                if len == 0 || offset >= size {
+                    break;
+                }
+            }
+            Ok::<(), std::io::Error>(())
+        });
+        let uploaded = client
+            .upload_artifact(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await;
+        if let Ok(Err(e)) = producer.await {
+            return Err(FederationError::Artifact(ArtifactError::Io(e)));
+        }
+        match uploaded {
+            Ok(status) if status.get_ref().complete => return Ok(reference),
+            Ok(status) => {
+                last_error = format!("stopped at {} of {} bytes", status.get_ref().received, size)
+            }
+            Err(status) => last_error = status.message().to_string(),
+        }
+    }
+    Err(FederationError::ArtifactTransfer(format!(
+        "upload of {} failed after {} attempts: {}",
+        sha256, UPLOAD_ATTEMPTS, last_error
+    )))
+}
+
+/// Downloads artifact `sha256` of `task_id` into `path` for `node_id`, continuing a previous
+/// partial download, and checks the digest. Returns the artifact's size.
+pub async fn fetch_artifact(
+    client: &mut PhoenixServiceClient<Channel>,
+    node_id: &str,
+    task_id: &str,
+    sha256: &str,
+    path: &Path,
+) -> Result<u64, FederationError> {
+    let io = |e: std::io::Error| FederationError::Artifact(ArtifactError::Io(e));
+    let offset = file_len(path).map_err(FederationError::Artifact)?;
+    let mut stream = client
+        .fetch_artifact(FetchArtifactRequest {
+            sha256: sha256.to_string(),
+            offset,
+            node_id: node_id.to_string(),
+            task_id: task_id.to_string(),
+        })
+        .await
+        .map_err(transfer_error)?
+        .into_inner();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io)?;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(transfer_error)?;
+        file.write_all(&chunk.data).map_err(io)?;
+    }
+    file.sync_all().map_err(io)?;
+    let (digest, size) = digest_file(path).map_err(io)?;
+    if digest != sha256 {
+        let _ = std::fs::remove_file(path);
+        return Err(FederationError::Artifact(ArtifactError::DigestMismatch(
+            sha256.to_string(),
+        )));
+    }
+    Ok(size)
+}
+
+fn transfer_error(status: tonic::Status) -> FederationError {
+    FederationError::ArtifactTransfer(status.message().to_string())
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn digest_file(path: &Path) -> std::io::Result<(String, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((hex(&hasher.finalize()), size))
+}
+
+fn check_digest(sha256: &str) -> Result<(), ArtifactError> {
+    if sha256.len() == 64
+        && sha256
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        Ok(())
+    } else {
+        Err(ArtifactError::InvalidDigest(sha256.to_string()))
+    }
+}
+
+fn complete(sha256: &str, size: u64) -> ArtifactStatus {
+    ArtifactStatus {
+        sha256: sha256.to_string(),
+        received: size,
+        complete: true,
+    }
+}
+
+fn json_path(path: &Path) -> PathBuf {
+    path.with_extension("json")
+}
+
+fn file_len(path: &Path) -> Result<u64, ArtifactError> {
+    match std::fs::metadata(path) {
+        Ok(m) => Ok(m.len()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read_meta(path: &Path) -> Result<Option<ArtifactMeta>, ArtifactError> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| ArtifactError::Corrupt {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })
+}
+
+fn write_meta(path: &Path, meta: &ArtifactMeta) -> Result<(), ArtifactError> {
+    let bytes = serde_json::to_vec_pretty(meta).map_err(|e| ArtifactError::Corrupt {
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    })?;
+    write_atomic(path, &bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::{FederationHandle, MasterServer, MasterState, TaskOptions};
+    use crate::phoenix_federation::TaskResult;
+    use crate::scheduler::LeastLoaded;
+    use crate::testing::{FederationHarness, SatelliteBehaviour};
+
+    fn tempdir() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "pagi_federation_artifacts_{}",
+            uuid::Uuid::new_v4()
+        ))
+    }
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    fn chunk(data: &[u8], offset: usize, len: usize) -> ArtifactChunk {
+        let end = (offset + len).min(data.len());
+        ArtifactChunk {
+            sha256: sha256_hex(data),
+            size: data.len() as u64,
+            offset: offset as u64,
+            data: data[offset..end].to_vec(),
+            node_id: "a".into(),
+            task_id: "t1".into(),
+            name: "report.bin".into(),
+            content_type: "application/octet-stream".into(),
+        }
+    }
+
+    #[test]
+    fn uploads_resume_verify_and_expire() {
+        let dir = tempdir();
+        let store = ArtifactStore::open(&dir).unwrap();
+        let data = payload(1000);
+        let sha = sha256_hex(&data);
+
+        let status = store.write_chunk(&chunk(&data, 0, 400)).unwrap();
+        assert_eq!((status.received, status.complete), (400, false));
+        // A repeated or skipped chunk is refused; the status says where to continue.
+        assert!(matches!(
+            store.write_chunk(&chunk(&data, 0, 400)),
+            Err(ArtifactError::OffsetMismatch { expected: 400, .. })
+        ));
+        assert_eq!(store.status(&sha, "a").unwrap().received, 400);
+        // Another node's upload of the same digest is its own.
+        assert_eq!(store.status(&sha, "b").unwrap().received, 0);
+        let status = store.write_chunk(&chunk(&data, 400, 600)).unwrap();
+        assert!(status.complete);
+        assert_eq!(store.read_at(&sha, 900, 500).unwrap(), data[900..]);
+        // Stored content is not uploaded again.
+        assert!(store.write_chunk(&chunk(&data, 0, 10)).unwrap().complete);
+        assert!(store.link(&sha, "t1").unwrap().is_some());
+        assert_eq!(store.meta(&sha).unwrap().unwrap().task_ids, vec!["t1"]);
+
+        // Content that does not hash to the declared digest is discarded.
+        let mut forged = chunk(&payload(10), 0, 10);
+        forged.data[0] ^= 1;
+        assert!(matches!(
+            store.write_chunk(&forged),
+            Err(ArtifactError::DigestMismatch(_))
+        ));
+        assert_eq!(store.status(&forged.sha256, "a").unwrap().received, 0);
+        assert!(matches!(
+            store.status("not-a-digest", "a"),
+            Err(ArtifactError::InvalidDigest(_))
+        ));
+
+        // Only references to stored artifacts survive into a result.
+        let state = MasterState::new().with_artifacts(Arc::new(ArtifactStore::open(&dir).unwrap()));
+        let result = state.link_artifacts(TaskResult {
+            task_id: "t2".into(),
+            artifacts: vec![
+                ArtifactRef {
+                    sha256: sha.clone(),
+                    ..Default::default()
+                },
+                ArtifactRef {
+                    sha256: forged.sha256.clone(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        });
+        assert_eq!(result.artifacts.len(), 1);
+        assert_eq!(result.artifacts[0].size, 1000);
+        assert_eq!(result.artifacts[0].name, "report.bin");
+
+        // Newer than the first blob, so eviction order is by age.
+        std::thread::sleep(Duration::from_millis(5));
+        let small = payload(300);
+        store.write_chunk(&chunk(&small, 0, 300)).unwrap();
+        store.write_chunk(&chunk(&payload(50), 0, 20)).unwrap();
+        let now = chrono::Utc::now().timestamp_millis();
+        let store = store.with_retention(ArtifactRetention {
+            max_total_bytes: 500,
+            ..ArtifactRetention::default()
+        });
+        // Over the size budget the oldest blob goes first; the fresh partial stays.
+        let report = store.prune(now).unwrap();
+        assert_eq!((report.evicted, report.partials), (1, 0));
+        assert!(store.meta(&sha).unwrap().is_none());
+        assert!(store.meta(&sha256_hex(&small)).unwrap().is_some());
+
+        let later = now + ArtifactRetention::default().max_age.as_millis() as i64;
+        let report = store.prune(later).unwrap();
+        assert_eq!((report.expired, report.partials), (1, 1));
+        assert!(store.list().unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn partial_uploads_are_per_node_and_count_against_the_budget() {
+        let dir = tempdir();
+        let store = ArtifactStore::open(&dir)
+            .unwrap()
+            .with_retention(ArtifactRetention {
+                max_total_bytes: 1500,
+                ..ArtifactRetention::default()
+            });
+        let data = payload(1000);
+        let sha = sha256_hex(&data);
+
+        // A first chunk declaring a bogus size does not claim the digest for everyone.
+        let mut squat = chunk(&data, 0, 10);
+        squat.node_id = "b".into();
+        squat.size = 20;
+        store.write_chunk(&squat).unwrap();
+        store.write_chunk(&chunk(&data, 0, 400)).unwrap();
+        // Declared sizes in progress (20 + 1000) leave no room for another 999.
+        let other = payload(999);
+        assert!(matches!(
+            store.write_chunk(&chunk(&other, 0, 100)),
+            Err(ArtifactError::StoreFull { reserved: 1020, .. })
+        ));
+        assert!(store.write_chunk(&chunk(&data, 400, 600)).unwrap().complete);
+        assert_eq!(store.meta(&sha).unwrap().unwrap().node_id, "a");
+
+        // On-disk partials count too: the blob is evicted to make room for them.
+        let big = payload(1200);
+        store.write_chunk(&chunk(&big, 0, 600)).unwrap();
+        let report = store.prune(chrono::Utc::now().timestamp_millis()).unwrap();
+        assert_eq!((report.evicted, report.partials), (1, 0));
+        assert_eq!(store.status(&sha256_hex(&big), "a").unwrap().received, 600);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn satellite_uploads_large_result_and_client_fetches_it() {
+        let dir = tempdir();
+        let store = Arc::new(ArtifactStore::open(&dir.join("store")).unwrap());
+        let state = MasterState::new()
+            .with_artifacts(Arc::clone(&store))
+            .with_clock(FederationHarness::clock());
+        let handle = FederationHandle::with_scheduler(Arc::new(state), Arc::new(LeastLoaded))
+            .with_liveness(FederationHarness::liveness());
+        let harness = FederationHarness::start(MasterServer::from_handle(handle));
+
+        // Three chunks of ARTIFACT_CHUNK_SIZE.
+        let data = payload(700_000);
+        let sha = sha256_hex(&data);
+        let _a = harness.satellite(
+            "a",
+            "RedTeam",
+            &["scan"],
+            SatelliteBehaviour::default().attaching("capture.pcap", data.clone()),
+        );
+        assert!(harness.wait_connected(&["a"]).await);
+        let result = harness
+            .submit("scan", TaskOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(result.success, "{}", result.summary);
+        assert_eq!(result.artifacts.len(), 1);
+        let reference = &result.artifacts[0];
+        assert_eq!(
+            (reference.sha256.as_str(), reference.size),
+            (sha.as_str(), 700_000)
+        );
+        assert_eq!(reference.name, "capture.pcap");
+        assert_eq!(
+            store.meta(&sha).unwrap().unwrap().task_ids,
+            vec![result.task_id.clone()]
+        );
+
+        // A download that stopped part way continues from the bytes already on disk.
+        let target = dir.join("capture.pcap");
+        std::fs::write(&target, &data[..100_000]).unwrap();
+        let mut client = harness.client().await.unwrap();
+        assert_eq!(
+            fetch_artifact(&mut client, "a", &result.task_id, &sha, &target)
+                .await
+                .unwrap(),
+            700_000
+        );
+        assert_eq!(std::fs::read(&target).unwrap(), data);
+
+        // Only nodes the task was given to may read its artifacts.
+        for (node_id, task_id) in [("b", result.task_id.as_str()), ("b", "other")] {
+            assert!(
+                fetch_artifact(&mut client, node_id, task_id, &sha, &dir.join("stolen"))
+                    .await
+                    .is_err()
+            );
+        }
+        let missing = sha256_hex(b"never uploaded");
+        assert!(fetch_artifact(
+            &mut client,
+            "a",
+            &result.task_id,
+            &missing,
+            &dir.join("missing")
+        )
+        .await
+        .is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,22 @@
+//! `pagi-federation`: certificate authority and enrollment tooling for The Creator and its
+//! satellites. See `pagi_federation::cli::USAGE`.
+
+use pagi_federation::cli::{run, Command, USAGE};
+
+#[tokio::main]
+async fn main() {
+    let command = match Command::parse(std::env::args_os().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("pagi-federation: {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    match run(command).await {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("pagi-federation: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Federation certificate authority: enrollment tokens, satellite certificates and the CRL.
+//!
+//! Everything lives in one directory (`PAGI_FEDERATION_CA_DIR`, created by
+//! `pagi-federation init-ca`):
+//!
+//! | Path | Contents |
+//! |------|----------|
+//! | `ca.pem`, `ca.key` | CA certificate and key; `ca.pem` is the client CA of The Creator. |
+//! | `server.pem`, `server.key` | The Creator's server certificate, signed by the same CA. |
+//! | `crl.pem` | Revoked satellite certificates, re-read by the server verifier on change. |
+//! | `tokens/<sha256>.json` | Unredeemed enrollment tokens; only the token's hash is stored. |
+//! | `issued/<serial>.json` | Every satellite certificate issued, and when it was revoked. |
+//!
+//! One file per record, so the CLI and a running master can share the directory: redeeming a
+//! token removes its file, which succeeds for exactly one caller.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rand::RngCore;
+use rcgen::{
+    BasicConstraints, CertificateParams, CertificateRevocationListParams,
+    CertificateSigningRequestParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyIdMethod, KeyPair,
+    KeyUsagePurpose, RevocationReason, RevokedCertParams, SanType, SerialNumber,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+
+use crate::mtls::PeerIdentity;
+
+/// Lifetime of a satellite certificate; satellites renew once two thirds of it have passed.
+pub const DEFAULT_CERT_TTL: Duration = Duration::from_secs(24 * 3600);
+/// Lifetime of an unredeemed enrollment token.
+pub const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(24 * 3600);
+/// How long each CRL is valid; `pagi-federation crl` re-signs it.
+const CRL_VALIDITY: Duration = Duration::from_secs(30 * 24 * 3600);
+/// Lifetime of the CA and server certificates made by `init`.
+const CA_VALIDITY: Duration = Duration::from_secs(10 * 365 * 24 * 3600);
+/// Backdating of issued certificates, for clock skew between nodes.
+const CLOCK_SKEW: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum CaError {
+    #[error("CA storage: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("certificate: {0}")]
+    Certificate(#[from] rcgen::Error),
+    #[error("{0} already holds a CA")]
+    AlreadyInitialised(String),
+    #[error("corrupt CA record {path}: {reason}")]
+    Corrupt { path: String, reason: String },
+    #[error("invalid or already used enrollment token")]
+    InvalidToken,
+    #[error("enrollment token expired")]
+    TokenExpired,
+    #[error("token was issued for node {expected}, not {requested}")]
+    NodeMismatch { expected: String, requested: String },
+    #[error("certificate {0} was not issued by this CA")]
+    UnknownCertificate(String),
+    #[error("certificate {serial} of node {node_id} is revoked")]
+    Revoked { serial: String, node_id: String },
+}
+
+/// An unredeemed enrollment token, stored under the hash of the token.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnrollmentToken {
+    pub node_id: String,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+/// A satellite certificate issued by the CA.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssuedCert {
+    /// Lowercase hex, as in [`PeerIdentity::serial`].
+    pub serial: String,
+    pub node_id: String,
+    pub issued_at_ms: i64,
+    pub not_after_ms: i64,
+    pub revoked_at_ms: Option<i64>,
+}
+
+/// A freshly signed satellite certificate.
+#[derive(Debug, Clone)]
+pub struct SignedCert {
+    pub cert_pem: String,
+    pub serial: String,
+    pub not_after_ms: i64,
+}
+
+pub struct CertificateAuthority {
+    dir: PathBuf,
+    cert: rcgen::Certificate,
+    key: KeyPair,
+    cert_ttl: Duration,
+}
+
+impl CertificateAuthority {
+    /// Creates a CA named `name` in `dir`, a server certificate for `server_names` and an empty
+    /// CRL. Fails if `dir` already holds a CA.
+    pub fn init(dir: &Path, name: &str, server_names: &[String]) -> Result<Self, CaError> {
+        if dir.join("ca.pem").exists() {
+            return Err(CaError::AlreadyInitialised(dir.display().to_string()));
+        }
+        std::fs::create_dir_all(dir.join("tokens"))?;
+        std::fs::create_dir_all(dir.join("issued"))?;
+
+        let now = OffsetDateTime::now_utc();
+        let key = KeyPair::generate()?;
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+        params.key_usages = vec![
+            KeyUsagePurpose::KeyCertSign,
+            KeyUsagePurpose::CrlSign,
+            KeyUsagePurpose::DigitalSignature,
+        ];
+        params.not_before = now - CLOCK_SKEW;
+        params.not_after = now + CA_VALIDITY;
+        let cert = params.self_signed(&key)?;
+        write_secret(&dir.join("ca.key"), key.serialize_pem().as_bytes())?;
+        write_atomic(&dir.join("ca.pem"), cert.pem().as_bytes())?;
+
+        let server_key = KeyPair::generate()?;
+        let mut server = CertificateParams::new(server_names.to_vec())?;
+        if let Some(first) = server_names.first() {
+            server
+                .distinguished_name
+                .push(DnType::CommonName, first.as_str());
+        }
+        server.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+        server.use_authority_key_identifier_extension = true;
+        server.not_before = now - CLOCK_SKEW;
+        server.not_after = now + CA_VALIDITY;
+        let server_cert = server.signed_by(&server_key, &cert, &key)?;
+        write_secret(
+            &dir.join("server.key"),
+            server_key.serialize_pem().as_bytes(),
+        )?;
+        write_atomic(&dir.join("server.pem"), server_cert.pem().as_bytes())?;
+
+        let ca = Self {
+            dir: dir.to_path_buf(),
+            cert,
+            key,
+            cert_ttl: DEFAULT_CERT_TTL,
+        };
+        ca.write_crl()?;
+        Ok(ca)
+    }
+
+    pub fn open(dir: &Path) -> Result<Self, CaError> {
+        let key = KeyPair::from_pem(&std::fs::read_to_string(dir.join("ca.key"))?)?;
+        let params =
+            CertificateParams::from_ca_cert_pem(&std::fs::read_to_string(dir.join("ca.pem"))?)?;
+        // Same subject and key as ca.pem, which is all that signing needs.
+        let cert = params.self_signed(&key)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            cert,
+            key,
+            cert_ttl: DEFAULT_CERT_TTL,
+        })
+    }
+
+    /// The CA in `PAGI_FEDERATION_CA_DIR`, with `PAGI_FEDERATION_CERT_TTL_HOURS` if set.
+    pub fn from_env() -> Result<Option<Self>, CaError> {
+        let Some(dir) = std::env::var("PAGI_FEDERATION_CA_DIR")
+            .ok()
+            .filter(|d| !d.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let mut ca = Self::open(Path::new(dir.trim()))?;
+        if let Some(hours) = std::env::var("PAGI_FEDERATION_CERT_TTL_HOURS")
+            .ok()
+            .and_then(|h| h.trim().parse::<u64>().ok())
+            .filter(|h| *h > 0)
+        {
+            ca.cert_ttl = Duration::from_secs(hours * 3600);
+        }
+        Ok(Some(ca))
+    }
+
+    pub fn with_cert_ttl(mut self, ttl: Duration) -> Self {
+        self.cert_ttl = ttl;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn ca_path(&self) -> PathBuf {
+        self.dir.join("ca.pem")
+    }
+
+    pub fn server_cert_path(&self) -> PathBuf {
+        self.dir.join("server.pem")
+    }
+
+    pub fn server_key_path(&self) -> PathBuf {
+        self.dir.join("server.key")
+    }
+
+    pub fn crl_path(&self) -> PathBuf {
+        self.dir.join("crl.pem")
+    }
+
+    /// A one-time token that lets `node_id` enroll within `ttl`. Only its hash is stored.
+    pub fn issue_token(&self, node_id: &str, ttl: Duration) -> Result<String, CaError> {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = hex(&bytes);
+        let now = now_ms();
+        let record = EnrollmentToken {
+            node_id: node_id.to_string(),
+            created_at_ms: now,
+            expires_at_ms: now + ttl.as_millis() as i64,
+        };
+        write_record(&self.token_path(&token), &record)?;
+        Ok(token)
+    }
+
+    /// Redeems `token` (once) and signs `csr_pem` for the token's node. `node_id`, if not empty,
+    /// must be the node the token was issued for.
+    pub fn redeem(&self, token: &str, node_id: &str, csr_pem: &str) -> Result<SignedCert, CaError> {
+        let path = self.token_path(token.trim());
+        let record: EnrollmentToken = match read_record(&path) {
+            Ok(record) => record,
+            Err(CaError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(CaError::InvalidToken)
+            }
+            Err(e) => return Err(e),
+        };
+        if !node_id.is_empty() && node_id != record.node_id {
+            return Err(CaError::NodeMismatch {
+                expected: record.node_id,
+                requested: node_id.to_string(),
+            });
+        }
+        // Whoever removes the file redeemed the token.
+        if let Err(e) = std::fs::remove_file(&path) {
+            return Err(match e.kind() {
+                std::io::ErrorKind::NotFound => CaError::InvalidToken,
+                _ => e.into(),
+            });
+        }
+        if record.expires_at_ms < now_ms() {
+            return Err(CaError::TokenExpired);
+        }
+        self.sign(&record.node_id, csr_pem)
+    }
+
+    /// Signs a new certificate for the node that holds `current`, unless it is revoked.
+    pub fn renew(&self, current: &PeerIdentity, csr_pem: &str) -> Result<SignedCert, CaError> {
+        let issued = self
+            .issued_cert(&current.serial)?
+            .ok_or_else(|| CaError::UnknownCertificate(current.serial.clone()))?;
+        if issued.revoked_at_ms.is_some() {
+            return Err(CaError::Revoked {
+                serial: issued.serial,
+                node_id: issued.node_id,
+            });
+        }
+        self.sign(&issued.node_id, csr_pem)
+    }
+
+    /// Revokes every unrevoked certificate of `node_id` and rewrites the CRL. Returns how many
+    /// were revoked.
+    pub fn revoke(&self, node_id: &str) -> Result<usize, CaError> {
+        let now = now_ms();
+        let mut revoked = 0;
+        for mut cert in self.issued()? {
+            if cert.node_id == node_id && cert.revoked_at_ms.is_none() {
+                cert.revoked_at_ms = Some(now);
+                write_record(&self.issued_path(&cert.serial), &cert)?;
+                revoked += 1;
+            }
+        }
+        self.write_crl()?;
+        Ok(revoked)
+    }
+
+    /// Every certificate issued, oldest first.
+    pub fn issued(&self) -> Result<Vec<IssuedCert>, CaError> {
+        let mut all = Vec::new();
+        for entry in std::fs::read_dir(self.dir.join("issued"))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                all.push(read_record::<IssuedCert>(&path)?);
+            }
+        }
+        all.sort_by_key(|c| (c.issued_at_ms, c.serial.clone()));
+        Ok(all)
+    }
+
+    pub fn issued_cert(&self, serial: &str) -> Result<Option<IssuedCert>, CaError> {
+        match read_record(&self.issued_path(serial)) {
+            Ok(cert) => Ok(Some(cert)),
+            Err(CaError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Signs a fresh CRL listing every revoked certificate that has not yet expired.
+    pub fn write_crl(&self) -> Result<(), CaError> {
+        let now = OffsetDateTime::now_utc();
+        let mut revoked_certs = Vec::new();
+        for cert in self.issued()? {
+            let Some(revoked_at_ms) = cert.revoked_at_ms else {
+                continue;
+            };
+            if cert.not_after_ms < now_ms() {
+                continue;
+            }
+            let serial = unhex(&cert.serial).ok_or_else(|| CaError::Corrupt {
+                path: self.issued_path(&cert.serial).display().to_string(),
+                reason: "serial is not hex".to_string(),
+            })?;
+            revoked_certs.push(RevokedCertParams {
+                serial_number: SerialNumber::from_slice(&serial),
+                revocation_time: datetime(revoked_at_ms),
+                reason_code: Some(RevocationReason::CessationOfOperation),
+                invalidity_date: None,
+            });
+        }
+        let crl = CertificateRevocationListParams {
+            this_update: now,
+            next_update: now + CRL_VALIDITY,
+            crl_number: SerialNumber::from(now_ms() as u64),
+            issuing_distribution_point: None,
+            revoked_certs,
+            key_identifier_method: KeyIdMethod::Sha256,
+        }
+        .signed_by(&self.cert, &self.key)?;
+        write_atomic(&self.crl_path(), crl.pem()?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Signs `csr_pem` as a client certificate for `node_id`; the CSR contributes only its key.
+    fn sign(&self, node_id: &str, csr_pem: &str) -> Result<SignedCert, CaError> {
+        let mut csr = CertificateSigningRequestParams::from_pem(csr_pem)?;
+        let now = OffsetDateTime::now_utc();
+        let mut serial = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut serial);
+        // Positive, and without a leading zero byte, so the DER serial is these exact bytes.
+        serial[0] = (serial[0] & 0x7f).max(1);
+
+        let mut params = CertificateParams::default();
+        params.distinguished_name.push(DnType::CommonName, node_id);
+        if let Ok(name) = node_id.try_into() {
+            params.subject_alt_names = vec![SanType::DnsName(name)];
+        }
+        params.serial_number = Some(SerialNumber::from_slice(&serial));
+        params.not_before = now - CLOCK_SKEW;
+        params.not_after = now + self.cert_ttl;
+        params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        params.use_authority_key_identifier_extension = true;
+        csr.params = params;
+        let cert = csr.signed_by(&self.cert, &self.key)?;
+
+        let issued = IssuedCert {
+            serial: hex(&serial),
+            node_id: node_id.to_string(),
+            issued_at_ms: now_ms(),
+            not_after_ms: (now + self.cert_ttl).unix_timestamp() * 1000,
+            revoked_at_ms: None,
+        };
+        write_record(&self.issued_path(&issued.serial), &issued)?;
+        Ok(SignedCert {
+            cert_pem: cert.pem(),
+            serial: issued.serial,
+            not_after_ms: issued.not_after_ms,
+        })
+    }
+
+    fn token_path(&self, token: &str) -> PathBuf {
+        let digest = Sha256::digest(token.as_bytes());
+        self.dir
+            .join("tokens")
+            .join(format!("{}.json", hex(&digest)))
+    }
+
+    fn issued_path(&self, serial: &str) -> PathBuf {
+        // Serials come from certificates; keep them from naming other paths.
+        let serial: String = serial.chars().filter(char::is_ascii_hexdigit).collect();
+        self.dir.join("issued").join(format!("{}.json", serial))
+    }
+}
+
+/// A new key pair and a CSR for it (PEM), as a satellite sends in `EnrollRequest`.
+pub fn generate_csr(node_id: &str) -> Result<(KeyPair, String), CaError> {
+    let key = KeyPair::generate()?;
+    let mut params = CertificateParams::default();
+    params.distinguished_name.push(DnType::CommonName, node_id);
+    let csr = params.serialize_request(&key)?.pem()?;
+    Ok((key, csr))
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn datetime(ms: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(ms / 1000).unwrap_or_else(|_| OffsetDateTime::now_utc())
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn read_record<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, CaError> {
+    let bytes = std::fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|e| CaError::Corrupt {
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    })
+}
+
+fn write_record<T: Serialize>(path: &Path, record: &T) -> Result<(), CaError> {
+    let bytes = serde_json::to_vec_pretty(record).map_err(|e| CaError::Corrupt {
+        path: path.display().to_string(),
+        reason: e.to_string(),
+    })?;
+    write_atomic(path, &bytes)?;
+    Ok(())
+}
+
+/// Writes through a temporary file and a rename, so readers never see a partial file.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Like [`write_atomic`], with the file readable by its owner only from the moment it exists.
+pub(crate) fn write_secret(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x509_parser::prelude::FromDer;
+
+    #[test]
+    fn tokens_are_single_use_and_revocation_reaches_the_crl() {
+        let dir = std::env::temp_dir().join(format!("pagi_federation_ca_{}", uuid::Uuid::new_v4()));
+        let ca = CertificateAuthority::init(&dir, "test ca", &["localhost".to_string()]).unwrap();
+        assert!(matches!(
+            CertificateAuthority::init(&dir, "again", &[]),
+            Err(CaError::AlreadyInitialised(_))
+        ));
+        let ca_again = CertificateAuthority::open(&dir).unwrap();
+
+        let token = ca.issue_token("kali-1", DEFAULT_TOKEN_TTL).unwrap();
+        let (_, csr) = generate_csr("whatever-the-satellite-asks-for").unwrap();
+        assert!(matches!(
+            ca.redeem(&token, "kali-2", &csr),
+            Err(CaError::NodeMismatch { .. })
+        ));
+        let signed = ca_again.redeem(&token, "kali-1", &csr).unwrap();
+        assert!(matches!(
+            ca.redeem(&token, "kali-1", &csr),
+            Err(CaError::InvalidToken)
+        ));
+
+        let der = rustls_pemfile::certs(&mut signed.cert_pem.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap();
+        let identity = PeerIdentity::from_der(der.as_ref()).unwrap();
+        assert_eq!(identity.primary(), Some("kali-1"));
+        assert_eq!(identity.serial, signed.serial);
+
+        let expired = ca.issue_token("kali-3", Duration::ZERO).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(matches!(
+            ca.redeem(&expired, "", &csr),
+            Err(CaError::TokenExpired)
+        ));
+
+        let renewed = ca.renew(&identity, &csr).unwrap();
+        assert_ne!(renewed.serial, signed.serial);
+        assert_eq!(ca.revoke("kali-1").unwrap(), 2);
+        assert!(matches!(
+            ca.renew(&identity, &csr),
+            Err(CaError::Revoked { .. })
+        ));
+
+        let crl_pem = std::fs::read(ca.crl_path()).unwrap();
+        let crl_der = rustls_pemfile::crls(&mut crl_pem.as_slice())
+            .next()
+            .unwrap()
+            .unwrap();
+        let (_, crl) =
+            x509_parser::revocation_list::CertificateRevocationList::from_der(crl_der.as_ref())
+                .unwrap();
+        let listed: Vec<String> = crl
+            .iter_revoked_certificates()
+            .map(|r| hex(r.raw_serial()))
+            .collect();
+        assert!(listed.contains(&signed.serial) && listed.contains(&renewed.serial));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
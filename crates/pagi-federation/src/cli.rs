@@ -0,0 +1,270 @@
+//! `pagi-federation` command line: CA setup, enrollment tokens, satellite enrollment and
+//! revocation. See [`USAGE`].
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::ca::{CertificateAuthority, DEFAULT_TOKEN_TTL};
+use crate::enrollment::SatelliteCredentials;
+
+pub const USAGE: &str = "\
+usage: pagi-federation <command> [options]
+
+The Creator (CA directory from --dir or PAGI_FEDERATION_CA_DIR):
+  init-ca   [--dir DIR] [--name NAME] [--server-name HOST]...   create CA, server cert, empty CRL
+  token     [--dir DIR] --node ID [--ttl-hours N]                 one-time enrollment token
+  revoke    [--dir DIR] --node ID                                 revoke a node's certificates
+  crl       [--dir DIR]                                           re-sign the CRL
+  list      [--dir DIR]                                           issued certificates
+
+Satellite:
+  enroll    --creator URL --server-ca PEM [--domain HOST] --out DIR --node ID --token TOKEN
+  renew     --creator URL --server-ca PEM [--domain HOST] --out DIR";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    InitCa {
+        dir: PathBuf,
+        name: String,
+        server_names: Vec<String>,
+    },
+    Token {
+        dir: PathBuf,
+        node_id: String,
+        ttl: Duration,
+    },
+    Revoke {
+        dir: PathBuf,
+        node_id: String,
+    },
+    Crl {
+        dir: PathBuf,
+    },
+    List {
+        dir: PathBuf,
+    },
+    Enroll {
+        creator: String,
+        credentials: SatelliteCredentialsArgs,
+        node_id: String,
+        token: String,
+    },
+    Renew {
+        creator: String,
+        credentials: SatelliteCredentialsArgs,
+    },
+}
+
+/// Where a satellite keeps its certificate and how it verifies The Creator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatelliteCredentialsArgs {
+    pub out: PathBuf,
+    pub server_ca: PathBuf,
+    pub domain: String,
+}
+
+impl Command {
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Self, String> {
+        let mut it = args.into_iter();
+        let command = it
+            .next()
+            .map(|c| c.to_string_lossy().into_owned())
+            .ok_or_else(|| "missing command".to_string())?;
+        let mut flags: Vec<(String, String)> = Vec::new();
+        while let Some(arg) = it.next() {
+            let flag = arg.to_string_lossy().into_owned();
+            let value = it
+                .next()
+                .ok_or_else(|| format!("{} requires a value", flag))?;
+            flags.push((flag, value.to_string_lossy().into_owned()));
+        }
+        let get = |name: &str| {
+            flags
+                .iter()
+                .rev()
+                .find(|(f, _)| f == name)
+                .map(|(_, v)| v.clone())
+        };
+        let require = |name: &str| get(name).ok_or_else(|| format!("{} is required", name));
+        let dir = || {
+            get("--dir")
+                .or_else(|| std::env::var("PAGI_FEDERATION_CA_DIR").ok())
+                .map(PathBuf::from)
+                .ok_or_else(|| "--dir (or PAGI_FEDERATION_CA_DIR) is required".to_string())
+        };
+        let credentials = || -> Result<SatelliteCredentialsArgs, String> {
+            Ok(SatelliteCredentialsArgs {
+                out: PathBuf::from(require("--out")?),
+                server_ca: PathBuf::from(require("--server-ca")?),
+                domain: get("--domain").unwrap_or_else(|| "localhost".to_string()),
+            })
+        };
+        let known: &[&str] = match command.as_str() {
+            "init-ca" => &["--dir", "--name", "--server-name"],
+            "token" => &["--dir", "--node", "--ttl-hours"],
+            "revoke" => &["--dir", "--node"],
+            "crl" | "list" => &["--dir"],
+            "enroll" => &[
+                "--creator",
+                "--server-ca",
+                "--domain",
+                "--out",
+                "--node",
+                "--token",
+            ],
+            "renew" => &["--creator", "--server-ca", "--domain", "--out"],
+            other => return Err(format!("unknown command: {}", other)),
+        };
+        if let Some((flag, _)) = flags.iter().find(|(f, _)| !known.contains(&f.as_str())) {
+            return Err(format!("unknown argument for {}: {}", command, flag));
+        }
+
+        Ok(match command.as_str() {
+            "init-ca" => {
+                let mut server_names: Vec<String> = flags
+                    .iter()
+                    .filter(|(f, _)| f == "--server-name")
+                    .map(|(_, v)| v.clone())
+                    .collect();
+                if server_names.is_empty() {
+                    server_names.push("localhost".to_string());
+                }
+                Command::InitCa {
+                    dir: dir()?,
+                    name: get("--name").unwrap_or_else(|| "PAGI Federation CA".to_string()),
+                    server_names,
+                }
+            }
+            "token" => Command::Token {
+                dir: dir()?,
+                node_id: require("--node")?,
+                ttl: match get("--ttl-hours") {
+                    Some(h) => Duration::from_secs(
+                        h.parse::<u64>()
+                            .map_err(|e| format!("--ttl-hours: {}", e))?
+                            * 3600,
+                    ),
+                    None => DEFAULT_TOKEN_TTL,
+                },
+            },
+            "revoke" => Command::Revoke {
+                dir: dir()?,
+                node_id: require("--node")?,
+            },
+            "crl" => Command::Crl { dir: dir()? },
+            "list" => Command::List { dir: dir()? },
+            "enroll" => Command::Enroll {
+                creator: require("--creator")?,
+                credentials: credentials()?,
+                node_id: require("--node")?,
+                token: require("--token")?,
+            },
+            _ => Command::Renew {
+                creator: require("--creator")?,
+                credentials: credentials()?,
+            },
+        })
+    }
+}
+
+impl SatelliteCredentialsArgs {
+    fn credentials(&self) -> SatelliteCredentials {
+        SatelliteCredentials::new(&self.out, &self.server_ca, &self.domain)
+    }
+}
+
+/// Runs `command`; returns what to print on success.
+pub async fn run(command: Command) -> Result<String, String> {
+    match command {
+        Command::InitCa {
+            dir,
+            name,
+            server_names,
+        } => {
+            let ca = CertificateAuthority::init(&dir, &name, &server_names)
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "CA created in {dir}\n\
+                 The Creator: PAGI_FEDERATION_CA_DIR={dir}\n\
+                 Satellites:  --server-ca {ca_pem} --domain {domain}",
+                dir = ca.dir().display(),
+                ca_pem = ca.ca_path().display(),
+                domain = server_names[0],
+            ))
+        }
+        Command::Token { dir, node_id, ttl } => {
+            let ca = CertificateAuthority::open(&dir).map_err(|e| e.to_string())?;
+            ca.issue_token(&node_id, ttl).map_err(|e| e.to_string())
+        }
+        Command::Revoke { dir, node_id } => {
+            let ca = CertificateAuthority::open(&dir).map_err(|e| e.to_string())?;
+            let revoked = ca.revoke(&node_id).map_err(|e| e.to_string())?;
+            Ok(format!(
+                "revoked {} certificate(s) of {}; CRL updated",
+                revoked, node_id
+            ))
+        }
+        Command::Crl { dir } => {
+            let ca = CertificateAuthority::open(&dir).map_err(|e| e.to_string())?;
+            ca.write_crl().map_err(|e| e.to_string())?;
+            Ok(format!("CRL written to {}", ca.crl_path().display()))
+        }
+        Command::List { dir } => {
+            let ca = CertificateAuthority::open(&dir).map_err(|e| e.to_string())?;
+            let lines: Vec<String> = ca
+                .issued()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .map(|c| {
+                    let state = match c.revoked_at_ms {
+                        Some(at) => format!("revoked {}", rfc3339(at)),
+                        None => format!("valid until {}", rfc3339(c.not_after_ms)),
+                    };
+                    format!("{}  {}  {}", c.serial, c.node_id, state)
+                })
+                .collect();
+            if lines.is_empty() {
+                return Ok("no certificates issued".to_string());
+            }
+            Ok(lines.join("\n"))
+        }
+        Command::Enroll {
+            creator,
+            credentials,
+            node_id,
+            token,
+        } => {
+            let creds = credentials.credentials();
+            let response = creds
+                .enroll(&creator, &node_id, &token)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "enrolled {} (serial {}, valid until {}); certificate in {}",
+                node_id,
+                response.serial,
+                rfc3339(response.not_after_ms),
+                creds.cert_path().display()
+            ))
+        }
+        Command::Renew {
+            creator,
+            credentials,
+        } => {
+            let creds = credentials.credentials();
+            let response = creds.renew(&creator).await.map_err(|e| e.to_string())?;
+            Ok(format!(
+                "renewed (serial {}, valid until {})",
+                response.serial,
+                rfc3339(response.not_after_ms)
+            ))
+        }
+    }
+}
+
+fn rfc3339(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| ms.to_string())
+}
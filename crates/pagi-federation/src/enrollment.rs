@@ -0,0 +1,326 @@
+//! Satellite side of enrollment: trading a one-time token for a client certificate, and keeping
+//! that certificate fresh.
+//!
+//! Credentials live in a directory (`PAGI_FEDERATION_CREDENTIALS_DIR`) as `node.pem` and
+//! `node.key`, inside the generation subdirectory named by its `current` file (older installs
+//! keep them at the top). [`SatelliteCredentials::enroll`] creates them over a TLS connection
+//! without a client certificate (The Creator serves only Enroll to such clients); afterwards
+//! `SatelliteClient::with_credentials` uses them for every connection and renews them through
+//! RenewCertificate once two thirds of their lifetime have passed.
+
+use std::path::{Path, PathBuf};
+
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint};
+
+use crate::ca::{generate_csr, write_atomic, write_secret};
+use crate::federation::FederationError;
+use crate::mtls::{install_default_provider, tonic_client_tls, PeerIdentity};
+use crate::phoenix_federation::phoenix_service_client::PhoenixServiceClient;
+use crate::phoenix_federation::{EnrollRequest, EnrollResponse, RenewCertificateRequest};
+
+/// Names the generation subdirectory holding the current pair.
+const CURRENT_FILE: &str = "current";
+
+#[derive(Debug, Clone)]
+pub struct SatelliteCredentials {
+    pub dir: PathBuf,
+    /// CA of The Creator's server certificate (`ca.pem` of its CA directory).
+    pub server_ca: PathBuf,
+    /// Name to verify in the server certificate.
+    pub domain: String,
+}
+
+impl SatelliteCredentials {
+    pub fn new(dir: &Path, server_ca: &Path, domain: &str) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            server_ca: server_ca.to_path_buf(),
+            domain: domain.to_string(),
+        }
+    }
+
+    /// `PAGI_FEDERATION_CREDENTIALS_DIR` with `PAGI_FEDERATION_TLS_SERVER_CA` and
+    /// `PAGI_FEDERATION_TLS_DOMAIN` (default `localhost`); `None` unless the first two are set.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Some(Self::new(
+            Path::new(&var("PAGI_FEDERATION_CREDENTIALS_DIR")?),
+            Path::new(&var("PAGI_FEDERATION_TLS_SERVER_CA")?),
+            &var("PAGI_FEDERATION_TLS_DOMAIN").unwrap_or_else(|| "localhost".to_string()),
+        ))
+    }
+
+    /// Directory of the current key and certificate pair.
+    fn current_dir(&self) -> PathBuf {
+        match std::fs::read_to_string(self.dir.join(CURRENT_FILE)) {
+            Ok(name) if !name.trim().is_empty() => self.dir.join(name.trim()),
+            _ => self.dir.clone(),
+        }
+    }
+
+    pub fn cert_path(&self) -> PathBuf {
+        self.current_dir().join("node.pem")
+    }
+
+    pub fn key_path(&self) -> PathBuf {
+        self.current_dir().join("node.key")
+    }
+
+    pub fn is_enrolled(&self) -> bool {
+        self.cert_path().exists() && self.key_path().exists()
+    }
+
+    /// Channel TLS with the current certificate, read from disk.
+    pub fn tls(&self) -> Result<ClientTlsConfig, String> {
+        let current = self.current_dir();
+        tonic_client_tls(
+            &current.join("node.pem"),
+            &current.join("node.key"),
+            &self.server_ca,
+            &self.domain,
+        )
+    }
+
+    /// The current certificate (DER).
+    fn current_cert(&self) -> Result<Vec<u8>, String> {
+        let path = self.cert_path();
+        let pem = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let der = rustls_pemfile::certs(&mut pem.as_slice())
+            .next()
+            .ok_or_else(|| format!("{}: no certificate", path.display()))?
+            .map_err(|e| e.to_string())?;
+        Ok(der.to_vec())
+    }
+
+    /// `(not_before_ms, not_after_ms)` of the current certificate.
+    pub fn validity(&self) -> Result<(i64, i64), String> {
+        use x509_parser::prelude::{FromDer, X509Certificate};
+        let der = self.current_cert()?;
+        let (_, cert) = X509Certificate::from_der(&der).map_err(|e| e.to_string())?;
+        let validity = cert.validity();
+        Ok((
+            validity.not_before.timestamp() * 1000,
+            validity.not_after.timestamp() * 1000,
+        ))
+    }
+
+    /// Whether less than a third of the certificate's lifetime is left at `now_ms`.
+    pub fn renewal_due(&self, now_ms: i64) -> Result<bool, String> {
+        let (not_before, not_after) = self.validity()?;
+        Ok(not_after - now_ms < (not_after - not_before) / 3)
+    }
+
+    /// Generates a key, redeems `token` for `node_id` at `creator_addr` and stores the result.
+    pub async fn enroll(
+        &self,
+        creator_addr: &str,
+        node_id: &str,
+        token: &str,
+    ) -> Result<EnrollResponse, FederationError> {
+        install_default_provider();
+        let ca = std::fs::read(&self.server_ca).map_err(|e| {
+            FederationError::Enrollment(format!("{}: {}", self.server_ca.display(), e))
+        })?;
+        let tls = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(ca))
+            .domain_name(self.domain.clone());
+        let mut client = connect(creator_addr, tls).await?;
+        let (key, csr_pem) = generate_csr(node_id).map_err(enrollment_error)?;
+        let response = client
+            .enroll(EnrollRequest {
+                token: token.to_string(),
+                node_id: node_id.to_string(),
+                csr_pem,
+            })
+            .await
+            .map_err(|s| FederationError::Enrollment(s.message().to_string()))?
+            .into_inner();
+        self.store(&key.serialize_pem(), &response)?;
+        Ok(response)
+    }
+
+    /// Trades the current certificate for a fresh one with a new key.
+    pub async fn renew(&self, creator_addr: &str) -> Result<EnrollResponse, FederationError> {
+        let tls = self.tls().map_err(FederationError::Enrollment)?;
+        let mut client = connect(creator_addr, tls).await?;
+        let identity = self
+            .current_cert()
+            .and_then(|der| PeerIdentity::from_der(&der))
+            .map_err(FederationError::Enrollment)?;
+        let (key, csr_pem) =
+            generate_csr(identity.primary().unwrap_or_default()).map_err(enrollment_error)?;
+        let response = client
+            .renew_certificate(RenewCertificateRequest { csr_pem })
+            .await
+            .map_err(|s| FederationError::Enrollment(s.message().to_string()))?
+            .into_inner();
+        self.store(&key.serialize_pem(), &response)?;
+        Ok(response)
+    }
+
+    /// Renews if [`Self::renewal_due`]; returns whether it did.
+    pub async fn renew_if_due(&self, creator_addr: &str) -> Result<bool, FederationError> {
+        let now = chrono::Utc::now().timestamp_millis();
+        if !self.renewal_due(now).map_err(FederationError::Enrollment)? {
+            return Ok(false);
+        }
+        self.renew(creator_addr).await.map(|_| true)
+    }
+
+    /// Writes key and certificate to a new generation directory, then switches `current` to it
+    /// with one rename. Until then the old pair stays in use; a crash leaves it in place.
+    fn store(&self, key_pem: &str, response: &EnrollResponse) -> Result<(), FederationError> {
+        let previous = self.current_dir();
+        let generation = format!(
+            "gen-{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        let next = self.dir.join(&generation);
+        let switch = || -> std::io::Result<()> {
+            std::fs::create_dir_all(&next)?;
+            write_secret(&next.join("node.key"), key_pem.as_bytes())?;
+            write_secret(&next.join("node.pem"), response.cert_pem.as_bytes())?;
+            write_atomic(&self.dir.join(CURRENT_FILE), generation.as_bytes())
+        };
+        if let Err(e) = switch() {
+            let _ = std::fs::remove_dir_all(&next);
+            return Err(FederationError::Enrollment(format!(
+                "{}: {}",
+                self.dir.display(),
+                e
+            )));
+        }
+        // The old pair is no longer referenced.
+        if previous == self.dir {
+            let _ = std::fs::remove_file(previous.join("node.key"));
+            let _ = std::fs::remove_file(previous.join("node.pem"));
+        } else {
+            let _ = std::fs::remove_dir_all(previous);
+        }
+        Ok(())
+    }
+}
+
+async fn connect(
+    creator_addr: &str,
+    tls: ClientTlsConfig,
+) -> Result<PhoenixServiceClient<tonic::transport::Channel>, FederationError> {
+    let channel = Endpoint::from_shared(creator_addr.to_string())
+        .map_err(|e| FederationError::Connect(e.to_string()))?
+        .tls_config(tls)
+        .map_err(|e| FederationError::Connect(e.to_string()))?
+        .connect()
+        .await
+        .map_err(|e| FederationError::Connect(e.to_string()))?;
+    Ok(PhoenixServiceClient::new(channel))
+}
+
+fn enrollment_error(e: crate::ca::CaError) -> FederationError {
+    FederationError::Enrollment(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ca::{CertificateAuthority, DEFAULT_TOKEN_TTL};
+    use crate::federation::{MasterServer, MasterState};
+    use crate::mtls::ServerTlsOptions;
+    use crate::phoenix_federation::RegisterNodeRequest;
+    use std::sync::Arc;
+
+    async fn register(creator: &str, tls: ClientTlsConfig) -> Result<(), String> {
+        let mut client = connect(creator, tls).await.map_err(|e| e.to_string())?;
+        client
+            .register_node(RegisterNodeRequest {
+                node_id: "kali-1".into(),
+                role: "RedTeam".into(),
+                host: "127.0.0.1".into(),
+                capabilities: vec!["scan".into()],
+                ..Default::default()
+            })
+            .await
+            .map(|_| ())
+            .map_err(|s| s.message().to_string())
+    }
+
+    #[tokio::test]
+    async fn enrolled_satellite_registers_renews_and_is_refused_after_revocation() {
+        let root =
+            std::env::temp_dir().join(format!("pagi_federation_enroll_{}", uuid::Uuid::new_v4()));
+        let ca = Arc::new(
+            CertificateAuthority::init(&root.join("ca"), "test ca", &["localhost".to_string()])
+                .unwrap(),
+        );
+        let tls = ServerTlsOptions {
+            crl: Some(ca.crl_path()),
+            allow_anonymous: true,
+            ..ServerTlsOptions::new(&ca.server_cert_path(), &ca.server_key_path(), &ca.ca_path())
+        }
+        .build()
+        .unwrap();
+        let server = Arc::new(
+            MasterServer::new(Arc::new(MasterState::new()))
+                .with_tls(tls)
+                .with_enrollment(Arc::clone(&ca)),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let creator = format!(
+            "https://localhost:{}",
+            listener.local_addr().unwrap().port()
+        );
+        let served = tokio::spawn(MasterServer::serve_on(Arc::clone(&server), listener));
+
+        let creds = SatelliteCredentials::new(&root.join("satellite"), &ca.ca_path(), "localhost");
+        let token = ca.issue_token("kali-1", DEFAULT_TOKEN_TTL).unwrap();
+        let enrolled = creds.enroll(&creator, "kali-1", &token).await.unwrap();
+        assert!(creds.is_enrolled());
+        let reused = creds.enroll(&creator, "kali-1", &token).await.unwrap_err();
+        assert!(reused.to_string().contains("already used"), "{reused}");
+
+        let late = SatelliteCredentials::new(&root.join("late"), &ca.ca_path(), "localhost");
+        let expired = ca.issue_token("kali-2", std::time::Duration::ZERO).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let refused = late.enroll(&creator, "kali-2", &expired).await.unwrap_err();
+        assert!(refused.to_string().contains("expired"), "{refused}");
+        assert!(!late.is_enrolled());
+
+        // Without a certificate only Enroll is served.
+        let anonymous = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(std::fs::read(ca.ca_path()).unwrap()))
+            .domain_name("localhost");
+        assert!(register(&creator, anonymous).await.is_err());
+        register(&creator, creds.tls().unwrap()).await.unwrap();
+
+        assert!(!creds.renew_if_due(&creator).await.unwrap());
+        let first_pair = creds.key_path();
+        let renewed = creds.renew(&creator).await.unwrap();
+        assert_ne!(renewed.serial, enrolled.serial);
+        // The new pair was switched in as a whole and the old one removed.
+        assert_ne!(creds.key_path(), first_pair);
+        assert!(!first_pair.exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(creds.key_path())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        register(&creator, creds.tls().unwrap()).await.unwrap();
+
+        // Both certificates land in the CRL; the next handshake and renewal are refused.
+        assert_eq!(ca.revoke("kali-1").unwrap(), 2);
+        assert!(register(&creator, creds.tls().unwrap()).await.is_err());
+        assert!(creds.renew(&creator).await.is_err());
+
+        served.abort();
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -4,22 +4,41 @@
 use tokio_stream::StreamExt;
 
 use crate::phoenix_federation::{
-    phoenix_service_server::PhoenixService, HeartbeatRequest, HeartbeatResponse,
-    RegisterNodeRequest, RegisterNodeResponse, Task, TaskResult,
+    phoenix_service_server::PhoenixService, ArtifactChunk, ArtifactRef, ArtifactStatus,
+    ArtifactStatusRequest, EnrollRequest, FetchArtifactRequest, EnrollResponse, HardwareSpecs,
+    HeartbeatRequest, HeartbeatResponse, KnowledgeSyncRequest, KnowledgeSyncResponse,
+    PartialArtifact, RegisterNodeRequest, RegisterNodeResponse, RenewCertificateRequest, Task,
+    TaskControl, TaskProgress, TaskResult,
 };
+use crate::artifacts::{
+    ArtifactError, ArtifactMeta, ArtifactSource, ArtifactStore, ARTIFACT_CHUNK_SIZE, ARTIFACT_PRUNE_INTERVAL,
+};
+use crate::ca::{CaError, CertificateAuthority, SignedCert};
+use crate::enrollment::SatelliteCredentials;
+use crate::liveness::{system_clock, Clock, LivenessConfig, NodeHealth, NODE_STATE_PREFIX};
+use crate::status::{NodeEvent, NODE_EVENT_CAPACITY};
+use crate::mtls::{PeerIdentity, PeerInfo, ServerTlsOptions};
+use crate::policy::NodePolicy;
+use crate::queue::{QueueError, QueuedTask, TaskQueue, TaskState};
+use crate::replication::{KnowledgeReplicator, ReplicaStore};
+use crate::scheduler::{scheduler_from_env, Scheduler};
 use dashmap::DashMap;
-use pagi_core::{AgentSkill, MemoryManager, TenantContext};
+use pagi_core::{AgentSkill, KnowledgeStore, MemoryManager, TenantContext};
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use tonic::{Request, Response, Status};
-use tracing::{info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 const REMOTE_INTELLIGENCE_PREFIX: &str = "remote_intelligence/";
 const THE_CREATOR_IDENTITY: &str = "The Creator";
 /// First message from Satellite in SubmitTask: task_id empty, summary "READY", node_id set.
 const READY_SUMMARY: &str = "READY";
+/// How long finished tasks stay in the durable queue.
+pub const FINISHED_TASK_RETENTION: std::time::Duration = std::time::Duration::from_secs(7 * 24 * 3600);
+/// How long a cancel waits for room in the satellite's task channel.
+const CANCEL_DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
 
-/// Per-satellite info after RegisterNode.
+/// Per-satellite info after RegisterNode, updated by heartbeats and task dispatch.
 #[derive(Debug, Clone)]
 pub struct SatelliteInfo {
     pub node_id: String,
@@ -27,16 +46,121 @@ pub struct SatelliteInfo {
     pub host: String,
     pub port: u32,
     pub capabilities: Vec<String>,
+    /// Hardware announced at registration.
+    pub hardware: Option<HardwareSpecs>,
+    /// Most recent heartbeat telemetry.
+    pub last_heartbeat: Option<HeartbeatSample>,
+    pub registered_at_ms: i64,
+    /// Tasks dispatched to this node that have not completed yet.
+    pub in_flight: u32,
+    /// Liveness derived from `last_seen_ms` by the liveness sweep.
+    pub health: NodeHealth,
+    /// Last registration, heartbeat or task-stream message from the node.
+    pub last_seen_ms: i64,
+}
+
+#[cfg(test)]
+impl SatelliteInfo {
+    /// A healthy, idle node without telemetry, registered and last seen at `now_ms`.
+    pub(crate) fn for_test(node_id: &str, role: &str, capabilities: &[&str], now_ms: i64) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            role: role.to_string(),
+            host: "10.0.0.1".to_string(),
+            port: 0,
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            hardware: None,
+            last_heartbeat: None,
+            registered_at_ms: now_ms,
+            in_flight: 0,
+            health: NodeHealth::Healthy,
+            last_seen_ms: now_ms,
+        }
+    }
+}
+
+/// Telemetry from one heartbeat.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct HeartbeatSample {
+    pub bandwidth_mbps: f64,
+    pub cpu_percent: f64,
+    pub ram_used_mb: u64,
+    pub at_ms: i64,
+}
+
+impl From<&HeartbeatRequest> for HeartbeatSample {
+    fn from(req: &HeartbeatRequest) -> Self {
+        Self {
+            bandwidth_mbps: req.bandwidth_mbps,
+            cpu_percent: req.cpu_percent,
+            ram_used_mb: req.ram_used_mb,
+            at_ms: req.at_ms,
+        }
+    }
+}
+
+/// How a dispatched task ended for the node it was sent to.
+#[derive(Debug)]
+enum TaskOutcome {
+    Completed(Box<TaskResult>),
+    /// The node was evicted before answering; the task may be re-dispatched.
+    NodeLost(String),
+    /// Stopped by [`FederationHandle::cancel_task`].
+    Cancelled(String),
+}
+
+/// Intermediate update from a running task, as delivered to its submitter.
+#[derive(Debug, Clone)]
+pub struct ProgressUpdate {
+    pub task_id: String,
+    pub node_id: String,
+    pub progress: TaskProgress,
 }
 
-/// Pending task: oneshot to complete when TaskResult arrives.
-type PendingTask = oneshot::Sender<TaskResult>;
+/// Per-task options for [`FederationHandle::submit_task_with`].
+#[derive(Debug, Default)]
+pub struct TaskOptions {
+    /// Id to dispatch under (a fresh UUID if `None`); lets the caller cancel the task while
+    /// `submit_task_with` is still waiting.
+    pub task_id: Option<String>,
+    /// Receives progress updates. Updates are dropped, never awaited, when the receiver lags.
+    pub progress: Option<mpsc::Sender<ProgressUpdate>>,
+}
+
+/// Pending task: the node currently running it and the oneshot completed by its outcome.
+pub struct PendingTask {
+    node_id: String,
+    pub(crate) goal: String,
+    pub(crate) created_at_ms: i64,
+    /// Dispatch attempt this node is running.
+    pub(crate) attempt: u32,
+    result_tx: oneshot::Sender<TaskOutcome>,
+    progress: Option<mpsc::Sender<ProgressUpdate>>,
+    /// The node confirmed receipt.
+    pub(crate) acked: bool,
+}
+
+impl PendingTask {
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+}
 
 /// Master (The Creator) state: registered satellites and pending task completions.
 pub struct MasterState {
     pub satellites: DashMap<String, SatelliteInfo>,
     pub pending: DashMap<String, PendingTask>,
     pub memory: Option<Arc<MemoryManager>>,
+    /// KB store for satellite state transitions (KB-08).
+    pub knowledge: Option<Arc<KnowledgeStore>>,
+    /// Durable task queue; without it tasks live only in `pending`.
+    pub queue: Option<Arc<TaskQueue>>,
+    /// Blob store for UploadArtifact; without it artifact RPCs are unimplemented.
+    pub artifacts: Option<Arc<ArtifactStore>>,
+    /// Satellite state transitions, for `FederationHandle::subscribe_node_events`.
+    pub(crate) node_events: broadcast::Sender<NodeEvent>,
+    /// Time source for `last_seen_ms` and the liveness sweep.
+    clock: Clock,
 }
 
 impl MasterState {
@@ -45,14 +169,213 @@ impl MasterState {
             satellites: DashMap::new(),
             pending: DashMap::new(),
             memory: None,
+            knowledge: None,
+            queue: None,
+            artifacts: None,
+            node_events: broadcast::channel(NODE_EVENT_CAPACITY).0,
+            clock: system_clock(),
         }
     }
 
     pub fn with_memory(memory: Arc<MemoryManager>) -> Self {
         Self {
-            satellites: DashMap::new(),
-            pending: DashMap::new(),
             memory: Some(memory),
+            ..Self::new()
+        }
+    }
+
+    /// Logs satellite state transitions to KB-08.
+    pub fn with_knowledge(mut self, knowledge: Arc<KnowledgeStore>) -> Self {
+        self.knowledge = Some(knowledge);
+        self
+    }
+
+    /// Persists tasks in `queue` so they survive a restart and are delivered at least once.
+    pub fn with_queue(mut self, queue: Arc<TaskQueue>) -> Self {
+        self.queue = Some(queue);
+        self
+    }
+
+    /// Accepts artifact uploads into `store`, referenced from task results.
+    pub fn with_artifacts(mut self, store: Arc<ArtifactStore>) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+
+    /// Measures satellite silence with `clock` instead of the wall clock.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Current time in Unix milliseconds according to the state's clock.
+    pub fn now_ms(&self) -> i64 {
+        (self.clock)()
+    }
+
+    /// Keeps the artifact references of `result` that are in the store, filled in from its
+    /// metadata and linked to the task; drops (and logs) references to anything not uploaded.
+    pub(crate) fn link_artifacts(&self, mut result: TaskResult) -> TaskResult {
+        let task_id = result.task_id.clone();
+        result.artifacts.retain_mut(|a| {
+            let linked = match self.artifacts {
+                Some(ref store) => store.link(&a.sha256, &task_id),
+                None => Ok(None),
+            };
+            match linked {
+                Ok(Some(meta)) => {
+                    let name = std::mem::take(&mut a.name);
+                    *a = meta.to_ref();
+                    if !name.is_empty() {
+                        a.name = name;
+                    }
+                    true
+                }
+                Ok(None) => {
+                    warn!(%task_id, sha256 = %a.sha256, "Dropping reference to an artifact that was not uploaded");
+                    false
+                }
+                Err(e) => {
+                    warn!(%task_id, sha256 = %a.sha256, error = %e, "Dropping unreadable artifact reference");
+                    false
+                }
+            }
+        });
+        result
+    }
+
+    /// Whether `node_id` may download `meta`: it uploaded it, or `task_id` references it and was
+    /// dispatched to the node.
+    pub(crate) fn may_fetch(&self, meta: &ArtifactMeta, node_id: &str, task_id: &str) -> bool {
+        if meta.node_id == node_id {
+            return true;
+        }
+        if !meta.task_ids.iter().any(|t| t == task_id) {
+            return false;
+        }
+        if self.pending.get(task_id).is_some_and(|p| p.node_id == node_id) {
+            return true;
+        }
+        self.queue.as_ref().is_some_and(|q| {
+            q.get(task_id)
+                .ok()
+                .flatten()
+                .is_some_and(|r| r.node_id.as_deref() == Some(node_id))
+        })
+    }
+
+    /// Completes the pending task for `result.task_id` and records it in the queue. Returns false
+    /// for unknown ids, for late duplicates of a re-dispatched or replayed task and for results
+    /// from a node the task was not dispatched to.
+    pub(crate) fn complete(&self, result: TaskResult) -> bool {
+        let first = match self.queue {
+            Some(ref queue) => queue.complete(&result).unwrap_or_else(|e| {
+                warn!(task_id = %result.task_id, error = %e, "Failed to record task result in queue");
+                false
+            }),
+            None => false,
+        };
+        match self
+            .pending
+            .remove_if(&result.task_id, |_, p| p.node_id == result.node_id)
+        {
+            Some((_, pending)) => {
+                let _ = pending.result_tx.send(TaskOutcome::Completed(Box::new(result)));
+                true
+            }
+            None => first,
+        }
+    }
+
+    /// Records that `result.node_id` received `result.task_id` (an ack or progress frame).
+    pub(crate) fn acknowledge(&self, result: &TaskResult) {
+        if let Some(mut pending) = self.pending.get_mut(&result.task_id) {
+            if pending.node_id == result.node_id {
+                pending.acked = true;
+            }
+        }
+        if let Some(ref queue) = self.queue {
+            if let Err(e) = queue.mark_acked(&result.task_id, &result.node_id) {
+                warn!(task_id = %result.task_id, error = %e, "Failed to record task ack in queue");
+            }
+        }
+    }
+
+    fn is_acked(&self, task_id: &str) -> bool {
+        self.pending.get(task_id).is_some_and(|p| p.acked)
+    }
+
+    /// Forwards a progress frame to the submitter of its task. Returns false for unknown tasks and
+    /// frames from a node the task is no longer assigned to.
+    pub(crate) fn report_progress(&self, result: &TaskResult) -> bool {
+        let (Some(progress), Some(pending)) = (&result.progress, self.pending.get(&result.task_id))
+        else {
+            return false;
+        };
+        if pending.node_id != result.node_id {
+            return false;
+        }
+        if let Some(ref tx) = pending.progress {
+            let _ = tx.try_send(ProgressUpdate {
+                task_id: result.task_id.clone(),
+                node_id: result.node_id.clone(),
+                progress: progress.clone(),
+            });
+        }
+        true
+    }
+
+    /// Refreshes `last_seen_ms`, reviving a suspect node. Returns the node's health, or `None`
+    /// if it is not registered. Dead nodes stay dead until they register again.
+    pub(crate) fn mark_seen(&self, node_id: &str) -> Option<NodeHealth> {
+        let mut s = self.satellites.get_mut(node_id)?;
+        if s.health == NodeHealth::Dead {
+            return Some(NodeHealth::Dead);
+        }
+        s.last_seen_ms = self.now_ms();
+        let previous = std::mem::replace(&mut s.health, NodeHealth::Healthy);
+        drop(s);
+        if previous != NodeHealth::Healthy {
+            self.log_node_transition(node_id, Some(previous), NodeHealth::Healthy, "seen again");
+        }
+        Some(NodeHealth::Healthy)
+    }
+
+    /// Records a satellite state change (`from` is `None` for a first registration) in KB-08
+    /// and publishes it to node event subscribers.
+    pub(crate) fn log_node_transition(
+        &self,
+        node_id: &str,
+        from: Option<NodeHealth>,
+        to: NodeHealth,
+        reason: &str,
+    ) {
+        let at_ms = self.now_ms();
+        if to == NodeHealth::Healthy {
+            info!(node_id, from = ?from, to = %to, reason, "Satellite state changed");
+        } else {
+            warn!(node_id, from = ?from, to = %to, reason, "Satellite state changed");
+        }
+        let _ = self.node_events.send(NodeEvent {
+            node_id: node_id.to_string(),
+            from,
+            to,
+            reason: reason.to_string(),
+            at_ms,
+        });
+        if let Some(ref kb) = self.knowledge {
+            const SOMA_SLOT: u8 = 8;
+            let key = format!("{}{:016x}_{}", NODE_STATE_PREFIX, at_ms, node_id);
+            let value = serde_json::json!({
+                "node_id": node_id,
+                "from": from.map(|h| h.as_str()),
+                "to": to.as_str(),
+                "reason": reason,
+                "at_ms": at_ms,
+            });
+            if let Err(e) = kb.insert(SOMA_SLOT, &key, value.to_string().as_bytes()) {
+                warn!(node_id, error = %e, "Failed to log satellite state to KB-08");
+            }
         }
     }
 
@@ -67,6 +390,7 @@ impl MasterState {
             "role": result.role,
             "completed_at_ms": result.completed_at_ms,
             "energy_used": result.energy_used,
+            "artifacts": artifacts_json(&result.artifacts),
         });
         if let Some(ref mem) = self.memory {
             if let Ok(()) = mem.save_path(ctx, &path, value.to_string().as_bytes()) {
@@ -78,6 +402,20 @@ impl MasterState {
     }
 }
 
+fn artifacts_json(artifacts: &[ArtifactRef]) -> Vec<serde_json::Value> {
+    artifacts
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "sha256": a.sha256,
+                "size_bytes": a.size,
+                "name": a.name,
+                "content_type": a.content_type,
+            })
+        })
+        .collect()
+}
+
 impl Default for MasterState {
     fn default() -> Self {
         Self::new()
@@ -88,69 +426,463 @@ impl Default for MasterState {
 pub struct FederationHandle {
     state: Arc<MasterState>,
     task_tx: DashMap<String, mpsc::Sender<Result<Task, Status>>>,
+    scheduler: Arc<dyn Scheduler>,
+    liveness: LivenessConfig,
+    /// Task ids currently being dispatched by this process.
+    active: Arc<DashMap<String, ()>>,
 }
 
 impl FederationHandle {
+    /// Uses the scheduler selected by `PAGI_FEDERATION_SCHEDULER` (least-loaded by default).
     pub fn new(state: Arc<MasterState>) -> Self {
+        Self::with_scheduler(state, scheduler_from_env())
+    }
+
+    /// Liveness deadlines come from the environment ([`LivenessConfig::from_env`]).
+    pub fn with_scheduler(state: Arc<MasterState>, scheduler: Arc<dyn Scheduler>) -> Self {
         Self {
             state,
             task_tx: DashMap::new(),
+            scheduler,
+            liveness: LivenessConfig::from_env(),
+            active: Arc::new(DashMap::new()),
         }
     }
 
+    pub fn with_liveness(mut self, liveness: LivenessConfig) -> Self {
+        self.liveness = liveness;
+        self
+    }
+
+    pub fn scheduler(&self) -> &dyn Scheduler {
+        self.scheduler.as_ref()
+    }
+
+    pub fn liveness(&self) -> &LivenessConfig {
+        &self.liveness
+    }
+
     pub fn register_node_tx(&self, node_id: String, tx: mpsc::Sender<Result<Task, Status>>) {
         self.task_tx.insert(node_id, tx);
     }
 
+    pub fn state(&self) -> Arc<MasterState> {
+        Arc::clone(&self.state)
+    }
+
+    /// Whether `node_id` has an open task stream (tasks can be dispatched to it).
+    pub fn is_connected(&self, node_id: &str) -> bool {
+        self.task_tx.contains_key(node_id)
+    }
+
     pub fn unregister_node_tx(&self, node_id: &str) {
         self.task_tx.remove(node_id);
     }
 
-    #[instrument(skip(self, ctx))]
+    /// Dispatches a task for `goal` and waits for its result. If the chosen node is evicted
+    /// first, the task is re-dispatched under the same `task_id` to another capable node, up to
+    /// `max_attempts` dispatches within `task_timeout`.
     pub async fn submit_task(
         &self,
         goal: &str,
         context_json: &str,
         ctx: &TenantContext,
     ) -> Result<TaskResult, FederationError> {
-        let node_id = self
-            .state
-            .satellites
-            .iter()
-            .find(|s| s.value().capabilities.iter().any(|c| c == goal))
-            .map(|s| s.key().clone())
-            .ok_or(FederationError::NoSatelliteForCapability(goal.to_string()))?;
-
-        let tx = self
-            .task_tx
-            .get(&node_id)
-            .ok_or(FederationError::SatelliteDisconnected(node_id.clone()))?;
-
-        let task_id = uuid::Uuid::new_v4().to_string();
-        let (result_tx, result_rx) = oneshot::channel();
-        self.state.pending.insert(task_id.clone(), result_tx);
+        self.submit_task_with(goal, context_json, ctx, TaskOptions::default())
+            .await
+    }
 
+    /// [`Self::submit_task`] with a caller-chosen task id and a progress channel.
+    #[instrument(skip(self, ctx, options))]
+    pub async fn submit_task_with(
+        &self,
+        goal: &str,
+        context_json: &str,
+        ctx: &TenantContext,
+        options: TaskOptions,
+    ) -> Result<TaskResult, FederationError> {
+        let task_id = options
+            .task_id
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let _claim = self.claim(&task_id)?;
         let task = Task {
             task_id: task_id.clone(),
             goal: goal.to_string(),
             context_json: context_json.to_string(),
             tenant_id: ctx.tenant_id.clone(),
             created_at_ms: chrono::Utc::now().timestamp_millis(),
+            control: None,
         };
+        let task = match self.state.queue {
+            Some(ref queue) => match queue.enqueue(&task)? {
+                None => task,
+                Some(existing) => return self.resume(queue, existing, ctx, options.progress).await,
+            },
+            None => task,
+        };
+        self.dispatch(task, options.progress).await
+    }
 
-        tx.try_send(Ok(task))
-            .map_err(|_| FederationError::SatelliteDisconnected(node_id))?;
+    /// A resubmitted task id: the stored result or failure for a finished task, otherwise the
+    /// stored task is driven to completion. Ids of another tenant are refused.
+    async fn resume(
+        &self,
+        queue: &TaskQueue,
+        existing: QueuedTask,
+        ctx: &TenantContext,
+        progress: Option<mpsc::Sender<ProgressUpdate>>,
+    ) -> Result<TaskResult, FederationError> {
+        if existing.tenant_id != ctx.tenant_id {
+            return Err(FederationError::DuplicateTask(existing.task_id));
+        }
+        match existing.state {
+            TaskState::Completed => queue.result(&existing.task_id)?.ok_or_else(|| {
+                QueueError::Corrupt {
+                    task_id: existing.task_id.clone(),
+                    reason: "completed without a stored result".into(),
+                }
+                .into()
+            }),
+            TaskState::Failed => Err(FederationError::AlreadyFailed {
+                reason: existing.error.clone().unwrap_or_default(),
+                task_id: existing.task_id,
+            }),
+            _ => self.dispatch(existing.to_task(), progress).await,
+        }
+    }
 
-        let result = tokio::time::timeout(
-            std::time::Duration::from_secs(300),
-            result_rx,
-        )
-        .await
-        .map_err(|_| FederationError::Timeout)?
-        .map_err(|_| FederationError::ChannelClosed)?;
+    /// Marks `task_id` as driven by this process until the returned guard drops.
+    fn claim(&self, task_id: &str) -> Result<ActiveTask, FederationError> {
+        if self.active.insert(task_id.to_string(), ()).is_some() {
+            return Err(FederationError::DuplicateTask(task_id.to_string()));
+        }
+        Ok(ActiveTask {
+            active: Arc::clone(&self.active),
+            task_id: task_id.to_string(),
+        })
+    }
+
+    /// Sends `task` until a result arrives: re-dispatches when the node is lost or does not
+    /// acknowledge within `ack_timeout`, waits (backpressure) while a node's channel is full,
+    /// and records every step in the durable queue. Failures are recorded there too.
+    async fn dispatch(
+        &self,
+        task: Task,
+        progress: Option<mpsc::Sender<ProgressUpdate>>,
+    ) -> Result<TaskResult, FederationError> {
+        let result = self.dispatch_attempts(&task, progress).await;
+        if let (Err(e), Some(queue)) = (&result, &self.state.queue) {
+            if let Err(qe) = queue.fail(&task.task_id, &e.to_string()) {
+                warn!(task_id = %task.task_id, error = %qe, "Failed to record task failure in queue");
+            }
+        }
+        result
+    }
+
+    async fn dispatch_attempts(
+        &self,
+        task: &Task,
+        progress: Option<mpsc::Sender<ProgressUpdate>>,
+    ) -> Result<TaskResult, FederationError> {
+        let task_id = task.task_id.clone();
+        let deadline = tokio::time::Instant::now() + self.liveness.task_timeout;
+        let mut lost: Vec<String> = Vec::new();
 
-        self.state.pending.remove(&task_id);
-        Ok(result)
+        for attempt in 1..=self.liveness.max_attempts {
+            let node_id = match self.pick_node(&task.goal, &lost) {
+                Ok(node_id) => node_id,
+                // Every capable node has been lost during this task.
+                Err(_) if !lost.is_empty() => break,
+                Err(e) => return Err(e),
+            };
+            let Some(tx) = self.task_tx.get(&node_id).map(|tx| tx.clone()) else {
+                lost.push(node_id);
+                continue;
+            };
+
+            let (result_tx, mut result_rx) = oneshot::channel();
+            self.state.pending.insert(
+                task_id.clone(),
+                PendingTask {
+                    node_id: node_id.clone(),
+                    goal: task.goal.clone(),
+                    created_at_ms: task.created_at_ms,
+                    attempt,
+                    result_tx,
+                    progress: progress.clone(),
+                    acked: false,
+                },
+            );
+            let _in_flight = InFlight::start(&self.state, &node_id);
+            // Recorded before sending so an early ack finds the dispatch.
+            if let Some(ref queue) = self.state.queue {
+                if let Err(e) = queue.mark_dispatched(&task_id, &node_id) {
+                    warn!(%task_id, error = %e, "Failed to record dispatch in queue");
+                }
+            }
+
+            // A full channel is backpressure: wait for room until the task deadline.
+            match tokio::time::timeout_at(deadline, tx.send(Ok(task.clone()))).await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) => {
+                    self.state.pending.remove(&task_id);
+                    lost.push(node_id);
+                    continue;
+                }
+                Err(_) => {
+                    self.state.pending.remove(&task_id);
+                    return Err(FederationError::Timeout);
+                }
+            }
+
+            let ack_deadline = if self.liveness.ack_timeout.is_zero() {
+                deadline
+            } else {
+                (tokio::time::Instant::now() + self.liveness.ack_timeout).min(deadline)
+            };
+            let outcome = match tokio::time::timeout_at(ack_deadline, &mut result_rx).await {
+                Ok(outcome) => outcome,
+                Err(_) if self.state.is_acked(&task_id) => {
+                    match tokio::time::timeout_at(deadline, &mut result_rx).await {
+                        Ok(outcome) => outcome,
+                        Err(_) => {
+                            self.state.pending.remove(&task_id);
+                            return Err(FederationError::Timeout);
+                        }
+                    }
+                }
+                Err(_) if ack_deadline < deadline => {
+                    // Never arrived, as far as we know: send it again (possibly to the same node,
+                    // which replays rather than re-runs a task it already has).
+                    self.state.pending.remove(&task_id);
+                    warn!(%task_id, node_id = %node_id, attempt, "Task not acknowledged; re-dispatching");
+                    continue;
+                }
+                Err(_) => {
+                    self.state.pending.remove(&task_id);
+                    return Err(FederationError::Timeout);
+                }
+            };
+            match outcome {
+                Ok(TaskOutcome::Completed(result)) => return Ok(*result),
+                Ok(TaskOutcome::NodeLost(reason)) => {
+                    warn!(%task_id, node_id = %node_id, attempt, reason = %reason, "Satellite lost; re-dispatching task");
+                    lost.push(node_id);
+                }
+                Ok(TaskOutcome::Cancelled(reason)) => {
+                    return Err(FederationError::Cancelled { task_id, reason })
+                }
+                Err(_) => return Err(FederationError::ChannelClosed),
+            }
+        }
+        Err(FederationError::RetriesExhausted {
+            task_id,
+            lost_nodes: lost,
+        })
+    }
+
+    /// Re-dispatches queued tasks that no caller in this process is driving (left over from a
+    /// restart) once a capable satellite is connected, and prunes finished tasks older than
+    /// [`FINISHED_TASK_RETENTION`]. Results are recorded in the queue and as Remote Intelligence.
+    pub fn replay_queued(self: &Arc<Self>) {
+        let Some(ref queue) = self.state.queue else {
+            return;
+        };
+        let cutoff = chrono::Utc::now().timestamp_millis()
+            - FINISHED_TASK_RETENTION.as_millis() as i64;
+        if let Err(e) = queue.prune_finished(cutoff) {
+            warn!(error = %e, "Failed to prune finished federation tasks");
+        }
+        let unfinished = match queue.unfinished() {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                warn!(error = %e, "Failed to read federation task queue");
+                return;
+            }
+        };
+        for record in unfinished {
+            if self.pick_node(&record.goal, &[]).is_err() {
+                continue;
+            }
+            let Ok(claim) = self.claim(&record.task_id) else {
+                continue;
+            };
+            info!(task_id = %record.task_id, goal = %record.goal, attempts = record.attempts, "Replaying queued task");
+            let this = Arc::clone(self);
+            tokio::spawn(async move {
+                let _claim = claim;
+                let _ = this.dispatch(record.to_task(), None).await;
+            });
+        }
+    }
+
+    /// Stops a pending task: fails the submitter with [`FederationError::Cancelled`] and tells
+    /// its satellite to cancel. Returns `Ok(false)` if the task is not pending and
+    /// [`FederationError::CancelUndelivered`] if the satellite could not be told (disconnected, or
+    /// its channel stayed full for [`CANCEL_DELIVERY_TIMEOUT`]); the task is cancelled either way.
+    pub async fn cancel_task(&self, task_id: &str, reason: &str) -> Result<bool, FederationError> {
+        let Some((_, pending)) = self.state.pending.remove(task_id) else {
+            return Ok(false);
+        };
+        let _ = pending
+            .result_tx
+            .send(TaskOutcome::Cancelled(reason.to_string()));
+        let control = Task {
+            task_id: task_id.to_string(),
+            control: Some(TaskControl {
+                cancel: true,
+                reason: reason.to_string(),
+            }),
+            ..Default::default()
+        };
+        // Cloned so the map is not locked across the await.
+        let tx = self.task_tx.get(&pending.node_id).map(|tx| tx.clone());
+        let delivered = match tx {
+            Some(tx) => matches!(
+                tokio::time::timeout(CANCEL_DELIVERY_TIMEOUT, tx.send(Ok(control))).await,
+                Ok(Ok(()))
+            ),
+            None => false,
+        };
+        info!(task_id, node_id = %pending.node_id, reason, delivered, "Task cancelled");
+        if !delivered {
+            warn!(task_id, node_id = %pending.node_id, "Cancel was not delivered to the satellite");
+            return Err(FederationError::CancelUndelivered {
+                task_id: task_id.to_string(),
+                node_id: pending.node_id,
+            });
+        }
+        Ok(true)
+    }
+
+    /// Asks the scheduler to choose among connected, live satellites advertising `goal`,
+    /// skipping `exclude`. Suspect nodes are used only when no healthy node qualifies.
+    fn pick_node(&self, goal: &str, exclude: &[String]) -> Result<String, FederationError> {
+        let mut candidates: Vec<SatelliteInfo> = self
+            .state
+            .satellites
+            .iter()
+            .filter(|s| s.value().capabilities.iter().any(|c| c == goal))
+            .map(|s| s.value().clone())
+            .collect();
+        if candidates.is_empty() {
+            return Err(FederationError::NoSatelliteForCapability(goal.to_string()));
+        }
+        candidates.retain(|s| {
+            s.health != NodeHealth::Dead
+                && !exclude.contains(&s.node_id)
+                && self.task_tx.contains_key(&s.node_id)
+        });
+        if candidates.is_empty() {
+            return Err(FederationError::SatelliteDisconnected(goal.to_string()));
+        }
+        if candidates.iter().any(|s| s.health == NodeHealth::Healthy) {
+            candidates.retain(|s| s.health == NodeHealth::Healthy);
+        }
+        candidates.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        self.scheduler
+            .pick(goal, &candidates)
+            .ok_or(FederationError::NoSatelliteForCapability(goal.to_string()))
+    }
+
+    /// Marks a node dead, drops its task sender and hands its pending tasks back to their
+    /// submitters for re-dispatch.
+    pub fn evict(&self, node_id: &str, reason: &str) {
+        self.task_tx.remove(node_id);
+        let previous = self
+            .state
+            .satellites
+            .get_mut(node_id)
+            .map(|mut s| std::mem::replace(&mut s.health, NodeHealth::Dead));
+        if previous != Some(NodeHealth::Dead) {
+            self.state
+                .log_node_transition(node_id, previous, NodeHealth::Dead, reason);
+        }
+        let orphaned: Vec<String> = self
+            .state
+            .pending
+            .iter()
+            .filter(|p| p.value().node_id == node_id)
+            .map(|p| p.key().clone())
+            .collect();
+        for task_id in orphaned {
+            if let Some((_, pending)) = self
+                .state
+                .pending
+                .remove_if(&task_id, |_, p| p.node_id == node_id)
+            {
+                let _ = pending.result_tx.send(TaskOutcome::NodeLost(reason.to_string()));
+            }
+        }
+    }
+
+    /// Evicts `node_id` when the task stream behind `tx` closes, unless the node has since
+    /// reconnected on a new stream.
+    fn stream_closed(&self, node_id: &str, tx: &mpsc::Sender<Result<Task, Status>>) {
+        let current = self
+            .task_tx
+            .get(node_id)
+            .map(|t| t.same_channel(tx))
+            .unwrap_or(false);
+        if current {
+            self.evict(node_id, "task stream closed");
+        }
+    }
+
+    /// One liveness sweep: classifies every node by silence, marks suspects, evicts the dead and
+    /// forgets nodes that have been dead for another `dead_after`.
+    pub fn check_liveness(&self) {
+        let now = self.state.now_ms();
+        let dead_after_ms = self.liveness.dead_after.as_millis() as i64;
+        let changes: Vec<(String, NodeHealth, NodeHealth, i64)> = self
+            .state
+            .satellites
+            .iter()
+            .filter_map(|s| {
+                let silent_ms = now - s.last_seen_ms;
+                let health = self.liveness.classify(silent_ms);
+                (health != s.health || s.health == NodeHealth::Dead)
+                    .then(|| (s.node_id.clone(), s.health, health, silent_ms))
+            })
+            .collect();
+        for (node_id, from, to, silent_ms) in changes {
+            let reason = format!("no heartbeat for {}s", silent_ms / 1000);
+            match (from, to) {
+                (NodeHealth::Dead, _) => {
+                    if silent_ms >= 2 * dead_after_ms {
+                        self.state.satellites.remove(&node_id);
+                        info!(node_id = %node_id, "Dead satellite forgotten");
+                    }
+                }
+                (_, NodeHealth::Dead) => self.evict(&node_id, &reason),
+                (_, to) => {
+                    if let Some(mut s) = self.state.satellites.get_mut(&node_id) {
+                        s.health = to;
+                    }
+                    self.state.log_node_transition(&node_id, Some(from), to, &reason);
+                }
+            }
+        }
+    }
+
+    /// Runs [`Self::check_liveness`] and [`Self::replay_queued`] every `sweep_interval` until the
+    /// handle is dropped.
+    pub fn spawn_liveness_monitor(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let weak = Arc::downgrade(self);
+        let period = self.liveness.sweep_interval;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                match weak.upgrade() {
+                    Some(handle) => {
+                        handle.check_liveness();
+                        handle.replay_queued();
+                    }
+                    None => break,
+                }
+            }
+        })
     }
 
     pub fn available_capabilities(&self) -> Vec<String> {
@@ -164,6 +896,44 @@ impl FederationHandle {
     }
 }
 
+/// A task id driven by a `submit_task_with` or replay in this process.
+struct ActiveTask {
+    active: Arc<DashMap<String, ()>>,
+    task_id: String,
+}
+
+impl Drop for ActiveTask {
+    fn drop(&mut self) {
+        self.active.remove(&self.task_id);
+    }
+}
+
+/// Counts a dispatched task against its node until dropped.
+struct InFlight<'a> {
+    state: &'a MasterState,
+    node_id: String,
+}
+
+impl<'a> InFlight<'a> {
+    fn start(state: &'a MasterState, node_id: &str) -> Self {
+        if let Some(mut s) = state.satellites.get_mut(node_id) {
+            s.in_flight += 1;
+        }
+        Self {
+            state,
+            node_id: node_id.to_string(),
+        }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Some(mut s) = self.state.satellites.get_mut(&self.node_id) {
+            s.in_flight = s.in_flight.saturating_sub(1);
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum FederationError {
     #[error("no satellite with capability: {0}")]
@@ -172,10 +942,35 @@ pub enum FederationError {
     SatelliteDisconnected(String),
     #[error("timeout waiting for task result")]
     Timeout,
+    #[error("task {task_id} cancelled: {reason}")]
+    Cancelled { task_id: String, reason: String },
+    #[error("cancel of task {task_id} not delivered to satellite {node_id}")]
+    CancelUndelivered { task_id: String, node_id: String },
+    #[error("task id already pending: {0}")]
+    DuplicateTask(String),
+    #[error("task {task_id} already failed: {reason}")]
+    AlreadyFailed { task_id: String, reason: String },
+    #[error(transparent)]
+    Queue(#[from] QueueError),
+    #[error("task {task_id} lost its satellite on every attempt: {lost_nodes:?}")]
+    RetriesExhausted {
+        task_id: String,
+        lost_nodes: Vec<String>,
+    },
     #[error("channel closed")]
     ChannelClosed,
     #[error("connect: {0}")]
     Connect(String),
+    #[error("enrollment: {0}")]
+    Enrollment(String),
+    #[error(transparent)]
+    Replication(#[from] crate::replication::ReplicationError),
+    #[error(transparent)]
+    Artifact(#[from] ArtifactError),
+    #[error("artifact transfer: {0}")]
+    ArtifactTransfer(String),
+    #[error("knowledge sync: {0}")]
+    Sync(String),
 }
 
 // ---------------------------------------------------------------------------
@@ -185,12 +980,92 @@ pub enum FederationError {
 pub struct MasterServer {
     state: Arc<MasterState>,
     handle: Arc<FederationHandle>,
+    /// When set, `serve` requires client certificates (mTLS).
+    tls: Option<Arc<rustls::ServerConfig>>,
+    /// What each certificate identity may register as; without it a certificate may only
+    /// register under one of its own names.
+    policy: Option<Arc<NodePolicy>>,
+    /// node_id -> certificate identity that registered it.
+    bindings: Arc<DashMap<String, String>>,
+    /// When set, serves the Enroll and RenewCertificate RPCs.
+    enrollment: Option<Arc<CertificateAuthority>>,
+    /// When set, serves SyncKnowledge.
+    replication: Option<Arc<KnowledgeReplicator>>,
 }
 
+// The request helpers return `tonic::Status`, the error type of every PhoenixService handler.
+#[allow(clippy::result_large_err)]
 impl MasterServer {
     pub fn new(state: Arc<MasterState>) -> Self {
-        let handle = Arc::new(FederationHandle::new(Arc::clone(&state)));
-        Self { state, handle }
+        Self::from_handle(FederationHandle::new(state))
+    }
+
+    /// Like [`Self::new`] with an explicit task scheduler.
+    pub fn with_scheduler(state: Arc<MasterState>, scheduler: Arc<dyn Scheduler>) -> Self {
+        Self::from_handle(FederationHandle::with_scheduler(state, scheduler))
+    }
+
+    /// Serves an already configured handle (scheduler, liveness deadlines).
+    pub fn from_handle(handle: FederationHandle) -> Self {
+        Self {
+            state: Arc::clone(&handle.state),
+            handle: Arc::new(handle),
+            tls: None,
+            policy: None,
+            bindings: Arc::new(DashMap::new()),
+            enrollment: None,
+            replication: None,
+        }
+    }
+
+    /// Require mTLS with `config` (see [`crate::mtls::server_tls_config`]).
+    pub fn with_tls(mut self, config: Arc<rustls::ServerConfig>) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    pub fn with_policy(mut self, policy: NodePolicy) -> Self {
+        self.policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// Issue and renew satellite certificates with `ca`. The TLS config must then allow anonymous
+    /// clients ([`ServerTlsOptions::allow_anonymous`]) so new satellites can reach Enroll.
+    pub fn with_enrollment(mut self, ca: Arc<CertificateAuthority>) -> Self {
+        self.enrollment = Some(ca);
+        self
+    }
+
+    /// Replicate the KB scopes of each satellite's role with `replicator`. Only served over mTLS,
+    /// to certificates whose node policy grant names the role explicitly.
+    pub fn with_replication(mut self, replicator: Arc<KnowledgeReplicator>) -> Self {
+        self.replication = Some(replicator);
+        self
+    }
+
+    /// Applies [`ServerTlsOptions::from_env`], [`CertificateAuthority::from_env`] and
+    /// [`NodePolicy::from_env`]. With a CA, TLS defaults to its server certificate, checks its
+    /// CRL and admits certificate-less clients to Enroll.
+    pub fn with_security_from_env(mut self) -> Result<Self, String> {
+        let ca = CertificateAuthority::from_env().map_err(|e| e.to_string())?;
+        let mut tls = ServerTlsOptions::from_env()?;
+        if let Some(ref ca) = ca {
+            let options = tls.get_or_insert_with(|| {
+                ServerTlsOptions::new(&ca.server_cert_path(), &ca.server_key_path(), &ca.ca_path())
+            });
+            options.crl.get_or_insert_with(|| ca.crl_path());
+            options.allow_anonymous = true;
+        }
+        if let Some(options) = tls {
+            self = self.with_tls(options.build()?);
+        }
+        if let Some(ca) = ca {
+            self = self.with_enrollment(Arc::new(ca));
+        }
+        if let Some(policy) = NodePolicy::from_env().map_err(|e| e.to_string())? {
+            self = self.with_policy(policy);
+        }
+        Ok(self)
     }
 
     pub fn handle(&self) -> Arc<FederationHandle> {
@@ -198,18 +1073,111 @@ impl MasterServer {
     }
 
     /// Run the Phoenix gRPC server (The Creator's listener). Use a port in 8001–8099 per architecture.
-    /// Call with `Arc::clone(&server)`. For mTLS, use `tonic::transport::Server::builder().tls_config(...)`.
+    /// Call with `Arc::clone(&server)`. Also starts the liveness sweep. Requires mTLS when
+    /// configured with [`Self::with_tls`].
     pub async fn serve(
         this: Arc<Self>,
         addr: std::net::SocketAddr,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        Self::serve_on(this, listener).await
+    }
+
+    /// [`Self::serve`] on an already bound listener.
+    pub async fn serve_on(
+        this: Arc<Self>,
+        listener: tokio::net::TcpListener,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         use crate::phoenix_federation::phoenix_service_server::PhoenixServiceServer;
-        let svc = PhoenixServiceServer::new(this);
-        tonic::transport::Server::builder()
-            .add_service(svc)
-            .serve(addr)
-            .await
-            .map_err(Into::into)
+        let monitor = this.handle.spawn_liveness_monitor();
+        let pruner = this
+            .state
+            .artifacts
+            .as_ref()
+            .map(|store| store.spawn_pruner(ARTIFACT_PRUNE_INTERVAL));
+        let tls = this.tls.clone();
+        let router =
+            tonic::transport::Server::builder().add_service(PhoenixServiceServer::new(this));
+        let served = match tls {
+            Some(config) => {
+                router
+                    .serve_with_incoming(crate::mtls::tls_incoming(listener, config))
+                    .await
+            }
+            None => {
+                warn!("Federation server running without mTLS; any client can register");
+                router
+                    .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                    .await
+            }
+        };
+        monitor.abort();
+        if let Some(pruner) = pruner {
+            pruner.abort();
+        }
+        served.map_err(Into::into)
+    }
+
+    /// The caller's certificate identity; `None` when mTLS is not configured.
+    fn peer_identity<T>(&self, request: &Request<T>) -> Result<Option<PeerIdentity>, Status> {
+        if self.tls.is_none() {
+            return Ok(None);
+        }
+        let identity = request
+            .extensions()
+            .get::<PeerInfo>()
+            .and_then(PeerInfo::identity)
+            .ok_or_else(|| Status::unauthenticated("client certificate required"))?
+            .map_err(|e| Status::unauthenticated(format!("unreadable client certificate: {}", e)))?;
+        Ok(Some(identity))
+    }
+
+    /// Checks a registration against the policy (or, without one, the certificate's own names)
+    /// and binds `node_id` to the certificate.
+    fn authorize_registration(
+        &self,
+        identity: &PeerIdentity,
+        req: &RegisterNodeRequest,
+    ) -> Result<(), Status> {
+        let name = identity.primary().unwrap_or_default().to_string();
+        let verdict = match self.policy {
+            Some(ref policy) => policy
+                .authorize(identity, &req.node_id, &req.role, &req.capabilities)
+                .map_err(|e| e.to_string()),
+            None if identity.has_name(&req.node_id) => Ok(()),
+            None => Err(format!("{} may not register as node {}", name, req.node_id)),
+        };
+        if let Err(reason) = verdict {
+            warn!(identity = %name, node_id = %req.node_id, %reason, "Registration rejected");
+            return Err(Status::permission_denied(reason));
+        }
+        self.bindings.insert(req.node_id.clone(), name);
+        Ok(())
+    }
+
+    fn artifacts(&self) -> Result<Arc<ArtifactStore>, Status> {
+        self.state
+            .artifacts
+            .clone()
+            .ok_or_else(|| Status::unimplemented("artifact storage is not enabled on this master"))
+    }
+
+    fn enrollment(&self) -> Result<&CertificateAuthority, Status> {
+        self.enrollment
+            .as_deref()
+            .ok_or_else(|| Status::unimplemented("enrollment is not enabled on this master"))
+    }
+}
+
+/// Whether `identity` (if mTLS is on) registered `node_id`.
+fn is_bound(
+    bindings: &DashMap<String, String>,
+    identity: &Option<PeerIdentity>,
+    node_id: &str,
+) -> bool {
+    match identity {
+        None => true,
+        Some(id) => bindings.get(node_id).is_some_and(|b| id.has_name(b.value())),
     }
 }
 
@@ -237,6 +1205,51 @@ impl PhoenixService for Arc<MasterServer> {
     ) -> Result<Response<HeartbeatResponse>, Status> {
         self.as_ref().heartbeat(request).await
     }
+
+    async fn enroll(
+        &self,
+        request: Request<EnrollRequest>,
+    ) -> Result<Response<EnrollResponse>, Status> {
+        self.as_ref().enroll(request).await
+    }
+
+    async fn renew_certificate(
+        &self,
+        request: Request<RenewCertificateRequest>,
+    ) -> Result<Response<EnrollResponse>, Status> {
+        self.as_ref().renew_certificate(request).await
+    }
+
+    async fn sync_knowledge(
+        &self,
+        request: Request<KnowledgeSyncRequest>,
+    ) -> Result<Response<KnowledgeSyncResponse>, Status> {
+        self.as_ref().sync_knowledge(request).await
+    }
+
+    async fn upload_artifact(
+        &self,
+        request: Request<tonic::Streaming<ArtifactChunk>>,
+    ) -> Result<Response<ArtifactStatus>, Status> {
+        self.as_ref().upload_artifact(request).await
+    }
+
+    async fn get_artifact_status(
+        &self,
+        request: Request<ArtifactStatusRequest>,
+    ) -> Result<Response<ArtifactStatus>, Status> {
+        self.as_ref().get_artifact_status(request).await
+    }
+
+    type FetchArtifactStream =
+        tokio_stream::wrappers::ReceiverStream<Result<ArtifactChunk, Status>>;
+
+    async fn fetch_artifact(
+        &self,
+        request: Request<FetchArtifactRequest>,
+    ) -> Result<Response<Self::FetchArtifactStream>, Status> {
+        self.as_ref().fetch_artifact(request).await
+    }
 }
 
 #[tonic::async_trait]
@@ -245,16 +1258,44 @@ impl PhoenixService for MasterServer {
         &self,
         request: Request<RegisterNodeRequest>,
     ) -> Result<Response<RegisterNodeResponse>, Status> {
+        let identity = self.peer_identity(&request)?;
         let req = request.into_inner();
+        if let Some(ref identity) = identity {
+            self.authorize_registration(identity, &req)?;
+        }
+        // Re-registration keeps the node's in-flight count and last heartbeat.
+        let (in_flight, last_heartbeat, previous) = self
+            .state
+            .satellites
+            .get(&req.node_id)
+            .map(|s| (s.in_flight, s.last_heartbeat.clone(), Some(s.health)))
+            .unwrap_or_default();
+        let now = self.state.now_ms();
         let info = SatelliteInfo {
             node_id: req.node_id.clone(),
             role: req.role.clone(),
             host: req.host.clone(),
             port: req.port,
             capabilities: req.capabilities.clone(),
+            hardware: req.hardware.clone(),
+            last_heartbeat,
+            registered_at_ms: now,
+            in_flight,
+            health: NodeHealth::Healthy,
+            last_seen_ms: now,
         };
         self.state.satellites.insert(req.node_id.clone(), info);
-        info!(node_id = %req.node_id, role = %req.role, "Satellite registered");
+        if previous != Some(NodeHealth::Healthy) {
+            self.state
+                .log_node_transition(&req.node_id, previous, NodeHealth::Healthy, "registered");
+        }
+        info!(
+            node_id = %req.node_id,
+            role = %req.role,
+            cpu_cores = req.hardware.as_ref().map(|h| h.cpu_cores).unwrap_or(0),
+            ram_mb = req.hardware.as_ref().map(|h| h.ram_mb).unwrap_or(0),
+            "Satellite registered"
+        );
         Ok(Response::new(RegisterNodeResponse {
             accepted: true,
             message: format!("Welcome, {} registered as {}", req.node_id, req.role),
@@ -271,28 +1312,58 @@ impl PhoenixService for MasterServer {
         Response<tokio_stream::wrappers::ReceiverStream<Result<Task, Status>>>,
         Status,
     > {
+        let identity = self.peer_identity(&request)?;
         let mut stream = request.into_inner();
         let (tx, rx) = mpsc::channel::<Result<Task, Status>>(256);
         let tx_reg = tx.clone();
         let state = Arc::clone(&self.state);
         let handle = Arc::clone(&self.handle);
+        let bindings = Arc::clone(&self.bindings);
 
         tokio::spawn(async move {
+            let mut stream_node: Option<String> = None;
             while let Some(Ok(result)) = stream.next().await {
+                // Over mTLS a stream may only speak for nodes its certificate registered.
+                if !is_bound(&bindings, &identity, &result.node_id) {
+                    warn!(node_id = %result.node_id, "Ignoring message for a node not registered by this certificate");
+                    continue;
+                }
+                let health = state.mark_seen(&result.node_id);
                 if result.task_id.is_empty() && result.summary == READY_SUMMARY {
-                    handle.register_node_tx(result.node_id.clone(), tx_reg.clone());
+                    // Dead or unknown nodes must register before taking tasks again.
+                    if health == Some(NodeHealth::Healthy) {
+                        handle.register_node_tx(result.node_id.clone(), tx_reg.clone());
+                        stream_node = Some(result.node_id.clone());
+                    }
+                    continue;
+                }
+                if result.ack {
+                    state.acknowledge(&result);
+                    continue;
+                }
+                if result.progress.is_some() {
+                    state.acknowledge(&result);
+                    state.report_progress(&result);
                     continue;
                 }
-                if let Some((_, pending_tx)) = state.pending.remove(&result.task_id) {
-                    let _ = pending_tx.send(result.clone());
+                // Only the first result for a task id is accepted and logged.
+                let result = state.link_artifacts(result);
+                if !state.complete(result.clone()) {
+                    debug!(task_id = %result.task_id, node_id = %result.node_id, "Ignoring result for unknown or completed task");
+                    continue;
                 }
                 let ctx = TenantContext {
                     tenant_id: result.tenant_id.clone(),
                     correlation_id: None,
                     agent_id: None,
+                    session_id: None,
+                    capabilities: None,
                 };
                 state.log_remote_intelligence(&result, &ctx);
             }
+            if let Some(node_id) = stream_node {
+                handle.stream_closed(&node_id, &tx_reg);
+            }
         });
 
         Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
@@ -302,14 +1373,265 @@ impl PhoenixService for MasterServer {
         &self,
         request: Request<HeartbeatRequest>,
     ) -> Result<Response<HeartbeatResponse>, Status> {
+        let identity = self.peer_identity(&request)?;
         let req = request.into_inner();
-        info!(
+        if !is_bound(&self.bindings, &identity, &req.node_id) {
+            return Err(Status::permission_denied(format!(
+                "node {} is not registered by this certificate",
+                req.node_id
+            )));
+        }
+        // Unknown and dead nodes are not acked so they know to re-register.
+        let known = self.state.mark_seen(&req.node_id) == Some(NodeHealth::Healthy);
+        if known {
+            if let Some(mut s) = self.state.satellites.get_mut(&req.node_id) {
+                s.last_heartbeat = Some(HeartbeatSample::from(&req));
+            }
+        }
+        debug!(
             node_id = %req.node_id,
             bandwidth_mbps = %req.bandwidth_mbps,
             cpu_percent = %req.cpu_percent,
+            ram_used_mb = req.ram_used_mb,
+            known,
             "Heartbeat"
         );
-        Ok(Response::new(HeartbeatResponse { ack: true }))
+        Ok(Response::new(HeartbeatResponse { ack: known }))
+    }
+
+    async fn enroll(
+        &self,
+        request: Request<EnrollRequest>,
+    ) -> Result<Response<EnrollResponse>, Status> {
+        let ca = self.enrollment()?;
+        let req = request.into_inner();
+        let signed = ca
+            .redeem(&req.token, &req.node_id, &req.csr_pem)
+            .map_err(|e| enrollment_status(&req.node_id, e))?;
+        info!(node_id = %req.node_id, serial = %signed.serial, "Satellite enrolled");
+        Ok(Response::new(signed.into()))
+    }
+
+    async fn renew_certificate(
+        &self,
+        request: Request<RenewCertificateRequest>,
+    ) -> Result<Response<EnrollResponse>, Status> {
+        let ca = self.enrollment()?;
+        let identity = self
+            .peer_identity(&request)?
+            .ok_or_else(|| Status::unauthenticated("renewal requires the current certificate"))?;
+        let name = identity.primary().unwrap_or_default().to_string();
+        let signed = ca
+            .renew(&identity, &request.get_ref().csr_pem)
+            .map_err(|e| enrollment_status(&name, e))?;
+        info!(node_id = %name, serial = %signed.serial, "Satellite certificate renewed");
+        Ok(Response::new(signed.into()))
+    }
+
+    async fn sync_knowledge(
+        &self,
+        request: Request<KnowledgeSyncRequest>,
+    ) -> Result<Response<KnowledgeSyncResponse>, Status> {
+        let replicator = self
+            .replication
+            .clone()
+            .ok_or_else(|| Status::unimplemented("knowledge replication is not enabled on this master"))?;
+        let identity = self.peer_identity(&request)?;
+        let req = request.into_inner();
+        // Replicated knowledge only goes to authenticated nodes.
+        let Some(ref peer) = identity else {
+            return Err(Status::permission_denied("knowledge sync requires mTLS"));
+        };
+        if !is_bound(&self.bindings, &identity, &req.node_id) {
+            return Err(Status::permission_denied(format!(
+                "node {} is not registered by this certificate",
+                req.node_id
+            )));
+        }
+        // The role is the one the node registered with, not one it claims now.
+        let role = self
+            .state
+            .satellites
+            .get(&req.node_id)
+            .map(|s| s.role.clone())
+            .ok_or_else(|| Status::failed_precondition(format!("node {} is not registered", req.node_id)))?;
+        if !self.policy.as_ref().is_some_and(|p| p.grants_role(peer, &role)) {
+            warn!(node_id = %req.node_id, %role, "Knowledge sync refused: role not granted by the node policy");
+            return Err(Status::permission_denied(format!(
+                "role {} is not granted to this certificate by the node policy",
+                role
+            )));
+        }
+        let page = tokio::task::spawn_blocking(move || {
+            replicator.changes(&role, req.since_version, &req.scope_digest)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map_err(|e| Status::internal(e.to_string()))?;
+        debug!(
+            node_id = %req.node_id,
+            since = req.since_version,
+            version = page.version,
+            entries = page.entries.len(),
+            reset = page.reset,
+            "Knowledge sync"
+        );
+        Ok(Response::new(page))
+    }
+
+    async fn upload_artifact(
+        &self,
+        request: Request<tonic::Streaming<ArtifactChunk>>,
+    ) -> Result<Response<ArtifactStatus>, Status> {
+        let store = self.artifacts()?;
+        let identity = self.peer_identity(&request)?;
+        let mut stream = request.into_inner();
+        let mut status: Option<ArtifactStatus> = None;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            if !is_bound(&self.bindings, &identity, &chunk.node_id) {
+                return Err(Status::permission_denied(format!(
+                    "node {} is not registered by this certificate",
+                    chunk.node_id
+                )));
+            }
+            if status.as_ref().is_some_and(|s| s.sha256 != chunk.sha256) {
+                return Err(Status::invalid_argument("one artifact per upload"));
+            }
+            let store = Arc::clone(&store);
+            let (node_id, sha256) = (chunk.node_id.clone(), chunk.sha256.clone());
+            let written = tokio::task::spawn_blocking(move || store.write_chunk(&chunk))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_err(|e| artifact_status(&sha256, e))?;
+            if written.complete && !status.as_ref().is_some_and(|s| s.complete) {
+                info!(%node_id, sha256 = %written.sha256, size = written.received, "Artifact stored");
+            }
+            status = Some(written);
+        }
+        status
+            .map(Response::new)
+            .ok_or_else(|| Status::invalid_argument("empty artifact upload"))
+    }
+
+    async fn get_artifact_status(
+        &self,
+        request: Request<ArtifactStatusRequest>,
+    ) -> Result<Response<ArtifactStatus>, Status> {
+        let store = self.artifacts()?;
+        let identity = self.peer_identity(&request)?;
+        let req = request.into_inner();
+        if !is_bound(&self.bindings, &identity, &req.node_id) {
+            return Err(Status::permission_denied(format!(
+                "node {} is not registered by this certificate",
+                req.node_id
+            )));
+        }
+        store
+            .status(&req.sha256, &req.node_id)
+            .map(Response::new)
+            .map_err(|e| artifact_status(&req.sha256, e))
+    }
+
+    type FetchArtifactStream =
+        tokio_stream::wrappers::ReceiverStream<Result<ArtifactChunk, Status>>;
+
+    async fn fetch_artifact(
+        &self,
+        request: Request<FetchArtifactRequest>,
+    ) -> Result<Response<Self::FetchArtifactStream>, Status> {
+        let store = self.artifacts()?;
+        let identity = self.peer_identity(&request)?;
+        let req = request.into_inner();
+        if !is_bound(&self.bindings, &identity, &req.node_id) {
+            return Err(Status::permission_denied(format!(
+                "node {} is not registered by this certificate",
+                req.node_id
+            )));
+        }
+        let meta = store
+            .meta(&req.sha256)
+            .map_err(|e| artifact_status(&req.sha256, e))?
+            .ok_or_else(|| Status::not_found(format!("artifact {} not found", req.sha256)))?;
+        if !self.state.may_fetch(&meta, &req.node_id, &req.task_id) {
+            warn!(node_id = %req.node_id, task_id = %req.task_id, sha256 = %req.sha256, "Artifact fetch refused");
+            return Err(Status::permission_denied(format!(
+                "artifact {} is not available to node {} for task {}",
+                req.sha256, req.node_id, req.task_id
+            )));
+        }
+        let (tx, rx) = mpsc::channel(4);
+        tokio::spawn(async move {
+            let mut offset = req.offset.min(meta.size);
+            while offset < meta.size {
+                let store = Arc::clone(&store);
+                let sha256 = meta.sha256.clone();
+                let read = tokio::task::spawn_blocking(move || {
+                    store.read_at(&sha256, offset, ARTIFACT_CHUNK_SIZE)
+                })
+                .await;
+                let chunk = match read {
+                    Ok(Ok(data)) if !data.is_empty() => {
+                        let len = data.len() as u64;
+                        let chunk = ArtifactChunk {
+                            sha256: meta.sha256.clone(),
+                            size: meta.size,
+                            offset,
+                            data,
+                            name: meta.name.clone(),
+                            content_type: meta.content_type.clone(),
+                            ..Default::default()
+                        };
+                        offset += len;
+                        Ok(chunk)
+                    }
+                    Ok(Ok(_)) => Err(Status::data_loss("artifact shorter than recorded")),
+                    Ok(Err(e)) => Err(artifact_status(&meta.sha256, e)),
+                    Err(e) => Err(Status::internal(e.to_string())),
+                };
+                let failed = chunk.is_err();
+                if tx.send(chunk).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+}
+
+fn artifact_status(sha256: &str, e: ArtifactError) -> Status {
+    match e {
+        ArtifactError::NotFound(_) => Status::not_found(e.to_string()),
+        ArtifactError::OffsetMismatch { .. } => Status::failed_precondition(e.to_string()),
+        ArtifactError::StoreFull { .. } => Status::resource_exhausted(e.to_string()),
+        ArtifactError::Io(_) | ArtifactError::Corrupt { .. } => {
+            warn!(%sha256, error = %e, "Artifact store error");
+            Status::internal(e.to_string())
+        }
+        _ => Status::invalid_argument(e.to_string()),
+    }
+}
+
+fn enrollment_status(node_id: &str, e: CaError) -> Status {
+    warn!(%node_id, error = %e, "Enrollment rejected");
+    match e {
+        CaError::InvalidToken
+        | CaError::TokenExpired
+        | CaError::NodeMismatch { .. }
+        | CaError::UnknownCertificate(_)
+        | CaError::Revoked { .. } => Status::permission_denied(e.to_string()),
+        CaError::Certificate(_) => Status::invalid_argument(e.to_string()),
+        _ => Status::internal(e.to_string()),
+    }
+}
+
+impl From<SignedCert> for EnrollResponse {
+    fn from(signed: SignedCert) -> Self {
+        Self {
+            cert_pem: signed.cert_pem,
+            serial: signed.serial,
+            not_after_ms: signed.not_after_ms,
+        }
     }
 }
 
@@ -318,14 +1640,70 @@ impl PhoenixService for MasterServer {
 pub struct FederatedBridgeSkill {
     capability: String,
     handle: Arc<FederationHandle>,
+    progress_sink: Option<broadcast::Sender<String>>,
 }
 
+/// Prefix of the progress lines [`FederatedBridgeSkill`] sends to its progress sink; the gateway's
+/// `/api/v1/federation/progress` SSE stream routes lines with it as `federation_progress` events.
+pub const PROGRESS_LINE_PREFIX: &str = "FEDERATION_PROGRESS:";
+
 impl FederatedBridgeSkill {
     pub fn new(capability: String, handle: Arc<FederationHandle>) -> Self {
-        Self { capability, handle }
+        Self {
+            capability,
+            handle,
+            progress_sink: None,
+        }
+    }
+
+    /// Publishes task progress as `FEDERATION_PROGRESS:{json}` lines, e.g. on the gateway's log
+    /// broadcast channel.
+    pub fn with_progress_sink(mut self, sink: broadcast::Sender<String>) -> Self {
+        self.progress_sink = Some(sink);
+        self
+    }
+
+    fn forward_progress(&self, sink: broadcast::Sender<String>) -> mpsc::Sender<ProgressUpdate> {
+        let (tx, mut rx) = mpsc::channel::<ProgressUpdate>(64);
+        let capability = self.capability.clone();
+        tokio::spawn(async move {
+            while let Some(update) = rx.recv().await {
+                let line = progress_json(&capability, &update);
+                let _ = sink.send(format!("{}{}", PROGRESS_LINE_PREFIX, line));
+            }
+        });
+        tx
     }
 }
 
+/// JSON form of a progress update. Artifact bytes are inlined only for text content.
+fn progress_json(capability: &str, update: &ProgressUpdate) -> serde_json::Value {
+    let p = &update.progress;
+    let artifacts: Vec<serde_json::Value> = p
+        .artifacts
+        .iter()
+        .map(|a| {
+            let text = (a.content_type.starts_with("text/") || a.content_type.ends_with("json"))
+                .then(|| String::from_utf8_lossy(&a.data).into_owned());
+            serde_json::json!({
+                "name": a.name,
+                "content_type": a.content_type,
+                "size_bytes": a.data.len(),
+                "text": text,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "task_id": update.task_id,
+        "capability": capability,
+        "node_id": update.node_id,
+        "percent": (p.percent >= 0.0).then_some(p.percent),
+        "log_lines": p.log_lines,
+        "artifacts": artifacts,
+        "at_ms": p.at_ms,
+    })
+}
+
 #[async_trait::async_trait]
 impl AgentSkill for FederatedBridgeSkill {
     fn name(&self) -> &str {
@@ -344,18 +1722,26 @@ impl AgentSkill for FederatedBridgeSkill {
             .ok()
             .flatten()
             .unwrap_or_default();
+        let task_id = uuid::Uuid::new_v4().to_string();
+        let options = TaskOptions {
+            task_id: Some(task_id.clone()),
+            progress: self.progress_sink.clone().map(|sink| self.forward_progress(sink)),
+        };
         let result = self
             .handle
-            .submit_task(&self.capability, &context_json, ctx)
+            .submit_task_with(&self.capability, &context_json, ctx, options)
             .await
             .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?;
         Ok(serde_json::json!({
+            "task_id": task_id,
             "success": result.success,
             "summary": result.summary,
             "details": result.details_json,
             "node_id": result.node_id,
             "role": result.role,
             "energy_used": result.energy_used,
+            "cancelled": result.cancelled,
+            "artifacts": artifacts_json(&result.artifacts),
         }))
     }
 }
@@ -369,15 +1755,101 @@ use crate::phoenix_federation::phoenix_service_client::PhoenixServiceClient;
 use std::future::Future;
 use std::time::Duration;
 
-const HEARTBEAT_INTERVAL_SECS: u64 = 30;
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// Completed results a satellite keeps to answer re-dispatched task ids without re-running them.
+const COMPLETED_CACHE_SIZE: usize = 256;
+
+/// Lets a running task stream progress back to The Creator ahead of its final result.
+#[derive(Clone)]
+pub struct TaskReporter {
+    task_id: String,
+    node_id: String,
+    role: String,
+    tenant_id: String,
+    tx: mpsc::UnboundedSender<TaskResult>,
+    /// Satellite and master address for artifact uploads.
+    upload: (SatelliteClient, String),
+}
+
+impl TaskReporter {
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    /// Sends percent complete (0-100, or negative if unknown) and any new log lines.
+    pub fn progress(&self, percent: f32, log_lines: Vec<String>) {
+        self.send(TaskProgress {
+            percent,
+            log_lines,
+            ..Default::default()
+        });
+    }
+
+    /// Sends a piece of output before the task finishes.
+    pub fn artifact(&self, name: &str, content_type: &str, data: Vec<u8>) {
+        self.send(TaskProgress {
+            percent: -1.0,
+            artifacts: vec![PartialArtifact {
+                name: name.to_string(),
+                content_type: content_type.to_string(),
+                data,
+            }],
+            ..Default::default()
+        });
+    }
+
+    /// Uploads a large output to The Creator's artifact store (chunked, resumable) and returns
+    /// the reference to put in `TaskResult.artifacts`.
+    pub async fn upload_artifact(
+        &self,
+        name: &str,
+        content_type: &str,
+        source: impl Into<ArtifactSource>,
+    ) -> Result<ArtifactRef, FederationError> {
+        let (ref satellite, ref creator) = self.upload;
+        let mut client = PhoenixServiceClient::new(satellite.connect(creator).await?);
+        crate::artifacts::upload_artifact(
+            &mut client,
+            &self.node_id,
+            &self.task_id,
+            name,
+            content_type,
+            source.into(),
+        )
+        .await
+    }
+
+    pub fn send(&self, mut progress: TaskProgress) {
+        progress.at_ms = chrono::Utc::now().timestamp_millis();
+        let _ = self.tx.send(TaskResult {
+            task_id: self.task_id.clone(),
+            node_id: self.node_id.clone(),
+            role: self.role.clone(),
+            tenant_id: self.tenant_id.clone(),
+            progress: Some(progress),
+            ..Default::default()
+        });
+    }
+}
 
 /// Satellite client: connect to The Creator's IP, register, maintain SubmitTask stream, heartbeat every 30s.
+#[derive(Clone)]
 pub struct SatelliteClient {
     pub node_id: String,
     pub role: String,
     pub host: String,
     pub port: u32,
     pub capabilities: Vec<String>,
+    /// Client certificate and trusted CA for an mTLS master (see `mtls::tonic_client_tls`).
+    pub tls: Option<tonic::transport::ClientTlsConfig>,
+    /// Enrolled certificate, re-read on every connect and renewed before it expires.
+    pub credentials: Option<Arc<SatelliteCredentials>>,
+    /// Read-only copy of the KB scopes replicated to this node's role.
+    pub replica: Option<Arc<ReplicaStore>>,
+    pub heartbeat_interval: Duration,
+    /// In-memory connections to a [`crate::testing::FederationHarness`] instead of the network.
+    #[cfg(any(test, feature = "test-support"))]
+    pub(crate) transport: Option<crate::testing::DuplexTransport>,
 }
 
 impl SatelliteClient {
@@ -394,27 +1866,112 @@ impl SatelliteClient {
             host,
             port,
             capabilities,
+            tls: None,
+            credentials: None,
+            replica: None,
+            heartbeat_interval: HEARTBEAT_INTERVAL,
+            #[cfg(any(test, feature = "test-support"))]
+            transport: None,
+        }
+    }
+
+    /// Connect to The Creator over mTLS with `tls`.
+    pub fn with_tls(mut self, tls: tonic::transport::ClientTlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Connect over mTLS with the certificate in `credentials` (see
+    /// [`SatelliteCredentials::enroll`]), renewing it from the heartbeat loop once two thirds of
+    /// its lifetime have passed. Takes precedence over [`Self::with_tls`].
+    pub fn with_credentials(mut self, credentials: SatelliteCredentials) -> Self {
+        self.credentials = Some(Arc::new(credentials));
+        self
+    }
+
+    /// Keep `replica` in sync with The Creator's KB (SyncKnowledge after registering and on every
+    /// heartbeat). Hand the same `Arc` to executors that ground on local knowledge.
+    pub fn with_replica(mut self, replica: Arc<ReplicaStore>) -> Self {
+        self.replica = Some(replica);
+        self
+    }
+
+    /// Heartbeat (and certificate/knowledge refresh) period; 30s by default.
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    fn endpoint(&self, addr: &str) -> Result<tonic::transport::Endpoint, FederationError> {
+        let tls = match self.credentials {
+            Some(ref credentials) => Some(credentials.tls().map_err(FederationError::Connect)?),
+            None => self.tls.clone(),
+        };
+        let endpoint = tonic::transport::Endpoint::from_shared(addr.to_string())
+            .map_err(|e| FederationError::Connect(e.to_string()))?;
+        match tls {
+            Some(tls) => endpoint
+                .tls_config(tls)
+                .map_err(|e| FederationError::Connect(e.to_string())),
+            None => Ok(endpoint),
         }
     }
 
+    async fn connect(&self, addr: &str) -> Result<tonic::transport::Channel, FederationError> {
+        let endpoint = self.endpoint(addr)?;
+        #[cfg(any(test, feature = "test-support"))]
+        if let Some(ref transport) = self.transport {
+            return transport
+                .connect(endpoint)
+                .await
+                .map_err(|e| FederationError::Connect(e.to_string()));
+        }
+        endpoint
+            .connect()
+            .await
+            .map_err(|e| FederationError::Connect(e.to_string()))
+    }
+
     /// Run the satellite: connect to The Creator at `creator_addr` (e.g. "https://192.168.1.2:50052"),
     /// register, open bi-di stream (send READY, then receive Task -> run executor -> send TaskResult),
-    /// and send heartbeat every 30s.
-    /// `executor` runs each Task locally and returns the TaskResult to send back.
-    pub async fn run<F, Fut>(
+    /// and send heartbeat every 30s (stopped when this returns). An unacked heartbeat means The Creator evicted this node, so it
+    /// registers again and re-sends READY on the open stream.
+    /// `executor` runs each Task locally and returns the TaskResult to send back; a task id seen
+    /// before is answered from the last `COMPLETED_CACHE_SIZE` results instead.
+    pub async fn run<F, Fut>(&self, creator_addr: &str, mut executor: F) -> Result<(), FederationError>
+    where
+        F: FnMut(Task) -> Fut + Send + 'static,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        self.run_with_progress(creator_addr, move |task, _reporter| executor(task))
+            .await
+    }
+
+    /// Receipt of `task`, sent before it runs.
+    fn ack(&self, task: &Task) -> TaskResult {
+        TaskResult {
+            task_id: task.task_id.clone(),
+            node_id: self.node_id.clone(),
+            role: self.role.clone(),
+            tenant_id: task.tenant_id.clone(),
+            ack: true,
+            ..Default::default()
+        }
+    }
+
+    /// Like [`Self::run`], but the executor also gets a [`TaskReporter`] for progress updates.
+    /// Tasks run concurrently; a cancel from The Creator aborts the task's future and answers
+    /// with a `cancelled` result.
+    pub async fn run_with_progress<F, Fut>(
         &self,
         creator_addr: &str,
         mut executor: F,
     ) -> Result<(), FederationError>
     where
-        F: FnMut(Task) -> Fut + Send + 'static,
+        F: FnMut(Task, TaskReporter) -> Fut + Send + 'static,
         Fut: Future<Output = TaskResult> + Send + 'static,
     {
-        let channel = tonic::transport::Channel::from_shared(creator_addr.to_string())
-            .map_err(|e| FederationError::Connect(e.to_string()))?
-            .connect()
-            .await
-            .map_err(|e| FederationError::Connect(e.to_string()))?;
+        let channel = self.connect(creator_addr).await?;
 
         let mut client = PhoenixServiceClient::new(channel);
 
@@ -424,34 +1981,38 @@ impl SatelliteClient {
             host: self.host.clone(),
             port: self.port,
             capabilities: self.capabilities.clone(),
-            hardware: Some(crate::phoenix_federation::HardwareSpecs {
-                cpu_cores: 0,
-                ram_mb: 0,
-                os: std::env::consts::OS.to_string(),
-                arch: std::env::consts::ARCH.to_string(),
-            }),
+            hardware: Some(local_hardware()),
         };
         let _ = client
-            .register_node(req)
+            .register_node(req.clone())
             .await
             .map_err(|e| FederationError::Connect(e.to_string()))?;
+        if let Some(ref replica) = self.replica {
+            if let Err(e) = sync_replica(&mut client, &self.node_id, replica).await {
+                warn!(node_id = %self.node_id, error = %e, "Knowledge sync failed");
+            }
+        }
 
         let (tx, rx) = mpsc::unbounded_channel::<TaskResult>();
         let out_stream = tokio_stream::wrappers::UnboundedReceiverStream::new(rx);
 
-        let task_tx = tx.clone();
-        let ready = TaskResult {
-            task_id: String::new(),
-            success: true,
-            summary: READY_SUMMARY.to_string(),
-            details_json: String::new(),
-            node_id: self.node_id.clone(),
-            role: self.role.clone(),
-            completed_at_ms: chrono::Utc::now().timestamp_millis(),
-            energy_used: 0,
-            tenant_id: String::new(),
+        let ready = {
+            let node_id = self.node_id.clone();
+            let role = self.role.clone();
+            move || TaskResult {
+                task_id: String::new(),
+                success: true,
+                summary: READY_SUMMARY.to_string(),
+                details_json: String::new(),
+                node_id: node_id.clone(),
+                role: role.clone(),
+                completed_at_ms: chrono::Utc::now().timestamp_millis(),
+                energy_used: 0,
+                tenant_id: String::new(),
+                ..Default::default()
+            }
         };
-        task_tx.send(ready).ok();
+        tx.send(ready()).ok();
 
         let mut in_stream = client
             .submit_task(tonic::Request::new(out_stream))
@@ -460,37 +2021,470 @@ impl SatelliteClient {
             .into_inner();
 
         let node_id = self.node_id.clone();
-        let heartbeat_addr = creator_addr.to_string();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+        let ready_tx = tx.clone();
+        let this = self.clone();
+        let creator = creator_addr.to_string();
+        let _heartbeats = AbortOnDrop(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(this.heartbeat_interval);
             loop {
                 interval.tick().await;
-                if let Ok(ep) =
-                    tonic::transport::Endpoint::from_shared(heartbeat_addr.clone())
-                {
-                    if let Ok(ch) = ep.connect().await {
-                        let mut c = PhoenixServiceClient::new(ch);
-                        let _ = c
-                            .heartbeat(HeartbeatRequest {
-                                node_id: node_id.clone(),
-                                bandwidth_mbps: 0.0,
-                                cpu_percent: 0.0,
-                                ram_used_mb: 0,
-                                at_ms: chrono::Utc::now().timestamp_millis(),
-                            })
-                            .await;
+                if let Some(ref credentials) = this.credentials {
+                    match credentials.renew_if_due(&creator).await {
+                        Ok(true) => info!(node_id = %node_id, "Renewed satellite certificate"),
+                        Ok(false) => {}
+                        Err(e) => warn!(node_id = %node_id, error = %e, "Certificate renewal failed"),
+                    }
+                }
+                if let Ok(ch) = this.connect(&creator).await {
+                    let mut c = PhoenixServiceClient::new(ch);
+                    let (cpu_percent, ram_used_mb) = local_load();
+                    let acked = c
+                        .heartbeat(HeartbeatRequest {
+                            node_id: node_id.clone(),
+                            bandwidth_mbps: 0.0,
+                            cpu_percent,
+                            ram_used_mb,
+                            at_ms: chrono::Utc::now().timestamp_millis(),
+                        })
+                        .await
+                        .map(|r| r.into_inner().ack);
+                    if let Ok(false) = acked {
+                        if c.register_node(req.clone()).await.is_ok() {
+                            ready_tx.send(ready()).ok();
+                        }
+                    }
+                    if let Some(ref replica) = this.replica {
+                        if let Err(e) = sync_replica(&mut c, &node_id, replica).await {
+                            warn!(node_id = %node_id, error = %e, "Knowledge sync failed");
+                        }
                     }
                 }
             }
-        });
+        }));
 
-        while let Some(Ok(task)) = in_stream.next().await {
-            let result = executor(task).await;
+        let mut completed: std::collections::VecDeque<TaskResult> =
+            std::collections::VecDeque::with_capacity(COMPLETED_CACHE_SIZE);
+        // Running tasks by id, with the tenant for a cancelled result.
+        let mut running: std::collections::HashMap<String, (tokio::task::JoinHandle<()>, String)> =
+            std::collections::HashMap::new();
+        let (done_tx, mut done_rx) = mpsc::unbounded_channel::<TaskResult>();
+        loop {
+            let result = tokio::select! {
+                message = in_stream.next() => {
+                    let Some(Ok(task)) = message else { break };
+                    if let Some(control) = task.control {
+                        match running.remove(&task.task_id) {
+                            Some((job, tenant_id)) if control.cancel => {
+                                job.abort();
+                                TaskResult {
+                                    task_id: task.task_id,
+                                    success: false,
+                                    summary: format!("cancelled: {}", control.reason),
+                                    node_id: self.node_id.clone(),
+                                    role: self.role.clone(),
+                                    completed_at_ms: chrono::Utc::now().timestamp_millis(),
+                                    tenant_id,
+                                    cancelled: true,
+                                    ..Default::default()
+                                }
+                            }
+                            Some(entry) => {
+                                running.insert(task.task_id, entry);
+                                continue;
+                            }
+                            None => continue,
+                        }
+                    } else if let Some(done) = completed.iter().find(|r| r.task_id == task.task_id) {
+                        done.clone()
+                    } else if running.contains_key(&task.task_id) {
+                        // Already running; its result answers both dispatches.
+                        tx.send(self.ack(&task)).ok();
+                        continue;
+                    } else {
+                        tx.send(self.ack(&task)).ok();
+                        let reporter = TaskReporter {
+                            task_id: task.task_id.clone(),
+                            node_id: self.node_id.clone(),
+                            role: self.role.clone(),
+                            tenant_id: task.tenant_id.clone(),
+                            tx: tx.clone(),
+                            upload: (self.clone(), creator_addr.to_string()),
+                        };
+                        let (task_id, tenant_id) = (task.task_id.clone(), task.tenant_id.clone());
+                        let job = executor(task, reporter);
+                        let done_tx = done_tx.clone();
+                        let job = tokio::spawn(async move {
+                            let _ = done_tx.send(job.await);
+                        });
+                        running.insert(task_id, (job, tenant_id));
+                        continue;
+                    }
+                }
+                Some(result) = done_rx.recv() => {
+                    running.remove(&result.task_id);
+                    result
+                }
+            };
+            if !completed.iter().any(|r| r.task_id == result.task_id) {
+                if completed.len() == COMPLETED_CACHE_SIZE {
+                    completed.pop_front();
+                }
+                completed.push_back(result.clone());
+            }
             if tx.send(result).is_err() {
                 break;
             }
         }
+        for (job, _) in running.into_values() {
+            job.abort();
+        }
 
         Ok(())
     }
 }
+
+/// Aborts a background task (the heartbeat loop) when its owner goes away.
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Pulls SyncKnowledge pages into `replica` until it has caught up; returns the entries applied.
+async fn sync_replica(
+    client: &mut PhoenixServiceClient<tonic::transport::Channel>,
+    node_id: &str,
+    replica: &ReplicaStore,
+) -> Result<usize, FederationError> {
+    let mut applied = 0;
+    loop {
+        let page = client
+            .sync_knowledge(KnowledgeSyncRequest {
+                node_id: node_id.to_string(),
+                since_version: replica.version(),
+                scope_digest: replica.scope_digest(),
+            })
+            .await
+            .map_err(|s| FederationError::Sync(s.message().to_string()))?
+            .into_inner();
+        applied += replica.apply(&page)?;
+        if !page.has_more {
+            return Ok(applied);
+        }
+    }
+}
+
+/// Cores, RAM, OS and arch of this machine, announced at registration.
+fn local_hardware() -> HardwareSpecs {
+    let cpu_cores = std::thread::available_parallelism()
+        .map(|n| n.get() as u32)
+        .unwrap_or(0);
+    HardwareSpecs {
+        cpu_cores,
+        ram_mb: meminfo_mb("MemTotal:").unwrap_or(0),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+    }
+}
+
+/// CPU load (1-minute load average as a percentage of cores) and RAM in use, for heartbeats.
+/// Zero where `/proc` is unavailable.
+fn local_load() -> (f64, u64) {
+    let cores = std::thread::available_parallelism()
+        .map(|n| n.get() as f64)
+        .unwrap_or(1.0);
+    let cpu_percent = std::fs::read_to_string("/proc/loadavg")
+        .ok()
+        .and_then(|s| s.split_whitespace().next()?.parse::<f64>().ok())
+        .map(|load| (load / cores * 100.0).min(100.0))
+        .unwrap_or(0.0);
+    let ram_used_mb = match (meminfo_mb("MemTotal:"), meminfo_mb("MemAvailable:")) {
+        (Some(total), Some(available)) => total.saturating_sub(available),
+        _ => 0,
+    };
+    (cpu_percent, ram_used_mb)
+}
+
+fn meminfo_mb(field: &str) -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let kb: u64 = meminfo
+        .lines()
+        .find(|l| l.starts_with(field))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()?;
+    Some(kb / 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::LeastLoaded;
+
+    fn satellite(id: &str, capability: &str) -> SatelliteInfo {
+        let now = chrono::Utc::now().timestamp_millis();
+        SatelliteInfo::for_test(id, "Research", &[capability], now)
+    }
+
+    #[tokio::test]
+    async fn resubmitting_a_finished_task_returns_the_stored_result() {
+        let queue = Arc::new(TaskQueue::temporary().unwrap());
+        let task = Task {
+            task_id: "t1".into(),
+            goal: "research".into(),
+            tenant_id: "tenant".into(),
+            ..Default::default()
+        };
+        queue.enqueue(&task).unwrap();
+        queue.mark_dispatched("t1", "a").unwrap();
+        queue
+            .complete(&TaskResult {
+                task_id: "t1".into(),
+                success: true,
+                summary: "done".into(),
+                node_id: "a".into(),
+                ..Default::default()
+            })
+            .unwrap();
+        let state = Arc::new(MasterState::new().with_queue(Arc::clone(&queue)));
+        let handle = FederationHandle::with_scheduler(state, Arc::new(LeastLoaded));
+        let submit = |tenant_id: &str| {
+            let ctx = TenantContext {
+                tenant_id: tenant_id.into(),
+                correlation_id: None,
+                agent_id: None,
+                session_id: None,
+                capabilities: None,
+            };
+            let options = TaskOptions {
+                task_id: Some("t1".into()),
+                progress: None,
+            };
+            let handle = &handle;
+            async move {
+                handle
+                    .submit_task_with("research", "{}", &ctx, options)
+                    .await
+            }
+        };
+
+        // No satellite is connected: the answer comes from the queue.
+        assert_eq!(submit("tenant").await.unwrap().summary, "done");
+        assert!(matches!(
+            submit("other").await,
+            Err(FederationError::DuplicateTask(_))
+        ));
+        assert_eq!(
+            queue.get("t1").unwrap().unwrap().state,
+            TaskState::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn queued_tasks_are_replayed_after_a_restart_and_completed_once() {
+        // A task accepted (and dispatched) by a previous master process.
+        let queue = Arc::new(TaskQueue::temporary().unwrap());
+        let task = Task {
+            task_id: "t1".into(),
+            goal: "research".into(),
+            context_json: "{}".into(),
+            tenant_id: "tenant".into(),
+            created_at_ms: 1,
+            control: None,
+        };
+        queue.enqueue(&task).unwrap();
+        queue.mark_dispatched("t1", "gone").unwrap();
+
+        let state = Arc::new(MasterState::new().with_queue(Arc::clone(&queue)));
+        state.satellites.insert("a".into(), satellite("a", "research"));
+        let handle = Arc::new(FederationHandle::with_scheduler(
+            Arc::clone(&state),
+            Arc::new(LeastLoaded),
+        ));
+        let (a_tx, mut a_rx) = mpsc::channel(4);
+        handle.register_node_tx("a".into(), a_tx);
+
+        handle.replay_queued();
+        handle.replay_queued();
+        let replayed = a_rx.recv().await.unwrap().unwrap();
+        assert_eq!(replayed, task);
+        state.acknowledge(&TaskResult {
+            task_id: "t1".into(),
+            node_id: "a".into(),
+            ack: true,
+            ..Default::default()
+        });
+        assert_eq!(queue.get("t1").unwrap().unwrap().state, TaskState::Acked);
+
+        let result = TaskResult {
+            task_id: "t1".into(),
+            success: true,
+            node_id: "a".into(),
+            ..Default::default()
+        };
+        assert!(state.complete(result.clone()));
+        assert!(!state.complete(result));
+        let record = queue.get("t1").unwrap().unwrap();
+        assert_eq!((record.state, record.attempts), (TaskState::Completed, 2));
+        // The second sweep did not dispatch a duplicate.
+        assert!(a_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn only_the_dispatched_node_can_complete_a_task() {
+        let queue = Arc::new(TaskQueue::temporary().unwrap());
+        let state = Arc::new(MasterState::new().with_queue(Arc::clone(&queue)));
+        state.satellites.insert("a".into(), satellite("a", "research"));
+        state.satellites.insert("b".into(), satellite("b", "research"));
+        let handle = Arc::new(FederationHandle::with_scheduler(
+            Arc::clone(&state),
+            Arc::new(LeastLoaded),
+        ));
+        let (a_tx, mut a_rx) = mpsc::channel(4);
+        handle.register_node_tx("a".into(), a_tx);
+
+        let submitter = Arc::clone(&handle);
+        let task = tokio::spawn(async move {
+            let ctx = TenantContext {
+                tenant_id: "tenant".into(),
+                correlation_id: None,
+                agent_id: None,
+                session_id: None,
+                capabilities: None,
+            };
+            let options = TaskOptions {
+                task_id: Some("t1".into()),
+                progress: None,
+            };
+            submitter.submit_task_with("research", "{}", &ctx, options).await
+        });
+        assert_eq!(a_rx.recv().await.unwrap().unwrap().task_id, "t1");
+
+        let result = |node_id: &str| TaskResult {
+            task_id: "t1".into(),
+            success: true,
+            summary: format!("from {node_id}"),
+            node_id: node_id.into(),
+            ..Default::default()
+        };
+        assert!(!state.complete(result("b")));
+        assert_eq!(queue.get("t1").unwrap().unwrap().state, TaskState::Dispatched);
+        assert!(state.complete(result("a")));
+        assert_eq!(task.await.unwrap().unwrap().summary, "from a");
+        assert_eq!(queue.result("t1").unwrap().unwrap().node_id, "a");
+    }
+
+    #[tokio::test]
+    async fn progress_reaches_the_submitter_and_cancel_stops_the_task() {
+        let state = Arc::new(MasterState::new());
+        state.satellites.insert("a".into(), satellite("a", "research"));
+        let handle = Arc::new(FederationHandle::with_scheduler(
+            Arc::clone(&state),
+            Arc::new(LeastLoaded),
+        ));
+        let (a_tx, mut a_rx) = mpsc::channel(4);
+        handle.register_node_tx("a".into(), a_tx);
+
+        let (progress_tx, mut progress_rx) = mpsc::channel(4);
+        let submitter = Arc::clone(&handle);
+        let task = tokio::spawn(async move {
+            let ctx = TenantContext {
+                tenant_id: "test".into(),
+                correlation_id: None,
+                agent_id: None,
+                session_id: None,
+                capabilities: None,
+            };
+            let options = TaskOptions {
+                task_id: Some("t1".into()),
+                progress: Some(progress_tx),
+            };
+            submitter.submit_task_with("research", "{}", &ctx, options).await
+        });
+        assert_eq!(a_rx.recv().await.unwrap().unwrap().task_id, "t1");
+
+        let frame = |node_id: &str| TaskResult {
+            task_id: "t1".into(),
+            node_id: node_id.into(),
+            progress: Some(TaskProgress {
+                percent: 50.0,
+                log_lines: vec!["half way".into()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(state.report_progress(&frame("a")));
+        assert!(!state.report_progress(&frame("b")));
+        let update = progress_rx.recv().await.unwrap();
+        assert_eq!(update.progress.percent, 50.0);
+        assert_eq!(progress_json("research", &update)["log_lines"][0], "half way");
+
+        assert!(handle.cancel_task("t1", "operator").await.unwrap());
+        assert!(!handle.cancel_task("t1", "operator").await.unwrap());
+        let control = a_rx.recv().await.unwrap().unwrap();
+        assert!(control.control.is_some_and(|c| c.cancel));
+        assert!(matches!(
+            task.await.unwrap(),
+            Err(FederationError::Cancelled { ref task_id, .. }) if task_id == "t1"
+        ));
+    }
+
+    #[tokio::test]
+    async fn knowledge_sync_is_refused_without_mtls() {
+        let dir =
+            std::env::temp_dir().join(format!("pagi_federation_sync_{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(KnowledgeStore::open_with_key(&dir, Some(&[7u8; 32])).unwrap());
+        let policy = crate::replication::ReplicationPolicy::parse(
+            "[[role]]\nrole = \"*\"\nscopes = [{ slot = 5 }]",
+        )
+        .unwrap();
+        let replicator = KnowledgeReplicator::temporary(store, policy).unwrap();
+        let state = Arc::new(MasterState::new());
+        state.satellites.insert("a".into(), satellite("a", "research"));
+        let server = MasterServer::new(state).with_replication(Arc::new(replicator));
+
+        let denied = server
+            .sync_knowledge(Request::new(KnowledgeSyncRequest {
+                node_id: "a".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+        drop(server);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn cancel_reports_a_satellite_that_was_not_told() {
+        let state = Arc::new(MasterState::new());
+        let handle = FederationHandle::with_scheduler(Arc::clone(&state), Arc::new(LeastLoaded));
+        let (a_tx, a_rx) = mpsc::channel(1);
+        handle.register_node_tx("a".into(), a_tx);
+        drop(a_rx);
+        let (result_tx, result_rx) = oneshot::channel();
+        state.pending.insert(
+            "t1".into(),
+            PendingTask {
+                node_id: "a".into(),
+                goal: "research".into(),
+                created_at_ms: 0,
+                attempt: 1,
+                result_tx,
+                progress: None,
+                acked: true,
+            },
+        );
+
+        assert!(matches!(
+            handle.cancel_task("t1", "operator").await,
+            Err(FederationError::CancelUndelivered { ref node_id, .. }) if node_id == "a"
+        ));
+        // The submitter is released regardless.
+        assert!(matches!(result_rx.await, Ok(TaskOutcome::Cancelled(_))));
+        assert!(state.pending.is_empty());
+    }
+}
@@ -3,8 +3,20 @@
 //! gRPC-based nervous system connecting The Creator (Master Orchestrator) to remote Satellites.
 //! Bare-metal, mTLS-secured, binary Protocol Buffers for bandwidth efficiency.
 
+pub mod artifacts;
+pub mod ca;
+pub mod cli;
+pub mod enrollment;
 pub mod federation;
+pub mod liveness;
+pub mod queue;
 pub mod mtls;
+pub mod policy;
+pub mod replication;
+pub mod scheduler;
+pub mod status;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 
 // Generated gRPC types (package federation); flat module, no nested "federation".
 #[allow(dead_code, unreachable_pub)]
@@ -13,12 +25,40 @@ pub mod phoenix_federation {
 }
 
 pub use federation::{
-    FederatedBridgeSkill, FederationHandle, MasterServer, SatelliteClient, SatelliteInfo,
+    FederatedBridgeSkill, FederationError, FederationHandle, HeartbeatSample, MasterServer,
+    MasterState, PendingTask, ProgressUpdate, SatelliteClient, SatelliteInfo, TaskOptions,
+    TaskReporter, FINISHED_TASK_RETENTION, PROGRESS_LINE_PREFIX,
+};
+pub use artifacts::{
+    fetch_artifact, upload_artifact, ArtifactError, ArtifactMeta, ArtifactRetention,
+    ArtifactSource, ArtifactStore, PruneReport, ARTIFACT_CHUNK_SIZE,
+};
+pub use ca::{
+    generate_csr, CaError, CertificateAuthority, EnrollmentToken, IssuedCert, SignedCert,
+    DEFAULT_CERT_TTL, DEFAULT_TOKEN_TTL,
+};
+pub use enrollment::SatelliteCredentials;
+pub use queue::{QueueError, QueuedTask, TaskQueue, TaskState};
+pub use liveness::{system_clock, Clock, LivenessConfig, NodeHealth, NODE_STATE_PREFIX};
+pub use mtls::{
+    client_tls_config, client_tls_from_env, server_tls_config, server_tls_from_env,
+    tonic_client_tls, PeerIdentity, PeerInfo, ServerTlsOptions,
+};
+pub use policy::{NodeGrant, NodePolicy, PolicyError};
+pub use replication::{
+    KnowledgeReplicator, ReplicaStore, ReplicationError, ReplicationPolicy, ReplicationScope,
+    RoleReplication, REPLICATION_PAGE_ENTRIES,
+};
+pub use status::{NodeEvent, NodeHardware, NodeStatus, TaskStatus, NODE_EVENT_CAPACITY};
+pub use scheduler::{
+    scheduler_from_env, LeastLoaded, RoleAffinity, RoundRobin, Scheduler, Weighted,
 };
-pub use mtls::{client_tls_config, server_tls_config};
 pub use phoenix_federation::{
     phoenix_service_client::PhoenixServiceClient,
     phoenix_service_server::{PhoenixService, PhoenixServiceServer},
-    HardwareSpecs, HeartbeatRequest, HeartbeatResponse, RegisterNodeRequest,
-    RegisterNodeResponse, Task, TaskResult,
+    ArtifactChunk, ArtifactRef, ArtifactStatus, ArtifactStatusRequest, EnrollRequest,
+    EnrollResponse, FetchArtifactRequest, HardwareSpecs, HeartbeatRequest, HeartbeatResponse,
+    KnowledgeEntry, KnowledgeSyncRequest, KnowledgeSyncResponse, RegisterNodeRequest,
+    PartialArtifact, RegisterNodeResponse, RenewCertificateRequest, Task, TaskControl,
+    TaskProgress, TaskResult,
 };
@@ -0,0 +1,250 @@
+//! Satellite liveness: heartbeat deadlines, eviction and task re-dispatch.
+//!
+//! Registration, heartbeats and task-stream messages refresh a satellite's `last_seen_ms`. The
+//! liveness sweep (started by `MasterServer::serve`, or by
+//! `FederationHandle::spawn_liveness_monitor`) classifies each node by how long it has been silent:
+//!
+//! | State | Silent for | Effect |
+//! |-------|------------|--------|
+//! | [`NodeHealth::Healthy`] | < `suspect_after` | Scheduled normally. |
+//! | [`NodeHealth::Suspect`] | ≥ `suspect_after` | Scheduled only if no healthy node has the capability. |
+//! | [`NodeHealth::Dead`] | ≥ `dead_after` | Evicted: task sender dropped, in-flight tasks re-dispatched. |
+//!
+//! A satellite whose task stream closes is evicted at once. Re-dispatched tasks keep their
+//! `task_id`; the master accepts the first result for an id and satellites replay results for ids
+//! they already ran. Dead nodes must re-register, and are forgotten after another `dead_after`.
+//! Every transition is logged to KB-08 under [`NODE_STATE_PREFIX`].
+
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Current time in Unix milliseconds, as seen by the liveness sweep (`MasterState::with_clock`).
+pub type Clock = Arc<dyn Fn() -> i64 + Send + Sync>;
+
+/// The wall clock; the default for [`crate::MasterState`].
+pub fn system_clock() -> Clock {
+    Arc::new(|| chrono::Utc::now().timestamp_millis())
+}
+
+/// Key prefix for KB-08 (Soma) satellite state transitions.
+pub const NODE_STATE_PREFIX: &str = "federation_node_state/";
+
+/// Liveness of a registered satellite.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeHealth {
+    #[default]
+    Healthy,
+    Suspect,
+    Dead,
+}
+
+impl NodeHealth {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Suspect => "suspect",
+            Self::Dead => "dead",
+        }
+    }
+}
+
+impl std::fmt::Display for NodeHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Heartbeat deadlines and the re-dispatch budget.
+#[derive(Debug, Clone)]
+pub struct LivenessConfig {
+    /// Silence after which a node is only used as a last resort.
+    pub suspect_after: Duration,
+    /// Silence after which a node is evicted.
+    pub dead_after: Duration,
+    /// How often the sweep runs.
+    pub sweep_interval: Duration,
+    /// Dispatches per task, counting the first; each lost node costs one.
+    pub max_attempts: u32,
+    /// Overall deadline for a task across all attempts.
+    pub task_timeout: Duration,
+    /// Time a node has to acknowledge a task before it is sent again; zero disables.
+    pub ack_timeout: Duration,
+}
+
+impl Default for LivenessConfig {
+    /// Suspect after 2.5 missed 30s heartbeats, dead after 4, three attempts within 300s, 30s
+    /// to acknowledge.
+    fn default() -> Self {
+        Self {
+            suspect_after: Duration::from_secs(75),
+            dead_after: Duration::from_secs(120),
+            sweep_interval: Duration::from_secs(15),
+            max_attempts: 3,
+            task_timeout: Duration::from_secs(300),
+            ack_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl LivenessConfig {
+    /// Reads `PAGI_FEDERATION_SUSPECT_AFTER_SECS`, `PAGI_FEDERATION_DEAD_AFTER_SECS`,
+    /// `PAGI_FEDERATION_MAX_TASK_ATTEMPTS`, `PAGI_FEDERATION_TASK_TIMEOUT_SECS` and
+    /// `PAGI_FEDERATION_ACK_TIMEOUT_SECS` (0 disables); unset or invalid values keep the
+    /// defaults. The sweep runs five times per suspect window.
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok()?.trim().parse().ok()
+        }
+        let defaults = Self::default();
+        let suspect_after = var::<u64>("PAGI_FEDERATION_SUSPECT_AFTER_SECS")
+            .filter(|s| *s > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.suspect_after);
+        let dead_after = var::<u64>("PAGI_FEDERATION_DEAD_AFTER_SECS")
+            .map(Duration::from_secs)
+            .unwrap_or(defaults.dead_after)
+            .max(suspect_after);
+        Self {
+            suspect_after,
+            dead_after,
+            sweep_interval: (suspect_after / 5).max(Duration::from_secs(1)),
+            max_attempts: var("PAGI_FEDERATION_MAX_TASK_ATTEMPTS")
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_attempts),
+            task_timeout: var::<u64>("PAGI_FEDERATION_TASK_TIMEOUT_SECS")
+                .filter(|s| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.task_timeout),
+            ack_timeout: var::<u64>("PAGI_FEDERATION_ACK_TIMEOUT_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.ack_timeout),
+        }
+    }
+
+    /// Health of a node that has been silent for `silent_ms`.
+    pub fn classify(&self, silent_ms: i64) -> NodeHealth {
+        let silent = Duration::from_millis(silent_ms.max(0) as u64);
+        if silent >= self.dead_after {
+            NodeHealth::Dead
+        } else if silent >= self.suspect_after {
+            NodeHealth::Suspect
+        } else {
+            NodeHealth::Healthy
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::{FederationHandle, MasterState, SatelliteInfo};
+    use crate::phoenix_federation::TaskResult;
+    use crate::scheduler::RoundRobin;
+    use pagi_core::TenantContext;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    fn config() -> LivenessConfig {
+        LivenessConfig {
+            suspect_after: Duration::from_secs(60),
+            dead_after: Duration::from_secs(90),
+            sweep_interval: Duration::from_secs(1),
+            max_attempts: 2,
+            task_timeout: Duration::from_secs(5),
+            ack_timeout: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn silence_is_classified_against_the_deadlines() {
+        let config = config();
+        assert_eq!(config.classify(59_000), NodeHealth::Healthy);
+        assert_eq!(config.classify(60_000), NodeHealth::Suspect);
+        assert_eq!(config.classify(90_000), NodeHealth::Dead);
+    }
+
+    #[test]
+    fn suspect_nodes_recover_when_seen_but_dead_nodes_must_register() {
+        let state = Arc::new(MasterState::new().with_clock(Arc::new(|| 1_000_000)));
+        let now = state.now_ms();
+        for (id, silent_ms) in [("a", 70_000), ("b", 95_000)] {
+            let node = SatelliteInfo::for_test(id, "RedTeam", &["scan"], now - silent_ms);
+            state.satellites.insert(id.into(), node);
+        }
+        let handle =
+            FederationHandle::with_scheduler(Arc::clone(&state), Arc::new(RoundRobin::default()))
+                .with_liveness(config());
+        handle.check_liveness();
+        assert_eq!(
+            state.satellites.get("a").unwrap().health,
+            NodeHealth::Suspect
+        );
+        assert_eq!(state.satellites.get("b").unwrap().health, NodeHealth::Dead);
+
+        assert_eq!(state.mark_seen("a"), Some(NodeHealth::Healthy));
+        assert_eq!(state.mark_seen("b"), Some(NodeHealth::Dead));
+        assert_eq!(
+            state.satellites.get("b").unwrap().last_seen_ms,
+            now - 95_000
+        );
+    }
+
+    #[tokio::test]
+    async fn dead_node_is_evicted_and_its_task_redispatched_with_the_same_id() {
+        let config = config();
+        let state = Arc::new(MasterState::new().with_clock(Arc::new(|| 1_000_000)));
+        let now = state.now_ms();
+        state.satellites.insert(
+            "a".into(),
+            SatelliteInfo::for_test("a", "RedTeam", &["scan"], now),
+        );
+        state.satellites.insert(
+            "b".into(),
+            SatelliteInfo::for_test("b", "RedTeam", &["scan"], now),
+        );
+        let handle = Arc::new(
+            FederationHandle::with_scheduler(Arc::clone(&state), Arc::new(RoundRobin::default()))
+                .with_liveness(config),
+        );
+        let (a_tx, mut a_rx) = mpsc::channel(4);
+        let (b_tx, mut b_rx) = mpsc::channel(4);
+        handle.register_node_tx("a".into(), a_tx);
+        handle.register_node_tx("b".into(), b_tx);
+
+        let submitter = Arc::clone(&handle);
+        let task = tokio::spawn(async move {
+            let ctx = TenantContext {
+                tenant_id: "test".into(),
+                correlation_id: None,
+                agent_id: None,
+                session_id: None,
+                capabilities: None,
+            };
+            submitter.submit_task("scan", "{}", &ctx).await
+        });
+
+        // Round-robin sends the first attempt to "a", which then goes silent.
+        let first = a_rx.recv().await.unwrap().unwrap();
+        state.satellites.get_mut("a").unwrap().last_seen_ms = now - 100_000;
+        handle.check_liveness();
+        assert_eq!(state.satellites.get("a").unwrap().health, NodeHealth::Dead);
+
+        let second = b_rx.recv().await.unwrap().unwrap();
+        assert_eq!(second.task_id, first.task_id);
+        let result = TaskResult {
+            task_id: second.task_id.clone(),
+            success: true,
+            summary: "done".into(),
+            node_id: "b".into(),
+            ..Default::default()
+        };
+        assert!(state.complete(result.clone()));
+        // A late duplicate for the same id is ignored.
+        assert!(!state.complete(result));
+
+        let result = task.await.unwrap().unwrap();
+        assert_eq!(result.node_id, "b");
+        assert_eq!(state.satellites.get("b").unwrap().in_flight, 0);
+    }
+}
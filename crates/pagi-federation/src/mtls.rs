@@ -1,13 +1,31 @@
 //! mTLS (Mutual TLS) configuration for The Creator <-> Satellite links.
 //! Only bare-metal nodes with valid certificates can handshake.
+//!
+//! The Creator terminates TLS itself ([`tls_incoming`]) so each connection carries its client
+//! certificate chain as [`PeerInfo`]; `MasterServer` derives a [`PeerIdentity`] (subject CN and
+//! SANs) from the leaf and checks it against the node policy (`crate::policy`). With a CRL
+//! ([`ServerTlsOptions::crl`]) the verifier re-reads it whenever the file changes, so a revoked
+//! satellite is refused on its next handshake without restarting The Creator.
 
-use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::client::danger::HandshakeSignatureValid;
+use rustls::pki_types::{CertificateDer, CertificateRevocationListDer, PrivateKeyDer, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
 use rustls::server::WebPkiClientVerifier;
-use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use rustls::{
+    ClientConfig, DigitallySignedStruct, DistinguishedName, RootCertStore, ServerConfig,
+    SignatureScheme,
+};
 use std::fs::File;
 use std::io::BufReader;
-use std::path::Path;
-use std::sync::Arc;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, warn};
 
 /// Load PEM certificates from a file (server cert chain or CA certs).
 fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, std::io::Error> {
@@ -30,6 +48,19 @@ fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, std::io::Erro
     Ok(key)
 }
 
+/// The crypto provider for every config built here (explicit, so it does not depend on which
+/// rustls backends other crates in the build enable).
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// tonic's client connector uses the process-wide provider; install ours unless one already is.
+pub(crate) fn install_default_provider() {
+    if rustls::crypto::CryptoProvider::get_default().is_none() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    }
+}
+
 /// Build server TLS config for The Creator (Master): present server cert, require client cert (mTLS).
 /// - `server_cert_path`: PEM chain (server cert + optional intermediates).
 /// - `server_key_path`: PEM private key for the server.
@@ -39,24 +70,199 @@ pub fn server_tls_config(
     server_key_path: &Path,
     client_ca_path: &Path,
 ) -> Result<Arc<ServerConfig>, String> {
-    let certs = load_certs(server_cert_path).map_err(|e| e.to_string())?;
-    let key = load_private_key(server_key_path).map_err(|e| e.to_string())?;
-    let client_ca_certs = load_certs(client_ca_path).map_err(|e| e.to_string())?;
+    ServerTlsOptions::new(server_cert_path, server_key_path, client_ca_path).build()
+}
+
+/// Server TLS for The Creator, with the optional CRL and enrollment settings.
+#[derive(Debug, Clone)]
+pub struct ServerTlsOptions {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    pub client_ca: PathBuf,
+    /// PEM CRL(s) from the client CA; re-read when the file changes.
+    pub crl: Option<PathBuf>,
+    /// Accept connections without a client certificate. Only the Enroll RPC serves them.
+    pub allow_anonymous: bool,
+}
 
-    let mut client_ca_store = RootCertStore::empty();
-    for cert in client_ca_certs {
-        client_ca_store.add(cert).map_err(|e| e.to_string())?;
+impl ServerTlsOptions {
+    pub fn new(cert: &Path, key: &Path, client_ca: &Path) -> Self {
+        Self {
+            cert: cert.to_path_buf(),
+            key: key.to_path_buf(),
+            client_ca: client_ca.to_path_buf(),
+            crl: None,
+            allow_anonymous: false,
+        }
     }
-    let verifier = WebPkiClientVerifier::builder(Arc::new(client_ca_store))
-        .build()
-        .map_err(|e| e.to_string())?;
 
-    let config = ServerConfig::builder()
-        .with_client_cert_verifier(verifier)
-        .with_single_cert(certs, key)
-        .map_err(|e| e.to_string())?;
+    /// `PAGI_FEDERATION_TLS_CERT`, `PAGI_FEDERATION_TLS_KEY`, `PAGI_FEDERATION_TLS_CLIENT_CA` and
+    /// the optional `PAGI_FEDERATION_TLS_CRL`. `None` when none of the first three are set.
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let paths = env_paths(&[
+            "PAGI_FEDERATION_TLS_CERT",
+            "PAGI_FEDERATION_TLS_KEY",
+            "PAGI_FEDERATION_TLS_CLIENT_CA",
+        ])?;
+        let crl = env_paths(&["PAGI_FEDERATION_TLS_CRL"])?.and_then(|mut p| p.pop());
+        Ok(paths.map(|p| Self {
+            crl,
+            ..Self::new(&p[0], &p[1], &p[2])
+        }))
+    }
 
-    Ok(Arc::new(config))
+    pub fn build(&self) -> Result<Arc<ServerConfig>, String> {
+        let certs = load_certs(&self.cert).map_err(|e| e.to_string())?;
+        let key = load_private_key(&self.key).map_err(|e| e.to_string())?;
+        let client_ca_certs = load_certs(&self.client_ca).map_err(|e| e.to_string())?;
+
+        let mut client_ca_store = RootCertStore::empty();
+        for cert in client_ca_certs {
+            client_ca_store.add(cert).map_err(|e| e.to_string())?;
+        }
+        let roots = Arc::new(client_ca_store);
+        let verifier: Arc<dyn ClientCertVerifier> = match self.crl {
+            Some(ref crl) => Arc::new(CrlVerifier::new(roots, crl.clone(), self.allow_anonymous)?),
+            None => webpki_verifier(roots, Vec::new(), self.allow_anonymous)?,
+        };
+
+        let mut config = ServerConfig::builder_with_provider(provider())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(certs, key)
+            .map_err(|e| e.to_string())?;
+        // gRPC clients require HTTP/2 to be negotiated.
+        config.alpn_protocols = vec![b"h2".to_vec()];
+
+        Ok(Arc::new(config))
+    }
+}
+
+fn webpki_verifier(
+    roots: Arc<RootCertStore>,
+    crls: Vec<CertificateRevocationListDer<'static>>,
+    allow_anonymous: bool,
+) -> Result<Arc<dyn ClientCertVerifier>, String> {
+    let mut builder =
+        WebPkiClientVerifier::builder_with_provider(roots, provider()).with_crls(crls);
+    if allow_anonymous {
+        builder = builder.allow_unauthenticated();
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+fn load_crls(path: &Path) -> Result<Vec<CertificateRevocationListDer<'static>>, String> {
+    let pem = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    rustls_pemfile::crls(&mut pem.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Modification time and size of the CRL file; `None` when it cannot be read.
+type CrlStamp = Option<(SystemTime, u64)>;
+
+/// Client verifier that rebuilds itself from the CRL file whenever its modification time or size
+/// changes. An unreadable CRL keeps the previous one.
+#[derive(Debug)]
+struct CrlVerifier {
+    roots: Arc<RootCertStore>,
+    crl_path: PathBuf,
+    allow_anonymous: bool,
+    hints: Vec<DistinguishedName>,
+    current: RwLock<(CrlStamp, Arc<dyn ClientCertVerifier>)>,
+}
+
+impl CrlVerifier {
+    fn new(
+        roots: Arc<RootCertStore>,
+        crl_path: PathBuf,
+        allow_anonymous: bool,
+    ) -> Result<Self, String> {
+        let stamp = crl_stamp(&crl_path);
+        let inner = webpki_verifier(Arc::clone(&roots), load_crls(&crl_path)?, allow_anonymous)?;
+        Ok(Self {
+            roots,
+            crl_path,
+            allow_anonymous,
+            hints: inner.root_hint_subjects().to_vec(),
+            current: RwLock::new((stamp, inner)),
+        })
+    }
+
+    fn current(&self) -> Arc<dyn ClientCertVerifier> {
+        let stamp = crl_stamp(&self.crl_path);
+        {
+            let current = self.current.read().unwrap_or_else(|e| e.into_inner());
+            if current.0 == stamp {
+                return Arc::clone(&current.1);
+            }
+        }
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        if current.0 != stamp {
+            match load_crls(&self.crl_path).and_then(|crls| {
+                webpki_verifier(Arc::clone(&self.roots), crls, self.allow_anonymous)
+            }) {
+                Ok(verifier) => {
+                    debug!(path = %self.crl_path.display(), "Reloaded federation CRL");
+                    *current = (stamp, verifier);
+                }
+                Err(e) => warn!(error = %e, "Keeping previous federation CRL"),
+            }
+        }
+        Arc::clone(&current.1)
+    }
+}
+
+fn crl_stamp(path: &Path) -> CrlStamp {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+impl ClientCertVerifier for CrlVerifier {
+    fn offer_client_auth(&self) -> bool {
+        true
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        !self.allow_anonymous
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &self.hints
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        self.current()
+            .verify_client_cert(end_entity, intermediates, now)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.current().verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.current().verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.current().supported_verify_schemes()
+    }
 }
 
 /// Build client TLS config for a Satellite: present client cert, trust The Creator's server via `ca_path`.
@@ -77,10 +283,469 @@ pub fn client_tls_config(
         root_store.add(cert).map_err(|e| e.to_string())?;
     }
 
-    let config = ClientConfig::builder()
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
         .with_root_certificates(root_store)
         .with_client_auth_cert(certs, key)
         .map_err(|e| e.to_string())?;
 
     Ok(Arc::new(config))
 }
+
+/// Tonic channel TLS for a Satellite (client cert + key, CA of The Creator's server cert).
+/// `domain` must match a name in the server certificate.
+pub fn tonic_client_tls(
+    client_cert_path: &Path,
+    client_key_path: &Path,
+    ca_path: &Path,
+    domain: &str,
+) -> Result<tonic::transport::ClientTlsConfig, String> {
+    install_default_provider();
+    let read = |p: &Path| std::fs::read(p).map_err(|e| format!("{}: {}", p.display(), e));
+    Ok(tonic::transport::ClientTlsConfig::new()
+        .ca_certificate(tonic::transport::Certificate::from_pem(read(ca_path)?))
+        .identity(tonic::transport::Identity::from_pem(
+            read(client_cert_path)?,
+            read(client_key_path)?,
+        ))
+        .domain_name(domain))
+}
+
+/// Server TLS from [`ServerTlsOptions::from_env`]. `None` when none of the certificate paths are
+/// set; an error when only some are.
+pub fn server_tls_from_env() -> Result<Option<Arc<ServerConfig>>, String> {
+    ServerTlsOptions::from_env()?.map(|o| o.build()).transpose()
+}
+
+/// Satellite channel TLS from `PAGI_FEDERATION_TLS_CLIENT_CERT`, `PAGI_FEDERATION_TLS_CLIENT_KEY`,
+/// `PAGI_FEDERATION_TLS_SERVER_CA` and `PAGI_FEDERATION_TLS_DOMAIN` (default `localhost`).
+pub fn client_tls_from_env() -> Result<Option<tonic::transport::ClientTlsConfig>, String> {
+    let paths = env_paths(&[
+        "PAGI_FEDERATION_TLS_CLIENT_CERT",
+        "PAGI_FEDERATION_TLS_CLIENT_KEY",
+        "PAGI_FEDERATION_TLS_SERVER_CA",
+    ])?;
+    let domain = std::env::var("PAGI_FEDERATION_TLS_DOMAIN")
+        .ok()
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or_else(|| "localhost".to_string());
+    paths
+        .map(|p| tonic_client_tls(&p[0], &p[1], &p[2], domain.trim()))
+        .transpose()
+}
+
+fn env_paths(names: &[&str]) -> Result<Option<Vec<PathBuf>>, String> {
+    let values: Vec<Option<PathBuf>> = names
+        .iter()
+        .map(|n| {
+            std::env::var(n)
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| PathBuf::from(v.trim()))
+        })
+        .collect();
+    if values.iter().all(Option::is_none) {
+        return Ok(None);
+    }
+    if let Some(i) = values.iter().position(Option::is_none) {
+        return Err(format!(
+            "{} is required when any of {:?} is set",
+            names[i], names
+        ));
+    }
+    Ok(Some(values.into_iter().flatten().collect()))
+}
+
+/// Names a client certificate vouches for: subject CN and DNS/URI SANs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerIdentity {
+    /// Certificate serial number, lowercase hex.
+    pub serial: String,
+    pub common_name: Option<String>,
+    pub dns_names: Vec<String>,
+    pub uris: Vec<String>,
+}
+
+impl PeerIdentity {
+    /// Parses the leaf certificate (DER).
+    pub fn from_der(der: &[u8]) -> Result<Self, String> {
+        use x509_parser::prelude::{FromDer, GeneralName, X509Certificate};
+        let (_, cert) = X509Certificate::from_der(der).map_err(|e| e.to_string())?;
+        let common_name = cert
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_string);
+        let mut dns_names = Vec::new();
+        let mut uris = Vec::new();
+        if let Ok(Some(san)) = cert.subject_alternative_name() {
+            for name in &san.value.general_names {
+                match name {
+                    GeneralName::DNSName(n) => dns_names.push(n.to_string()),
+                    GeneralName::URI(u) => uris.push(u.to_string()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(Self {
+            serial: crate::ca::hex(cert.raw_serial()),
+            common_name,
+            dns_names,
+            uris,
+        })
+    }
+
+    /// CN first, then SANs.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.common_name
+            .iter()
+            .chain(&self.dns_names)
+            .chain(&self.uris)
+            .map(String::as_str)
+    }
+
+    /// The name used in logs and bindings: the CN, else the first SAN.
+    pub fn primary(&self) -> Option<&str> {
+        self.names().next()
+    }
+
+    pub fn has_name(&self, name: &str) -> bool {
+        self.names().any(|n| n == name)
+    }
+}
+
+/// Connection info attached to every request on a [`tls_incoming`] connection.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    pub remote_addr: Option<SocketAddr>,
+    /// Client certificate chain, leaf first.
+    pub certs: Arc<Vec<CertificateDer<'static>>>,
+}
+
+impl PeerInfo {
+    /// Identity from the leaf certificate, if the client presented one.
+    pub fn identity(&self) -> Option<Result<PeerIdentity, String>> {
+        self.certs
+            .first()
+            .map(|leaf| PeerIdentity::from_der(leaf.as_ref()))
+    }
+}
+
+/// A TLS connection accepted by [`tls_incoming`].
+pub struct MtlsConnection {
+    stream: tokio_rustls::server::TlsStream<TcpStream>,
+    info: PeerInfo,
+}
+
+impl tonic::transport::server::Connected for MtlsConnection {
+    type ConnectInfo = PeerInfo;
+
+    fn connect_info(&self) -> PeerInfo {
+        self.info.clone()
+    }
+}
+
+impl AsyncRead for MtlsConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for MtlsConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+/// Accepts TCP connections on `listener` and completes the TLS handshake for each (in parallel),
+/// yielding only connections whose client certificate `config`'s verifier accepted.
+pub fn tls_incoming(
+    listener: TcpListener,
+    config: Arc<ServerConfig>,
+) -> tokio_stream::wrappers::ReceiverStream<Result<MtlsConnection, std::io::Error>> {
+    let acceptor = tokio_rustls::TlsAcceptor::from(config);
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+    tokio::spawn(async move {
+        loop {
+            let (tcp, remote_addr) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!(error = %e, "Federation accept failed");
+                    continue;
+                }
+            };
+            let acceptor = acceptor.clone();
+            let conn_tx = tx.clone();
+            tokio::spawn(async move {
+                match acceptor.accept(tcp).await {
+                    Ok(stream) => {
+                        let certs = stream
+                            .get_ref()
+                            .1
+                            .peer_certificates()
+                            .map(|c| c.iter().map(|c| c.clone().into_owned()).collect())
+                            .unwrap_or_default();
+                        let info = PeerInfo {
+                            remote_addr: Some(remote_addr),
+                            certs: Arc::new(certs),
+                        };
+                        let _ = conn_tx.send(Ok(MtlsConnection { stream, info })).await;
+                    }
+                    Err(e) => debug!(%remote_addr, error = %e, "Rejected TLS handshake"),
+                }
+            });
+            if tx.is_closed() {
+                break;
+            }
+        }
+    });
+    tokio_stream::wrappers::ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::{MasterServer, MasterState};
+    use crate::phoenix_federation::phoenix_service_client::PhoenixServiceClient;
+    use crate::phoenix_federation::{KnowledgeSyncRequest, RegisterNodeRequest};
+    use crate::policy::NodePolicy;
+    use crate::replication::{KnowledgeReplicator, ReplicationPolicy};
+    use pagi_core::KnowledgeStore;
+    use rcgen::{
+        BasicConstraints, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    };
+
+    /// Throwaway PKI: a CA, a `localhost` server cert and client certs for `kali-1` and `rogue`,
+    /// plus `outsider`: a `kali-1` certificate issued by an unrelated CA.
+    fn write_pki(dir: &Path) -> PeerIdentity {
+        std::fs::create_dir_all(dir).unwrap();
+        let write = |name: &str, pem: String| std::fs::write(dir.join(name), pem).unwrap();
+
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "pagi test ca");
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+        write("ca.pem", ca.pem());
+
+        let server_key = KeyPair::generate().unwrap();
+        let mut server_params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        server_params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+        let server = server_params.signed_by(&server_key, &ca, &ca_key).unwrap();
+        write("server.pem", server.pem());
+        write("server.key", server_key.serialize_pem());
+
+        let mut kali = None;
+        for (cn, san) in [
+            ("kali-1", "kali-1.satellites.pagi"),
+            ("rogue", "rogue.example"),
+        ] {
+            let key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(vec![san.to_string()]).unwrap();
+            params.distinguished_name.push(DnType::CommonName, cn);
+            params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+            let cert = params.signed_by(&key, &ca, &ca_key).unwrap();
+            write(&format!("{cn}.pem"), cert.pem());
+            write(&format!("{cn}.key"), key.serialize_pem());
+            if cn == "kali-1" {
+                kali = Some(PeerIdentity::from_der(cert.der()).unwrap());
+            }
+        }
+
+        let other_ca_key = KeyPair::generate().unwrap();
+        let mut other_ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        other_ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        other_ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "pagi test ca");
+        let other_ca = other_ca_params.self_signed(&other_ca_key).unwrap();
+        let key = KeyPair::generate().unwrap();
+        let mut params =
+            CertificateParams::new(vec!["kali-1.satellites.pagi".to_string()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, "kali-1");
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+        let cert = params.signed_by(&key, &other_ca, &other_ca_key).unwrap();
+        write("outsider.pem", cert.pem());
+        write("outsider.key", key.serialize_pem());
+        kali.unwrap()
+    }
+
+    fn registration(node_id: &str, capability: &str) -> RegisterNodeRequest {
+        RegisterNodeRequest {
+            node_id: node_id.into(),
+            role: "RedTeam".into(),
+            host: "127.0.0.1".into(),
+            port: 0,
+            capabilities: vec![capability.into()],
+            hardware: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn registrations_are_bound_to_the_client_certificate() {
+        let dir =
+            std::env::temp_dir().join(format!("pagi_federation_mtls_{}", uuid::Uuid::new_v4()));
+        let kali = write_pki(&dir);
+        assert_eq!(kali.primary(), Some("kali-1"));
+        assert!(kali.has_name("kali-1.satellites.pagi"));
+
+        let policy: NodePolicy = toml::from_str(
+            r#"
+            [[node]]
+            identity = "kali-1.satellites.pagi"
+            node_ids = ["kali-1"]
+            roles = ["RedTeam"]
+            capabilities = ["scan"]
+            "#,
+        )
+        .unwrap();
+        assert!(policy
+            .authorize(&kali, "kali-1", "redteam", &["scan".into()])
+            .is_ok());
+        assert!(policy.grants_role(&kali, "redteam"));
+        let any_role: NodePolicy =
+            toml::from_str("[[node]]\nidentity = \"kali-1\"\ncapabilities = [\"*\"]").unwrap();
+        assert!(any_role
+            .authorize(&kali, "kali-1", "RedTeam", &["scan".into()])
+            .is_ok());
+        assert!(!any_role.grants_role(&kali, "RedTeam"));
+
+        let store =
+            Arc::new(KnowledgeStore::open_with_key(dir.join("kb"), Some(&[7u8; 32])).unwrap());
+        store.insert(5, "protocols/recon", b"step 1").unwrap();
+        let replicator = KnowledgeReplicator::temporary(
+            store,
+            ReplicationPolicy::parse("[[role]]\nrole = \"RedTeam\"\nscopes = [{ slot = 5 }]")
+                .unwrap(),
+        )
+        .unwrap();
+
+        let tls = server_tls_config(
+            &dir.join("server.pem"),
+            &dir.join("server.key"),
+            &dir.join("ca.pem"),
+        )
+        .unwrap();
+        let server = Arc::new(
+            MasterServer::new(Arc::new(MasterState::new()))
+                .with_tls(tls)
+                .with_policy(policy)
+                .with_replication(Arc::new(replicator)),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = format!(
+            "https://localhost:{}",
+            listener.local_addr().unwrap().port()
+        );
+        let served = tokio::spawn(MasterServer::serve_on(Arc::clone(&server), listener));
+
+        let connect = |tls: tonic::transport::ClientTlsConfig| {
+            let addr = addr.clone();
+            async move {
+                let channel = tonic::transport::Endpoint::from_shared(addr)
+                    .unwrap()
+                    .tls_config(tls)
+                    .unwrap()
+                    .connect()
+                    .await?;
+                Ok::<_, tonic::transport::Error>(PhoenixServiceClient::new(channel))
+            }
+        };
+        let client_tls = |cn: &str| {
+            tonic_client_tls(
+                &dir.join(format!("{cn}.pem")),
+                &dir.join(format!("{cn}.key")),
+                &dir.join("ca.pem"),
+                "localhost",
+            )
+            .unwrap()
+        };
+
+        let mut kali_client = connect(client_tls("kali-1")).await.unwrap();
+        kali_client
+            .register_node(registration("kali-1", "scan"))
+            .await
+            .unwrap();
+        let page = kali_client
+            .sync_knowledge(KnowledgeSyncRequest {
+                node_id: "kali-1".into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(page.entries.len(), 1);
+        for (node_id, capability) in [("kali-2", "scan"), ("kali-1", "nmap")] {
+            let denied = kali_client
+                .register_node(registration(node_id, capability))
+                .await
+                .unwrap_err();
+            assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+        }
+        // A role the certificate's policy entry does not grant.
+        let denied = kali_client
+            .register_node(RegisterNodeRequest {
+                role: "BlueTeam".into(),
+                ..registration("kali-1", "scan")
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+
+        // A valid certificate that the policy does not know.
+        let mut rogue = connect(client_tls("rogue")).await.unwrap();
+        let denied = rogue
+            .register_node(registration("kali-1", "scan"))
+            .await
+            .unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+
+        // No client certificate: the handshake is refused.
+        let anonymous = tonic::transport::ClientTlsConfig::new()
+            .ca_certificate(tonic::transport::Certificate::from_pem(
+                std::fs::read(dir.join("ca.pem")).unwrap(),
+            ))
+            .domain_name("localhost");
+        let refused = match connect(anonymous).await {
+            Ok(mut client) => client
+                .register_node(registration("kali-1", "scan"))
+                .await
+                .is_err(),
+            Err(_) => true,
+        };
+        assert!(refused);
+
+        // Same names as kali-1, but issued by another CA: the handshake is refused.
+        let refused = match connect(client_tls("outsider")).await {
+            Ok(mut client) => client
+                .register_node(registration("kali-1", "scan"))
+                .await
+                .is_err(),
+            Err(_) => true,
+        };
+        assert!(refused);
+
+        served.abort();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
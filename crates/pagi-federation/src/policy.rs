@@ -0,0 +1,130 @@
+//! Node policy: which node ids, roles and capabilities a client certificate may claim.
+//!
+//! Loaded from the TOML file named by `PAGI_FEDERATION_NODE_POLICY`; one `[[node]]` per
+//! certificate identity (subject CN or SAN):
+//!
+//! ```toml
+//! [[node]]
+//! identity = "kali-1.satellites.pagi"
+//! node_ids = ["kali-1"]                       # default: the certificate's own names
+//! roles = ["RedTeam"]                         # default: any role
+//! capabilities = ["red_team_scan", "nmap"]    # "*" allows any; default: none
+//! ```
+//!
+//! Identities without an entry are rejected outright.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::mtls::PeerIdentity;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PolicyError {
+    #[error("node policy {path}: {reason}")]
+    Load { path: String, reason: String },
+    #[error("certificate identity {0:?} is not in the node policy")]
+    UnknownIdentity(String),
+    #[error("{identity} may not register as node {node_id}")]
+    NodeIdNotAllowed { identity: String, node_id: String },
+    #[error("{identity} may not register with role {role}")]
+    RoleNotAllowed { identity: String, role: String },
+    #[error("{identity} may not offer capability {capability}")]
+    CapabilityNotAllowed {
+        identity: String,
+        capability: String,
+    },
+}
+
+/// What one certificate identity may claim.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeGrant {
+    pub identity: String,
+    #[serde(default)]
+    pub node_ids: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NodePolicy {
+    #[serde(rename = "node", default)]
+    pub nodes: Vec<NodeGrant>,
+}
+
+impl NodePolicy {
+    pub fn load(path: &Path) -> Result<Self, PolicyError> {
+        let load_err = |reason: String| PolicyError::Load {
+            path: path.display().to_string(),
+            reason,
+        };
+        let text = std::fs::read_to_string(path).map_err(|e| load_err(e.to_string()))?;
+        toml::from_str(&text).map_err(|e| load_err(e.to_string()))
+    }
+
+    /// The policy named by `PAGI_FEDERATION_NODE_POLICY`, if set.
+    pub fn from_env() -> Result<Option<Self>, PolicyError> {
+        match std::env::var("PAGI_FEDERATION_NODE_POLICY") {
+            Ok(path) if !path.trim().is_empty() => Self::load(Path::new(path.trim())).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// The grant for the first of `identity`'s names that has one.
+    pub fn grant_for(&self, identity: &PeerIdentity) -> Option<&NodeGrant> {
+        identity
+            .names()
+            .find_map(|name| self.nodes.iter().find(|g| g.identity == name))
+    }
+
+    /// Whether the grant for `identity` names `role` explicitly. Knowledge sync requires this: an
+    /// empty `roles` list lets a node register with any role but replicates nothing to it.
+    pub fn grants_role(&self, identity: &PeerIdentity, role: &str) -> bool {
+        self.grant_for(identity)
+            .is_some_and(|g| g.roles.iter().any(|r| r.eq_ignore_ascii_case(role)))
+    }
+
+    /// Checks a registration claim against the grant for `identity`.
+    pub fn authorize(
+        &self,
+        identity: &PeerIdentity,
+        node_id: &str,
+        role: &str,
+        capabilities: &[String],
+    ) -> Result<(), PolicyError> {
+        let name = identity.primary().unwrap_or_default().to_string();
+        let grant = self
+            .grant_for(identity)
+            .ok_or_else(|| PolicyError::UnknownIdentity(name.clone()))?;
+        let node_allowed = if grant.node_ids.is_empty() {
+            identity.has_name(node_id)
+        } else {
+            grant.node_ids.iter().any(|n| n == node_id)
+        };
+        if !node_allowed {
+            return Err(PolicyError::NodeIdNotAllowed {
+                identity: name,
+                node_id: node_id.to_string(),
+            });
+        }
+        if !grant.roles.is_empty() && !grant.roles.iter().any(|r| r.eq_ignore_ascii_case(role)) {
+            return Err(PolicyError::RoleNotAllowed {
+                identity: name,
+                role: role.to_string(),
+            });
+        }
+        let any = grant.capabilities.iter().any(|c| c == "*");
+        if let Some(c) = capabilities
+            .iter()
+            .find(|c| !any && !grant.capabilities.contains(c))
+        {
+            return Err(PolicyError::CapabilityNotAllowed {
+                identity: name,
+                capability: c.clone(),
+            });
+        }
+        Ok(())
+    }
+}
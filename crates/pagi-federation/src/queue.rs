@@ -0,0 +1,376 @@
+//! Durable federation task queue (sled) with at-least-once delivery.
+//!
+//! With `MasterState::with_queue`, every task `FederationHandle::submit_task_with` accepts is
+//! written here before dispatch and moves through:
+//!
+//! | State | Meaning |
+//! |-------|---------|
+//! | [`TaskState::Queued`] | Accepted, not yet sent to a satellite. |
+//! | [`TaskState::Dispatched`] | Sent to `node_id`; no receipt yet. |
+//! | [`TaskState::Acked`] | The satellite confirmed receipt (`TaskResult.ack`). |
+//! | [`TaskState::Completed`] | A final result arrived; it is kept in the `results` tree. |
+//! | [`TaskState::Failed`] | The master gave up (timeout, retries exhausted, cancelled). |
+//!
+//! Tasks that are not finished when the master restarts are re-dispatched by the liveness sweep
+//! under the same `task_id` once a capable satellite connects; satellites answer ids they already
+//! ran from their result cache. Only the first final result moves a task to `Completed`, so late
+//! or duplicate results are dropped.
+
+use std::path::Path;
+
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::phoenix_federation::{Task, TaskResult};
+
+/// Error from reading or updating the queue.
+#[derive(Debug, thiserror::Error)]
+pub enum QueueError {
+    #[error("queue storage: {0}")]
+    Storage(#[from] sled::Error),
+    #[error("corrupt queue record for task {task_id}: {reason}")]
+    Corrupt { task_id: String, reason: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Queued,
+    Dispatched,
+    Acked,
+    Completed,
+    Failed,
+}
+
+impl TaskState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+}
+
+/// A task as stored in the queue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedTask {
+    pub task_id: String,
+    pub goal: String,
+    pub context_json: String,
+    pub tenant_id: String,
+    pub created_at_ms: i64,
+    pub state: TaskState,
+    /// Node of the latest dispatch (or of the result).
+    pub node_id: Option<String>,
+    /// Dispatches so far, across restarts.
+    pub attempts: u32,
+    pub updated_at_ms: i64,
+    /// Why the task failed.
+    pub error: Option<String>,
+}
+
+impl QueuedTask {
+    fn from_task(task: &Task) -> Self {
+        Self {
+            task_id: task.task_id.clone(),
+            goal: task.goal.clone(),
+            context_json: task.context_json.clone(),
+            tenant_id: task.tenant_id.clone(),
+            created_at_ms: task.created_at_ms,
+            state: TaskState::Queued,
+            node_id: None,
+            attempts: 0,
+            updated_at_ms: now_ms(),
+            error: None,
+        }
+    }
+
+    /// The task to (re-)dispatch.
+    pub fn to_task(&self) -> Task {
+        Task {
+            task_id: self.task_id.clone(),
+            goal: self.goal.clone(),
+            context_json: self.context_json.clone(),
+            tenant_id: self.tenant_id.clone(),
+            created_at_ms: self.created_at_ms,
+            control: None,
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Sled-backed task queue. Records are JSON in the `tasks` tree; final results are protobuf in
+/// the `results` tree.
+pub struct TaskQueue {
+    tasks: sled::Tree,
+    results: sled::Tree,
+}
+
+impl TaskQueue {
+    /// Opens or creates the queue database at `path` (e.g. `./data/pagi_federation_queue`).
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, QueueError> {
+        Self::from_db(sled::open(path)?)
+    }
+
+    /// A queue that is deleted when dropped (tests, ephemeral masters).
+    pub fn temporary() -> Result<Self, QueueError> {
+        Self::from_db(sled::Config::new().temporary(true).open()?)
+    }
+
+    fn from_db(db: sled::Db) -> Result<Self, QueueError> {
+        Ok(Self {
+            tasks: db.open_tree("tasks")?,
+            results: db.open_tree("results")?,
+        })
+    }
+
+    /// Stores `task` as queued. A known id is left untouched and its existing record returned, so
+    /// a resubmission cannot reset a dispatched task or discard a stored result.
+    pub fn enqueue(&self, task: &Task) -> Result<Option<QueuedTask>, QueueError> {
+        let record = QueuedTask::from_task(task);
+        match self.tasks.compare_and_swap(
+            task.task_id.as_bytes(),
+            None as Option<&[u8]>,
+            Some(encode(&record)),
+        )? {
+            Ok(()) => {
+                self.tasks.flush()?;
+                Ok(None)
+            }
+            Err(existing) => match existing.current {
+                Some(bytes) => decode(&task.task_id, &bytes).map(Some),
+                // Removed between the read and the swap; store it again.
+                None => self.enqueue(task),
+            },
+        }
+    }
+
+    pub fn get(&self, task_id: &str) -> Result<Option<QueuedTask>, QueueError> {
+        self.tasks
+            .get(task_id.as_bytes())?
+            .map(|bytes| decode(task_id, &bytes))
+            .transpose()
+    }
+
+    /// Every stored task, oldest first.
+    pub fn list(&self) -> Result<Vec<QueuedTask>, QueueError> {
+        let mut all = self
+            .tasks
+            .iter()
+            .map(|entry| {
+                let (key, bytes) = entry?;
+                decode(&String::from_utf8_lossy(&key), &bytes)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        all.sort_by_key(|t| (t.created_at_ms, t.task_id.clone()));
+        Ok(all)
+    }
+
+    /// Tasks not yet completed or failed, oldest first.
+    pub fn unfinished(&self) -> Result<Vec<QueuedTask>, QueueError> {
+        let mut all = self.list()?;
+        all.retain(|t| !t.state.is_finished());
+        Ok(all)
+    }
+
+    pub fn mark_dispatched(&self, task_id: &str, node_id: &str) -> Result<bool, QueueError> {
+        self.transition(task_id, |t| {
+            if t.state.is_finished() {
+                return false;
+            }
+            t.state = TaskState::Dispatched;
+            t.node_id = Some(node_id.to_string());
+            t.attempts += 1;
+            true
+        })
+    }
+
+    /// Records the receipt of a task by the node it was last sent to.
+    pub fn mark_acked(&self, task_id: &str, node_id: &str) -> Result<bool, QueueError> {
+        self.transition(task_id, |t| {
+            if t.state != TaskState::Dispatched || t.node_id.as_deref() != Some(node_id) {
+                return false;
+            }
+            t.state = TaskState::Acked;
+            true
+        })
+    }
+
+    /// Stores the final result. Returns false if the task is unknown, already finished or was
+    /// last dispatched to another node, i.e. for late, duplicate and unsolicited results.
+    pub fn complete(&self, result: &TaskResult) -> Result<bool, QueueError> {
+        let completed = self.transition(&result.task_id, |t| {
+            if t.state.is_finished() || t.node_id.as_deref() != Some(result.node_id.as_str()) {
+                return false;
+            }
+            t.state = TaskState::Completed;
+            true
+        })?;
+        if completed {
+            self.results
+                .insert(result.task_id.as_bytes(), result.encode_to_vec())?;
+            self.results.flush()?;
+        }
+        Ok(completed)
+    }
+
+    /// Marks an unfinished task failed. Returns false if it was unknown or already finished.
+    pub fn fail(&self, task_id: &str, reason: &str) -> Result<bool, QueueError> {
+        self.transition(task_id, |t| {
+            if t.state.is_finished() {
+                return false;
+            }
+            t.state = TaskState::Failed;
+            t.error = Some(reason.to_string());
+            true
+        })
+    }
+
+    /// The final result of a completed task.
+    pub fn result(&self, task_id: &str) -> Result<Option<TaskResult>, QueueError> {
+        self.results
+            .get(task_id.as_bytes())?
+            .map(|bytes| {
+                TaskResult::decode(bytes.as_ref()).map_err(|e| QueueError::Corrupt {
+                    task_id: task_id.to_string(),
+                    reason: e.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Removes finished tasks (and their results) last updated before `before_ms`.
+    pub fn prune_finished(&self, before_ms: i64) -> Result<usize, QueueError> {
+        let stale: Vec<String> = self
+            .list()?
+            .into_iter()
+            .filter(|t| t.state.is_finished() && t.updated_at_ms < before_ms)
+            .map(|t| t.task_id)
+            .collect();
+        for task_id in &stale {
+            self.tasks.remove(task_id.as_bytes())?;
+            self.results.remove(task_id.as_bytes())?;
+        }
+        Ok(stale.len())
+    }
+
+    /// Applies `change` with compare-and-swap; `change` returns false to leave the record as is.
+    fn transition(
+        &self,
+        task_id: &str,
+        change: impl Fn(&mut QueuedTask) -> bool,
+    ) -> Result<bool, QueueError> {
+        loop {
+            let Some(current) = self.tasks.get(task_id.as_bytes())? else {
+                return Ok(false);
+            };
+            let mut record = decode(task_id, &current)?;
+            if !change(&mut record) {
+                return Ok(false);
+            }
+            record.updated_at_ms = now_ms();
+            let swapped = self.tasks.compare_and_swap(
+                task_id.as_bytes(),
+                Some(current),
+                Some(encode(&record)),
+            )?;
+            if swapped.is_ok() {
+                self.tasks.flush()?;
+                return Ok(true);
+            }
+        }
+    }
+}
+
+fn encode(record: &QueuedTask) -> Vec<u8> {
+    serde_json::to_vec(record).unwrap_or_default()
+}
+
+fn decode(task_id: &str, bytes: &[u8]) -> Result<QueuedTask, QueueError> {
+    serde_json::from_slice(bytes).map_err(|e| QueueError::Corrupt {
+        task_id: task_id.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tasks_move_through_states_and_late_results_are_dropped() {
+        let dir = tempdir();
+        let task = Task {
+            task_id: "t1".into(),
+            goal: "scan".into(),
+            context_json: "{}".into(),
+            tenant_id: "tenant".into(),
+            created_at_ms: 1,
+            control: None,
+        };
+        {
+            let queue = TaskQueue::open(&dir).unwrap();
+            assert!(queue.enqueue(&task).unwrap().is_none());
+            assert!(queue.mark_dispatched("t1", "a").unwrap());
+            assert!(!queue.mark_acked("t1", "b").unwrap());
+            assert!(queue.mark_acked("t1", "a").unwrap());
+        }
+
+        // Reopened after a "restart": the task is still owed a result.
+        let queue = TaskQueue::open(&dir).unwrap();
+        let pending = queue.unfinished().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].state, TaskState::Acked);
+        assert_eq!(pending[0].to_task(), task);
+
+        let result = TaskResult {
+            task_id: "t1".into(),
+            success: true,
+            summary: "done".into(),
+            node_id: "a".into(),
+            ..Default::default()
+        };
+        assert!(queue.complete(&result).unwrap());
+        assert!(!queue.complete(&result).unwrap());
+        assert!(!queue.fail("t1", "timeout").unwrap());
+        assert_eq!(queue.result("t1").unwrap().unwrap().summary, "done");
+        assert!(queue.unfinished().unwrap().is_empty());
+
+        assert_eq!(queue.prune_finished(i64::MAX).unwrap(), 1);
+        assert!(queue.get("t1").unwrap().is_none());
+        drop(queue);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn results_from_other_nodes_and_resubmissions_do_not_overwrite() {
+        let queue = TaskQueue::temporary().unwrap();
+        let task = Task {
+            task_id: "t1".into(),
+            goal: "scan".into(),
+            tenant_id: "tenant".into(),
+            ..Default::default()
+        };
+        let result = |node_id: &str| TaskResult {
+            task_id: "t1".into(),
+            success: true,
+            summary: format!("from {node_id}"),
+            node_id: node_id.into(),
+            ..Default::default()
+        };
+        queue.enqueue(&task).unwrap();
+        // Not dispatched yet: nobody may complete it.
+        assert!(!queue.complete(&result("a")).unwrap());
+        queue.mark_dispatched("t1", "a").unwrap();
+        assert!(!queue.complete(&result("b")).unwrap());
+        assert!(queue.complete(&result("a")).unwrap());
+
+        let existing = queue.enqueue(&task).unwrap().expect("known id");
+        assert_eq!(existing.state, TaskState::Completed);
+        assert_eq!(queue.result("t1").unwrap().unwrap().summary, "from a");
+    }
+
+    fn tempdir() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("pagi_federation_queue_{}", uuid::Uuid::new_v4()))
+    }
+}
@@ -0,0 +1,777 @@
+//! Selective knowledge replication: The Creator copies chosen KB slots and key prefixes to
+//! satellites by role, so satellite skills can ground their work locally.
+//!
+//! The per-role policy is TOML, named by `PAGI_FEDERATION_REPLICATION_POLICY`:
+//!
+//! ```toml
+//! [[role]]
+//! role = "RedTeam"                  # "*" applies to every role
+//! scopes = [
+//!     { slot = 2, prefix = "docs/" },   # KB-02 (Oikos) technical docs
+//!     { slot = 5 },                     # all of KB-05 (Techne) protocols
+//! ]
+//! ```
+//!
+//! Slot 9 (Shadow) is never replicated: a policy naming it is rejected, the master never scans it
+//! and replicas refuse it.
+//!
+//! On the master, [`KnowledgeReplicator`] keeps a version log (sled) of every replicated entry:
+//! each change, including a removal, gets the next version number. A satellite asks for
+//! everything after the last version it applied (`SyncKnowledge`) and receives pages of changes
+//! in its role's scopes. When the role's scopes or the master's log change, the answer says
+//! `reset` and the satellite starts over from version 0.
+//!
+//! On the satellite, [`ReplicaStore`] holds the copy. It has no public write API; only the sync
+//! in `SatelliteClient` (heartbeat loop) updates it.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use pagi_core::{KbRecord, KbType, KnowledgeStore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ca::hex;
+use crate::phoenix_federation::{KnowledgeEntry, KnowledgeSyncResponse};
+
+/// Most entries in one `SyncKnowledge` page.
+pub const REPLICATION_PAGE_ENTRIES: usize = 500;
+/// Value bytes after which a page is closed (tonic's default message limit is 4 MiB).
+const REPLICATION_PAGE_BYTES: usize = 2 * 1024 * 1024;
+const VERSION_KEY: &str = "version";
+const LOG_ID_KEY: &str = "log_id";
+const DIGEST_KEY: &str = "scope_digest";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplicationError {
+    #[error("replication policy {path}: {reason}")]
+    Policy { path: String, reason: String },
+    #[error("Slot 9 (Shadow) is never replicated")]
+    ShadowSlot,
+    #[error("KB slot {0} does not exist")]
+    InvalidSlot(u32),
+    #[error("replication storage: {0}")]
+    Storage(#[from] sled::Error),
+    #[error("corrupt replication record {key}: {reason}")]
+    Corrupt { key: String, reason: String },
+}
+
+/// A KB slot, optionally narrowed to keys starting with `prefix`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ReplicationScope {
+    pub slot: u8,
+    #[serde(default)]
+    pub prefix: String,
+}
+
+impl ReplicationScope {
+    pub fn contains(&self, slot: u8, key: &str) -> bool {
+        self.slot == slot && key.starts_with(&self.prefix)
+    }
+}
+
+/// What one role receives.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoleReplication {
+    pub role: String,
+    #[serde(default)]
+    pub scopes: Vec<ReplicationScope>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReplicationPolicy {
+    #[serde(rename = "role", default)]
+    pub roles: Vec<RoleReplication>,
+}
+
+impl ReplicationPolicy {
+    pub fn load(path: &Path) -> Result<Self, ReplicationError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ReplicationError::Policy {
+            path: path.display().to_string(),
+            reason: e.to_string(),
+        })?;
+        Self::parse(&text).map_err(|e| match e {
+            ReplicationError::Policy { reason, .. } => ReplicationError::Policy {
+                path: path.display().to_string(),
+                reason,
+            },
+            other => other,
+        })
+    }
+
+    /// Parses and validates a policy (slots 1-8 only).
+    pub fn parse(text: &str) -> Result<Self, ReplicationError> {
+        let policy: Self = toml::from_str(text).map_err(|e| ReplicationError::Policy {
+            path: "<inline>".to_string(),
+            reason: e.to_string(),
+        })?;
+        for scope in policy.roles.iter().flat_map(|r| &r.scopes) {
+            check_slot(scope.slot as u32)?;
+        }
+        Ok(policy)
+    }
+
+    /// The policy named by `PAGI_FEDERATION_REPLICATION_POLICY`, if set.
+    pub fn from_env() -> Result<Option<Self>, ReplicationError> {
+        match std::env::var("PAGI_FEDERATION_REPLICATION_POLICY") {
+            Ok(path) if !path.trim().is_empty() => Self::load(Path::new(path.trim())).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Scopes for `role` (and `"*"`), sorted and without duplicates.
+    pub fn scopes_for(&self, role: &str) -> Vec<ReplicationScope> {
+        let mut scopes: Vec<ReplicationScope> = self
+            .roles
+            .iter()
+            .filter(|r| r.role == "*" || r.role.eq_ignore_ascii_case(role))
+            .flat_map(|r| r.scopes.iter().cloned())
+            .collect();
+        scopes.sort();
+        scopes.dedup();
+        scopes
+    }
+
+    fn all_scopes(&self) -> Vec<ReplicationScope> {
+        let mut scopes: Vec<ReplicationScope> = self
+            .roles
+            .iter()
+            .flat_map(|r| r.scopes.iter().cloned())
+            .collect();
+        scopes.sort();
+        scopes.dedup();
+        scopes
+    }
+}
+
+fn check_slot(slot: u32) -> Result<u8, ReplicationError> {
+    match u8::try_from(slot).ok().and_then(KbType::from_slot_id) {
+        Some(KbType::Shadow) => Err(ReplicationError::ShadowSlot),
+        Some(kb) => Ok(kb.slot_id()),
+        None => Err(ReplicationError::InvalidSlot(slot)),
+    }
+}
+
+/// Version log record of one replicated entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LogEntry {
+    version: u64,
+    hash: String,
+    deleted: bool,
+}
+
+/// Master side: versions every entry in the policy's scopes and answers `SyncKnowledge`.
+pub struct KnowledgeReplicator {
+    store: Arc<KnowledgeStore>,
+    policy: ReplicationPolicy,
+    /// "{slot}/{key}" -> LogEntry (JSON).
+    entries: sled::Tree,
+    /// Version (big-endian) -> "{slot}/{key}".
+    by_version: sled::Tree,
+    meta: sled::Tree,
+    log_id: String,
+    /// Serializes refreshes so versions are handed out in order.
+    refresh_lock: Mutex<()>,
+}
+
+impl KnowledgeReplicator {
+    /// Opens or creates the version log at `path` (e.g. `./data/pagi_federation_replication`).
+    pub fn open<P: AsRef<Path>>(
+        store: Arc<KnowledgeStore>,
+        policy: ReplicationPolicy,
+        path: P,
+    ) -> Result<Self, ReplicationError> {
+        Self::from_db(store, policy, sled::open(path)?)
+    }
+
+    /// A replicator whose log is deleted when dropped (tests, ephemeral masters).
+    pub fn temporary(
+        store: Arc<KnowledgeStore>,
+        policy: ReplicationPolicy,
+    ) -> Result<Self, ReplicationError> {
+        Self::from_db(store, policy, sled::Config::new().temporary(true).open()?)
+    }
+
+    /// [`ReplicationPolicy::from_env`] with the log at `PAGI_FEDERATION_REPLICATION_LOG`
+    /// (default `./data/pagi_federation_replication`); `None` without a policy.
+    pub fn from_env(store: Arc<KnowledgeStore>) -> Result<Option<Self>, ReplicationError> {
+        let Some(policy) = ReplicationPolicy::from_env()? else {
+            return Ok(None);
+        };
+        let path = std::env::var("PAGI_FEDERATION_REPLICATION_LOG")
+            .ok()
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| "./data/pagi_federation_replication".to_string());
+        Self::open(store, policy, path.trim()).map(Some)
+    }
+
+    fn from_db(
+        store: Arc<KnowledgeStore>,
+        policy: ReplicationPolicy,
+        db: sled::Db,
+    ) -> Result<Self, ReplicationError> {
+        let meta = db.open_tree("meta")?;
+        // Identifies this log; replicas built from another log are reset.
+        let log_id = match meta.get(LOG_ID_KEY)? {
+            Some(id) => String::from_utf8_lossy(&id).into_owned(),
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                meta.insert(LOG_ID_KEY, id.as_bytes())?;
+                id
+            }
+        };
+        Ok(Self {
+            store,
+            policy,
+            entries: db.open_tree("entries")?,
+            by_version: db.open_tree("by_version")?,
+            meta,
+            log_id,
+            refresh_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn policy(&self) -> &ReplicationPolicy {
+        &self.policy
+    }
+
+    /// Latest version handed out.
+    pub fn version(&self) -> Result<u64, ReplicationError> {
+        Ok(self
+            .meta
+            .get(VERSION_KEY)?
+            .and_then(|v| v.as_ref().try_into().ok())
+            .map(u64::from_be_bytes)
+            .unwrap_or(0))
+    }
+
+    /// Digest a replica of `scopes` built from this log carries.
+    pub fn scope_digest(&self, scopes: &[ReplicationScope]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.log_id.as_bytes());
+        for scope in scopes {
+            hasher.update([0, scope.slot]);
+            hasher.update(scope.prefix.as_bytes());
+        }
+        hex(&hasher.finalize())
+    }
+
+    /// Compares the KB with the log and records every change (new, updated, removed or out of
+    /// scope) under a new version. Returns how many changes were recorded.
+    pub fn refresh(&self) -> Result<usize, ReplicationError> {
+        let _guard = self.refresh_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let scopes = self.policy.all_scopes();
+        let mut current = std::collections::HashMap::new();
+        let mut slots: Vec<u8> = scopes.iter().map(|s| s.slot).collect();
+        slots.dedup();
+        for slot in slots {
+            if slot == KbType::Shadow.slot_id() {
+                continue;
+            }
+            for (key, value) in self.store.scan_kv(slot)? {
+                if scopes.iter().any(|s| s.contains(slot, &key)) {
+                    current.insert(log_key(slot, &key), hex(&Sha256::digest(&value)));
+                }
+            }
+        }
+
+        let mut version = self.version()?;
+        let mut changes = 0;
+        let mut record = |id: &str, hash: String, deleted: bool| -> Result<(), ReplicationError> {
+            if let Some(old) = self.entry(id)? {
+                self.by_version.remove(old.version.to_be_bytes())?;
+            }
+            version += 1;
+            let entry = LogEntry {
+                version,
+                hash,
+                deleted,
+            };
+            let bytes = serde_json::to_vec(&entry).map_err(|e| corrupt(id, e))?;
+            self.entries.insert(id.as_bytes(), bytes)?;
+            self.by_version
+                .insert(version.to_be_bytes(), id.as_bytes())?;
+            self.meta.insert(VERSION_KEY, &version.to_be_bytes())?;
+            changes += 1;
+            Ok(())
+        };
+
+        for (id, hash) in &current {
+            match self.entry(id)? {
+                Some(old) if !old.deleted && old.hash == *hash => {}
+                _ => record(id, hash.clone(), false)?,
+            }
+        }
+        let logged: Vec<(String, LogEntry)> = self
+            .entries
+            .iter()
+            .map(|item| {
+                let (k, v) = item?;
+                let id = String::from_utf8_lossy(&k).into_owned();
+                let entry = serde_json::from_slice(&v).map_err(|e| corrupt(&id, e))?;
+                Ok((id, entry))
+            })
+            .collect::<Result<_, ReplicationError>>()?;
+        for (id, entry) in logged {
+            if !entry.deleted && !current.contains_key(&id) {
+                record(&id, String::new(), true)?;
+            }
+        }
+        if changes > 0 {
+            self.entries.flush()?;
+        }
+        Ok(changes)
+    }
+
+    /// The page of changes in `role`'s scopes after `since`. A `digest` other than the role's
+    /// current one (or a `since` ahead of this log) answers from version 0 with `reset` set.
+    pub fn changes(
+        &self,
+        role: &str,
+        since: u64,
+        digest: &str,
+    ) -> Result<KnowledgeSyncResponse, ReplicationError> {
+        self.refresh()?;
+        let scopes = self.policy.scopes_for(role);
+        let scope_digest = self.scope_digest(&scopes);
+        let latest = self.version()?;
+        let reset = digest != scope_digest || since > latest;
+        let since = if reset { 0 } else { since };
+
+        let mut entries = Vec::new();
+        let mut bytes = 0;
+        let mut version = since;
+        let mut has_more = false;
+        for item in self.by_version.range((since + 1).to_be_bytes()..) {
+            let (v, id) = item?;
+            let v = v
+                .as_ref()
+                .try_into()
+                .map(u64::from_be_bytes)
+                .unwrap_or(version);
+            let id = String::from_utf8_lossy(&id).into_owned();
+            let Some((slot, key)) = split_log_key(&id) else {
+                version = v;
+                continue;
+            };
+            if !scopes.iter().any(|s| s.contains(slot, key)) {
+                version = v;
+                continue;
+            }
+            if entries.len() >= REPLICATION_PAGE_ENTRIES || bytes >= REPLICATION_PAGE_BYTES {
+                has_more = true;
+                break;
+            }
+            let deleted = self.entry(&id)?.is_none_or(|e| e.deleted);
+            let value = if deleted {
+                None
+            } else {
+                self.store.get(slot, key)?
+            };
+            // A fresh replica has nothing to remove.
+            if value.is_some() || !reset {
+                bytes += value.as_ref().map_or(0, Vec::len);
+                entries.push(KnowledgeEntry {
+                    slot: slot as u32,
+                    key: key.to_string(),
+                    deleted: value.is_none(),
+                    value: value.unwrap_or_default(),
+                    version: v,
+                });
+            }
+            version = v;
+        }
+        if !has_more {
+            version = version.max(latest);
+        }
+        Ok(KnowledgeSyncResponse {
+            entries,
+            version,
+            has_more,
+            reset,
+            scope_digest,
+        })
+    }
+
+    fn entry(&self, id: &str) -> Result<Option<LogEntry>, ReplicationError> {
+        self.entries
+            .get(id.as_bytes())?
+            .map(|v| serde_json::from_slice(&v).map_err(|e| corrupt(id, e)))
+            .transpose()
+    }
+}
+
+fn log_key(slot: u8, key: &str) -> String {
+    format!("{}/{}", slot, key)
+}
+
+fn split_log_key(id: &str) -> Option<(u8, &str)> {
+    let (slot, key) = id.split_once('/')?;
+    Some((slot.parse().ok()?, key))
+}
+
+fn corrupt(key: &str, e: impl std::fmt::Display) -> ReplicationError {
+    ReplicationError::Corrupt {
+        key: key.to_string(),
+        reason: e.to_string(),
+    }
+}
+
+/// Satellite side: a read-only copy of the KB entries its role may read.
+///
+/// Only the federation sync writes to it; code outside the crate can read but not apply pages:
+///
+/// ```compile_fail
+/// use pagi_federation::phoenix_federation::KnowledgeSyncResponse;
+/// let replica = pagi_federation::ReplicaStore::temporary().unwrap();
+/// replica.apply(&KnowledgeSyncResponse::default()).unwrap();
+/// ```
+pub struct ReplicaStore {
+    db: sled::Db,
+    meta: sled::Tree,
+}
+
+impl ReplicaStore {
+    /// Opens or creates the replica at `path` (e.g. `./data/pagi_federation_replica`).
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReplicationError> {
+        Self::from_db(sled::open(path)?)
+    }
+
+    /// A replica that is deleted when dropped.
+    pub fn temporary() -> Result<Self, ReplicationError> {
+        Self::from_db(sled::Config::new().temporary(true).open()?)
+    }
+
+    fn from_db(db: sled::Db) -> Result<Self, ReplicationError> {
+        Ok(Self {
+            meta: db.open_tree("meta")?,
+            db,
+        })
+    }
+
+    /// Highest master version applied.
+    pub fn version(&self) -> u64 {
+        self.meta
+            .get(VERSION_KEY)
+            .ok()
+            .flatten()
+            .and_then(|v| v.as_ref().try_into().ok())
+            .map(u64::from_be_bytes)
+            .unwrap_or(0)
+    }
+
+    pub fn scope_digest(&self) -> String {
+        self.meta
+            .get(DIGEST_KEY)
+            .ok()
+            .flatten()
+            .map(|d| String::from_utf8_lossy(&d).into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, slot: u8, key: &str) -> Result<Option<Vec<u8>>, ReplicationError> {
+        Ok(self.tree(slot)?.get(key.as_bytes())?.map(|v| v.to_vec()))
+    }
+
+    pub fn get_record(&self, slot: u8, key: &str) -> Result<Option<KbRecord>, ReplicationError> {
+        Ok(self.get(slot, key)?.and_then(|b| KbRecord::from_bytes(&b)))
+    }
+
+    /// Entries of `slot` whose key starts with `prefix`, in key order.
+    pub fn scan_prefix(
+        &self,
+        slot: u8,
+        prefix: &str,
+    ) -> Result<Vec<(String, Vec<u8>)>, ReplicationError> {
+        self.tree(slot)?
+            .scan_prefix(prefix.as_bytes())
+            .map(|item| {
+                let (k, v) = item?;
+                Ok((String::from_utf8_lossy(&k).into_owned(), v.to_vec()))
+            })
+            .collect()
+    }
+
+    pub fn count(&self, slot: u8) -> Result<usize, ReplicationError> {
+        Ok(self.tree(slot)?.len())
+    }
+
+    /// Applies one `SyncKnowledge` page. Refuses the whole page if it names Slot 9.
+    pub(crate) fn apply(&self, page: &KnowledgeSyncResponse) -> Result<usize, ReplicationError> {
+        for entry in &page.entries {
+            check_slot(entry.slot)?;
+        }
+        if page.reset {
+            for kb in KbType::all() {
+                self.tree(kb.slot_id())?.clear()?;
+            }
+        }
+        for entry in &page.entries {
+            let tree = self.tree(entry.slot as u8)?;
+            if entry.deleted {
+                tree.remove(entry.key.as_bytes())?;
+            } else {
+                tree.insert(entry.key.as_bytes(), entry.value.as_slice())?;
+            }
+        }
+        self.meta.insert(VERSION_KEY, &page.version.to_be_bytes())?;
+        self.meta.insert(DIGEST_KEY, page.scope_digest.as_bytes())?;
+        self.db.flush()?;
+        Ok(page.entries.len())
+    }
+
+    fn tree(&self, slot: u8) -> Result<sled::Tree, ReplicationError> {
+        let slot = check_slot(slot as u32)?;
+        Ok(self.db.open_tree(format!("kb{}", slot))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_receive_their_scopes_incrementally_and_never_slot_9() {
+        assert!(matches!(
+            ReplicationPolicy::parse("[[role]]\nrole = \"*\"\nscopes = [{ slot = 9 }]"),
+            Err(ReplicationError::ShadowSlot)
+        ));
+        let policy = ReplicationPolicy::parse(
+            r#"
+            [[role]]
+            role = "RedTeam"
+            scopes = [{ slot = 2, prefix = "docs/" }, { slot = 5 }]
+
+            [[role]]
+            role = "Finance"
+            scopes = [{ slot = 3 }]
+            "#,
+        )
+        .unwrap();
+
+        let dir =
+            std::env::temp_dir().join(format!("pagi_federation_repl_{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(KnowledgeStore::open_with_key(&dir, Some(&[7u8; 32])).unwrap());
+        store.insert(2, "docs/nmap", b"nmap -sV").unwrap();
+        store.insert(2, "notes/private", b"not replicated").unwrap();
+        store.insert(5, "protocols/recon", b"step 1").unwrap();
+        store.insert(9, "docs/anchor", b"never leaves").unwrap();
+        let master = KnowledgeReplicator::temporary(Arc::clone(&store), policy).unwrap();
+
+        // First sync: a full copy of the role's scopes.
+        let replica = ReplicaStore::temporary().unwrap();
+        let page = master
+            .changes("redteam", replica.version(), &replica.scope_digest())
+            .unwrap();
+        assert!(page.reset && !page.has_more);
+        assert_eq!(replica.apply(&page).unwrap(), 2);
+        assert_eq!(replica.get(2, "docs/nmap").unwrap().unwrap(), b"nmap -sV");
+        assert!(replica.get(2, "notes/private").unwrap().is_none());
+        assert!(matches!(
+            replica.get(9, "docs/anchor"),
+            Err(ReplicationError::ShadowSlot)
+        ));
+
+        // Later syncs carry only what changed, including removals.
+        store.insert(5, "protocols/recon", b"step 2").unwrap();
+        store.remove(2, "docs/nmap").unwrap();
+        let page = master
+            .changes("RedTeam", replica.version(), &replica.scope_digest())
+            .unwrap();
+        assert!(!page.reset);
+        assert_eq!(page.entries.len(), 2);
+        replica.apply(&page).unwrap();
+        assert_eq!(
+            replica.get(5, "protocols/recon").unwrap().unwrap(),
+            b"step 2"
+        );
+        assert!(replica.get(2, "docs/nmap").unwrap().is_none());
+        let page = master
+            .changes("RedTeam", replica.version(), &replica.scope_digest())
+            .unwrap();
+        assert!(page.entries.is_empty());
+
+        // Another role sees nothing from these scopes; a replica of it is reset.
+        let page = master
+            .changes("Finance", replica.version(), &replica.scope_digest())
+            .unwrap();
+        assert!(page.reset && page.entries.is_empty());
+        replica.apply(&page).unwrap();
+        assert_eq!(replica.count(5).unwrap(), 0);
+
+        let shadow = KnowledgeSyncResponse {
+            entries: vec![KnowledgeEntry {
+                slot: 9,
+                key: "docs/anchor".into(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            replica.apply(&shadow),
+            Err(ReplicationError::ShadowSlot)
+        ));
+        drop(store);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn open_store(name: &str) -> (std::path::PathBuf, Arc<KnowledgeStore>) {
+        let dir =
+            std::env::temp_dir().join(format!("pagi_federation_{name}_{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(KnowledgeStore::open_with_key(&dir, Some(&[7u8; 32])).unwrap());
+        (dir, store)
+    }
+
+    fn keys(page: &KnowledgeSyncResponse) -> Vec<(u32, &str)> {
+        let mut keys: Vec<(u32, &str)> = page
+            .entries
+            .iter()
+            .map(|e| (e.slot, e.key.as_str()))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn sync_resumes_after_the_replica_version_and_across_pages() {
+        let (dir, store) = open_store("repl_resume");
+        let policy =
+            ReplicationPolicy::parse("[[role]]\nrole = \"RedTeam\"\nscopes = [{ slot = 5 }]")
+                .unwrap();
+        store.insert(5, "a", b"1").unwrap();
+        store.insert(5, "b", b"1").unwrap();
+        let master = KnowledgeReplicator::temporary(Arc::clone(&store), policy).unwrap();
+        let replica = ReplicaStore::temporary().unwrap();
+        let page = master
+            .changes("RedTeam", replica.version(), &replica.scope_digest())
+            .unwrap();
+        replica.apply(&page).unwrap();
+        let applied = replica.version();
+        assert_eq!(applied, master.version().unwrap());
+
+        // Only entries versioned after the replica's are sent.
+        store.insert(5, "c", b"1").unwrap();
+        let page = master
+            .changes("RedTeam", applied, &replica.scope_digest())
+            .unwrap();
+        assert!(!page.reset);
+        assert_eq!(keys(&page), vec![(5, "c")]);
+        assert!(page.entries.iter().all(|e| e.version > applied));
+        replica.apply(&page).unwrap();
+
+        // A backlog larger than one page is resumed from the last page's version.
+        let total = REPLICATION_PAGE_ENTRIES + 5;
+        for i in 0..total {
+            store.insert(5, &format!("bulk/{i:04}"), b"x").unwrap();
+        }
+        let first = master
+            .changes("RedTeam", replica.version(), &replica.scope_digest())
+            .unwrap();
+        assert!(first.has_more);
+        assert_eq!(first.entries.len(), REPLICATION_PAGE_ENTRIES);
+        replica.apply(&first).unwrap();
+        let second = master
+            .changes("RedTeam", replica.version(), &replica.scope_digest())
+            .unwrap();
+        assert!(!second.has_more && !second.reset);
+        assert_eq!(second.entries.len(), 5);
+        assert!(second.entries.iter().all(|e| e.version > first.version));
+        replica.apply(&second).unwrap();
+        assert_eq!(replica.count(5).unwrap(), total + 3);
+        assert_eq!(replica.version(), master.version().unwrap());
+
+        // A replica claiming a version this log never handed out starts over.
+        let ahead = master
+            .changes("RedTeam", replica.version() + 10, &replica.scope_digest())
+            .unwrap();
+        assert!(ahead.reset);
+        assert_eq!(ahead.entries.len(), REPLICATION_PAGE_ENTRIES);
+        drop(store);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn each_role_receives_only_its_scopes() {
+        let (dir, store) = open_store("repl_roles");
+        let policy = ReplicationPolicy::parse(
+            r#"
+            [[role]]
+            role = "*"
+            scopes = [{ slot = 2, prefix = "shared/" }]
+
+            [[role]]
+            role = "RedTeam"
+            scopes = [{ slot = 5 }]
+
+            [[role]]
+            role = "BlueTeam"
+            scopes = [{ slot = 5, prefix = "blue/" }]
+            "#,
+        )
+        .unwrap();
+        store.insert(2, "shared/runbook", b"1").unwrap();
+        store.insert(2, "private/notes", b"1").unwrap();
+        store.insert(3, "ledger", b"1").unwrap();
+        store.insert(5, "red/exploit", b"1").unwrap();
+        store.insert(5, "blue/detection", b"1").unwrap();
+        let master = KnowledgeReplicator::temporary(Arc::clone(&store), policy).unwrap();
+
+        let sync = |role: &str| master.changes(role, 0, "").unwrap();
+        assert_eq!(
+            keys(&sync("redteam")),
+            vec![
+                (2, "shared/runbook"),
+                (5, "blue/detection"),
+                (5, "red/exploit")
+            ]
+        );
+        assert_eq!(
+            keys(&sync("BlueTeam")),
+            vec![(2, "shared/runbook"), (5, "blue/detection")]
+        );
+        assert_eq!(keys(&sync("Finance")), vec![(2, "shared/runbook")]);
+        drop(store);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pages_naming_slot_9_are_refused_without_touching_the_replica() {
+        let replica = ReplicaStore::temporary().unwrap();
+        let entry = |slot: u32, key: &str| KnowledgeEntry {
+            slot,
+            key: key.into(),
+            value: b"v".to_vec(),
+            ..Default::default()
+        };
+        replica
+            .apply(&KnowledgeSyncResponse {
+                entries: vec![entry(5, "kept")],
+                version: 3,
+                scope_digest: "d".into(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        for slot in [9, 42] {
+            let page = KnowledgeSyncResponse {
+                entries: vec![entry(5, "new"), entry(slot, "x")],
+                version: 10,
+                reset: true,
+                scope_digest: "other".into(),
+                ..Default::default()
+            };
+            assert!(replica.apply(&page).is_err());
+        }
+        assert!(matches!(
+            replica.apply(&KnowledgeSyncResponse {
+                entries: vec![entry(9, "x")],
+                ..Default::default()
+            }),
+            Err(ReplicationError::ShadowSlot)
+        ));
+        assert_eq!(replica.get(5, "kept").unwrap().unwrap(), b"v");
+        assert!(replica.get(5, "new").unwrap().is_none());
+        assert_eq!(replica.version(), 3);
+        assert_eq!(replica.scope_digest(), "d");
+    }
+}
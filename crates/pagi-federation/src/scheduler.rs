@@ -0,0 +1,238 @@
+//! Satellite scheduling: which capable node gets a task.
+//!
+//! `FederationHandle::submit_task` collects every connected satellite that advertises the goal
+//! and asks a [`Scheduler`] to pick one. Candidates arrive sorted by `node_id` with their last
+//! heartbeat, registered hardware and in-flight task count.
+//!
+//! | Scheduler | Picks |
+//! |-----------|-------|
+//! | [`LeastLoaded`] | Fewest in-flight tasks, then lowest reported CPU. |
+//! | [`RoundRobin`] | The next candidate in turn. |
+//! | [`Weighted`] | Lowest load relative to capacity (cores and RAM from registration). |
+//! | [`RoleAffinity`] | Least-loaded node of the role configured for the goal, else the fallback. |
+//!
+//! [`scheduler_from_env`] selects one via `PAGI_FEDERATION_SCHEDULER`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::federation::SatelliteInfo;
+
+/// Picks the satellite that runs a task for `goal`. `candidates` is never empty.
+pub trait Scheduler: Send + Sync {
+    fn name(&self) -> &str;
+    /// Returns the chosen `node_id`, or `None` to refuse every candidate.
+    fn pick(&self, goal: &str, candidates: &[SatelliteInfo]) -> Option<String>;
+}
+
+/// Load estimate used by [`LeastLoaded`] and [`Weighted`]: queued tasks plus reported CPU.
+fn load(info: &SatelliteInfo) -> f64 {
+    let cpu = info
+        .last_heartbeat
+        .as_ref()
+        .map(|h| h.cpu_percent.clamp(0.0, 100.0) / 100.0)
+        .unwrap_or(0.0);
+    info.in_flight as f64 + cpu
+}
+
+fn min_by(candidates: &[SatelliteInfo], score: impl Fn(&SatelliteInfo) -> f64) -> Option<String> {
+    candidates
+        .iter()
+        .min_by(|a, b| score(a).total_cmp(&score(b)))
+        .map(|s| s.node_id.clone())
+}
+
+/// Fewest in-flight tasks; ties go to the node reporting less CPU, then to the lower `node_id`.
+#[derive(Debug, Default)]
+pub struct LeastLoaded;
+
+impl Scheduler for LeastLoaded {
+    fn name(&self) -> &str {
+        "least_loaded"
+    }
+
+    fn pick(&self, _goal: &str, candidates: &[SatelliteInfo]) -> Option<String> {
+        min_by(candidates, load)
+    }
+}
+
+/// Rotates through the candidates regardless of load.
+#[derive(Debug, Default)]
+pub struct RoundRobin {
+    next: AtomicUsize,
+}
+
+impl Scheduler for RoundRobin {
+    fn name(&self) -> &str {
+        "round_robin"
+    }
+
+    fn pick(&self, _goal: &str, candidates: &[SatelliteInfo]) -> Option<String> {
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % candidates.len();
+        Some(candidates[i].node_id.clone())
+    }
+}
+
+/// Load divided by capacity, so a 32-core node takes proportionally more work than a 4-core
+/// one. Capacity is `cpu_cores` (1 if unknown) scaled by RAM relative to 8 GiB, floored at 0.25.
+#[derive(Debug, Default)]
+pub struct Weighted;
+
+impl Weighted {
+    fn capacity(info: &SatelliteInfo) -> f64 {
+        let (cores, ram_mb) = info
+            .hardware
+            .as_ref()
+            .map(|h| (h.cpu_cores, h.ram_mb))
+            .unwrap_or((0, 0));
+        let cores = f64::from(cores.max(1));
+        let ram = if ram_mb == 0 {
+            1.0
+        } else {
+            (ram_mb as f64 / 8192.0).max(0.25)
+        };
+        cores * ram
+    }
+}
+
+impl Scheduler for Weighted {
+    fn name(&self) -> &str {
+        "weighted"
+    }
+
+    fn pick(&self, _goal: &str, candidates: &[SatelliteInfo]) -> Option<String> {
+        min_by(candidates, |s| (load(s) + 1.0) / Self::capacity(s))
+    }
+}
+
+/// Prefers nodes of the role configured for a goal (e.g. `red_team_scan` → `RedTeam`), picking
+/// the least loaded of them; goals without a preference, or with no node of that role, go to
+/// `fallback`.
+pub struct RoleAffinity {
+    roles: HashMap<String, String>,
+    fallback: Arc<dyn Scheduler>,
+}
+
+impl RoleAffinity {
+    pub fn new(roles: HashMap<String, String>, fallback: Arc<dyn Scheduler>) -> Self {
+        Self { roles, fallback }
+    }
+
+    /// Parses `goal=Role,goal=Role` (as in `PAGI_FEDERATION_ROLE_AFFINITY`).
+    pub fn parse_roles(spec: &str) -> HashMap<String, String> {
+        spec.split(',')
+            .filter_map(|pair| {
+                let (goal, role) = pair.split_once('=')?;
+                let (goal, role) = (goal.trim(), role.trim());
+                (!goal.is_empty() && !role.is_empty()).then(|| (goal.to_string(), role.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl Scheduler for RoleAffinity {
+    fn name(&self) -> &str {
+        "role_affinity"
+    }
+
+    fn pick(&self, goal: &str, candidates: &[SatelliteInfo]) -> Option<String> {
+        if let Some(role) = self.roles.get(goal) {
+            let preferred: Vec<SatelliteInfo> = candidates
+                .iter()
+                .filter(|s| s.role.eq_ignore_ascii_case(role))
+                .cloned()
+                .collect();
+            if !preferred.is_empty() {
+                return LeastLoaded.pick(goal, &preferred);
+            }
+        }
+        self.fallback.pick(goal, candidates)
+    }
+}
+
+/// Scheduler named by `PAGI_FEDERATION_SCHEDULER` (`least_loaded` (default), `round_robin`,
+/// `weighted`, `role_affinity`). Role affinity reads `PAGI_FEDERATION_ROLE_AFFINITY`
+/// (`goal=Role,...`) and falls back to least-loaded.
+pub fn scheduler_from_env() -> Arc<dyn Scheduler> {
+    let kind = std::env::var("PAGI_FEDERATION_SCHEDULER").unwrap_or_default();
+    match kind.trim().to_ascii_lowercase().as_str() {
+        "round_robin" => Arc::new(RoundRobin::default()),
+        "weighted" => Arc::new(Weighted),
+        "role_affinity" => {
+            let spec = std::env::var("PAGI_FEDERATION_ROLE_AFFINITY").unwrap_or_default();
+            Arc::new(RoleAffinity::new(RoleAffinity::parse_roles(&spec), Arc::new(LeastLoaded)))
+        }
+        _ => Arc::new(LeastLoaded),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::HeartbeatSample;
+    use crate::phoenix_federation::HardwareSpecs;
+
+    fn node(id: &str, role: &str, in_flight: u32, cpu: f64, cores: u32) -> SatelliteInfo {
+        let mut node = SatelliteInfo::for_test(id, role, &["scan"], 0);
+        node.in_flight = in_flight;
+        node.hardware = Some(HardwareSpecs {
+            cpu_cores: cores,
+            ram_mb: 8192,
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+        });
+        node.last_heartbeat = Some(HeartbeatSample {
+            bandwidth_mbps: 100.0,
+            cpu_percent: cpu,
+            ram_used_mb: 1024,
+            at_ms: 0,
+        });
+        node
+    }
+
+    #[test]
+    fn least_loaded_picks_the_node_with_the_fewest_tasks() {
+        let nodes = vec![
+            node("a", "Research", 2, 10.0, 4),
+            node("b", "RedTeam", 1, 90.0, 4),
+            node("c", "Research", 1, 20.0, 32),
+        ];
+        assert_eq!(LeastLoaded.pick("scan", &nodes).as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn round_robin_takes_turns() {
+        let nodes = vec![
+            node("a", "Research", 2, 10.0, 4),
+            node("b", "RedTeam", 1, 90.0, 4),
+            node("c", "Research", 1, 20.0, 32),
+        ];
+        let rr = RoundRobin::default();
+        let picks: Vec<String> = (0..4).filter_map(|_| rr.pick("scan", &nodes)).collect();
+        assert_eq!(picks, vec!["a", "b", "c", "a"]);
+    }
+
+    #[test]
+    fn weighted_prefers_capacity_over_idleness() {
+        // Node a is idle but small; c has 8x the cores with one task queued.
+        let nodes = vec![
+            node("a", "Research", 0, 0.0, 2),
+            node("c", "Research", 1, 0.0, 32),
+        ];
+        assert_eq!(Weighted.pick("scan", &nodes).as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn role_affinity_prefers_the_goal_role_and_falls_back() {
+        let roles = RoleAffinity::parse_roles("scan=redteam, other=Finance,bad");
+        assert_eq!(roles.len(), 2);
+        let affinity = RoleAffinity::new(roles, Arc::new(LeastLoaded));
+        let nodes = vec![
+            node("a", "Research", 0, 0.0, 4),
+            node("b", "RedTeam", 3, 50.0, 4),
+        ];
+        assert_eq!(affinity.pick("scan", &nodes).as_deref(), Some("b"));
+        assert_eq!(affinity.pick("other", &nodes).as_deref(), Some("a"));
+    }
+}
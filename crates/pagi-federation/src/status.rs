@@ -0,0 +1,260 @@
+//! Operator view of the master: snapshots of registered satellites and tasks, and a stream of
+//! satellite state changes. The gateway serves these as JSON at `/api/v1/federation/nodes`,
+//! `/api/v1/federation/tasks` and `/api/v1/federation/events`.
+//!
+//! Task history comes from the durable queue (`MasterState::with_queue`); without one only the
+//! tasks currently dispatched are listed.
+
+use std::cmp::Reverse;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::federation::{FederationHandle, HeartbeatSample};
+use crate::liveness::NodeHealth;
+use crate::phoenix_federation::HardwareSpecs;
+use crate::queue::{QueueError, TaskState};
+
+/// Node events buffered per subscriber before it lags.
+pub const NODE_EVENT_CAPACITY: usize = 256;
+
+/// A registered satellite as seen by the master.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeStatus {
+    pub node_id: String,
+    pub role: String,
+    pub host: String,
+    pub port: u32,
+    pub capabilities: Vec<String>,
+    pub hardware: Option<NodeHardware>,
+    pub last_heartbeat: Option<HeartbeatSample>,
+    pub registered_at_ms: i64,
+    pub last_seen_ms: i64,
+    /// Tasks dispatched to the node and not finished.
+    pub in_flight: u32,
+    pub health: NodeHealth,
+    /// The node has an open task stream.
+    pub connected: bool,
+}
+
+/// Hardware announced at registration.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeHardware {
+    pub cpu_cores: u32,
+    pub ram_mb: u64,
+    pub os: String,
+    pub arch: String,
+}
+
+impl From<&HardwareSpecs> for NodeHardware {
+    fn from(specs: &HardwareSpecs) -> Self {
+        Self {
+            cpu_cores: specs.cpu_cores,
+            ram_mb: specs.ram_mb,
+            os: specs.os.clone(),
+            arch: specs.arch.clone(),
+        }
+    }
+}
+
+/// A queued, running or finished task.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub task_id: String,
+    pub goal: String,
+    pub tenant_id: String,
+    pub state: TaskState,
+    /// Node of the latest dispatch.
+    pub node_id: Option<String>,
+    pub attempts: u32,
+    pub created_at_ms: i64,
+    pub updated_at_ms: i64,
+    /// Submission to final result or failure; `None` while unfinished.
+    pub duration_ms: Option<i64>,
+    /// Outcome reported by the satellite (completed tasks).
+    pub success: Option<bool>,
+    pub summary: Option<String>,
+    /// Why the master gave up (failed tasks).
+    pub error: Option<String>,
+}
+
+/// A satellite state change; `from` is `None` for a first registration.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeEvent {
+    pub node_id: String,
+    pub from: Option<NodeHealth>,
+    pub to: NodeHealth,
+    pub reason: String,
+    pub at_ms: i64,
+}
+
+impl FederationHandle {
+    /// Every registered satellite, ordered by node id.
+    pub fn nodes(&self) -> Vec<NodeStatus> {
+        let state = self.state();
+        let mut nodes: Vec<NodeStatus> = state
+            .satellites
+            .iter()
+            .map(|s| NodeStatus {
+                node_id: s.node_id.clone(),
+                role: s.role.clone(),
+                host: s.host.clone(),
+                port: s.port,
+                capabilities: s.capabilities.clone(),
+                hardware: s.hardware.as_ref().map(NodeHardware::from),
+                last_heartbeat: s.last_heartbeat.clone(),
+                registered_at_ms: s.registered_at_ms,
+                last_seen_ms: s.last_seen_ms,
+                in_flight: s.in_flight,
+                health: s.health,
+                connected: self.is_connected(&s.node_id),
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        nodes
+    }
+
+    /// Up to `limit` tasks: unfinished ones first, then the most recently submitted.
+    pub fn tasks(&self, limit: usize) -> Result<Vec<TaskStatus>, QueueError> {
+        let state = self.state();
+        let mut tasks = Vec::new();
+        if let Some(ref queue) = state.queue {
+            for record in queue.list()? {
+                let result = match record.state {
+                    TaskState::Completed => queue.result(&record.task_id)?,
+                    _ => None,
+                };
+                tasks.push(TaskStatus {
+                    duration_ms: record
+                        .state
+                        .is_finished()
+                        .then(|| record.updated_at_ms - record.created_at_ms),
+                    success: result.as_ref().map(|r| r.success),
+                    summary: result.map(|r| r.summary),
+                    task_id: record.task_id,
+                    goal: record.goal,
+                    tenant_id: record.tenant_id,
+                    state: record.state,
+                    node_id: record.node_id,
+                    attempts: record.attempts,
+                    created_at_ms: record.created_at_ms,
+                    updated_at_ms: record.updated_at_ms,
+                    error: record.error,
+                });
+            }
+        } else {
+            for pending in state.pending.iter() {
+                tasks.push(TaskStatus {
+                    task_id: pending.key().clone(),
+                    goal: pending.goal.clone(),
+                    tenant_id: String::new(),
+                    state: if pending.acked {
+                        TaskState::Acked
+                    } else {
+                        TaskState::Dispatched
+                    },
+                    node_id: Some(pending.node_id().to_string()),
+                    attempts: pending.attempt,
+                    created_at_ms: pending.created_at_ms,
+                    updated_at_ms: pending.created_at_ms,
+                    duration_ms: None,
+                    success: None,
+                    summary: None,
+                    error: None,
+                });
+            }
+        }
+        tasks.sort_by_key(|t| (t.state.is_finished(), Reverse(t.created_at_ms)));
+        tasks.truncate(limit);
+        Ok(tasks)
+    }
+
+    /// Satellite state changes from now on: registrations, suspect, dead and recovered nodes.
+    pub fn subscribe_node_events(&self) -> broadcast::Receiver<NodeEvent> {
+        self.state().node_events.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::federation::{MasterServer, MasterState, TaskOptions};
+    use crate::queue::TaskQueue;
+    use crate::scheduler::LeastLoaded;
+    use crate::testing::{FederationHarness, SatelliteBehaviour, SCRIPTED_FAILURE};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn nodes_tasks_and_events_reflect_the_federation() {
+        let state = MasterState::new()
+            .with_queue(Arc::new(TaskQueue::temporary().unwrap()))
+            .with_clock(FederationHarness::clock());
+        let handle = FederationHandle::with_scheduler(Arc::new(state), Arc::new(LeastLoaded))
+            .with_liveness(FederationHarness::liveness());
+        let harness = FederationHarness::start(MasterServer::from_handle(handle));
+        let mut events = harness.handle().subscribe_node_events();
+
+        let a = harness.satellite(
+            "a",
+            "RedTeam",
+            &["scan"],
+            SatelliteBehaviour::default().failing_first(1),
+        );
+        assert!(harness.wait_connected(&["a"]).await);
+        let registered = events.recv().await.unwrap();
+        assert_eq!((registered.node_id.as_str(), registered.from), ("a", None));
+        assert_eq!(registered.to, NodeHealth::Healthy);
+
+        let failed = harness
+            .submit("scan", TaskOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+        let done = harness
+            .submit("scan", TaskOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(failed.summary, SCRIPTED_FAILURE);
+
+        let nodes = harness.handle().nodes();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(
+            (nodes[0].role.as_str(), nodes[0].health),
+            ("RedTeam", NodeHealth::Healthy)
+        );
+        assert!(nodes[0].connected && nodes[0].hardware.is_some());
+        assert!(
+            harness
+                .wait_until(Duration::from_secs(2), |h| h.nodes()[0]
+                    .last_heartbeat
+                    .is_some())
+                .await
+        );
+
+        let tasks = harness.handle().tasks(10).unwrap();
+        assert_eq!(tasks.len(), 2);
+        // Newest first, with the satellite's outcome and a duration.
+        assert_eq!(tasks[0].task_id, done.task_id);
+        assert_eq!(tasks[0].state, TaskState::Completed);
+        assert_eq!(tasks[0].success, Some(true));
+        assert_eq!(tasks[1].success, Some(false));
+        assert!(tasks.iter().all(|t| t.duration_ms.is_some_and(|d| d >= 0)));
+        assert_eq!(harness.handle().tasks(1).unwrap().len(), 1);
+
+        // The node goes silent: suspect, then dead.
+        a.disconnect();
+        let mut seen = Vec::new();
+        while !seen.contains(&NodeHealth::Dead) {
+            let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+                .await
+                .expect("node was never evicted")
+                .unwrap();
+            seen.push(event.to);
+        }
+        let json = serde_json::to_value(&harness.handle().nodes()[0]).unwrap();
+        assert_eq!(json["health"], "dead");
+        assert_eq!(json["connected"], false);
+    }
+}
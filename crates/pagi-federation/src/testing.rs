@@ -0,0 +1,578 @@
+//! In-process federation harness: a [`MasterServer`] and simulated satellites talking gRPC over
+//! in-memory duplex streams, so scheduling, retries, cancellation and liveness can be covered by
+//! plain `cargo test` runs without sockets.
+//!
+//! Each [`SimulatedSatellite`] is a real [`SatelliteClient`] whose executor follows a
+//! [`SatelliteBehaviour`] script: latency, scripted failures, tasks that never finish, dropping
+//! the connection on the n-th task, slow heartbeats, and artifact uploads. The harness uses millisecond liveness
+//! deadlines ([`FederationHarness::liveness`]), so evictions happen within a test, and measures
+//! silence with tokio's clock ([`FederationHarness::clock`]); under
+//! `#[tokio::test(start_paused = true)]` the sweeps, heartbeats and timeouts run in virtual time.
+//!
+//! Available to this crate's tests and, for other crates, with the `test-support` feature.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hyper_util::rt::TokioIo;
+use pagi_core::TenantContext;
+use tokio::io::DuplexStream;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+use tonic::transport::{Channel, Endpoint, Uri};
+
+use crate::federation::{
+    FederationError, FederationHandle, MasterServer, MasterState, SatelliteClient, TaskOptions,
+    TaskReporter,
+};
+use crate::liveness::{Clock, LivenessConfig, NodeHealth};
+use crate::phoenix_federation::phoenix_service_client::PhoenixServiceClient;
+use crate::phoenix_federation::phoenix_service_server::PhoenixServiceServer;
+use crate::phoenix_federation::{Task, TaskResult};
+use crate::scheduler::Scheduler;
+
+/// Address simulated satellites dial; never resolved.
+const HARNESS_ADDR: &str = "http://federation.harness";
+const DUPLEX_BUFFER: usize = 64 * 1024;
+/// Heartbeat period of a satellite with the default behaviour.
+pub const FAST_HEARTBEAT: Duration = Duration::from_millis(50);
+/// Summary of a result failed by [`SatelliteBehaviour::failing_first`].
+pub const SCRIPTED_FAILURE: &str = "scripted failure";
+
+/// Connections from one simulated satellite to the harness master.
+#[derive(Clone)]
+pub(crate) struct DuplexTransport {
+    incoming: mpsc::UnboundedSender<DuplexStream>,
+    /// False while the satellite is disconnected: open connections are cut, new ones refused.
+    online: watch::Receiver<bool>,
+}
+
+impl DuplexTransport {
+    pub(crate) async fn connect(
+        &self,
+        endpoint: Endpoint,
+    ) -> Result<Channel, tonic::transport::Error> {
+        let transport = self.clone();
+        endpoint
+            .connect_with_connector(tower::service_fn(move |_: Uri| {
+                let transport = transport.clone();
+                async move { transport.open().map(TokioIo::new) }
+            }))
+            .await
+    }
+
+    /// A client stream relayed to a server stream handed to the master; the relay stops (and
+    /// both ends see EOF) when the satellite goes offline.
+    fn open(&self) -> std::io::Result<DuplexStream> {
+        let refused = |why: &str| std::io::Error::new(std::io::ErrorKind::ConnectionRefused, why);
+        if !*self.online.borrow() {
+            return Err(refused("satellite is disconnected"));
+        }
+        let (client, mut relay_client) = tokio::io::duplex(DUPLEX_BUFFER);
+        let (mut relay_server, server) = tokio::io::duplex(DUPLEX_BUFFER);
+        self.incoming
+            .send(server)
+            .map_err(|_| refused("harness master stopped"))?;
+        let mut online = self.online.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::io::copy_bidirectional(&mut relay_client, &mut relay_server) => {}
+                _ = online.wait_for(|online| !*online) => {}
+            }
+        });
+        Ok(client)
+    }
+}
+
+/// Script of a simulated satellite. The default answers every task at once and heartbeats every
+/// [`FAST_HEARTBEAT`].
+#[derive(Debug, Clone)]
+pub struct SatelliteBehaviour {
+    /// Delay before each result.
+    pub latency: Duration,
+    /// The first `n` tasks received answer with `success: false`.
+    pub fail_first: usize,
+    /// Tasks never finish (until cancelled).
+    pub hang: bool,
+    /// Drop every connection on receiving the n-th task (1-based), without answering it.
+    pub disconnect_after: Option<usize>,
+    pub heartbeat_interval: Duration,
+    /// Uploaded through the task's reporter and referenced from each successful result.
+    pub attachment: Option<(String, Arc<Vec<u8>>)>,
+}
+
+impl Default for SatelliteBehaviour {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            fail_first: 0,
+            hang: false,
+            disconnect_after: None,
+            heartbeat_interval: FAST_HEARTBEAT,
+            attachment: None,
+        }
+    }
+}
+
+impl SatelliteBehaviour {
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    pub fn failing_first(mut self, n: usize) -> Self {
+        self.fail_first = n;
+        self
+    }
+
+    pub fn hanging(mut self) -> Self {
+        self.hang = true;
+        self
+    }
+
+    pub fn disconnecting_after(mut self, n: usize) -> Self {
+        self.disconnect_after = Some(n);
+        self
+    }
+
+    /// Heartbeat period; longer than the liveness deadlines makes the node go suspect, then dead,
+    /// while its task stream stays open.
+    pub fn heartbeat_every(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    pub fn attaching(mut self, name: &str, data: Vec<u8>) -> Self {
+        self.attachment = Some((name.to_string(), Arc::new(data)));
+        self
+    }
+}
+
+/// A master served over in-memory transport, with its liveness monitor.
+pub struct FederationHarness {
+    server: Arc<MasterServer>,
+    incoming: mpsc::UnboundedSender<DuplexStream>,
+    /// Never goes offline; connections of [`Self::client`].
+    online: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl FederationHarness {
+    /// Millisecond deadlines: suspect after 250ms and dead after 500ms of silence, swept every
+    /// 20ms; three dispatches per task within 10s, re-sent if not acked within 1s.
+    pub fn liveness() -> LivenessConfig {
+        LivenessConfig {
+            suspect_after: Duration::from_millis(250),
+            dead_after: Duration::from_millis(500),
+            sweep_interval: Duration::from_millis(20),
+            max_attempts: 3,
+            task_timeout: Duration::from_secs(10),
+            ack_timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Wall-clock time at creation advanced by tokio's clock, so liveness follows a paused
+    /// runtime. Install it with `MasterState::with_clock` on states passed to [`Self::start`].
+    pub fn clock() -> Clock {
+        let base = chrono::Utc::now().timestamp_millis();
+        let start = tokio::time::Instant::now();
+        Arc::new(move || base + start.elapsed().as_millis() as i64)
+    }
+
+    /// A fresh master with `scheduler`, [`Self::liveness`] and [`Self::clock`].
+    pub fn with_scheduler(scheduler: Arc<dyn Scheduler>) -> Self {
+        let state = MasterState::new().with_clock(Self::clock());
+        let handle = FederationHandle::with_scheduler(Arc::new(state), scheduler)
+            .with_liveness(Self::liveness());
+        Self::start(MasterServer::from_handle(handle))
+    }
+
+    /// Serves `server` (plaintext; TLS settings are not used) and runs its liveness monitor.
+    /// Must be called inside a tokio runtime.
+    pub fn start(server: MasterServer) -> Self {
+        let server = Arc::new(server);
+        let (incoming, rx) = mpsc::unbounded_channel::<DuplexStream>();
+        let connections =
+            tokio_stream::wrappers::UnboundedReceiverStream::new(rx).map(Ok::<_, std::io::Error>);
+        let router = tonic::transport::Server::builder()
+            .add_service(PhoenixServiceServer::new(Arc::clone(&server)));
+        let served = tokio::spawn(async move {
+            let _ = router.serve_with_incoming(connections).await;
+        });
+        let monitor = server.handle().spawn_liveness_monitor();
+        Self {
+            server,
+            incoming,
+            online: watch::Sender::new(true),
+            tasks: vec![served, monitor],
+        }
+    }
+
+    pub fn handle(&self) -> Arc<FederationHandle> {
+        self.server.handle()
+    }
+
+    pub fn state(&self) -> Arc<MasterState> {
+        self.server.handle().state()
+    }
+
+    /// A gRPC client connected to the master, as an operator tool or satellite would be.
+    pub async fn client(&self) -> Result<PhoenixServiceClient<Channel>, FederationError> {
+        let transport = DuplexTransport {
+            incoming: self.incoming.clone(),
+            online: self.online.subscribe(),
+        };
+        let endpoint = Endpoint::from_static(HARNESS_ADDR);
+        let channel = transport
+            .connect(endpoint)
+            .await
+            .map_err(|e| FederationError::Connect(e.to_string()))?;
+        Ok(PhoenixServiceClient::new(channel))
+    }
+
+    pub fn health(&self, node_id: &str) -> Option<NodeHealth> {
+        self.state().satellites.get(node_id).map(|s| s.health)
+    }
+
+    /// Starts a satellite that registers and opens its task stream right away.
+    pub fn satellite(
+        &self,
+        node_id: &str,
+        role: &str,
+        capabilities: &[&str],
+        behaviour: SatelliteBehaviour,
+    ) -> SimulatedSatellite {
+        let (online, online_rx) = watch::channel(true);
+        let mut client = SatelliteClient::new(
+            node_id.to_string(),
+            role.to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            capabilities.iter().map(|c| c.to_string()).collect(),
+        )
+        .with_heartbeat_interval(behaviour.heartbeat_interval);
+        client.transport = Some(DuplexTransport {
+            incoming: self.incoming.clone(),
+            online: online_rx,
+        });
+        let satellite = SimulatedSatellite {
+            node_id: node_id.to_string(),
+            client,
+            behaviour: Arc::new(Mutex::new(behaviour)),
+            received: Arc::new(Mutex::new(Vec::new())),
+            online: Arc::new(online),
+            running: Mutex::new(None),
+        };
+        satellite.spawn();
+        satellite
+    }
+
+    /// Polls `condition` every 5ms until it holds; false after `timeout`.
+    pub async fn wait_until(
+        &self,
+        timeout: Duration,
+        mut condition: impl FnMut(&FederationHandle) -> bool,
+    ) -> bool {
+        let handle = self.handle();
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if condition(&handle) {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    /// Waits (up to 5s) until every node in `node_ids` is healthy with an open task stream.
+    pub async fn wait_connected(&self, node_ids: &[&str]) -> bool {
+        let state = self.state();
+        self.wait_until(Duration::from_secs(5), |handle| {
+            node_ids.iter().all(|id| {
+                handle.is_connected(id)
+                    && state
+                        .satellites
+                        .get(*id)
+                        .is_some_and(|s| s.health == NodeHealth::Healthy)
+            })
+        })
+        .await
+    }
+
+    /// Submits a task for `goal` in the background, as tenant "harness".
+    pub fn submit(
+        &self,
+        goal: &str,
+        options: TaskOptions,
+    ) -> JoinHandle<Result<TaskResult, FederationError>> {
+        let handle = self.handle();
+        let goal = goal.to_string();
+        tokio::spawn(async move {
+            let ctx = TenantContext {
+                tenant_id: "harness".into(),
+                correlation_id: None,
+                agent_id: None,
+                session_id: None,
+                capabilities: None,
+            };
+            handle.submit_task_with(&goal, "{}", &ctx, options).await
+        })
+    }
+}
+
+impl Drop for FederationHarness {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// A scripted satellite connected to a [`FederationHarness`].
+pub struct SimulatedSatellite {
+    pub node_id: String,
+    client: SatelliteClient,
+    behaviour: Arc<Mutex<SatelliteBehaviour>>,
+    /// Task ids handed to the executor, in order.
+    received: Arc<Mutex<Vec<String>>>,
+    online: Arc<watch::Sender<bool>>,
+    running: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SimulatedSatellite {
+    /// Task ids this satellite started running, in order (replays of cached results excluded).
+    pub fn received(&self) -> Vec<String> {
+        lock(&self.received).clone()
+    }
+
+    /// Replaces the script for tasks received from now on. The heartbeat period changes on the
+    /// next [`Self::reconnect`].
+    pub fn set_behaviour(&self, behaviour: SatelliteBehaviour) {
+        *lock(&self.behaviour) = behaviour;
+    }
+
+    pub fn is_online(&self) -> bool {
+        *self.online.borrow()
+    }
+
+    /// Cuts every connection to the master and refuses new ones: the task stream closes and
+    /// heartbeats stop, as when the satellite's host drops off the network.
+    pub fn disconnect(&self) {
+        self.online.send_replace(false);
+        if let Some(run) = lock(&self.running).take() {
+            run.abort();
+        }
+    }
+
+    /// Comes back online: registers again and re-opens the task stream.
+    pub fn reconnect(&self) {
+        self.disconnect();
+        self.online.send_replace(true);
+        self.spawn();
+    }
+
+    fn spawn(&self) {
+        let behaviour = Arc::clone(&self.behaviour);
+        let received = Arc::clone(&self.received);
+        let online = Arc::clone(&self.online);
+        let client = self
+            .client
+            .clone()
+            .with_heartbeat_interval(lock(&behaviour).heartbeat_interval);
+        let (node_id, role) = (client.node_id.clone(), client.role.clone());
+        let executor = move |task: Task, reporter: TaskReporter| {
+            let script = lock(&behaviour).clone();
+            let seen = {
+                let mut received = lock(&received);
+                received.push(task.task_id.clone());
+                received.len()
+            };
+            let online = Arc::clone(&online);
+            let (node_id, role) = (node_id.clone(), role.clone());
+            async move {
+                if script.disconnect_after == Some(seen) {
+                    online.send_replace(false);
+                    std::future::pending::<()>().await;
+                }
+                if script.hang {
+                    std::future::pending::<()>().await;
+                }
+                tokio::time::sleep(script.latency).await;
+                let mut failed = (seen <= script.fail_first).then(|| SCRIPTED_FAILURE.to_string());
+                let mut artifacts = Vec::new();
+                if let (None, Some((name, data))) = (&failed, script.attachment) {
+                    let uploaded = reporter
+                        .upload_artifact(&name, "application/octet-stream", data)
+                        .await;
+                    match uploaded {
+                        Ok(reference) => artifacts.push(reference),
+                        Err(e) => failed = Some(e.to_string()),
+                    }
+                }
+                TaskResult {
+                    task_id: task.task_id,
+                    success: failed.is_none(),
+                    summary: failed.unwrap_or_else(|| format!("{} done", task.goal)),
+                    details_json: task.context_json,
+                    completed_at_ms: chrono::Utc::now().timestamp_millis(),
+                    tenant_id: task.tenant_id,
+                    node_id,
+                    role,
+                    artifacts,
+                    ..Default::default()
+                }
+            }
+        };
+        let run = tokio::spawn(async move {
+            let _ = client.run_with_progress(HARNESS_ADDR, executor).await;
+        });
+        *lock(&self.running) = Some(run);
+    }
+}
+
+impl Drop for SimulatedSatellite {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::{LeastLoaded, RoleAffinity};
+
+    fn harness(roles: &[(&str, &str)]) -> FederationHarness {
+        let roles = roles
+            .iter()
+            .map(|(goal, role)| (goal.to_string(), role.to_string()))
+            .collect();
+        FederationHarness::with_scheduler(Arc::new(RoleAffinity::new(roles, Arc::new(LeastLoaded))))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn scripted_satellites_cover_scheduling_failures_and_cancellation() {
+        let harness = harness(&[("scan", "RedTeam"), ("research", "Backup")]);
+        let a = harness.satellite(
+            "a",
+            "RedTeam",
+            &["scan"],
+            SatelliteBehaviour::default()
+                .failing_first(1)
+                .with_latency(Duration::from_millis(20)),
+        );
+        let b = harness.satellite(
+            "b",
+            "Backup",
+            &["scan", "research"],
+            SatelliteBehaviour::default().hanging(),
+        );
+        assert!(harness.wait_connected(&["a", "b"]).await);
+
+        // The role preference routes scans to "a", whose first task fails by script.
+        let first = harness
+            .submit("scan", TaskOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!first.success);
+        assert_eq!(first.summary, SCRIPTED_FAILURE);
+        let second = harness
+            .submit("scan", TaskOptions::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(second.success);
+        assert_eq!(second.node_id, "a");
+        assert_eq!(a.received().len(), 2);
+        assert!(b.received().is_empty());
+
+        // A task that never finishes is cancelled from The Creator.
+        let long = harness.submit(
+            "research",
+            TaskOptions {
+                task_id: Some("long".into()),
+                ..Default::default()
+            },
+        );
+        assert!(
+            harness
+                .wait_until(Duration::from_secs(5), |_| b.received() == ["long"])
+                .await
+        );
+        assert!(harness
+            .handle()
+            .cancel_task("long", "operator")
+            .await
+            .unwrap());
+        assert!(matches!(
+            long.await.unwrap(),
+            Err(FederationError::Cancelled { ref task_id, .. }) if task_id == "long"
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn disconnects_and_slow_heartbeats_redispatch_under_the_same_id() {
+        let harness = harness(&[("scan", "RedTeam"), ("probe", "Slow")]);
+        let a = harness.satellite(
+            "a",
+            "RedTeam",
+            &["scan"],
+            SatelliteBehaviour::default().disconnecting_after(1),
+        );
+        let b = harness.satellite(
+            "b",
+            "Backup",
+            &["scan", "probe"],
+            SatelliteBehaviour::default(),
+        );
+        let c = harness.satellite(
+            "c",
+            "Slow",
+            &["probe"],
+            SatelliteBehaviour::default()
+                .hanging()
+                .heartbeat_every(Duration::from_secs(60)),
+        );
+        assert!(harness.wait_connected(&["a", "b", "c"]).await);
+
+        // "a" drops off the network on receiving the task; "b" finishes it under the same id.
+        let options = |id: &str| TaskOptions {
+            task_id: Some(id.into()),
+            ..Default::default()
+        };
+        let result = harness
+            .submit("scan", options("t1"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (result.task_id.as_str(), result.node_id.as_str()),
+            ("t1", "b")
+        );
+        assert_eq!(a.received(), ["t1"]);
+        assert_eq!(harness.health("a"), Some(NodeHealth::Dead));
+        assert!(!a.is_online());
+
+        a.set_behaviour(SatelliteBehaviour::default());
+        a.reconnect();
+        assert!(harness.wait_connected(&["a"]).await);
+
+        // "c" keeps its stream open but stops heartbeating: it is evicted and "b" takes over.
+        let result = harness
+            .submit("probe", options("t2"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.node_id, "b");
+        assert_eq!(c.received(), ["t2"]);
+        assert_eq!(b.received(), ["t1", "t2"]);
+        assert_eq!(harness.health("c"), Some(NodeHealth::Dead));
+    }
+}
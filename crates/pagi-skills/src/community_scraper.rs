@@ -64,7 +64,7 @@ impl AgentSkill for CommunityScraper {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let payload = payload.ok_or("CommunityScraper requires payload: { url: string } or { slot_id?: 1..8, url?, html? }")?;
@@ -91,10 +91,8 @@ impl AgentSkill for CommunityScraper {
             html
         } else {
             let url = url.ok_or("CommunityScraper requires 'url' when 'html' is not provided")?;
-            let client = reqwest::Client::builder()
-                .user_agent("UAC-CommunityScraper/1.0")
-                .build()?;
-            let resp = client.get(&url).send().await?;
+            let client = reqwest::Client::builder().user_agent("UAC-CommunityScraper/1.0");
+            let resp = ctx.http_request(client, reqwest::Method::GET, &url)?.send().await?;
             resp.text().await?
         };
 
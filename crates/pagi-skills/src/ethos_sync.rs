@@ -149,6 +149,8 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            session_id: None,
+            capabilities: None,
         };
         let payload = serde_json::json!({
             "active_school": "Stoic",
@@ -184,6 +186,8 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            session_id: None,
+            capabilities: None,
         };
         let payload = serde_json::json!({
             "active_school": "Growth-Mindset",
@@ -207,6 +211,8 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            session_id: None,
+            capabilities: None,
         };
         let payload = serde_json::json!({
             "active_school": "Absurdist",
@@ -235,6 +241,8 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            session_id: None,
+            capabilities: None,
         };
         let payload = serde_json::json!({
             "active_school": "",
@@ -1,16 +1,54 @@
 //! The Forge: Self-Synthesis Skill (Code Generator).
 //!
-//! Takes a JSON tool-spec from the LLM, generates a new `.rs` file in the skills
-//! directory, appends the module to lib.rs, and runs `cargo check` to ensure the
-//! new tool doesn't break the build. "If the skill doesn't exist, we build it."
-
-use pagi_core::{AgentSkill, TenantContext};
+//! Takes a JSON tool-spec from the LLM and generates a new skill module. "If the skill
+//! doesn't exist, we build it."
+//!
+//! ## Overlay crate
+//!
+//! Generated modules never touch `crates/pagi-skills`. They live in a separate crate and
+//! workspace, `data/forge_overlay` (`PAGI_FORGE_OVERLAY_DIR`), so a bad generation cannot break
+//! the main build and generated code stays out of human commits:
+//!
+//! ```text
+//! data/forge_overlay/
+//! ├── Cargo.toml            own [workspace]; path dep on pagi-core; rlib + cdylib
+//! ├── forge_manifest.json   generated skills and whether they passed `cargo check`
+//! ├── src/lib.rs            regenerated from the manifest (mods + C ABI dispatcher)
+//! ├── src/forge_gen_*.rs    one module per generated skill
+//! └── promotions/*.patch    reviewed-diff requests to move a skill into pagi-skills
+//! ```
+//!
+//! The overlay builds as a cdylib exporting the `pagi_dynamic_skill_execute` C ABI, which
+//! dispatches to the generated skills by name. At startup, and again after every hot-reload
+//! build, the gateway loads it through the `SkillLoader` and registers one [`OverlaySkill`] per
+//! checked manifest entry.
+//! [`ForgeOverlay::promote`] writes a `git apply`-able patch that adds a skill to
+//! `pagi-skills` for normal review.
+//!
+//! The declared [`ToolSpecParam`]s double as a test oracle: [`ForgeOverlay::property_test`]
+//! runs generated valid and adversarial payloads against a built skill (see
+//! [`pagi_core::PropertyHarness`]).
+
+use pagi_core::{
+    AgentSkill, ParamSchema, PropertyConfig, PropertyHarness, PropertyReport, SkillCapabilities,
+    SkillManifestEntry, SkillManifestRegistry, SkillProbe, SkillRegistry, TenantContext, TrustTier,
+};
+use pagi_evolution::{BuildEnv, SkillLoader};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 const SKILL_NAME: &str = "forge";
 
+/// Name under which the overlay library is registered in the `SkillLoader`.
+pub const OVERLAY_LIBRARY: &str = "forge_overlay";
+
+const MANIFEST_FILE: &str = "forge_manifest.json";
+const OVERLAY_CRATE: &str = "pagi-forge-overlay";
+
 /// JSON tool-spec from the LLM for generating a new skill.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolSpec {
@@ -33,6 +71,13 @@ pub struct ToolSpecParam {
     pub required: bool,
 }
 
+impl ToolSpecParam {
+    /// Property-test schema for this parameter.
+    pub fn schema(&self) -> ParamSchema {
+        ParamSchema::new(&self.name, &self.param_type, self.required)
+    }
+}
+
 /// Result of a Forge create operation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ForgeResult {
@@ -89,12 +134,12 @@ fn generate_skill_rs(spec: &ToolSpec) -> String {
             let optional = !p.required;
             if optional {
                 format!(
-                    "        let {} = payload.get(\"{}\").cloned();",
+                    "        let {} = payload_obj.get(\"{}\").cloned();",
                     var, key
                 )
             } else {
                 format!(
-                    "        let {} = payload.get(\"{}\").ok_or(\"Missing '{}'\")?.clone();",
+                    "        let {} = payload_obj.get(\"{}\").ok_or(\"Missing '{}'\")?.clone();",
                     var, key, key
                 )
             }
@@ -114,7 +159,7 @@ fn generate_skill_rs(spec: &ToolSpec) -> String {
 
     format!(
         r#"//! {}
-//! Generated by The Forge. Do not edit by hand without re-running cargo check.
+//! Generated by The Forge into the overlay crate. Promote it to pagi-skills for review.
 
 use pagi_core::{{AgentSkill, TenantContext}};
 
@@ -139,7 +184,8 @@ impl AgentSkill for {} {{
         _ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {{
-        let payload = payload.ok_or("{} requires a payload")?.as_object().ok_or("payload must be object")?;
+        let payload_val = payload.ok_or("{} requires a payload")?;
+        let payload_obj = payload_val.as_object().ok_or("payload must be object")?;
 {}
         Ok(serde_json::json!({{
             "status": "ok",
@@ -172,89 +218,642 @@ fn pascal_case(s: &str) -> String {
         .collect()
 }
 
-/// Writes the new module file and appends `mod X;` to lib.rs, then runs `cargo check`.
-/// `skills_src_dir` = path to `crates/pagi-skills/src`, `workspace_root` = repo root for cargo.
-pub fn create_skill_from_spec(
-    spec: &ToolSpec,
-    skills_src_dir: &Path,
-    workspace_root: &Path,
-) -> Result<ForgeResult, String> {
-    let module_name = sanitize_module_name(&spec.name)?;
-    let file_name = format!("forge_gen_{}.rs", module_name);
-    let file_path = skills_src_dir.join(&file_name);
-    let lib_rs = skills_src_dir.join("lib.rs");
-
-    let code = generate_skill_rs(spec);
-    std::fs::write(&file_path, &code).map_err(|e| format!("Failed to write {}: {}", file_path.display(), e))?;
-
-    let mod_line = format!("mod forge_gen_{};", module_name);
-    let lib_content = std::fs::read_to_string(&lib_rs).map_err(|e| format!("Failed to read lib.rs: {}", e))?;
-    if lib_content.contains(&mod_line) {
-        // Already registered
-    } else {
-        let insert_after = "mod deep_audit;";
-        let new_lib = if let Some(pos) = lib_content.find(insert_after) {
-            let end = pos + insert_after.len();
+/// A generated skill recorded in `forge_manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForgeManifestEntry {
+    /// Skill name (as registered with the gateway).
+    pub name: String,
+    /// Module name inside the overlay crate (`forge_gen_{name}`).
+    pub module: String,
+    /// PascalCase type implementing `AgentSkill`.
+    pub type_name: String,
+    pub description: String,
+    #[serde(default)]
+    pub params: Vec<ToolSpecParam>,
+    /// Whether the module passed `cargo check`; only checked modules are compiled in.
+    pub checked: bool,
+    pub created_at_ms: i64,
+}
+
+/// Registry of generated skills; `src/lib.rs` of the overlay is regenerated from it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForgeManifest {
+    #[serde(default)]
+    pub skills: Vec<ForgeManifestEntry>,
+}
+
+impl ForgeManifest {
+    pub fn get(&self, name: &str) -> Option<&ForgeManifestEntry> {
+        self.skills.iter().find(|e| e.name == name)
+    }
+
+    /// Declared parameters of every checked skill, keyed by skill name
+    /// (for `MaintenanceConfig::skill_params`).
+    pub fn skill_params(&self) -> HashMap<String, Vec<ParamSchema>> {
+        self.skills
+            .iter()
+            .filter(|e| e.checked)
+            .map(|e| (e.name.clone(), e.params.iter().map(ToolSpecParam::schema).collect()))
+            .collect()
+    }
+
+    fn upsert(&mut self, entry: ForgeManifestEntry) {
+        self.skills.retain(|e| e.name != entry.name);
+        self.skills.push(entry);
+    }
+}
+
+/// Result of [`ForgeOverlay::promote`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionResult {
+    pub skill_name: String,
+    /// Patch file, relative paths from the workspace root (`git apply <patch>`).
+    pub patch_path: String,
+    pub diff: String,
+    /// Whether `git apply --check` accepted the patch against the current tree.
+    pub applies_cleanly: bool,
+    pub message: String,
+}
+
+/// The Forge overlay crate (see the module docs).
+#[derive(Debug, Clone)]
+pub struct ForgeOverlay {
+    root: PathBuf,
+    workspace_root: PathBuf,
+}
+
+impl ForgeOverlay {
+    /// Overlay at `root` for the workspace at `workspace_root` (which provides `pagi-core`).
+    pub fn new(root: impl Into<PathBuf>, workspace_root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            workspace_root: workspace_root.into(),
+        }
+    }
+
+    /// `PAGI_FORGE_OVERLAY_DIR` (default `data/forge_overlay`), relative to `workspace_root`.
+    pub fn from_env(workspace_root: &Path) -> Self {
+        let dir = std::env::var("PAGI_FORGE_OVERLAY_DIR")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| "data/forge_overlay".to_string());
+        Self::new(workspace_root.join(dir.trim()), workspace_root)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.root.join(MANIFEST_FILE)
+    }
+
+    /// The manifest, or an empty one if the overlay has not been created yet.
+    pub fn manifest(&self) -> ForgeManifest {
+        std::fs::read_to_string(self.manifest_path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_manifest(&self, manifest: &ForgeManifest) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+        let tmp = self.root.join(format!("{}.tmp", MANIFEST_FILE));
+        std::fs::write(&tmp, json).map_err(|e| format!("Failed to write manifest: {}", e))?;
+        std::fs::rename(&tmp, self.manifest_path()).map_err(|e| format!("Failed to write manifest: {}", e))
+    }
+
+    /// Path of the built overlay cdylib (exists after [`Self::build`]).
+    pub fn artifact_path(&self) -> PathBuf {
+        self.target_dir().join("release").join(format!(
+            "{}{}{}",
+            std::env::consts::DLL_PREFIX,
+            OVERLAY_CRATE.replace('-', "_"),
+            std::env::consts::DLL_SUFFIX
+        ))
+    }
+
+    fn target_dir(&self) -> PathBuf {
+        self.root.join("target")
+    }
+
+    /// Creates `Cargo.toml`, `.gitignore` and the seeded `Cargo.lock` on first use.
+    fn ensure_crate(&self) -> Result<(), String> {
+        std::fs::create_dir_all(self.root.join("src"))
+            .map_err(|e| format!("Failed to create {}: {}", self.root.display(), e))?;
+        let core = self.workspace_root.join("crates").join("pagi-core");
+        let core = core.canonicalize().unwrap_or(core);
+        let manifest = format!(
+            r#"# Generated by The Forge. Separate workspace: never part of the main build.
+[package]
+name = "{OVERLAY_CRATE}"
+version = "0.1.0"
+edition = "2021"
+publish = false
+
+[lib]
+crate-type = ["rlib", "cdylib"]
+
+[dependencies]
+pagi-core = {{ path = "{core}" }}
+async-trait = "0.1"
+futures = "0.3"
+serde_json = "1.0"
+
+[workspace]
+"#,
+            core = core.display().to_string().replace('\\', "/"),
+        );
+        write_if_changed(&self.root.join("Cargo.toml"), &manifest)?;
+        write_if_changed(&self.root.join(".gitignore"), "target/\n")?;
+
+        // Pin versions to the main workspace so the overlay builds offline from the same cache.
+        let lock = self.root.join("Cargo.lock");
+        let workspace_lock = self.workspace_root.join("Cargo.lock");
+        if !lock.exists() && workspace_lock.exists() {
+            std::fs::copy(&workspace_lock, &lock).map_err(|e| format!("Failed to seed Cargo.lock: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the module for `spec`, records it in the manifest and runs `cargo check` on the
+    /// overlay. A module that fails the check stays on disk but is left out of `lib.rs`, so the
+    /// overlay keeps building.
+    pub fn create_skill(&self, spec: &ToolSpec) -> Result<ForgeResult, String> {
+        let module_name = sanitize_module_name(&spec.name)?;
+        self.ensure_crate()?;
+
+        let module = format!("forge_gen_{}", module_name);
+        let file_path = self.root.join("src").join(format!("{}.rs", module));
+        std::fs::write(&file_path, generate_skill_rs(spec))
+            .map_err(|e| format!("Failed to write {}: {}", file_path.display(), e))?;
+
+        let mut manifest = self.manifest();
+        manifest.upsert(ForgeManifestEntry {
+            name: spec.name.clone(),
+            module: module.clone(),
+            type_name: pascal_case(&spec.name),
+            description: spec.description.clone(),
+            params: spec.params.clone(),
+            checked: true,
+            created_at_ms: now_epoch_ms(),
+        });
+        self.write_lib(&manifest)?;
+
+        let (cargo_check_ok, cargo_stderr) = self.cargo("check");
+        if !cargo_check_ok {
+            if let Some(entry) = manifest.skills.iter_mut().find(|e| e.name == spec.name) {
+                entry.checked = false;
+            }
+            self.write_lib(&manifest)?;
+        }
+        self.save_manifest(&manifest)?;
+
+        let message = if cargo_check_ok {
             format!(
-                "{}\n{}\n{}",
-                &lib_content[..end],
-                mod_line,
-                &lib_content[end..]
+                "Forge created skill '{}' in the overlay crate ({}). Once the overlay is built and its property tests pass, register_skills hot-loads it into the running gateway.",
+                spec.name,
+                self.root.display()
             )
         } else {
-            format!("{}\n{}\n", lib_content.trim_end(), mod_line)
+            format!(
+                "Forge wrote {} but cargo check failed; the module is excluded from the overlay until regenerated.",
+                file_path.display()
+            )
         };
-        std::fs::write(&lib_rs, new_lib).map_err(|e| format!("Failed to update lib.rs: {}", e))?;
+
+        Ok(ForgeResult {
+            success: cargo_check_ok,
+            module_name: module,
+            file_path: file_path.to_string_lossy().to_string(),
+            cargo_check_ok,
+            message,
+            cargo_stderr,
+        })
     }
 
-    let cargo_check = Command::new("cargo")
-        .arg("check")
-        .arg("-p")
-        .arg("pagi-skills")
-        .current_dir(workspace_root)
-        .output();
+    /// `cargo build --release` of the overlay cdylib. Returns the artifact path.
+    pub fn build(&self) -> Result<PathBuf, String> {
+        self.ensure_crate()?;
+        self.write_lib(&self.manifest())?;
+        let (ok, stderr) = self.cargo("build");
+        if !ok {
+            return Err(format!(
+                "Overlay build failed:\n{}",
+                stderr.unwrap_or_default()
+            ));
+        }
+        Ok(self.artifact_path())
+    }
 
-    let (cargo_check_ok, cargo_stderr) = match cargo_check {
-        Ok(out) => {
-            let ok = out.status.success();
-            let stderr = if out.stderr.is_empty() {
-                None
-            } else {
-                Some(String::from_utf8_lossy(&out.stderr).to_string())
-            };
-            (ok, stderr)
+    fn cargo(&self, command: &str) -> (bool, Option<String>) {
+        let mut cmd = Command::new("cargo");
+        cmd.arg(command)
+            .arg("--manifest-path")
+            .arg(self.root.join("Cargo.toml"))
+            .arg("--lib")
+            .env("CARGO_TARGET_DIR", self.target_dir())
+            .current_dir(&self.root);
+        if command == "build" {
+            cmd.arg("--release");
         }
-        Err(e) => (false, Some(format!("cargo check failed to run: {}", e))),
-    };
+        if BuildEnv::from_env().offline {
+            cmd.arg("--offline");
+        }
+        match cmd.output() {
+            Ok(out) => {
+                let stderr = (!out.stderr.is_empty()).then(|| String::from_utf8_lossy(&out.stderr).to_string());
+                (out.status.success(), stderr)
+            }
+            Err(e) => (false, Some(format!("cargo {} failed to run: {}", command, e))),
+        }
+    }
 
-    let message = if cargo_check_ok {
-        format!(
-            "Forge created skill '{}'. Module: forge_gen_{}. Rebuild gateway to load.",
-            spec.name, module_name
-        )
-    } else {
-        format!(
-            "Forge wrote {} but cargo check failed. Fix errors and run cargo check -p pagi-skills.",
-            file_path.display()
-        )
-    };
+    fn write_lib(&self, manifest: &ForgeManifest) -> Result<(), String> {
+        write_if_changed(&self.root.join("src").join("lib.rs"), &render_overlay_lib(manifest))
+    }
+
+    /// Registers one [`OverlaySkill`] per checked manifest entry, after loading the built
+    /// overlay into `loader`, and lists it in `manifests` as a generated (Tier 3) skill with no
+    /// capabilities. Safe to call again after a rebuild: the library is swapped in the loader, so
+    /// overlay skills registered earlier serve the new build, and only new names are added.
+    /// Returns the number newly registered (0 when the overlay has not been built).
+    pub fn register_skills(
+        &self,
+        registry: &SkillRegistry,
+        manifests: &SkillManifestRegistry,
+        loader: Arc<SkillLoader>,
+    ) -> Result<usize, String> {
+        let artifact = self.artifact_path();
+        let manifest = self.manifest();
+        if manifest.skills.is_empty() || !artifact.exists() {
+            return Ok(0);
+        }
+        let loaded = self.snapshot_artifact(&artifact)?;
+        loader
+            .load(&loaded, OVERLAY_LIBRARY.to_string())
+            .map_err(|e| format!("Failed to load {}: {}", loaded.display(), e))?;
+        let mut registered = 0;
+        for entry in manifest.skills.iter().filter(|e| e.checked) {
+            if registry.get(&entry.name).is_some() {
+                let is_overlay = manifests
+                    .capability_handle(&entry.name)
+                    .is_some_and(|h| h.tier() == TrustTier::Generated);
+                if is_overlay {
+                    continue;
+                }
+                tracing::warn!(
+                    target: "pagi::forge",
+                    skill = %entry.name,
+                    "Overlay skill shadows a built-in skill; skipped"
+                );
+                continue;
+            }
+            registry.register_runtime(Arc::new(OverlaySkill::new(&entry.name, Arc::clone(&loader))));
+            manifests.register_runtime(
+                TrustTier::Generated,
+                SkillManifestEntry {
+                    skill_id: entry.name.clone(),
+                    kb_layers_allowed: Vec::new(),
+                    description: Some(entry.description.clone()),
+                    capabilities: Default::default(),
+                    artifact: None,
+                    artifact_sha256: None,
+                    signature: None,
+                },
+            );
+            registered += 1;
+        }
+        Ok(registered)
+    }
+
+    /// Copies the built overlay to `target/loaded/` under a fresh name and removes older copies.
+    /// The dynamic linker caches libraries by path, so reloading the build output in place would
+    /// keep serving the first build in an in-process loader.
+    fn snapshot_artifact(&self, artifact: &Path) -> Result<PathBuf, String> {
+        let dir = self.target_dir().join("loaded");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let snapshot = dir.join(format!(
+            "{}{}_{}{}",
+            std::env::consts::DLL_PREFIX,
+            OVERLAY_CRATE.replace('-', "_"),
+            now_epoch_ms(),
+            std::env::consts::DLL_SUFFIX
+        ));
+        std::fs::copy(artifact, &snapshot).map_err(|e| format!("Failed to copy {}: {}", artifact.display(), e))?;
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            for old in entries.flatten().map(|e| e.path()).filter(|p| *p != snapshot) {
+                // Still mapped on some platforms; the next reload retries.
+                let _ = std::fs::remove_file(old);
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Runs property-based smoke tests for `skill_name` against the built overlay, using the
+    /// parameters declared in its manifest entry. The overlay is loaded into a fresh
+    /// `SkillLoader::from_env` (sandboxed by default), so a crashing payload cannot take down
+    /// the caller. Blocking; run from `spawn_blocking` in async code.
+    pub fn property_test(&self, skill_name: &str, config: PropertyConfig) -> Result<PropertyReport, String> {
+        let manifest = self.manifest();
+        let entry = manifest
+            .get(skill_name)
+            .filter(|e| e.checked)
+            .ok_or_else(|| format!("No checked overlay skill named '{}'", skill_name))?;
+        let artifact = self.artifact_path();
+        if !artifact.exists() {
+            return Err(format!("Overlay not built: {} is missing", artifact.display()));
+        }
+
+        let probe_name = format!("{}_probe", OVERLAY_LIBRARY);
+        let loader = Arc::new(SkillLoader::from_env());
+        loader
+            .load(&artifact, probe_name.clone())
+            .map_err(|e| format!("Failed to load {}: {}", artifact.display(), e))?;
+        let ctx = serde_json::json!({ "tenant_id": "forge_property_test", "correlation_id": null });
+        // Probes run with the narrowest scope: Generated tier, nothing declared.
+        let scope = serde_json::json!({ "tier": TrustTier::Generated, "grants": SkillCapabilities::default() });
+        let skill = entry.name.clone();
+        let probe: SkillProbe = {
+            let loader = Arc::clone(&loader);
+            let probe_name = probe_name.clone();
+            Arc::new(move |payload: &serde_json::Value| {
+                let args = serde_json::json!({
+                    "skill": skill,
+                    "ctx": ctx,
+                    "capabilities": scope,
+                    "payload": payload,
+                });
+                loader
+                    .execute(&probe_name, args)
+                    .map(|out| out.to_string())
+                    .map_err(|e| e.to_string())
+            })
+        };
 
-    Ok(ForgeResult {
-        success: cargo_check_ok,
-        module_name: format!("forge_gen_{}", module_name),
-        file_path: file_path.to_string_lossy().to_string(),
-        cargo_check_ok,
-        message,
-        cargo_stderr,
-    })
+        let params: Vec<ParamSchema> = entry.params.iter().map(ToolSpecParam::schema).collect();
+        let report = PropertyHarness::new(&params, config).run(&probe);
+        loader.unload(&probe_name);
+        Ok(report)
+    }
+
+    /// Writes `promotions/{module}.patch`: adds the module to `crates/pagi-skills/src` and its
+    /// `mod` line to `lib.rs`. Nothing in the source tree changes until the patch is reviewed
+    /// and applied with `git apply`.
+    pub fn promote(&self, skill_name: &str) -> Result<PromotionResult, String> {
+        let manifest = self.manifest();
+        let entry = manifest
+            .get(skill_name)
+            .ok_or_else(|| format!("No overlay skill named '{}'", skill_name))?;
+        if !entry.checked {
+            return Err(format!("Overlay skill '{}' did not pass cargo check", skill_name));
+        }
+        let source_path = self.root.join("src").join(format!("{}.rs", entry.module));
+        let source = std::fs::read_to_string(&source_path)
+            .map_err(|e| format!("Failed to read {}: {}", source_path.display(), e))?;
+        let lib_rs = std::fs::read_to_string(self.workspace_root.join("crates/pagi-skills/src/lib.rs"))
+            .map_err(|e| format!("Failed to read pagi-skills lib.rs: {}", e))?;
+
+        let diff = promotion_patch(&entry.module, &source, &lib_rs)?;
+        let dir = self.root.join("promotions");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let patch_path = dir.join(format!("{}.patch", entry.module));
+        std::fs::write(&patch_path, &diff).map_err(|e| format!("Failed to write patch: {}", e))?;
+
+        let applies_cleanly = Command::new("git")
+            .args(["apply", "--check"])
+            .arg(&patch_path)
+            .current_dir(&self.workspace_root)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false);
+        let message = if applies_cleanly {
+            format!(
+                "Review {} and apply it with `git apply` to move '{}' into pagi-skills.",
+                patch_path.display(),
+                skill_name
+            )
+        } else {
+            format!(
+                "Wrote {}, but it does not apply cleanly to the current tree; regenerate after rebasing.",
+                patch_path.display()
+            )
+        };
+
+        Ok(PromotionResult {
+            skill_name: skill_name.to_string(),
+            patch_path: patch_path.to_string_lossy().to_string(),
+            diff,
+            applies_cleanly,
+            message,
+        })
+    }
+}
+
+/// Overlay `src/lib.rs`: checked modules, `skills()`, and the C ABI dispatcher expected by
+/// `pagi_evolution::SkillLoader` (`{"skill", "ctx", "capabilities", "payload"}` in, `{"ok"}` /
+/// `{"error"}` out). `TenantContext` does not serialize its capability handle, so the dispatcher
+/// rebuilds it from `capabilities` (`{"tier", "grants"}`) and refuses calls that carry none.
+fn render_overlay_lib(manifest: &ForgeManifest) -> String {
+    let checked: Vec<&ForgeManifestEntry> = manifest.skills.iter().filter(|e| e.checked).collect();
+    let mods: String = checked.iter().map(|e| format!("pub mod {};\n", e.module)).collect();
+    let ctors: String = checked
+        .iter()
+        .map(|e| format!("        Arc::new({}::{}::new()),\n", e.module, e.type_name))
+        .collect();
+    format!(
+        r#"//! Generated by The Forge from forge_manifest.json. Regenerated on every Forge run; do not edit.
+
+use std::ffi::{{c_char, CStr, CString}};
+use std::sync::Arc;
+
+use pagi_core::{{AgentSkill, CapabilityHandle, SkillCapabilities, TenantContext, TrustTier}};
+
+{mods}
+/// Every checked overlay skill.
+pub fn skills() -> Vec<Arc<dyn AgentSkill>> {{
+    vec![
+{ctors}    ]
+}}
+
+fn dispatch(args: serde_json::Value) -> Result<serde_json::Value, String> {{
+    let name = args.get("skill").and_then(|v| v.as_str()).ok_or("missing 'skill'")?;
+    let mut ctx: TenantContext = serde_json::from_value(args.get("ctx").cloned().unwrap_or_default())
+        .map_err(|e| format!("invalid ctx: {{}}", e))?;
+    let scope = args
+        .get("capabilities")
+        .filter(|c| !c.is_null())
+        .ok_or("missing 'capabilities': overlay skills only run capability-scoped")?;
+    let tier: TrustTier = serde_json::from_value(scope.get("tier").cloned().unwrap_or_default())
+        .map_err(|e| format!("invalid capabilities.tier: {{}}", e))?;
+    let grants: SkillCapabilities = serde_json::from_value(scope.get("grants").cloned().unwrap_or_default())
+        .map_err(|e| format!("invalid capabilities.grants: {{}}", e))?;
+    ctx.capabilities = Some(CapabilityHandle::new(name, tier, grants));
+    let payload = args.get("payload").cloned().filter(|p| !p.is_null());
+    let skill = skills()
+        .into_iter()
+        .find(|s| s.name() == name)
+        .ok_or_else(|| format!("unknown overlay skill '{{}}'", name))?;
+    futures::executor::block_on(skill.execute(&ctx, payload)).map_err(|e| e.to_string())
+}}
+
+/// # Safety
+/// `args` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pagi_dynamic_skill_execute(args: *const c_char) -> *mut c_char {{
+    let args = CStr::from_ptr(args).to_string_lossy();
+    let out = match serde_json::from_str(&args).map_err(|e| e.to_string()).and_then(dispatch) {{
+        Ok(value) => serde_json::json!({{ "ok": value }}),
+        Err(error) => serde_json::json!({{ "error": error }}),
+    }};
+    CString::new(out.to_string()).map(CString::into_raw).unwrap_or(std::ptr::null_mut())
+}}
+
+/// # Safety
+/// `ptr` must come from `pagi_dynamic_skill_execute`.
+#[no_mangle]
+pub unsafe extern "C" fn pagi_dynamic_skill_free(ptr: *mut c_char) {{
+    if !ptr.is_null() {{
+        drop(CString::from_raw(ptr));
+    }}
+}}
+"#
+    )
+}
+
+/// Unified diff adding `crates/pagi-skills/src/{module}.rs` and its `mod` line after
+/// `mod deep_audit;` (or at the end of the `mod` block).
+fn promotion_patch(module: &str, source: &str, lib_rs: &str) -> Result<String, String> {
+    let mod_line = format!("mod {};", module);
+    let lines: Vec<&str> = lib_rs.lines().collect();
+    if lines.iter().any(|l| l.trim() == mod_line) {
+        return Err(format!("pagi-skills already declares `{}`", mod_line));
+    }
+    // Insert after this index (0-based).
+    let anchor = lines
+        .iter()
+        .position(|l| l.trim() == "mod deep_audit;")
+        .or_else(|| lines.iter().rposition(|l| l.starts_with("mod ") || l.starts_with("pub mod ")))
+        .ok_or("pagi-skills lib.rs has no mod declarations")?;
+
+    let file = format!("crates/pagi-skills/src/{}.rs", module);
+    let source_lines: Vec<&str> = source.lines().collect();
+    let mut diff = format!(
+        "diff --git a/{file} b/{file}\nnew file mode 100644\n--- /dev/null\n+++ b/{file}\n@@ -0,0 +1,{} @@\n",
+        source_lines.len()
+    );
+    for line in &source_lines {
+        diff.push_str(&format!("+{}\n", line));
+    }
+
+    let lib = "crates/pagi-skills/src/lib.rs";
+    let ctx_start = anchor.saturating_sub(2);
+    let ctx_end = (anchor + 3).min(lines.len() - 1);
+    let before = ctx_end - ctx_start + 1;
+    diff.push_str(&format!(
+        "diff --git a/{lib} b/{lib}\n--- a/{lib}\n+++ b/{lib}\n@@ -{},{} +{},{} @@\n",
+        ctx_start + 1,
+        before,
+        ctx_start + 1,
+        before + 1
+    ));
+    for (i, line) in lines.iter().enumerate().take(ctx_end + 1).skip(ctx_start) {
+        diff.push_str(&format!(" {}\n", line));
+        if i == anchor {
+            diff.push_str(&format!("+{}\n", mod_line));
+        }
+    }
+    Ok(diff)
+}
+
+fn write_if_changed(path: &Path, content: &str) -> Result<(), String> {
+    if std::fs::read_to_string(path).ok().as_deref() == Some(content) {
+        return Ok(());
+    }
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn now_epoch_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Writes the module into the Forge overlay crate and runs `cargo check` on the overlay only
+/// (see [`ForgeOverlay::create_skill`]).
+pub fn create_skill_from_spec(spec: &ToolSpec, overlay: &ForgeOverlay) -> Result<ForgeResult, String> {
+    overlay.create_skill(spec)
+}
+
+/// Gateway-side proxy for one overlay skill; calls into the overlay library via the `SkillLoader`.
+pub struct OverlaySkill {
+    name: String,
+    loader: Arc<SkillLoader>,
+}
+
+impl OverlaySkill {
+    pub fn new(name: &str, loader: Arc<SkillLoader>) -> Self {
+        Self {
+            name: name.to_string(),
+            loader,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentSkill for OverlaySkill {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn execute(
+        &self,
+        ctx: &TenantContext,
+        payload: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        // The handle does not survive serialization; send its scope alongside the context.
+        let caps = ctx.capabilities.as_ref().ok_or_else(|| {
+            format!("Overlay skill '{}' refused: context is not capability-scoped", self.name)
+        })?;
+        let args = serde_json::json!({
+            "skill": self.name,
+            "ctx": ctx,
+            "capabilities": { "tier": caps.tier(), "grants": caps.capabilities() },
+            "payload": payload,
+        });
+        let loader = Arc::clone(&self.loader);
+        let out = tokio::task::spawn_blocking(move || loader.execute(OVERLAY_LIBRARY, args)).await??;
+        match out.get("error").and_then(|e| e.as_str()) {
+            Some(error) => Err(error.to_string().into()),
+            None => Ok(out.get("ok").cloned().unwrap_or(serde_json::Value::Null)),
+        }
+    }
 }
 
 /// AgentSkill wrapper for The Forge (invokable by the orchestrator).
-pub struct ForgeSkill;
+///
+/// Payload is a [`ToolSpec`] to generate a skill, or `{ "action": "promote", "name": ... }` to
+/// write a promotion patch for an existing overlay skill.
+pub struct ForgeSkill {
+    overlay: Option<ForgeOverlay>,
+}
 
 impl ForgeSkill {
+    /// Uses [`ForgeOverlay::from_env`] relative to the current directory.
     pub fn new() -> Self {
-        Self {}
+        Self { overlay: None }
+    }
+
+    pub fn with_overlay(overlay: ForgeOverlay) -> Self {
+        Self { overlay: Some(overlay) }
+    }
+
+    fn overlay(&self) -> ForgeOverlay {
+        self.overlay.clone().unwrap_or_else(|| {
+            let workspace_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            ForgeOverlay::from_env(&workspace_root)
+        })
     }
 }
 
@@ -276,18 +875,117 @@ impl AgentSkill for ForgeSkill {
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let payload = payload.ok_or("forge requires payload: { name, description?, params? }")?;
+        let overlay = self.overlay();
+
+        if payload.get("action").and_then(|a| a.as_str()) == Some("promote") {
+            let name = payload
+                .get("name")
+                .and_then(|n| n.as_str())
+                .ok_or("promote requires 'name'")?;
+            let result = overlay.promote(name).map_err(|e| format!("Forge error: {}", e))?;
+            return Ok(serde_json::to_value(result)?);
+        }
+
         let spec: ToolSpec = serde_json::from_value(payload).map_err(|e| format!("Invalid tool spec: {}", e))?;
+        let result = tokio::task::spawn_blocking(move || create_skill_from_spec(&spec, &overlay))
+            .await?
+            .map_err(|e| format!("Forge error: {}", e))?;
 
-        let workspace_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-        let skills_src = workspace_root.join("crates/pagi-skills/src");
+        Ok(serde_json::to_value(result)?)
+    }
+}
 
-        if !skills_src.join("lib.rs").exists() {
-            return Err("crates/pagi-skills/src not found (run from workspace root)".into());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, checked: bool) -> ForgeManifestEntry {
+        ForgeManifestEntry {
+            name: name.to_string(),
+            module: format!("forge_gen_{}", name),
+            type_name: pascal_case(name),
+            description: String::new(),
+            params: Vec::new(),
+            checked,
+            created_at_ms: 0,
         }
+    }
 
-        let result = create_skill_from_spec(&spec, &skills_src, &workspace_root)
-            .map_err(|e| format!("Forge error: {}", e))?;
+    #[test]
+    fn overlay_lib_includes_only_checked_modules() {
+        let manifest = ForgeManifest {
+            skills: vec![entry("weather_sentinel", true), entry("broken_tool", false)],
+        };
+        let lib = render_overlay_lib(&manifest);
+        assert!(lib.contains("pub mod forge_gen_weather_sentinel;"));
+        assert!(lib.contains("Arc::new(forge_gen_weather_sentinel::WeatherSentinel::new()),"));
+        assert!(!lib.contains("broken_tool"));
+        assert!(lib.contains("pub unsafe extern \"C\" fn pagi_dynamic_skill_execute"));
+        assert!(lib.contains("ctx.capabilities = Some(CapabilityHandle::new(name, tier, grants));"));
+    }
 
-        Ok(serde_json::to_value(result)?)
+    #[test]
+    fn promotion_patch_adds_file_and_mod_line() {
+        let lib_rs = "mod a;\nmod b;\nmod deep_audit;\nmod c;\n\npub use a::A;\n";
+        let diff = promotion_patch("forge_gen_x", "//! X\npub struct X;\n", lib_rs).unwrap();
+        assert!(diff.contains("+++ b/crates/pagi-skills/src/forge_gen_x.rs\n@@ -0,0 +1,2 @@\n+//! X\n+pub struct X;\n"));
+        assert!(diff.contains("@@ -1,6 +1,7 @@\n mod a;\n mod b;\n mod deep_audit;\n+mod forge_gen_x;\n mod c;\n \n pub use a::A;\n"));
+
+        let promoted = lib_rs.replace("mod deep_audit;\n", "mod deep_audit;\nmod forge_gen_x;\n");
+        assert!(promotion_patch("forge_gen_x", "", &promoted).is_err());
+    }
+
+    #[test]
+    fn manifest_round_trips_and_promote_requires_checked_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let overlay = ForgeOverlay::new(dir.path().join("overlay"), dir.path());
+        std::fs::create_dir_all(overlay.root()).unwrap();
+        let manifest = ForgeManifest {
+            skills: vec![entry("broken_tool", false)],
+        };
+        overlay.save_manifest(&manifest).unwrap();
+
+        assert_eq!(overlay.manifest().skills.len(), 1);
+        assert!(overlay.promote("broken_tool").unwrap_err().contains("cargo check"));
+        assert!(overlay.promote("missing").is_err());
+    }
+
+    #[test]
+    fn reload_snapshots_the_artifact_and_drops_older_copies() {
+        let dir = tempfile::tempdir().unwrap();
+        let overlay = ForgeOverlay::new(dir.path().join("overlay"), dir.path());
+        let artifact = overlay.artifact_path();
+        std::fs::create_dir_all(artifact.parent().unwrap()).unwrap();
+        std::fs::write(&artifact, b"build 2").unwrap();
+        let loaded = overlay.target_dir().join("loaded");
+        std::fs::create_dir_all(&loaded).unwrap();
+        std::fs::write(loaded.join("stale_build_1"), b"build 1").unwrap();
+
+        let snapshot = overlay.snapshot_artifact(&artifact).unwrap();
+        assert_ne!(snapshot, artifact);
+        assert_eq!(std::fs::read(&snapshot).unwrap(), b"build 2");
+        let remaining: Vec<_> = std::fs::read_dir(&loaded).unwrap().flatten().map(|e| e.path()).collect();
+        assert_eq!(remaining, vec![snapshot]);
+    }
+
+    #[test]
+    fn skill_params_come_from_checked_entries() {
+        let mut weather = entry("weather_sentinel", true);
+        weather.params = vec![
+            ToolSpecParam { name: "location".into(), param_type: "string".into(), required: true },
+            ToolSpecParam { name: "days".into(), param_type: "i64".into(), required: false },
+        ];
+        let mut broken = entry("broken_tool", false);
+        broken.params = weather.params.clone();
+        let params = ForgeManifest { skills: vec![weather, broken] }.skill_params();
+
+        assert_eq!(params.len(), 1);
+        assert_eq!(
+            params["weather_sentinel"],
+            vec![
+                ParamSchema::new("location", "string", true),
+                ParamSchema { name: "days".into(), kind: pagi_core::ParamKind::Integer, required: false },
+            ]
+        );
     }
 }
@@ -655,6 +655,8 @@ mod tests {
                     tenant_id: "t".to_string(),
                     correlation_id: None,
                     agent_id: None,
+                    session_id: None,
+                    capabilities: None,
                 },
                 Some(serde_json::json!({
                     "path": "report.md",
@@ -139,7 +139,10 @@ pub use mimir_skills::PreFlightAudioSkill;
 // Deep Audit: Sovereign document ingestion and routing
 pub use deep_audit::{DeepAuditSkill, KnowledgeBase, IngestResult, AuditSummary};
 // The Forge: Self-synthesis skill (code generator from JSON tool-spec)
-pub use forge::{create_skill_from_spec, ForgeResult, ForgeSkill, ToolSpec, ToolSpecParam};
+pub use forge::{
+    create_skill_from_spec, ForgeManifest, ForgeManifestEntry, ForgeOverlay, ForgeResult, ForgeSkill,
+    OverlaySkill, PromotionResult, ToolSpec, ToolSpecParam, OVERLAY_LIBRARY,
+};
 // Sentinel: Active monitoring and intervention capabilities
 pub use sentinel::{
     // Physical Guard
@@ -18,6 +18,8 @@ const DEFAULT_API_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
 const DEFAULT_EMBEDDINGS_API_URL: &str = "https://openrouter.ai/api/v1/embeddings";
 const DEFAULT_MODEL: &str = "meta-llama/llama-3.3-70b-instruct";
 const DEFAULT_EMBEDDINGS_MODEL: &str = "text-embedding-3-small";
+/// Tokens reserved against a capability-scoped LLM allowance when the payload sets no max_tokens.
+const DEFAULT_MAX_TOKENS_RESERVATION: u32 = 1024;
 
 /// Mode for LLM invocation: mock (returns simulated generation) or live (calls external API).
 #[derive(Clone, Copy, Debug, Default)]
@@ -136,6 +138,19 @@ impl ModelRouter {
         Ok(s)
     }
 
+    /// Same lookup as [`Self::openrouter_api_key`], read through the caller's capability handle.
+    fn scoped_api_key(ctx: &TenantContext) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let s = match ctx.env_var(ENV_LLM_API_KEY)? {
+            Some(s) => s,
+            None => ctx.env_var(ENV_OPENROUTER_API_KEY)?.unwrap_or_default(),
+        };
+        let s = s.trim().to_string();
+        if s.is_empty() {
+            return Err("Missing PAGI_LLM_API_KEY or OPENROUTER_API_KEY".into());
+        }
+        Ok(s)
+    }
+
     pub fn new() -> Self {
         Self {
             mode: LlmMode::from_env(),
@@ -224,6 +239,7 @@ impl ModelRouter {
     /// When system_prompt is Some, sends [system, user] (Sovereign Mission Directive); otherwise [user] only.
     async fn live_generate(
         &self,
+        ctx: &TenantContext,
         system_prompt: Option<&str>,
         prompt: &str,
         model_override: Option<&str>,
@@ -231,12 +247,12 @@ impl ModelRouter {
         max_tokens: Option<u32>,
     ) -> Result<(String, Option<TokenUsage>), Box<dyn std::error::Error + Send + Sync>> {
         let messages = self.build_messages(system_prompt, prompt, system_prompt.is_none());
-        let url = std::env::var(ENV_LLM_API_URL).unwrap_or_else(|_| DEFAULT_API_URL.to_string());
-        let key = Self::openrouter_api_key()?;
-        let model = model_override
-            .map(|s| s.to_string())
-            .or_else(|| std::env::var(ENV_LLM_MODEL).ok())
-            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let url = ctx.env_var(ENV_LLM_API_URL)?.unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        let key = Self::scoped_api_key(ctx)?;
+        let model = match model_override {
+            Some(m) => m.to_string(),
+            None => ctx.env_var(ENV_LLM_MODEL)?.unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        };
 
         eprintln!("[ModelRouter] Dispatching to OpenRouter (model: {})...", model);
 
@@ -248,9 +264,8 @@ impl ModelRouter {
             stream: None, // Non-streaming mode
         };
 
-        let response = self
-            .client
-            .post(&url)
+        let response = ctx
+            .http_request(reqwest::Client::builder(), reqwest::Method::POST, &url)?
             .header("Authorization", format!("Bearer {}", key))
             .header("HTTP-Referer", "https://pagi-orchestrator.local")
             .header("X-Title", "PAGI-Master-Orchestrator")
@@ -702,7 +717,7 @@ impl AgentSkill for ModelRouter {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let prompt = payload
@@ -737,7 +752,11 @@ impl AgentSkill for ModelRouter {
         let (generated, usage) = match self.mode {
             LlmMode::Mock => (self.mock_generate(&prompt), None),
             LlmMode::Live => {
-                match self.live_generate(system_prompt, &prompt, model_override, temperature, max_tokens).await {
+                // Capability-scoped callers reserve their token allowance before the completion call.
+                if let Some(caps) = &ctx.capabilities {
+                    caps.charge_llm_tokens(u64::from(max_tokens.unwrap_or(DEFAULT_MAX_TOKENS_RESERVATION)))?;
+                }
+                match self.live_generate(ctx, system_prompt, &prompt, model_override, temperature, max_tokens).await {
                     Ok((text, usage)) => (text, usage),
                     Err(e) => {
                         eprintln!("[ModelRouter] Live generation failed: {}. Falling back to mock.", e);
@@ -9,8 +9,8 @@
 //! | Action | Description |
 //! |--------|-------------|
 //! | `review` | Submit code for adversarial peer review. |
-//! | `consensus` | Run the full consensus gate (review + decision). |
-//! | `heuristic` | Run heuristic-only analysis (no LLM call). |
+//! | `consensus` | Run the review panel and the quorum-based consensus gate. |
+//! | `heuristic` / `static` | Run the AST static analyzer only (no LLM call). |
 //!
 //! ## Integration with Maintenance Loop
 //!
@@ -22,7 +22,7 @@ use std::sync::Arc;
 
 use pagi_core::{AgentSkill, TenantContext};
 use pagi_evolution::{
-    ConsensusGate, ConsensusResult, RedTeamAnalyzer, RedTeamConfig, RollbackManager,
+    ConsensusGate, ConsensusResult, RedTeamAnalyzer, ReviewPanel, RollbackManager,
     SecurityVerdict,
 };
 use tracing::{info, warn};
@@ -33,6 +33,8 @@ use tracing::{info, warn};
 /// can dispatch security reviews through the standard skill interface.
 pub struct RedTeamSkill {
     analyzer: Arc<RedTeamAnalyzer>,
+    /// Reviewers consulted by `consensus`: the analyzer's model, extra models, and the AST pass.
+    panel: ReviewPanel,
     gate: ConsensusGate,
     rollback_manager: Option<Arc<RollbackManager>>,
 }
@@ -47,6 +49,7 @@ impl RedTeamSkill {
         rollback_manager: Option<Arc<RollbackManager>>,
     ) -> Self {
         Self {
+            panel: ReviewPanel::from_env(Arc::clone(&analyzer)),
             analyzer,
             gate: ConsensusGate::default(),
             rollback_manager,
@@ -55,11 +58,7 @@ impl RedTeamSkill {
 
     /// Create with default configuration from environment variables.
     pub fn from_env(rollback_manager: Option<Arc<RollbackManager>>) -> Self {
-        Self {
-            analyzer: Arc::new(RedTeamAnalyzer::from_env()),
-            gate: ConsensusGate::default(),
-            rollback_manager,
-        }
+        Self::new(Arc::new(RedTeamAnalyzer::from_env()), rollback_manager)
     }
 
     /// Run the full consensus pipeline: review + gate evaluation + genetic memory update.
@@ -71,15 +70,12 @@ impl RedTeamSkill {
         code: &str,
         patch_description: &str,
     ) -> ConsensusResult {
-        // Step 1: Adversarial review.
-        let verdict = self
-            .analyzer
-            .review_patch(skill_name, code, patch_description)
+        // Steps 1-2: Concurrent panel review, then the quorum decision.
+        let result = self
+            .gate
+            .review(&self.panel, skill_name, code, patch_description)
             .await;
 
-        // Step 2: Consensus gate evaluation.
-        let result = self.gate.evaluate(verdict);
-
         // Step 3: If lethal, mark in Genetic Memory.
         if result.mark_lethal {
             if let Some(ref rm) = self.rollback_manager {
@@ -114,12 +110,18 @@ impl RedTeamSkill {
             }
         }
 
+        // Step 5: Keep the reviewer verdicts on the patch version for its lineage.
+        if let Some(ref rm) = self.rollback_manager {
+            rm.record_security_audit(skill_name, code, result.audit_summary());
+        }
+
         info!(
             target: "pagi::redteam_skill",
             skill = skill_name,
             approved = result.approved,
             lethal = result.mark_lethal,
             severity = %result.verdict.overall_severity,
+            disagreements = result.disagreements.len(),
             "Consensus gate result"
         );
 
@@ -189,21 +191,20 @@ impl AgentSkill for RedTeamSkill {
                     "reason": result.reason,
                     "mark_lethal": result.mark_lethal,
                     "verdict": verdict_to_json(&result.verdict),
+                    "reviews": result.reviews.iter().map(|r| serde_json::json!({
+                        "reviewer": r.reviewer,
+                        "abstained": r.verdict.is_none(),
+                        "verdict": r.verdict.as_ref().map(verdict_to_json),
+                    })).collect::<Vec<_>>(),
+                    "disagreements": result.disagreements,
                 }))
             }
 
             // -----------------------------------------------------------------
-            // heuristic: Heuristic-only analysis (no LLM call)
+            // heuristic: Static AST analysis only (no LLM call)
             // -----------------------------------------------------------------
-            "heuristic" => {
-                // Access the heuristic directly via a temporary analyzer.
-                let config = RedTeamConfig::default();
-                let temp_analyzer = RedTeamAnalyzer::new(config);
-                // We need to use the public review_patch which falls back to heuristic
-                // when no API key is available. For explicit heuristic, we set no key.
-                let verdict = temp_analyzer
-                    .review_patch(skill_name, code, description)
-                    .await;
+            "heuristic" | "static" => {
+                let verdict = self.analyzer.review_static(skill_name, code);
 
                 Ok(verdict_to_json(&verdict))
             }
@@ -225,6 +226,7 @@ fn verdict_to_json(verdict: &SecurityVerdict) -> serde_json::Value {
                 "description": f.description,
                 "affected_region": f.affected_region,
                 "remediation": f.remediation,
+                "span": f.span,
             })
         })
         .collect();
@@ -294,6 +294,8 @@ mod tests {
             tenant_id: "test".to_string(),
             correlation_id: None,
             agent_id: Some("default".to_string()),
+            session_id: None,
+            capabilities: None,
         };
         let payload = serde_json::json!({
             "record_id": "journal/12345",
@@ -153,6 +153,8 @@ impl AgentSkill for RollbackSkill {
                                     "findings_count": sa.findings_count,
                                     "summary": sa.summary,
                                     "memory_warning": sa.memory_warning,
+                                    "reviews": sa.reviews,
+                                    "disagreements": sa.disagreements,
                                 })),
                             })),
                         })
@@ -189,6 +191,7 @@ impl AgentSkill for RollbackSkill {
                             "reason": dead_end.reason,
                             "timestamp_ms": dead_end.timestamp_ms,
                             "occurrence_count": dead_end.occurrence_count,
+                            "minimal_failing_payload": dead_end.minimal_failing_payload,
                         }
                     })),
                     None => Ok(serde_json::json!({
@@ -267,6 +270,8 @@ impl AgentSkill for RollbackSkill {
                                     "findings_count": sa.findings_count,
                                     "summary": sa.summary,
                                     "memory_warning": sa.memory_warning,
+                                    "reviews": sa.reviews,
+                                    "disagreements": sa.disagreements,
                                 })),
                             })),
                         })
@@ -295,6 +300,7 @@ impl AgentSkill for RollbackSkill {
                             "reason": d.reason,
                             "timestamp_ms": d.timestamp_ms,
                             "occurrence_count": d.occurrence_count,
+                            "minimal_failing_payload": d.minimal_failing_payload,
                         })
                     })
                     .collect();
@@ -22,6 +22,8 @@
 //!    semantic memory and "Reflexion" - the ability to reflect on past actions.
 //! 3. **Recursive Compiler**: When the Agent identifies a missing skill, it can
 //!    generate Rust code, compile it, and hot-swap the new .dll into the SkillRegistry.
+//!    With `PAGI_FORGE_TARGET=wasm` the skill is built for `wasm32-wasip1` instead and runs in
+//!    the embedded WASM runtime, reaching the KB / network only through capability-gated imports.
 
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -29,10 +31,13 @@ use std::time::Instant;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
-use pagi_core::{AgentSkill, KnowledgeStore, SkillRegistry, TenantContext};
+use pagi_core::{
+    validate_skill_permissions, AgentSkill, KbType, KnowledgeStore, SkillManifestRegistry, SkillRegistry,
+    TenantContext,
+};
 use pagi_evolution::{
-    ApprovalGate, ChangeSeverity, Compiler, ProposedChange,
-    RollbackConfig, RollbackManager, SkillError, SkillLoader,
+    ApprovalGate, CanaryPolicy, ChangeSeverity, CompileTarget, Compiler, PatchOrigin, ProposedChange,
+    RollbackConfig, RollbackManager, SkillError, SkillLoader, WasmCapabilities, WasmHost,
 };
 
 use crate::system::{
@@ -42,6 +47,93 @@ use crate::system::{
 use crate::rig_tools::{RigTool, RigToolRegistry};
 use crate::tool_memory::{ToolExecutionRecord, ToolMemoryManager};
 
+// ---------------------------------------------------------------------------
+// WASM Skill Host
+// ---------------------------------------------------------------------------
+
+/// KB and HTTP services for WASM skills. The runtime checks the skill's capabilities; this
+/// refuses the Shadow vault outright and checks each KB slot again with the Sovereignty
+/// Firewall rule native skills go through (`kb_layers_allowed` plus trust tier), denying
+/// everything when no manifests are attached. HTTP redirects are never followed, since the
+/// runtime's egress check only saw the first URL.
+struct KnowledgeWasmHost {
+    store: Arc<KnowledgeStore>,
+    manifests: Option<Arc<SkillManifestRegistry>>,
+    /// PAGI_FIREWALL_STRICT_MODE: only Core skills may touch any KB layer.
+    strict_mode: bool,
+}
+
+impl KnowledgeWasmHost {
+    /// Response bodies larger than this are rejected.
+    const MAX_HTTP_BODY: usize = 1024 * 1024;
+
+    fn new(store: Arc<KnowledgeStore>, manifests: Option<Arc<SkillManifestRegistry>>, strict_mode: bool) -> Self {
+        Self {
+            store,
+            manifests,
+            strict_mode,
+        }
+    }
+
+    fn check_slot(&self, skill: &str, slot: u8) -> Result<(), String> {
+        if slot == KbType::Shadow.slot_id() {
+            return Err("KB-09 (Shadow) is never exposed to generated skills".to_string());
+        }
+        let manifests = self
+            .manifests
+            .as_ref()
+            .ok_or_else(|| "no skill manifests are attached".to_string())?;
+        if !validate_skill_permissions(manifests, skill, slot, self.strict_mode) {
+            return Err(format!(
+                "'{}' may not access KB-{:02} (kb_layers_allowed or trust tier)",
+                skill, slot
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl WasmHost for KnowledgeWasmHost {
+    fn kb_read(&self, skill: &str, slot: u8, key: &str) -> Result<Option<Vec<u8>>, String> {
+        self.check_slot(skill, slot)?;
+        self.store.get(slot, key).map_err(|e| e.to_string())
+    }
+
+    fn kb_write(&self, skill: &str, slot: u8, key: &str, value: &[u8]) -> Result<(), String> {
+        self.check_slot(skill, slot)?;
+        self.store.insert(slot, key, value).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    fn http_get(&self, url: &str) -> Result<Vec<u8>, String> {
+        // Skills run synchronously, possibly on a Tokio worker; the blocking client needs its own thread.
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let client = reqwest::blocking::Client::builder()
+                        .timeout(std::time::Duration::from_secs(15))
+                        .redirect(reqwest::redirect::Policy::none())
+                        .build()
+                        .map_err(|e| e.to_string())?;
+                    let response = client
+                        .get(url)
+                        .send()
+                        .and_then(|r| r.error_for_status())
+                        .map_err(|e| e.to_string())?;
+                    if response.status().is_redirection() {
+                        return Err(format!("{} redirects ({}); redirects are not followed", url, response.status()));
+                    }
+                    let body = response.bytes().map_err(|e| e.to_string())?;
+                    if body.len() > Self::MAX_HTTP_BODY {
+                        return Err(format!("response exceeds {} bytes", Self::MAX_HTTP_BODY));
+                    }
+                    Ok(body.to_vec())
+                })
+                .join()
+                .map_err(|_| "http worker panicked".to_string())?
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Sovereign Operator Configuration
 // ---------------------------------------------------------------------------
@@ -73,6 +165,14 @@ impl Default for SovereignOperatorConfig {
     }
 }
 
+/// Whether recompiled skills roll out as canaries (`PAGI_FORGE_CANARY`, default true).
+fn forge_canary_enabled() -> bool {
+    !matches!(
+        std::env::var("PAGI_FORGE_CANARY").as_deref().map(str::trim),
+        Ok("false") | Ok("0") | Ok("off")
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Sovereign Operator
 // ---------------------------------------------------------------------------
@@ -109,6 +209,10 @@ pub struct SovereignOperator {
     approval_gate: Arc<ApprovalGate>,
     /// Knowledge store for KB-08 logging (optional)
     knowledge_store: Option<Arc<KnowledgeStore>>,
+    /// Skill manifests; the source of WASM skill capabilities (optional)
+    skill_manifests: Option<Arc<SkillManifestRegistry>>,
+    /// Sovereignty Firewall strict mode applied to WASM KB access
+    firewall_strict_mode: bool,
     /// Thread-safe runtime toggle for Forge safety (can be flipped by kill switch)
     forge_safety_atomic: Arc<AtomicBool>,
 }
@@ -144,7 +248,7 @@ impl SovereignOperator {
             None
         };
 
-        let skill_loader = Arc::new(SkillLoader::new());
+        let skill_loader = Arc::new(SkillLoader::from_env());
 
         let rollback_config = RollbackConfig {
             patches_dir: std::path::PathBuf::from("crates/pagi-skills/src/generated/patches"),
@@ -174,6 +278,8 @@ impl SovereignOperator {
             rollback_manager,
             approval_gate,
             knowledge_store: None, // Will be set via set_knowledge_store()
+            skill_manifests: None, // Will be set via set_skill_manifests()
+            firewall_strict_mode: false,
             forge_safety_atomic,
         })
     }
@@ -327,6 +433,21 @@ impl SovereignOperator {
         code: &str,
         name: &str,
     ) -> Result<(), SkillError> {
+        self.compile_and_load_skill_for(code, name, CompileTarget::from_env(), PatchOrigin::default())
+            .await
+    }
+
+    /// Same as [`Self::compile_and_load_skill`] with an explicit target. A WASM build gets the
+    /// host imports its manifest entry grants (see [`Self::wasm_capabilities`]). `origin` is
+    /// recorded in the patch lineage (the parent defaults to the active version).
+    pub async fn compile_and_load_skill_for(
+        &self,
+        code: &str,
+        name: &str,
+        target: CompileTarget,
+        origin: PatchOrigin,
+    ) -> Result<(), SkillError> {
+        let caps = self.wasm_capabilities(name);
         if !self.config.recursive_compilation_enabled {
             return Err(SkillError::Load(
                 "Recursive compilation is disabled".to_string(),
@@ -411,17 +532,22 @@ impl SovereignOperator {
             info!("⚡ Autonomous compilation proceeding for skill: {}", name);
         }
 
-        // Step 2: Save versioned patch source
-        let version = self.rollback_manager.save_versioned_patch(
-            name,
-            code,
-            &format!("Compiled skill: {}", name),
-            None,
-        )?;
-
-        // Step 3: Compile the code (with auto-revert on failure)
-        let lib_path = match Compiler::compile_from_string(code, name, None) {
-            Ok(path) => path,
+        // Step 2: Save versioned patch source. Replacing a loaded skill stages the new version
+        // as a canary (PAGI_FORGE_CANARY=false activates it directly).
+        let canary = forge_canary_enabled() && self.skill_loader.loaded_names().iter().any(|n| n == name);
+        let description = format!("Compiled skill: {}", name);
+        let version = if canary {
+            self.rollback_manager.stage_versioned_patch(name, code, &description, None, origin)?
+        } else {
+            self.rollback_manager.save_versioned_patch(name, code, &description, None, origin)?
+        };
+
+        // Step 3: Compile the code offline and reproducibly (with auto-revert on failure).
+        // Each version gets its own artifact: a canary loads next to the active library, and
+        // rollback reloads older versions by path, so a fixed `{name}.so` would be overwritten.
+        let artifact_name = format!("{}_v{}", name, version.timestamp_ms);
+        let compiled = match Compiler::compile_reproducible(code, &artifact_name, None, target) {
+            Ok(compiled) => compiled,
             Err(e) => {
                 // AUTO-REVERT: If we're in autonomous mode and compilation fails, re-enable safety
                 if !safety_enabled {
@@ -439,16 +565,31 @@ impl SovereignOperator {
         };
 
         // Step 4: Register the artifact with the RollbackManager
+        let lib_path = compiled.path;
         if let Err(e) = self.rollback_manager.register_artifact(
             name,
             version.timestamp_ms,
             lib_path.clone(),
+            Some(compiled.fingerprint),
         ) {
             warn!("Failed to register artifact with RollbackManager: {}", e);
         }
 
-        // Step 5: Load the compiled library
-        self.skill_loader.load(&lib_path, name.to_string())?;
+        // Step 5: Load the compiled library, or shadow the active one with it
+        if canary {
+            self.rollback_manager.start_canary(
+                name,
+                version.timestamp_ms,
+                CanaryPolicy::from_env(),
+                caps,
+            )?;
+            info!("🐤 Compiled skill '{}' is running as a canary", name);
+            return Ok(());
+        }
+        match target {
+            CompileTarget::Native => self.skill_loader.load(&lib_path, name.to_string())?,
+            CompileTarget::Wasm => self.skill_loader.load_wasm(&lib_path, name.to_string(), caps)?,
+        }
 
         info!("🎉 Successfully compiled and loaded skill: {}", name);
         Ok(())
@@ -500,8 +641,48 @@ impl SovereignOperator {
     }
 
     /// Set the knowledge store for KB-08 logging
+    /// Also backs the KB host imports of WASM skills.
     pub fn set_knowledge_store(&mut self, knowledge_store: Arc<KnowledgeStore>) {
         self.knowledge_store = Some(knowledge_store);
+        self.install_wasm_host();
+    }
+
+    /// Set the skill manifests that grant WASM skills their KB layers and network egress, and
+    /// whether the Sovereignty Firewall runs in strict mode (only Core skills touch the KB)
+    pub fn set_skill_manifests(&mut self, manifests: Arc<SkillManifestRegistry>, strict_mode: bool) {
+        self.skill_manifests = Some(manifests);
+        self.firewall_strict_mode = strict_mode;
+        self.install_wasm_host();
+    }
+
+    fn install_wasm_host(&self) {
+        if let Some(ref knowledge_store) = self.knowledge_store {
+            self.skill_loader.set_wasm_host(Arc::new(KnowledgeWasmHost::new(
+                Arc::clone(knowledge_store),
+                self.skill_manifests.clone(),
+                self.firewall_strict_mode,
+            )));
+        }
+    }
+
+    /// Host imports a WASM build of `name` may use: the network egress of its manifest entry
+    /// and the KB layers it lists that its trust tier may reach. Logging only when the skill is
+    /// not listed (or no manifests are set).
+    pub fn wasm_capabilities(&self, name: &str) -> WasmCapabilities {
+        let Some(manifests) = self.skill_manifests.as_ref() else {
+            return WasmCapabilities::default();
+        };
+        let Some(entry) = manifests.manifest_entry(name) else {
+            return WasmCapabilities::default();
+        };
+        WasmCapabilities {
+            kb_slots: entry
+                .kb_layers_allowed
+                .into_iter()
+                .filter(|&slot| validate_skill_permissions(manifests, name, slot, self.firewall_strict_mode))
+                .collect(),
+            network_egress: entry.capabilities.network_egress,
+        }
     }
 
     /// Get the current forge safety status (thread-safe)
@@ -589,7 +770,7 @@ impl AgentSkill for SovereignOperatorSkill {
 
     async fn execute(
         &self,
-        _ctx: &TenantContext,
+        ctx: &TenantContext,
         payload: Option<serde_json::Value>,
     ) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
         let payload = payload.ok_or("Missing payload")?;
@@ -628,8 +809,30 @@ impl AgentSkill for SovereignOperatorSkill {
                     .and_then(|v| v.as_str())
                     .ok_or("Missing 'name' field")?;
 
+                let target = match payload.get("target").and_then(|v| v.as_str()) {
+                    Some("wasm") => CompileTarget::Wasm,
+                    Some("native") => CompileTarget::Native,
+                    _ => CompileTarget::from_env(),
+                };
+                if payload.get("capabilities").is_some() {
+                    warn!(
+                        "compile_skill: ignoring requested capabilities for '{}'; they come from its skill manifest",
+                        name
+                    );
+                }
+                let mut origin: PatchOrigin = payload
+                    .get("origin")
+                    .cloned()
+                    .map(serde_json::from_value)
+                    .transpose()?
+                    .unwrap_or_default();
+                if origin.conversation_id.is_none() {
+                    origin.conversation_id =
+                        ctx.session_id.clone().or_else(|| ctx.correlation_id.clone());
+                }
+
                 self.operator
-                    .compile_and_load_skill(code, name)
+                    .compile_and_load_skill_for(code, name, target, origin)
                     .await
                     .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
@@ -722,3 +925,104 @@ impl AgentSkill for SovereignOperatorSkill {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pagi_core::{SkillManifestEntry, TrustTier};
+
+    #[test]
+    fn wasm_host_enforces_manifest_kb_layers() {
+        let kb_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(kb_dir.path()).unwrap());
+        let manifests = Arc::new(SkillManifestRegistry::new());
+        manifests.register_runtime(
+            TrustTier::Generated,
+            SkillManifestEntry {
+                skill_id: "weather".to_string(),
+                kb_layers_allowed: vec![5, 9],
+                description: None,
+                capabilities: Default::default(),
+                artifact: None,
+                artifact_sha256: None,
+                signature: None,
+            },
+        );
+
+        let host = KnowledgeWasmHost::new(Arc::clone(&store), Some(Arc::clone(&manifests)), false);
+        host.kb_write("weather", 5, "k", b"v").unwrap();
+        assert_eq!(host.kb_read("weather", 5, "k").unwrap(), Some(b"v".to_vec()));
+        assert!(host
+            .kb_write("weather", 3, "k", b"v")
+            .unwrap_err()
+            .contains("kb_layers_allowed"));
+        assert!(host.kb_read("weather", 9, "k").unwrap_err().contains("Shadow"));
+        assert!(host.kb_read("unlisted", 5, "k").is_err());
+
+        let strict = KnowledgeWasmHost::new(Arc::clone(&store), Some(manifests), true);
+        assert!(strict.kb_read("weather", 5, "k").is_err());
+
+        let unmanaged = KnowledgeWasmHost::new(store, None, false);
+        assert!(unmanaged.kb_read("weather", 5, "k").is_err());
+    }
+
+    #[test]
+    fn wasm_host_refuses_kb01_to_non_core_skills_that_list_it() {
+        let kb_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(kb_dir.path()).unwrap());
+        let manifests = Arc::new(SkillManifestRegistry::new());
+        for tier in [TrustTier::Import, TrustTier::Generated] {
+            manifests.register_runtime(
+                tier,
+                SkillManifestEntry {
+                    skill_id: format!("{}_soul_reader", tier.as_str()),
+                    kb_layers_allowed: vec![1, 5],
+                    description: None,
+                    capabilities: Default::default(),
+                    artifact: None,
+                    artifact_sha256: None,
+                    signature: None,
+                },
+            );
+        }
+
+        let host = KnowledgeWasmHost::new(store, Some(manifests), false);
+        for tier in [TrustTier::Import, TrustTier::Generated] {
+            let skill = format!("{}_soul_reader", tier.as_str());
+            assert!(host.kb_read(&skill, 1, "identity").is_err(), "{skill} read KB-01");
+            assert!(host.kb_write(&skill, 1, "identity", b"x").is_err(), "{skill} wrote KB-01");
+            assert!(host.kb_read(&skill, 5, "k").is_ok());
+        }
+    }
+
+    #[test]
+    fn wasm_host_does_not_follow_redirects() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        // The redirect target records whether it was ever reached.
+        let internal = TcpListener::bind("127.0.0.1:0").unwrap();
+        internal.set_nonblocking(true).unwrap();
+        let internal_addr = internal.local_addr().unwrap();
+        let allowed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let allowed_addr = allowed.local_addr().unwrap();
+        let stub = std::thread::spawn(move || {
+            let (mut conn, _) = allowed.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = conn.read(&mut buf);
+            let response = format!(
+                "HTTP/1.1 302 Found\r\nLocation: http://{}/secret\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                internal_addr
+            );
+            conn.write_all(response.as_bytes()).unwrap();
+        });
+
+        let kb_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(KnowledgeStore::open_path(kb_dir.path()).unwrap());
+        let host = KnowledgeWasmHost::new(store, None, false);
+        let err = host.http_get(&format!("http://{}/", allowed_addr)).unwrap_err();
+        stub.join().unwrap();
+
+        assert!(err.contains("redirects are not followed"), "{err}");
+        assert!(internal.accept().is_err(), "redirect target was contacted");
+    }
+}
@@ -24,11 +24,12 @@
 use std::env;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Output, Stdio};
+use std::process::{Output, Stdio};
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 use sysinfo::System;
+use tokio::process::Command;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
@@ -287,7 +288,7 @@ impl ShellExecutor {
         }
 
         // Execute the command
-        let output = self.execute_command(command)?;
+        let output = self.execute_command(command).await?;
 
         Ok(CommandResult {
             command: command.to_string(),
@@ -317,7 +318,7 @@ impl ShellExecutor {
             )));
         }
 
-        let output = self.execute_command(command)?;
+        let output = self.execute_command(command).await?;
 
         Ok(CommandResult {
             command: command.to_string(),
@@ -329,8 +330,8 @@ impl ShellExecutor {
     }
 
     /// Execute a command without approval (use with extreme caution)
-    pub fn execute_unsafe(&self, command: &str) -> Result<CommandResult, ExecutionError> {
-        let output = self.execute_command(command)?;
+    pub async fn execute_unsafe(&self, command: &str) -> Result<CommandResult, ExecutionError> {
+        let output = self.execute_command(command).await?;
 
         Ok(CommandResult {
             command: command.to_string(),
@@ -341,24 +342,15 @@ impl ShellExecutor {
         })
     }
 
-    /// Internal method to execute a command using the appropriate shell
-    fn execute_command(&self, command: &str) -> Result<Output, ExecutionError> {
-        match env::consts::OS {
-            "windows" => self.execute_windows(command),
-            _ => self.execute_unix(command),
-        }
-    }
-
-    /// Execute a command on Windows using PowerShell
-    fn execute_windows(&self, command: &str) -> Result<Output, ExecutionError> {
-        debug!("Executing Windows command: {}", command);
-
-        let mut cmd = Command::new("powershell.exe");
-        cmd.arg("-NoProfile")
-            .arg("-ExecutionPolicy")
-            .arg("Bypass")
-            .arg("-Command")
-            .arg(command);
+    /// Internal method to execute a command using the appropriate shell.
+    ///
+    /// The child is killed when the returned future is dropped, so a dispatch timeout or
+    /// cancellation stops the command instead of leaving it running in the background.
+    async fn execute_command(&self, command: &str) -> Result<Output, ExecutionError> {
+        let (mut cmd, shell) = match env::consts::OS {
+            "windows" => (self.windows_command(command), "PowerShell"),
+            _ => (self.unix_command(command), "sh"),
+        };
 
         // Set working directory if specified
         if let Some(ref dir) = self.working_dir {
@@ -371,40 +363,35 @@ impl ShellExecutor {
         }
 
         // Capture both stdout and stderr
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        cmd.stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        cmd.output().await.map_err(|e| {
+            ExecutionError::ExecutionFailed(format!("Failed to execute {} command: {}", shell, e))
+        })
+    }
 
-        let output = cmd
-            .output()
-            .map_err(|e| ExecutionError::ExecutionFailed(format!("Failed to execute PowerShell command: {}", e)))?;
+    /// PowerShell invocation for Windows
+    fn windows_command(&self, command: &str) -> Command {
+        debug!("Executing Windows command: {}", command);
 
-        Ok(output)
+        let mut cmd = Command::new("powershell.exe");
+        cmd.arg("-NoProfile")
+            .arg("-ExecutionPolicy")
+            .arg("Bypass")
+            .arg("-Command")
+            .arg(command);
+        cmd
     }
 
-    /// Execute a command on Unix/Linux/macOS using sh
-    fn execute_unix(&self, command: &str) -> Result<Output, ExecutionError> {
+    /// `sh -c` invocation for Unix/Linux/macOS
+    fn unix_command(&self, command: &str) -> Command {
         debug!("Executing Unix command: {}", command);
 
         let mut cmd = Command::new("sh");
         cmd.arg("-c").arg(command);
-
-        // Set working directory if specified
-        if let Some(ref dir) = self.working_dir {
-            cmd.current_dir(dir);
-        }
-
-        // Set environment variables
-        for (key, value) in &self.env_vars {
-            cmd.env(key, value);
-        }
-
-        // Capture both stdout and stderr
-        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
-
-        let output = cmd
-            .output()
-            .map_err(|e| ExecutionError::ExecutionFailed(format!("Failed to execute sh command: {}", e)))?;
-
-        Ok(output)
+        cmd
     }
 }
 
@@ -797,6 +784,10 @@ impl pagi_core::AgentSkill for SystemCommandSkill {
         "SystemCommand"
     }
 
+    fn side_effect_class(&self) -> pagi_core::SideEffectClass {
+        pagi_core::SideEffectClass::Shell
+    }
+
     async fn execute(
         &self,
         _ctx: &TenantContext,
@@ -946,3 +937,48 @@ impl pagi_core::AgentSkill for FileSystemSkill {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// `/proc/<pid>` is gone, or left as a zombie until the runtime reaps it.
+    #[cfg(target_os = "linux")]
+    fn process_exited(pid: &str) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .map(|stat| {
+                stat.rsplit(')')
+                    .next()
+                    .is_some_and(|rest| rest.trim_start().starts_with('Z'))
+            })
+            .unwrap_or(true)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn timed_out_command_is_killed() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        let executor = ShellExecutor::unsafe_mode();
+        // `exec` keeps the pid, so the recorded pid is the sleeping process itself.
+        let command = format!("echo $$ > {}; exec sleep 30", pid_file.display());
+
+        let timed_out =
+            tokio::time::timeout(Duration::from_millis(500), executor.execute(&command)).await;
+        assert!(timed_out.is_err());
+
+        let pid = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .to_string();
+        let mut exited = false;
+        for _ in 0..40 {
+            if process_exited(&pid) {
+                exited = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(exited, "sleep {} still running after the timeout", pid);
+    }
+}
@@ -77,7 +77,7 @@ impl AgentSkill for WebSearch {
         if let Some(ref url) = args.url {
             let url = url.trim();
             if !url.is_empty() {
-                return fetch_url(url).await.map(|out| {
+                return fetch_url(ctx, url).await.map(|out| {
                     serde_json::json!({
                         "status": "ok",
                         "skill": SKILL_NAME,
@@ -104,19 +104,19 @@ impl AgentSkill for WebSearch {
             .unwrap_or(DEFAULT_MAX_RESULTS)
             .min(MAX_RESULTS_CAP);
 
-        let result = if let Ok(key) = std::env::var("TAVILY_API_KEY") {
+        let result = if let Some(key) = ctx.env_var("TAVILY_API_KEY")? {
             let key = key.trim();
             if key.is_empty() {
                 search_fallback(query, max_results).await
             } else {
-                search_tavily(key, query, max_results).await
+                search_tavily(ctx, key, query, max_results).await
             }
-        } else if let Ok(key) = std::env::var("SERPAPI_KEY") {
+        } else if let Some(key) = ctx.env_var("SERPAPI_KEY")? {
             let key = key.trim();
             if key.is_empty() {
                 search_fallback(query, max_results).await
             } else {
-                search_serpapi(key, query, max_results).await
+                search_serpapi(ctx, key, query, max_results).await
             }
         } else {
             search_fallback(query, max_results).await
@@ -179,12 +179,16 @@ struct FetchedPage {
     content_length: usize,
 }
 
-async fn fetch_url(url: &str) -> Result<FetchedPage, Box<dyn std::error::Error + Send + Sync>> {
+async fn fetch_url(ctx: &TenantContext, url: &str) -> Result<FetchedPage, Box<dyn std::error::Error + Send + Sync>> {
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(15))
-        .user_agent("PAGI-Gateway/1.0 (Bare-Metal Rust)")
-        .build()?;
-    let body = client.get(url).send().await?.bytes().await?;
+        .user_agent("PAGI-Gateway/1.0 (Bare-Metal Rust)");
+    let body = ctx
+        .http_request(client, reqwest::Method::GET, url)?
+        .send()
+        .await?
+        .bytes()
+        .await?;
     let html = String::from_utf8_lossy(&body);
     let (title, text) = extract_title_and_text(&html);
     let preview_len = 12_000.min(text.len());
@@ -220,21 +224,20 @@ fn extract_title_and_text(html: &str) -> (Option<String>, String) {
 }
 
 async fn search_tavily(
+    ctx: &TenantContext,
     api_key: &str,
     query: &str,
     max_results: u32,
 ) -> Result<(Vec<SearchResult>, String), Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+    let client = reqwest::Client::builder().timeout(std::time::Duration::from_secs(30));
     let body = serde_json::json!({
         "query": query,
         "max_results": max_results,
         "search_depth": "basic",
         "include_answer": false,
     });
-    let res = client
-        .post("https://api.tavily.com/search")
+    let res = ctx
+        .http_request(client, reqwest::Method::POST, "https://api.tavily.com/search")?
         .header("Content-Type", "application/json")
         .bearer_auth(api_key.trim())
         .json(&body)
@@ -272,13 +275,12 @@ async fn search_tavily(
 }
 
 async fn search_serpapi(
+    ctx: &TenantContext,
     api_key: &str,
     query: &str,
     max_results: u32,
 ) -> Result<(Vec<SearchResult>, String), Box<dyn std::error::Error + Send + Sync>> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+    let client = reqwest::Client::builder().timeout(std::time::Duration::from_secs(30));
     let url = reqwest::Url::parse_with_params(
         "https://serpapi.com/search",
         &[
@@ -288,7 +290,10 @@ async fn search_serpapi(
             ("num", max_results.to_string().as_str()),
         ],
     )?;
-    let res = client.get(url).send().await?;
+    let res = ctx
+        .http_request(client, reqwest::Method::GET, url.as_str())?
+        .send()
+        .await?;
     let json: serde_json::Value = res.json().await?;
     let empty: Vec<serde_json::Value> = vec![];
     let arr = json
@@ -3,8 +3,9 @@
 //! This test demonstrates PAGI's ability to recursively synthesize new skills.
 //! The Forge creates a "Weather Sentinel" skill that can fetch weather data,
 //! proving the system can recompile itself and extend its own capabilities.
+//! Generated modules go into a throwaway overlay crate, never into pagi-skills itself.
 
-use pagi_skills::{create_skill_from_spec, ToolSpec, ToolSpecParam};
+use pagi_skills::{create_skill_from_spec, ForgeOverlay, ToolSpec, ToolSpecParam};
 use std::path::PathBuf;
 
 #[test]
@@ -35,17 +36,11 @@ fn test_forge_weather_sentinel() {
         .expect("Could not find workspace root")
         .to_path_buf();
 
-    let skills_src = workspace_root.join("crates/pagi-skills/src");
-
-    // Ensure we're in the right place
-    assert!(
-        skills_src.join("lib.rs").exists(),
-        "lib.rs not found at {:?}",
-        skills_src
-    );
+    let overlay_dir = tempfile::tempdir().expect("tempdir");
+    let overlay = ForgeOverlay::new(overlay_dir.path().join("forge_overlay"), &workspace_root);
 
     // Create the skill using The Forge
-    let result = create_skill_from_spec(&weather_spec, &skills_src, &workspace_root)
+    let result = create_skill_from_spec(&weather_spec, &overlay)
         .expect("Forge failed to create Weather Sentinel skill");
 
     // Verify the result
@@ -63,6 +58,7 @@ fn test_forge_weather_sentinel() {
     assert!(result.success, "Forge should successfully create the skill");
     assert!(result.cargo_check_ok, "Cargo check should pass");
     assert_eq!(result.module_name, "forge_gen_weather_sentinel");
+    assert!(overlay.manifest().get("weather_sentinel").is_some_and(|e| e.checked));
 
     // Verify the file was created
     let generated_file = PathBuf::from(&result.file_path);
@@ -120,10 +116,11 @@ fn test_forge_salesforce_auditor_spec() {
         .expect("Could not find workspace root")
         .to_path_buf();
 
-    let skills_src = workspace_root.join("crates/pagi-skills/src");
+    let overlay_dir = tempfile::tempdir().expect("tempdir");
+    let overlay = ForgeOverlay::new(overlay_dir.path().join("forge_overlay"), &workspace_root);
 
     // Create the Salesforce Auditor skill
-    let result = create_skill_from_spec(&salesforce_spec, &skills_src, &workspace_root)
+    let result = create_skill_from_spec(&salesforce_spec, &overlay)
         .expect("Forge failed to create Salesforce Auditor skill");
 
     println!("\n🔥 Salesforce Auditor Forge Result:");
@@ -155,7 +152,8 @@ fn test_forge_sanitization() {
         .expect("Could not find workspace root")
         .to_path_buf();
 
-    let skills_src = workspace_root.join("crates/pagi-skills/src");
+    let overlay_dir = tempfile::tempdir().expect("tempdir");
+    let overlay = ForgeOverlay::new(overlay_dir.path().join("forge_overlay"), &workspace_root);
 
     for (invalid_name, reason) in invalid_specs {
         let spec = ToolSpec {
@@ -164,7 +162,7 @@ fn test_forge_sanitization() {
             params: vec![],
         };
 
-        let result = create_skill_from_spec(&spec, &skills_src, &workspace_root);
+        let result = create_skill_from_spec(&spec, &overlay);
         assert!(
             result.is_err(),
             "{}: '{}' should be rejected",
@@ -229,9 +227,10 @@ mod integration {
             .expect("Could not find workspace root")
             .to_path_buf();
 
-        let skills_src = workspace_root.join("crates/pagi-skills/src");
+        let overlay_dir = tempfile::tempdir().expect("tempdir");
+        let overlay = ForgeOverlay::new(overlay_dir.path().join("forge_overlay"), &workspace_root);
 
-        let result = create_skill_from_spec(&salesforce_spec, &skills_src, &workspace_root)
+        let result = create_skill_from_spec(&salesforce_spec, &overlay)
             .expect("Forge failed");
 
         println!("   ✅ Forge created: {}", result.module_name);
@@ -259,4 +258,47 @@ mod integration {
 
         assert!(result.success);
     }
+
+    /// Builds the overlay and runs the property-based smoke tests generated from the
+    /// Weather Sentinel's declared params (valid, missing, wrong-type, huge and unicode payloads).
+    #[test]
+    #[ignore] // Release-builds the overlay; run manually with --ignored
+    fn test_forge_property_smoke() {
+        let spec = ToolSpec {
+            name: "weather_sentinel".to_string(),
+            description: "Fetches current weather data for a given location.".to_string(),
+            params: vec![
+                ToolSpecParam {
+                    name: "location".to_string(),
+                    param_type: "string".to_string(),
+                    required: true,
+                },
+                ToolSpecParam {
+                    name: "units".to_string(),
+                    param_type: "string".to_string(),
+                    required: false,
+                },
+            ],
+        };
+
+        let workspace_root = std::env::current_dir()
+            .expect("Failed to get current directory")
+            .ancestors()
+            .find(|p| p.join("Cargo.toml").exists() && p.join("crates").exists())
+            .expect("Could not find workspace root")
+            .to_path_buf();
+
+        let overlay_dir = tempfile::tempdir().expect("tempdir");
+        let overlay = ForgeOverlay::new(overlay_dir.path().join("forge_overlay"), &workspace_root);
+        assert!(create_skill_from_spec(&spec, &overlay).expect("Forge failed").cargo_check_ok);
+        overlay.build().expect("overlay build");
+
+        // The skill-host worker binary is not built for integration tests.
+        std::env::set_var("PAGI_SKILL_SANDBOX", "inprocess");
+        let report = overlay
+            .property_test("weather_sentinel", pagi_core::PropertyConfig::default())
+            .expect("property test");
+        println!("   {}", report.summary());
+        assert!(report.passed(), "{}", report.summary());
+    }
 }